@@ -85,21 +85,34 @@ pub use ockam_node::{
 pub mod tcp {
     pub use ockam_transport_tcp::{
         TcpConnection, TcpConnectionMode, TcpConnectionOptions, TcpInletOptions, TcpListener,
-        TcpListenerInfo, TcpListenerOptions, TcpOutletOptions, TcpSenderInfo, TcpTransport,
-        TcpTransportExtension, MAX_MESSAGE_SIZE, TCP,
+        TcpListenerInfo, TcpListenerOptions, TcpOutletHealthCheck, TcpOutletLoadBalancing,
+        TcpOutletOptions, TcpOutletTargetStatus, TcpOutletTargets, TcpOutletTlsConfig,
+        TcpSenderInfo, TcpTransport, TcpTransportExtension, TlsCertificate, TlsClientCertificate,
+        TlsPublicKeyPin, MAX_MESSAGE_SIZE, TCP,
     };
 }
 #[cfg(feature = "ockam_transport_udp")]
 /// UDP transport
 pub mod udp {
     pub use ockam_transport_udp::{
-        RendezvousClient, RendezvousService, UdpBind, UdpBindArguments, UdpBindOptions,
-        UdpPuncture, UdpPunctureNegotiation, UdpPunctureNegotiationListener,
-        UdpPunctureNegotiationListenerOptions, UdpTransport, UdpTransportExtension,
-        MAX_MESSAGE_SIZE, UDP,
+        RendezvousClient, RendezvousService, UdpBind, UdpBindArguments, UdpBindOptions, UdpInlet,
+        UdpInletOptions, UdpOutletOptions, UdpPuncture, UdpPunctureNegotiation,
+        UdpPunctureNegotiationListener, UdpPunctureNegotiationListenerOptions, UdpTransport,
+        UdpTransportExtension, MAX_MESSAGE_SIZE, UDP,
     };
 }
-pub use relay_service::{RelayService, RelayServiceOptions};
+pub use relay_service::{
+    RelayAcknowledgment, RelayAuditEvent, RelayAuditEventKind, RelayAuditOptions,
+    RelayAuditRepository, RelayDelivery, RelayMessagesRepository, RelayMessagesUsage, RelayService,
+    RelayServiceOptions, StoreAndForwardOptions, StoredRelayMessage, DEFAULT_AUDIT_EVENT_TTL,
+    DEFAULT_MAX_AUDIT_EVENTS, DEFAULT_MAX_STORED_BYTES, DEFAULT_MAX_STORED_MESSAGES,
+    DEFAULT_MAX_TOTAL_STORED_BYTES, DEFAULT_STORED_MESSAGE_TTL, MAX_RELAY_NAME_LENGTH,
+    RELAY_DELIVERY_ADDRESS,
+};
+#[cfg(feature = "storage")]
+pub use relay_service::{RelayAuditSqlxDatabase, RelayMessagesSqlxDatabase};
+#[cfg(feature = "std")]
+pub use relay_service::{RelayNamePattern, RelayRegistrationPolicies, RELAY_NAME_KEY};
 
 /// Transport
 pub mod transport {
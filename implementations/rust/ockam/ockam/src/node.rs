@@ -21,7 +21,10 @@ use crate::identity::{
     SecureChannel, SecureChannelListener, SecureChannelRegistry, SecureChannels,
     SecureChannelsBuilder,
 };
-use crate::identity::{SecureChannelListenerOptions, SecureChannelOptions};
+use crate::identity::{
+    SecureChannelListenerOptions, SecureChannelOptions, StaticKeySecureChannelListenerOptions,
+    StaticKeySecureChannelOptions,
+};
 use crate::remote::{RemoteRelay, RemoteRelayInfo, RemoteRelayOptions};
 use crate::OckamError;
 
@@ -160,6 +163,30 @@ impl Node {
             .await
     }
 
+    /// Spawns a lightweight SecureChannel listener, authenticated by a static key, at given
+    /// `Address` with given [`StaticKeySecureChannelListenerOptions`]
+    pub async fn create_static_key_secure_channel_listener(
+        &self,
+        address: impl Into<Address>,
+        options: StaticKeySecureChannelListenerOptions,
+    ) -> Result<SecureChannelListener> {
+        self.secure_channels()
+            .create_static_key_secure_channel_listener(self.get_context(), address, options)
+            .await
+    }
+
+    /// Initiate a lightweight SecureChannel using `Route` to a static key SecureChannel listener
+    /// and [`StaticKeySecureChannelOptions`]
+    pub async fn create_static_key_secure_channel(
+        &self,
+        route: impl Into<Route>,
+        options: StaticKeySecureChannelOptions,
+    ) -> Result<SecureChannel> {
+        self.secure_channels()
+            .create_static_key_secure_channel(self.get_context(), route, options)
+            .await
+    }
+
     /// Start a new worker instance at the given address. Default Access Control is AllowAll
     pub fn start_worker<W>(&self, address: impl Into<Address>, worker: W) -> Result<()>
     where
@@ -0,0 +1,43 @@
+use crate::relay_service::RelayAuditRepository;
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+
+/// Default maximum number of events kept in the relay audit log
+pub const DEFAULT_MAX_AUDIT_EVENTS: u64 = 10_000;
+
+/// Default time after which an event is removed from the relay audit log
+pub const DEFAULT_AUDIT_EVENT_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Options of the audit log of the relay registrations.
+///
+/// The claimed and stolen relay names are recorded, as well as the denied registrations
+/// of authenticated identities. The oldest events are removed once the log exceeds its limits.
+#[derive(Clone)]
+pub struct RelayAuditOptions {
+    pub(super) repository: Arc<dyn RelayAuditRepository>,
+    pub(super) max_events: u64,
+    pub(super) ttl: Duration,
+}
+
+impl RelayAuditOptions {
+    /// Record the events in the given repository, with the default limits
+    pub fn new(repository: Arc<dyn RelayAuditRepository>) -> Self {
+        Self {
+            repository,
+            max_events: DEFAULT_MAX_AUDIT_EVENTS,
+            ttl: DEFAULT_AUDIT_EVENT_TTL,
+        }
+    }
+
+    /// Maximum number of events kept in the audit log
+    pub fn with_max_events(mut self, max_events: u64) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    /// Time after which an event is removed from the audit log
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
@@ -1,7 +1,17 @@
+mod audit;
 mod options;
+#[cfg(feature = "std")]
+mod registration;
 mod relay;
 #[allow(clippy::module_inception)]
 mod relay_service;
+mod storage;
+mod store_and_forward;
 
+pub use audit::*;
 pub use options::*;
+#[cfg(feature = "std")]
+pub use registration::*;
 pub use relay_service::*;
+pub use storage::*;
+pub use store_and_forward::*;
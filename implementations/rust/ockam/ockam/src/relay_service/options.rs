@@ -1,4 +1,9 @@
+#[cfg(feature = "std")]
+use crate::abac::PolicyExpression;
 use crate::alloc::string::ToString;
+use crate::relay_service::{RelayAuditOptions, StoreAndForwardOptions};
+#[cfg(feature = "std")]
+use crate::relay_service::{RelayNamePattern, RelayRegistrationPolicies};
 use alloc::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
@@ -15,6 +20,10 @@ pub struct RelayServiceOptions {
     pub(super) prefix: String,
     pub(super) authority_validation: Option<AuthorityValidation>,
     pub(super) aliases: Vec<Address>,
+    pub(super) store_and_forward: Option<StoreAndForwardOptions>,
+    #[cfg(feature = "std")]
+    pub(super) registration_policies: RelayRegistrationPolicies,
+    pub(super) audit: Option<RelayAuditOptions>,
 }
 
 pub(super) struct AuthorityValidation {
@@ -33,6 +42,10 @@ impl RelayServiceOptions {
             prefix: "".to_string(),
             authority_validation: None,
             aliases: vec![],
+            store_and_forward: None,
+            #[cfg(feature = "std")]
+            registration_policies: RelayRegistrationPolicies::default(),
+            audit: None,
         }
     }
 
@@ -111,6 +124,50 @@ impl RelayServiceOptions {
         self
     }
 
+    /// Store the messages received by the relays until the node which registered them
+    /// acknowledges them. Requires the registered nodes to use a compatible
+    /// [`RemoteRelay`](crate::remote::RemoteRelay)
+    pub fn store_and_forward(mut self, options: StoreAndForwardOptions) -> Self {
+        self.store_and_forward = Some(options);
+        self
+    }
+
+    /// Only register the relays whose registering identity satisfies this policy,
+    /// unless the relay name is reserved with [`RelayServiceOptions::reserve`].
+    ///
+    /// The policy is evaluated against the credential attributes of the registering identity,
+    /// as `subject.<attribute>`, and the requested relay name, as `relay.name`.
+    /// For example `(= subject.relay relay.name)` only allows the holders of a `relay=<name>`
+    /// attribute to register the relay `<name>`.
+    /// See [`RELAY_NAME_KEY`](crate::relay_service::RELAY_NAME_KEY)
+    /// to accept attributes reserving a prefix.
+    ///
+    /// Requires [`RelayServiceOptions::authority`] to be set, and replaces
+    /// the `ockam-relay` attribute check.
+    #[cfg(feature = "std")]
+    pub fn registration_policy(mut self, expression: PolicyExpression) -> Self {
+        self.registration_policies.set_default(expression);
+        self
+    }
+
+    /// Reserve the relay names matching a pattern: `name`, `prefix*` or `*`.
+    /// The registration of these names is governed by the given policy, instead of the
+    /// [default registration policy](RelayServiceOptions::registration_policy).
+    /// When several reservations match a name, the most specific one is used
+    #[cfg(feature = "std")]
+    pub fn reserve(mut self, pattern: RelayNamePattern, expression: PolicyExpression) -> Self {
+        self.registration_policies.reserve(pattern, expression);
+        self
+    }
+
+    /// Record the claimed, stolen and denied relay names in an audit log.
+    /// A relay name is stolen when it is registered by a different identity than its last owner.
+    /// The denied registrations are only recorded for authenticated identities
+    pub fn audit(mut self, options: RelayAuditOptions) -> Self {
+        self.audit = Some(options);
+        self
+    }
+
     pub(super) fn setup_flow_control_for_relay_service(
         &self,
         flow_controls: &FlowControls,
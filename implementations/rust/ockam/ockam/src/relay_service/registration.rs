@@ -0,0 +1,196 @@
+use crate::abac::expr::str;
+use crate::abac::{Abac, Env, Expr, PolicyExpression, RateLimits};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_identity::{Identifier, IdentitiesAttributes};
+
+/// Environment key of the requested relay name, without the service prefix.
+///
+/// A policy can accept credentials reserving a prefix with the `matches?` function:
+/// `(matches? subject.relay relay.name)` accepts `relay=prod-*` for the relay `prod-db`.
+pub const RELAY_NAME_KEY: &str = "relay.name";
+
+/// Pattern of relay names: an exact name, a prefix followed by `*`, or `*` for any name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RelayNamePattern {
+    /// Match any relay name
+    Any,
+    /// Match the relay names starting with the given prefix
+    Prefix(String),
+    /// Match a single relay name
+    Exact(String),
+}
+
+impl RelayNamePattern {
+    /// Return true if the relay name matches this pattern
+    pub fn matches(&self, relay_name: &str) -> bool {
+        match self {
+            RelayNamePattern::Any => true,
+            RelayNamePattern::Prefix(prefix) => relay_name.starts_with(prefix.as_str()),
+            RelayNamePattern::Exact(name) => name == relay_name,
+        }
+    }
+
+    /// Exact names are more specific than prefixes, and longer prefixes are more specific
+    fn specificity(&self) -> usize {
+        match self {
+            RelayNamePattern::Any => 0,
+            RelayNamePattern::Prefix(prefix) => 1 + prefix.len(),
+            RelayNamePattern::Exact(_) => usize::MAX,
+        }
+    }
+}
+
+impl Display for RelayNamePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RelayNamePattern::Any => f.write_str("*"),
+            RelayNamePattern::Prefix(prefix) => write!(f, "{prefix}*"),
+            RelayNamePattern::Exact(name) => f.write_str(name),
+        }
+    }
+}
+
+impl FromStr for RelayNamePattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.find('*') {
+            None if s.is_empty() => Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                "a relay name pattern can't be empty",
+            )),
+            None => Ok(RelayNamePattern::Exact(s.to_string())),
+            Some(index) if index == s.len() - 1 => {
+                if index == 0 {
+                    Ok(RelayNamePattern::Any)
+                } else {
+                    Ok(RelayNamePattern::Prefix(s[..index].to_string()))
+                }
+            }
+            Some(_) => Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                format!("invalid relay name pattern {s}, a wildcard is only supported at the end"),
+            )),
+        }
+    }
+}
+
+/// Policies deciding which identities can register a relay, depending on its name.
+///
+/// A reservation applies a policy to the relay names matching a pattern. When several
+/// reservations match a name, the most specific one is used. The names which are not
+/// reserved use the default policy, if any.
+#[derive(Clone, Debug, Default)]
+pub struct RelayRegistrationPolicies {
+    default: Option<Expr>,
+    reservations: Vec<(RelayNamePattern, Expr)>,
+    rate_limits: RateLimits,
+}
+
+impl RelayRegistrationPolicies {
+    pub(super) fn set_default(&mut self, expression: PolicyExpression) {
+        self.default = Some(expression.to_expression());
+    }
+
+    pub(super) fn reserve(&mut self, pattern: RelayNamePattern, expression: PolicyExpression) {
+        self.reservations.retain(|(p, _)| p != &pattern);
+        self.reservations
+            .push((pattern, expression.to_expression()));
+    }
+
+    /// Return the policy applying to a relay name
+    pub fn policy_for(&self, relay_name: &str) -> Option<&Expr> {
+        self.reservations
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(relay_name))
+            .max_by_key(|(pattern, _)| pattern.specificity())
+            .map(|(_, expression)| expression)
+            .or(self.default.as_ref())
+    }
+
+    /// Evaluate a registration policy against the attributes of the registering identity
+    pub(super) async fn is_authorized(
+        &self,
+        expression: &Expr,
+        identities_attributes: Arc<IdentitiesAttributes>,
+        authority: &Identifier,
+        identifier: &Identifier,
+        relay_name: &str,
+    ) -> Result<bool> {
+        let mut environment = Env::new();
+        environment.put(RELAY_NAME_KEY, str(relay_name));
+
+        Abac::is_identity_authorized_static(
+            identities_attributes,
+            &environment,
+            &self.rate_limits,
+            Some(authority),
+            identifier,
+            expression,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(
+            "*".parse::<RelayNamePattern>().unwrap(),
+            RelayNamePattern::Any
+        );
+        assert_eq!(
+            "prod-*".parse::<RelayNamePattern>().unwrap(),
+            RelayNamePattern::Prefix("prod-".to_string())
+        );
+        assert_eq!(
+            "prod".parse::<RelayNamePattern>().unwrap(),
+            RelayNamePattern::Exact("prod".to_string())
+        );
+        assert!("".parse::<RelayNamePattern>().is_err());
+        assert!("pr*d".parse::<RelayNamePattern>().is_err());
+    }
+
+    #[test]
+    fn test_most_specific_reservation_wins() {
+        let policy = |p: &str| PolicyExpression::from_str(p).unwrap();
+        let mut policies = RelayRegistrationPolicies::default();
+        assert_eq!(policies.policy_for("prod-db"), None);
+
+        policies.set_default(policy("(= subject.relay relay.name)"));
+        policies.reserve(
+            "prod-*".parse().unwrap(),
+            policy("(= subject.env \"prod\")"),
+        );
+        policies.reserve(
+            "prod-db".parse().unwrap(),
+            policy("(= subject.team \"db\")"),
+        );
+        policies.reserve("*".parse().unwrap(), policy("subject.admin"));
+
+        let expression = |p: &str| Some(policy(p).to_expression());
+        assert_eq!(
+            policies.policy_for("prod-db").cloned(),
+            expression("(= subject.team \"db\")")
+        );
+        assert_eq!(
+            policies.policy_for("prod-web").cloned(),
+            expression("(= subject.env \"prod\")")
+        );
+        assert_eq!(
+            policies.policy_for("dev").cloned(),
+            expression("subject.admin")
+        );
+    }
+}
@@ -1,9 +1,15 @@
+use crate::identity::{Identifier, TimestampInSeconds};
+use crate::relay_service::{
+    RelayAcknowledgment, RelayDelivery, StoreAndForwardOptions, StoredRelayMessage,
+    RELAY_DELIVERY_ADDRESS,
+};
 use crate::Context;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::{
-    route, Address, AllowAll, AllowOnwardAddress, Any, IncomingAccessControl, LocalMessage,
-    OutgoingAccessControl, Result, Route, Routed, Worker,
+    route, Address, AllowAll, AllowOnwardAddress, Any, Decodable, Encodable, IncomingAccessControl,
+    LocalMessage, OutgoingAccessControl, Result, Route, Routed, Worker,
 };
 use ockam_node::WorkerBuilder;
 use tracing::info;
@@ -14,6 +20,17 @@ pub(super) struct Relay {
     // while initializing, the worker will send the payload contained in this
     // field to the `forward_route`, to indicate a successful connection
     payload: Option<Vec<u8>>,
+    store_and_forward: Option<StoreAndForward>,
+}
+
+/// State of a relay storing its messages until they are acknowledged
+struct StoreAndForward {
+    options: StoreAndForwardOptions,
+    relay_name: String,
+    // identity which registered this relay, if the registration was authenticated
+    owner: Option<Identifier>,
+    // route to the RemoteRelay which registered this relay
+    delivery_route: Route,
 }
 
 impl Relay {
@@ -23,6 +40,8 @@ impl Relay {
         forward_route: Route,
         registration_payload: Vec<u8>,
         incoming_access_control: Arc<dyn IncomingAccessControl>,
+        store_and_forward: Option<StoreAndForwardOptions>,
+        owner: Option<Identifier>,
     ) -> Result<()> {
         info!("Created new alias {} for {}", address, forward_route);
 
@@ -35,9 +54,17 @@ impl Relay {
             Arc::new(AllowOnwardAddress(next_hop))
         };
 
+        let store_and_forward = store_and_forward.map(|options| StoreAndForward {
+            options,
+            relay_name: address.address().to_string(),
+            owner,
+            delivery_route: forward_route.clone() + RELAY_DELIVERY_ADDRESS,
+        });
+
         let relay = Self {
             forward_route,
             payload: Some(registration_payload.clone()),
+            store_and_forward,
         };
 
         WorkerBuilder::new(relay)
@@ -48,6 +75,157 @@ impl Relay {
 
         Ok(())
     }
+
+    /// Allow the messages to flow between the sender and the receiver of a relayed message
+    fn setup_flow_control(ctx: &Context, next_hop: &Address, prev_hop: &Address) {
+        if let Some(info) = ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(next_hop)
+        {
+            ctx.flow_controls()
+                .add_consumer(prev_hop, info.flow_control_id());
+        }
+
+        if let Some(info) = ctx
+            .flow_controls()
+            .find_flow_control_with_producer_address(prev_hop)
+        {
+            ctx.flow_controls()
+                .add_consumer(next_hop, info.flow_control_id());
+        }
+    }
+}
+
+impl StoreAndForward {
+    /// Discard the messages stored for a previous owner and the expired messages,
+    /// then deliver the remaining ones in order
+    async fn redeliver(&self, ctx: &Context) -> Result<()> {
+        let repository = &self.options.repository;
+        let discarded = repository
+            .set_owner(&self.relay_name, self.owner.as_ref())
+            .await?;
+        if discarded > 0 {
+            warn!(relay = %self.relay_name, %discarded, "The relay is registered by another identity, discarded the messages stored for the previous one");
+        }
+
+        let expired = repository
+            .delete_messages_stored_before(Some(&self.relay_name), self.expiration()?)
+            .await?;
+        if expired > 0 {
+            warn!(relay = %self.relay_name, %expired, "Discarded expired relay messages");
+        }
+
+        let messages = repository.get_messages(&self.relay_name).await?;
+        if !messages.is_empty() {
+            info!(relay = %self.relay_name, count = messages.len(), "Redelivering stored relay messages");
+        }
+        for message in messages {
+            self.deliver(ctx, message.delivery().clone()).await?;
+        }
+        Ok(())
+    }
+
+    /// Store a message, then try to deliver it
+    async fn store_and_deliver(&self, ctx: &Context, local_message: LocalMessage) -> Result<()> {
+        let storage_guard = self.options.storage_lock.lock().await;
+        if !self
+            .has_room_for(local_message.payload().len() as u64)
+            .await?
+        {
+            warn!(relay = %self.relay_name, "The relay storage is full, dropping the message");
+            return Ok(());
+        }
+
+        let repository = &self.options.repository;
+        let delivery = RelayDelivery {
+            sequence: repository.next_sequence(&self.relay_name).await?,
+            onward_route: local_message.onward_route().clone(),
+            return_route: local_message.return_route().clone(),
+            payload: local_message.into_payload(),
+        };
+        let message = StoredRelayMessage::new(delivery.clone(), now()?);
+        repository.store_message(&self.relay_name, &message).await?;
+        drop(storage_guard);
+
+        // the message stays stored until it is acknowledged
+        if let Err(e) = self.deliver(ctx, delivery).await {
+            debug!(relay = %self.relay_name, %e, "The relay message will be delivered when the relay is registered again");
+        }
+        Ok(())
+    }
+
+    /// Delete a message once the node which registered the relay has acknowledged it
+    async fn acknowledge(&self, local_message: LocalMessage) -> Result<()> {
+        // only the registered node can acknowledge messages
+        if local_message.return_route().next().ok() != self.delivery_route.next().ok() {
+            warn!(relay = %self.relay_name, "Relay acknowledgment received from an unexpected route, dropping");
+            return Ok(());
+        }
+
+        let acknowledgment = RelayAcknowledgment::decode(local_message.payload())?;
+        trace!(relay = %self.relay_name, sequence = acknowledgment.sequence, "Relay message acknowledged");
+        self.options
+            .repository
+            .delete_message(&self.relay_name, acknowledgment.sequence)
+            .await
+    }
+
+    /// Return true if a message of the given size can be stored without exceeding the limits
+    /// of the relay and the limit of all the relays
+    async fn has_room_for(&self, size: u64) -> Result<bool> {
+        if self.fits(size).await? {
+            return Ok(true);
+        }
+
+        // make room by discarding the expired messages of all the relays, if any
+        if self
+            .options
+            .repository
+            .delete_messages_stored_before(None, self.expiration()?)
+            .await?
+            == 0
+        {
+            return Ok(false);
+        }
+        self.fits(size).await
+    }
+
+    async fn fits(&self, size: u64) -> Result<bool> {
+        let repository = &self.options.repository;
+        let usage = repository.get_usage(Some(&self.relay_name)).await?;
+        if usage.messages >= self.options.max_messages
+            || usage.bytes + size > self.options.max_bytes
+        {
+            return Ok(false);
+        }
+        let total = repository.get_usage(None).await?;
+        Ok(total.bytes + size <= self.options.max_total_bytes)
+    }
+
+    async fn deliver(&self, ctx: &Context, delivery: RelayDelivery) -> Result<()> {
+        if let Ok(prev_hop) = delivery.return_route.next() {
+            Relay::setup_flow_control(ctx, self.delivery_route.next()?, prev_hop);
+        }
+
+        ctx.forward(
+            LocalMessage::new()
+                .with_onward_route(self.delivery_route.clone())
+                .with_return_route(route![ctx.primary_address().clone()])
+                .with_payload(delivery.encode()?),
+        )
+        .await
+    }
+
+    /// Messages stored before this time are expired
+    fn expiration(&self) -> Result<TimestampInSeconds> {
+        Ok(TimestampInSeconds(
+            now()?.0.saturating_sub(self.options.ttl.as_secs()),
+        ))
+    }
+}
+
+fn now() -> Result<TimestampInSeconds> {
+    Ok(TimestampInSeconds(ockam_core::compat::time::now()?))
 }
 
 #[crate::worker]
@@ -72,6 +250,13 @@ impl Worker for Relay {
         // Remove the last hop so that just route to the node itself is left
         self.forward_route = self.forward_route.clone().modify().pop_back().into();
 
+        // Deliver the messages received while the registered node was not connected
+        if let Some(store_and_forward) = self.store_and_forward.as_ref() {
+            if let Err(e) = store_and_forward.redeliver(ctx).await {
+                warn!(relay = %store_and_forward.relay_name, %e, "Could not redeliver the stored relay messages");
+            }
+        }
+
         Ok(())
     }
 
@@ -82,6 +267,18 @@ impl Worker for Relay {
     ) -> Result<()> {
         let mut local_message = msg.into_local_message();
 
+        if let Some(store_and_forward) = self.store_and_forward.as_ref() {
+            local_message = local_message.pop_front_onward_route()?;
+            // Messages addressed to the relay itself are acknowledgments
+            return if local_message.has_next_on_onward_route() {
+                store_and_forward
+                    .store_and_deliver(ctx, local_message)
+                    .await
+            } else {
+                store_and_forward.acknowledge(local_message).await
+            };
+        }
+
         local_message = local_message
             .pop_front_onward_route()?
             .prepend_front_onward_route(self.forward_route.clone());
@@ -89,21 +286,7 @@ impl Worker for Relay {
         let next_hop = local_message.next_on_onward_route()?;
         let prev_hop = local_message.return_route().next()?;
 
-        if let Some(info) = ctx
-            .flow_controls()
-            .find_flow_control_with_producer_address(next_hop)
-        {
-            ctx.flow_controls()
-                .add_consumer(prev_hop, info.flow_control_id());
-        }
-
-        if let Some(info) = ctx
-            .flow_controls()
-            .find_flow_control_with_producer_address(prev_hop)
-        {
-            ctx.flow_controls()
-                .add_consumer(next_hop, info.flow_control_id());
-        }
+        Self::setup_flow_control(ctx, next_hop, prev_hop);
 
         ctx.forward(local_message).await
     }
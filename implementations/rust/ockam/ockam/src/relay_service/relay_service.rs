@@ -1,5 +1,7 @@
 use crate::alloc::string::ToString;
+use crate::identity::{Identifier, TimestampInSeconds};
 use crate::relay_service::relay::Relay;
+use crate::relay_service::{RelayAuditEvent, RelayAuditEventKind};
 use crate::{Context, RelayServiceOptions};
 use alloc::string::String;
 use ockam_core::compat::boxed::Box;
@@ -9,6 +11,9 @@ use ockam_core::{
 };
 use ockam_node::WorkerBuilder;
 
+/// Maximum length of a requested relay name, in bytes
+pub const MAX_RELAY_NAME_LENGTH: usize = 256;
+
 /// Alias worker to register remote workers under local names.
 ///
 /// To talk with this worker, you can use the
@@ -58,6 +63,147 @@ impl RelayService {
 
         Ok(())
     }
+
+    /// Check if an identity can register a relay with the requested name
+    async fn is_authorized(
+        &self,
+        identifier: Option<&Identifier>,
+        requested_relay_name: &str,
+    ) -> Result<bool> {
+        // Verify the relay usage only when an authority is set, otherwise allow any relay name
+        let Some(authority_validation) = &self.options.authority_validation else {
+            #[cfg(feature = "std")]
+            if self
+                .options
+                .registration_policies
+                .policy_for(requested_relay_name)
+                .is_some()
+            {
+                warn!(%requested_relay_name, "Relay creation request can't be authorized, a registration policy requires an authority, dropping.");
+                return Ok(false);
+            }
+            return Ok(true);
+        };
+
+        let Some(identifier) = identifier else {
+            warn!("Relay creation request not authenticated, dropping.");
+            return Ok(false);
+        };
+
+        #[cfg(feature = "std")]
+        if let Some(expression) = self
+            .options
+            .registration_policies
+            .policy_for(requested_relay_name)
+        {
+            let authorized = self
+                .options
+                .registration_policies
+                .is_authorized(
+                    expression,
+                    authority_validation.identities_attributes.clone(),
+                    &authority_validation.authority,
+                    identifier,
+                    requested_relay_name,
+                )
+                .await?;
+            if !authorized {
+                warn!(%identifier, %requested_relay_name, policy = %expression, "Relay creation request not authorized by the registration policy, dropping.");
+            }
+            return Ok(authorized);
+        }
+
+        let attributes = authority_validation
+            .identities_attributes
+            .get_attributes(identifier, &authority_validation.authority)
+            .await?;
+
+        if let Some(attributes) = attributes {
+            let ockam_relay = attributes
+                .attrs()
+                .get("ockam-relay".as_bytes())
+                .and_then(|a| String::from_utf8(a.clone()).ok());
+
+            if let Some(ockam_relay) = ockam_relay {
+                match ockam_relay.as_str() {
+                    "*" => {
+                        // allow any relay name
+                    }
+                    allowed_name => {
+                        if allowed_name != requested_relay_name {
+                            warn!(%allowed_name, %requested_relay_name, "Relay creation request not authorized, relay name does not match the attribute, dropping.");
+                            return Ok(false);
+                        }
+                    }
+                }
+            } else {
+                warn!(%attributes, "Relay creation request not authorized, missing or invalid `ockam-relay` attribute, dropping.");
+                return Ok(false);
+            }
+        } else {
+            warn!("Relay creation request not authorized, missing `ockam-relay` attribute, no other attribute was found, dropping.");
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Record a relay registration in the audit log, if any
+    async fn audit(
+        &self,
+        relay_name: &str,
+        identifier: Option<Identifier>,
+        authorized: bool,
+    ) -> Result<()> {
+        let Some(audit) = &self.options.audit else {
+            return Ok(());
+        };
+        // don't let unauthenticated callers fill the audit log
+        if !authorized && identifier.is_none() {
+            return Ok(());
+        }
+        let repository = &audit.repository;
+        let now = TimestampInSeconds(ockam_core::compat::time::now()?);
+
+        let event = if !authorized {
+            RelayAuditEvent::new(
+                relay_name,
+                RelayAuditEventKind::Denied,
+                identifier,
+                None,
+                now,
+            )
+        } else {
+            match repository.get_owner(relay_name).await? {
+                Some(previous) if Some(&previous) != identifier.as_ref() => {
+                    warn!(%relay_name, previous_owner = %previous, "Relay registered by a different identity than its previous owner");
+                    RelayAuditEvent::new(
+                        relay_name,
+                        RelayAuditEventKind::Stolen,
+                        identifier,
+                        Some(previous),
+                        now,
+                    )
+                }
+                _ => RelayAuditEvent::new(
+                    relay_name,
+                    RelayAuditEventKind::Claimed,
+                    identifier,
+                    None,
+                    now,
+                ),
+            }
+        };
+        repository.add_event(&event).await?;
+
+        repository
+            .delete_events_created_before(TimestampInSeconds(
+                now.0.saturating_sub(audit.ttl.as_secs()),
+            ))
+            .await?;
+        repository.delete_oldest_events(audit.max_events).await?;
+        Ok(())
+    }
 }
 
 #[crate::worker]
@@ -76,6 +222,14 @@ impl Worker for RelayService {
         let forward_route = message.return_route().clone();
         let requested_relay_address = message.into_body()?;
 
+        if requested_relay_address.len() > MAX_RELAY_NAME_LENGTH {
+            warn!(
+                length = requested_relay_address.len(),
+                "Relay creation request with a name longer than {MAX_RELAY_NAME_LENGTH} bytes, dropping."
+            );
+            return Ok(());
+        }
+
         let requested_relay_name = if requested_relay_address == "register" {
             Address::random_tagged("Relay.service")
                 .address()
@@ -86,50 +240,19 @@ impl Worker for RelayService {
 
         debug!(%requested_relay_name, "Relay creation request");
 
-        // Verify the relay usage only when an authority is set, otherwise allow any relay name
-        if let Some(authority_validation) = &self.options.authority_validation {
-            if let Some(secure_channel_local_info) = secure_channel_local_info {
-                let attributes = authority_validation
-                    .identities_attributes
-                    .get_attributes(
-                        &secure_channel_local_info.their_identifier().into(),
-                        &authority_validation.authority,
-                    )
-                    .await?;
-
-                if let Some(attributes) = attributes {
-                    let ockam_relay = attributes
-                        .attrs()
-                        .get("ockam-relay".as_bytes())
-                        .and_then(|a| String::from_utf8(a.clone()).ok());
-
-                    if let Some(ockam_relay) = ockam_relay {
-                        match ockam_relay.as_str() {
-                            "*" => {
-                                // allow any relay name
-                            }
-                            allowed_name => {
-                                if allowed_name != requested_relay_name {
-                                    warn!(%allowed_name, %requested_relay_name, "Relay creation request not authorized, relay name does not match the attribute, dropping.");
-                                    return Ok(());
-                                }
-                            }
-                        }
-                    } else {
-                        warn!(%attributes, "Relay creation request not authorized, missing or invalid `ockam-relay` attribute, dropping.");
-                        return Ok(());
-                    }
-                } else {
-                    warn!("Relay creation request not authorized, missing `ockam-relay` attribute, no other attribute was found, dropping.");
-                    return Ok(());
-                }
-            } else {
-                warn!("Relay creation request not authenticated, dropping.");
-                return Ok(());
-            }
+        let identifier: Option<Identifier> =
+            secure_channel_local_info.map(|info| info.their_identifier().into());
+        let final_relay_name = self.options.prefix.clone() + &requested_relay_name;
+
+        let authorized = self
+            .is_authorized(identifier.as_ref(), &requested_relay_name)
+            .await?;
+        self.audit(&final_relay_name, identifier.clone(), authorized)
+            .await?;
+        if !authorized {
+            return Ok(());
         }
 
-        let final_relay_name = self.options.prefix.clone() + &requested_relay_name;
         let payload = final_relay_name.clone().encode()?;
         let final_relay_address = Address::from_string(final_relay_name);
 
@@ -146,6 +269,8 @@ impl Worker for RelayService {
             forward_route,
             payload.to_vec(),
             self.options.relays_incoming_access_control.clone(),
+            self.options.store_and_forward.clone(),
+            identifier,
         )?;
 
         Ok(())
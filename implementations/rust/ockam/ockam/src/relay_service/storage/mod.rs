@@ -0,0 +1,14 @@
+pub use relay_audit_repository::*;
+#[cfg(feature = "storage")]
+pub use relay_audit_repository_sql::*;
+pub use relay_messages_repository::*;
+#[cfg(feature = "storage")]
+pub use relay_messages_repository_sql::*;
+
+mod relay_audit_repository;
+mod relay_messages_repository;
+
+#[cfg(feature = "storage")]
+mod relay_audit_repository_sql;
+#[cfg(feature = "storage")]
+mod relay_messages_repository_sql;
@@ -0,0 +1,150 @@
+use crate::identity::{Identifier, TimestampInSeconds};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
+#[cfg(feature = "std")]
+use ockam_node::database::AutoRetry;
+#[cfg(feature = "std")]
+use ockam_node::retry;
+
+/// This trait supports the persistence of an audit log of the relay registrations
+#[async_trait]
+pub trait RelayAuditRepository: Send + Sync + 'static {
+    /// Add an event to the audit log.
+    /// A `Claimed` or `Stolen` event also sets the identifier of the current relay owner
+    async fn add_event(&self, event: &RelayAuditEvent) -> Result<()>;
+
+    /// Return the identifier of the last identity which registered a relay, if known
+    async fn get_owner(&self, relay_name: &str) -> Result<Option<Identifier>>;
+
+    /// Return the events of a relay, or of all the relays, ordered by time
+    async fn get_events(&self, relay_name: Option<&str>) -> Result<Vec<RelayAuditEvent>>;
+
+    /// Delete the events created before a given time.
+    /// Return the number of deleted events
+    async fn delete_events_created_before(&self, created_at: TimestampInSeconds) -> Result<u64>;
+
+    /// Delete the oldest events to keep at most `max_events` events.
+    /// Return the number of deleted events
+    async fn delete_oldest_events(&self, max_events: u64) -> Result<u64>;
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl<T: RelayAuditRepository> RelayAuditRepository for AutoRetry<T> {
+    async fn add_event(&self, event: &RelayAuditEvent) -> Result<()> {
+        retry!(self.wrapped.add_event(event))
+    }
+
+    async fn get_owner(&self, relay_name: &str) -> Result<Option<Identifier>> {
+        retry!(self.wrapped.get_owner(relay_name))
+    }
+
+    async fn get_events(&self, relay_name: Option<&str>) -> Result<Vec<RelayAuditEvent>> {
+        retry!(self.wrapped.get_events(relay_name))
+    }
+
+    async fn delete_events_created_before(&self, created_at: TimestampInSeconds) -> Result<u64> {
+        retry!(self.wrapped.delete_events_created_before(created_at))
+    }
+
+    async fn delete_oldest_events(&self, max_events: u64) -> Result<u64> {
+        retry!(self.wrapped.delete_oldest_events(max_events))
+    }
+}
+
+/// Kind of relay registration event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelayAuditEventKind {
+    /// The relay was registered by its previous owner, or for the first time
+    Claimed,
+    /// The relay was registered by a different identity than its previous owner
+    Stolen,
+    /// The relay registration was not authorized
+    Denied,
+}
+
+impl Display for RelayAuditEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            RelayAuditEventKind::Claimed => "claimed",
+            RelayAuditEventKind::Stolen => "stolen",
+            RelayAuditEventKind::Denied => "denied",
+        })
+    }
+}
+
+impl FromStr for RelayAuditEventKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "claimed" => Ok(RelayAuditEventKind::Claimed),
+            "stolen" => Ok(RelayAuditEventKind::Stolen),
+            "denied" => Ok(RelayAuditEventKind::Denied),
+            _ => Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                format!("unknown relay audit event kind {s}"),
+            )),
+        }
+    }
+}
+
+/// Registration event of a relay
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayAuditEvent {
+    relay_name: String,
+    kind: RelayAuditEventKind,
+    identifier: Option<Identifier>,
+    previous_identifier: Option<Identifier>,
+    created_at: TimestampInSeconds,
+}
+
+impl RelayAuditEvent {
+    /// Create a new event
+    pub fn new(
+        relay_name: &str,
+        kind: RelayAuditEventKind,
+        identifier: Option<Identifier>,
+        previous_identifier: Option<Identifier>,
+        created_at: TimestampInSeconds,
+    ) -> Self {
+        Self {
+            relay_name: relay_name.to_string(),
+            kind,
+            identifier,
+            previous_identifier,
+            created_at,
+        }
+    }
+
+    /// Name of the relay, including the relay service prefix
+    pub fn relay_name(&self) -> &str {
+        &self.relay_name
+    }
+
+    /// Kind of event
+    pub fn kind(&self) -> RelayAuditEventKind {
+        self.kind
+    }
+
+    /// Identifier of the identity registering the relay, if the registration was authenticated
+    pub fn identifier(&self) -> Option<&Identifier> {
+        self.identifier.as_ref()
+    }
+
+    /// Identifier of the previous owner of a stolen relay
+    pub fn previous_identifier(&self) -> Option<&Identifier> {
+        self.previous_identifier.as_ref()
+    }
+
+    /// Time of the event
+    pub fn created_at(&self) -> TimestampInSeconds {
+        self.created_at
+    }
+}
@@ -0,0 +1,259 @@
+use core::str::FromStr;
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::identity::{Identifier, TimestampInSeconds};
+use crate::relay_service::{RelayAuditEvent, RelayAuditEventKind, RelayAuditRepository};
+use ockam_core::{async_trait, Result};
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToVoid};
+
+/// Implementation of [`RelayAuditRepository`] trait based on an underlying database
+/// using sqlx as its API
+#[derive(Clone)]
+pub struct RelayAuditSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl RelayAuditSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for the relay audit log");
+        Self { database }
+    }
+
+    /// Create a repository
+    pub fn make_repository(database: SqlxDatabase) -> Arc<dyn RelayAuditRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database)))
+        } else {
+            Arc::new(Self::new(database))
+        }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(SqlxDatabase::in_memory("relay audit").await?))
+    }
+}
+
+#[async_trait]
+impl RelayAuditRepository for RelayAuditSqlxDatabase {
+    async fn add_event(&self, event: &RelayAuditEvent) -> Result<()> {
+        let mut transaction = self.database.begin().await.into_core()?;
+        let query1 = query(
+            r#"
+            INSERT INTO relay_audit_event (relay_name, kind, identifier, previous_identifier, created_at)
+            VALUES ($1, $2, $3, $4, $5)"#,
+        )
+        .bind(event.relay_name())
+        .bind(event.kind().to_string())
+        .bind(event.identifier().map(|i| i.to_string()))
+        .bind(event.previous_identifier().map(|i| i.to_string()))
+        .bind(event.created_at().0 as i64);
+        query1.execute(&mut *transaction).await.void()?;
+
+        if let (RelayAuditEventKind::Claimed | RelayAuditEventKind::Stolen, Some(identifier)) =
+            (event.kind(), event.identifier())
+        {
+            let query2 = query(
+                r#"
+                INSERT INTO relay_owner (relay_name, identifier)
+                VALUES ($1, $2)
+                ON CONFLICT (relay_name)
+                DO UPDATE SET identifier = $2"#,
+            )
+            .bind(event.relay_name())
+            .bind(identifier.to_string());
+            query2.execute(&mut *transaction).await.void()?;
+        }
+
+        transaction.commit().await.void()
+    }
+
+    async fn get_owner(&self, relay_name: &str) -> Result<Option<Identifier>> {
+        let query = query_scalar("SELECT identifier FROM relay_owner WHERE relay_name = $1")
+            .bind(relay_name);
+        let identifier: Option<String> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        identifier.map(|i| Identifier::from_str(&i)).transpose()
+    }
+
+    async fn get_events(&self, relay_name: Option<&str>) -> Result<Vec<RelayAuditEvent>> {
+        let rows: Vec<RelayAuditEventRow> = match relay_name {
+            Some(relay_name) => {
+                let query = query_as(
+                    r#"
+                    SELECT relay_name, kind, identifier, previous_identifier, created_at
+                    FROM relay_audit_event
+                    WHERE relay_name = $1
+                    ORDER BY created_at"#,
+                )
+                .bind(relay_name);
+                query.fetch_all(&*self.database.pool).await.into_core()?
+            }
+            None => {
+                let query = query_as(
+                    r#"
+                    SELECT relay_name, kind, identifier, previous_identifier, created_at
+                    FROM relay_audit_event
+                    ORDER BY created_at"#,
+                );
+                query.fetch_all(&*self.database.pool).await.into_core()?
+            }
+        };
+        rows.into_iter().map(|r| r.relay_audit_event()).collect()
+    }
+
+    async fn delete_events_created_before(&self, created_at: TimestampInSeconds) -> Result<u64> {
+        let query =
+            query("DELETE FROM relay_audit_event WHERE created_at < $1").bind(created_at.0 as i64);
+        let result = query.execute(&*self.database.pool).await.into_core()?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_oldest_events(&self, max_events: u64) -> Result<u64> {
+        // the events created at the same time as the first event to delete are deleted as well
+        let query1 = query_scalar(
+            r#"
+            SELECT created_at FROM relay_audit_event
+            ORDER BY created_at DESC
+            LIMIT 1 OFFSET $1"#,
+        )
+        .bind(max_events as i64);
+        let created_at: Option<i64> = query1
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        let Some(created_at) = created_at else {
+            return Ok(0);
+        };
+
+        let query2 = query("DELETE FROM relay_audit_event WHERE created_at <= $1").bind(created_at);
+        let result = query2.execute(&*self.database.pool).await.into_core()?;
+        Ok(result.rows_affected())
+    }
+}
+
+// Database serialization / deserialization
+
+/// Low-level representation of a row in the relay_audit_event table
+#[derive(FromRow)]
+struct RelayAuditEventRow {
+    relay_name: String,
+    kind: String,
+    identifier: Option<String>,
+    previous_identifier: Option<String>,
+    created_at: i64,
+}
+
+impl RelayAuditEventRow {
+    fn relay_audit_event(self) -> Result<RelayAuditEvent> {
+        Ok(RelayAuditEvent::new(
+            &self.relay_name,
+            RelayAuditEventKind::from_str(&self.kind)?,
+            self.identifier
+                .map(|i| Identifier::from_str(&i))
+                .transpose()?,
+            self.previous_identifier
+                .map(|i| Identifier::from_str(&i))
+                .transpose()?,
+            TimestampInSeconds(self.created_at as u64),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_node::database::with_dbs;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn RelayAuditRepository> =
+                Arc::new(RelayAuditSqlxDatabase::new(db));
+
+            let identifier1 = Identifier::from_str(
+                "I0000000000000000000000000000000000000000000000000000000000000001",
+            )?;
+            let identifier2 = Identifier::from_str(
+                "I0000000000000000000000000000000000000000000000000000000000000002",
+            )?;
+            assert_eq!(repository.get_owner("relay").await?, None);
+
+            let claimed = RelayAuditEvent::new(
+                "relay",
+                RelayAuditEventKind::Claimed,
+                Some(identifier1.clone()),
+                None,
+                TimestampInSeconds(10),
+            );
+            let denied = RelayAuditEvent::new(
+                "relay",
+                RelayAuditEventKind::Denied,
+                Some(identifier2.clone()),
+                None,
+                TimestampInSeconds(20),
+            );
+            let other = RelayAuditEvent::new(
+                "other",
+                RelayAuditEventKind::Claimed,
+                None,
+                None,
+                TimestampInSeconds(25),
+            );
+            repository.add_event(&claimed).await?;
+            repository.add_event(&denied).await?;
+            repository.add_event(&other).await?;
+
+            // a denied registration doesn't change the owner
+            assert_eq!(
+                repository.get_owner("relay").await?,
+                Some(identifier1.clone())
+            );
+            // an unauthenticated registration has no owner
+            assert_eq!(repository.get_owner("other").await?, None);
+
+            let stolen = RelayAuditEvent::new(
+                "relay",
+                RelayAuditEventKind::Stolen,
+                Some(identifier2.clone()),
+                Some(identifier1.clone()),
+                TimestampInSeconds(30),
+            );
+            repository.add_event(&stolen).await?;
+            assert_eq!(repository.get_owner("relay").await?, Some(identifier2));
+
+            assert_eq!(
+                repository.get_events(Some("relay")).await?,
+                vec![claimed.clone(), denied.clone(), stolen.clone()]
+            );
+            assert_eq!(
+                repository.get_events(None).await?,
+                vec![
+                    claimed.clone(),
+                    denied.clone(),
+                    other.clone(),
+                    stolen.clone()
+                ]
+            );
+
+            // the oldest events are deleted
+            assert_eq!(
+                repository
+                    .delete_events_created_before(TimestampInSeconds(20))
+                    .await?,
+                1
+            );
+            assert_eq!(repository.delete_oldest_events(2).await?, 1);
+            assert_eq!(repository.get_events(None).await?, vec![other, stolen]);
+            assert_eq!(repository.delete_oldest_events(2).await?, 0);
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,128 @@
+use crate::identity::{Identifier, TimestampInSeconds};
+use crate::relay_service::RelayDelivery;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, Result};
+#[cfg(feature = "std")]
+use ockam_node::database::AutoRetry;
+#[cfg(feature = "std")]
+use ockam_node::retry;
+
+/// This trait supports the persistence of the messages received by store-and-forward relays
+#[async_trait]
+pub trait RelayMessagesRepository: Send + Sync + 'static {
+    /// Store a message received by a relay
+    async fn store_message(&self, relay_name: &str, message: &StoredRelayMessage) -> Result<()>;
+
+    /// Return the messages stored for a relay, ordered by sequence number
+    async fn get_messages(&self, relay_name: &str) -> Result<Vec<StoredRelayMessage>>;
+
+    /// Delete a message once it has been acknowledged
+    async fn delete_message(&self, relay_name: &str, sequence: u64) -> Result<()>;
+
+    /// Delete the messages stored for a relay, or for all the relays, before a given time.
+    /// Return the number of deleted messages
+    async fn delete_messages_stored_before(
+        &self,
+        relay_name: Option<&str>,
+        stored_at: TimestampInSeconds,
+    ) -> Result<u64>;
+
+    /// Return the number of messages and the total payload size stored for a relay,
+    /// or for all the relays
+    async fn get_usage(&self, relay_name: Option<&str>) -> Result<RelayMessagesUsage>;
+
+    /// Set the identity which registered a relay, `None` if the registration was not
+    /// authenticated. When the owner changes, the messages stored for the previous owner are
+    /// deleted. Return the number of deleted messages
+    async fn set_owner(&self, relay_name: &str, owner: Option<&Identifier>) -> Result<u64>;
+
+    /// Return the next sequence number of a relay.
+    /// Sequence numbers are never reused, even after all the messages of the relay were deleted,
+    /// so that a late acknowledgment can't delete a newer message
+    async fn next_sequence(&self, relay_name: &str) -> Result<u64>;
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl<T: RelayMessagesRepository> RelayMessagesRepository for AutoRetry<T> {
+    async fn store_message(&self, relay_name: &str, message: &StoredRelayMessage) -> Result<()> {
+        retry!(self.wrapped.store_message(relay_name, message))
+    }
+
+    async fn get_messages(&self, relay_name: &str) -> Result<Vec<StoredRelayMessage>> {
+        retry!(self.wrapped.get_messages(relay_name))
+    }
+
+    async fn delete_message(&self, relay_name: &str, sequence: u64) -> Result<()> {
+        retry!(self.wrapped.delete_message(relay_name, sequence))
+    }
+
+    async fn delete_messages_stored_before(
+        &self,
+        relay_name: Option<&str>,
+        stored_at: TimestampInSeconds,
+    ) -> Result<u64> {
+        retry!(self
+            .wrapped
+            .delete_messages_stored_before(relay_name, stored_at))
+    }
+
+    async fn get_usage(&self, relay_name: Option<&str>) -> Result<RelayMessagesUsage> {
+        retry!(self.wrapped.get_usage(relay_name))
+    }
+
+    async fn set_owner(&self, relay_name: &str, owner: Option<&Identifier>) -> Result<u64> {
+        retry!(self.wrapped.set_owner(relay_name, owner))
+    }
+
+    async fn next_sequence(&self, relay_name: &str) -> Result<u64> {
+        retry!(self.wrapped.next_sequence(relay_name))
+    }
+}
+
+/// A message stored by a relay until it is acknowledged
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredRelayMessage {
+    delivery: RelayDelivery,
+    stored_at: TimestampInSeconds,
+}
+
+impl StoredRelayMessage {
+    /// Create a new stored message
+    pub fn new(delivery: RelayDelivery, stored_at: TimestampInSeconds) -> Self {
+        Self {
+            delivery,
+            stored_at,
+        }
+    }
+
+    /// Message, as it is delivered to the registered node
+    pub fn delivery(&self) -> &RelayDelivery {
+        &self.delivery
+    }
+
+    /// Sequence number of the message
+    pub fn sequence(&self) -> u64 {
+        self.delivery.sequence
+    }
+
+    /// Size of the message payload
+    pub fn size(&self) -> u64 {
+        self.delivery.payload.len() as u64
+    }
+
+    /// Time when the message was received
+    pub fn stored_at(&self) -> TimestampInSeconds {
+        self.stored_at
+    }
+}
+
+/// Number of messages and total payload size stored for a relay
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RelayMessagesUsage {
+    /// Number of stored messages
+    pub messages: u64,
+    /// Total size of the stored payloads
+    pub bytes: u64,
+}
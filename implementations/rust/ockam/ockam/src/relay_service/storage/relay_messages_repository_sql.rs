@@ -0,0 +1,349 @@
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::identity::{Identifier, TimestampInSeconds};
+use crate::relay_service::{
+    RelayDelivery, RelayMessagesRepository, RelayMessagesUsage, StoredRelayMessage,
+};
+use ockam_core::{async_trait, Decodable, Encodable, Result};
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToVoid};
+
+/// Implementation of [`RelayMessagesRepository`] trait based on an underlying database
+/// using sqlx as its API
+#[derive(Clone)]
+pub struct RelayMessagesSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl RelayMessagesSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for relay messages");
+        Self { database }
+    }
+
+    /// Create a repository
+    pub fn make_repository(database: SqlxDatabase) -> Arc<dyn RelayMessagesRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database)))
+        } else {
+            Arc::new(Self::new(database))
+        }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(SqlxDatabase::in_memory("relay messages").await?))
+    }
+}
+
+#[async_trait]
+impl RelayMessagesRepository for RelayMessagesSqlxDatabase {
+    async fn store_message(&self, relay_name: &str, message: &StoredRelayMessage) -> Result<()> {
+        let query = query(
+            r#"
+            INSERT INTO relay_message (relay_name, sequence, delivery, size, stored_at)
+            VALUES ($1, $2, $3, $4, $5)"#,
+        )
+        .bind(relay_name)
+        .bind(message.sequence() as i64)
+        .bind(message.delivery().clone().encode()?)
+        .bind(message.size() as i64)
+        .bind(message.stored_at().0 as i64);
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_messages(&self, relay_name: &str) -> Result<Vec<StoredRelayMessage>> {
+        let query = query_as(
+            r#"
+            SELECT delivery, stored_at FROM relay_message
+            WHERE relay_name = $1
+            ORDER BY sequence"#,
+        )
+        .bind(relay_name);
+        let rows: Vec<RelayMessageRow> = query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.into_iter().map(|r| r.stored_relay_message()).collect()
+    }
+
+    async fn delete_message(&self, relay_name: &str, sequence: u64) -> Result<()> {
+        let query = query("DELETE FROM relay_message WHERE relay_name = $1 AND sequence = $2")
+            .bind(relay_name)
+            .bind(sequence as i64);
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn delete_messages_stored_before(
+        &self,
+        relay_name: Option<&str>,
+        stored_at: TimestampInSeconds,
+    ) -> Result<u64> {
+        let query = match relay_name {
+            Some(relay_name) => {
+                query("DELETE FROM relay_message WHERE relay_name = $1 AND stored_at < $2")
+                    .bind(relay_name)
+                    .bind(stored_at.0 as i64)
+            }
+            None => {
+                query("DELETE FROM relay_message WHERE stored_at < $1").bind(stored_at.0 as i64)
+            }
+        };
+        let result = query.execute(&*self.database.pool).await.into_core()?;
+        Ok(result.rows_affected())
+    }
+
+    async fn get_usage(&self, relay_name: Option<&str>) -> Result<RelayMessagesUsage> {
+        let row: RelayMessagesUsageRow = match relay_name {
+            Some(relay_name) => {
+                let query = query_as(
+                    r#"
+                    SELECT COUNT(*) AS messages, CAST(COALESCE(SUM(size), 0) AS BIGINT) AS bytes
+                    FROM relay_message
+                    WHERE relay_name = $1"#,
+                )
+                .bind(relay_name);
+                query.fetch_one(&*self.database.pool).await.into_core()?
+            }
+            None => {
+                let query = query_as(
+                    r#"
+                    SELECT COUNT(*) AS messages, CAST(COALESCE(SUM(size), 0) AS BIGINT) AS bytes
+                    FROM relay_message"#,
+                );
+                query.fetch_one(&*self.database.pool).await.into_core()?
+            }
+        };
+        Ok(RelayMessagesUsage {
+            messages: row.messages as u64,
+            bytes: row.bytes as u64,
+        })
+    }
+
+    async fn set_owner(&self, relay_name: &str, owner: Option<&Identifier>) -> Result<u64> {
+        // unauthenticated registrations are compared with an empty identifier.
+        // The messages stored before the owner was known are deleted as well
+        let owner = owner.map(|i| i.to_string());
+        let mut transaction = self.database.begin().await.into_core()?;
+        let query1 = query(
+            r#"
+            DELETE FROM relay_message
+            WHERE relay_name = $1 AND NOT EXISTS (
+                SELECT 1 FROM relay_message_owner
+                WHERE relay_name = $1 AND COALESCE(identifier, '') = $2)"#,
+        )
+        .bind(relay_name)
+        .bind(owner.clone().unwrap_or_default());
+        let deleted = query1
+            .execute(&mut *transaction)
+            .await
+            .into_core()?
+            .rows_affected();
+
+        let query2 = query(
+            r#"
+            INSERT INTO relay_message_owner (relay_name, identifier)
+            VALUES ($1, $2)
+            ON CONFLICT (relay_name)
+            DO UPDATE SET identifier = $2"#,
+        )
+        .bind(relay_name)
+        .bind(owner);
+        query2.execute(&mut *transaction).await.void()?;
+
+        transaction.commit().await.void()?;
+        Ok(deleted)
+    }
+
+    async fn next_sequence(&self, relay_name: &str) -> Result<u64> {
+        let query = query_scalar(
+            r#"
+            INSERT INTO relay_sequence (relay_name, last_sequence)
+            VALUES ($1, 1)
+            ON CONFLICT (relay_name)
+            DO UPDATE SET last_sequence = relay_sequence.last_sequence + 1
+            RETURNING last_sequence"#,
+        )
+        .bind(relay_name);
+        let sequence: i64 = query.fetch_one(&*self.database.pool).await.into_core()?;
+        Ok(sequence as u64)
+    }
+}
+
+// Database serialization / deserialization
+
+/// Low-level representation of a row in the relay_message table
+#[derive(FromRow)]
+struct RelayMessageRow {
+    delivery: Vec<u8>,
+    stored_at: i64,
+}
+
+impl RelayMessageRow {
+    fn stored_relay_message(self) -> Result<StoredRelayMessage> {
+        Ok(StoredRelayMessage::new(
+            RelayDelivery::decode(&self.delivery)?,
+            TimestampInSeconds(self.stored_at as u64),
+        ))
+    }
+}
+
+#[derive(FromRow)]
+struct RelayMessagesUsageRow {
+    messages: i64,
+    bytes: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+    use ockam_core::route;
+    use ockam_node::database::with_dbs;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn RelayMessagesRepository> =
+                Arc::new(RelayMessagesSqlxDatabase::new(db));
+
+            assert_eq!(
+                repository.get_usage(Some("relay")).await?,
+                RelayMessagesUsage::default()
+            );
+
+            let message1 = stored_message(1, b"hello", 10);
+            let message2 = stored_message(2, b"world!", 20);
+            let other = stored_message(1, b"other", 10);
+            // the messages are returned in order
+            repository.store_message("relay", &message2).await?;
+            repository.store_message("relay", &message1).await?;
+            repository.store_message("other", &other).await?;
+
+            assert_eq!(
+                repository.get_messages("relay").await?,
+                vec![message1.clone(), message2.clone()]
+            );
+            assert_eq!(
+                repository.get_usage(Some("relay")).await?,
+                RelayMessagesUsage {
+                    messages: 2,
+                    bytes: 11
+                }
+            );
+
+            // an acknowledged message is deleted
+            repository.delete_message("relay", 1).await?;
+            assert_eq!(
+                repository.get_messages("relay").await?,
+                vec![message2.clone()]
+            );
+
+            // expired messages are deleted
+            assert_eq!(
+                repository
+                    .delete_messages_stored_before(Some("relay"), TimestampInSeconds(21))
+                    .await?,
+                1
+            );
+            assert!(repository.get_messages("relay").await?.is_empty());
+            assert_eq!(repository.get_messages("other").await?, vec![other]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_sequence_numbers_are_not_reused() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn RelayMessagesRepository> =
+                Arc::new(RelayMessagesSqlxDatabase::new(db));
+
+            assert_eq!(repository.next_sequence("relay").await?, 1);
+            assert_eq!(repository.next_sequence("other").await?, 1);
+
+            // all the messages of the relay are acknowledged
+            repository
+                .store_message("relay", &stored_message(1, b"hello", 10))
+                .await?;
+            repository.delete_message("relay", 1).await?;
+            assert!(repository.get_messages("relay").await?.is_empty());
+
+            // a new message gets a new sequence number
+            let sequence = repository.next_sequence("relay").await?;
+            assert_eq!(sequence, 2);
+            let message = stored_message(sequence, b"world!", 20);
+            repository.store_message("relay", &message).await?;
+
+            // a late acknowledgment of the first message doesn't delete the new one
+            repository.delete_message("relay", 1).await?;
+            assert_eq!(repository.get_messages("relay").await?, vec![message]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_owner_change_deletes_the_messages() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn RelayMessagesRepository> =
+                Arc::new(RelayMessagesSqlxDatabase::new(db));
+            let identifier1 = Identifier::from_str(
+                "I0000000000000000000000000000000000000000000000000000000000000001",
+            )?;
+            let identifier2 = Identifier::from_str(
+                "I0000000000000000000000000000000000000000000000000000000000000002",
+            )?;
+
+            assert_eq!(repository.set_owner("relay", Some(&identifier1)).await?, 0);
+            repository
+                .store_message("relay", &stored_message(1, b"hello", 10))
+                .await?;
+            let other = stored_message(1, b"other", 10);
+            repository.store_message("other", &other).await?;
+
+            // the same owner registers the relay again
+            assert_eq!(repository.set_owner("relay", Some(&identifier1)).await?, 0);
+            assert_eq!(repository.get_messages("relay").await?.len(), 1);
+            assert_eq!(
+                repository.get_usage(None).await?,
+                RelayMessagesUsage {
+                    messages: 2,
+                    bytes: 10
+                }
+            );
+
+            // another identity registers the relay
+            assert_eq!(repository.set_owner("relay", Some(&identifier2)).await?, 1);
+            assert!(repository.get_messages("relay").await?.is_empty());
+
+            // an unauthenticated registration is another owner
+            repository
+                .store_message("relay", &stored_message(2, b"world", 20))
+                .await?;
+            assert_eq!(repository.set_owner("relay", None).await?, 1);
+            assert_eq!(repository.set_owner("relay", None).await?, 0);
+
+            // the messages stored for a relay whose owner is unknown are deleted
+            assert_eq!(repository.set_owner("other", None).await?, 1);
+            assert_eq!(
+                repository.get_usage(None).await?,
+                RelayMessagesUsage::default()
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    fn stored_message(sequence: u64, payload: &[u8], stored_at: u64) -> StoredRelayMessage {
+        StoredRelayMessage::new(
+            RelayDelivery {
+                sequence,
+                onward_route: route!["echoer"],
+                return_route: route!["sender"],
+                payload: payload.to_vec(),
+            },
+            TimestampInSeconds(stored_at),
+        )
+    }
+}
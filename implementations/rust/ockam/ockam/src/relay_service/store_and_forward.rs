@@ -0,0 +1,103 @@
+use crate::relay_service::RelayMessagesRepository;
+use crate::Message;
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Route;
+use ockam_node::compat::asynchronous::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Address following the [`RemoteRelay`](crate::remote::RemoteRelay) address in the route
+/// of the messages delivered by a store-and-forward relay
+pub const RELAY_DELIVERY_ADDRESS: &str = "relay_delivery";
+
+/// Default maximum number of messages stored for a relay
+pub const DEFAULT_MAX_STORED_MESSAGES: u64 = 10_000;
+
+/// Default maximum size of the payloads stored for a relay
+pub const DEFAULT_MAX_STORED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default maximum size of the payloads stored for all the relays
+pub const DEFAULT_MAX_TOTAL_STORED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Default time after which an unacknowledged message is discarded
+pub const DEFAULT_STORED_MESSAGE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A message stored by a relay, sent to the [`RemoteRelay`](crate::remote::RemoteRelay)
+/// which registered the relay. The `RemoteRelay` forwards the message on its onward route
+/// and sends back a [`RelayAcknowledgment`]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Message)]
+pub struct RelayDelivery {
+    /// Sequence number of the message for its relay
+    pub sequence: u64,
+    /// Route of the message after the relay
+    pub onward_route: Route,
+    /// Route to the sender of the message, from the relay node
+    pub return_route: Route,
+    /// Message payload
+    pub payload: Vec<u8>,
+}
+
+/// Acknowledgment of a [`RelayDelivery`], the message is then removed from the relay storage
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Message)]
+pub struct RelayAcknowledgment {
+    /// Sequence number of the delivered message
+    pub sequence: u64,
+}
+
+/// Options of the relays storing their messages until they are acknowledged.
+///
+/// The messages received by such a relay are stored, then delivered to the
+/// [`RemoteRelay`](crate::remote::RemoteRelay) which registered the relay.
+/// The messages which are not acknowledged, for example because the registered node
+/// was disconnected, are delivered again, in order, when the relay is registered again.
+/// They are deleted if the relay is registered again by another identity.
+#[derive(Clone)]
+pub struct StoreAndForwardOptions {
+    pub(super) repository: Arc<dyn RelayMessagesRepository>,
+    pub(super) max_messages: u64,
+    pub(super) max_bytes: u64,
+    pub(super) max_total_bytes: u64,
+    pub(super) ttl: Duration,
+    // shared by all the relays, so that concurrent messages can't exceed the total limit
+    pub(super) storage_lock: Arc<Mutex<()>>,
+}
+
+impl StoreAndForwardOptions {
+    /// Store the messages in the given repository, with the default limits
+    pub fn new(repository: Arc<dyn RelayMessagesRepository>) -> Self {
+        Self {
+            repository,
+            max_messages: DEFAULT_MAX_STORED_MESSAGES,
+            max_bytes: DEFAULT_MAX_STORED_BYTES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_STORED_BYTES,
+            ttl: DEFAULT_STORED_MESSAGE_TTL,
+            storage_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Maximum number of messages stored for a relay. New messages are dropped once it is reached
+    pub fn with_max_messages(mut self, max_messages: u64) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// Maximum size of the payloads stored for a relay. New messages are dropped once it is reached
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Maximum size of the payloads stored for all the relays.
+    /// New messages are dropped once it is reached
+    pub fn with_max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    /// Time after which a message which hasn't been acknowledged is discarded
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
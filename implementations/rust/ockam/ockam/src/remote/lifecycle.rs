@@ -60,6 +60,7 @@ impl RemoteRelay {
             registration_route,
             registration_payload,
             flow_control_id,
+            relay_route: None,
         }
     }
 
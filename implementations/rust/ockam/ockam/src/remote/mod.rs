@@ -23,4 +23,7 @@ pub struct RemoteRelay {
     registration_route: Route,
     registration_payload: String,
     flow_control_id: Option<FlowControlId>,
+    /// Route to the relay, known once the registration is confirmed.
+    /// Only the messages stored by the relay and coming from this route are delivered
+    relay_route: Option<Route>,
 }
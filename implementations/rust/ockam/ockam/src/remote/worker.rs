@@ -1,11 +1,11 @@
 use crate::remote::{RemoteRelay, RemoteRelayInfo};
-use crate::{Context, OckamError};
+use crate::{Context, OckamError, RelayAcknowledgment, RelayDelivery, RELAY_DELIVERY_ADDRESS};
 use ockam_core::compat::{
     boxed::Box,
     string::{String, ToString},
 };
-use ockam_core::{Any, Decodable, Result, Routed, Worker};
-use tracing::{debug, info};
+use ockam_core::{Address, Any, Decodable, LocalMessage, Result, Route, Routed, Worker};
+use tracing::{debug, info, warn};
 
 #[crate::worker]
 impl Worker for RemoteRelay {
@@ -50,6 +50,7 @@ impl Worker for RemoteRelay {
                     {
                         return Err(OckamError::InvalidResponseFromRelayService)?;
                     }
+                    self.relay_route = Some(local_message.return_route.clone());
 
                     if !self.completion_msg_sent {
                         info!(registration_route = %self.registration_route, "RemoteRelay registered with route: {}", local_message.return_route);
@@ -86,6 +87,38 @@ impl Worker for RemoteRelay {
                     // to exploit it in any way
                     return Err(OckamError::UnknownForwarderNextHopAddress)?;
                 }
+                Ok(next) if next == &Address::from_string(RELAY_DELIVERY_ADDRESS) => {
+                    // A message stored by the relay, which must be acknowledged once forwarded.
+                    // It must come from the relay registered by this worker, through the same
+                    // route as the registration response
+                    let relay_route = local_message.return_route().clone();
+                    if self.relay_route.as_ref() != Some(&relay_route) {
+                        warn!(registration_route = %self.registration_route, %relay_route, "RemoteRelay received a stored message from an unexpected route, dropping");
+                        return Ok(());
+                    }
+
+                    let delivery = RelayDelivery::decode(local_message.payload())?;
+                    debug!(registration_route = %self.registration_route, sequence = delivery.sequence, "RemoteRelay received stored message");
+
+                    // Explicitly check that we don't forward to ourselves, as for the messages
+                    // which are not stored. The message is still acknowledged to be discarded
+                    if delivery.onward_route.next().ok() == Some(&self.addresses.main_remote) {
+                        self.acknowledge(ctx, relay_route, delivery.sequence)
+                            .await?;
+                        return Err(OckamError::UnknownForwarderNextHopAddress)?;
+                    }
+
+                    // The replies go back through the relay node
+                    let relay_node_route: Route = relay_route.clone().modify().pop_back().into();
+                    let message = LocalMessage::new()
+                        .with_onward_route(delivery.onward_route)
+                        .with_return_route(relay_node_route + delivery.return_route)
+                        .with_payload(delivery.payload);
+                    ctx.forward_from_address(message, self.addresses.main_internal.clone())
+                        .await?;
+
+                    self.acknowledge(ctx, relay_route, delivery.sequence).await
+                }
                 Ok(_) => {
                     // Forwarding the message
                     debug!(registration_route = %self.registration_route, "RemoteRelay received payload message");
@@ -102,3 +135,15 @@ impl Worker for RemoteRelay {
         }
     }
 }
+
+impl RemoteRelay {
+    /// Acknowledge a message stored by the relay, so that it is deleted from its storage
+    async fn acknowledge(&self, ctx: &Context, relay_route: Route, sequence: u64) -> Result<()> {
+        ctx.send_from_address(
+            relay_route,
+            RelayAcknowledgment { sequence },
+            self.addresses.main_remote.clone(),
+        )
+        .await
+    }
+}
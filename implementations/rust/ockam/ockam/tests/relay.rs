@@ -1,10 +1,22 @@
-use ockam::identity::{secure_channels, SecureChannelListenerOptions, SecureChannelOptions};
+use ockam::abac::PolicyExpression;
+use ockam::identity::utils::now;
+use ockam::identity::{
+    secure_channels, AttributesEntry, Identifier, SecureChannelListenerOptions,
+    SecureChannelOptions, SecureChannels,
+};
 use ockam::remote::{RemoteRelay, RemoteRelayOptions};
 use ockam::workers::Echoer;
-use ockam::{RelayService, RelayServiceOptions};
-use ockam_core::{route, AllowAll, Result};
+use ockam::{
+    RelayAuditEventKind, RelayAuditOptions, RelayAuditRepository, RelayAuditSqlxDatabase,
+    RelayDelivery, RelayMessagesRepository, RelayMessagesSqlxDatabase, RelayService,
+    RelayServiceOptions, StoreAndForwardOptions, MAX_RELAY_NAME_LENGTH, RELAY_DELIVERY_ADDRESS,
+};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Encodable, LocalMessage, Result, Route};
 use ockam_node::{Context, MessageReceiveOptions};
 use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+use std::str::FromStr;
 use std::time::Duration;
 
 // Node creates a Relay service and a Remote Relay, Echoer is reached through the Relay. No flow control
@@ -246,3 +258,261 @@ async fn test4(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+// Node creates a store-and-forward Relay service and a static Remote Relay.
+// A message sent while the Remote Relay is stopped is delivered to the Echoer once the
+// Remote Relay is registered again, then removed from the storage when it is acknowledged
+#[ockam_macros::test]
+async fn test5(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(RelayMessagesSqlxDatabase::create().await?);
+    let options = RelayServiceOptions::new()
+        .store_and_forward(StoreAndForwardOptions::new(repository.clone()));
+    RelayService::create(ctx, "static_forwarding_service", options)?;
+
+    ctx.start_worker("echoer", Echoer)?;
+
+    let remote_info =
+        RemoteRelay::create_static(ctx, route![], "alias", RemoteRelayOptions::new()).await?;
+    let relay_name = remote_info.remote_address().to_string();
+
+    let resp = ctx
+        .send_and_receive::<String>(route![relay_name.clone(), "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(resp, "Hello");
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(repository.get_messages(&relay_name).await?.is_empty());
+
+    // the registered node is not reachable anymore
+    ctx.stop_address(remote_info.worker_address())?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    ctx.send(
+        route![relay_name.clone(), "echoer"],
+        "Hello again".to_string(),
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(repository.get_messages(&relay_name).await?.len(), 1);
+
+    // the stored message is delivered when the relay is registered again
+    RemoteRelay::create_static(ctx, route![], "alias", RemoteRelayOptions::new()).await?;
+    let resp = ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_secs(5)),
+        )
+        .await?;
+    assert_eq!(resp.into_body()?, "Hello again");
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(repository.get_messages(&relay_name).await?.is_empty());
+    Ok(())
+}
+
+// Node creates a store-and-forward Relay service and a static Remote Relay.
+// A stored message which is not delivered by the relay is not forwarded to the Echoer
+#[ockam_macros::test]
+async fn test6(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(RelayMessagesSqlxDatabase::create().await?);
+    let options =
+        RelayServiceOptions::new().store_and_forward(StoreAndForwardOptions::new(repository));
+    RelayService::create(ctx, "static_forwarding_service", options)?;
+
+    ctx.start_worker("echoer", Echoer)?;
+
+    let remote_info =
+        RemoteRelay::create_static(ctx, route![], "alias", RemoteRelayOptions::new()).await?;
+
+    let delivery = RelayDelivery {
+        sequence: 1,
+        onward_route: route!["echoer"],
+        return_route: route![ctx.primary_address().clone()],
+        payload: "Hello".to_string().encode()?,
+    };
+    ctx.forward(
+        LocalMessage::new()
+            .with_onward_route(route![
+                remote_info.worker_address().clone(),
+                RELAY_DELIVERY_ADDRESS
+            ])
+            .with_return_route(route![ctx.primary_address().clone()])
+            .with_payload(delivery.encode()?),
+    )
+    .await?;
+
+    let resp = ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(resp.is_err());
+    Ok(())
+}
+
+// Node creates a store-and-forward Relay service and two static Remote Relays.
+// The messages are dropped once the payloads stored for all the relays reach the global limit
+#[ockam_macros::test]
+async fn test_store_and_forward_total_limit(ctx: &mut Context) -> Result<()> {
+    let repository = Arc::new(RelayMessagesSqlxDatabase::create().await?);
+    let options = RelayServiceOptions::new().store_and_forward(
+        StoreAndForwardOptions::new(repository.clone()).with_max_total_bytes(1000),
+    );
+    RelayService::create(ctx, "static_forwarding_service", options)?;
+
+    let mut relay_names = vec![];
+    for alias in ["alias1", "alias2"] {
+        let remote_info =
+            RemoteRelay::create_static(ctx, route![], alias, RemoteRelayOptions::new()).await?;
+        // the registered node is not reachable anymore
+        ctx.stop_address(remote_info.worker_address())?;
+        relay_names.push(remote_info.remote_address().to_string());
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for relay_name in &relay_names {
+        ctx.send(route![relay_name.clone(), "echoer"], "x".repeat(600))
+            .await?;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // only one of the messages is stored
+    let mut stored = 0;
+    for relay_name in &relay_names {
+        stored += repository.get_messages(relay_name).await?.len();
+    }
+    assert_eq!(stored, 1);
+    Ok(())
+}
+
+// Node creates a Relay service with a registration policy, a reserved relay name pattern and
+// an audit log. Clients register relays through secure channels, or without authentication
+#[ockam_macros::test]
+async fn test7(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+    let authority = identities_creation.create_identity().await?;
+    let cloud = identities_creation.create_identity().await?;
+
+    let listener_options = SecureChannelListenerOptions::new();
+    let audit = Arc::new(RelayAuditSqlxDatabase::create().await?);
+    let options = RelayServiceOptions::new()
+        .service_as_consumer(&listener_options.spawner_flow_control_id())
+        .relay_as_consumer(&listener_options.spawner_flow_control_id())
+        .authority(
+            authority.clone(),
+            secure_channels.identities().identities_attributes(),
+        )
+        .registration_policy(PolicyExpression::from_str(
+            "(matches? subject.relay relay.name)",
+        )?)
+        .reserve(
+            "prod-*".parse()?,
+            PolicyExpression::from_str("(= subject.env \"prod\")")?,
+        )
+        .audit(RelayAuditOptions::new(audit.clone()));
+    RelayService::create(ctx, "forwarding_service", options)?;
+    secure_channels.create_secure_channel_listener(
+        ctx,
+        &cloud,
+        "cloud_listener",
+        listener_options,
+    )?;
+
+    // the default registration policy allows the relay names given by the attributes
+    let (alice, alice_channel) =
+        create_client(ctx, &secure_channels, &authority, ("relay", "db")).await?;
+    assert!(register(ctx, alice_channel.clone(), "db").await?);
+    assert!(!register(ctx, alice_channel, "web").await?);
+
+    // an attribute can reserve a prefix, for names shorter than the maximum length
+    let (_dave, dave_channel) =
+        create_client(ctx, &secure_channels, &authority, ("relay", "dev-*")).await?;
+    assert!(register(ctx, dave_channel.clone(), "dev-api").await?);
+    let long_name = format!("dev-{}", "x".repeat(MAX_RELAY_NAME_LENGTH));
+    assert!(!register(ctx, dave_channel, &long_name).await?);
+    assert!(audit.get_events(Some(&long_name)).await?.is_empty());
+
+    // the reserved relay names are only allowed by their own policy
+    let (bob, bob_channel) =
+        create_client(ctx, &secure_channels, &authority, ("relay", "prod-db")).await?;
+    assert!(!register(ctx, bob_channel, "prod-db").await?);
+    let (carol, carol_channel) =
+        create_client(ctx, &secure_channels, &authority, ("env", "prod")).await?;
+    assert!(register(ctx, carol_channel, "prod-db").await?);
+
+    // the registrations are denied without authentication, and not recorded
+    assert!(!register(ctx, route![], "prod-web").await?);
+
+    let events = audit.get_events(Some("db")).await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind(), RelayAuditEventKind::Claimed);
+    assert_eq!(events[0].identifier(), Some(&alice));
+
+    let events = audit.get_events(Some("web")).await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind(), RelayAuditEventKind::Denied);
+    assert_eq!(events[0].identifier(), Some(&alice));
+
+    let events = audit.get_events(Some("prod-db")).await?;
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .any(|e| e.kind() == RelayAuditEventKind::Denied && e.identifier() == Some(&bob)));
+    assert!(events
+        .iter()
+        .any(|e| e.kind() == RelayAuditEventKind::Claimed && e.identifier() == Some(&carol)));
+
+    assert!(audit.get_events(Some("prod-web")).await?.is_empty());
+    Ok(())
+}
+
+/// Create an identity with an attribute attested by the authority,
+/// and a secure channel to the cloud listener
+async fn create_client(
+    ctx: &Context,
+    secure_channels: &SecureChannels,
+    authority: &Identifier,
+    attribute: (&str, &str),
+) -> Result<(Identifier, Route)> {
+    let identities = secure_channels.identities();
+    let client = identities.identities_creation().create_identity().await?;
+    identities
+        .identities_attributes()
+        .put_attributes(
+            &client,
+            AttributesEntry::new(
+                BTreeMap::from([(
+                    attribute.0.as_bytes().to_vec(),
+                    attribute.1.as_bytes().to_vec(),
+                )]),
+                now()?,
+                None,
+                Some(authority.clone()),
+            ),
+        )
+        .await?;
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client,
+            route!["cloud_listener"],
+            SecureChannelOptions::new(),
+        )
+        .await?;
+    // the registration replies are received by the test context
+    ctx.flow_controls()
+        .add_consumer(ctx.primary_address(), channel.flow_control_id());
+    Ok((client, route![channel]))
+}
+
+/// Request a relay registration and return true if the relay was created
+async fn register(ctx: &mut Context, route: Route, relay_name: &str) -> Result<bool> {
+    ctx.send(route + "forwarding_service", relay_name.to_string())
+        .await?;
+    let resp = ctx
+        .receive_extended::<String>(
+            MessageReceiveOptions::new().with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    Ok(resp.is_ok())
+}
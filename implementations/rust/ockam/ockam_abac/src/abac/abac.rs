@@ -9,9 +9,10 @@ use ockam_core::{RelayMessage, SecureChannelMetadata};
 use ockam_core::{Result, SecureChannelLocalInfo};
 
 use crate::expr::str;
-use crate::{eval, Env, Expr};
+use crate::{eval_with_rate_limits, explain, Env, Expr, PolicyExplanation, RateLimits};
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::format;
-use ockam_core::compat::string::ToString;
+use ockam_core::compat::string::{String, ToString};
 use ockam_identity::{Identifier, IdentitiesAttributes};
 use ockam_node::Context;
 use tracing::{debug, warn};
@@ -25,6 +26,9 @@ pub const ABAC_HAS_CREDENTIAL_KEY: &str = "has_credential";
 /// Key we use to check Identifier
 pub const ABAC_IDENTIFIER_KEY: &str = "identifier";
 
+/// Key we use to check the expiration time of the subject credential, in seconds since the epoch
+pub const ABAC_CREDENTIAL_EXPIRES_AT_KEY: &str = "credential_expires_at";
+
 /// This AccessControl uses a storage for authenticated attributes in order
 /// to verify if a policy expression is valid
 /// A similar access control policy is available as [`crate::policy::PolicyAccessControl`] where
@@ -34,6 +38,7 @@ pub struct Abac {
     identities_attributes: Arc<IdentitiesAttributes>,
     authority: Option<Identifier>,
     environment: Env,
+    rate_limits: RateLimits,
 }
 
 /// Debug implementation printing out the policy expression only
@@ -54,8 +59,15 @@ impl Abac {
             identities_attributes,
             authority,
             environment,
+            rate_limits: RateLimits::default(),
         }
     }
+
+    /// Add attributes to the environment used to evaluate policy expressions.
+    /// They replace the existing attributes having the same names.
+    pub fn extend_environment(&mut self, environment: Env) {
+        self.environment.merge_right(environment)
+    }
 }
 
 impl Abac {
@@ -98,6 +110,7 @@ impl Abac {
         Self::is_identity_authorized_static(
             self.identities_attributes.clone(),
             &self.environment,
+            &self.rate_limits,
             self.authority.as_ref(),
             identifier,
             expression,
@@ -105,14 +118,124 @@ impl Abac {
         .await
     }
 
-    /// Returns true if the identity is authorized
+    /// Returns true if the identity is authorized.
+    /// The `rate-limit` events of the expression are counted with the given counters
     pub async fn is_identity_authorized_static(
         identities_attributes: Arc<IdentitiesAttributes>,
         environment: &Env,
+        rate_limits: &RateLimits,
         authority: Option<&Identifier>,
         identifier: &Identifier,
         expression: &Expr,
     ) -> Result<bool> {
+        let environment = Self::create_environment(
+            identities_attributes,
+            environment,
+            authority,
+            identifier,
+            expression,
+        )
+        .await?;
+
+        // Finally, evaluate the expression and return the result:
+        match eval_with_rate_limits(expression, &environment, rate_limits) {
+            Ok(Expr::Bool(b)) => {
+                debug! {
+                    policy        = %expression,
+                    id            = %identifier,
+                    is_authorized = %b,
+                    "policy evaluated"
+                }
+                Ok(b)
+            }
+            Ok(x) => {
+                warn! {
+                    policy = %expression,
+                    id     = %identifier,
+                    expr   = %x,
+                    "evaluation did not yield a boolean result"
+                }
+                Ok(false)
+            }
+            Err(e) => {
+                warn! {
+                    policy = %expression,
+                    id     = %identifier,
+                    err    = %e,
+                    env    = %environment,
+                    "policy evaluation failed"
+                }
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl Abac {
+    /// Evaluate a policy expression for an identity and explain how the decision was made.
+    ///
+    /// Additional subject attributes can be provided to simulate the presentation of a credential.
+    /// In that case they take precedence over the attributes already known for that identity.
+    pub async fn explain(
+        &self,
+        identifier: &Identifier,
+        attributes: &BTreeMap<String, String>,
+        expression: &Expr,
+    ) -> Result<PolicyExplanation> {
+        Self::explain_static(
+            self.identities_attributes.clone(),
+            &self.environment,
+            &self.rate_limits,
+            self.authority.as_ref(),
+            identifier,
+            attributes,
+            expression,
+        )
+        .await
+    }
+
+    /// Evaluate a policy expression for an identity and explain how the decision was made.
+    /// The `rate-limit` events of the expression are checked with the given counters
+    pub async fn explain_static(
+        identities_attributes: Arc<IdentitiesAttributes>,
+        environment: &Env,
+        rate_limits: &RateLimits,
+        authority: Option<&Identifier>,
+        identifier: &Identifier,
+        attributes: &BTreeMap<String, String>,
+        expression: &Expr,
+    ) -> Result<PolicyExplanation> {
+        let mut environment = Self::create_environment(
+            identities_attributes,
+            environment,
+            authority,
+            identifier,
+            expression,
+        )
+        .await?;
+
+        if !attributes.is_empty() {
+            environment.put(
+                subject_has_credential_attribute().to_string(),
+                Expr::CONST_TRUE,
+            );
+            for (key, value) in attributes {
+                environment.put(format!("{}.{key}", SUBJECT_KEY), str(value.clone()));
+            }
+        }
+
+        Ok(explain(expression, &environment, rate_limits))
+    }
+
+    /// Create the environment used to evaluate a policy expression for a given identity:
+    /// the initial environment is augmented with the identity attributes attested by the authority
+    async fn create_environment(
+        identities_attributes: Arc<IdentitiesAttributes>,
+        environment: &Env,
+        authority: Option<&Identifier>,
+        identifier: &Identifier,
+        expression: &Expr,
+    ) -> Result<Env> {
         let mut environment = environment.clone();
 
         // add the identifier itself as a subject parameter
@@ -179,6 +302,15 @@ impl Abac {
                             }
                         }
                     }
+
+                    // the expiration time comes from the credential itself
+                    // and can't be replaced by one of its attributes
+                    if let Some(expires_at) = attrs.expires_at() {
+                        environment.put(
+                            subject_credential_expires_at_attribute().to_string(),
+                            Expr::Int(*expires_at as i64),
+                        );
+                    }
                 }
                 None => {
                     environment.put(
@@ -189,37 +321,7 @@ impl Abac {
             }
         }
 
-        // Finally, evaluate the expression and return the result:
-        match eval(expression, &environment) {
-            Ok(Expr::Bool(b)) => {
-                debug! {
-                    policy        = %expression,
-                    id            = %identifier,
-                    is_authorized = %b,
-                    "policy evaluated"
-                }
-                Ok(b)
-            }
-            Ok(x) => {
-                warn! {
-                    policy = %expression,
-                    id     = %identifier,
-                    expr   = %x,
-                    "evaluation did not yield a boolean result"
-                }
-                Ok(false)
-            }
-            Err(e) => {
-                warn! {
-                    policy = %expression,
-                    id     = %identifier,
-                    err    = %e,
-                    env    = %environment,
-                    "policy evaluation failed"
-                }
-                Ok(false)
-            }
-        }
+        Ok(environment)
     }
 }
 
@@ -241,3 +343,59 @@ pub fn subject_has_credential_attribute() -> Expr {
 pub fn subject_identifier_attribute() -> Expr {
     Expr::Ident(format!("{}.{}", SUBJECT_KEY, ABAC_IDENTIFIER_KEY))
 }
+
+/// Identifier for the subject 'credential_expires_at' attribute
+pub fn subject_credential_expires_at_attribute() -> Expr {
+    Expr::Ident(format!(
+        "{}.{}",
+        SUBJECT_KEY, ABAC_CREDENTIAL_EXPIRES_AT_KEY
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::int;
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_identity::utils::now;
+    use ockam_identity::{identities, AttributesEntry, TimestampInSeconds};
+
+    #[tokio::test]
+    async fn test_credential_expiration_time() -> Result<()> {
+        let identities = identities().await?;
+        let authority = identities.identities_creation().create_identity().await?;
+        let subject = identities.identities_creation().create_identity().await?;
+
+        let expires_at = *now()? + 3600;
+        let mut attributes = BTreeMap::new();
+        attributes.insert(
+            ABAC_CREDENTIAL_EXPIRES_AT_KEY.as_bytes().to_vec(),
+            b"0".to_vec(),
+        );
+        identities
+            .identities_attributes()
+            .put_attributes(
+                &subject,
+                AttributesEntry::new(
+                    attributes,
+                    now()?,
+                    Some(TimestampInSeconds(expires_at)),
+                    Some(authority.clone()),
+                ),
+            )
+            .await?;
+
+        let environment = Abac::create_environment(
+            identities.identities_attributes(),
+            &Env::new(),
+            Some(&authority),
+            &subject,
+            &Expr::CONST_TRUE,
+        )
+        .await?;
+        let credential_expires_at =
+            environment.get(&subject_credential_expires_at_attribute().to_string());
+        assert_eq!(credential_expires_at.unwrap(), &int(expires_at as i64));
+        Ok(())
+    }
+}
@@ -2,12 +2,58 @@ use core::cmp::Ordering;
 
 use crate::env::Env;
 use crate::error::EvalError;
+use crate::explanation::EvalStep;
 use crate::expr::{unit, Expr};
+use crate::rate_limit::RateLimits;
+use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
+use ockam_core::compat::time::now;
 use ockam_core::compat::vec::Vec;
 
-#[rustfmt::skip]
+/// Identifier which can be bound in the environment to fix the value returned by `(now)`.
+/// When it is not bound, `(now)` returns the current system time.
+pub const NOW_KEY: &str = "now";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Evaluate an expression in a given environment.
+/// The events counted by `rate-limit` are not kept after the evaluation,
+/// use [`eval_with_rate_limits`] to limit the events over several evaluations.
 pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
+    eval_impl(expr, env, &RateLimits::default(), None)
+}
+
+/// Evaluate an expression in a given environment, counting the `rate-limit` events
+/// with the given counters
+pub fn eval_with_rate_limits(
+    expr: &Expr,
+    env: &Env,
+    rate_limits: &RateLimits,
+) -> Result<Expr, EvalError> {
+    eval_impl(expr, env, rate_limits, None)
+}
+
+/// Evaluate an expression in a given environment and return the value
+/// of each identifier and sub-expression, in evaluation order.
+/// If the evaluation fails, the steps evaluated before the failure are still returned.
+/// The `rate-limit` events are checked with the given counters but not recorded.
+pub fn eval_with_trace(
+    expr: &Expr,
+    env: &Env,
+    rate_limits: &RateLimits,
+) -> (Result<Expr, EvalError>, Vec<EvalStep>) {
+    let mut steps = Vec::new();
+    let result = eval_impl(expr, env, rate_limits, Some(&mut steps));
+    (result, steps)
+}
+
+#[rustfmt::skip]
+fn eval_impl(
+    expr: &Expr,
+    env: &Env,
+    rate_limits: &RateLimits,
+    mut trace: Option<&mut Vec<EvalStep>>,
+) -> Result<Expr, EvalError> {
     /// A stack operation.
     ///
     /// Each operation uses the arguments stack as input. The number of
@@ -23,7 +69,17 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
         Gt(usize),
         Lt(usize),
         Member,
+        Matches,
         Seq(usize),
+        Trace(&'a Expr),
+        Add(usize),
+        Sub(usize),
+        ToInt,
+        Duration(i64),
+        TimeOfDay(usize),
+        DayOfWeek(usize),
+        Clock,
+        RateLimit,
     }
 
     // Control stack.
@@ -36,11 +92,22 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
 
     while let Some(x) = ctrl.pop() {
         match x {
-            Op::Eval(Expr::Ident(id)) => ctrl.push(Op::Eval(env.get(id)?)),
-            Op::Eval(Expr::List(xs))  => match &xs[..] {
+            Op::Eval(e @ Expr::Ident(id)) => {
+                let value = env.get(id)?;
+                if trace.is_some() {
+                    ctrl.push(Op::Trace(e))
+                }
+                ctrl.push(Op::Eval(value))
+            }
+            Op::Eval(e @ Expr::List(xs)) => match &xs[..] {
                 []                    => args.push(unit()),
                 [Expr::Ident(id), ..] => {
                     let nargs = xs.len() - 1; // number of arguments
+                    // The trace operation is executed once the whole expression
+                    // has been evaluated and its value is on top of the arguments stack.
+                    if trace.is_some() {
+                        ctrl.push(Op::Trace(e))
+                    }
                     match id.as_str() {
                         "and" => {
                             // 'and' evaluates its arguments lazily. As soon as a
@@ -132,6 +199,13 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             }
                             ctrl.push(Op::Member)
                         }
+                        "matches?" => {
+                            if nargs != 2 {
+                                let msg = "'matches?' requires a pattern and a string";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Matches)
+                        }
                         "exists?" => {
                             let mut b = true;
                             for x in &xs[1 ..] {
@@ -149,6 +223,78 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             args.push(Expr::Bool(b));
                             continue
                         }
+                        "now" => {
+                            if nargs != 0 {
+                                return Err(EvalError::malformed("'now' does not take arguments"))
+                            }
+                            if env.contains(NOW_KEY) {
+                                ctrl.push(Op::Eval(env.get(NOW_KEY)?))
+                            } else {
+                                let now = now().map_err(|e| EvalError::malformed(e.to_string()))?;
+                                args.push(Expr::Int(now as i64))
+                            }
+                            continue
+                        }
+                        "+" => {
+                            if nargs < 2 {
+                                let msg = "'+' requires at least two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Add(nargs))
+                        }
+                        "-" => {
+                            if nargs < 2 {
+                                let msg = "'-' requires at least two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Sub(nargs))
+                        }
+                        "int" => {
+                            if nargs != 1 {
+                                return Err(EvalError::malformed("'int' requires one argument"))
+                            }
+                            ctrl.push(Op::ToInt)
+                        }
+                        "seconds" | "minutes" | "hours" | "days" => {
+                            if nargs != 1 {
+                                let msg = format!("'{id}' requires one argument");
+                                return Err(EvalError::malformed(msg))
+                            }
+                            let unit = match id.as_str() {
+                                "seconds" => 1,
+                                "minutes" => 60,
+                                "hours"   => 3600,
+                                _         => SECONDS_PER_DAY,
+                            };
+                            ctrl.push(Op::Duration(unit))
+                        }
+                        "time-of-day" => {
+                            if !(1 ..= 2).contains(&nargs) {
+                                let msg = "'time-of-day' requires a timestamp and an optional UTC offset";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::TimeOfDay(nargs))
+                        }
+                        "day-of-week" => {
+                            if !(1 ..= 2).contains(&nargs) {
+                                let msg = "'day-of-week' requires a timestamp and an optional UTC offset";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::DayOfWeek(nargs))
+                        }
+                        "clock" => {
+                            if nargs != 1 {
+                                return Err(EvalError::malformed("'clock' requires one argument"))
+                            }
+                            ctrl.push(Op::Clock)
+                        }
+                        "rate-limit" => {
+                            if nargs != 3 {
+                                let msg = "'rate-limit' requires a key, a count and a window";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::RateLimit)
+                        }
                         _  => return Err(EvalError::Unknown(id.to_string()))
                     }
                     for x in xs[1 ..].iter().rev() {
@@ -255,10 +401,110 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                     }
                 }
             }
+            Op::Matches => {
+                let value = match pop(&mut args) {
+                    Expr::Str(s) => s,
+                    other => {
+                        let msg = "'matches?' expects a string as second argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                };
+                match pop(&mut args) {
+                    Expr::Str(pattern) => args.push(Expr::Bool(matches_pattern(&pattern, &value))),
+                    other => {
+                        let msg = "'matches?' expects a string pattern as first argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
             Op::Seq(n) => {
                 let s = args.split_off(args.len() - n);
                 args.push(Expr::Seq(s))
             }
+            Op::Trace(e) => {
+                if let (Some(steps), Some(value)) = (trace.as_mut(), args.last()) {
+                    steps.push(EvalStep::new(e.clone(), value.clone()))
+                }
+            }
+            Op::Add(n) => eval_arithmetic(n, &mut args, "'+'", i64::checked_add)?,
+            Op::Sub(n) => eval_arithmetic(n, &mut args, "'-'", i64::checked_sub)?,
+            Op::ToInt => {
+                match pop(&mut args) {
+                    Expr::Int(i) => args.push(Expr::Int(i)),
+                    Expr::Str(s) => match s.trim().parse::<i64>() {
+                        Ok(i) => args.push(Expr::Int(i)),
+                        Err(_) => {
+                            let msg = "'int' expects a string representing an integer";
+                            return Err(EvalError::InvalidType(Expr::Str(s), msg))
+                        }
+                    }
+                    other => {
+                        let msg = "'int' expects an integer or a string argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::Duration(unit) => {
+                match pop(&mut args) {
+                    Expr::Int(i) => match i.checked_mul(unit) {
+                        Some(d) => args.push(Expr::Int(d)),
+                        None => return Err(EvalError::malformed("duration overflow"))
+                    }
+                    other => {
+                        let msg = "durations expect an integer argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::TimeOfDay(n) => {
+                let t = pop_timestamp(n, &mut args, "'time-of-day'")?;
+                args.push(Expr::Int(t.rem_euclid(SECONDS_PER_DAY)))
+            }
+            Op::DayOfWeek(n) => {
+                let t = pop_timestamp(n, &mut args, "'day-of-week'")?;
+                // 1970-01-01 was a Thursday, the 4th day of the ISO week
+                let days = t.div_euclid(SECONDS_PER_DAY);
+                args.push(Expr::Int((days + 3).rem_euclid(7) + 1))
+            }
+            Op::Clock => {
+                match pop(&mut args) {
+                    Expr::Str(s) => match parse_clock(&s) {
+                        Some(t) => args.push(Expr::Int(t)),
+                        None => {
+                            let msg = "'clock' expects a time formatted as HH:MM or HH:MM:SS";
+                            return Err(EvalError::InvalidType(Expr::Str(s), msg))
+                        }
+                    }
+                    other => {
+                        let msg = "'clock' expects a string argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::RateLimit => {
+                let window = match pop(&mut args) {
+                    Expr::Int(w) if w > 0 => w,
+                    other => {
+                        let msg = "'rate-limit' expects a positive window duration";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                };
+                let max = match pop(&mut args) {
+                    Expr::Int(m) if m >= 0 => m,
+                    other => {
+                        let msg = "'rate-limit' expects a non-negative maximum number of events";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                };
+                let key = match pop(&mut args) {
+                    Expr::Str(s) => s,
+                    other      => other.to_string(),
+                };
+                // Explaining a policy must not consume the rate limit
+                let record = trace.is_none();
+                let allowed = rate_limits.check(&key, max, window, current_time(env)?, record);
+                args.push(Expr::Bool(allowed))
+            }
         }
     }
 
@@ -275,6 +521,31 @@ fn pop<T>(s: &mut Vec<T>) -> T {
     s.pop().expect("stack is not empty")
 }
 
+/// Return true if a value is equal to a pattern or, when the pattern ends with `*`,
+/// if the value starts with the rest of the pattern.
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+/// Return the time bound to `now` in the environment, or the current system time.
+fn current_time(env: &Env) -> Result<i64, EvalError> {
+    if env.contains(NOW_KEY) {
+        match env.get(NOW_KEY)? {
+            Expr::Int(t) => Ok(*t),
+            other => Err(EvalError::InvalidType(
+                other.clone(),
+                "a timestamp must be an integer",
+            )),
+        }
+    } else {
+        let now = now().map_err(|e| EvalError::malformed(e.to_string()))?;
+        Ok(now as i64)
+    }
+}
+
 /// Evaluate a predicate against the `n` topmost arguments.
 fn eval_predicate<F>(n: usize, args: &mut Vec<Expr>, f: F) -> Result<(), EvalError>
 where
@@ -293,11 +564,92 @@ where
     Ok(())
 }
 
+/// Fold the `n` topmost integer arguments with an arithmetic operation.
+fn eval_arithmetic<F>(
+    n: usize,
+    args: &mut Vec<Expr>,
+    op: &'static str,
+    f: F,
+) -> Result<(), EvalError>
+where
+    F: Fn(i64, i64) -> Option<i64>,
+{
+    let xs = args.split_off(args.len() - n);
+    let mut result = None;
+    for x in xs {
+        match (result, x) {
+            (None, Expr::Int(i)) => result = Some(i),
+            (Some(r), Expr::Int(i)) => match f(r, i) {
+                Some(r) => result = Some(r),
+                None => return Err(EvalError::malformed(format!("{op} overflow"))),
+            },
+            (_, other) => {
+                return Err(EvalError::InvalidType(
+                    other,
+                    "arithmetic operations expect integer arguments",
+                ))
+            }
+        }
+    }
+    args.push(Expr::Int(result.unwrap_or_default()));
+    Ok(())
+}
+
+/// Pop a timestamp and an optional UTC offset (in seconds) off the stack,
+/// and return the timestamp shifted by the offset.
+fn pop_timestamp(n: usize, args: &mut Vec<Expr>, op: &'static str) -> Result<i64, EvalError> {
+    let offset = if n == 2 {
+        match pop(args) {
+            Expr::Int(i) => i,
+            other => {
+                return Err(EvalError::InvalidType(
+                    other,
+                    "the UTC offset must be an integer",
+                ))
+            }
+        }
+    } else {
+        0
+    };
+    match pop(args) {
+        Expr::Int(t) => t
+            .checked_add(offset)
+            .ok_or_else(|| EvalError::malformed(format!("{op} overflow"))),
+        other => Err(EvalError::InvalidType(
+            other,
+            "a timestamp must be an integer",
+        )),
+    }
+}
+
+/// Parse a time of the day formatted as HH:MM or HH:MM:SS
+/// and return the number of seconds since midnight.
+fn parse_clock(s: &str) -> Option<i64> {
+    let mut parts = s.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds: i64 = match parts.next() {
+        Some(seconds) => seconds.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some()
+        || !(0..24).contains(&hours)
+        || !(0..60).contains(&minutes)
+        || !(0..60).contains(&seconds)
+    {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::expr::str;
     use crate::{
-        eval, subject_has_credential_attribute, subject_has_credential_policy_expression, Env, Expr,
+        eval, eval_with_rate_limits, eval_with_trace, parse, subject_has_credential_attribute,
+        subject_has_credential_policy_expression, Env, Expr, PolicyExpression, RateLimits, NOW_KEY,
     };
+    use ockam_core::compat::string::ToString;
 
     #[test]
     fn test() {
@@ -324,4 +676,232 @@ mod tests {
         let res = eval(&check_credential_expression, &environment).unwrap();
         matches!(res, Expr::Bool(true));
     }
+
+    // Monday 2024-01-15 10:30:00 UTC
+    const MONDAY_MORNING: i64 = 1705314600;
+
+    #[test]
+    fn test_time_operators() {
+        let mut environment = Env::new();
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING));
+
+        assert_eq!(evaluate("(now)", &environment), Expr::Int(MONDAY_MORNING));
+        assert_eq!(
+            evaluate("(time-of-day (now))", &environment),
+            Expr::Int(10 * 3600 + 30 * 60)
+        );
+        assert_eq!(evaluate("(day-of-week (now))", &environment), Expr::Int(1));
+        // with a UTC offset of -11 hours it is still Sunday
+        assert_eq!(
+            evaluate("(day-of-week (now) (- 0 (hours 11)))", &environment),
+            Expr::Int(7)
+        );
+        assert_eq!(
+            evaluate("(clock \"17:45\")", &environment),
+            Expr::Int(63900)
+        );
+        assert_eq!(
+            evaluate("(clock \"08:00:30\")", &environment),
+            Expr::Int(28830)
+        );
+        assert_eq!(
+            evaluate("(+ (days 1) (minutes 2) 3)", &environment),
+            Expr::Int(86523)
+        );
+        assert_eq!(evaluate("(- 10 (seconds 3) 2)", &environment), Expr::Int(5));
+        assert_eq!(evaluate("(int \"42\")", &environment), Expr::Int(42));
+
+        let mut environment = Env::new();
+        let now = evaluate("(now)", &environment);
+        assert!(matches!(now, Expr::Int(t) if t > MONDAY_MORNING));
+        environment.put("now", Expr::Str("not a timestamp".into()));
+        assert!(eval(
+            &parse("(time-of-day (now))").unwrap().unwrap(),
+            &environment
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_time_window_policy() {
+        let policy = parse(
+            r#"(and (= subject.role "oncall")
+                    (< (clock "09:00") (time-of-day (now)) (clock "17:00"))
+                    (< (day-of-week (now)) 6)
+                    (< (now) (- subject.credential_expires_at (hours 1))))"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let mut environment = Env::new();
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING));
+        environment.put("subject.role", str("oncall"));
+        environment.put(
+            "subject.credential_expires_at",
+            Expr::Int(MONDAY_MORNING + 7200),
+        );
+        assert!(eval(&policy, &environment).unwrap().is_true());
+
+        // the credential expires in less than one hour
+        environment.put(
+            "subject.credential_expires_at",
+            Expr::Int(MONDAY_MORNING + 1800),
+        );
+        assert!(eval(&policy, &environment).unwrap().is_false());
+
+        // outside of business hours
+        environment.put(
+            "subject.credential_expires_at",
+            Expr::Int(MONDAY_MORNING + 86400),
+        );
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING + 8 * 3600));
+        assert!(eval(&policy, &environment).unwrap().is_false());
+
+        // on a Saturday
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING - 2 * 86400));
+        assert!(eval(&policy, &environment).unwrap().is_false());
+    }
+
+    #[test]
+    fn test_time_operators_errors() {
+        let environment = Env::new();
+        for expression in [
+            "(now 1)",
+            "(int \"abc\")",
+            "(int true)",
+            "(clock \"25:00\")",
+            "(clock \"10:00:00:00\")",
+            "(hours \"1\")",
+            "(+ 1)",
+            "(+ 1 \"2\")",
+            "(- 9223372036854775807 -1)",
+            "(time-of-day \"10:00\")",
+            "(day-of-week 1 2 3)",
+        ] {
+            let expression = parse(expression).unwrap().unwrap();
+            assert!(
+                eval(&expression, &environment).is_err(),
+                "{expression} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_operators_cbor() {
+        let expression =
+            parse("(< (clock \"09:00\") (time-of-day (now) (hours 2)) (clock \"17:00\"))")
+                .unwrap()
+                .unwrap();
+        let policy_expression = PolicyExpression::FullExpression(expression);
+        let bytes = minicbor::to_vec(&policy_expression).unwrap();
+        let decoded: PolicyExpression = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded, policy_expression);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let policy = parse(
+            r#"(and (= subject.role "admin")
+                    (rate-limit subject.identifier 2 (minutes 1)))"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        let rate_limits = RateLimits::default();
+        let eval = |policy: &Expr, environment: &Env| {
+            eval_with_rate_limits(policy, environment, &rate_limits)
+        };
+        let mut environment = Env::new();
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING));
+        environment.put("subject.role", str("admin"));
+        environment.put("subject.identifier", str("I1"));
+        assert!(eval(&policy, &environment).unwrap().is_true());
+        assert!(eval(&policy, &environment).unwrap().is_true());
+
+        // explaining the policy does not consume the limit
+        let (result, _) = eval_with_trace(&policy, &environment, &rate_limits);
+        assert!(result.unwrap().is_false());
+        assert!(eval(&policy, &environment).unwrap().is_false());
+
+        // other subjects have their own limit
+        environment.put("subject.identifier", str("I2"));
+        assert!(eval(&policy, &environment).unwrap().is_true());
+
+        // denied requests are not counted when the rate limit is evaluated last
+        environment.put("subject.role", str("guest"));
+        assert!(eval(&policy, &environment).unwrap().is_false());
+        environment.put("subject.role", str("admin"));
+        assert!(eval(&policy, &environment).unwrap().is_true());
+        assert!(eval(&policy, &environment).unwrap().is_false());
+
+        // the limit is restored once the window has passed
+        environment.put("subject.identifier", str("I1"));
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING + 60));
+        assert!(eval(&policy, &environment).unwrap().is_true());
+
+        // the events are only counted by the same rate limits
+        environment.put(NOW_KEY, Expr::Int(MONDAY_MORNING));
+        let other_rate_limits = RateLimits::default();
+        assert!(
+            eval_with_rate_limits(&policy, &environment, &other_rate_limits)
+                .unwrap()
+                .is_true()
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_errors() {
+        let environment = Env::new();
+        for expression in [
+            "(rate-limit \"key\" 10)",
+            "(rate-limit \"key\" \"10\" 60)",
+            "(rate-limit \"key\" -1 60)",
+            "(rate-limit \"key\" 10 0)",
+        ] {
+            let expression = parse(expression).unwrap().unwrap();
+            assert!(
+                eval(&expression, &environment).is_err(),
+                "{expression} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut environment = Env::new();
+        environment.put("relay.name", str("prod-db"));
+        for (pattern, expected) in [
+            ("*", true),
+            ("prod-*", true),
+            ("prod-db", true),
+            ("prod-db*", true),
+            ("prod", false),
+            ("dev-*", false),
+            ("prod-db-1*", false),
+        ] {
+            let expression = format!("(matches? \"{pattern}\" relay.name)");
+            assert_eq!(
+                evaluate(&expression, &environment),
+                Expr::Bool(expected),
+                "{expression}"
+            );
+        }
+
+        for expression in [
+            "(matches? \"*\")",
+            "(matches? 1 relay.name)",
+            "(matches? \"*\" 1)",
+        ] {
+            let expression = parse(expression).unwrap().unwrap();
+            assert!(
+                eval(&expression, &environment).is_err(),
+                "{expression} should fail"
+            );
+        }
+    }
+
+    /// HELPERS
+    fn evaluate(expression: &str, environment: &Env) -> Expr {
+        eval(&parse(expression).unwrap().unwrap(), environment).unwrap()
+    }
 }
@@ -0,0 +1,202 @@
+use core::fmt;
+use minicbor::{CborLen, Decode, Encode};
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::{vec, Vec};
+use serde::Serialize;
+
+use crate::eval::eval_with_trace;
+use crate::rate_limit::RateLimits;
+use crate::{Env, Expr};
+
+/// Value of an identifier or a sub-expression obtained while evaluating a policy expression
+#[derive(Debug, Clone, Encode, Decode, CborLen, Serialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EvalStep {
+    #[n(1)] pub expression: Expr,
+    #[n(2)] pub value: Expr,
+}
+
+impl EvalStep {
+    pub fn new(expression: Expr, value: Expr) -> Self {
+        Self { expression, value }
+    }
+}
+
+/// Full trace of the evaluation of a policy expression:
+///
+///  - the environment used to evaluate the expression
+///  - the value of each identifier and sub-expression, in evaluation order
+///  - the identifiers used in the expression which are not bound in the environment
+///  - the final decision
+#[derive(Debug, Clone, Encode, Decode, CborLen, Serialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyExplanation {
+    #[n(1)] pub expression: Expr,
+    #[n(2)] pub environment: BTreeMap<String, Expr>,
+    #[n(3)] pub steps: Vec<EvalStep>,
+    #[n(4)] pub missing_identifiers: Vec<String>,
+    #[n(5)] pub result: Option<Expr>,
+    #[n(6)] pub error: Option<String>,
+    #[n(7)] pub is_authorized: bool,
+}
+
+/// Evaluate a policy expression and explain how the decision was made.
+/// Access is only granted if the expression evaluates to `true`.
+/// The `rate-limit` events are checked with the given counters but not recorded.
+pub fn explain(
+    expression: &Expr,
+    environment: &Env,
+    rate_limits: &RateLimits,
+) -> PolicyExplanation {
+    let (result, steps) = eval_with_trace(expression, environment, rate_limits);
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let is_authorized = result.as_ref().map(|r| r.is_true()).unwrap_or(false);
+    PolicyExplanation {
+        expression: expression.clone(),
+        environment: environment
+            .entries()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+        steps,
+        missing_identifiers: missing_identifiers(expression, environment),
+        result,
+        error,
+        is_authorized,
+    }
+}
+
+/// Return the identifiers used as arguments in an expression which are not bound in the environment.
+/// The arguments of `exists?` are not reported since they are allowed to be missing.
+fn missing_identifiers(expression: &Expr, environment: &Env) -> Vec<String> {
+    let mut missing = BTreeSet::new();
+    let mut ctrl = vec![expression];
+    while let Some(e) = ctrl.pop() {
+        match e {
+            Expr::Ident(id) => {
+                if !environment.contains(id) {
+                    missing.insert(id.clone());
+                }
+            }
+            Expr::List(xs) => match &xs[..] {
+                [Expr::Ident(op), ..] if op == "exists?" => {}
+                [Expr::Ident(_), args @ ..] => ctrl.extend(args.iter()),
+                _ => ctrl.extend(xs.iter()),
+            },
+            Expr::Seq(xs) => ctrl.extend(xs.iter()),
+            _ => {}
+        }
+    }
+    missing.into_iter().collect()
+}
+
+impl fmt::Display for PolicyExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decision = if self.is_authorized {
+            "allowed"
+        } else {
+            "denied"
+        };
+        writeln!(f, "expression: {}", self.expression)?;
+        writeln!(f, "decision: {decision}")?;
+        if let Some(result) = &self.result {
+            writeln!(f, "result: {result}")?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "error: {error}")?;
+        }
+        if !self.missing_identifiers.is_empty() {
+            writeln!(
+                f,
+                "missing identifiers: {}",
+                self.missing_identifiers.join(", ")
+            )?;
+        }
+        writeln!(f, "steps:")?;
+        for step in &self.steps {
+            writeln!(f, "  {} => {}", step.expression, step.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::str;
+    use crate::parse;
+
+    #[test]
+    fn explain_allowed() {
+        let expression =
+            parse(r#"(and (= subject.role "oncall") (member? subject.team ["a" "b"]))"#)
+                .unwrap()
+                .unwrap();
+        let mut environment = Env::new();
+        environment.put("subject.role", str("oncall"));
+        environment.put("subject.team", str("b"));
+
+        let explanation = explain(&expression, &environment, &RateLimits::default());
+        assert!(explanation.is_authorized);
+        assert_eq!(explanation.result, Some(Expr::Bool(true)));
+        assert_eq!(explanation.error, None);
+        assert!(explanation.missing_identifiers.is_empty());
+        let steps: Vec<String> = explanation
+            .steps
+            .iter()
+            .map(|s| format!("{} => {}", s.expression, s.value))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                r#"subject.role => "oncall""#,
+                r#"(= subject.role "oncall") => true"#,
+                r#"subject.team => "b""#,
+                r#"(member? subject.team ["a" "b"]) => true"#,
+                r#"(and (= subject.role "oncall") (member? subject.team ["a" "b"])) => true"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_denied() {
+        let expression = parse(r#"(or (= subject.role "admin") (= subject.team "ops"))"#)
+            .unwrap()
+            .unwrap();
+        let mut environment = Env::new();
+        environment.put("subject.role", str("oncall"));
+
+        let explanation = explain(&expression, &environment, &RateLimits::default());
+        assert!(!explanation.is_authorized);
+        assert_eq!(explanation.result, None);
+        assert_eq!(
+            explanation.error,
+            Some("unbound identifier: subject.team".to_string())
+        );
+        assert_eq!(explanation.missing_identifiers, vec!["subject.team"]);
+        // the steps evaluated before the error are kept
+        assert_eq!(explanation.steps.len(), 2);
+        assert_eq!(explanation.steps[1].value, Expr::Bool(false));
+
+        // the explanation can be sent over the wire
+        let bytes = minicbor::to_vec(&explanation).unwrap();
+        let decoded: PolicyExplanation = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded, explanation);
+    }
+
+    #[test]
+    fn exists_arguments_are_not_missing() {
+        let expression = parse("(if (exists? subject.role) true false)")
+            .unwrap()
+            .unwrap();
+        let explanation = explain(&expression, &Env::new(), &RateLimits::default());
+        assert!(explanation.missing_identifiers.is_empty());
+        assert_eq!(explanation.result, Some(Expr::Bool(false)));
+        assert_eq!(explanation.error, None);
+    }
+}
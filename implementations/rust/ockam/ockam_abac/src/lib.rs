@@ -10,7 +10,9 @@ extern crate core;
 mod env;
 mod error;
 mod eval;
+mod explanation;
 mod policy;
+mod rate_limit;
 mod types;
 
 #[cfg(feature = "std")]
@@ -28,12 +30,14 @@ pub use abac::*;
 pub use boolean_expr::*;
 pub use env::Env;
 pub use error::{EvalError, ParseError};
-pub use eval::eval;
+pub use eval::{eval, eval_with_rate_limits, eval_with_trace, NOW_KEY};
+pub use explanation::*;
 pub use expr::Expr;
 pub use policy::{
     storage::*, Policies, PolicyAccessControl, ResourcePolicy, ResourceTypePolicy, Resources,
 };
 pub use policy_expr::*;
+pub use rate_limit::{uses_rate_limits, RateLimits};
 pub use resource::{Resource, ResourceType};
 pub use types::{Action, ResourceName, Subject};
 
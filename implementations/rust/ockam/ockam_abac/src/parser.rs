@@ -15,12 +15,34 @@ use wast::lexer::{FloatKind, TokenKind};
 fn ident_pattern() -> &'static Regex {
     static INSTANCE: OnceBox<Regex> = OnceBox::new();
     INSTANCE.get_or_init(|| {
-        Box::new(Regex::new("^[a-zA-Z!$%&*/<=>?~_^][a-zA-Z0-9!$%&*/<=>?~_^.+-@]*$").unwrap())
+        Box::new(Regex::new("^([+-]|[a-zA-Z!$%&*/<=>?~_^][a-zA-Z0-9!$%&*/<=>?~_^.+-@]*)$").unwrap())
     })
 }
 
-pub const OPERATORS: [&str; 10] = [
-    "and", "or", "not", "if", "<", ">", "=", "!=", "member?", "exists?",
+pub const OPERATORS: [&str; 23] = [
+    "and",
+    "or",
+    "not",
+    "if",
+    "<",
+    ">",
+    "=",
+    "!=",
+    "member?",
+    "exists?",
+    "matches?",
+    "now",
+    "+",
+    "-",
+    "int",
+    "seconds",
+    "minutes",
+    "hours",
+    "days",
+    "time-of-day",
+    "day-of-week",
+    "clock",
+    "rate-limit",
 ];
 
 #[rustfmt::skip]
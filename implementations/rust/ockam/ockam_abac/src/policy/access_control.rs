@@ -1,6 +1,6 @@
 use crate::abac::Abac;
 use crate::policy::{IncomingPolicyAccessControl, OutgoingPolicyAccessControl};
-use crate::{Action, Env, Policies, Resource};
+use crate::{uses_rate_limits, Action, Env, Policies, Resource};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use ockam_core::compat::sync::Arc;
@@ -56,6 +56,14 @@ impl PolicyAccessControl {
         }
     }
 
+    /// Return a copy of this access control where the environment also contains
+    /// the given attributes, for example the attributes of a specific connection.
+    pub fn with_additional_environment(&self, environment: Env) -> Self {
+        let mut policy_access_control = self.clone();
+        policy_access_control.abac.extend_environment(environment);
+        policy_access_control
+    }
+
     pub fn create_incoming(&self) -> IncomingPolicyAccessControl {
         IncomingPolicyAccessControl {
             policy_access_control: self.clone(),
@@ -75,6 +83,16 @@ impl PolicyAccessControl {
         })
     }
 
+    /// Return true if the current policy for the resource and action uses the `rate-limit`
+    /// function. In that case every access decision must be evaluated, without a cache
+    pub async fn uses_rate_limits(&self) -> Result<bool> {
+        Ok(self
+            .policies
+            .get_expression_for_resource(&self.resource, &self.action)
+            .await?
+            .is_some_and(|expression| uses_rate_limits(&expression)))
+    }
+
     pub async fn is_identity_authorized(&self, identifier: &Identifier) -> Result<bool> {
         // Load the policy expression for resource and action:
         let expression = if let Some(expr) = self
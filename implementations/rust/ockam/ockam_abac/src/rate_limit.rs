@@ -0,0 +1,236 @@
+use core::fmt::{Debug, Formatter};
+use ockam_core::compat::collections::{BTreeMap, BTreeSet, VecDeque};
+use ockam_core::compat::format;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::vec;
+
+use crate::Expr;
+
+/// Maximum number of rate-limited keys tracked at once.
+/// When this limit is reached, the keys without any event in their window are removed,
+/// and if there is still no room, the events of new keys are rejected.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Counters of the events accepted by the `rate-limit` function of policy expressions.
+///
+/// The counters are shared by the clones of a `RateLimits` value. Each access control
+/// creates its own counters, so that policies evaluated by different access controls
+/// don't consume each other's limits, even when they use the same keys.
+#[derive(Clone, Default)]
+pub struct RateLimits {
+    windows: Arc<Mutex<RateLimitWindows>>,
+}
+
+impl Debug for RateLimits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("RateLimits")
+    }
+}
+
+impl RateLimits {
+    /// Return true if fewer than `max` events were accepted for `key` during the last
+    /// `window` seconds. If `record` is true, an accepted event is counted.
+    ///
+    /// Counters are shared by the expressions using the same key and the same limits.
+    pub(crate) fn check(&self, key: &str, max: i64, window: i64, now: i64, record: bool) -> bool {
+        let key = format!("{max}/{window}/{key}");
+        // the counters stay consistent if a thread panics while holding the lock
+        #[cfg(feature = "std")]
+        let mut windows = self
+            .windows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        #[cfg(not(feature = "std"))]
+        let mut windows = self.windows.lock().unwrap();
+        windows.check(key, max, window, now, record)
+    }
+}
+
+/// Return true if an expression uses the `rate-limit` function.
+/// The access decisions made with such an expression must not be cached, since every
+/// authorized message must be counted
+pub fn uses_rate_limits(expr: &Expr) -> bool {
+    let mut exprs = vec![expr];
+    while let Some(expr) = exprs.pop() {
+        match expr {
+            Expr::List(xs) => {
+                if matches!(xs.first(), Some(Expr::Ident(id)) if id == "rate-limit") {
+                    return true;
+                }
+                exprs.extend(xs)
+            }
+            Expr::Seq(xs) => exprs.extend(xs),
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Sliding windows of accepted events, keyed by rate-limit key
+#[derive(Default)]
+struct RateLimitWindows {
+    windows: BTreeMap<String, RateLimitWindow>,
+    // keys ordered by the time when their last event leaves the window, to evict keys
+    // without scanning all the windows
+    expirations: BTreeSet<(i64, String)>,
+}
+
+struct RateLimitWindow {
+    duration: i64,
+    events: VecDeque<i64>,
+    // time when the last event leaves the window
+    expires_at: i64,
+}
+
+impl RateLimitWindow {
+    fn remove_expired(&mut self, now: i64) {
+        while let Some(t) = self.events.front() {
+            if *t > now.saturating_sub(self.duration) {
+                break;
+            }
+            self.events.pop_front();
+        }
+    }
+}
+
+impl RateLimitWindows {
+    fn check(&mut self, key: String, max: i64, window: i64, now: i64, record: bool) -> bool {
+        if let Some(w) = self.windows.get_mut(&key) {
+            w.remove_expired(now);
+            let accepted = (w.events.len() as i64) < max;
+            if accepted && record {
+                w.events.push_back(now);
+                let expires_at = now.saturating_add(w.duration);
+                let previous = core::mem::replace(&mut w.expires_at, expires_at);
+                self.expirations.remove(&(previous, key.clone()));
+                self.expirations.insert((expires_at, key));
+            }
+            return accepted;
+        }
+
+        if max < 1 {
+            return false;
+        }
+        if self.windows.len() >= MAX_TRACKED_KEYS {
+            self.evict(now);
+            // don't reset the counters of keys which are still limited
+            if self.windows.len() >= MAX_TRACKED_KEYS {
+                return false;
+            }
+        }
+        if !record {
+            return true;
+        }
+        let expires_at = now.saturating_add(window);
+        self.expirations.insert((expires_at, key.clone()));
+        self.windows.insert(
+            key,
+            RateLimitWindow {
+                duration: window,
+                events: VecDeque::from([now]),
+                expires_at,
+            },
+        );
+        true
+    }
+
+    /// Remove the keys without any event in their window
+    fn evict(&mut self, now: i64) {
+        while let Some((expires_at, key)) = self.expirations.first().cloned() {
+            if expires_at > now {
+                break;
+            }
+            self.expirations.pop_first();
+            self.windows.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use ockam_core::compat::string::ToString;
+    use ockam_core::compat::vec::{vec, Vec};
+
+    #[test]
+    fn test_sliding_window() {
+        let mut limits = RateLimitWindows::default();
+        let key = "subject".to_string();
+
+        assert!(limits.check(key.clone(), 2, 60, 1000, true));
+        assert!(limits.check(key.clone(), 2, 60, 1010, true));
+        assert!(!limits.check(key.clone(), 2, 60, 1020, true));
+
+        // the first event leaves the window
+        assert!(limits.check(key.clone(), 2, 60, 1060, true));
+        assert!(!limits.check(key.clone(), 2, 60, 1065, true));
+
+        // checking without recording does not consume the limit
+        assert!(limits.check(key.clone(), 2, 60, 1075, false));
+        assert!(limits.check(key.clone(), 2, 60, 1075, true));
+        assert!(!limits.check(key, 2, 60, 1075, false));
+
+        assert!(!limits.check("other".to_string(), 0, 60, 1000, true));
+    }
+
+    #[test]
+    fn test_uses_rate_limits() {
+        let uses = |s: &str| uses_rate_limits(&parse(s).unwrap().unwrap());
+        assert!(uses("(rate-limit subject.identifier 10 (minutes 1))"));
+        assert!(uses(
+            r#"(and (= subject.role "api") (rate-limit subject.identifier 10 (minutes 1)))"#
+        ));
+        assert!(!uses(r#"(= subject.role "rate-limit")"#));
+        assert!(!uses("(and (= subject.has_credential true) rate-limit)"));
+    }
+
+    #[test]
+    fn test_shared_counters() {
+        let limits = RateLimits::default();
+        assert!(limits.check("subject", 1, 60, 1000, true));
+        assert!(!limits.clone().check("subject", 1, 60, 1000, true));
+
+        // other rate limits have their own counters
+        assert!(RateLimits::default().check("subject", 1, 60, 1000, true));
+    }
+
+    #[test]
+    fn test_maximum_number_of_keys() {
+        let mut limits = RateLimitWindows::default();
+        assert!(limits.check("first".to_string(), 1, 60, 999, true));
+        for i in 1..MAX_TRACKED_KEYS {
+            assert!(limits.check(i.to_string(), 1, 60, 1000, true));
+        }
+
+        // no key is evicted while its events are in their window, new keys are rejected
+        assert!(!limits.check("new".to_string(), 1, 60, 1030, true));
+        assert!(!limits.check("new".to_string(), 1, 60, 1030, false));
+        assert_eq!(limits.windows.len(), MAX_TRACKED_KEYS);
+        assert!(limits.windows.contains_key("first"));
+        assert!(!limits.check("first".to_string(), 1, 60, 1030, true));
+
+        // a key whose events left their window is evicted to make room for a new one
+        assert!(limits.check("new".to_string(), 1, 60, 1059, true));
+        assert!(!limits.windows.contains_key("first"));
+        assert_eq!(limits.windows.len(), MAX_TRACKED_KEYS);
+        assert!(!limits.check("after-new".to_string(), 1, 60, 1059, true));
+
+        // expired keys are evicted to make room for new ones
+        assert!(limits.check("other".to_string(), 1, 60, 1120, true));
+        assert_eq!(limits.windows.len(), 1);
+        assert_eq!(limits.expirations.len(), 1);
+    }
+
+    #[test]
+    fn test_expirations_follow_the_last_event() {
+        let mut limits = RateLimitWindows::default();
+        assert!(limits.check("subject".to_string(), 2, 60, 1000, true));
+        assert!(limits.check("subject".to_string(), 2, 60, 1030, true));
+        assert_eq!(
+            limits.expirations.iter().collect::<Vec<_>>(),
+            vec![&(1090, "subject".to_string())]
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::body::Bytes;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response};
+use hyper_util::rt::TokioIo;
+use ockam_core::async_trait;
+use ockam_core::env::get_env;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_core::HostnamePort;
+use tokio::net::TcpListener;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::acme::AcmeChallenge;
+
+/// Path where the ACME server fetches the HTTP-01 challenges
+const HTTP01_CHALLENGE_PATH: &str = "/.well-known/acme-challenge/";
+
+/// Prefix of the DNS record name where the ACME server fetches the DNS-01 challenges
+const DNS01_RECORD_PREFIX: &str = "_acme-challenge";
+
+/// Command called by the node to publish the DNS-01 challenges
+pub const OCKAM_ACME_DNS01_HOOK: &str = "OCKAM_ACME_DNS01_HOOK";
+
+/// Comma-separated list of the other DNS-01 hook commands which can be requested for an inlet
+pub const OCKAM_ACME_DNS01_HOOK_ALLOW_LIST: &str = "OCKAM_ACME_DNS01_HOOK_ALLOW_LIST";
+
+/// Type of challenge supported by an [`AcmeChallengeSolver`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AcmeChallengeType {
+    Http01,
+    Dns01,
+}
+
+/// A challenge which must be made available to the ACME server
+/// in order to prove the control of a domain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcmeChallengeResponse {
+    /// Domain being validated
+    pub domain: String,
+    /// Token identifying the challenge
+    pub token: String,
+    /// Value expected by the ACME server:
+    ///  - the key authorization for an HTTP-01 challenge
+    ///  - the digest of the key authorization for a DNS-01 challenge
+    pub value: String,
+}
+
+impl AcmeChallengeResponse {
+    /// Name of the DNS TXT record for a DNS-01 challenge
+    pub fn dns_record_name(&self) -> String {
+        format!(
+            "{DNS01_RECORD_PREFIX}.{}",
+            self.domain.trim_start_matches("*.")
+        )
+    }
+}
+
+/// This trait is used to publish the ACME challenges.
+/// It can be implemented to support other ways of publishing them, for example
+/// by calling the API of a DNS provider
+#[async_trait]
+pub trait AcmeChallengeSolver: Send + Sync + 'static {
+    /// Type of challenge published by this solver
+    fn challenge_type(&self) -> AcmeChallengeType;
+
+    /// Make the challenge available to the ACME server
+    async fn present(&self, challenge: &AcmeChallengeResponse) -> Result<()>;
+
+    /// Remove the challenge once it has been validated, or failed to be validated
+    async fn cleanup(&self, challenge: &AcmeChallengeResponse) -> Result<()>;
+}
+
+/// Create the challenge solver corresponding to a challenge configuration
+pub fn make_challenge_solver(challenge: &AcmeChallenge) -> Result<Arc<dyn AcmeChallengeSolver>> {
+    Ok(match challenge {
+        AcmeChallenge::Http01 { listen_address } => {
+            Arc::new(Http01ChallengeSolver::new(listen_address.clone()))
+        }
+        AcmeChallenge::Dns01 { hook } => Arc::new(Dns01CommandChallengeSolver::new(
+            resolve_dns01_hook(hook.as_deref())?,
+        )),
+    })
+}
+
+/// Return the DNS-01 hook command configured on the node with [`OCKAM_ACME_DNS01_HOOK`],
+/// or the requested hook if it is part of [`OCKAM_ACME_DNS01_HOOK_ALLOW_LIST`]
+pub fn resolve_dns01_hook(requested: Option<&str>) -> Result<String> {
+    check_dns01_hook(
+        requested,
+        get_env::<String>(OCKAM_ACME_DNS01_HOOK)?,
+        get_env::<Vec<String>>(OCKAM_ACME_DNS01_HOOK_ALLOW_LIST)?.unwrap_or_default(),
+    )
+}
+
+fn check_dns01_hook(
+    requested: Option<&str>,
+    node_hook: Option<String>,
+    allow_list: Vec<String>,
+) -> Result<String> {
+    match (requested, node_hook) {
+        (None, Some(node_hook)) => Ok(node_hook),
+        (None, None) => Err(Error::new(
+            Origin::Api,
+            Kind::Invalid,
+            format!("no ACME DNS-01 hook is configured on the node with {OCKAM_ACME_DNS01_HOOK}"),
+        )),
+        (Some(requested), node_hook) => {
+            if node_hook.as_deref() == Some(requested)
+                || allow_list.iter().any(|allowed| allowed.trim() == requested)
+            {
+                Ok(requested.to_string())
+            } else {
+                Err(Error::new(
+                    Origin::Api,
+                    Kind::Invalid,
+                    format!(
+                        "the ACME DNS-01 hook {requested} is not allowed by the node, \
+                         see {OCKAM_ACME_DNS01_HOOK_ALLOW_LIST}"
+                    ),
+                ))
+            }
+        }
+    }
+}
+
+/// This solver serves the HTTP-01 challenges with an HTTP server
+/// which is only running while some challenges are pending
+pub struct Http01ChallengeSolver {
+    listen_address: HostnamePort,
+    key_authorizations: Arc<Mutex<HashMap<String, String>>>,
+    server: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Http01ChallengeSolver {
+    /// Create a solver serving the challenges on the given address
+    pub fn new(listen_address: HostnamePort) -> Self {
+        Self {
+            listen_address,
+            key_authorizations: Default::default(),
+            server: Default::default(),
+        }
+    }
+
+    async fn start_server(&self) -> Result<()> {
+        let mut server = self.server.lock().await;
+        if server.is_some() {
+            return Ok(());
+        }
+
+        let listener = TcpListener::bind(self.listen_address.to_string())
+            .await
+            .map_err(|e| Error::new(Origin::Api, Kind::Io, e))?;
+        debug!(
+            "serving the ACME HTTP-01 challenges on {}",
+            self.listen_address
+        );
+
+        let key_authorizations = self.key_authorizations.clone();
+        *server = Some(tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let key_authorizations = key_authorizations.clone();
+                tokio::spawn(async move {
+                    let service =
+                        service_fn(|req| Self::handle_request(key_authorizations.clone(), req));
+                    if let Err(err) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        warn!("Error serving an ACME challenge: {err:?}");
+                    }
+                });
+            }
+        }));
+        Ok(())
+    }
+
+    async fn handle_request(
+        key_authorizations: Arc<Mutex<HashMap<String, String>>>,
+        req: Request<hyper::body::Incoming>,
+    ) -> std::result::Result<Response<BoxBody<Bytes, Infallible>>, hyper::http::Error> {
+        let token = req.uri().path().strip_prefix(HTTP01_CHALLENGE_PATH);
+        let key_authorization = match (req.method(), token) {
+            (&Method::GET, Some(token)) => key_authorizations.lock().await.get(token).cloned(),
+            _ => None,
+        };
+        match key_authorization {
+            Some(key_authorization) => Response::builder()
+                .header(hyper::header::CONTENT_TYPE, "application/octet-stream")
+                .body(Full::new(Bytes::from(key_authorization)).boxed()),
+            None => {
+                debug!("No ACME challenge for the request: {req:?}");
+                Response::builder()
+                    .status(404)
+                    .body(Empty::<Bytes>::new().boxed())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AcmeChallengeSolver for Http01ChallengeSolver {
+    fn challenge_type(&self) -> AcmeChallengeType {
+        AcmeChallengeType::Http01
+    }
+
+    async fn present(&self, challenge: &AcmeChallengeResponse) -> Result<()> {
+        self.key_authorizations
+            .lock()
+            .await
+            .insert(challenge.token.clone(), challenge.value.clone());
+        self.start_server().await
+    }
+
+    async fn cleanup(&self, challenge: &AcmeChallengeResponse) -> Result<()> {
+        let mut key_authorizations = self.key_authorizations.lock().await;
+        key_authorizations.remove(&challenge.token);
+        if key_authorizations.is_empty() {
+            if let Some(server) = self.server.lock().await.take() {
+                server.abort();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// This solver publishes the DNS-01 challenges by calling an external command:
+///
+///  - `<command> present <record name> <record value>` must create the TXT record
+///  - `<command> cleanup <record name> <record value>` must delete it
+///
+/// The `present` command must only return when the record can be resolved
+pub struct Dns01CommandChallengeSolver {
+    hook_command: String,
+}
+
+impl Dns01CommandChallengeSolver {
+    /// Create a solver calling the given command
+    pub fn new(hook_command: impl Into<String>) -> Self {
+        Self {
+            hook_command: hook_command.into(),
+        }
+    }
+
+    async fn run(&self, action: &str, challenge: &AcmeChallengeResponse) -> Result<()> {
+        let record_name = challenge.dns_record_name();
+        debug!(
+            "calling the ACME DNS hook {} {action} {record_name}",
+            self.hook_command
+        );
+        let output = Command::new(&self.hook_command)
+            .arg(action)
+            .arg(&record_name)
+            .arg(&challenge.value)
+            .output()
+            .await
+            .map_err(|e| Error::new(Origin::Api, Kind::Io, e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                Origin::Api,
+                Kind::Io,
+                format!(
+                    "the ACME DNS hook {} failed to {action} the record {record_name}: {}",
+                    self.hook_command,
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            ))
+        }
+    }
+}
+
+#[async_trait]
+impl AcmeChallengeSolver for Dns01CommandChallengeSolver {
+    fn challenge_type(&self) -> AcmeChallengeType {
+        AcmeChallengeType::Dns01
+    }
+
+    async fn present(&self, challenge: &AcmeChallengeResponse) -> Result<()> {
+        self.run("present", challenge).await
+    }
+
+    async fn cleanup(&self, challenge: &AcmeChallengeResponse) -> Result<()> {
+        self.run("cleanup", challenge).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::get_free_address_for;
+
+    #[tokio::test]
+    async fn test_http01_challenge_is_served_while_pending() -> Result<()> {
+        let port = get_free_address_for("127.0.0.1").unwrap().port();
+        let solver = Http01ChallengeSolver::new(HostnamePort::localhost(port));
+        let challenge = AcmeChallengeResponse {
+            domain: "example.com".to_string(),
+            token: "token".to_string(),
+            value: "token.thumbprint".to_string(),
+        };
+        solver.present(&challenge).await?;
+
+        let url = format!("http://127.0.0.1:{port}{HTTP01_CHALLENGE_PATH}token");
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "token.thumbprint");
+
+        let unknown = format!("http://127.0.0.1:{port}{HTTP01_CHALLENGE_PATH}unknown");
+        assert_eq!(reqwest::get(&unknown).await.unwrap().status(), 404);
+
+        // the server is stopped when there are no more pending challenges
+        solver.cleanup(&challenge).await?;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(reqwest::get(&url).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dns01_hook_is_set_by_the_node() {
+        let allow_list = vec!["/usr/local/bin/route53-hook".to_string()];
+        assert_eq!(
+            check_dns01_hook(None, Some("hook".to_string()), allow_list.clone()).unwrap(),
+            "hook"
+        );
+        assert!(check_dns01_hook(None, None, allow_list.clone()).is_err());
+
+        // a requested hook must be allowed by the node
+        assert_eq!(
+            check_dns01_hook(
+                Some("/usr/local/bin/route53-hook"),
+                None,
+                allow_list.clone()
+            )
+            .unwrap(),
+            "/usr/local/bin/route53-hook"
+        );
+        assert_eq!(
+            check_dns01_hook(Some("hook"), Some("hook".to_string()), vec![]).unwrap(),
+            "hook"
+        );
+        let error = check_dns01_hook(Some("rm"), Some("hook".to_string()), allow_list)
+            .err()
+            .unwrap();
+        assert_eq!(error.code().kind, Kind::Invalid);
+    }
+
+    #[test]
+    fn test_dns_record_name() {
+        let challenge = AcmeChallengeResponse {
+            domain: "*.example.com".to_string(),
+            token: "token".to_string(),
+            value: "digest".to_string(),
+        };
+        assert_eq!(challenge.dns_record_name(), "_acme-challenge.example.com");
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::{Display, Formatter};
+
+use minicbor::{CborLen, Decode, Encode};
+use ockam_transport_core::HostnamePort;
+
+/// Directory of the Let's Encrypt production ACME server
+pub const LETS_ENCRYPT_DIRECTORY_URL: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Address where the HTTP-01 challenges are served by default.
+/// The ACME server always validates HTTP-01 challenges on port 80
+pub const DEFAULT_HTTP01_LISTEN_ADDRESS: &str = "0.0.0.0:80";
+
+/// Configuration of a TLS certificate obtained from an ACME server
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AcmeCertificateConfig {
+    /// Domain name of the certificate
+    #[n(1)] pub domain: String,
+    /// URL of the ACME server directory
+    #[n(2)] pub directory_url: String,
+    /// Email address registered as a contact for the ACME account
+    #[n(3)] pub contact_email: Option<String>,
+    /// Challenge used to prove the control of the domain
+    #[n(4)] pub challenge: AcmeChallenge,
+    /// True if the terms of service of the ACME server were agreed to.
+    /// An ACME account can't be created otherwise
+    #[n(5)] pub terms_of_service_agreed: bool,
+}
+
+impl AcmeCertificateConfig {
+    /// Create a configuration for a domain, using the Let's Encrypt production server
+    pub fn new(domain: impl Into<String>, challenge: AcmeChallenge) -> Self {
+        Self {
+            domain: domain.into(),
+            directory_url: LETS_ENCRYPT_DIRECTORY_URL.to_string(),
+            contact_email: None,
+            challenge,
+            terms_of_service_agreed: false,
+        }
+    }
+
+    /// Use another ACME server
+    pub fn with_directory_url(mut self, directory_url: impl Into<String>) -> Self {
+        self.directory_url = directory_url.into();
+        self
+    }
+
+    /// Register an email address as a contact for the ACME account
+    pub fn with_contact_email(mut self, contact_email: impl Into<String>) -> Self {
+        self.contact_email = Some(contact_email.into());
+        self
+    }
+
+    /// Agree to the terms of service of the ACME server, which is required to create an account
+    pub fn with_terms_of_service_agreed(mut self) -> Self {
+        self.terms_of_service_agreed = true;
+        self
+    }
+}
+
+impl Display for AcmeCertificateConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ACME certificate for {} from {} ({})",
+            self.domain, self.directory_url, self.challenge
+        )
+    }
+}
+
+/// Challenge used to prove the control of a domain to the ACME server
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+pub enum AcmeChallenge {
+    /// The challenge is served over HTTP by the node, on the given address
+    #[n(0)] Http01 {
+        #[n(0)] listen_address: HostnamePort,
+    },
+    /// The challenge is published as a DNS TXT record by a hook command configured on the node,
+    /// see [`OCKAM_ACME_DNS01_HOOK`](crate::acme::OCKAM_ACME_DNS01_HOOK).
+    /// Another hook can be requested if it is allowed by the node, see
+    /// [`OCKAM_ACME_DNS01_HOOK_ALLOW_LIST`](crate::acme::OCKAM_ACME_DNS01_HOOK_ALLOW_LIST).
+    #[n(1)] Dns01 {
+        #[n(0)] hook: Option<String>,
+    },
+}
+
+impl AcmeChallenge {
+    /// HTTP-01 challenge served on the default address
+    pub fn http01() -> ockam_core::Result<Self> {
+        Ok(AcmeChallenge::Http01 {
+            listen_address: HostnamePort::try_from(DEFAULT_HTTP01_LISTEN_ADDRESS)?,
+        })
+    }
+
+    /// DNS-01 challenge published by the hook command of the node
+    pub fn dns01() -> Self {
+        AcmeChallenge::Dns01 { hook: None }
+    }
+
+    /// DNS-01 challenge published by one of the hook commands allowed by the node
+    pub fn dns01_with_hook(hook: impl Into<String>) -> Self {
+        AcmeChallenge::Dns01 {
+            hook: Some(hook.into()),
+        }
+    }
+}
+
+impl Display for AcmeChallenge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AcmeChallenge::Http01 { listen_address } => {
+                write!(f, "HTTP-01 challenge served on {listen_address}")
+            }
+            AcmeChallenge::Dns01 { hook: None } => {
+                write!(f, "DNS-01 challenge published by the node hook")
+            }
+            AcmeChallenge::Dns01 { hook: Some(hook) } => {
+                write!(f, "DNS-01 challenge published by {hook}")
+            }
+        }
+    }
+}
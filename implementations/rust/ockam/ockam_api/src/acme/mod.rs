@@ -0,0 +1,11 @@
+//! TLS certificates obtained and renewed with the ACME protocol,
+//! for example from Let's Encrypt, to be used by TLS inlets.
+
+mod challenges;
+mod config;
+mod provider;
+pub mod storage;
+
+pub use challenges::*;
+pub use config::*;
+pub use provider::*;
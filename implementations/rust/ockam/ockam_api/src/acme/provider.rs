@@ -0,0 +1,492 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount,
+    NewOrder, Order, OrderStatus,
+};
+use ockam::identity::utils::now;
+use ockam_core::async_trait;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TlsCertificate, TlsCertificateProvider};
+use rcgen::{CertificateParams, DistinguishedName, KeyPair};
+use std::sync::Mutex as StdMutex;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::acme::storage::{AcmeCertificate, AcmeCertificatesRepository};
+use crate::acme::{
+    make_challenge_solver, AcmeCertificateConfig, AcmeChallengeResponse, AcmeChallengeSolver,
+    AcmeChallengeType,
+};
+
+/// Certificates are renewed when 2/3 of their lifetime has elapsed,
+/// which is 30 days before the expiration of a 90 days Let's Encrypt certificate
+pub const DEFAULT_RENEWAL_RATIO: f64 = 2.0 / 3.0;
+
+/// Maximum time to wait for the ACME server to validate the challenges,
+/// and then to issue the certificate
+const MAX_ORDER_WAIT_TIME: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum delay between two polls of the ACME server
+const MAX_POLL_DELAY: Duration = Duration::from_secs(10);
+
+/// Delay before a failed order is retried, to stay under the rate limits of the ACME server
+const ORDER_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// This provider obtains a TLS certificate from an ACME server (Let's Encrypt for example).
+///
+/// The certificate and the ACME account are stored in the node database and the certificate
+/// is only renewed when a given ratio of its lifetime, between its `notBefore` and `notAfter`
+/// dates, has elapsed.
+///
+/// The orders are made by a background task, while the stored certificate is still returned.
+/// No certificate is returned until the first order succeeds.
+pub struct AcmeCertificateProvider {
+    client: AcmeClient,
+    renewal_ratio: f64,
+    // only one order is made at the time for a given provider
+    order: StdMutex<Option<JoinHandle<()>>>,
+}
+
+/// Parameters needed to order a certificate from a background task
+#[derive(Clone)]
+struct AcmeClient {
+    config: AcmeCertificateConfig,
+    repository: Arc<dyn AcmeCertificatesRepository>,
+    challenge_solver: Arc<dyn AcmeChallengeSolver>,
+}
+
+impl AcmeCertificateProvider {
+    /// Create a provider publishing the challenges as specified in the configuration
+    pub fn new(
+        config: AcmeCertificateConfig,
+        repository: Arc<dyn AcmeCertificatesRepository>,
+    ) -> Result<Self> {
+        let challenge_solver = make_challenge_solver(&config.challenge)?;
+        Ok(Self {
+            client: AcmeClient {
+                config,
+                repository,
+                challenge_solver,
+            },
+            renewal_ratio: DEFAULT_RENEWAL_RATIO,
+            order: StdMutex::new(None),
+        })
+    }
+
+    /// Publish the challenges with another solver, for example to update a DNS provider
+    /// with its API
+    pub fn with_challenge_solver(mut self, challenge_solver: Arc<dyn AcmeChallengeSolver>) -> Self {
+        self.client.challenge_solver = challenge_solver;
+        self
+    }
+
+    /// Renew the certificate after a different ratio of its lifetime than
+    /// [`DEFAULT_RENEWAL_RATIO`]. With a ratio of 0, the certificate is always renewed
+    pub fn with_renewal_ratio(mut self, renewal_ratio: f64) -> Self {
+        self.renewal_ratio = renewal_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Return the time after which a certificate must be renewed.
+    /// A certificate with an invalid validity period is renewed immediately
+    fn renewal_time(&self, certificate: &TlsCertificate) -> i64 {
+        match certificate.validity() {
+            Ok((not_before, not_after)) if not_before < not_after => {
+                let lifetime = (not_after - not_before) as f64;
+                not_before + (lifetime * self.renewal_ratio).round() as i64
+            }
+            Ok(_) => i64::MIN,
+            Err(e) => {
+                warn!("could not read the validity of the ACME certificate: {e:?}");
+                i64::MIN
+            }
+        }
+    }
+
+    /// Start a background order unless one is already running
+    fn start_order(&self) {
+        let mut order = self.order.lock().unwrap();
+        if order.as_ref().is_some_and(|order| !order.is_finished()) {
+            return;
+        }
+        let client = self.client.clone();
+        *order = Some(tokio::spawn(async move {
+            let domain = &client.config.domain;
+            if let Err(e) = client.order_and_store_certificate().await {
+                warn!("could not obtain an ACME certificate for {domain}: {e:?}");
+                sleep(ORDER_RETRY_DELAY).await;
+            }
+        }));
+    }
+}
+
+impl Drop for AcmeCertificateProvider {
+    fn drop(&mut self) {
+        if let Some(order) = self.order.lock().unwrap().take() {
+            order.abort();
+        }
+    }
+}
+
+impl Debug for AcmeCertificateProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AcmeCertificateProvider {{ config: {:?} }}",
+            self.client.config
+        )
+    }
+}
+
+impl Display for AcmeCertificateProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.client.config)
+    }
+}
+
+#[async_trait]
+impl TlsCertificateProvider for AcmeCertificateProvider {
+    async fn get_certificate(&self, _context: &Context) -> Result<TlsCertificate> {
+        let domain = &self.client.config.domain;
+        let stored = self
+            .client
+            .repository
+            .get_certificate(&self.client.config.directory_url, domain)
+            .await?;
+
+        match stored {
+            Some(stored) => {
+                if self.renewal_time(stored.certificate()) <= *now()? as i64 {
+                    debug!("renewing the ACME certificate for {domain}");
+                    self.start_order();
+                }
+                Ok(stored.certificate().clone())
+            }
+            None => {
+                self.start_order();
+                Err(Error::new(
+                    Origin::Api,
+                    Kind::NotFound,
+                    format!("the ACME certificate for {domain} is being ordered"),
+                ))
+            }
+        }
+    }
+}
+
+impl AcmeClient {
+    /// Order a new certificate and store it
+    async fn order_and_store_certificate(&self) -> Result<()> {
+        let now = now()?;
+        let certificate = self.order_certificate().await?;
+        self.repository
+            .store_certificate(&AcmeCertificate::new(
+                &self.config.directory_url,
+                &self.config.domain,
+                certificate,
+                now,
+            ))
+            .await?;
+        info!("obtained a new ACME certificate for {}", self.config.domain);
+        Ok(())
+    }
+
+    /// Return the ACME account stored for the ACME server or create a new one
+    async fn account(&self) -> Result<Account> {
+        let directory_url = &self.config.directory_url;
+        if let Some(credentials) = self
+            .repository
+            .get_account_credentials(directory_url)
+            .await?
+        {
+            let credentials: AccountCredentials = serde_json::from_str(&credentials)
+                .map_err(|e| Error::new(Origin::Api, Kind::Serialization, e))?;
+            return Account::from_credentials(credentials)
+                .await
+                .map_err(acme_error);
+        }
+
+        if !self.config.terms_of_service_agreed {
+            return Err(Error::new(
+                Origin::Api,
+                Kind::Invalid,
+                format!(
+                    "the terms of service of the ACME server {directory_url} were not agreed to"
+                ),
+            ));
+        }
+
+        debug!("creating an ACME account on {directory_url}");
+        let contact = self
+            .config
+            .contact_email
+            .iter()
+            .map(|email| format!("mailto:{email}"))
+            .collect::<Vec<_>>();
+        let contact = contact.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+        let (account, credentials) = Account::create(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: self.config.terms_of_service_agreed,
+                only_return_existing: false,
+            },
+            directory_url,
+            None,
+        )
+        .await
+        .map_err(acme_error)?;
+
+        let credentials = serde_json::to_string(&credentials)
+            .map_err(|e| Error::new(Origin::Api, Kind::Serialization, e))?;
+        self.repository
+            .store_account_credentials(directory_url, &credentials)
+            .await?;
+        Ok(account)
+    }
+
+    /// Order a new certificate: publish the challenges, wait for their validation,
+    /// then send a certificate signing request for a freshly generated key
+    async fn order_certificate(&self) -> Result<TlsCertificate> {
+        let account = self.account().await?;
+        let mut order = account
+            .new_order(&NewOrder {
+                identifiers: &[Identifier::Dns(self.config.domain.clone())],
+            })
+            .await
+            .map_err(acme_error)?;
+
+        // the challenges are always removed, even if the validation fails
+        let mut presented = vec![];
+        let validated = self.validate_challenges(&mut order, &mut presented).await;
+        for challenge in presented.iter() {
+            if let Err(e) = self.challenge_solver.cleanup(challenge).await {
+                warn!(
+                    "could not clean up the ACME challenge for {}: {e:?}",
+                    challenge.domain
+                );
+            }
+        }
+        validated?;
+
+        let mut params =
+            CertificateParams::new(vec![self.config.domain.clone()]).map_err(acme_error)?;
+        params.distinguished_name = DistinguishedName::new();
+        let private_key = KeyPair::generate().map_err(acme_error)?;
+        let csr = params.serialize_request(&private_key).map_err(acme_error)?;
+        order.finalize(csr.der()).await.map_err(acme_error)?;
+
+        let mut delay = Duration::from_millis(250);
+        let mut waited = Duration::ZERO;
+        let full_chain_pem = loop {
+            if let Some(full_chain_pem) = order.certificate().await.map_err(acme_error)? {
+                break full_chain_pem;
+            }
+            if waited >= MAX_ORDER_WAIT_TIME {
+                return Err(order_timeout(&self.config.domain));
+            }
+            sleep(delay).await;
+            waited += delay;
+            delay = (delay * 2).min(MAX_POLL_DELAY);
+        };
+
+        Ok(TlsCertificate {
+            full_chain_pem: full_chain_pem.into_bytes(),
+            private_key_pem: private_key.serialize_pem().into_bytes(),
+        })
+    }
+
+    /// Publish the challenges of all the pending authorizations and wait until the
+    /// order is ready to be finalized
+    async fn validate_challenges(
+        &self,
+        order: &mut Order,
+        presented: &mut Vec<AcmeChallengeResponse>,
+    ) -> Result<()> {
+        let challenge_type = match self.challenge_solver.challenge_type() {
+            AcmeChallengeType::Http01 => ChallengeType::Http01,
+            AcmeChallengeType::Dns01 => ChallengeType::Dns01,
+        };
+
+        let authorizations = order.authorizations().await.map_err(acme_error)?;
+        for authorization in authorizations.iter() {
+            match authorization.status {
+                AuthorizationStatus::Pending => {}
+                AuthorizationStatus::Valid => continue,
+                status => {
+                    return Err(Error::new(
+                        Origin::Api,
+                        Kind::Invalid,
+                        format!(
+                            "the ACME authorization for {} is {status:?}",
+                            self.config.domain
+                        ),
+                    ))
+                }
+            }
+
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|c| c.r#type == challenge_type)
+                .ok_or_else(|| {
+                    Error::new(
+                        Origin::Api,
+                        Kind::NotFound,
+                        format!(
+                            "the ACME server doesn't offer a {challenge_type:?} challenge for {}",
+                            self.config.domain
+                        ),
+                    )
+                })?;
+
+            let key_authorization = order.key_authorization(challenge);
+            let value = match challenge_type {
+                ChallengeType::Dns01 => key_authorization.dns_value(),
+                _ => key_authorization.as_str().to_string(),
+            };
+            let response = AcmeChallengeResponse {
+                domain: self.config.domain.clone(),
+                token: challenge.token.clone(),
+                value,
+            };
+            self.challenge_solver.present(&response).await?;
+            presented.push(response);
+            order
+                .set_challenge_ready(&challenge.url)
+                .await
+                .map_err(acme_error)?;
+        }
+
+        let mut delay = Duration::from_millis(250);
+        let mut waited = Duration::ZERO;
+        while waited < MAX_ORDER_WAIT_TIME {
+            sleep(delay).await;
+            waited += delay;
+            delay = (delay * 2).min(MAX_POLL_DELAY);
+
+            let state = order.refresh().await.map_err(acme_error)?;
+            match state.status {
+                OrderStatus::Ready => return Ok(()),
+                OrderStatus::Invalid => {
+                    return Err(Error::new(
+                        Origin::Api,
+                        Kind::Invalid,
+                        format!(
+                            "the ACME order for {} is invalid: {:?}",
+                            self.config.domain, state.error
+                        ),
+                    ))
+                }
+                _ => debug!("waiting for the ACME challenges validation"),
+            }
+        }
+        Err(order_timeout(&self.config.domain))
+    }
+}
+
+fn acme_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> Error {
+    Error::new(Origin::Api, Kind::Io, e)
+}
+
+fn order_timeout(domain: &str) -> Error {
+    Error::new(
+        Origin::Api,
+        Kind::Timeout,
+        format!("the ACME order for {domain} timed out"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acme::storage::AcmeCertificatesSqlxDatabase;
+    use crate::acme::AcmeChallenge;
+    use rcgen::{date_time_ymd, KeyPair};
+
+    #[tokio::test]
+    async fn renew_certificates_after_a_ratio_of_their_lifetime() -> Result<()> {
+        let provider = create_provider(AcmeCertificateConfig::new(
+            "example.com",
+            AcmeChallenge::http01()?,
+        ))
+        .await?;
+
+        // 2025-01-01 -> 2025-04-01, the certificate is renewed on 2025-03-02
+        let certificate = create_certificate(2025, 1, 4)?;
+        assert_eq!(provider.renewal_time(&certificate), 1740873600);
+
+        let provider = provider.with_renewal_ratio(0.0);
+        assert_eq!(provider.renewal_time(&certificate), 1735689600);
+
+        let invalid = TlsCertificate {
+            full_chain_pem: b"not a certificate".to_vec(),
+            private_key_pem: vec![],
+        };
+        assert_eq!(provider.renewal_time(&invalid), i64::MIN);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn require_the_agreement_to_the_terms_of_service() -> Result<()> {
+        let config = AcmeCertificateConfig::new("example.com", AcmeChallenge::http01()?)
+            .with_directory_url("http://127.0.0.1:1/directory");
+        let provider = create_provider(config).await?;
+
+        let error = provider.client.account().await.err().unwrap();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn order_certificates_in_the_background(ctx: &mut Context) -> Result<()> {
+        // the ACME server can't be reached, so the orders fail
+        let config = AcmeCertificateConfig::new("example.com", AcmeChallenge::http01()?)
+            .with_directory_url("http://127.0.0.1:1/directory")
+            .with_terms_of_service_agreed();
+        let provider = create_provider(config.clone()).await?;
+
+        // no certificate is returned while the first one is ordered
+        let error = provider.get_certificate(ctx).await.err().unwrap();
+        assert_eq!(error.code().kind, Kind::NotFound);
+        assert!(provider.order.lock().unwrap().is_some());
+
+        // the stored certificate is returned while it is renewed
+        let certificate = create_certificate(2025, 1, 4)?;
+        provider
+            .client
+            .repository
+            .store_certificate(&AcmeCertificate::new(
+                &config.directory_url,
+                &config.domain,
+                certificate.clone(),
+                now()?,
+            ))
+            .await?;
+        assert_eq!(provider.get_certificate(ctx).await?, certificate);
+        Ok(())
+    }
+
+    async fn create_provider(config: AcmeCertificateConfig) -> Result<AcmeCertificateProvider> {
+        AcmeCertificateProvider::new(config, AcmeCertificatesSqlxDatabase::create().await?)
+    }
+
+    /// Create a self-signed certificate valid from the first day of the `from` month
+    /// to the first day of the `to` month
+    fn create_certificate(year: i32, from: u8, to: u8) -> Result<TlsCertificate> {
+        let mut params =
+            CertificateParams::new(vec!["example.com".to_string()]).map_err(acme_error)?;
+        params.not_before = date_time_ymd(year, from, 1);
+        params.not_after = date_time_ymd(year, to, 1);
+        let private_key = KeyPair::generate().map_err(acme_error)?;
+        let certificate = params.self_signed(&private_key).map_err(acme_error)?;
+        Ok(TlsCertificate {
+            full_chain_pem: certificate.pem().into_bytes(),
+            private_key_pem: private_key.serialize_pem().into_bytes(),
+        })
+    }
+}
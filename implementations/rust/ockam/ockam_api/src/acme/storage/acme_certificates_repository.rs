@@ -0,0 +1,99 @@
+use ockam::identity::TimestampInSeconds;
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::retry;
+use ockam_transport_tcp::TlsCertificate;
+
+/// This repository stores the ACME accounts and the certificates obtained with them,
+/// so that a certificate is only renewed when it is close to its expiration,
+/// even if the node is restarted
+#[async_trait]
+pub trait AcmeCertificatesRepository: Send + Sync + 'static {
+    /// Store the serialized credentials of the account created on an ACME server
+    async fn store_account_credentials(&self, directory_url: &str, credentials: &str)
+        -> Result<()>;
+
+    /// Return the serialized credentials of the account created on an ACME server
+    async fn get_account_credentials(&self, directory_url: &str) -> Result<Option<String>>;
+
+    /// Store a certificate, replacing the previous certificate for the same domain
+    async fn store_certificate(&self, certificate: &AcmeCertificate) -> Result<()>;
+
+    /// Return the last certificate obtained for a domain from an ACME server
+    async fn get_certificate(
+        &self,
+        directory_url: &str,
+        domain: &str,
+    ) -> Result<Option<AcmeCertificate>>;
+}
+
+#[async_trait]
+impl<T: AcmeCertificatesRepository> AcmeCertificatesRepository for AutoRetry<T> {
+    async fn store_account_credentials(
+        &self,
+        directory_url: &str,
+        credentials: &str,
+    ) -> Result<()> {
+        retry!(self
+            .wrapped
+            .store_account_credentials(directory_url, credentials))
+    }
+
+    async fn get_account_credentials(&self, directory_url: &str) -> Result<Option<String>> {
+        retry!(self.wrapped.get_account_credentials(directory_url))
+    }
+
+    async fn store_certificate(&self, certificate: &AcmeCertificate) -> Result<()> {
+        retry!(self.wrapped.store_certificate(certificate))
+    }
+
+    async fn get_certificate(
+        &self,
+        directory_url: &str,
+        domain: &str,
+    ) -> Result<Option<AcmeCertificate>> {
+        retry!(self.wrapped.get_certificate(directory_url, domain))
+    }
+}
+
+/// Certificate obtained for a domain from an ACME server
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcmeCertificate {
+    directory_url: String,
+    domain: String,
+    certificate: TlsCertificate,
+    issued_at: TimestampInSeconds,
+}
+
+impl AcmeCertificate {
+    pub fn new(
+        directory_url: &str,
+        domain: &str,
+        certificate: TlsCertificate,
+        issued_at: TimestampInSeconds,
+    ) -> Self {
+        Self {
+            directory_url: directory_url.to_string(),
+            domain: domain.to_string(),
+            certificate,
+            issued_at,
+        }
+    }
+
+    pub fn directory_url(&self) -> &str {
+        &self.directory_url
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub fn certificate(&self) -> &TlsCertificate {
+        &self.certificate
+    }
+
+    pub fn issued_at(&self) -> TimestampInSeconds {
+        self.issued_at
+    }
+}
@@ -0,0 +1,220 @@
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::acme::storage::{AcmeCertificate, AcmeCertificatesRepository};
+use ockam::identity::TimestampInSeconds;
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToVoid};
+use ockam_transport_tcp::TlsCertificate;
+
+/// Implementation of [`AcmeCertificatesRepository`] trait based on an underlying database
+/// using sqlx as its API
+#[derive(Clone)]
+pub struct AcmeCertificatesSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl AcmeCertificatesSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for ACME certificates");
+        Self { database }
+    }
+
+    /// Create a repository
+    pub fn make_repository(database: SqlxDatabase) -> Arc<dyn AcmeCertificatesRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database)))
+        } else {
+            Arc::new(Self::new(database))
+        }
+    }
+
+    /// Create a new in-memory database
+    #[allow(unused)]
+    pub async fn create() -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::new(
+            SqlxDatabase::in_memory("acme certificates").await?,
+        )))
+    }
+}
+
+#[async_trait]
+impl AcmeCertificatesRepository for AcmeCertificatesSqlxDatabase {
+    async fn store_account_credentials(
+        &self,
+        directory_url: &str,
+        credentials: &str,
+    ) -> Result<()> {
+        let query = query(
+            r#"
+            INSERT INTO acme_account (directory_url, credentials)
+            VALUES ($1, $2)
+            ON CONFLICT (directory_url)
+            DO UPDATE SET credentials = $2"#,
+        )
+        .bind(directory_url)
+        .bind(credentials);
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_account_credentials(&self, directory_url: &str) -> Result<Option<String>> {
+        let query = query_scalar("SELECT credentials FROM acme_account WHERE directory_url = $1")
+            .bind(directory_url);
+        query.fetch_optional(&*self.database.pool).await.into_core()
+    }
+
+    async fn store_certificate(&self, certificate: &AcmeCertificate) -> Result<()> {
+        let query = query(
+            r#"
+            INSERT INTO acme_certificate (directory_url, domain, full_chain_pem, private_key_pem, issued_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (directory_url, domain)
+            DO UPDATE SET full_chain_pem = $3, private_key_pem = $4, issued_at = $5"#,
+        )
+        .bind(certificate.directory_url())
+        .bind(certificate.domain())
+        .bind(&certificate.certificate().full_chain_pem)
+        .bind(&certificate.certificate().private_key_pem)
+        .bind(certificate.issued_at().0 as i64);
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_certificate(
+        &self,
+        directory_url: &str,
+        domain: &str,
+    ) -> Result<Option<AcmeCertificate>> {
+        let query = query_as(
+            r#"
+            SELECT directory_url, domain, full_chain_pem, private_key_pem, issued_at
+            FROM acme_certificate
+            WHERE directory_url = $1 AND domain = $2"#,
+        )
+        .bind(directory_url)
+        .bind(domain);
+        let row: Option<AcmeCertificateRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        Ok(row.map(|r| r.certificate()))
+    }
+}
+
+// Database serialization / deserialization
+
+/// Low-level representation of a row in the acme_certificate table
+#[derive(FromRow)]
+struct AcmeCertificateRow {
+    directory_url: String,
+    domain: String,
+    full_chain_pem: Vec<u8>,
+    private_key_pem: Vec<u8>,
+    issued_at: i64,
+}
+
+impl AcmeCertificateRow {
+    fn certificate(self) -> AcmeCertificate {
+        AcmeCertificate::new(
+            &self.directory_url,
+            &self.domain,
+            TlsCertificate {
+                full_chain_pem: self.full_chain_pem,
+                private_key_pem: self.private_key_pem,
+            },
+            TimestampInSeconds(self.issued_at as u64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_node::database::with_dbs;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn AcmeCertificatesRepository> =
+                Arc::new(AcmeCertificatesSqlxDatabase::new(db));
+            let directory_url = "https://localhost:14000/dir";
+
+            // accounts
+            assert!(repository
+                .get_account_credentials(directory_url)
+                .await?
+                .is_none());
+            repository
+                .store_account_credentials(directory_url, "credentials1")
+                .await?;
+            repository
+                .store_account_credentials(directory_url, "credentials2")
+                .await?;
+            assert_eq!(
+                repository.get_account_credentials(directory_url).await?,
+                Some("credentials2".to_string())
+            );
+
+            // certificates
+            let certificate1 = AcmeCertificate::new(
+                directory_url,
+                "example.com",
+                TlsCertificate {
+                    full_chain_pem: b"full chain 1".to_vec(),
+                    private_key_pem: b"private key 1".to_vec(),
+                },
+                TimestampInSeconds(10),
+            );
+            let other = AcmeCertificate::new(
+                directory_url,
+                "other.com",
+                TlsCertificate {
+                    full_chain_pem: b"full chain".to_vec(),
+                    private_key_pem: b"private key".to_vec(),
+                },
+                TimestampInSeconds(10),
+            );
+            repository.store_certificate(&certificate1).await?;
+            repository.store_certificate(&other).await?;
+            assert_eq!(
+                repository
+                    .get_certificate(directory_url, "example.com")
+                    .await?,
+                Some(certificate1)
+            );
+
+            // a renewed certificate replaces the previous one
+            let certificate2 = AcmeCertificate::new(
+                directory_url,
+                "example.com",
+                TlsCertificate {
+                    full_chain_pem: b"full chain 2".to_vec(),
+                    private_key_pem: b"private key 2".to_vec(),
+                },
+                TimestampInSeconds(20),
+            );
+            repository.store_certificate(&certificate2).await?;
+            assert_eq!(
+                repository
+                    .get_certificate(directory_url, "example.com")
+                    .await?,
+                Some(certificate2)
+            );
+            assert_eq!(
+                repository
+                    .get_certificate(directory_url, "other.com")
+                    .await?,
+                Some(other)
+            );
+            assert!(repository
+                .get_certificate("https://acme.example.com/dir", "example.com")
+                .await?
+                .is_none());
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,5 @@
+pub use acme_certificates_repository::*;
+pub use acme_certificates_repository_sql::*;
+
+mod acme_certificates_repository;
+mod acme_certificates_repository_sql;
@@ -40,6 +40,7 @@ pub struct CreateToken {
     #[b(1)] attributes: BTreeMap<String, String>,
     #[n(2)] ttl_secs: Option<u64>,
     #[n(3)] ttl_count: Option<u64>,
+    #[n(4)] parameters: Option<BTreeMap<String, String>>,
 }
 
 impl CreateToken {
@@ -49,6 +50,7 @@ impl CreateToken {
             attributes: Default::default(),
             ttl_count: None,
             ttl_secs: None,
+            parameters: None,
         }
     }
 
@@ -67,10 +69,23 @@ impl CreateToken {
         self
     }
 
+    pub fn with_parameters(mut self, parameters: BTreeMap<String, String>) -> Self {
+        self.parameters = if parameters.is_empty() {
+            None
+        } else {
+            Some(parameters)
+        };
+        self
+    }
+
     pub fn into_owned_attributes(self) -> BTreeMap<String, String> {
         self.attributes.clone()
     }
 
+    pub fn parameters(&self) -> BTreeMap<String, String> {
+        self.parameters.clone().unwrap_or_default()
+    }
+
     pub fn ttl_count(&self) -> Option<u64> {
         self.ttl_count
     }
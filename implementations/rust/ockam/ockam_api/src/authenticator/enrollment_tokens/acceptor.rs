@@ -5,10 +5,12 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
 
 use crate::authenticator::common::EnrollerAccessControlChecks;
+use crate::authenticator::enrollment_tokens::render_attributes_template;
 use crate::authenticator::one_time_code::OneTimeCode;
 use crate::authenticator::{
     AuthorityEnrollmentTokenRepository, AuthorityMember, AuthorityMembersRepository,
 };
+use std::collections::BTreeMap;
 
 pub struct EnrollmentTokenAcceptorError(pub String);
 
@@ -37,6 +39,7 @@ impl EnrollmentTokenAcceptor {
     pub async fn accept_token(
         &mut self,
         otc: OneTimeCode,
+        parameters: BTreeMap<String, String>,
         from: &Identifier,
     ) -> Result<EnrollmentTokenAcceptorResult<()>> {
         let check = EnrollerAccessControlChecks::check_is_member(
@@ -54,7 +57,29 @@ impl EnrollmentTokenAcceptor {
             )));
         }
 
-        let token = match self.tokens.use_token(otc, now()?).await {
+        // The parameters are validated before using the token, so that an invalid value
+        // doesn't use up the token
+        let Some(token) = self.tokens.get_token(otc, now()?).await? else {
+            warn!("Unknown enrollment token received from {}", from);
+            return Ok(Either::Right(EnrollmentTokenAcceptorError(
+                "Unknown enrollment token".to_string(),
+            )));
+        };
+        let attrs = match render_attributes_template(&token.attrs, &token.parameters, &parameters) {
+            Ok(attrs) => attrs,
+            Err(error) => {
+                warn!(
+                    "Invalid token parameters received from {}. Reference: {}. Error: {}",
+                    from,
+                    token.reference(),
+                    error
+                );
+                return Ok(Either::Right(EnrollmentTokenAcceptorError(error)));
+            }
+        };
+
+        // The redemption is recorded when the token is used
+        let token = match self.tokens.use_token(otc, now()?, from, &attrs).await {
             Ok(Some(token)) => token,
             Ok(None) => {
                 warn!("Unknown enrollment token received from {}", from);
@@ -74,13 +99,18 @@ impl EnrollmentTokenAcceptor {
         };
 
         let reference = token.reference();
-        let attrs = token
-            .attrs
+        let member_attrs = attrs
             .iter()
             .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
             .collect();
 
-        let member = AuthorityMember::new(from.clone(), attrs, token.issued_by, now()?, false);
+        let member = AuthorityMember::new(
+            from.clone(),
+            member_attrs,
+            token.issued_by.clone(),
+            now()?,
+            false,
+        );
 
         if let Err(err) = self.members.add_member(&self.authority, member).await {
             warn!(
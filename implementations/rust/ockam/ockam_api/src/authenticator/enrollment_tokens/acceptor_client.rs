@@ -1,9 +1,11 @@
 use miette::IntoDiagnostic;
+use std::collections::BTreeMap;
 
 use ockam_core::api::Request;
 use ockam_core::async_trait;
 use ockam_node::Context;
 
+use crate::authenticator::enrollment_tokens::AcceptToken;
 use crate::authenticator::one_time_code::OneTimeCode;
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::orchestrator::{AuthorityNodeClient, HasSecureClient};
@@ -11,13 +13,32 @@ use crate::orchestrator::{AuthorityNodeClient, HasSecureClient};
 #[async_trait]
 pub trait TokenAcceptor {
     async fn present_token(&self, ctx: &Context, token: OneTimeCode) -> miette::Result<()>;
+
+    /// Present a token together with the values of its parameters
+    async fn present_token_with_parameters(
+        &self,
+        ctx: &Context,
+        token: OneTimeCode,
+        parameters: BTreeMap<String, String>,
+    ) -> miette::Result<()>;
 }
 
 #[async_trait]
 impl TokenAcceptor for AuthorityNodeClient {
     #[instrument(skip_all)]
     async fn present_token(&self, ctx: &Context, token: OneTimeCode) -> miette::Result<()> {
-        let req = Request::post("/").body(token);
+        self.present_token_with_parameters(ctx, token, BTreeMap::new())
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn present_token_with_parameters(
+        &self,
+        ctx: &Context,
+        token: OneTimeCode,
+        parameters: BTreeMap<String, String>,
+    ) -> miette::Result<()> {
+        let req = Request::post("/").body(AcceptToken::new(&token).with_parameters(parameters));
         self.get_secure_client()
             .tell(ctx, DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR, req)
             .await
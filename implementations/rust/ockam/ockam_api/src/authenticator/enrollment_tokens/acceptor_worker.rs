@@ -1,5 +1,4 @@
-use crate::authenticator::enrollment_tokens::EnrollmentTokenAcceptor;
-use crate::authenticator::one_time_code::OneTimeCode;
+use crate::authenticator::enrollment_tokens::{AcceptToken, EnrollmentTokenAcceptor};
 use crate::authenticator::{AuthorityEnrollmentTokenRepository, AuthorityMembersRepository};
 use either::Either;
 use minicbor::Decoder;
@@ -57,8 +56,11 @@ impl Worker for EnrollmentTokenAcceptorWorker {
         }
         let res = match (req.method(), req.path()) {
             (Some(Method::Post), "/") | (Some(Method::Post), "/credential") => {
-                let otc: OneTimeCode = dec.decode()?;
-                let res = self.acceptor.accept_token(otc, &from).await?;
+                let accept: AcceptToken = dec.decode()?;
+                let res = self
+                    .acceptor
+                    .accept_token(accept.one_time_code(), accept.parameters(), &from)
+                    .await?;
                 match res {
                     Either::Left(_) => Response::ok().with_headers(&req).to_vec()?,
                     Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
@@ -9,11 +9,13 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::time::Duration;
 use ockam_core::Result;
 
-use crate::authenticator::common::EnrollerAccessControlChecks;
+use crate::authenticator::common::{EnrollerAccessControlChecks, EnrollerCheckResult};
 use crate::authenticator::direct::AccountAuthorityInfo;
+use crate::authenticator::enrollment_tokens::{validate_attributes_template, EnrollmentTokenInfo};
 use crate::authenticator::one_time_code::OneTimeCode;
 use crate::authenticator::{
     AuthorityEnrollmentTokenRepository, AuthorityMembersRepository, EnrollmentToken,
+    EnrollmentTokenRedemption,
 };
 
 pub const DEFAULT_TOKEN_DURATION: Duration = Duration::from_secs(60 * 10);
@@ -55,17 +57,11 @@ impl EnrollmentTokenIssuer {
         &self,
         enroller: &Identifier,
         attrs: BTreeMap<String, String>,
+        parameters: BTreeMap<String, String>,
         token_duration: Option<Duration>,
         ttl_count: Option<u64>,
     ) -> Result<EnrollmentTokenIssuerResult<OneTimeCode>> {
-        let check = EnrollerAccessControlChecks::check_identifier(
-            &self.authority,
-            self.members.clone(),
-            self.identities_attributes.clone(),
-            enroller,
-            &self.account_authority,
-        )
-        .await?;
+        let check = self.check_enroller(enroller).await?;
 
         if !check.is_enroller {
             warn!(
@@ -91,6 +87,14 @@ impl EnrollmentTokenIssuer {
             }
         }
 
+        if let Err(error) = validate_attributes_template(&attrs, &parameters) {
+            warn!(
+                "{} is trying to issue an invalid enrollment token",
+                enroller
+            );
+            return Ok(Either::Right(EnrollmentTokenIssuerError(error)));
+        }
+
         let one_time_code = OneTimeCode::new();
         let reference: String = rand::thread_rng()
             .sample_iter(&Alphanumeric)
@@ -109,6 +113,7 @@ impl EnrollmentTokenIssuer {
             expires_at,
             ttl_count,
             attrs,
+            parameters,
         };
         self.tokens.store_new_token(tkn).await?;
 
@@ -119,4 +124,115 @@ impl EnrollmentTokenIssuer {
 
         Ok(Either::Left(one_time_code))
     }
+
+    /// Return the tokens which can still be redeemed. Admins can see all the tokens,
+    /// enrollers can only see the tokens they issued
+    #[instrument(skip_all, fields(enroller = %enroller))]
+    pub async fn list_tokens(
+        &self,
+        enroller: &Identifier,
+    ) -> Result<EnrollmentTokenIssuerResult<Vec<EnrollmentTokenInfo>>> {
+        let check = self.check_enroller(enroller).await?;
+        if !check.is_enroller {
+            warn!(
+                "Non-enroller {} is trying to list enrollment tokens",
+                enroller
+            );
+            return Ok(Either::Right(EnrollmentTokenIssuerError(
+                "Non-enroller is trying to list enrollment tokens".to_string(),
+            )));
+        }
+
+        let tokens = self
+            .tokens
+            .get_tokens(now()?)
+            .await?
+            .into_iter()
+            .filter(|token| check.is_admin || &token.issued_by == enroller)
+            .map(EnrollmentTokenInfo::from)
+            .collect();
+        Ok(Either::Left(tokens))
+    }
+
+    /// Return the redemptions of a token, even if it can't be redeemed anymore.
+    /// Enrollers can only see the redemptions of the tokens they issued
+    #[instrument(skip_all, fields(enroller = %enroller, reference = %reference))]
+    pub async fn get_redemptions(
+        &self,
+        enroller: &Identifier,
+        reference: &str,
+    ) -> Result<EnrollmentTokenIssuerResult<Vec<EnrollmentTokenRedemption>>> {
+        let check = self.check_enroller(enroller).await?;
+        if !check.is_enroller {
+            warn!(
+                "Non-enroller {} is trying to get the redemptions of an enrollment token",
+                enroller
+            );
+            return Ok(Either::Right(EnrollmentTokenIssuerError(
+                "Non-enroller is trying to get the redemptions of an enrollment token".to_string(),
+            )));
+        }
+
+        let redemptions = self
+            .tokens
+            .get_redemptions(reference)
+            .await?
+            .into_iter()
+            .filter(|redemption| check.is_admin || &redemption.issued_by == enroller)
+            .collect();
+        Ok(Either::Left(redemptions))
+    }
+
+    /// Revoke a token so that it can't be redeemed anymore.
+    /// Enrollers can only revoke the tokens they issued.
+    /// Return false if there is no such token
+    #[instrument(skip_all, fields(enroller = %enroller, reference = %reference))]
+    pub async fn revoke_token(
+        &self,
+        enroller: &Identifier,
+        reference: &str,
+    ) -> Result<EnrollmentTokenIssuerResult<bool>> {
+        let check = self.check_enroller(enroller).await?;
+        if !check.is_enroller {
+            warn!(
+                "Non-enroller {} is trying to revoke an enrollment token",
+                enroller
+            );
+            return Ok(Either::Right(EnrollmentTokenIssuerError(
+                "Non-enroller is trying to revoke an enrollment token".to_string(),
+            )));
+        }
+
+        let Some(token) = self
+            .tokens
+            .get_token_by_reference(reference, now()?)
+            .await?
+        else {
+            return Ok(Either::Left(false));
+        };
+        if !check.is_admin && &token.issued_by != enroller {
+            warn!(
+                "{} is trying to revoke an enrollment token issued by {}",
+                enroller, token.issued_by
+            );
+            return Ok(Either::Right(EnrollmentTokenIssuerError(
+                "Enrollers can only revoke the enrollment tokens they issued".to_string(),
+            )));
+        }
+
+        let revoked = self.tokens.revoke_token(reference).await?;
+        info!("Revoked the enrollment token with reference {}", reference);
+        Ok(Either::Left(revoked))
+    }
+
+    async fn check_enroller(&self, enroller: &Identifier) -> Result<EnrollerCheckResult> {
+        EnrollerAccessControlChecks::check_identifier(
+            &self.authority,
+            self.members.clone(),
+            self.identities_attributes.clone(),
+            enroller,
+            &self.account_authority,
+        )
+        .await
+    }
 }
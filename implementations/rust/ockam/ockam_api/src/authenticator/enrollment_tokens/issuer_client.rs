@@ -7,7 +7,9 @@ use ockam_core::compat::time::Duration;
 use ockam_node::Context;
 
 use crate::authenticator::direct::types::CreateToken;
+use crate::authenticator::enrollment_tokens::EnrollmentTokenInfo;
 use crate::authenticator::one_time_code::OneTimeCode;
+use crate::authenticator::EnrollmentTokenRedemption;
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::orchestrator::{AuthorityNodeClient, HasSecureClient};
 
@@ -20,6 +22,30 @@ pub trait TokenIssuer {
         duration: Option<Duration>,
         ttl_count: Option<u64>,
     ) -> miette::Result<OneTimeCode>;
+
+    /// Create a token whose attributes can contain `${name}` placeholders, replaced by
+    /// the values supplied by the enrollee for each parameter
+    async fn create_token_with_parameters(
+        &self,
+        ctx: &Context,
+        attributes: BTreeMap<String, String>,
+        parameters: BTreeMap<String, String>,
+        duration: Option<Duration>,
+        ttl_count: Option<u64>,
+    ) -> miette::Result<OneTimeCode>;
+
+    /// Return the tokens which can still be redeemed
+    async fn list_tokens(&self, ctx: &Context) -> miette::Result<Vec<EnrollmentTokenInfo>>;
+
+    /// Return the identities which redeemed a token
+    async fn get_token_redemptions(
+        &self,
+        ctx: &Context,
+        reference: &str,
+    ) -> miette::Result<Vec<EnrollmentTokenRedemption>>;
+
+    /// Revoke a token so that it can't be redeemed anymore
+    async fn revoke_token(&self, ctx: &Context, reference: &str) -> miette::Result<()>;
 }
 
 #[async_trait]
@@ -30,9 +56,22 @@ impl TokenIssuer for AuthorityNodeClient {
         attributes: BTreeMap<String, String>,
         duration: Option<Duration>,
         ttl_count: Option<u64>,
+    ) -> miette::Result<OneTimeCode> {
+        self.create_token_with_parameters(ctx, attributes, BTreeMap::new(), duration, ttl_count)
+            .await
+    }
+
+    async fn create_token_with_parameters(
+        &self,
+        ctx: &Context,
+        attributes: BTreeMap<String, String>,
+        parameters: BTreeMap<String, String>,
+        duration: Option<Duration>,
+        ttl_count: Option<u64>,
     ) -> miette::Result<OneTimeCode> {
         let body = CreateToken::new()
             .with_attributes(attributes)
+            .with_parameters(parameters)
             .with_ttl(duration)
             .with_ttl_count(ttl_count);
 
@@ -44,4 +83,38 @@ impl TokenIssuer for AuthorityNodeClient {
             .success()
             .into_diagnostic()
     }
+
+    async fn list_tokens(&self, ctx: &Context) -> miette::Result<Vec<EnrollmentTokenInfo>> {
+        let req = Request::get("/tokens");
+        self.get_secure_client()
+            .ask(ctx, DefaultAddress::ENROLLMENT_TOKEN_ISSUER, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn get_token_redemptions(
+        &self,
+        ctx: &Context,
+        reference: &str,
+    ) -> miette::Result<Vec<EnrollmentTokenRedemption>> {
+        let req = Request::get(format!("/tokens/{reference}/redemptions"));
+        self.get_secure_client()
+            .ask(ctx, DefaultAddress::ENROLLMENT_TOKEN_ISSUER, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn revoke_token(&self, ctx: &Context, reference: &str) -> miette::Result<()> {
+        let req = Request::delete(format!("/tokens/{reference}"));
+        self.get_secure_client()
+            .tell(ctx, DefaultAddress::ENROLLMENT_TOKEN_ISSUER, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
 }
@@ -67,15 +67,23 @@ impl Worker for EnrollmentTokenIssuerWorker {
             body   = %req.has_body(),
             "request"
         }
-        let res = match (req.method(), req.path()) {
-            (Some(Method::Post), "/") | (Some(Method::Post), "/tokens") => {
+        let path_segments = req.path_segments::<5>();
+        let res = match (req.method(), path_segments.as_slice()) {
+            (Some(Method::Post), [""]) | (Some(Method::Post), ["tokens"]) => {
                 let att: CreateToken = dec.decode()?;
                 let duration = att.ttl_secs().map(Duration::from_secs);
                 let ttl_count = att.ttl_count();
+                let parameters = att.parameters();
 
                 let res = self
                     .issuer
-                    .issue_token(&from, att.into_owned_attributes(), duration, ttl_count)
+                    .issue_token(
+                        &from,
+                        att.into_owned_attributes(),
+                        parameters,
+                        duration,
+                        ttl_count,
+                    )
                     .await?;
 
                 match res {
@@ -83,6 +91,30 @@ impl Worker for EnrollmentTokenIssuerWorker {
                     Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
                 }
             }
+            (Some(Method::Get), ["tokens"]) => match self.issuer.list_tokens(&from).await? {
+                Either::Left(tokens) => Response::ok().with_headers(&req).body(tokens).to_vec()?,
+                Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
+            },
+            (Some(Method::Get), ["tokens", reference, "redemptions"]) => {
+                match self.issuer.get_redemptions(&from, reference).await? {
+                    Either::Left(redemptions) => Response::ok()
+                        .with_headers(&req)
+                        .body(redemptions)
+                        .to_vec()?,
+                    Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
+                }
+            }
+            (Some(Method::Delete), ["tokens", reference]) => {
+                match self.issuer.revoke_token(&from, reference).await? {
+                    Either::Left(true) => Response::ok().with_headers(&req).to_vec()?,
+                    Either::Left(false) => Response::not_found(
+                        &req,
+                        &format!("No enrollment token found with the reference {reference}"),
+                    )
+                    .to_vec()?,
+                    Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
+                }
+            }
             _ => Response::unknown_path(&req).to_vec()?,
         };
         c.send(return_route, res).await
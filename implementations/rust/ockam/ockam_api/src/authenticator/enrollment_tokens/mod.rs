@@ -4,6 +4,8 @@ mod acceptor_worker;
 mod issuer;
 mod issuer_client;
 mod issuer_worker;
+mod template;
+mod types;
 
 pub use acceptor::*;
 pub use acceptor_client::*;
@@ -11,3 +13,5 @@ pub use acceptor_worker::*;
 pub use issuer::*;
 pub use issuer_client::*;
 pub use issuer_worker::*;
+pub use template::*;
+pub use types::*;
@@ -0,0 +1,166 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+
+use crate::authenticator::direct::OCKAM_ROLE_ATTRIBUTE_KEY;
+
+/// Maximum length of the value of a token parameter
+pub const MAX_TOKEN_PARAMETER_LENGTH: usize = 256;
+
+/// Regular expression matching the `${name}` placeholders of attribute values
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{([A-Za-z0-9_-]+)\}").unwrap());
+
+/// Return true if a parameter name can be used in a `${name}` placeholder
+fn is_parameter_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Return the regular expression validating the whole value of a parameter
+fn parameter_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{pattern})$"))
+        .map_err(|e| format!("Invalid regular expression for a token parameter: {e}"))
+}
+
+/// Check that the attributes of a new token only use declared parameters and that
+/// the regular expressions of the parameters are valid
+pub fn validate_attributes_template(
+    attributes: &BTreeMap<String, String>,
+    parameters: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (name, pattern) in parameters {
+        if !is_parameter_name(name) {
+            return Err(format!("Invalid token parameter name: {name}"));
+        }
+        parameter_regex(pattern)?;
+    }
+
+    for (key, value) in attributes {
+        for captures in PLACEHOLDER.captures_iter(value) {
+            // The role of a member can't be chosen by the enrollee
+            if key == OCKAM_ROLE_ATTRIBUTE_KEY {
+                return Err(format!("The {key} attribute can't use a token parameter"));
+            }
+            let name = &captures[1];
+            if !parameters.contains_key(name) {
+                return Err(format!(
+                    "The {key} attribute uses the undeclared token parameter {name}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replace the placeholders of the attributes of a token with the values supplied by an
+/// enrollee. All the parameters of the token must be supplied, and each value must match
+/// the regular expression of its parameter
+pub fn render_attributes_template(
+    attributes: &BTreeMap<String, String>,
+    parameters: &BTreeMap<String, String>,
+    values: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    // Tokens issued without parameters are not templates
+    if parameters.is_empty() && values.is_empty() {
+        return Ok(attributes.clone());
+    }
+    if let Some(name) = values.keys().find(|name| !parameters.contains_key(*name)) {
+        return Err(format!("Unknown token parameter: {name}"));
+    }
+    for (name, pattern) in parameters {
+        let Some(value) = values.get(name) else {
+            return Err(format!("Missing token parameter: {name}"));
+        };
+        if value.len() > MAX_TOKEN_PARAMETER_LENGTH {
+            return Err(format!("The token parameter {name} is too long"));
+        }
+        if !parameter_regex(pattern)?.is_match(value) {
+            return Err(format!(
+                "The value of the token parameter {name} doesn't match {pattern}"
+            ));
+        }
+    }
+
+    Ok(attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = PLACEHOLDER.replace_all(value, |captures: &Captures| {
+                values
+                    .get(&captures[1])
+                    .cloned()
+                    .unwrap_or_else(|| captures[0].to_string())
+            });
+            (key.clone(), value.to_string())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_attributes_template() {
+        let parameters = BTreeMap::from([("serial".to_string(), "[0-9]{4}".to_string())]);
+        let attributes = BTreeMap::from([
+            ("device".to_string(), "sensor-${serial}".to_string()),
+            ("ockam-relay".to_string(), "${serial}".to_string()),
+        ]);
+        assert!(validate_attributes_template(&attributes, &parameters).is_ok());
+        assert!(validate_attributes_template(&attributes, &BTreeMap::new()).is_err());
+
+        let role = BTreeMap::from([("ockam-role".to_string(), "${serial}".to_string())]);
+        assert!(validate_attributes_template(&role, &parameters).is_err());
+
+        let invalid_regex = BTreeMap::from([("serial".to_string(), "[0-9".to_string())]);
+        assert!(validate_attributes_template(&attributes, &invalid_regex).is_err());
+
+        let invalid_name = BTreeMap::from([("serial}".to_string(), "[0-9]".to_string())]);
+        assert!(validate_attributes_template(&BTreeMap::new(), &invalid_name).is_err());
+    }
+
+    #[test]
+    fn test_render_attributes_template() {
+        let parameters = BTreeMap::from([
+            ("serial".to_string(), "[0-9]{4}".to_string()),
+            ("site".to_string(), "paris|london".to_string()),
+        ]);
+        let attributes = BTreeMap::from([
+            ("device".to_string(), "sensor-${serial}".to_string()),
+            ("location".to_string(), "${site}/${serial}".to_string()),
+            ("component".to_string(), "db".to_string()),
+        ]);
+        let values = |serial: &str, site: &str| {
+            BTreeMap::from([
+                ("serial".to_string(), serial.to_string()),
+                ("site".to_string(), site.to_string()),
+            ])
+        };
+
+        assert_eq!(
+            render_attributes_template(&attributes, &parameters, &values("1234", "paris")),
+            Ok(BTreeMap::from([
+                ("device".to_string(), "sensor-1234".to_string()),
+                ("location".to_string(), "paris/1234".to_string()),
+                ("component".to_string(), "db".to_string()),
+            ]))
+        );
+
+        // the whole value must match the regular expression
+        for (serial, site) in [("12345", "paris"), ("1234", "parisx"), ("x1234", "london")] {
+            assert!(
+                render_attributes_template(&attributes, &parameters, &values(serial, site))
+                    .is_err()
+            );
+        }
+
+        // all the parameters must be supplied, and only them
+        let missing = BTreeMap::from([("serial".to_string(), "1234".to_string())]);
+        assert!(render_attributes_template(&attributes, &parameters, &missing).is_err());
+        let mut unknown = values("1234", "paris");
+        unknown.insert("other".to_string(), "value".to_string());
+        assert!(render_attributes_template(&attributes, &parameters, &unknown).is_err());
+    }
+}
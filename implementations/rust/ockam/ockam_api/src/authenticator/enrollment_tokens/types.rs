@@ -0,0 +1,164 @@
+use minicbor::bytes::ByteArray;
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::{Identifier, TimestampInSeconds};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::authenticator::one_time_code::OneTimeCode;
+use crate::authenticator::{EnrollmentToken, EnrollmentTokenRedemption};
+use crate::colors::color_primary;
+use crate::output::Output;
+use crate::terminal::fmt;
+
+/// Request sent to redeem an enrollment token.
+///
+/// Its encoding is the same as a [`OneTimeCode`] when no parameters are supplied,
+/// so that it is compatible with the authorities which don't support token parameters
+#[derive(Clone, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AcceptToken {
+    #[n(1)] code: ByteArray<32>,
+    #[n(2)] parameters: Option<BTreeMap<String, String>>,
+}
+
+impl AcceptToken {
+    pub fn new(one_time_code: &OneTimeCode) -> Self {
+        AcceptToken {
+            code: (*one_time_code.code()).into(),
+            parameters: None,
+        }
+    }
+
+    pub fn with_parameters(mut self, parameters: BTreeMap<String, String>) -> Self {
+        self.parameters = if parameters.is_empty() {
+            None
+        } else {
+            Some(parameters)
+        };
+        self
+    }
+
+    pub fn one_time_code(&self) -> OneTimeCode {
+        OneTimeCode::from(*self.code)
+    }
+
+    pub fn parameters(&self) -> BTreeMap<String, String> {
+        self.parameters.clone().unwrap_or_default()
+    }
+}
+
+impl Debug for AcceptToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AcceptToken")
+            .field("code", &"{ONE_TIME_CODE}")
+            .field("parameters", &self.parameters)
+            .finish()
+    }
+}
+
+/// Description of an enrollment token which can still be redeemed.
+/// It doesn't contain the one-time code of the token
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollmentTokenInfo {
+    #[n(1)] pub reference: String,
+    #[n(2)] pub issued_by: Identifier,
+    #[n(3)] pub created_at: TimestampInSeconds,
+    #[n(4)] pub expires_at: TimestampInSeconds,
+    #[n(5)] pub remaining_uses: u64,
+    #[n(6)] pub attributes: BTreeMap<String, String>,
+    #[n(7)] pub parameters: BTreeMap<String, String>,
+}
+
+impl From<EnrollmentToken> for EnrollmentTokenInfo {
+    fn from(token: EnrollmentToken) -> Self {
+        EnrollmentTokenInfo {
+            reference: token.reference(),
+            issued_by: token.issued_by,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            remaining_uses: token.ttl_count,
+            attributes: token.attrs,
+            parameters: token.parameters,
+        }
+    }
+}
+
+impl Display for EnrollmentTokenInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Reference: {}", color_primary(&self.reference))?;
+        writeln!(f, "{}Issued by: {}", fmt::INDENTATION, self.issued_by)?;
+        writeln!(
+            f,
+            "{}Expires at: {}",
+            fmt::INDENTATION,
+            format_timestamp(self.expires_at)
+        )?;
+        writeln!(
+            f,
+            "{}Remaining uses: {}",
+            fmt::INDENTATION,
+            color_primary(self.remaining_uses.to_string())
+        )?;
+        write_map(f, "Attributes", &self.attributes)?;
+        write_map(f, "Parameters", &self.parameters)
+    }
+}
+
+impl Output for EnrollmentTokenInfo {
+    fn item(&self) -> crate::Result<String> {
+        Ok(self.padded_display())
+    }
+}
+
+impl Display for EnrollmentTokenRedemption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Redeemed by: {}", color_primary(&self.redeemed_by))?;
+        writeln!(
+            f,
+            "{}Redeemed at: {}",
+            fmt::INDENTATION,
+            format_timestamp(self.redeemed_at)
+        )?;
+        write_map(f, "Attributes", &self.attrs)
+    }
+}
+
+impl Output for EnrollmentTokenRedemption {
+    fn item(&self) -> crate::Result<String> {
+        Ok(self.padded_display())
+    }
+}
+
+fn format_timestamp(timestamp: TimestampInSeconds) -> String {
+    time::OffsetDateTime::from_unix_timestamp(timestamp.0 as i64)
+        .ok()
+        .and_then(|t| {
+            t.format(&time::format_description::well_known::Rfc3339)
+                .ok()
+        })
+        .unwrap_or(timestamp.to_string())
+}
+
+fn write_map(
+    f: &mut Formatter<'_>,
+    title: &str,
+    entries: &BTreeMap<String, String>,
+) -> std::fmt::Result {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "{}{title}:", fmt::INDENTATION)?;
+    for (key, value) in entries {
+        writeln!(
+            f,
+            "{}{}",
+            fmt::INDENTATION.repeat(2),
+            color_primary(format!("{key}={value}"))
+        )?;
+    }
+    Ok(())
+}
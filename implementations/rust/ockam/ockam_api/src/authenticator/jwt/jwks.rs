@@ -0,0 +1,310 @@
+use crate::authority_node::JwtAuthenticatorConfiguration;
+use crate::error::ApiError;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Mutex as SyncMutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Minimum time between two fetches of a JSON Web Key Set, when a token
+/// refers to an unknown key
+pub const MIN_JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Time during which a JSON Web Key Set is not fetched again after a failed fetch
+pub const JWKS_FETCH_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Maximum time to connect to the server of a JSON Web Key Set
+pub const JWKS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum time to fetch a JSON Web Key Set, or the OpenID configuration of its issuer
+pub const JWKS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// This trait retrieves the JSON Web Key Set of a JWT issuer
+#[async_trait]
+pub trait JwksFetcher: Send + Sync + 'static {
+    async fn fetch_jwks(&self) -> Result<JwkSet>;
+}
+
+/// Cache for the JSON Web Key Set of a JWT issuer.
+///
+/// The keys are fetched again after the configured refresh interval, or when a token
+/// refers to an unknown key, since the issuer may have rotated its keys.
+/// The keys are fetched by a single task at a time, and without blocking the tasks
+/// which find their key in the cache. After a failed fetch, the cached keys are used
+/// until the fetch can be retried
+pub struct JwksCache {
+    fetcher: Option<Arc<dyn JwksFetcher>>,
+    refresh_interval: Duration,
+    state: SyncMutex<JwksState>,
+    refreshing: Mutex<()>,
+}
+
+#[derive(Default)]
+struct JwksState {
+    cached: Option<CachedJwks>,
+    failed_at: Option<Instant>,
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+impl JwksCache {
+    /// Create a cache for the key set of the configured issuer
+    pub fn new(configuration: &JwtAuthenticatorConfiguration) -> Result<Self> {
+        Ok(Self::with_fetcher(
+            Arc::new(HttpJwksFetcher::new(
+                &configuration.issuer,
+                configuration.jwks_url.clone(),
+            )?),
+            configuration.jwks_refresh_interval,
+        ))
+    }
+
+    /// Create a cache for a key set retrieved with the given fetcher
+    pub fn with_fetcher(fetcher: Arc<dyn JwksFetcher>, refresh_interval: Duration) -> Self {
+        Self {
+            fetcher: Some(fetcher),
+            refresh_interval,
+            state: SyncMutex::new(JwksState::default()),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Create a cache with a fixed key set, which is never fetched
+    pub fn with_keys(keys: JwkSet) -> Self {
+        Self {
+            fetcher: None,
+            refresh_interval: Duration::MAX,
+            state: SyncMutex::new(JwksState {
+                cached: Some(CachedJwks {
+                    keys,
+                    fetched_at: Instant::now(),
+                }),
+                failed_at: None,
+            }),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Return the key with the given key id.
+    /// If the token doesn't specify a key id, the key set must contain a single key
+    pub async fn get_key(&self, key_id: Option<&str>) -> Result<Option<Jwk>> {
+        let Some(fetcher) = &self.fetcher else {
+            return Ok(self.cached_key(key_id));
+        };
+        if !self.needs_refresh(key_id)? {
+            return Ok(self.cached_key(key_id));
+        }
+
+        // the other tasks needing a refresh wait for the result of the current fetch
+        let _refreshing = self.refreshing.lock().await;
+        if self.needs_refresh(key_id)? {
+            let fetched = fetcher.fetch_jwks().await;
+            let mut state = self.state.lock().unwrap();
+            match fetched {
+                Ok(keys) => {
+                    debug!(keys = keys.keys.len(), "fetched the JWKS of the JWT issuer");
+                    state.cached = Some(CachedJwks {
+                        keys,
+                        fetched_at: Instant::now(),
+                    });
+                    state.failed_at = None;
+                }
+                Err(e) => {
+                    state.failed_at = Some(Instant::now());
+                    // keep using the previous keys if the issuer can't be reached
+                    if state.cached.is_none() {
+                        return Err(e);
+                    }
+                    warn!(%e, "could not refresh the JWKS of the JWT issuer");
+                }
+            }
+        }
+
+        Ok(self.cached_key(key_id))
+    }
+
+    /// Return true if the key set must be fetched to find a key.
+    /// Return an error if the key set could not be fetched recently, and there are no cached keys
+    fn needs_refresh(&self, key_id: Option<&str>) -> Result<bool> {
+        let state = self.state.lock().unwrap();
+        if let Some(failed_at) = state.failed_at {
+            if failed_at.elapsed() < JWKS_FETCH_FAILURE_BACKOFF {
+                return match state.cached {
+                    Some(_) => Ok(false),
+                    None => Err(ApiError::core(
+                        "the JWKS of the JWT issuer is not available",
+                    )),
+                };
+            }
+        }
+
+        Ok(match &state.cached {
+            Some(c) => {
+                let elapsed = c.fetched_at.elapsed();
+                elapsed >= self.refresh_interval
+                    || (find_key(&c.keys, key_id).is_none() && elapsed >= MIN_JWKS_REFRESH_INTERVAL)
+            }
+            None => true,
+        })
+    }
+
+    fn cached_key(&self, key_id: Option<&str>) -> Option<Jwk> {
+        let state = self.state.lock().unwrap();
+        state
+            .cached
+            .as_ref()
+            .and_then(|c| find_key(&c.keys, key_id))
+    }
+}
+
+fn find_key(keys: &JwkSet, key_id: Option<&str>) -> Option<Jwk> {
+    match key_id {
+        Some(key_id) => keys.find(key_id).cloned(),
+        None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+        None => None,
+    }
+}
+
+/// Fetch a JSON Web Key Set over HTTP, from a configured URL or from the URL
+/// advertised by the OpenID configuration of the issuer
+pub struct HttpJwksFetcher {
+    source: JwksSource,
+    client: reqwest::Client,
+}
+
+/// Location of a JSON Web Key Set
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JwksSource {
+    /// URL of the key set
+    Url(String),
+    /// URL of an OpenID configuration containing the URL of the key set
+    Discovery(String),
+}
+
+/// Subset of an OpenID provider configuration
+#[derive(Deserialize)]
+struct OpenIdConfiguration {
+    jwks_uri: String,
+}
+
+impl HttpJwksFetcher {
+    pub fn new(issuer: &str, jwks_url: Option<String>) -> Result<Self> {
+        let source = match jwks_url {
+            Some(url) => JwksSource::Url(url),
+            None => JwksSource::Discovery(format!(
+                "{}/.well-known/openid-configuration",
+                issuer.trim_end_matches('/')
+            )),
+        };
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(JWKS_CONNECT_TIMEOUT)
+            .timeout(JWKS_REQUEST_TIMEOUT)
+            .build()
+            .map_err(ApiError::core)?;
+        Ok(Self { source, client })
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ApiError::core(format!("could not fetch {url}: {e}")))?
+            .json()
+            .await
+            .map_err(|e| ApiError::core(format!("invalid response from {url}: {e}")))
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for HttpJwksFetcher {
+    async fn fetch_jwks(&self) -> Result<JwkSet> {
+        let url = match &self.source {
+            JwksSource::Url(url) => url.clone(),
+            JwksSource::Discovery(url) => self.get_json::<OpenIdConfiguration>(url).await?.jwks_uri,
+        };
+        self.get_json(&url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::jwt::test_support::create_keys;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_serve_the_cached_keys_after_a_failed_fetch() -> Result<()> {
+        let (_, jwks) = create_keys("key-1");
+        let fetcher = Arc::new(FakeFetcher::new(jwks));
+        let cache = JwksCache::with_fetcher(fetcher.clone(), Duration::ZERO);
+
+        assert!(cache.get_key(Some("key-1")).await?.is_some());
+        assert_eq!(fetcher.fetches(), 1);
+
+        // the keys are stale, but the issuer can't be reached
+        fetcher.fail.store(true, Ordering::SeqCst);
+        assert!(cache.get_key(Some("key-1")).await?.is_some());
+        assert_eq!(fetcher.fetches(), 2);
+
+        // the keys are not fetched again until the end of the backoff
+        fetcher.fail.store(false, Ordering::SeqCst);
+        assert!(cache.get_key(Some("key-1")).await?.is_some());
+        assert!(cache.get_key(Some("key-2")).await?.is_none());
+        assert_eq!(fetcher.fetches(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_back_off_when_the_keys_were_never_fetched() -> Result<()> {
+        let (_, jwks) = create_keys("key-1");
+        let fetcher = Arc::new(FakeFetcher::new(jwks));
+        fetcher.fail.store(true, Ordering::SeqCst);
+        let cache = JwksCache::with_fetcher(fetcher.clone(), Duration::ZERO);
+
+        assert!(cache.get_key(Some("key-1")).await.is_err());
+        assert!(cache.get_key(Some("key-1")).await.is_err());
+        assert_eq!(fetcher.fetches(), 1);
+        Ok(())
+    }
+
+    /// Return a fixed key set, or an error when `fail` is set
+    struct FakeFetcher {
+        keys: JwkSet,
+        fail: AtomicBool,
+        fetches: AtomicUsize,
+    }
+
+    impl FakeFetcher {
+        fn new(keys: JwkSet) -> Self {
+            Self {
+                keys,
+                fail: AtomicBool::new(false),
+                fetches: AtomicUsize::new(0),
+            }
+        }
+
+        fn fetches(&self) -> usize {
+            self.fetches.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl JwksFetcher for FakeFetcher {
+        async fn fetch_jwks(&self) -> Result<JwkSet> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                Err(ApiError::core("the issuer can't be reached"))
+            } else {
+                Ok(self.keys.clone())
+            }
+        }
+    }
+}
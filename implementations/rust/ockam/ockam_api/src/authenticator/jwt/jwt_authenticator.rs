@@ -0,0 +1,163 @@
+use either::Either;
+use ockam::identity::models::CredentialAndPurposeKey;
+use ockam::identity::utils::now;
+use ockam::identity::Identifier;
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+
+use crate::authenticator::credential_issuer::CredentialIssuer;
+use crate::authenticator::jwt::JwtValidator;
+use crate::authenticator::{AuthorityMember, AuthorityMembersRepository};
+
+pub struct JwtAuthenticatorError(pub String);
+
+pub type JwtAuthenticatorResult<T> = Either<T, JwtAuthenticatorError>;
+
+/// This struct authenticates identities presenting a JWT signed by a trusted issuer.
+/// The attributes mapped from the token claims replace the attributes of the member, then
+/// a credential is issued with the member attributes
+pub struct JwtAuthenticator {
+    authority: Identifier,
+    members: Arc<dyn AuthorityMembersRepository>,
+    validator: JwtValidator,
+    credential_issuer: CredentialIssuer,
+}
+
+impl JwtAuthenticator {
+    pub fn new(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        validator: JwtValidator,
+        credential_issuer: CredentialIssuer,
+    ) -> Self {
+        Self {
+            authority: authority.clone(),
+            members,
+            validator,
+            credential_issuer,
+        }
+    }
+
+    #[instrument(skip_all, fields(from = %from))]
+    pub async fn authenticate(
+        &self,
+        token: &str,
+        from: &Identifier,
+    ) -> Result<JwtAuthenticatorResult<CredentialAndPurposeKey>> {
+        let claims_attributes = match self.validator.validate(token).await? {
+            Either::Left(attributes) => attributes,
+            Either::Right(error) => return Ok(Either::Right(error)),
+        };
+
+        // The attributes of a member are entirely derived from its last token, so that
+        // the attributes mapped from claims which are not present anymore are removed
+        if let Some(member) = self.members.get_member(&self.authority, from).await? {
+            if member.is_pre_trusted() {
+                warn!(
+                    "{} is a pre-trusted member, its attributes can't be updated",
+                    from
+                );
+                return Ok(Either::Right(JwtAuthenticatorError(
+                    "Pre-trusted members can't be updated".to_string(),
+                )));
+            }
+        }
+        let attributes = claims_attributes
+            .into_iter()
+            .map(|(key, value)| (key.into_bytes(), value.into_bytes()))
+            .collect();
+
+        let member = AuthorityMember::new(
+            from.clone(),
+            attributes,
+            self.authority.clone(),
+            now()?,
+            false,
+        );
+        self.members.add_member(&self.authority, member).await?;
+        info!("Successfully authenticated {} with a JWT", from);
+
+        match self.credential_issuer.issue_credential(from).await? {
+            Some(credential) => Ok(Either::Left(credential)),
+            None => Ok(Either::Right(JwtAuthenticatorError(
+                "Unauthorized member".to_string(),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::jwt::test_support::{create_keys, sign};
+    use crate::authenticator::jwt::JwksCache;
+    use crate::authenticator::AuthorityMembersSqlxDatabase;
+    use crate::authority_node::JwtAuthenticatorConfiguration;
+    use ockam::identity::identities;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    const ISSUER: &str = "https://issuer.example.com";
+
+    #[tokio::test]
+    async fn test_replace_the_attributes_of_a_member() -> Result<()> {
+        let identities = identities().await?;
+        let authority = identities.identities_creation().create_identity().await?;
+        let member = identities.identities_creation().create_identity().await?;
+        let members: Arc<dyn AuthorityMembersRepository> =
+            Arc::new(AuthorityMembersSqlxDatabase::create().await?);
+
+        let (encoding_key, jwks) = create_keys("key-1");
+        let configuration = JwtAuthenticatorConfiguration::new(
+            ISSUER,
+            vec!["ockam".to_string()],
+            BTreeMap::from([
+                ("sub".to_string(), "user".to_string()),
+                ("groups".to_string(), "groups".to_string()),
+            ]),
+        );
+        let authenticator = JwtAuthenticator::new(
+            &authority,
+            members.clone(),
+            JwtValidator::new(&configuration, JwksCache::with_keys(jwks))?,
+            CredentialIssuer::new(
+                members.clone(),
+                identities.identities_attributes(),
+                identities.credentials(),
+                &authority,
+                "project".to_string(),
+                None,
+                None,
+                false,
+            ),
+        );
+
+        let token = sign(
+            &encoding_key,
+            Some("key-1"),
+            json!({
+                "iss": ISSUER,
+                "aud": "ockam",
+                "sub": "alice",
+                "exp": now()?.0 + 60,
+                "groups": ["admin"],
+            }),
+        );
+        assert!(authenticator.authenticate(&token, &member).await?.is_left());
+
+        // the groups claim was removed from the token of the member
+        let token = sign(
+            &encoding_key,
+            Some("key-1"),
+            json!({"iss": ISSUER, "aud": "ockam", "sub": "alice", "exp": now()?.0 + 60}),
+        );
+        assert!(authenticator.authenticate(&token, &member).await?.is_left());
+
+        let member = members.get_member(&authority, &member).await?.unwrap();
+        assert_eq!(
+            member.attributes(),
+            &BTreeMap::from([(b"user".to_vec(), b"alice".to_vec())])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+mod jwks;
+mod jwt_authenticator;
+mod jwt_authenticator_worker;
+mod types;
+mod validator;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use jwks::*;
+pub use jwt_authenticator::*;
+pub use jwt_authenticator_worker::*;
+pub use types::*;
+pub use validator::*;
@@ -0,0 +1,33 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+
+/// Create a P-256 key pair, and a key set containing its public key
+pub(crate) fn create_keys(key_id: &str) -> (EncodingKey, JwkSet) {
+    let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256).unwrap();
+    let encoding_key = EncodingKey::from_ec_pem(key_pair.serialize_pem().as_bytes()).unwrap();
+
+    // the raw public key is 0x04 followed by the x and y coordinates
+    let public_key = key_pair.public_key_raw();
+    let jwks = serde_json::from_value(json!({
+        "keys": [{
+            "kty": "EC",
+            "crv": "P-256",
+            "kid": key_id,
+            "alg": "ES256",
+            "x": URL_SAFE_NO_PAD.encode(&public_key[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&public_key[33..65]),
+        }]
+    }))
+    .unwrap();
+    (encoding_key, jwks)
+}
+
+/// Sign a token with a key created by [`create_keys`]
+pub(crate) fn sign(key: &EncodingKey, key_id: Option<&str>, claims: Value) -> String {
+    let mut header = Header::new(Algorithm::ES256);
+    header.kid = key_id.map(|k| k.to_string());
+    encode(&header, &claims, key).unwrap()
+}
@@ -0,0 +1,27 @@
+use minicbor::{CborLen, Decode, Encode};
+
+/// Request sent to the JWT authenticator to obtain a credential
+#[derive(Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthenticateJwt {
+    #[n(1)] token: String,
+}
+
+impl AuthenticateJwt {
+    pub fn new(token: &str) -> Self {
+        Self {
+            token: token.to_string(),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl std::fmt::Debug for AuthenticateJwt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("{JWT}")
+    }
+}
@@ -0,0 +1,271 @@
+use crate::authenticator::jwt::{JwksCache, JwtAuthenticatorError, JwtAuthenticatorResult};
+use crate::authority_node::JwtAuthenticatorConfiguration;
+use crate::error::ApiError;
+use either::Either;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use ockam_core::Result;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Only asymmetric algorithms are accepted, since the keys of the issuer are public
+pub const ACCEPTED_JWT_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// This struct validates JWTs offline, against the keys of their issuer,
+/// and maps their claims to member attributes
+pub struct JwtValidator {
+    issuer: String,
+    audiences: Vec<String>,
+    leeway: Duration,
+    claims: BTreeMap<String, String>,
+    jwks: JwksCache,
+}
+
+impl JwtValidator {
+    /// Create a validator for the configured issuer.
+    /// Return an error if the configuration does not accept any audience
+    pub fn new(configuration: &JwtAuthenticatorConfiguration, jwks: JwksCache) -> Result<Self> {
+        if configuration.audiences.is_empty() {
+            return Err(ApiError::core(format!(
+                "at least one audience is required to validate the tokens of {}",
+                configuration.issuer
+            )));
+        }
+        Ok(Self {
+            issuer: configuration.issuer.clone(),
+            audiences: configuration.audiences.clone(),
+            leeway: configuration.leeway,
+            claims: configuration.claims.clone(),
+            jwks,
+        })
+    }
+
+    /// Validate a token and return the attributes mapped from its claims
+    #[instrument(skip_all)]
+    pub async fn validate(
+        &self,
+        token: &str,
+    ) -> Result<JwtAuthenticatorResult<BTreeMap<String, String>>> {
+        Ok(self
+            .validate_claims(token)
+            .await?
+            .map_left(|claims| self.map_claims(&claims)))
+    }
+
+    /// Validate a token and return its claims
+    pub async fn validate_claims(&self, token: &str) -> Result<JwtAuthenticatorResult<Value>> {
+        let header = match decode_header(token) {
+            Ok(header) => header,
+            Err(e) => return Ok(rejected(format!("Invalid token: {e}"))),
+        };
+        if !ACCEPTED_JWT_ALGORITHMS.contains(&header.alg) {
+            return Ok(rejected(format!(
+                "Unsupported token algorithm {:?}",
+                header.alg
+            )));
+        }
+
+        let Some(jwk) = self.jwks.get_key(header.kid.as_deref()).await? else {
+            return Ok(rejected("Unknown token signing key"));
+        };
+        let key = match DecodingKey::from_jwk(&jwk) {
+            Ok(key) => key,
+            Err(e) => return Ok(rejected(format!("Invalid token signing key: {e}"))),
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.leeway.as_secs();
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&self.audiences);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+
+        match decode::<Map<String, Value>>(token, &key, &validation) {
+            Ok(token) => Ok(Either::Left(Value::Object(token.claims))),
+            Err(e) => Ok(rejected(format!("Invalid token: {e}"))),
+        }
+    }
+
+    /// Return the attributes mapped from the claims present in the token.
+    /// The elements of an array claim are joined with a comma
+    pub fn map_claims(&self, claims: &Value) -> BTreeMap<String, String> {
+        let mut attributes = BTreeMap::new();
+        for (claim, attribute) in &self.claims {
+            let value = if claim.starts_with('/') {
+                claims.pointer(claim)
+            } else {
+                claims.get(claim)
+            };
+            if let Some(value) = value.and_then(claim_value) {
+                attributes.insert(attribute.clone(), value);
+            }
+        }
+        attributes
+    }
+}
+
+fn claim_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        Value::Array(values) => Some(
+            values
+                .iter()
+                .filter_map(claim_value)
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+pub(crate) fn rejected<T>(reason: impl Into<String>) -> JwtAuthenticatorResult<T> {
+    let reason = reason.into();
+    warn!(%reason, "JWT rejected");
+    Either::Right(JwtAuthenticatorError(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::jwt::test_support::{create_keys, sign};
+    use ockam::identity::utils::now;
+    use serde_json::json;
+
+    const ISSUER: &str = "https://issuer.example.com";
+
+    #[tokio::test]
+    async fn test_validate_token() -> Result<()> {
+        let (encoding_key, jwks) = create_keys("key-1");
+        let validator = JwtValidator::new(
+            &configuration(vec!["ockam".to_string()]),
+            JwksCache::with_keys(jwks),
+        )?;
+
+        let token = sign(
+            &encoding_key,
+            Some("key-1"),
+            json!({
+                "iss": ISSUER,
+                "aud": ["other", "ockam"],
+                "sub": "alice",
+                "exp": now()?.0 + 60,
+                "email_verified": true,
+                "groups": ["admin", "dev"],
+                "realm_access": {"roles": ["operator"]},
+                "ignored": "value",
+            }),
+        );
+        let attributes = validator.validate(&token).await?.left().unwrap();
+        assert_eq!(
+            attributes,
+            BTreeMap::from([
+                ("email-verified".to_string(), "true".to_string()),
+                ("groups".to_string(), "admin,dev".to_string()),
+                ("role".to_string(), "operator".to_string()),
+                ("user".to_string(), "alice".to_string()),
+            ])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_tokens() -> Result<()> {
+        let (encoding_key, jwks) = create_keys("key-1");
+        let (other_key, _) = create_keys("key-1");
+        let validator = JwtValidator::new(
+            &configuration(vec!["ockam".to_string()]),
+            JwksCache::with_keys(jwks),
+        )?;
+        let valid_until = now()?.0 + 60;
+
+        let valid = sign(
+            &encoding_key,
+            Some("key-1"),
+            claims(ISSUER, "ockam", valid_until),
+        );
+        assert!(validator.validate(&valid).await?.is_left());
+
+        let invalid_tokens = vec![
+            "not a token".to_string(),
+            // signed by another key
+            sign(
+                &other_key,
+                Some("key-1"),
+                claims(ISSUER, "ockam", valid_until),
+            ),
+            // unknown key id
+            sign(
+                &encoding_key,
+                Some("key-2"),
+                claims(ISSUER, "ockam", valid_until),
+            ),
+            // wrong issuer
+            sign(
+                &encoding_key,
+                Some("key-1"),
+                claims("https://other.example.com", "ockam", valid_until),
+            ),
+            // wrong audience
+            sign(
+                &encoding_key,
+                Some("key-1"),
+                claims(ISSUER, "other", valid_until),
+            ),
+            // no audience
+            sign(
+                &encoding_key,
+                Some("key-1"),
+                json!({"iss": ISSUER, "sub": "alice", "exp": valid_until}),
+            ),
+            // expired
+            sign(
+                &encoding_key,
+                Some("key-1"),
+                claims(ISSUER, "ockam", now()?.0 - 3600),
+            ),
+        ];
+        for token in invalid_tokens {
+            assert!(validator.validate(&token).await?.is_right(), "{token}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_audience_is_required() {
+        let (_, jwks) = create_keys("key-1");
+        assert!(JwtValidator::new(&configuration(vec![]), JwksCache::with_keys(jwks)).is_err());
+    }
+
+    fn claims(issuer: &str, audience: &str, exp: u64) -> Value {
+        json!({
+            "iss": issuer,
+            "aud": audience,
+            "sub": "alice",
+            "exp": exp,
+        })
+    }
+
+    fn configuration(audiences: Vec<String>) -> JwtAuthenticatorConfiguration {
+        JwtAuthenticatorConfiguration::new(
+            ISSUER,
+            audiences,
+            BTreeMap::from([
+                ("sub".to_string(), "user".to_string()),
+                ("email_verified".to_string(), "email-verified".to_string()),
+                ("groups".to_string(), "groups".to_string()),
+                ("/realm_access/roles".to_string(), "role".to_string()),
+            ]),
+        )
+    }
+}
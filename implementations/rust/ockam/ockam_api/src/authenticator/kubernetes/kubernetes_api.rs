@@ -0,0 +1,117 @@
+use crate::authenticator::jwt::{JwksFetcher, JWKS_CONNECT_TIMEOUT, JWKS_REQUEST_TIMEOUT};
+use crate::error::ApiError;
+use jsonwebtoken::jwk::JwkSet;
+use ockam_core::{async_trait, Result};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Directory where Kubernetes mounts the service account credentials of a pod
+pub const SERVICE_ACCOUNT_DIRECTORY: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// This trait retrieves the pods of a Kubernetes cluster
+#[async_trait]
+pub trait KubernetesPods: Send + Sync + 'static {
+    /// Return a pod, if it exists
+    async fn get_pod(&self, namespace: &str, name: &str) -> Result<Option<KubernetesPod>>;
+}
+
+/// Subset of the metadata of a Kubernetes pod
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct KubernetesPod {
+    pub uid: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct PodResponse {
+    metadata: KubernetesPod,
+}
+
+/// Client for the API server of the Kubernetes cluster running the current pod.
+/// It authenticates with the service account of the pod
+pub struct KubernetesApi {
+    url: String,
+    client: reqwest::Client,
+    token_path: PathBuf,
+}
+
+impl KubernetesApi {
+    /// Create a client from the environment of a pod
+    pub fn in_cluster() -> Result<Self> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST").map_err(|_| {
+            ApiError::core("KUBERNETES_SERVICE_HOST is not set, the authority must run in a pod")
+        })?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or("443".to_string());
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host
+        };
+
+        let directory = Path::new(SERVICE_ACCOUNT_DIRECTORY);
+        let ca = std::fs::read(directory.join("ca.crt"))
+            .map_err(|e| ApiError::core(format!("could not read the cluster CA: {e}")))?;
+        let ca = reqwest::Certificate::from_pem(&ca).map_err(ApiError::core)?;
+        let client = reqwest::ClientBuilder::new()
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(ca)
+            .connect_timeout(JWKS_CONNECT_TIMEOUT)
+            .timeout(JWKS_REQUEST_TIMEOUT)
+            .build()
+            .map_err(ApiError::core)?;
+
+        Ok(Self {
+            url: format!("https://{host}:{port}"),
+            client,
+            token_path: directory.join("token"),
+        })
+    }
+
+    /// Send a GET request to the API server, return None if the resource doesn't exist
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        // the service account token is rotated by the kubelet, so it is read for each request
+        let token = std::fs::read_to_string(&self.token_path).map_err(|e| {
+            ApiError::core(format!("could not read the service account token: {e}"))
+        })?;
+        let response = self
+            .client
+            .get(format!("{}{path}", self.url))
+            .bearer_auth(token.trim())
+            .send()
+            .await
+            .map_err(|e| ApiError::core(format!("could not fetch {path}: {e}")))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response
+            .error_for_status()
+            .map_err(|e| ApiError::core(format!("could not fetch {path}: {e}")))?
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| ApiError::core(format!("invalid response for {path}: {e}")))
+    }
+}
+
+#[async_trait]
+impl JwksFetcher for KubernetesApi {
+    async fn fetch_jwks(&self) -> Result<JwkSet> {
+        self.get_json("/openid/v1/jwks")
+            .await?
+            .ok_or_else(|| ApiError::core("the service account issuer keys are not available"))
+    }
+}
+
+#[async_trait]
+impl KubernetesPods for KubernetesApi {
+    async fn get_pod(&self, namespace: &str, name: &str) -> Result<Option<KubernetesPod>> {
+        let pod: Option<PodResponse> = self
+            .get_json(&format!("/api/v1/namespaces/{namespace}/pods/{name}"))
+            .await?;
+        Ok(pod.map(|p| p.metadata))
+    }
+}
@@ -0,0 +1,465 @@
+use either::Either;
+use ockam::identity::utils::now;
+use ockam::identity::Identifier;
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::authenticator::jwt::{HttpJwksFetcher, JwksCache, JwksFetcher, JwtValidator};
+use crate::authenticator::kubernetes::{KubernetesApi, KubernetesPods};
+use crate::authenticator::{AuthorityMember, AuthorityMembersRepository};
+use crate::authority_node::{JwtAuthenticatorConfiguration, KubernetesAuthenticatorConfiguration};
+use crate::error::ApiError;
+
+/// Attribute containing the namespace of the enrolled workload
+pub const KUBERNETES_NAMESPACE_ATTRIBUTE: &str = "kubernetes-namespace";
+/// Attribute containing the service account of the enrolled workload
+pub const KUBERNETES_SERVICE_ACCOUNT_ATTRIBUTE: &str = "kubernetes-service-account";
+/// Attribute containing the pod of the enrolled workload
+pub const KUBERNETES_POD_ATTRIBUTE: &str = "kubernetes-pod";
+/// Prefix of the attributes containing the labels of the pod of the enrolled workload
+pub const KUBERNETES_LABEL_ATTRIBUTE_PREFIX: &str = "kubernetes-label-";
+
+pub struct KubernetesAuthenticatorError(pub String);
+
+pub type KubernetesAuthenticatorResult<T> = Either<T, KubernetesAuthenticatorError>;
+
+/// Kubernetes specific claims of a service account token
+#[derive(Deserialize)]
+struct ServiceAccountClaims {
+    #[serde(rename = "kubernetes.io")]
+    kubernetes: KubernetesClaims,
+}
+
+#[derive(Deserialize)]
+struct KubernetesClaims {
+    namespace: String,
+    serviceaccount: ObjectReference,
+    pod: Option<ObjectReference>,
+}
+
+#[derive(Deserialize)]
+struct ObjectReference {
+    name: String,
+    uid: String,
+}
+
+/// This struct enrolls identities presenting a projected Kubernetes service account token.
+///
+/// The member attributes are derived from the namespace, service account and pod of the
+/// token, so that workloads can enroll without being given a secret out of band.
+/// The tokens are validated offline against the keys of the cluster. When pods are verified,
+/// the pod bound to the token must still exist, which stands in for a TokenReview
+pub struct KubernetesAuthenticator {
+    authority: Identifier,
+    members: Arc<dyn AuthorityMembersRepository>,
+    validator: JwtValidator,
+    pods: Option<Arc<dyn KubernetesPods>>,
+    service_accounts: Vec<String>,
+    pod_labels: Vec<String>,
+}
+
+impl KubernetesAuthenticator {
+    /// Create an authenticator for the configured service accounts.
+    /// Return an error if no service account is allowed to enroll, or if an entry
+    /// is not `namespace/name`, `namespace/*` or `*/*`
+    pub fn new(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        validator: JwtValidator,
+        pods: Option<Arc<dyn KubernetesPods>>,
+        configuration: &KubernetesAuthenticatorConfiguration,
+    ) -> Result<Self> {
+        if configuration.service_accounts.is_empty() {
+            return Err(ApiError::core(
+                "at least one service account is required to enroll with a Kubernetes token, use `*/*` to allow all of them",
+            ));
+        }
+        for service_account in &configuration.service_accounts {
+            match service_account.split_once('/') {
+                Some(("*", "*")) => {}
+                Some((namespace, name))
+                    if !namespace.is_empty()
+                        && namespace != "*"
+                        && !name.is_empty()
+                        && !name.contains('/') => {}
+                _ => {
+                    return Err(ApiError::core(format!(
+                        "invalid service account {service_account}, expected `namespace/name`, `namespace/*` or `*/*`"
+                    )))
+                }
+            }
+        }
+        Ok(Self {
+            authority: authority.clone(),
+            members,
+            validator,
+            pods,
+            service_accounts: configuration.service_accounts.clone(),
+            pod_labels: configuration.pod_labels.clone(),
+        })
+    }
+
+    /// Create an authenticator retrieving the keys and pods of the cluster as configured.
+    /// The Kubernetes API server is only used if no JWKS URL is configured or if pods are verified
+    pub fn create(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        configuration: &KubernetesAuthenticatorConfiguration,
+    ) -> Result<Self> {
+        let api = if configuration.jwks_url.is_none() || configuration.uses_pods() {
+            Some(Arc::new(KubernetesApi::in_cluster()?))
+        } else {
+            None
+        };
+
+        let fetcher: Arc<dyn JwksFetcher> = match (&configuration.jwks_url, &api) {
+            (None, Some(api)) => api.clone(),
+            (jwks_url, _) => Arc::new(HttpJwksFetcher::new(
+                &configuration.issuer,
+                jwks_url.clone(),
+            )?),
+        };
+        let jwks = JwksCache::with_fetcher(fetcher, configuration.jwks_refresh_interval);
+        let validator = JwtValidator::new(&Self::jwt_configuration(configuration), jwks)?;
+
+        let pods: Option<Arc<dyn KubernetesPods>> = match api {
+            Some(api) if configuration.uses_pods() => Some(api),
+            _ => None,
+        };
+        Self::new(authority, members, validator, pods, configuration)
+    }
+
+    /// Return the configuration used to validate the service account tokens
+    pub fn jwt_configuration(
+        configuration: &KubernetesAuthenticatorConfiguration,
+    ) -> JwtAuthenticatorConfiguration {
+        let mut jwt = JwtAuthenticatorConfiguration::new(
+            &configuration.issuer,
+            vec![configuration.audience.clone()],
+            BTreeMap::new(),
+        );
+        jwt.address = configuration.address.clone();
+        jwt.jwks_url = configuration.jwks_url.clone();
+        jwt.jwks_refresh_interval = configuration.jwks_refresh_interval;
+        jwt.leeway = configuration.leeway;
+        jwt
+    }
+
+    #[instrument(skip_all, fields(from = %from))]
+    pub async fn authenticate(
+        &self,
+        token: &str,
+        from: &Identifier,
+    ) -> Result<KubernetesAuthenticatorResult<()>> {
+        let claims = match self.validator.validate_claims(token).await? {
+            Either::Left(claims) => claims,
+            Either::Right(error) => return Ok(rejected(error.0)),
+        };
+        let claims = match serde_json::from_value::<ServiceAccountClaims>(claims) {
+            Ok(claims) => claims.kubernetes,
+            Err(e) => {
+                return Ok(rejected(format!(
+                    "The token is not a service account token: {e}"
+                )))
+            }
+        };
+
+        let namespace = claims.namespace;
+        let service_account = claims.serviceaccount.name;
+        if !self.is_allowed(&namespace, &service_account) {
+            return Ok(rejected(format!(
+                "The service account {namespace}/{service_account} is not allowed to enroll"
+            )));
+        }
+
+        let mut attributes = BTreeMap::from([
+            (
+                KUBERNETES_NAMESPACE_ATTRIBUTE.to_string(),
+                namespace.clone(),
+            ),
+            (
+                KUBERNETES_SERVICE_ACCOUNT_ATTRIBUTE.to_string(),
+                service_account,
+            ),
+        ]);
+        if let Some(pod) = &claims.pod {
+            attributes.insert(KUBERNETES_POD_ATTRIBUTE.to_string(), pod.name.clone());
+        }
+
+        if let Some(pods) = &self.pods {
+            let Some(pod_reference) = &claims.pod else {
+                return Ok(rejected("The token is not bound to a pod"));
+            };
+            let pod = match pods.get_pod(&namespace, &pod_reference.name).await? {
+                Some(pod) if pod.uid == pod_reference.uid => pod,
+                _ => {
+                    return Ok(rejected(format!(
+                        "The pod {namespace}/{} doesn't exist anymore",
+                        pod_reference.name
+                    )))
+                }
+            };
+            for label in &self.pod_labels {
+                if let Some(value) = pod.labels.get(label) {
+                    attributes.insert(
+                        format!("{KUBERNETES_LABEL_ATTRIBUTE_PREFIX}{label}"),
+                        value.clone(),
+                    );
+                }
+            }
+        }
+
+        // The attributes of a workload are entirely derived from its token
+        if let Some(member) = self.members.get_member(&self.authority, from).await? {
+            if member.is_pre_trusted() {
+                warn!(
+                    "{} is a pre-trusted member, its attributes can't be updated",
+                    from
+                );
+                return Ok(rejected("Pre-trusted members can't be updated"));
+            }
+        }
+        let member = AuthorityMember::new(
+            from.clone(),
+            attributes
+                .into_iter()
+                .map(|(k, v)| (k.into_bytes(), v.into_bytes()))
+                .collect(),
+            self.authority.clone(),
+            now()?,
+            false,
+        );
+        self.members.add_member(&self.authority, member).await?;
+        info!(
+            "Successfully enrolled {} with a service account token of the namespace {}",
+            from, namespace
+        );
+        Ok(Either::Left(()))
+    }
+
+    /// Return true if the service account matches an entry of the allow list
+    fn is_allowed(&self, namespace: &str, service_account: &str) -> bool {
+        self.service_accounts
+            .iter()
+            .any(|allowed| match allowed.split_once('/') {
+                Some(("*", "*")) => true,
+                Some((ns, "*")) => ns == namespace,
+                Some((ns, name)) => ns == namespace && name == service_account,
+                None => false,
+            })
+    }
+}
+
+fn rejected<T>(reason: impl Into<String>) -> KubernetesAuthenticatorResult<T> {
+    let reason = reason.into();
+    warn!(%reason, "service account token rejected");
+    Either::Right(KubernetesAuthenticatorError(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticator::jwt::test_support::{create_keys, sign};
+    use crate::authenticator::kubernetes::KubernetesPod;
+    use crate::authenticator::AuthorityMembersSqlxDatabase;
+    use jsonwebtoken::EncodingKey;
+    use ockam::identity::identities;
+    use ockam_core::async_trait;
+    use serde_json::{json, Value};
+
+    const ISSUER: &str = "https://kubernetes.default.svc.cluster.local";
+    const POD_UID: &str = "2f9e5c1a-8f04-4d0e-9d5f-3c1b7a2e6d40";
+
+    #[tokio::test]
+    async fn test_enroll_with_service_account_token() -> Result<()> {
+        let test = Test::create(vec!["*/*".to_string()], true, vec!["app".to_string()]).await?;
+        let token = test.sign(claims("payments", "api", Some(("api-0", POD_UID))));
+
+        let result = test
+            .authenticator
+            .authenticate(&token, &test.member)
+            .await?;
+        assert!(result.is_left());
+
+        let member = test
+            .members
+            .get_member(&test.authority, &test.member)
+            .await?
+            .unwrap();
+        assert_eq!(
+            member.attributes(),
+            &BTreeMap::from([
+                (b"kubernetes-label-app".to_vec(), b"payments-api".to_vec()),
+                (b"kubernetes-namespace".to_vec(), b"payments".to_vec()),
+                (b"kubernetes-pod".to_vec(), b"api-0".to_vec()),
+                (b"kubernetes-service-account".to_vec(), b"api".to_vec()),
+            ])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_service_accounts_outside_of_the_allow_list() -> Result<()> {
+        let test = Test::create(
+            vec!["payments/api".to_string(), "monitoring/*".to_string()],
+            false,
+            vec![],
+        )
+        .await?;
+
+        for (namespace, service_account, allowed) in [
+            ("payments", "api", true),
+            ("payments", "worker", false),
+            ("monitoring", "prometheus", true),
+            ("default", "api", false),
+        ] {
+            let token = test.sign(claims(namespace, service_account, None));
+            let result = test
+                .authenticator
+                .authenticate(&token, &test.member)
+                .await?;
+            assert_eq!(result.is_left(), allowed, "{namespace}/{service_account}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_require_an_allow_list() -> Result<()> {
+        assert!(Test::create(vec![], false, vec![]).await.is_err());
+        for invalid in ["payments", "*/api", "/api", "payments/", "payments/api/v1"] {
+            assert!(
+                Test::create(vec![invalid.to_string()], false, vec![])
+                    .await
+                    .is_err(),
+                "{invalid}"
+            );
+        }
+        assert!(Test::create(vec!["*/*".to_string()], false, vec![])
+            .await
+            .is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_tokens_of_deleted_pods() -> Result<()> {
+        let test = Test::create(vec!["*/*".to_string()], true, vec![]).await?;
+
+        let invalid_tokens = vec![
+            // not bound to a pod
+            test.sign(claims("payments", "api", None)),
+            // unknown pod
+            test.sign(claims("payments", "api", Some(("api-1", POD_UID)))),
+            // the pod was recreated with the same name
+            test.sign(claims("payments", "api", Some(("api-0", "other-uid")))),
+            // not a service account token
+            test.sign(json!({"iss": ISSUER, "aud": "ockam", "exp": now()?.0 + 60})),
+        ];
+        for token in invalid_tokens {
+            let result = test
+                .authenticator
+                .authenticate(&token, &test.member)
+                .await?;
+            assert!(result.is_right());
+        }
+        assert!(test
+            .members
+            .get_member(&test.authority, &test.member)
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    struct Test {
+        authority: Identifier,
+        member: Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        authenticator: KubernetesAuthenticator,
+        encoding_key: EncodingKey,
+    }
+
+    impl Test {
+        async fn create(
+            service_accounts: Vec<String>,
+            verify_pods: bool,
+            pod_labels: Vec<String>,
+        ) -> Result<Self> {
+            let identities = identities().await?;
+            let authority = identities.identities_creation().create_identity().await?;
+            let member = identities.identities_creation().create_identity().await?;
+            let members: Arc<dyn AuthorityMembersRepository> =
+                Arc::new(AuthorityMembersSqlxDatabase::create().await?);
+
+            let mut configuration = KubernetesAuthenticatorConfiguration::new(ISSUER, "ockam");
+            configuration.service_accounts = service_accounts;
+            configuration.verify_pods = verify_pods;
+            configuration.pod_labels = pod_labels;
+
+            let (encoding_key, jwks) = create_keys("key-1");
+            let validator = JwtValidator::new(
+                &KubernetesAuthenticator::jwt_configuration(&configuration),
+                JwksCache::with_keys(jwks),
+            )?;
+            let pods: Option<Arc<dyn KubernetesPods>> = if configuration.uses_pods() {
+                Some(Arc::new(FakePods))
+            } else {
+                None
+            };
+            let authenticator = KubernetesAuthenticator::new(
+                &authority,
+                members.clone(),
+                validator,
+                pods,
+                &configuration,
+            )?;
+            Ok(Self {
+                authority,
+                member,
+                members,
+                authenticator,
+                encoding_key,
+            })
+        }
+
+        fn sign(&self, claims: Value) -> String {
+            sign(&self.encoding_key, Some("key-1"), claims)
+        }
+    }
+
+    /// Pods of a cluster running a single pod, payments/api-0
+    struct FakePods;
+
+    #[async_trait]
+    impl KubernetesPods for FakePods {
+        async fn get_pod(&self, namespace: &str, name: &str) -> Result<Option<KubernetesPod>> {
+            if (namespace, name) != ("payments", "api-0") {
+                return Ok(None);
+            }
+            Ok(Some(KubernetesPod {
+                uid: POD_UID.to_string(),
+                labels: BTreeMap::from([
+                    ("app".to_string(), "payments-api".to_string()),
+                    ("tier".to_string(), "backend".to_string()),
+                ]),
+            }))
+        }
+    }
+
+    fn claims(namespace: &str, service_account: &str, pod: Option<(&str, &str)>) -> Value {
+        let mut kubernetes = json!({
+            "namespace": namespace,
+            "serviceaccount": {"name": service_account, "uid": "a7c1e9b2"},
+        });
+        if let Some((name, uid)) = pod {
+            kubernetes["pod"] = json!({"name": name, "uid": uid});
+        }
+        json!({
+            "iss": ISSUER,
+            "aud": ["ockam"],
+            "sub": format!("system:serviceaccount:{namespace}:{service_account}"),
+            "exp": now().unwrap().0 + 60,
+            "kubernetes.io": kubernetes,
+        })
+    }
+}
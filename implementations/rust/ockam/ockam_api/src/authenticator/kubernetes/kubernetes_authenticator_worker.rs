@@ -0,0 +1,70 @@
+use crate::authenticator::jwt::AuthenticateJwt;
+use crate::authenticator::kubernetes::KubernetesAuthenticator;
+use either::Either;
+use minicbor::Decoder;
+use ockam::identity::Identifier;
+use ockam_core::api::{Method, RequestHeader, Response};
+use ockam_core::{Result, Routed, SecureChannelLocalInfo, Worker};
+use ockam_node::Context;
+use tracing::trace;
+
+/// This struct runs as a Worker to enroll identities presenting a Kubernetes
+/// service account token
+pub struct KubernetesAuthenticatorWorker {
+    authenticator: KubernetesAuthenticator,
+}
+
+impl KubernetesAuthenticatorWorker {
+    pub fn new(authenticator: KubernetesAuthenticator) -> Self {
+        Self { authenticator }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for KubernetesAuthenticatorWorker {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let secure_channel_info = match SecureChannelLocalInfo::find_info(m.local_message()) {
+            Ok(secure_channel_info) => secure_channel_info,
+            Err(_e) => {
+                let resp = Response::bad_request_no_request("secure channel required").to_vec()?;
+                c.send(m.return_route().clone(), resp).await?;
+                return Ok(());
+            }
+        };
+
+        let from = Identifier::from(secure_channel_info.their_identifier());
+        let return_route = m.return_route().clone();
+        let body = m.into_body()?;
+        let mut dec = Decoder::new(&body);
+        let req: RequestHeader = dec.decode()?;
+        trace! {
+            target: "kubernetes_authenticator",
+            from   = %from,
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+        let res = match (req.method(), req.path()) {
+            (Some(Method::Post), "/") | (Some(Method::Post), "/members") => {
+                // the service account token is sent in the same message as a JWT
+                let request: AuthenticateJwt = dec.decode()?;
+                match self
+                    .authenticator
+                    .authenticate(request.token(), &from)
+                    .await
+                {
+                    Ok(Either::Left(())) => Response::ok().with_headers(&req).to_vec()?,
+                    Ok(Either::Right(error)) => Response::forbidden(&req, &error.0).to_vec()?,
+                    Err(error) => Response::internal_error(&req, &error.to_string()).to_vec()?,
+                }
+            }
+            _ => Response::unknown_path(&req).to_vec()?,
+        };
+        c.send(return_route, res).await
+    }
+}
@@ -0,0 +1,7 @@
+mod kubernetes_api;
+mod kubernetes_authenticator;
+mod kubernetes_authenticator_worker;
+
+pub use kubernetes_api::*;
+pub use kubernetes_authenticator::*;
+pub use kubernetes_authenticator_worker::*;
@@ -1,7 +1,11 @@
 pub mod credential_issuer;
 pub mod direct;
 pub mod enrollment_tokens;
+pub mod jwt;
+pub mod kubernetes;
 pub mod one_time_code;
+pub mod revocation;
+pub mod spiffe;
 
 pub(crate) mod common;
 
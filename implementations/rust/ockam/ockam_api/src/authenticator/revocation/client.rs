@@ -0,0 +1,86 @@
+use miette::IntoDiagnostic;
+
+use ockam::identity::models::{CredentialHash, RevocationListAndPurposeKey};
+use ockam::identity::Identifier;
+use ockam_core::api::Request;
+use ockam_core::async_trait;
+use ockam_node::Context;
+
+use crate::authenticator::revocation::types::Revoke;
+use crate::nodes::service::default_address::DefaultAddress;
+use crate::orchestrator::{AuthorityNodeClient, HasSecureClient};
+
+#[async_trait]
+pub trait RevocationLists {
+    /// Revoke all the credentials issued to a member and remove that member from the project
+    async fn revoke_member(&self, ctx: &Context, identifier: Identifier) -> miette::Result<()>;
+
+    /// Lift the revocation of all the credentials of a member, for example after
+    /// adding that member again to the project
+    async fn unrevoke_member(&self, ctx: &Context, identifier: Identifier) -> miette::Result<()>;
+
+    /// Revoke a specific credential
+    async fn revoke_credential(
+        &self,
+        ctx: &Context,
+        identifier: Identifier,
+        credential_hash: CredentialHash,
+    ) -> miette::Result<()>;
+
+    async fn get_revocation_list(
+        &self,
+        ctx: &Context,
+    ) -> miette::Result<RevocationListAndPurposeKey>;
+}
+
+#[async_trait]
+impl RevocationLists for AuthorityNodeClient {
+    async fn revoke_member(&self, ctx: &Context, identifier: Identifier) -> miette::Result<()> {
+        let req = Request::post("/").body(Revoke::new(identifier));
+        self.get_secure_client()
+            .tell(ctx, DefaultAddress::REVOCATION_LIST, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn unrevoke_member(&self, ctx: &Context, identifier: Identifier) -> miette::Result<()> {
+        let req = Request::delete("/").body(Revoke::new(identifier));
+        self.get_secure_client()
+            .tell(ctx, DefaultAddress::REVOCATION_LIST, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn revoke_credential(
+        &self,
+        ctx: &Context,
+        identifier: Identifier,
+        credential_hash: CredentialHash,
+    ) -> miette::Result<()> {
+        let req = Request::post("/")
+            .body(Revoke::new(identifier).with_credential_hash(credential_hash.to_string()));
+        self.get_secure_client()
+            .tell(ctx, DefaultAddress::REVOCATION_LIST, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn get_revocation_list(
+        &self,
+        ctx: &Context,
+    ) -> miette::Result<RevocationListAndPurposeKey> {
+        let req = Request::get("/");
+        self.get_secure_client()
+            .ask(ctx, DefaultAddress::REVOCATION_LIST, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+}
@@ -0,0 +1,9 @@
+pub mod types;
+
+mod client;
+mod revocation_list_issuer;
+mod revocation_list_worker;
+
+pub use client::*;
+pub use revocation_list_issuer::*;
+pub use revocation_list_worker::*;
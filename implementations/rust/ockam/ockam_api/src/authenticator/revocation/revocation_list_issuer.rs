@@ -0,0 +1,262 @@
+use either::Either;
+
+use ockam::identity::models::{CredentialHash, RevocationListAndPurposeKey, RevokedCredential};
+use ockam::identity::{Credentials, Identifier, IdentitiesAttributes};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::Result;
+
+use crate::authenticator::common::EnrollerAccessControlChecks;
+use crate::authenticator::direct::AccountAuthorityInfo;
+use crate::authenticator::{AuthorityMembersRepository, AuthorityRevocationsRepository};
+
+pub struct RevocationError(pub String);
+
+pub type RevocationResult<T> = Either<T, RevocationError>;
+
+/// This struct maintains the list of credentials revoked by an Authority.
+///
+/// Enrollers can revoke a specific credential, or all the credentials of a member.
+/// The resulting revocation list is signed by the Authority so that it can be distributed
+/// to all the nodes of the project, which then reject the revoked credentials.
+pub struct RevocationListIssuer {
+    authority: Identifier,
+    members: Arc<dyn AuthorityMembersRepository>,
+    revocations: Arc<dyn AuthorityRevocationsRepository>,
+    identities_attributes: Arc<IdentitiesAttributes>,
+    credentials: Arc<Credentials>,
+    account_authority: Option<AccountAuthorityInfo>,
+    // The last signed revocation list. It is re-issued when the version changes
+    last_revocation_list: Arc<RwLock<Option<(u64, RevocationListAndPurposeKey)>>>,
+}
+
+impl RevocationListIssuer {
+    pub fn new(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        revocations: Arc<dyn AuthorityRevocationsRepository>,
+        identities_attributes: Arc<IdentitiesAttributes>,
+        credentials: Arc<Credentials>,
+        account_authority: Option<AccountAuthorityInfo>,
+    ) -> Self {
+        Self {
+            authority: authority.clone(),
+            members,
+            revocations,
+            identities_attributes,
+            credentials,
+            account_authority,
+            last_revocation_list: Default::default(),
+        }
+    }
+
+    /// Revoke a specific credential of a subject, or all its credentials if no credential hash is given.
+    /// When all the credentials are revoked, the subject is also removed from the project members
+    /// so that no new credential can be issued for it.
+    #[instrument(skip_all, fields(enroller = %enroller, subject = %subject))]
+    pub async fn revoke(
+        &self,
+        enroller: &Identifier,
+        subject: &Identifier,
+        credential_hash: Option<CredentialHash>,
+    ) -> Result<RevocationResult<u64>> {
+        let check_enroller = EnrollerAccessControlChecks::check_identifier(
+            &self.authority,
+            self.members.clone(),
+            self.identities_attributes.clone(),
+            enroller,
+            &self.account_authority,
+        )
+        .await?;
+
+        if !check_enroller.is_enroller {
+            warn!(
+                "Non-enroller {} is trying to revoke the credentials of {}",
+                enroller, subject
+            );
+            return Ok(Either::Right(RevocationError(
+                "Non-enroller is trying to revoke a credential".to_string(),
+            )));
+        }
+
+        let check_subject = EnrollerAccessControlChecks::check_is_member(
+            &self.authority,
+            self.members.clone(),
+            subject,
+        )
+        .await?;
+
+        if check_subject.is_pre_trusted {
+            warn!(
+                "Enroller {} is trying to revoke a pre trusted identity {}",
+                enroller, subject
+            );
+            return Ok(Either::Right(RevocationError(
+                "Enroller is trying to revoke a pre trusted identity".to_string(),
+            )));
+        }
+
+        if check_subject.is_enroller && !check_enroller.is_admin {
+            warn!(
+                "Not admin {} is trying to revoke enroller {}",
+                enroller, subject
+            );
+            return Ok(Either::Right(RevocationError(
+                "Not admin is trying to revoke an enroller".to_string(),
+            )));
+        }
+
+        let revokes_subject = credential_hash.is_none();
+        let version = self
+            .revocations
+            .revoke(
+                &self.authority,
+                subject,
+                credential_hash,
+                enroller,
+                check_subject.is_enroller,
+            )
+            .await?;
+
+        if revokes_subject {
+            self.members.delete_member(&self.authority, subject).await?;
+        }
+
+        info!(
+            "Successfully revoked the credentials of {} by {}. Revocation list version: {}",
+            subject, enroller, version
+        );
+        Ok(Either::Left(version))
+    }
+
+    /// Lift the revocation of all the credentials of a subject, so that a member which is
+    /// added again to the project can use its new credentials.
+    /// Return the new version of the revocation list, or None if the subject was not revoked
+    #[instrument(skip_all, fields(enroller = %enroller, subject = %subject))]
+    pub async fn unrevoke(
+        &self,
+        enroller: &Identifier,
+        subject: &Identifier,
+    ) -> Result<RevocationResult<Option<u64>>> {
+        let check_enroller = EnrollerAccessControlChecks::check_identifier(
+            &self.authority,
+            self.members.clone(),
+            self.identities_attributes.clone(),
+            enroller,
+            &self.account_authority,
+        )
+        .await?;
+
+        if !check_enroller.is_enroller {
+            warn!(
+                "Non-enroller {} is trying to lift the revocation of {}",
+                enroller, subject
+            );
+            return Ok(Either::Right(RevocationError(
+                "Non-enroller is trying to lift a revocation".to_string(),
+            )));
+        }
+
+        // a revoked enroller is removed from the members, so its role at the time
+        // of the revocation is checked too
+        if !check_enroller.is_admin {
+            let check_subject = EnrollerAccessControlChecks::check_is_member(
+                &self.authority,
+                self.members.clone(),
+                subject,
+            )
+            .await?;
+            if check_subject.is_enroller
+                || self
+                    .revocations
+                    .is_revoked_enroller(&self.authority, subject)
+                    .await?
+            {
+                warn!(
+                    "Not admin {} is trying to lift the revocation of enroller {}",
+                    enroller, subject
+                );
+                return Ok(Either::Right(RevocationError(
+                    "Not admin is trying to lift the revocation of an enroller".to_string(),
+                )));
+            }
+        }
+
+        let version = self
+            .revocations
+            .unrevoke_subject(&self.authority, subject)
+            .await?;
+
+        match version {
+            Some(version) => info!(
+                "Successfully lifted the revocation of {} by {}. Revocation list version: {}",
+                subject, enroller, version
+            ),
+            None => info!("The credentials of {} were not revoked", subject),
+        }
+        Ok(Either::Left(version))
+    }
+
+    /// Return true if an identity can subscribe to the revocation lists.
+    /// It must be a member of the project, or have presented a credential issued by the Authority
+    pub async fn can_subscribe(&self, subscriber: &Identifier) -> Result<bool> {
+        let check_subscriber = EnrollerAccessControlChecks::check_identifier(
+            &self.authority,
+            self.members.clone(),
+            self.identities_attributes.clone(),
+            subscriber,
+            &self.account_authority,
+        )
+        .await?;
+        if check_subscriber.is_member {
+            return Ok(true);
+        }
+
+        Ok(self
+            .identities_attributes
+            .get_attributes(subscriber, &self.authority)
+            .await?
+            .is_some())
+    }
+
+    /// Return the current revocation list, signed by the Authority
+    #[instrument(skip_all)]
+    pub async fn get_revocation_list(&self) -> Result<RevocationListAndPurposeKey> {
+        let version = self.revocations.get_version(&self.authority).await?;
+        if let Some((last_version, revocation_list)) =
+            self.last_revocation_list.read().unwrap().as_ref()
+        {
+            if *last_version == version {
+                return Ok(revocation_list.clone());
+            }
+        }
+
+        let mut revoked_subjects = vec![];
+        let mut revoked_credentials = vec![];
+        let mut version = version;
+        for revocation in self.revocations.get_revocations(&self.authority).await? {
+            // a revocation could be added between the two queries
+            version = version.max(revocation.version());
+            match revocation.credential_hash() {
+                Some(credential_hash) => revoked_credentials.push(RevokedCredential {
+                    subject: revocation.subject().clone(),
+                    credential_hash: credential_hash.clone(),
+                }),
+                None => revoked_subjects.push(revocation.subject().clone()),
+            }
+        }
+
+        let revocation_list = self
+            .credentials
+            .credentials_creation()
+            .issue_revocation_list(
+                &self.authority,
+                version,
+                revoked_subjects,
+                revoked_credentials,
+            )
+            .await?;
+
+        *self.last_revocation_list.write().unwrap() = Some((version, revocation_list.clone()));
+        Ok(revocation_list)
+    }
+}
@@ -0,0 +1,223 @@
+use either::Either;
+use minicbor::Decoder;
+use tracing::{debug, trace, warn};
+
+use ockam::identity::models::CredentialHash;
+use ockam::identity::{Credentials, Identifier, IdentitiesAttributes};
+use ockam_core::api::{Method, RequestHeader, Response};
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Result, Route, Routed, SecureChannelLocalInfo, Worker};
+use ockam_node::Context;
+use std::time::{Duration, Instant};
+
+use crate::authenticator::direct::AccountAuthorityInfo;
+use crate::authenticator::revocation::types::Revoke;
+use crate::authenticator::revocation::RevocationListIssuer;
+use crate::authenticator::{AuthorityMembersRepository, AuthorityRevocationsRepository};
+
+/// Maximum number of nodes subscribed to the revocation lists of an Authority
+pub const MAX_REVOCATION_LIST_SUBSCRIBERS: usize = 10_000;
+
+/// A subscription to the revocation lists expires when it is not renewed during this time.
+/// Nodes renew their subscription every minute by default
+pub const REVOCATION_LIST_SUBSCRIPTION_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// This struct runs as a Worker to serve the Authority revocation list
+/// and to let enrollers revoke credentials, or lift the revocation of a member.
+///
+/// The members of the project can also subscribe to the revocation list. They are then sent
+/// the current list, and every new list as soon as a credential is revoked.
+///
+/// When several replicas of the Authority share the same database, a new list is only pushed
+/// to the subscribers of the replica where the revocation was made. The subscribers of the other
+/// replicas receive it when they renew their subscription.
+pub struct RevocationListWorker {
+    issuer: RevocationListIssuer,
+    subscribers: HashMap<Identifier, Subscriber>,
+}
+
+/// Return route of a subscribed node, and the time of its last subscription
+struct Subscriber {
+    route: Route,
+    subscribed_at: Instant,
+}
+
+impl RevocationListWorker {
+    pub fn new(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        revocations: Arc<dyn AuthorityRevocationsRepository>,
+        identities_attributes: Arc<IdentitiesAttributes>,
+        credentials: Arc<Credentials>,
+        account_authority: Option<AccountAuthorityInfo>,
+    ) -> Self {
+        Self {
+            issuer: RevocationListIssuer::new(
+                authority,
+                members,
+                revocations,
+                identities_attributes,
+                credentials,
+                account_authority,
+            ),
+            subscribers: Default::default(),
+        }
+    }
+
+    /// Add or renew a subscription, after removing the expired ones.
+    /// Return false if there are already too many subscribers
+    fn subscribe(&mut self, subscriber: Identifier, route: Route) -> bool {
+        self.remove_expired_subscribers();
+        if !self.subscribers.contains_key(&subscriber)
+            && self.subscribers.len() >= MAX_REVOCATION_LIST_SUBSCRIBERS
+        {
+            return false;
+        }
+        self.subscribers.insert(
+            subscriber,
+            Subscriber {
+                route,
+                subscribed_at: Instant::now(),
+            },
+        );
+        true
+    }
+
+    fn remove_expired_subscribers(&mut self) {
+        self.subscribers
+            .retain(|_, s| s.subscribed_at.elapsed() < REVOCATION_LIST_SUBSCRIPTION_TTL);
+    }
+
+    /// Send the current revocation list to all the subscribers
+    async fn publish_revocation_list(&mut self, c: &Context) {
+        self.remove_expired_subscribers();
+        let subscribers = self.subscribers.keys().cloned().collect();
+        self.send_revocation_list(c, subscribers).await
+    }
+
+    /// Send the current revocation list to some subscribers.
+    /// The subscribers which can't be reached anymore are removed
+    async fn send_revocation_list(&mut self, c: &Context, subscribers: Vec<Identifier>) {
+        let revocation_list = match self.issuer.get_revocation_list().await {
+            Ok(revocation_list) => revocation_list,
+            Err(error) => {
+                warn!(%error, "cannot issue the revocation list");
+                return;
+            }
+        };
+        let revocation_list = match ockam_core::cbor_encode_preallocate(&revocation_list) {
+            Ok(revocation_list) => revocation_list,
+            Err(error) => {
+                warn!(%error, "cannot encode the revocation list");
+                return;
+            }
+        };
+
+        for subscriber in subscribers {
+            let Some(route) = self.subscribers.get(&subscriber).map(|s| s.route.clone()) else {
+                continue;
+            };
+            if let Err(error) = c.send(route, revocation_list.clone()).await {
+                debug!(%subscriber, %error, "removing a revocation list subscriber");
+                self.subscribers.remove(&subscriber);
+            }
+        }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for RevocationListWorker {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let secure_channel_info = match SecureChannelLocalInfo::find_info(m.local_message()) {
+            Ok(secure_channel_info) => secure_channel_info,
+            Err(_e) => {
+                let resp = Response::bad_request_no_request("secure channel required").to_vec()?;
+                c.send(m.return_route().clone(), resp).await?;
+                return Ok(());
+            }
+        };
+
+        let from = Identifier::from(secure_channel_info.their_identifier());
+        let return_route = m.return_route().clone();
+        let body = m.into_body()?;
+        let mut dec = Decoder::new(&body);
+        let req: RequestHeader = dec.decode()?;
+        trace! {
+            target: "revocation_list",
+            from   = %from,
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+        let path_segments = req.path_segments::<5>();
+        let res = match (req.method(), path_segments.as_slice()) {
+            (Some(Method::Get), [""]) => match self.issuer.get_revocation_list().await {
+                Ok(revocation_list) => Response::ok()
+                    .with_headers(&req)
+                    .body(revocation_list)
+                    .to_vec()?,
+                Err(error) => Response::internal_error(&req, &error.to_string()).to_vec()?,
+            },
+            (Some(Method::Post), ["subscribe"]) => {
+                if !self.issuer.can_subscribe(&from).await? {
+                    warn!(%from, "Non-member is trying to subscribe to the revocation lists");
+                    Response::forbidden(&req, "Only members can subscribe to the revocation lists")
+                        .to_vec()?
+                } else if !self.subscribe(from.clone(), return_route.clone()) {
+                    warn!(%from, "Too many subscribers to the revocation lists");
+                    Response::forbidden(&req, "Too many subscribers to the revocation lists")
+                        .to_vec()?
+                } else {
+                    // the subscriber is answered with the current revocation list
+                    self.send_revocation_list(c, vec![from]).await;
+                    return Ok(());
+                }
+            }
+            (Some(Method::Post), [""]) => {
+                let revoke: Revoke = dec.decode()?;
+                let credential_hash = match revoke.credential_hash() {
+                    Some(hash) => match CredentialHash::try_from(hash.as_str()) {
+                        Ok(hash) => Some(hash),
+                        Err(error) => {
+                            let resp = Response::bad_request(&req, &error.to_string()).to_vec()?;
+                            return c.send(return_route, resp).await;
+                        }
+                    },
+                    None => None,
+                };
+                let res = self
+                    .issuer
+                    .revoke(&from, revoke.subject(), credential_hash)
+                    .await?;
+                match res {
+                    Either::Left(_) => {
+                        self.publish_revocation_list(c).await;
+                        Response::ok().with_headers(&req).to_vec()?
+                    }
+                    Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
+                }
+            }
+            (Some(Method::Delete), [""]) => {
+                let revoke: Revoke = dec.decode()?;
+                match self.issuer.unrevoke(&from, revoke.subject()).await? {
+                    Either::Left(version) => {
+                        if version.is_some() {
+                            self.publish_revocation_list(c).await;
+                        }
+                        Response::ok().with_headers(&req).to_vec()?
+                    }
+                    Either::Right(error) => Response::forbidden(&req, &error.0).to_vec()?,
+                }
+            }
+            _ => Response::unknown_path(&req).to_vec()?,
+        };
+
+        c.send(return_route, res).await
+    }
+}
@@ -0,0 +1,35 @@
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::Identifier;
+
+/// Request to revoke all the credentials of a subject
+/// or one specific credential when a credential hash is provided.
+/// It is also used to lift the revocation of all the credentials of a subject
+#[derive(Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Revoke {
+    #[n(1)] subject: Identifier,
+    #[n(2)] credential_hash: Option<String>,
+}
+
+impl Revoke {
+    pub fn new(subject: Identifier) -> Self {
+        Revoke {
+            subject,
+            credential_hash: None,
+        }
+    }
+
+    pub fn with_credential_hash(mut self, credential_hash: String) -> Self {
+        self.credential_hash = Some(credential_hash);
+        self
+    }
+
+    pub fn subject(&self) -> &Identifier {
+        &self.subject
+    }
+
+    pub fn credential_hash(&self) -> Option<&String> {
+        self.credential_hash.as_ref()
+    }
+}
@@ -0,0 +1,11 @@
+mod spiffe_authenticator;
+mod spiffe_authenticator_worker;
+mod spiffe_id;
+mod svid_validator;
+mod types;
+
+pub use spiffe_authenticator::*;
+pub use spiffe_authenticator_worker::*;
+pub use spiffe_id::*;
+pub use svid_validator::*;
+pub use types::*;
@@ -0,0 +1,153 @@
+use either::Either;
+use ockam::identity::models::CredentialAndPurposeKey;
+use ockam::identity::utils::now;
+use ockam::identity::Identifier;
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use std::collections::BTreeMap;
+
+use crate::authenticator::credential_issuer::CredentialIssuer;
+use crate::authenticator::spiffe::{svid_proof_of_possession, SpiffeId, SvidValidator};
+use crate::authenticator::{AuthorityMember, AuthorityMembersRepository};
+
+/// Attribute containing the SPIFFE ID of the member, for example `spiffe://example.org/api`
+pub const SPIFFE_ID_ATTRIBUTE: &str = "spiffe_id";
+/// Attribute containing the trust domain of the SPIFFE ID, for example `example.org`
+pub const SPIFFE_TRUST_DOMAIN_ATTRIBUTE: &str = "spiffe_trust_domain";
+/// Attribute containing the path of the SPIFFE ID, for example `/ns/prod/sa/api`
+pub const SPIFFE_PATH_ATTRIBUTE: &str = "spiffe_path";
+/// Prefix of the attributes containing the SPIFFE ID attributes
+const SPIFFE_ATTRIBUTE_PREFIX: &str = "spiffe_";
+
+pub struct SpiffeAuthenticatorError(pub String);
+
+pub type SpiffeAuthenticatorResult<T> = Either<T, SpiffeAuthenticatorError>;
+
+/// This struct authenticates identities presenting an X.509-SVID of a trusted SPIFFE
+/// trust domain, and a proof of possession of its private key.
+///
+/// The SPIFFE ID and its path segments are stored as member attributes, then a credential
+/// is issued with the member attributes. For example, the ID `spiffe://example.org/ns/prod`
+/// gives the attributes `spiffe_id`, `spiffe_trust_domain=example.org`, `spiffe_path=/ns/prod`,
+/// `spiffe_path.0=ns` and `spiffe_path.1=prod`
+pub struct SpiffeAuthenticator {
+    authority: Identifier,
+    members: Arc<dyn AuthorityMembersRepository>,
+    validator: SvidValidator,
+    credential_issuer: CredentialIssuer,
+}
+
+impl SpiffeAuthenticator {
+    pub fn new(
+        authority: &Identifier,
+        members: Arc<dyn AuthorityMembersRepository>,
+        validator: SvidValidator,
+        credential_issuer: CredentialIssuer,
+    ) -> Self {
+        Self {
+            authority: authority.clone(),
+            members,
+            validator,
+            credential_issuer,
+        }
+    }
+
+    #[instrument(skip_all, fields(from = %from))]
+    pub async fn authenticate(
+        &self,
+        certificates: &[&[u8]],
+        signature: &[u8],
+        from: &Identifier,
+    ) -> Result<SpiffeAuthenticatorResult<CredentialAndPurposeKey>> {
+        let message = svid_proof_of_possession(&self.authority, from);
+        let spiffe_id = match self.validator.validate(certificates, &message, signature)? {
+            Either::Left(spiffe_id) => spiffe_id,
+            Either::Right(error) => return Ok(Either::Right(error)),
+        };
+
+        // The SPIFFE attributes replace the attributes of a previous SVID, other attributes
+        // of an existing member are kept
+        let mut attributes = match self.members.get_member(&self.authority, from).await? {
+            Some(member) if member.is_pre_trusted() => {
+                warn!(
+                    "{} is a pre-trusted member, its attributes can't be updated",
+                    from
+                );
+                return Ok(Either::Right(SpiffeAuthenticatorError(
+                    "Pre-trusted members can't be updated".to_string(),
+                )));
+            }
+            Some(member) => member.attributes().clone(),
+            None => Default::default(),
+        };
+        attributes.retain(|key, _| !key.starts_with(SPIFFE_ATTRIBUTE_PREFIX.as_bytes()));
+        for (key, value) in spiffe_attributes(&spiffe_id) {
+            attributes.insert(key.into_bytes(), value.into_bytes());
+        }
+
+        let member = AuthorityMember::new(
+            from.clone(),
+            attributes,
+            self.authority.clone(),
+            now()?,
+            false,
+        );
+        self.members.add_member(&self.authority, member).await?;
+        info!("Successfully authenticated {} as {}", from, spiffe_id);
+
+        match self.credential_issuer.issue_credential(from).await? {
+            Some(credential) => Ok(Either::Left(credential)),
+            None => Ok(Either::Right(SpiffeAuthenticatorError(
+                "Unauthorized member".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Return the member attributes derived from a SPIFFE ID
+pub fn spiffe_attributes(spiffe_id: &SpiffeId) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::from([
+        (SPIFFE_ID_ATTRIBUTE.to_string(), spiffe_id.to_string()),
+        (
+            SPIFFE_TRUST_DOMAIN_ATTRIBUTE.to_string(),
+            spiffe_id.trust_domain().to_string(),
+        ),
+        (
+            SPIFFE_PATH_ATTRIBUTE.to_string(),
+            spiffe_id.path().to_string(),
+        ),
+    ]);
+    for (index, segment) in spiffe_id.segments().enumerate() {
+        attributes.insert(
+            format!("{SPIFFE_PATH_ATTRIBUTE}.{index}"),
+            segment.to_string(),
+        );
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_spiffe_attributes() {
+        let spiffe_id = SpiffeId::from_str("spiffe://example.org/ns/prod/sa/api").unwrap();
+        assert_eq!(
+            spiffe_attributes(&spiffe_id),
+            BTreeMap::from([
+                (
+                    "spiffe_id".to_string(),
+                    "spiffe://example.org/ns/prod/sa/api".to_string()
+                ),
+                ("spiffe_path".to_string(), "/ns/prod/sa/api".to_string()),
+                ("spiffe_path.0".to_string(), "ns".to_string()),
+                ("spiffe_path.1".to_string(), "prod".to_string()),
+                ("spiffe_path.2".to_string(), "sa".to_string()),
+                ("spiffe_path.3".to_string(), "api".to_string()),
+                ("spiffe_trust_domain".to_string(), "example.org".to_string()),
+            ])
+        );
+    }
+}
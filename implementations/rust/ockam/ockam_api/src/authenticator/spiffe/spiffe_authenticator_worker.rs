@@ -0,0 +1,69 @@
+use crate::authenticator::spiffe::{AuthenticateSvid, SpiffeAuthenticator};
+use either::Either;
+use minicbor::Decoder;
+use ockam::identity::Identifier;
+use ockam_core::api::{Method, RequestHeader, Response};
+use ockam_core::{Result, Routed, SecureChannelLocalInfo, Worker};
+use ockam_node::Context;
+use tracing::trace;
+
+/// This struct runs as a Worker to issue credentials to identities presenting a valid X.509-SVID
+pub struct SpiffeAuthenticatorWorker {
+    authenticator: SpiffeAuthenticator,
+}
+
+impl SpiffeAuthenticatorWorker {
+    pub fn new(authenticator: SpiffeAuthenticator) -> Self {
+        Self { authenticator }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for SpiffeAuthenticatorWorker {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let secure_channel_info = match SecureChannelLocalInfo::find_info(m.local_message()) {
+            Ok(secure_channel_info) => secure_channel_info,
+            Err(_e) => {
+                let resp = Response::bad_request_no_request("secure channel required").to_vec()?;
+                c.send(m.return_route().clone(), resp).await?;
+                return Ok(());
+            }
+        };
+
+        let from = Identifier::from(secure_channel_info.their_identifier());
+        let return_route = m.return_route().clone();
+        let body = m.into_body()?;
+        let mut dec = Decoder::new(&body);
+        let req: RequestHeader = dec.decode()?;
+        trace! {
+            target: "spiffe_authenticator",
+            from   = %from,
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+        let res = match (req.method(), req.path()) {
+            (Some(Method::Post), "/") | (Some(Method::Post), "/credential") => {
+                let request: AuthenticateSvid = dec.decode()?;
+                match self
+                    .authenticator
+                    .authenticate(&request.certificates(), request.signature(), &from)
+                    .await
+                {
+                    Ok(Either::Left(crd)) => {
+                        Response::ok().with_headers(&req).body(crd).to_vec()?
+                    }
+                    Ok(Either::Right(error)) => Response::forbidden(&req, &error.0).to_vec()?,
+                    Err(error) => Response::internal_error(&req, &error.to_string()).to_vec()?,
+                }
+            }
+            _ => Response::unknown_path(&req).to_vec()?,
+        };
+        c.send(return_route, res).await
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::ApiError;
+use ockam_core::compat::fmt::{Display, Formatter};
+use ockam_core::Error;
+use std::str::FromStr;
+
+/// Scheme of the SPIFFE IDs
+pub const SPIFFE_SCHEME: &str = "spiffe://";
+
+/// Identity of a workload, as defined by the SPIFFE specification,
+/// for example `spiffe://example.org/ns/prod/sa/api`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpiffeId {
+    trust_domain: String,
+    path: String,
+}
+
+impl SpiffeId {
+    /// Trust domain of the workload, for example `example.org`
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// Path of the workload in its trust domain, for example `/ns/prod/sa/api`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Segments of the path, for example `ns`, `prod`, `sa` and `api`
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.path.split('/').skip(1)
+    }
+}
+
+impl Display for SpiffeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{SPIFFE_SCHEME}{}{}", self.trust_domain, self.path)
+    }
+}
+
+/// Parse the SPIFFE ID of a workload, which must have a path
+impl FromStr for SpiffeId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ApiError::core(format!("invalid SPIFFE ID {s}: {reason}"));
+
+        let Some(id) = s.strip_prefix(SPIFFE_SCHEME) else {
+            return Err(invalid("the scheme must be spiffe"));
+        };
+        let (trust_domain, path) = match id.find('/') {
+            Some(index) => id.split_at(index),
+            None => (id, ""),
+        };
+
+        if trust_domain.is_empty() {
+            return Err(invalid("the trust domain is missing"));
+        }
+        if !trust_domain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_'))
+        {
+            return Err(invalid("the trust domain contains invalid characters"));
+        }
+
+        if path.is_empty() {
+            return Err(invalid("the path of the workload is missing"));
+        }
+        for segment in path.split('/').skip(1) {
+            if segment.is_empty() || segment == "." || segment == ".." {
+                return Err(invalid("the path contains an empty or relative segment"));
+            }
+            if !segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+            {
+                return Err(invalid("the path contains invalid characters"));
+            }
+        }
+
+        Ok(SpiffeId {
+            trust_domain: trust_domain.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spiffe_id() {
+        let id = SpiffeId::from_str("spiffe://example.org/ns/prod/sa/api").unwrap();
+        assert_eq!(id.trust_domain(), "example.org");
+        assert_eq!(id.path(), "/ns/prod/sa/api");
+        assert_eq!(
+            id.segments().collect::<Vec<_>>(),
+            vec!["ns", "prod", "sa", "api"]
+        );
+        assert_eq!(id.to_string(), "spiffe://example.org/ns/prod/sa/api");
+
+        for invalid in [
+            "https://example.org/api",
+            "spiffe://",
+            "spiffe:///api",
+            "spiffe://example.org",
+            "spiffe://example.org/",
+            "spiffe://example.org/ns//api",
+            "spiffe://example.org/ns/../api",
+            "spiffe://Example.org/api",
+            "spiffe://example.org/api?query",
+            "spiffe://user@example.org/api",
+        ] {
+            assert!(SpiffeId::from_str(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::authenticator::spiffe::{SpiffeAuthenticatorError, SpiffeAuthenticatorResult, SpiffeId};
+use crate::error::ApiError;
+use either::Either;
+use ockam::tcp::TlsClientCertificate;
+use ockam_core::Result;
+use rustls_pki_types::{CertificateDer, SignatureVerificationAlgorithm, TrustAnchor, UnixTime};
+use std::io::BufReader;
+use std::str::FromStr;
+use webpki::{EndEntityCert, KeyUsage};
+
+/// Signature algorithms accepted for the certificates of a trust domain and for the proofs
+/// of possession of the X.509-SVIDs
+static SVID_SIGNATURE_ALGORITHMS: &[&dyn SignatureVerificationAlgorithm] = &[
+    webpki::ring::ECDSA_P256_SHA256,
+    webpki::ring::ECDSA_P256_SHA384,
+    webpki::ring::ECDSA_P384_SHA256,
+    webpki::ring::ECDSA_P384_SHA384,
+    webpki::ring::ED25519,
+    webpki::ring::RSA_PKCS1_2048_8192_SHA256,
+    webpki::ring::RSA_PKCS1_2048_8192_SHA384,
+    webpki::ring::RSA_PKCS1_2048_8192_SHA512,
+    webpki::ring::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    webpki::ring::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    webpki::ring::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+];
+
+/// This struct validates X.509-SVIDs against the X.509 authorities of a SPIFFE trust domain
+pub struct SvidValidator {
+    trust_domain: String,
+    trust_anchors: Vec<TrustAnchor<'static>>,
+}
+
+impl SvidValidator {
+    /// Create a validator for a trust domain, from its PEM encoded trust bundle
+    pub fn new(trust_domain: &str, trust_bundle_pem: &[u8]) -> Result<Self> {
+        let mut reader = BufReader::new(trust_bundle_pem);
+        let certificates = rustls_pemfile::certs(&mut reader)
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(|e| ApiError::core(format!("invalid SPIFFE trust bundle: {e}")))?;
+        if certificates.is_empty() {
+            return Err(ApiError::core(
+                "No certificate found in the SPIFFE trust bundle",
+            ));
+        }
+
+        let trust_anchors = certificates
+            .iter()
+            .map(|c| webpki::anchor_from_trusted_cert(c).map(|a| a.to_owned()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| ApiError::core(format!("invalid SPIFFE trust bundle: {e:?}")))?;
+
+        Ok(Self {
+            trust_domain: trust_domain.to_string(),
+            trust_anchors,
+        })
+    }
+
+    /// Validate an X.509-SVID, followed by its intermediate certificates, and the signature
+    /// of a message with its private key. Return the SPIFFE ID of the SVID
+    #[instrument(skip_all)]
+    pub fn validate(
+        &self,
+        certificates: &[&[u8]],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<SpiffeAuthenticatorResult<SpiffeId>> {
+        let Some((svid, intermediates)) = certificates.split_first() else {
+            return Ok(rejected("No X.509-SVID was provided"));
+        };
+        let svid = CertificateDer::from(*svid);
+        let intermediates: Vec<CertificateDer> = intermediates
+            .iter()
+            .map(|c| CertificateDer::from(*c))
+            .collect();
+
+        let end_entity = match EndEntityCert::try_from(&svid) {
+            Ok(end_entity) => end_entity,
+            Err(e) => return Ok(rejected(format!("Invalid X.509-SVID: {e:?}"))),
+        };
+        // a CA certificate is rejected as an end entity, as required for X.509-SVIDs
+        if let Err(e) = end_entity.verify_for_usage(
+            SVID_SIGNATURE_ALGORITHMS,
+            &self.trust_anchors,
+            &intermediates,
+            UnixTime::now(),
+            KeyUsage::client_auth(),
+            None,
+            None,
+        ) {
+            return Ok(rejected(format!(
+                "The X.509-SVID is not trusted by the trust domain {}: {e:?}",
+                self.trust_domain
+            )));
+        }
+
+        // the SPIFFE ID is the single URI of the subject alternative names
+        let uris = TlsClientCertificate::from_der(svid.as_ref())?.uris;
+        let spiffe_id = match uris.as_slice() {
+            [uri] => match SpiffeId::from_str(uri) {
+                Ok(spiffe_id) => spiffe_id,
+                Err(e) => return Ok(rejected(e.to_string())),
+            },
+            _ => return Ok(rejected("An X.509-SVID must contain exactly one URI")),
+        };
+        if spiffe_id.trust_domain() != self.trust_domain {
+            return Ok(rejected(format!(
+                "The SPIFFE ID {spiffe_id} doesn't belong to the trust domain {}",
+                self.trust_domain
+            )));
+        }
+
+        if !SVID_SIGNATURE_ALGORITHMS.iter().any(|algorithm| {
+            end_entity
+                .verify_signature(*algorithm, message, signature)
+                .is_ok()
+        }) {
+            return Ok(rejected(format!(
+                "Invalid proof of possession of the X.509-SVID of {spiffe_id}"
+            )));
+        }
+
+        Ok(Either::Left(spiffe_id))
+    }
+}
+
+pub(crate) fn rejected<T>(reason: impl Into<String>) -> SpiffeAuthenticatorResult<T> {
+    let reason = reason.into();
+    warn!(%reason, "X.509-SVID rejected");
+    Either::Right(SpiffeAuthenticatorError(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::{Signature, SigningKey};
+    use p256::pkcs8::DecodePrivateKey;
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa, KeyPair, SanType};
+
+    const MESSAGE: &[u8] = b"proof of possession";
+
+    #[test]
+    fn test_validate_svid() -> Result<()> {
+        let (root, root_key) = create_ca("root", None);
+        let (intermediate, intermediate_key) = create_ca("intermediate", Some((&root, &root_key)));
+        let validator = SvidValidator::new("example.org", root.pem().as_bytes())?;
+
+        // an SVID issued by the root
+        let (svid, svid_key) = create_svid("spiffe://example.org/ns/prod/sa/api", &root, &root_key);
+        let spiffe_id = validator
+            .validate(&[der(&svid)], MESSAGE, &sign(&svid_key, MESSAGE))?
+            .left()
+            .unwrap();
+        assert_eq!(spiffe_id.to_string(), "spiffe://example.org/ns/prod/sa/api");
+
+        // an SVID issued by an intermediate authority
+        let (svid, svid_key) = create_svid(
+            "spiffe://example.org/billing",
+            &intermediate,
+            &intermediate_key,
+        );
+        let signature = sign(&svid_key, MESSAGE);
+        assert!(validator
+            .validate(&[der(&svid), der(&intermediate)], MESSAGE, &signature)?
+            .is_left());
+        // the intermediate certificate is required
+        assert!(validator
+            .validate(&[der(&svid)], MESSAGE, &signature)?
+            .is_right());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_invalid_svids() -> Result<()> {
+        let (root, root_key) = create_ca("root", None);
+        let (other_root, other_root_key) = create_ca("other root", None);
+        let validator = SvidValidator::new("example.org", root.pem().as_bytes())?;
+
+        // an SVID of another trust domain
+        let (svid, svid_key) = create_svid("spiffe://other.org/api", &root, &root_key);
+        assert!(validator
+            .validate(&[der(&svid)], MESSAGE, &sign(&svid_key, MESSAGE))?
+            .is_right());
+
+        // an SVID issued by an untrusted authority
+        let (svid, svid_key) =
+            create_svid("spiffe://example.org/api", &other_root, &other_root_key);
+        assert!(validator
+            .validate(&[der(&svid)], MESSAGE, &sign(&svid_key, MESSAGE))?
+            .is_right());
+
+        // a signature made with another key
+        let (svid, _) = create_svid("spiffe://example.org/api", &root, &root_key);
+        let (_, other_key) = create_svid("spiffe://example.org/api", &root, &root_key);
+        assert!(validator
+            .validate(&[der(&svid)], MESSAGE, &sign(&other_key, MESSAGE))?
+            .is_right());
+
+        // a signature of another message
+        let (svid, svid_key) = create_svid("spiffe://example.org/api", &root, &root_key);
+        assert!(validator
+            .validate(&[der(&svid)], b"other message", &sign(&svid_key, MESSAGE))?
+            .is_right());
+
+        // a CA certificate
+        assert!(validator
+            .validate(&[der(&root)], MESSAGE, &sign(&root_key, MESSAGE))?
+            .is_right());
+
+        assert!(validator.validate(&[], MESSAGE, &[])?.is_right());
+        Ok(())
+    }
+
+    fn create_ca(name: &str, issuer: Option<(&Certificate, &KeyPair)>) -> (Certificate, KeyPair) {
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+            .subject_alt_names
+            .push(SanType::URI("spiffe://example.org".try_into().unwrap()));
+        let key_pair = KeyPair::generate().unwrap();
+        let certificate = match issuer {
+            Some((issuer, issuer_key)) => params.signed_by(&key_pair, issuer, issuer_key),
+            None => params.self_signed(&key_pair),
+        }
+        .unwrap();
+        (certificate, key_pair)
+    }
+
+    fn create_svid(
+        spiffe_id: &str,
+        issuer: &Certificate,
+        issuer_key: &KeyPair,
+    ) -> (Certificate, KeyPair) {
+        let mut params = CertificateParams::new(vec![]).unwrap();
+        params
+            .subject_alt_names
+            .push(SanType::URI(spiffe_id.try_into().unwrap()));
+        let key_pair = KeyPair::generate().unwrap();
+        let certificate = params.signed_by(&key_pair, issuer, issuer_key).unwrap();
+        (certificate, key_pair)
+    }
+
+    fn der(certificate: &Certificate) -> &[u8] {
+        certificate.der()
+    }
+
+    /// Sign a message with an ECDSA P-256 key, as an ASN.1 DER encoded signature
+    fn sign(key_pair: &KeyPair, message: &[u8]) -> Vec<u8> {
+        let signing_key = SigningKey::from_pkcs8_pem(&key_pair.serialize_pem()).unwrap();
+        let signature: Signature = signing_key.sign(message);
+        signature.to_der().as_bytes().to_vec()
+    }
+}
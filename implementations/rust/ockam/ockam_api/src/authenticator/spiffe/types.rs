@@ -0,0 +1,50 @@
+use minicbor::bytes::ByteVec;
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::Identifier;
+
+/// Request sent to the SPIFFE authenticator to obtain a credential.
+///
+/// The request contains the X.509-SVID of the workload, followed by its intermediate
+/// certificates, and a signature of [`svid_proof_of_possession`] with the private key of the SVID
+#[derive(Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthenticateSvid {
+    #[n(1)] certificates: Vec<ByteVec>,
+    #[n(2)] signature: ByteVec,
+}
+
+impl AuthenticateSvid {
+    /// Create a request from DER encoded certificates, starting with the SVID, and a signature.
+    /// ECDSA signatures must be ASN.1 DER encoded
+    pub fn new(certificates: Vec<Vec<u8>>, signature: Vec<u8>) -> Self {
+        Self {
+            certificates: certificates.into_iter().map(ByteVec::from).collect(),
+            signature: signature.into(),
+        }
+    }
+
+    pub fn certificates(&self) -> Vec<&[u8]> {
+        self.certificates.iter().map(|c| c.as_slice()).collect()
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+impl std::fmt::Debug for AuthenticateSvid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthenticateSvid")
+            .field("certificates", &self.certificates.len())
+            .finish()
+    }
+}
+
+/// Return the message signed with the private key of an X.509-SVID to prove its possession.
+///
+/// The message is bound to the authority and to the identity requesting a credential,
+/// so that a signature can't be replayed by another identity, or with another authority
+pub fn svid_proof_of_possession(authority: &Identifier, identifier: &Identifier) -> Vec<u8> {
+    format!("ockam-svid-proof-of-possession:{authority}:{identifier}").into_bytes()
+}
@@ -1,8 +1,10 @@
 use crate::authenticator::one_time_code::OneTimeCode;
-use crate::authenticator::EnrollmentToken;
-use ockam::identity::TimestampInSeconds;
+use crate::authenticator::{EnrollmentToken, EnrollmentTokenRedemption};
+use ockam::identity::{Identifier, TimestampInSeconds};
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_node::database::AutoRetry;
 use ockam_node::retry;
@@ -10,15 +12,43 @@ use ockam_node::retry;
 /// This repository stores enrollment tokens on the Authority node
 #[async_trait]
 pub trait AuthorityEnrollmentTokenRepository: Send + Sync + 'static {
-    /// Use previously issued token
+    /// Use previously issued token.
+    /// If the token has a reference, its redemption by a new member with the given attributes
+    /// is recorded in the same transaction
     async fn use_token(
         &self,
         one_time_code: OneTimeCode,
         now: TimestampInSeconds,
+        redeemed_by: &Identifier,
+        attrs: &BTreeMap<String, String>,
     ) -> Result<Option<EnrollmentToken>>;
 
     /// Store a newly issued enrolment token
     async fn store_new_token(&self, token: EnrollmentToken) -> Result<()>;
+
+    /// Return a token which has not expired yet, without using it
+    async fn get_token(
+        &self,
+        one_time_code: OneTimeCode,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>>;
+
+    /// Return the token with a given reference, if it has not expired yet
+    async fn get_token_by_reference(
+        &self,
+        reference: &str,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>>;
+
+    /// Return all the tokens which have not expired yet
+    async fn get_tokens(&self, now: TimestampInSeconds) -> Result<Vec<EnrollmentToken>>;
+
+    /// Delete the token with a given reference so that it can't be used anymore.
+    /// Return false if there is no such token
+    async fn revoke_token(&self, reference: &str) -> Result<bool>;
+
+    /// Return the redemptions of the token with a given reference, oldest first
+    async fn get_redemptions(&self, reference: &str) -> Result<Vec<EnrollmentTokenRedemption>>;
 }
 
 #[async_trait]
@@ -27,11 +57,43 @@ impl<T: AuthorityEnrollmentTokenRepository> AuthorityEnrollmentTokenRepository f
         &self,
         one_time_code: OneTimeCode,
         now: TimestampInSeconds,
+        redeemed_by: &Identifier,
+        attrs: &BTreeMap<String, String>,
     ) -> Result<Option<EnrollmentToken>> {
-        retry!(self.wrapped.use_token(one_time_code, now))
+        retry!(self
+            .wrapped
+            .use_token(one_time_code, now, redeemed_by, attrs))
     }
 
     async fn store_new_token(&self, token: EnrollmentToken) -> Result<()> {
         retry!(self.wrapped.store_new_token(token.clone()))
     }
+
+    async fn get_token(
+        &self,
+        one_time_code: OneTimeCode,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>> {
+        retry!(self.wrapped.get_token(one_time_code, now))
+    }
+
+    async fn get_token_by_reference(
+        &self,
+        reference: &str,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>> {
+        retry!(self.wrapped.get_token_by_reference(reference, now))
+    }
+
+    async fn get_tokens(&self, now: TimestampInSeconds) -> Result<Vec<EnrollmentToken>> {
+        retry!(self.wrapped.get_tokens(now))
+    }
+
+    async fn revoke_token(&self, reference: &str) -> Result<bool> {
+        retry!(self.wrapped.revoke_token(reference))
+    }
+
+    async fn get_redemptions(&self, reference: &str) -> Result<Vec<EnrollmentTokenRedemption>> {
+        retry!(self.wrapped.get_redemptions(reference))
+    }
 }
@@ -1,13 +1,15 @@
-use ockam::identity::TimestampInSeconds;
+use ockam::identity::{Identifier, TimestampInSeconds};
 use sqlx::encode::IsNull;
 use sqlx::error::BoxDynError;
 use sqlx::*;
 use sqlx_core::any::AnyArgumentBuffer;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use crate::authenticator::one_time_code::OneTimeCode;
 use crate::authenticator::{
-    AuthorityEnrollmentTokenRepository, EnrollmentToken, EnrollmentTokenRow,
+    AuthorityEnrollmentTokenRepository, EnrollmentToken, EnrollmentTokenRedemption,
+    EnrollmentTokenRedemptionRow, EnrollmentTokenRow,
 };
 use ockam_core::async_trait;
 use ockam_core::Result;
@@ -52,6 +54,8 @@ impl AuthorityEnrollmentTokenRepository for AuthorityEnrollmentTokenSqlxDatabase
         &self,
         one_time_code: OneTimeCode,
         now: TimestampInSeconds,
+        redeemed_by: &Identifier,
+        attrs: &BTreeMap<String, String>,
     ) -> Result<Option<EnrollmentToken>> {
         // We need to delete expired tokens regularly
         // Also makes sure we don't get expired tokens later inside this function
@@ -61,52 +65,76 @@ impl AuthorityEnrollmentTokenRepository for AuthorityEnrollmentTokenSqlxDatabase
         let res = query1.execute(&*self.database.pool).await.into_core()?;
         debug!("Deleted {} expired enrollment tokens", res.rows_affected());
 
-        let mut transaction = self.database.pool.begin().await.into_core()?;
-
-        let query2 = query_as("SELECT one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes FROM authority_enrollment_token WHERE one_time_code = $1")
-            .bind(one_time_code);
-        let row: Option<EnrollmentTokenRow> =
-            query2.fetch_optional(&mut *transaction).await.into_core()?;
-        let token: Option<EnrollmentToken> = row.map(|r| r.try_into()).transpose()?;
+        // The usage count of the token is decreased with a compare-and-swap, so that
+        // concurrent redemptions, possibly made by several authority replicas sharing
+        // the same database, can't use a token more times than it was issued for.
+        // The redemption is recorded in the same transaction, so that a token can't be
+        // used without leaving an audit record
+        loop {
+            let query2 = query_as("SELECT one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes, parameters FROM authority_enrollment_token WHERE one_time_code = $1")
+                .bind(one_time_code);
+            let row: Option<EnrollmentTokenRow> = query2
+                .fetch_optional(&*self.database.pool)
+                .await
+                .into_core()?;
+            let Some(token) = row.map(EnrollmentToken::try_from).transpose()? else {
+                return Ok(None);
+            };
 
-        if let Some(token) = &token {
-            if token.ttl_count <= 1 {
-                let query3 =
-                    query("DElETE FROM authority_enrollment_token WHERE one_time_code = $1")
-                        .bind(one_time_code);
-                query3.execute(&mut *transaction).await.void()?;
-                debug!(
-                    "Deleted enrollment token because it has been used. Reference: {}",
-                    token.reference()
-                );
-            } else {
-                let new_ttl_count = token.ttl_count - 1;
-                let query3 = query(
-                    "UPDATE authority_enrollment_token SET ttl_count = $1 WHERE one_time_code = $2",
+            let query3 = if token.ttl_count <= 1 {
+                query(
+                    "DELETE FROM authority_enrollment_token WHERE one_time_code = $1 AND ttl_count = $2",
                 )
-                .bind(new_ttl_count as i64)
-                .bind(one_time_code);
-                query3.execute(&mut *transaction).await.void()?;
+                .bind(one_time_code)
+                .bind(token.ttl_count as i64)
+            } else {
+                query("UPDATE authority_enrollment_token SET ttl_count = $1 WHERE one_time_code = $2 AND ttl_count = $3")
+                    .bind((token.ttl_count - 1) as i64)
+                    .bind(one_time_code)
+                    .bind(token.ttl_count as i64)
+            };
+            let mut transaction = self.database.begin().await.into_core()?;
+            let res = query3.execute(&mut *transaction).await.into_core()?;
+
+            if res.rows_affected() == 1 {
+                // Tokens issued before references were introduced can't be audited
+                if let Some(reference) = &token.reference {
+                    query(
+                        r#"
+                        INSERT INTO authority_enrollment_token_redemption (reference, issued_by, redeemed_by, redeemed_at, attributes)
+                        VALUES ($1, $2, $3, $4, $5)"#,
+                    )
+                    .bind(reference)
+                    .bind(&token.issued_by)
+                    .bind(redeemed_by)
+                    .bind(now)
+                    .bind(ockam_core::cbor_encode_preallocate(attrs)?)
+                    .execute(&mut *transaction)
+                    .await
+                    .void()?;
+                }
+                transaction.commit().await.void()?;
                 debug!(
-                    "Decreasing enrollment token usage count to {}. Reference: {}",
-                    new_ttl_count,
+                    "Decreased enrollment token usage count to {}. Reference: {}",
+                    token.ttl_count - 1,
                     token.reference()
                 );
+                return Ok(Some(token));
             }
+            debug!(
+                "The enrollment token was used concurrently, trying again. Reference: {}",
+                token.reference()
+            );
         }
-
-        transaction.commit().await.void()?;
-
-        Ok(token)
     }
 
     async fn store_new_token(&self, token: EnrollmentToken) -> Result<()> {
         let query = query(
             r#"
-            INSERT INTO authority_enrollment_token (one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO authority_enrollment_token (one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes, parameters)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             ON CONFLICT (one_time_code)
-            DO UPDATE SET reference = $2, issued_by = $3, created_at = $4, expires_at = $5, ttl_count = $6, attributes = $7"#,
+            DO UPDATE SET reference = $2, issued_by = $3, created_at = $4, expires_at = $5, ttl_count = $6, attributes = $7, parameters = $8"#,
         )
         .bind(token.one_time_code)
         .bind(token.reference)
@@ -114,10 +142,64 @@ impl AuthorityEnrollmentTokenRepository for AuthorityEnrollmentTokenSqlxDatabase
         .bind(token.created_at)
         .bind(token.expires_at)
         .bind(token.ttl_count as i64)
-        .bind(ockam_core::cbor_encode_preallocate(token.attrs)?);
+        .bind(ockam_core::cbor_encode_preallocate(token.attrs)?)
+        .bind(ockam_core::cbor_encode_preallocate(token.parameters)?);
 
         query.execute(&*self.database.pool).await.void()
     }
+
+    async fn get_token(
+        &self,
+        one_time_code: OneTimeCode,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>> {
+        let query = query_as("SELECT one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes, parameters FROM authority_enrollment_token WHERE one_time_code = $1 AND expires_at > $2")
+            .bind(one_time_code)
+            .bind(now.0 as i64);
+        let row: Option<EnrollmentTokenRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.try_into()).transpose()
+    }
+
+    async fn get_token_by_reference(
+        &self,
+        reference: &str,
+        now: TimestampInSeconds,
+    ) -> Result<Option<EnrollmentToken>> {
+        let query = query_as("SELECT one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes, parameters FROM authority_enrollment_token WHERE reference = $1 AND expires_at > $2")
+            .bind(reference)
+            .bind(now.0 as i64);
+        let row: Option<EnrollmentTokenRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.try_into()).transpose()
+    }
+
+    async fn get_tokens(&self, now: TimestampInSeconds) -> Result<Vec<EnrollmentToken>> {
+        let query = query_as("SELECT one_time_code, reference, issued_by, created_at, expires_at, ttl_count, attributes, parameters FROM authority_enrollment_token WHERE expires_at > $1 ORDER BY created_at")
+            .bind(now.0 as i64);
+        let rows: Vec<EnrollmentTokenRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.into_iter().map(|r| r.try_into()).collect()
+    }
+
+    async fn revoke_token(&self, reference: &str) -> Result<bool> {
+        let query =
+            query("DELETE FROM authority_enrollment_token WHERE reference = $1").bind(reference);
+        let res = query.execute(&*self.database.pool).await.into_core()?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn get_redemptions(&self, reference: &str) -> Result<Vec<EnrollmentTokenRedemption>> {
+        let query = query_as("SELECT reference, issued_by, redeemed_by, redeemed_at, attributes FROM authority_enrollment_token_redemption WHERE reference = $1 ORDER BY redeemed_at")
+            .bind(reference);
+        let rows: Vec<EnrollmentTokenRedemptionRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.into_iter().map(|r| r.try_into()).collect()
+    }
 }
 
 // Database serialization / deserialization
@@ -172,11 +254,14 @@ mod tests {
                 expires_at,
                 ttl_count: 1,
                 attrs: attrs.clone(),
+                parameters: Default::default(),
             };
 
             repository.store_new_token(token).await?;
 
-            let token1 = repository.use_token(one_time_code, now()?).await?;
+            let token1 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
             assert!(token1.is_some());
             let token1 = token1.unwrap();
             assert_eq!(token1.one_time_code, one_time_code);
@@ -187,7 +272,9 @@ mod tests {
             assert_eq!(token1.ttl_count, 1);
             assert_eq!(token1.attrs, attrs);
 
-            let token2 = repository.use_token(one_time_code, now()?).await?;
+            let token2 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
             assert!(token2.is_none());
 
             Ok(())
@@ -223,11 +310,14 @@ mod tests {
                 expires_at,
                 ttl_count: 1,
                 attrs: attrs.clone(),
+                parameters: Default::default(),
             };
 
             repository.store_new_token(token).await?;
 
-            let token1 = repository.use_token(one_time_code, now()?).await?;
+            let token1 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
             assert!(token1.is_some());
             let token1 = token1.unwrap();
             assert_eq!(token1.one_time_code, one_time_code);
@@ -270,13 +360,20 @@ mod tests {
                 expires_at,
                 ttl_count: 2,
                 attrs: attrs.clone(),
+                parameters: Default::default(),
             };
 
             repository.store_new_token(token).await?;
 
-            let token1 = repository.use_token(one_time_code, now()?).await?;
-            let token2 = repository.use_token(one_time_code, now()?).await?;
-            let token3 = repository.use_token(one_time_code, now()?).await?;
+            let token1 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
+            let token2 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
+            let token3 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
             assert!(token1.is_some());
             assert!(token2.is_some());
             assert!(token3.is_none());
@@ -299,6 +396,56 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_authority_enrollment_token_repository_concurrent_use() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn AuthorityEnrollmentTokenRepository> =
+                Arc::new(AuthorityEnrollmentTokenSqlxDatabase::new(db));
+
+            let one_time_code = OneTimeCode::new();
+            let created_at = now()?;
+            let token = EnrollmentToken {
+                one_time_code,
+                reference: None,
+                issued_by: Identifier::from_str(
+                    "I0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+                )
+                .unwrap(),
+                created_at,
+                expires_at: created_at + 10,
+                ttl_count: 3,
+                attrs: Default::default(),
+                parameters: Default::default(),
+            };
+            repository.store_new_token(token).await?;
+
+            // the token can only be used 3 times, even if it is redeemed concurrently
+            let results = futures::future::join_all((0..10).map(|_| {
+                let repository = repository.clone();
+                async move {
+                    repository
+                        .use_token(one_time_code, now()?, &member(), &Default::default())
+                        .await
+                }
+            }))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+            let mut ttl_counts: Vec<u64> =
+                results.into_iter().flatten().map(|t| t.ttl_count).collect();
+            ttl_counts.sort();
+            assert_eq!(ttl_counts, vec![1, 2, 3]);
+            assert!(repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?
+                .is_none());
+
+            Ok(())
+        })
+        .await
+    }
+
     #[tokio::test]
     async fn test_authority_enrollment_token_repository_expired_token() -> Result<()> {
         with_dbs(|db| async move {
@@ -326,6 +473,7 @@ mod tests {
                 expires_at,
                 ttl_count: 1,
                 attrs: attrs.clone(),
+                parameters: Default::default(),
             };
 
             repository.store_new_token(token.clone()).await?;
@@ -334,10 +482,169 @@ mod tests {
 
             tokio::time::sleep(Duration::from_secs(2)).await;
 
-            let token1 = repository.use_token(one_time_code, now()?).await?;
+            let token1 = repository
+                .use_token(one_time_code, now()?, &member(), &Default::default())
+                .await?;
             assert!(token1.is_none());
             Ok(())
         })
         .await
     }
+
+    #[tokio::test]
+    async fn test_authority_enrollment_token_repository_list_and_revoke() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn AuthorityEnrollmentTokenRepository> =
+                Arc::new(AuthorityEnrollmentTokenSqlxDatabase::new(db));
+
+            let issued_by = Identifier::from_str(
+                "I0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            )
+            .unwrap();
+            let created_at = now()?;
+
+            let token = EnrollmentToken {
+                one_time_code: OneTimeCode::new(),
+                reference: Some("reference1".to_string()),
+                issued_by: issued_by.clone(),
+                created_at,
+                expires_at: created_at + 10,
+                ttl_count: 2,
+                attrs: BTreeMap::from([("serial".to_string(), "${serial}".to_string())]),
+                parameters: BTreeMap::from([("serial".to_string(), "[0-9]+".to_string())]),
+            };
+            repository.store_new_token(token.clone()).await?;
+
+            let other_token = EnrollmentToken {
+                one_time_code: OneTimeCode::new(),
+                reference: Some("reference2".to_string()),
+                ..token.clone()
+            };
+            repository.store_new_token(other_token.clone()).await?;
+
+            // the parameters are stored with the token
+            let token1 = repository.get_token(token.one_time_code, now()?).await?;
+            assert!(token1 == Some(token.clone()));
+            let token1 = repository
+                .get_token_by_reference("reference1", now()?)
+                .await?;
+            assert!(token1 == Some(token.clone()));
+
+            let tokens = repository.get_tokens(now()?).await?;
+            assert_eq!(tokens.len(), 2);
+
+            // a revoked token can't be used anymore
+            assert!(repository.revoke_token("reference1").await?);
+            assert!(!repository.revoke_token("reference1").await?);
+            assert!(repository
+                .use_token(token.one_time_code, now()?, &member(), &Default::default())
+                .await?
+                .is_none());
+            let tokens = repository.get_tokens(now()?).await?;
+            assert!(tokens == vec![other_token]);
+
+            // expired tokens are not returned
+            assert!(repository
+                .get_token_by_reference("reference2", created_at + 10)
+                .await?
+                .is_none());
+            assert!(repository.get_tokens(created_at + 10).await?.is_empty());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_authority_enrollment_token_repository_redemptions() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn AuthorityEnrollmentTokenRepository> =
+                Arc::new(AuthorityEnrollmentTokenSqlxDatabase::new(db));
+
+            let issued_by = Identifier::from_str(
+                "I0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            )
+            .unwrap();
+            let redeemed_by1 = Identifier::from_str(
+                "I1123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            )
+            .unwrap();
+            let redeemed_by2 = Identifier::from_str(
+                "I2123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            )
+            .unwrap();
+            let created_at = now()?;
+
+            let token = EnrollmentToken {
+                one_time_code: OneTimeCode::new(),
+                reference: Some("reference".to_string()),
+                issued_by: issued_by.clone(),
+                created_at,
+                expires_at: created_at + 10,
+                ttl_count: 2,
+                attrs: BTreeMap::from([("serial".to_string(), "${serial}".to_string())]),
+                parameters: BTreeMap::from([("serial".to_string(), "[0-9]+".to_string())]),
+            };
+            let other_token = EnrollmentToken {
+                one_time_code: OneTimeCode::new(),
+                reference: Some("other".to_string()),
+                ..token.clone()
+            };
+            repository.store_new_token(token.clone()).await?;
+            repository.store_new_token(other_token.clone()).await?;
+
+            // each use of a token is recorded with the attributes of the new member
+            let attrs1 = BTreeMap::from([("serial".to_string(), "1".to_string())]);
+            let attrs2 = BTreeMap::from([("serial".to_string(), "2".to_string())]);
+            repository
+                .use_token(token.one_time_code, created_at, &redeemed_by1, &attrs1)
+                .await?
+                .unwrap();
+            repository
+                .use_token(token.one_time_code, created_at + 1, &redeemed_by2, &attrs2)
+                .await?
+                .unwrap();
+            repository
+                .use_token(
+                    other_token.one_time_code,
+                    created_at,
+                    &redeemed_by1,
+                    &attrs1,
+                )
+                .await?
+                .unwrap();
+
+            // a token which can't be used anymore is not recorded
+            assert!(repository
+                .use_token(token.one_time_code, created_at + 2, &redeemed_by1, &attrs1)
+                .await?
+                .is_none());
+
+            let redemption1 = EnrollmentTokenRedemption {
+                reference: "reference".to_string(),
+                issued_by: issued_by.clone(),
+                redeemed_by: redeemed_by1,
+                redeemed_at: created_at,
+                attrs: attrs1,
+            };
+            let redemption2 = EnrollmentTokenRedemption {
+                redeemed_by: redeemed_by2,
+                redeemed_at: created_at + 1,
+                attrs: attrs2,
+                ..redemption1.clone()
+            };
+            let redemptions = repository.get_redemptions("reference").await?;
+            assert_eq!(redemptions, vec![redemption1, redemption2]);
+            assert_eq!(repository.get_redemptions("other").await?.len(), 1);
+            assert!(repository.get_redemptions("unknown").await?.is_empty());
+
+            Ok(())
+        })
+        .await
+    }
+
+    fn member() -> Identifier {
+        Identifier::from_str("I3123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef")
+            .unwrap()
+    }
 }
@@ -0,0 +1,79 @@
+use ockam::identity::models::CredentialHash;
+use ockam::identity::{Identifier, TimestampInSeconds};
+use ockam_core::compat::str::FromStr;
+use ockam_core::{Error, Result};
+use ockam_node::database::Nullable;
+
+/// Revocation stored on the Authority node.
+/// It revokes either a specific credential or all the credentials of a subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityRevocation {
+    subject: Identifier,
+    // If None, all the credentials of the subject are revoked
+    credential_hash: Option<CredentialHash>,
+    revoked_by: Identifier,
+    revoked_at: TimestampInSeconds,
+    // Version of the revocation list which first contained this revocation
+    version: u64,
+}
+
+impl AuthorityRevocation {
+    pub fn new(
+        subject: Identifier,
+        credential_hash: Option<CredentialHash>,
+        revoked_by: Identifier,
+        revoked_at: TimestampInSeconds,
+        version: u64,
+    ) -> Self {
+        Self {
+            subject,
+            credential_hash,
+            revoked_by,
+            revoked_at,
+            version,
+        }
+    }
+    pub fn subject(&self) -> &Identifier {
+        &self.subject
+    }
+    pub fn credential_hash(&self) -> Option<&CredentialHash> {
+        self.credential_hash.as_ref()
+    }
+    pub fn revoked_by(&self) -> &Identifier {
+        &self.revoked_by
+    }
+    pub fn revoked_at(&self) -> TimestampInSeconds {
+        self.revoked_at
+    }
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+// Low-level representation of a table row
+#[derive(sqlx::FromRow)]
+pub(crate) struct AuthorityRevocationRow {
+    subject_identifier: String,
+    credential_hash: Nullable<String>,
+    revoked_by: String,
+    revoked_at: i64,
+    version: i64,
+}
+
+impl TryFrom<AuthorityRevocationRow> for AuthorityRevocation {
+    type Error = Error;
+
+    fn try_from(value: AuthorityRevocationRow) -> Result<Self, Self::Error> {
+        Ok(AuthorityRevocation::new(
+            Identifier::from_str(&value.subject_identifier)?,
+            value
+                .credential_hash
+                .to_option()
+                .map(|h| CredentialHash::from_str(&h))
+                .transpose()?,
+            Identifier::from_str(&value.revoked_by)?,
+            TimestampInSeconds(value.revoked_at as u64),
+            value.version as u64,
+        ))
+    }
+}
@@ -0,0 +1,90 @@
+use crate::authenticator::AuthorityRevocation;
+use ockam::identity::models::CredentialHash;
+use ockam::identity::Identifier;
+use ockam_core::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::retry;
+
+/// This repository stores the credentials revoked by the Authority node
+#[async_trait]
+pub trait AuthorityRevocationsRepository: Send + Sync + 'static {
+    /// Revoke a specific credential of a subject, or all its credentials if no hash is given.
+    /// Return the new version of the revocation list
+    async fn revoke(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+        credential_hash: Option<CredentialHash>,
+        revoked_by: &Identifier,
+        subject_is_enroller: bool,
+    ) -> Result<u64>;
+
+    /// Lift the revocation of all the credentials of a subject, for example when it is added
+    /// again to the project. Specific credentials which were revoked stay revoked.
+    /// Return the new version of the revocation list, or None if the subject was not revoked
+    async fn unrevoke_subject(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<u64>>;
+
+    /// Return true if all the credentials of a subject were revoked while it was an enroller
+    async fn is_revoked_enroller(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool>;
+
+    /// Return all the revocations made by the Authority
+    async fn get_revocations(&self, authority: &Identifier) -> Result<Vec<AuthorityRevocation>>;
+
+    /// Return the current version of the revocation list (0 if nothing was revoked)
+    async fn get_version(&self, authority: &Identifier) -> Result<u64>;
+}
+
+#[async_trait]
+impl<T: AuthorityRevocationsRepository> AuthorityRevocationsRepository for AutoRetry<T> {
+    async fn revoke(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+        credential_hash: Option<CredentialHash>,
+        revoked_by: &Identifier,
+        subject_is_enroller: bool,
+    ) -> Result<u64> {
+        retry!(self.wrapped.revoke(
+            authority,
+            subject,
+            credential_hash.clone(),
+            revoked_by,
+            subject_is_enroller
+        ))
+    }
+
+    async fn unrevoke_subject(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<u64>> {
+        retry!(self.wrapped.unrevoke_subject(authority, subject))
+    }
+
+    async fn is_revoked_enroller(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool> {
+        retry!(self.wrapped.is_revoked_enroller(authority, subject))
+    }
+
+    async fn get_revocations(&self, authority: &Identifier) -> Result<Vec<AuthorityRevocation>> {
+        retry!(self.wrapped.get_revocations(authority))
+    }
+
+    async fn get_version(&self, authority: &Identifier) -> Result<u64> {
+        retry!(self.wrapped.get_version(authority))
+    }
+}
@@ -0,0 +1,260 @@
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::authenticator::{
+    AuthorityRevocation, AuthorityRevocationRow, AuthorityRevocationsRepository,
+};
+use ockam::identity::models::CredentialHash;
+use ockam::identity::utils::now;
+use ockam::identity::Identifier;
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToVoid};
+
+/// Implementation of [`AuthorityRevocationsRepository`] trait based on an underlying database
+/// using sqlx as its API
+#[derive(Clone)]
+pub struct AuthorityRevocationsSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl AuthorityRevocationsSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for authority revocations");
+        Self { database }
+    }
+
+    /// Create a repository
+    pub fn make_repository(database: SqlxDatabase) -> Arc<dyn AuthorityRevocationsRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database)))
+        } else {
+            Arc::new(Self::new(database))
+        }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(
+            SqlxDatabase::in_memory("authority revocations").await?,
+        ))
+    }
+
+    /// Increment the version of the revocation list of an authority and return the new version.
+    /// The upsert locks the version row until the end of the transaction, so that concurrent
+    /// changes, for example from several authority replicas, get distinct versions
+    async fn increment_version(
+        transaction: &mut Transaction<'_, Any>,
+        authority: &Identifier,
+    ) -> Result<i64> {
+        let query1 = query(
+            r#"
+            INSERT INTO authority_revocation_version (authority_id, version) VALUES ($1, 1)
+            ON CONFLICT (authority_id)
+            DO UPDATE SET version = authority_revocation_version.version + 1"#,
+        )
+        .bind(authority);
+        query1.execute(&mut **transaction).await.void()?;
+
+        let query2 = query_scalar(
+            "SELECT version FROM authority_revocation_version WHERE authority_id = $1",
+        )
+        .bind(authority);
+        query2.fetch_one(&mut **transaction).await.into_core()
+    }
+}
+
+#[async_trait]
+impl AuthorityRevocationsRepository for AuthorityRevocationsSqlxDatabase {
+    async fn revoke(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+        credential_hash: Option<CredentialHash>,
+        revoked_by: &Identifier,
+        subject_is_enroller: bool,
+    ) -> Result<u64> {
+        let mut transaction = self.database.begin().await.into_core()?;
+        let version = Self::increment_version(&mut transaction, authority).await?;
+
+        let query2 = query(
+            r#"
+            INSERT INTO authority_revocation (authority_id, subject_identifier, credential_hash, revoked_by, revoked_at, version, subject_is_enroller)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+        )
+        .bind(authority)
+        .bind(subject)
+        .bind(credential_hash.map(|h| h.to_string()))
+        .bind(revoked_by)
+        .bind(now()?)
+        .bind(version)
+        .bind(subject_is_enroller);
+        query2.execute(&mut *transaction).await.void()?;
+
+        transaction.commit().await.void()?;
+        Ok(version as u64)
+    }
+
+    async fn unrevoke_subject(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<Option<u64>> {
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        let query = query(
+            "DELETE FROM authority_revocation WHERE authority_id = $1 AND subject_identifier = $2 AND credential_hash IS NULL",
+        )
+        .bind(authority)
+        .bind(subject);
+        let deleted = query
+            .execute(&mut *transaction)
+            .await
+            .into_core()?
+            .rows_affected();
+        if deleted == 0 {
+            transaction.rollback().await.void()?;
+            return Ok(None);
+        }
+        let version = Self::increment_version(&mut transaction, authority).await?;
+
+        transaction.commit().await.void()?;
+        Ok(Some(version as u64))
+    }
+
+    async fn is_revoked_enroller(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool> {
+        let query = query_scalar(
+            "SELECT COUNT(*) FROM authority_revocation WHERE authority_id = $1 AND subject_identifier = $2 AND credential_hash IS NULL AND subject_is_enroller = $3",
+        )
+        .bind(authority)
+        .bind(subject)
+        .bind(true);
+        let count: i64 = query.fetch_one(&*self.database.pool).await.into_core()?;
+        Ok(count > 0)
+    }
+
+    async fn get_revocations(&self, authority: &Identifier) -> Result<Vec<AuthorityRevocation>> {
+        let query = query_as(
+            "SELECT subject_identifier, credential_hash, revoked_by, revoked_at, version FROM authority_revocation WHERE authority_id = $1 ORDER BY version",
+        )
+        .bind(authority);
+        let rows: Vec<AuthorityRevocationRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        rows.into_iter().map(|r| r.try_into()).collect()
+    }
+
+    async fn get_version(&self, authority: &Identifier) -> Result<u64> {
+        let query = query_scalar(
+            "SELECT version FROM authority_revocation_version WHERE authority_id = $1",
+        )
+        .bind(authority);
+        let version: Option<i64> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        Ok(version.unwrap_or(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::identities;
+    use ockam_core::compat::sync::Arc;
+    use ockam_node::database::with_dbs;
+
+    #[tokio::test]
+    async fn test_authority_revocations_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn AuthorityRevocationsRepository> =
+                Arc::new(AuthorityRevocationsSqlxDatabase::new(db));
+
+            let identities = identities().await?;
+            let authority = identities.identities_creation().create_identity().await?;
+            let enroller = identities.identities_creation().create_identity().await?;
+            let subject1 = identities.identities_creation().create_identity().await?;
+            let subject2 = identities.identities_creation().create_identity().await?;
+            let credential_hash = CredentialHash([2; 32]);
+
+            assert_eq!(repository.get_version(&authority).await?, 0);
+            assert!(repository.get_revocations(&authority).await?.is_empty());
+
+            let version1 = repository
+                .revoke(&authority, &subject1, None, &enroller, true)
+                .await?;
+            let version2 = repository
+                .revoke(
+                    &authority,
+                    &subject2,
+                    Some(credential_hash.clone()),
+                    &enroller,
+                    false,
+                )
+                .await?;
+            assert_eq!(version1, 1);
+            assert_eq!(version2, 2);
+            assert_eq!(repository.get_version(&authority).await?, 2);
+
+            let revocations = repository.get_revocations(&authority).await?;
+            assert_eq!(revocations.len(), 2);
+            assert_eq!(revocations[0].subject(), &subject1);
+            assert_eq!(revocations[0].credential_hash(), None);
+            assert_eq!(revocations[1].subject(), &subject2);
+            assert_eq!(revocations[1].credential_hash(), Some(&credential_hash));
+            assert_eq!(revocations[1].revoked_by(), &enroller);
+            assert_eq!(revocations[1].version(), 2);
+
+            // revocations are scoped by authority
+            assert_eq!(repository.get_version(&enroller).await?, 0);
+
+            assert!(
+                repository
+                    .is_revoked_enroller(&authority, &subject1)
+                    .await?
+            );
+            assert!(
+                !repository
+                    .is_revoked_enroller(&authority, &subject2)
+                    .await?
+            );
+
+            // lifting the revocation of a subject creates a new version of the list
+            assert_eq!(
+                repository.unrevoke_subject(&authority, &subject1).await?,
+                Some(3)
+            );
+            assert_eq!(repository.get_version(&authority).await?, 3);
+            let revocations = repository.get_revocations(&authority).await?;
+            assert_eq!(revocations.len(), 1);
+            assert_eq!(revocations[0].subject(), &subject2);
+
+            // a specific credential stays revoked
+            assert_eq!(
+                repository.unrevoke_subject(&authority, &subject2).await?,
+                None
+            );
+            assert_eq!(repository.get_version(&authority).await?, 3);
+
+            // a subject can be revoked again
+            assert!(
+                !repository
+                    .is_revoked_enroller(&authority, &subject1)
+                    .await?
+            );
+            let version4 = repository
+                .revoke(&authority, &subject1, None, &enroller, false)
+                .await?;
+            assert_eq!(version4, 4);
+
+            Ok(())
+        })
+        .await
+    }
+}
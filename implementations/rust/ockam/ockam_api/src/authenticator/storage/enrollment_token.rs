@@ -1,8 +1,10 @@
 use crate::authenticator::one_time_code::OneTimeCode;
+use minicbor::{CborLen, Decode, Encode};
 use ockam::identity::{Identifier, TimestampInSeconds};
 use ockam_core::compat::str::FromStr;
 use ockam_core::{Error, Result};
 use ockam_node::database::Nullable;
+use serde::Serialize;
 use std::collections::BTreeMap;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -22,8 +24,12 @@ pub struct EnrollmentToken {
     pub expires_at: TimestampInSeconds,
     /// Number of times a [`OneTimeCode`] can be used (1 by default)
     pub ttl_count: u64,
-    /// Attributes that will be assigned to a member upon usage of that token
+    /// Attributes that will be assigned to a member upon usage of that token.
+    /// Their values can contain `${name}` placeholders for the parameters of the token
     pub attrs: BTreeMap<String, String>,
+    /// Parameters supplied by the member upon usage of that token, mapping a parameter name
+    /// to the regular expression that its value must match
+    pub parameters: BTreeMap<String, String>,
 }
 
 impl EnrollmentToken {
@@ -42,6 +48,7 @@ pub(crate) struct EnrollmentTokenRow {
     expires_at: i64,
     ttl_count: i64,
     attributes: Vec<u8>,
+    parameters: Nullable<Vec<u8>>,
 }
 
 impl TryFrom<EnrollmentTokenRow> for EnrollmentToken {
@@ -56,8 +63,53 @@ impl TryFrom<EnrollmentTokenRow> for EnrollmentToken {
             expires_at: TimestampInSeconds(value.expires_at as u64),
             ttl_count: value.ttl_count as u64,
             attrs: minicbor::decode(&value.attributes)?,
+            parameters: match value.parameters.to_option() {
+                Some(parameters) => minicbor::decode(&parameters)?,
+                None => BTreeMap::new(),
+            },
         };
 
         Ok(member)
     }
 }
+
+/// Record of an identity which became a member by redeeming an enrollment token
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode, CborLen, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollmentTokenRedemption {
+    /// Reference of the redeemed token
+    #[n(1)] pub reference: String,
+    /// [`Identifier`] of the enroller who issued the token
+    #[n(2)] pub issued_by: Identifier,
+    /// [`Identifier`] of the new member
+    #[n(3)] pub redeemed_by: Identifier,
+    /// Redemption timestamp
+    #[n(4)] pub redeemed_at: TimestampInSeconds,
+    /// Attributes assigned to the new member
+    #[n(5)] pub attrs: BTreeMap<String, String>,
+}
+
+// Low-level representation of a table row
+#[derive(sqlx::FromRow)]
+pub(crate) struct EnrollmentTokenRedemptionRow {
+    reference: String,
+    issued_by: String,
+    redeemed_by: String,
+    redeemed_at: i64,
+    attributes: Vec<u8>,
+}
+
+impl TryFrom<EnrollmentTokenRedemptionRow> for EnrollmentTokenRedemption {
+    type Error = Error;
+
+    fn try_from(value: EnrollmentTokenRedemptionRow) -> Result<Self, Self::Error> {
+        Ok(EnrollmentTokenRedemption {
+            reference: value.reference,
+            issued_by: Identifier::from_str(&value.issued_by)?,
+            redeemed_by: Identifier::from_str(&value.redeemed_by)?,
+            redeemed_at: TimestampInSeconds(value.redeemed_at as u64),
+            attrs: minicbor::decode(&value.attributes)?,
+        })
+    }
+}
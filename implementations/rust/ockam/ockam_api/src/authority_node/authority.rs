@@ -1,19 +1,26 @@
 use std::collections::BTreeMap;
 use tracing::info;
 
-use crate::authenticator::credential_issuer::CredentialIssuerWorker;
+use crate::authenticator::credential_issuer::{CredentialIssuer, CredentialIssuerWorker};
 use crate::authenticator::direct::{AccountAuthorityInfo, DirectAuthenticatorWorker};
 use crate::authenticator::enrollment_tokens::{
     EnrollmentTokenAcceptorWorker, EnrollmentTokenIssuerWorker,
 };
+use crate::authenticator::jwt::{
+    JwksCache, JwtAuthenticator, JwtAuthenticatorWorker, JwtValidator,
+};
+use crate::authenticator::kubernetes::{KubernetesAuthenticator, KubernetesAuthenticatorWorker};
+use crate::authenticator::revocation::RevocationListWorker;
+use crate::authenticator::spiffe::{SpiffeAuthenticator, SpiffeAuthenticatorWorker, SvidValidator};
 use crate::authenticator::{
     AuthorityEnrollmentTokenRepository, AuthorityEnrollmentTokenSqlxDatabase, AuthorityMember,
-    AuthorityMembersRepository, AuthorityMembersSqlxDatabase,
+    AuthorityMembersRepository, AuthorityMembersSqlxDatabase, AuthorityRevocationsRepository,
+    AuthorityRevocationsSqlxDatabase,
 };
 use ockam::identity::utils::now;
 use ockam::identity::{
     Identifier, Identities, SecureChannelListenerOptions, SecureChannelSqlxDatabase,
-    SecureChannels, TrustEveryonePolicy,
+    SecureChannels, TrustEveryonePolicy, Vault,
 };
 use ockam::tcp::{TcpListenerOptions, TcpTransport};
 use ockam_core::compat::sync::Arc;
@@ -23,8 +30,9 @@ use ockam_core::Result;
 use ockam_node::database::SqlxDatabase;
 use ockam_node::Context;
 
-use crate::authority_node::Configuration;
+use crate::authority_node::{AuthorityHealthWorker, Configuration};
 use crate::echoer::Echoer;
+use crate::error::ApiError;
 use crate::nodes::service::default_address::DefaultAddress;
 
 /// This struct represents an Authority, which is an
@@ -35,13 +43,21 @@ use crate::nodes::service::default_address::DefaultAddress;
 //   - a credential issuer: return the attributes of a member as a time-limited credential.
 //   - an enrollment token issuer: create a token attributed allowing an identity to acquire some specific attributes.
 //   - an enrollment token acceptor: create or update a member, given a token.
+//   - a revocation list service: revoke credentials and distribute the signed revocation list.
+//   - a JWT authenticator: issue a credential to an identity presenting a JWT from a trusted issuer.
+//   - a health service: check that the node can reach its database.
+///
+/// Several Authority replicas can be started with the same identity, stored in a shared vault,
+/// and the same Postgres database, in which case they share their members and enrollment tokens.
 #[derive(Clone)]
 pub struct Authority {
     identifier: Identifier,
     secure_channels: Arc<SecureChannels>,
     members: Arc<dyn AuthorityMembersRepository>,
     tokens: Arc<dyn AuthorityEnrollmentTokenRepository>,
+    revocations: Arc<dyn AuthorityRevocationsRepository>,
     account_authority: Option<AccountAuthorityInfo>,
+    database: SqlxDatabase,
 }
 
 /// Public functions to:
@@ -64,6 +80,17 @@ impl Authority {
     pub async fn create(
         configuration: &Configuration,
         database: Option<SqlxDatabase>,
+    ) -> Result<Self> {
+        Self::create_with_vault(configuration, database, None).await
+    }
+
+    /// Create an authority using a specific vault for its identity keys, for example a vault
+    /// backed by AWS KMS or a PKCS#11 token, so that several replicas can share the same identity.
+    /// If no vault is given, the keys are stored in the database
+    pub async fn create_with_vault(
+        configuration: &Configuration,
+        database: Option<SqlxDatabase>,
+        vault: Option<Vault>,
     ) -> Result<Self> {
         debug!(?configuration, "creating the authority");
 
@@ -77,12 +104,18 @@ impl Authority {
 
         let members = AuthorityMembersSqlxDatabase::make_repository(database.clone());
         let tokens = AuthorityEnrollmentTokenSqlxDatabase::make_repository(database.clone());
+        let revocations = AuthorityRevocationsSqlxDatabase::make_repository(database.clone());
         let secure_channel_repository =
             SecureChannelSqlxDatabase::make_repository(database.clone());
 
         Self::bootstrap_repository(members.clone(), configuration).await?;
 
-        let identities = Identities::create_with_node(database, node_name).build();
+        let identities = Identities::create_with_node(database.clone(), node_name);
+        let identities = match vault {
+            Some(vault) => identities.with_vault(vault),
+            None => identities,
+        }
+        .build();
 
         let secure_channels =
             SecureChannels::from_identities(identities.clone(), secure_channel_repository);
@@ -109,7 +142,9 @@ impl Authority {
             secure_channels,
             members,
             tokens,
+            revocations,
             account_authority,
+            database,
         })
     }
 
@@ -270,6 +305,32 @@ impl Authority {
         Ok(())
     }
 
+    /// Start the revocation list service, used by enrollers to revoke credentials
+    /// and by project nodes to retrieve the signed list of revoked credentials
+    pub fn start_revocation_list_service(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+    ) -> Result<()> {
+        let worker = RevocationListWorker::new(
+            &self.identifier,
+            self.members.clone(),
+            self.revocations.clone(),
+            self.secure_channels.identities().identities_attributes(),
+            self.secure_channels.identities().credentials(),
+            self.account_authority.clone(),
+        );
+
+        let address = DefaultAddress::REVOCATION_LIST.to_string();
+        ctx.flow_controls()
+            .add_consumer(&address.clone().into(), secure_channel_flow_control_id);
+
+        ctx.start_worker(address.clone(), worker)?;
+
+        info!("started a revocation list service at '{address}'");
+        Ok(())
+    }
+
     /// Start the Okta service to retrieve attributes authenticated by Okta
     pub fn start_okta(
         &self,
@@ -294,6 +355,133 @@ impl Authority {
         Ok(())
     }
 
+    /// Start the JWT authenticator service to issue credentials for identities
+    /// authenticated by a trusted JWT issuer
+    pub fn start_jwt_authenticator(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        if let Some(jwt) = &configuration.jwt {
+            let credential_issuer = CredentialIssuer::new(
+                self.members.clone(),
+                self.secure_channels.identities().identities_attributes(),
+                self.secure_channels.identities().credentials(),
+                &self.identifier,
+                configuration.project_identifier(),
+                get_env("CREDENTIAL_TTL_SECS")?,
+                self.account_authority.clone(),
+                configuration.disable_trust_context_id,
+            );
+            let authenticator = JwtAuthenticator::new(
+                &self.identifier,
+                self.members.clone(),
+                JwtValidator::new(jwt, JwksCache::new(jwt)?)?,
+                credential_issuer,
+            );
+
+            ctx.flow_controls()
+                .add_consumer(&jwt.address.clone().into(), secure_channel_flow_control_id);
+
+            ctx.start_worker(
+                jwt.address.clone(),
+                JwtAuthenticatorWorker::new(authenticator),
+            )?;
+
+            info!(
+                "started a JWT authenticator at '{}' for the issuer {}",
+                jwt.address, jwt.issuer
+            );
+        }
+        Ok(())
+    }
+
+    /// Start the Kubernetes authenticator service to enroll identities presenting
+    /// a service account token of the configured cluster
+    pub fn start_kubernetes_authenticator(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        if let Some(kubernetes) = &configuration.kubernetes {
+            let authenticator = KubernetesAuthenticator::create(
+                &self.identifier,
+                self.members.clone(),
+                kubernetes,
+            )?;
+
+            ctx.flow_controls().add_consumer(
+                &kubernetes.address.clone().into(),
+                secure_channel_flow_control_id,
+            );
+
+            ctx.start_worker(
+                kubernetes.address.clone(),
+                KubernetesAuthenticatorWorker::new(authenticator),
+            )?;
+
+            info!(
+                "started a Kubernetes authenticator at '{}' for the issuer {}",
+                kubernetes.address, kubernetes.issuer
+            );
+        }
+        Ok(())
+    }
+
+    /// Start the SPIFFE authenticator service to issue credentials for identities
+    /// presenting an X.509-SVID of the configured trust domain
+    pub fn start_spiffe_authenticator(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        if let Some(spiffe) = &configuration.spiffe {
+            let trust_bundle = std::fs::read(&spiffe.trust_bundle).map_err(|e| {
+                ApiError::core(format!(
+                    "could not read the SPIFFE trust bundle {}: {e}",
+                    spiffe.trust_bundle.display()
+                ))
+            })?;
+            let validator = SvidValidator::new(&spiffe.trust_domain, &trust_bundle)?;
+
+            let credential_issuer = CredentialIssuer::new(
+                self.members.clone(),
+                self.secure_channels.identities().identities_attributes(),
+                self.secure_channels.identities().credentials(),
+                &self.identifier,
+                configuration.project_identifier(),
+                get_env("CREDENTIAL_TTL_SECS")?,
+                self.account_authority.clone(),
+                configuration.disable_trust_context_id,
+            );
+            let authenticator = SpiffeAuthenticator::new(
+                &self.identifier,
+                self.members.clone(),
+                validator,
+                credential_issuer,
+            );
+
+            ctx.flow_controls().add_consumer(
+                &spiffe.address.clone().into(),
+                secure_channel_flow_control_id,
+            );
+
+            ctx.start_worker(
+                spiffe.address.clone(),
+                SpiffeAuthenticatorWorker::new(authenticator),
+            )?;
+
+            info!(
+                "started a SPIFFE authenticator at '{}' for the trust domain {}",
+                spiffe.address, spiffe.trust_domain
+            );
+        }
+        Ok(())
+    }
+
     /// Start an echo service
     pub fn start_echo_service(
         &self,
@@ -308,6 +496,29 @@ impl Authority {
         ctx.start_worker(address, Echoer)
     }
 
+    /// Start the health service, used to check that the node can reach its database
+    pub fn start_health_service(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        let worker = AuthorityHealthWorker::new(
+            &self.identifier,
+            configuration.replica_name.clone(),
+            self.database.clone(),
+        );
+
+        let address = DefaultAddress::AUTHORITY_HEALTH.to_string();
+        ctx.flow_controls()
+            .add_consumer(&address.clone().into(), secure_channel_flow_control_id);
+
+        ctx.start_worker(address.clone(), worker)?;
+
+        info!("started a health service at '{address}'");
+        Ok(())
+    }
+
     /// Add a member directly to storage, without additional validation
     /// This is used during the authority start-up to add an identity for exporting traces
     pub async fn add_member(
@@ -363,6 +574,7 @@ mod tests {
     use crate::authenticator::one_time_code::OneTimeCode;
     use crate::authenticator::{PreTrustedIdentities, PreTrustedIdentity};
     use crate::authority_node;
+    use crate::authority_node::AuthorityHealthCheck;
     use crate::config::lookup::InternetAddress;
     use crate::enroll::enrollment::{EnrollStatus, Enrollment};
     use crate::nodes::NodeManager;
@@ -476,6 +688,99 @@ mod tests {
         result.unwrap()
     }
 
+    /// This test starts 2 replicas of the same authority, sharing the same identity
+    /// and the same postgres database.
+    ///
+    /// We test:
+    ///
+    ///  - That both replicas are healthy
+    ///  - That a token issued by a replica can be accepted by the other one
+    ///  - That a token can't be used more than once across replicas
+    #[test]
+    fn test_authority_replicas_sharing_the_same_identity_and_postgres_database() {
+        let result = execute_test(|db_base, ctx1_base, ctx2_base, ctx_client_base| {
+            let db = db_base.clone();
+            let ctx1 = ctx1_base.try_clone().unwrap();
+            let ctx2 = ctx2_base.try_clone().unwrap();
+            let ctx_client = ctx_client_base.try_clone().unwrap();
+            async move {
+                let port1 = random_port();
+                let port2 = random_port();
+                let secure_channels: Arc<SecureChannels> = secure_channels().await?;
+                let identities_creation = secure_channels.identities().identities_creation();
+                let enroller = identities_creation.create_identity().await?;
+
+                let authority = identities::create(db.clone(), "authority")
+                    .identities_creation()
+                    .create_identity()
+                    .await?;
+                let replica1 = start_authority_replica(
+                    db.clone(),
+                    &ctx1,
+                    port1,
+                    &authority,
+                    "replica-1",
+                    &[enroller.clone()],
+                )
+                .await?;
+                let replica2 = start_authority_replica(
+                    db,
+                    &ctx2,
+                    port2,
+                    &authority,
+                    "replica-2",
+                    &[enroller.clone()],
+                )
+                .await?;
+                assert_eq!(replica1.identifier(), replica2.identifier());
+
+                let client1 = make_authority_node_client(
+                    &ctx_client,
+                    secure_channels.clone(),
+                    &authority,
+                    &MultiAddr::from_str(&format!("/dnsaddr/127.0.0.1/tcp/{}/secure/api", port1))?,
+                    &enroller,
+                )
+                .await?;
+                let client2 = make_authority_node_client(
+                    &ctx_client,
+                    secure_channels.clone(),
+                    &authority,
+                    &MultiAddr::from_str(&format!("/dnsaddr/127.0.0.1/tcp/{}/secure/api", port2))?,
+                    &enroller,
+                )
+                .await?;
+
+                // both replicas are healthy
+                let health1 = client1.check_health(&ctx_client).await.unwrap();
+                let health2 = client2.check_health(&ctx_client).await.unwrap();
+                assert_eq!(health1.identifier, authority);
+                assert_eq!(health1.replica_name, Some("replica-1".to_string()));
+                assert_eq!(health2.identifier, authority);
+                assert_eq!(health2.replica_name, Some("replica-2".to_string()));
+
+                // a token issued by a replica is accepted by the other one, only once
+                let token = create_token(&ctx_client, &client1, &enroller).await?;
+                let member1 = identities_creation.create_identity().await?;
+                let member2 = identities_creation.create_identity().await?;
+                let enroll_status = accept_token(&ctx_client, &client2, &member1, token).await?;
+                assert_eq!(enroll_status, EnrollStatus::EnrolledSuccessfully);
+                let result = client1
+                    .clone()
+                    .with_client_identifier(&member2)
+                    .present_token(&ctx_client, &token, BTreeMap::new())
+                    .await;
+                assert!(result.is_err());
+
+                // the member can get a credential from both replicas
+                issue_credential(&ctx_client, &client1, &member1).await?;
+                issue_credential(&ctx_client, &client2, &member1).await?;
+                Ok(())
+            }
+        });
+        result.unwrap()
+    }
+
     /// HELPERS
 
     /// Create an Authority configuration with:
@@ -514,9 +819,13 @@ mod tests {
             no_direct_authentication: false,
             no_token_enrollment: false,
             okta: None,
+            jwt: None,
+            kubernetes: None,
+            spiffe: None,
             account_authority: None,
             enforce_admin_checks: false,
             disable_trust_context_id: false,
+            replica_name: None,
         })
     }
 
@@ -555,9 +864,24 @@ mod tests {
     ) -> Result<Authority> {
         let identities = identities::create(db.clone(), node_name);
         let authority = identities.identities_creation().create_identity().await?;
+        start_authority_replica(db, ctx, port, &authority, node_name, trusted).await
+    }
 
-        let configuration =
-            create_configuration(db.configuration.clone(), &authority, port, trusted)?;
+    /// Create and start an authority node for an existing authority identity, with:
+    ///  - A specific TCP listener port
+    ///  - A specific replica name
+    ///  - An identifier for an enroller
+    async fn start_authority_replica(
+        db: SqlxDatabase,
+        ctx: &Context,
+        port: u16,
+        authority: &Identifier,
+        replica_name: &str,
+        trusted: &[Identifier],
+    ) -> Result<Authority> {
+        let mut configuration =
+            create_configuration(db.configuration.clone(), authority, port, trusted)?;
+        configuration.replica_name = Some(replica_name.to_string());
         let authority = Authority::create(&configuration, Some(db.clone())).await?;
         authority_node::start_node(ctx, &configuration, authority.clone()).await?;
         Ok(authority)
@@ -637,7 +961,7 @@ mod tests {
         Ok(client
             .clone()
             .with_client_identifier(member)
-            .present_token(ctx, &token)
+            .present_token(ctx, &token, BTreeMap::new())
             .await
             .unwrap())
     }
@@ -1,5 +1,8 @@
 use ockam::identity::models::ChangeHistory;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use ockam::identity::Identifier;
 use ockam_core::compat::collections::HashMap;
@@ -46,6 +49,15 @@ pub struct Configuration {
     /// optional configuration for the okta service
     pub okta: Option<OktaConfiguration>,
 
+    /// optional configuration for the JWT authenticator service
+    pub jwt: Option<JwtAuthenticatorConfiguration>,
+
+    /// optional configuration for the Kubernetes service account authenticator service
+    pub kubernetes: Option<KubernetesAuthenticatorConfiguration>,
+
+    /// optional configuration for the SPIFFE authenticator service
+    pub spiffe: Option<SpiffeAuthenticatorConfiguration>,
+
     /// Account Authority identity
     pub account_authority: Option<ChangeHistory>,
 
@@ -55,6 +67,10 @@ pub struct Configuration {
     /// Will not include trust_context_id and project id into credential
     /// Set to true after old clients are updated
     pub disable_trust_context_id: bool,
+
+    /// optional name of this node, when several authority replicas share the same
+    /// identity and database. It is returned by the health service
+    pub replica_name: Option<String>,
 }
 
 /// Local and private functions for the authority configuration
@@ -117,6 +133,146 @@ impl OktaConfiguration {
     }
 }
 
+/// Default time after which the JWKS of a JWT issuer is fetched again
+pub const DEFAULT_JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Default clock skew tolerated when validating the time claims of a JWT
+pub const DEFAULT_JWT_LEEWAY: Duration = Duration::from_secs(60);
+
+/// Configuration for the JWT authenticator service
+///
+/// The service accepts an OIDC ID token, or any JWT signed by the configured issuer,
+/// and issues a credential containing the attributes mapped from the token claims
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct JwtAuthenticatorConfiguration {
+    pub address: String,
+
+    /// Expected `iss` claim of the tokens
+    pub issuer: String,
+
+    /// Accepted `aud` claims. At least one audience is required
+    pub audiences: Vec<String>,
+
+    /// URL of the issuer JSON Web Key Set. If it is not set, the URL is discovered
+    /// from the OpenID configuration of the issuer
+    pub jwks_url: Option<String>,
+
+    /// Time after which the JSON Web Key Set is fetched again
+    pub jwks_refresh_interval: Duration,
+
+    /// Clock skew tolerated when validating the `exp` and `nbf` claims
+    pub leeway: Duration,
+
+    /// Mapping from claim names to member attribute names.
+    /// A claim name starting with `/` is a JSON pointer to a nested claim, for example
+    /// `/realm_access/roles`
+    pub claims: BTreeMap<String, String>,
+}
+
+impl JwtAuthenticatorConfiguration {
+    /// Create a configuration for an issuer and its accepted audiences,
+    /// with the default address, refresh interval and leeway
+    pub fn new(issuer: &str, audiences: Vec<String>, claims: BTreeMap<String, String>) -> Self {
+        Self {
+            address: DefaultAddress::JWT_AUTHENTICATOR.to_string(),
+            issuer: issuer.to_string(),
+            audiences,
+            jwks_url: None,
+            jwks_refresh_interval: DEFAULT_JWKS_REFRESH_INTERVAL,
+            leeway: DEFAULT_JWT_LEEWAY,
+            claims,
+        }
+    }
+}
+
+/// Issuer of the service account tokens of a Kubernetes cluster with the default configuration
+pub const DEFAULT_KUBERNETES_ISSUER: &str = "https://kubernetes.default.svc.cluster.local";
+
+/// Configuration for the Kubernetes service account authenticator service
+///
+/// The service accepts a projected service account token and enrolls the presenting
+/// identity with attributes derived from the namespace, service account and pod of the token
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct KubernetesAuthenticatorConfiguration {
+    pub address: String,
+
+    /// Expected `iss` claim of the tokens
+    pub issuer: String,
+
+    /// Expected `aud` claim of the tokens, set in the projected volume of the pods
+    pub audience: String,
+
+    /// URL of the cluster JSON Web Key Set. If it is not set, the keys are retrieved
+    /// from the Kubernetes API server, which requires the authority to run in the cluster
+    pub jwks_url: Option<String>,
+
+    /// Time after which the JSON Web Key Set is fetched again
+    pub jwks_refresh_interval: Duration,
+
+    /// Clock skew tolerated when validating the `exp` and `nbf` claims
+    pub leeway: Duration,
+
+    /// Service accounts allowed to enroll, as `namespace/name` or `namespace/*`.
+    /// At least one entry is required, `*/*` allows any service account
+    pub service_accounts: Vec<String>,
+
+    /// If true, check with the Kubernetes API server that the pod of the token
+    /// still exists, in place of a TokenReview
+    pub verify_pods: bool,
+
+    /// Pod labels copied to the member attributes.
+    /// The pods are retrieved from the Kubernetes API server if this list is not empty
+    pub pod_labels: Vec<String>,
+}
+
+impl KubernetesAuthenticatorConfiguration {
+    /// Create a configuration for a cluster, with the default address, refresh interval and leeway
+    pub fn new(issuer: &str, audience: &str) -> Self {
+        Self {
+            address: DefaultAddress::KUBERNETES_AUTHENTICATOR.to_string(),
+            issuer: issuer.to_string(),
+            audience: audience.to_string(),
+            jwks_url: None,
+            jwks_refresh_interval: DEFAULT_JWKS_REFRESH_INTERVAL,
+            leeway: DEFAULT_JWT_LEEWAY,
+            service_accounts: vec![],
+            verify_pods: false,
+            pod_labels: vec![],
+        }
+    }
+
+    /// Return true if the pods must be retrieved from the Kubernetes API server
+    pub fn uses_pods(&self) -> bool {
+        self.verify_pods || !self.pod_labels.is_empty()
+    }
+}
+
+/// Configuration for the SPIFFE authenticator service
+///
+/// The service accepts an X.509-SVID of the configured trust domain, and issues a credential
+/// containing the SPIFFE ID of the workload and its path segments
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SpiffeAuthenticatorConfiguration {
+    pub address: String,
+
+    /// Trust domain of the accepted SPIFFE IDs, for example `example.org`
+    pub trust_domain: String,
+
+    /// Path of the PEM encoded X.509 authorities of the trust domain
+    pub trust_bundle: PathBuf,
+}
+
+impl SpiffeAuthenticatorConfiguration {
+    /// Create a configuration for a trust domain, with the default address
+    pub fn new(trust_domain: &str, trust_bundle: PathBuf) -> Self {
+        Self {
+            address: DefaultAddress::SPIFFE_AUTHENTICATOR.to_string(),
+            trust_domain: trust_domain.to_string(),
+            trust_bundle,
+        }
+    }
+}
+
 /// This struct represents an identity that the Authority accepts
 /// as having all its attributes fully authenticated
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -0,0 +1,124 @@
+use miette::IntoDiagnostic;
+use minicbor::{CborLen, Decode, Encode};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+use ockam::identity::Identifier;
+use ockam_core::api::{Method, Request, RequestHeader, Response};
+use ockam_core::{async_trait, Result, Routed, Worker};
+use ockam_node::database::SqlxDatabase;
+use ockam_node::Context;
+
+use crate::colors::color_primary;
+use crate::nodes::service::default_address::DefaultAddress;
+use crate::orchestrator::{AuthorityNodeClient, HasSecureClient};
+use crate::output::Output;
+
+/// Health of an authority node, returned when the node can serve requests
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthorityHealth {
+    #[n(1)] pub identifier: Identifier,
+    #[n(2)] pub replica_name: Option<String>,
+}
+
+impl Display for AuthorityHealth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Authority {} is healthy",
+            color_primary(&self.identifier)
+        )?;
+        if let Some(replica_name) = &self.replica_name {
+            write!(f, " (replica {})", color_primary(replica_name))?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for AuthorityHealth {
+    fn item(&self) -> crate::Result<String> {
+        Ok(self.to_string())
+    }
+}
+
+/// This worker reports if an authority node is healthy.
+///
+/// Contrary to the echo service, it checks that the database storing the members and the
+/// enrollment tokens can be reached, so that a replica which lost its database connection
+/// can be detected, and avoided by the clients
+pub struct AuthorityHealthWorker {
+    identifier: Identifier,
+    replica_name: Option<String>,
+    database: SqlxDatabase,
+}
+
+impl AuthorityHealthWorker {
+    pub fn new(
+        identifier: &Identifier,
+        replica_name: Option<String>,
+        database: SqlxDatabase,
+    ) -> Self {
+        Self {
+            identifier: identifier.clone(),
+            replica_name,
+            database,
+        }
+    }
+
+    async fn handle_request(&self, req: &RequestHeader) -> Result<Vec<u8>> {
+        match (req.method(), req.path()) {
+            (Some(Method::Get), "/") => match self.database.ping().await {
+                Ok(()) => Ok(Response::ok()
+                    .with_headers(req)
+                    .body(AuthorityHealth {
+                        identifier: self.identifier.clone(),
+                        replica_name: self.replica_name.clone(),
+                    })
+                    .to_vec()?),
+                Err(e) => {
+                    warn!(%e, "the authority database is not reachable");
+                    Ok(
+                        Response::internal_error(req, "The authority database is not reachable")
+                            .to_vec()?,
+                    )
+                }
+            },
+            _ => Ok(Response::unknown_path(req).to_vec()?),
+        }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for AuthorityHealthWorker {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let return_route = m.return_route().clone();
+        let body = m.into_body()?;
+        let req: RequestHeader = minicbor::decode(&body)?;
+        let res = self.handle_request(&req).await?;
+        c.send(return_route, res).await
+    }
+}
+
+#[async_trait]
+pub trait AuthorityHealthCheck {
+    /// Return the health of an authority node, or an error if it can't serve requests
+    async fn check_health(&self, ctx: &Context) -> miette::Result<AuthorityHealth>;
+}
+
+#[async_trait]
+impl AuthorityHealthCheck for AuthorityNodeClient {
+    #[instrument(skip_all)]
+    async fn check_health(&self, ctx: &Context) -> miette::Result<AuthorityHealth> {
+        self.get_secure_client()
+            .ask(ctx, DefaultAddress::AUTHORITY_HEALTH, Request::get("/"))
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+}
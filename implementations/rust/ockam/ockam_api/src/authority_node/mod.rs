@@ -1,7 +1,9 @@
 mod authority;
 mod configuration;
+mod health;
 mod node;
 
 pub use authority::*;
 pub use configuration::*;
+pub use health::*;
 pub use node::*;
@@ -28,15 +28,38 @@ pub async fn start_node(
     authority.start_credential_issuer(ctx, &secure_channel_flow_control_id, configuration)?;
     debug!("credential issuer started");
 
+    authority.start_revocation_list_service(ctx, &secure_channel_flow_control_id)?;
+    debug!("revocation list service started");
+
     // start the Okta service (if the optional configuration has been provided)
     authority.start_okta(ctx, &secure_channel_flow_control_id, configuration)?;
     debug!("okta service started");
 
+    // start the JWT authenticator (if the optional configuration has been provided)
+    authority.start_jwt_authenticator(ctx, &secure_channel_flow_control_id, configuration)?;
+    debug!("jwt authenticator started");
+
+    // start the Kubernetes authenticator (if the optional configuration has been provided)
+    authority.start_kubernetes_authenticator(
+        ctx,
+        &secure_channel_flow_control_id,
+        configuration,
+    )?;
+    debug!("kubernetes authenticator started");
+
+    // start the SPIFFE authenticator (if the optional configuration has been provided)
+    authority.start_spiffe_authenticator(ctx, &secure_channel_flow_control_id, configuration)?;
+    debug!("spiffe authenticator started");
+
     // start an echo service so that the node can be queried as healthy
     authority.start_echo_service(ctx, &secure_channel_flow_control_id)?;
 
     debug!("echo service started");
 
+    // start a health service, checking that the node can reach its database
+    authority.start_health_service(ctx, &secure_channel_flow_control_id, configuration)?;
+    debug!("health service started");
+
     info!("authority node started");
 
     Ok(())
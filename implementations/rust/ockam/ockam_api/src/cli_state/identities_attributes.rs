@@ -1,6 +1,7 @@
 use crate::CliState;
 use ockam::identity::{
     IdentitiesAttributes, IdentityAttributesRepository, IdentityAttributesSqlxDatabase,
+    RevocationListRepository, RevocationListSqlxDatabase,
 };
 use std::sync::Arc;
 
@@ -9,6 +10,7 @@ impl CliState {
     pub fn identities_attributes(&self, node_name: &str) -> Arc<IdentitiesAttributes> {
         Arc::new(IdentitiesAttributes::new(
             self.identity_attributes_repository(node_name),
+            self.revocation_list_repository(node_name),
         ))
     }
 
@@ -20,4 +22,10 @@ impl CliState {
     ) -> Arc<dyn IdentityAttributesRepository> {
         IdentityAttributesSqlxDatabase::make_repository(self.database(), node_name)
     }
+
+    /// The revocation list repository is used by the identities_attributes service
+    /// to ignore the attributes of revoked identities
+    fn revocation_list_repository(&self, node_name: &str) -> Arc<dyn RevocationListRepository> {
+        RevocationListSqlxDatabase::make_repository(self.database(), node_name)
+    }
 }
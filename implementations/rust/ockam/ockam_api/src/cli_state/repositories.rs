@@ -6,12 +6,16 @@ use ockam::identity::{
 use ockam_core::compat::sync::Arc;
 use ockam_vault::storage::{SecretsRepository, SecretsSqlxDatabase};
 
+use crate::acme::storage::{AcmeCertificatesRepository, AcmeCertificatesSqlxDatabase};
 use crate::cli_state::storage::*;
 use crate::cli_state::CliState;
 use crate::cli_state::{EnrollmentsRepository, EnrollmentsSqlxDatabase};
 use crate::cli_state::{ProjectsRepository, ProjectsSqlxDatabase};
 use crate::cli_state::{SpacesRepository, SpacesSqlxDatabase};
 use crate::cli_state::{UsersRepository, UsersSqlxDatabase};
+use crate::kafka::reencryption::storage::{
+    KafkaReencryptionRepository, KafkaReencryptionSqlxDatabase,
+};
 
 /// These functions create repository implementations to access data
 /// stored in the database
@@ -64,6 +68,14 @@ impl CliState {
         JourneysSqlxDatabase::make_repository(self.application_database())
     }
 
+    pub fn kafka_reencryption_repository(&self) -> Arc<dyn KafkaReencryptionRepository> {
+        KafkaReencryptionSqlxDatabase::make_repository(self.database())
+    }
+
+    pub fn acme_certificates_repository(&self) -> Arc<dyn AcmeCertificatesRepository> {
+        AcmeCertificatesSqlxDatabase::make_repository(self.database())
+    }
+
     pub fn cached_credentials_repository(&self, node_name: &str) -> Arc<dyn CredentialRepository> {
         CredentialSqlxDatabase::make_repository(self.database(), node_name)
     }
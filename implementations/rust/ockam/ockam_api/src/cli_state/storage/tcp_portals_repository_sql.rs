@@ -15,7 +15,7 @@ use ockam_core::Result;
 use ockam_core::{async_trait, Address};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::database::AutoRetry;
-use ockam_node::database::Boolean;
+use ockam_node::database::{Boolean, Nullable};
 use ockam_transport_core::HostnamePort;
 
 #[derive(Clone)]
@@ -102,15 +102,22 @@ impl TcpPortalsRepository for TcpPortalsSqlxDatabase {
     ) -> ockam_core::Result<()> {
         let query = query(
             r#"
-            INSERT INTO tcp_outlet_status (node_name, socket_addr, worker_addr, payload, privileged)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO tcp_outlet_status (node_name, socket_addr, worker_addr, payload, privileged, load_balancing)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT DO NOTHING"#,
         )
         .bind(node_name)
         .bind(tcp_outlet_status.to.to_string())
         .bind(tcp_outlet_status.worker_addr.to_string())
         .bind(tcp_outlet_status.payload.as_ref())
-        .bind(tcp_outlet_status.privileged);
+        .bind(tcp_outlet_status.privileged)
+        .bind(
+            tcp_outlet_status
+                .load_balancing
+                .as_ref()
+                .map(ockam_core::cbor_encode_preallocate)
+                .transpose()?,
+        );
         query.execute(&*self.database.pool).await.void()?;
         Ok(())
     }
@@ -120,7 +127,7 @@ impl TcpPortalsRepository for TcpPortalsSqlxDatabase {
         node_name: &str,
         worker_addr: &Address,
     ) -> ockam_core::Result<Option<OutletStatus>> {
-        let query = query_as("SELECT socket_addr, worker_addr, payload, privileged FROM tcp_outlet_status WHERE node_name = $1 AND worker_addr = $2")
+        let query = query_as("SELECT socket_addr, worker_addr, payload, privileged, load_balancing FROM tcp_outlet_status WHERE node_name = $1 AND worker_addr = $2")
             .bind(node_name)
             .bind(worker_addr.to_string());
         let result: Option<TcpOutletStatusRow> = query
@@ -183,6 +190,7 @@ struct TcpOutletStatusRow {
     worker_addr: String,
     payload: Option<String>,
     privileged: Boolean,
+    load_balancing: Nullable<Vec<u8>>,
 }
 
 impl TcpOutletStatusRow {
@@ -190,18 +198,25 @@ impl TcpOutletStatusRow {
         let to = HostnamePort::from_str(&self.socket_addr)
             .map_err(|e| Error::new(Origin::Application, Kind::Serialization, e.to_string()))?;
         let worker_addr = Address::from_string(&self.worker_addr);
-        Ok(OutletStatus {
+        let load_balancing = match self.load_balancing.to_option() {
+            Some(load_balancing) => Some(minicbor::decode(&load_balancing)?),
+            None => None,
+        };
+        Ok(OutletStatus::new(
             to,
             worker_addr,
-            payload: self.payload.clone(),
-            privileged: self.privileged.to_bool(),
-        })
+            self.payload.clone(),
+            self.privileged.to_bool(),
+        )
+        .with_load_balancing(load_balancing))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nodes::models::portal::OutletLoadBalancing;
+    use ockam::tcp::{TcpOutletHealthCheck, TcpOutletLoadBalancing};
     use ockam_node::database::with_dbs;
 
     #[tokio::test]
@@ -237,6 +252,27 @@ mod tests {
             let actual = repository.get_tcp_outlet("node_name", &worker_addr).await?;
             assert_eq!(actual, Some(tcp_outlet_status.clone()));
 
+            // the settings of a load balanced outlet are stored with the outlet
+            let load_balanced_worker_addr = Address::from_str("load_balanced").unwrap();
+            let load_balanced_outlet_status = OutletStatus::new(
+                HostnamePort::from_str("127.0.0.1:80").unwrap(),
+                load_balanced_worker_addr.clone(),
+                None,
+                false,
+            )
+            .with_load_balancing(Some(OutletLoadBalancing::new(
+                vec![HostnamePort::from_str("127.0.0.1:81").unwrap()],
+                TcpOutletLoadBalancing::LeastConnections,
+                Some(TcpOutletHealthCheck::new()),
+            )));
+            repository
+                .store_tcp_outlet("node_name", &load_balanced_outlet_status)
+                .await?;
+            let actual = repository
+                .get_tcp_outlet("node_name", &load_balanced_worker_addr)
+                .await?;
+            assert_eq!(actual, Some(load_balanced_outlet_status));
+
             repository
                 .delete_tcp_outlet("node_name", &worker_addr)
                 .await?;
@@ -1,6 +1,6 @@
 use super::Result;
 use crate::cli_state::TcpInlet;
-use crate::nodes::models::portal::OutletStatus;
+use crate::nodes::models::portal::{OutletLoadBalancing, OutletStatus};
 use crate::CliState;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Address;
@@ -60,9 +60,11 @@ impl CliState {
         worker_addr: &Address,
         payload: &Option<String>,
         privileged: bool,
+        load_balancing: Option<OutletLoadBalancing>,
     ) -> Result<OutletStatus> {
         let tcp_outlet_status =
-            OutletStatus::new(to.clone(), worker_addr.clone(), payload.clone(), privileged);
+            OutletStatus::new(to.clone(), worker_addr.clone(), payload.clone(), privileged)
+                .with_load_balancing(load_balancing);
 
         self.tcp_portals_repository()
             .store_tcp_outlet(node_name, &tcp_outlet_status)
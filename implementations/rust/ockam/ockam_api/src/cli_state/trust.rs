@@ -7,7 +7,7 @@ use crate::{ApiError, CliState, TransportRouteResolver};
 use ockam::identity::models::ChangeHistory;
 use ockam::identity::{IdentitiesVerification, RemoteCredentialRetrieverInfo};
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{Error, Result};
+use ockam_core::{Error, Result, Route};
 use ockam_multiaddr::MultiAddr;
 use ockam_vault::SoftwareVaultForVerifyingSignatures;
 
@@ -16,6 +16,7 @@ impl CliState {
         &self,
         authority_identity: &str,
         authority_route: &Option<MultiAddr>,
+        authority_fallback_routes: &[MultiAddr],
         credential_scope: &Option<String>,
     ) -> Result<NodeManagerTrustOptions> {
         let identities_verification = IdentitiesVerification::new(
@@ -34,39 +35,32 @@ impl CliState {
             .import(None, &authority_identity)
             .await?;
 
-        if let Some(authority_multiaddr) = authority_route {
-            let scope = match credential_scope {
-                Some(scope) => scope.clone(),
-                None => {
-                    return Err(Error::new(
-                        Origin::Api,
-                        Kind::NotFound,
-                        "Authority address was provided but credential scope was not provided",
-                    ))
-                }
-            };
-
-            let authority_route = TransportRouteResolver::default()
-                .allow_tcp()
-                .resolve(authority_multiaddr)
-                .map_err(|err| {
-                    Error::new(
-                        Origin::Api,
-                        Kind::NotFound,
-                        format!("Invalid authority route. Err: {}", &err),
-                    )
-                })?;
+        // the other replicas of the authority are used if it can't be reached
+        let authority_routes = match authority_route {
+            Some(authority_multiaddr) => std::iter::once(authority_multiaddr)
+                .chain(authority_fallback_routes)
+                .map(resolve_authority_route)
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![],
+        };
+
+        if let (Some(authority_multiaddr), Some(scope)) = (authority_route, credential_scope) {
             let info = RemoteCredentialRetrieverInfo::create_for_project_member(
                 authority_identifier.clone(),
-                authority_route,
-            );
+                authority_routes[0].clone(),
+            )
+            .with_fallback_routes(authority_routes[1..].to_vec());
 
             let trust_options = NodeManagerTrustOptions::new(
-                NodeManagerCredentialRetrieverOptions::Remote { info, scope },
+                NodeManagerCredentialRetrieverOptions::Remote {
+                    info,
+                    scope: scope.clone(),
+                },
                 NodeManagerCredentialRetrieverOptions::None,
                 Some(authority_identifier.clone()),
                 NodeManagerCredentialRetrieverOptions::None,
-            );
+            )
+            .with_project_authority_routes(authority_routes);
 
             debug!(
                     "TrustOptions configured: Authority: {}. Credentials retrieved from Remote Authority: {}",
@@ -85,7 +79,8 @@ impl CliState {
                 NodeManagerCredentialRetrieverOptions::None,
                 Some(authority_identifier.clone()),
                 NodeManagerCredentialRetrieverOptions::None,
-            );
+            )
+            .with_project_authority_routes(authority_routes);
 
             debug!(
                 "TrustOptions configured: Authority: {}. Expect credentials in cache",
@@ -100,7 +95,8 @@ impl CliState {
             NodeManagerCredentialRetrieverOptions::None,
             Some(authority_identifier.clone()),
             NodeManagerCredentialRetrieverOptions::None,
-        );
+        )
+        .with_project_authority_routes(authority_routes);
 
         debug!(
             "TrustOptions configured: Authority: {}. Only verifying credentials",
@@ -118,16 +114,8 @@ impl CliState {
             .authority_identifier()
             .ok_or_else(|| ApiError::core("no authority identifier"))?;
         let authority_multiaddr = project.authority_multiaddr()?;
-        let authority_route = TransportRouteResolver::default()
-            .allow_tcp()
-            .resolve(authority_multiaddr)
-            .map_err(|err| {
-                Error::new(
-                    Origin::Api,
-                    Kind::NotFound,
-                    format!("Invalid authority route. Err: {}", &err),
-                )
-            })?;
+        let authority_route = resolve_authority_route(authority_multiaddr)?;
+        let authority_routes = vec![authority_route.clone()];
 
         let project_id = project.project_id().to_string();
         let project_member_retriever = NodeManagerCredentialRetrieverOptions::Remote {
@@ -174,7 +162,8 @@ impl CliState {
             project_admin_retriever,
             Some(authority_identifier.clone()),
             account_admin_retriever,
-        );
+        )
+        .with_project_authority_routes(authority_routes);
 
         debug!(
             "TrustOptions configured: Authority: {}. Credentials retrieved from project: {}",
@@ -193,6 +182,7 @@ impl CliState {
         project_name: &Option<String>,
         authority_identity: &Option<ChangeHistory>,
         authority_route: &Option<MultiAddr>,
+        authority_fallback_routes: &[MultiAddr],
         credential_scope: &Option<String>,
     ) -> Result<NodeManagerTrustOptions> {
         if project_name.is_some() && (authority_identity.is_some() || authority_route.is_some()) {
@@ -217,6 +207,7 @@ impl CliState {
                 .retrieve_trust_options_explicit_project_authority(
                     &authority_identity.export_as_string()?,
                     authority_route,
+                    authority_fallback_routes,
                     credential_scope,
                 )
                 .await;
@@ -253,3 +244,17 @@ impl CliState {
         self.retrieve_trust_options_with_project(project).await
     }
 }
+
+/// Resolve the route to an authority node, which must be reached via TCP
+fn resolve_authority_route(authority_multiaddr: &MultiAddr) -> Result<Route> {
+    TransportRouteResolver::default()
+        .allow_tcp()
+        .resolve(authority_multiaddr)
+        .map_err(|err| {
+            Error::new(
+                Origin::Api,
+                Kind::NotFound,
+                format!("Invalid authority route. Err: {}", &err),
+            )
+        })
+}
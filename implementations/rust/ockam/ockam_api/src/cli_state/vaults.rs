@@ -4,9 +4,11 @@ use crate::output::Output;
 use crate::{fmt_log, fmt_ok, fmt_warn};
 use colorful::Colorful;
 use ockam::identity::{Identities, Vault};
+use ockam_core::env::get_env;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_node::database::SqlxDatabase;
 use ockam_vault_aws::AwsSigningVault;
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
 use std::fmt::Write;
 use std::fmt::{Debug, Display, Formatter};
 use std::fs::OpenOptions;
@@ -15,9 +17,13 @@ use std::sync::Arc;
 
 static DEFAULT_VAULT_NAME: &str = "default";
 
+/// Environment variable containing the user PIN of a PKCS#11 token.
+/// The PIN is never stored with the vault metadata
+pub const OCKAM_PKCS11_PIN: &str = "OCKAM_PKCS11_PIN";
+
 /// The methods below support the creation and update of local vaults
 ///
-///  - by default private keys are stored locally but they can also be stored in a KMS
+///  - by default private keys are stored locally but they can also be stored in a KMS or in a PKCS#11 token
 ///  - keys stored locally are stored with other application data in the local database if the default vault is used
 ///  - any additional vault stores its keys in a separate file
 ///
@@ -32,6 +38,29 @@ impl CliState {
         vault_name: Option<String>,
         path: Option<PathBuf>,
         use_aws_kms: UseAwsKms,
+    ) -> Result<NamedVault> {
+        self.create_vault(vault_name, path, use_aws_kms, None).await
+    }
+
+    /// Create a vault with a given name, storing its signing keys in a PKCS#11 token.
+    /// The other secrets are persisted like the secrets of any other vault
+    #[instrument(skip_all, fields(vault_name = vault_name.clone()))]
+    pub async fn create_named_pkcs11_vault(
+        &self,
+        vault_name: Option<String>,
+        path: Option<PathBuf>,
+        pkcs11: Pkcs11Slot,
+    ) -> Result<NamedVault> {
+        self.create_vault(vault_name, path, UseAwsKms::No, Some(pkcs11))
+            .await
+    }
+
+    async fn create_vault(
+        &self,
+        vault_name: Option<String>,
+        path: Option<PathBuf>,
+        use_aws_kms: UseAwsKms,
+        pkcs11: Option<Pkcs11Slot>,
     ) -> Result<NamedVault> {
         let vaults_repository = self.vaults_repository();
 
@@ -58,17 +87,20 @@ impl CliState {
         match path {
             None => match self.vaults_repository().get_database_vault().await? {
                 None => Ok(vaults_repository
-                    .store_vault(&vault_name, VaultType::database(use_aws_kms))
+                    .store_vault(
+                        &vault_name,
+                        VaultType::database(use_aws_kms).with_pkcs11(pkcs11),
+                    )
                     .await?),
                 Some(_) => {
                     let path = self.make_vault_path(&vault_name)?;
                     Ok(self
-                        .create_local_vault(vault_name, &path, use_aws_kms)
+                        .create_local_vault(vault_name, &path, use_aws_kms, pkcs11)
                         .await?)
                 }
             },
             Some(path) => Ok(self
-                .create_local_vault(vault_name, &path, use_aws_kms)
+                .create_local_vault(vault_name, &path, use_aws_kms, pkcs11)
                 .await?),
         }
     }
@@ -208,6 +240,7 @@ impl CliState {
                     vault_name.to_string(),
                     &self.make_vault_path(vault_name)?,
                     UseAwsKms::No,
+                    None,
                 )
                 .await?;
             self.notify_message(fmt_ok!(
@@ -277,12 +310,16 @@ impl CliState {
             VaultType::LocalFileVault {
                 path: old_path,
                 use_aws_kms,
+                pkcs11,
             } => {
                 // copy the file to the new location
                 std::fs::copy(&old_path, path)?;
                 // update the path in the database
                 repository
-                    .update_vault(vault_name, VaultType::local_file(path, use_aws_kms))
+                    .update_vault(
+                        vault_name,
+                        VaultType::local_file(path, use_aws_kms).with_pkcs11(pkcs11),
+                    )
                     .await?;
                 // remove the old file
                 std::fs::remove_file(old_path)?;
@@ -309,6 +346,16 @@ impl CliState {
             vault.identity_vault = aws_vault.clone();
             vault.credential_vault = aws_vault;
             Ok(vault)
+        } else if let Some(pkcs11) = named_vault.vault_type.pkcs11() {
+            let mut config = Pkcs11Config::new(pkcs11.module(), pkcs11.slot());
+            if let Some(pin) = get_env::<String>(OCKAM_PKCS11_PIN)? {
+                config = config.with_pin(pin);
+            }
+            let mut vault = Vault::create_with_database(db);
+            let pkcs11_vault = Arc::new(Pkcs11SigningVault::create(config).await?);
+            vault.identity_vault = pkcs11_vault.clone();
+            vault.credential_vault = pkcs11_vault;
+            Ok(vault)
         } else {
             Ok(Vault::create_with_database(db))
         }
@@ -351,6 +398,7 @@ impl CliState {
         vault_name: String,
         path: &PathBuf,
         use_aws_kms: UseAwsKms,
+        pkcs11: Option<Pkcs11Slot>,
     ) -> Result<NamedVault> {
         // check if the new file can be created
         let path_taken = self
@@ -374,7 +422,10 @@ impl CliState {
         };
         Ok(self
             .vaults_repository()
-            .store_vault(&vault_name, VaultType::local_file(path, use_aws_kms))
+            .store_vault(
+                &vault_name,
+                VaultType::local_file(path, use_aws_kms).with_pkcs11(pkcs11),
+            )
             .await?)
     }
 
@@ -411,10 +462,14 @@ pub struct NamedVault {
 pub enum VaultType {
     DatabaseVault {
         use_aws_kms: UseAwsKms,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pkcs11: Option<Pkcs11Slot>,
     },
     LocalFileVault {
         path: PathBuf,
         use_aws_kms: UseAwsKms,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pkcs11: Option<Pkcs11Slot>,
     },
 }
 
@@ -431,6 +486,9 @@ impl Display for VaultType {
         if self.use_aws_kms() {
             writeln!(f, "Uses AWS KMS: true",)?;
         }
+        if let Some(pkcs11) = self.pkcs11() {
+            writeln!(f, "{pkcs11}")?;
+        }
         Ok(())
     }
 }
@@ -451,15 +509,72 @@ impl UseAwsKms {
     }
 }
 
+/// A slot of a PKCS#11 module storing the signing keys of a vault
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Pkcs11Slot {
+    module: PathBuf,
+    slot: u64,
+}
+
+impl Pkcs11Slot {
+    pub fn new(module: impl Into<PathBuf>, slot: u64) -> Self {
+        Self {
+            module: module.into(),
+            slot,
+        }
+    }
+
+    /// Path of the PKCS#11 module
+    pub fn module(&self) -> &Path {
+        self.module.as_path()
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
+impl Display for Pkcs11Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PKCS#11 module: {}, slot: {}",
+            self.module.to_string_lossy(),
+            self.slot
+        )
+    }
+}
+
 impl VaultType {
     pub fn database(use_aws_kms: UseAwsKms) -> Self {
-        VaultType::DatabaseVault { use_aws_kms }
+        VaultType::DatabaseVault {
+            use_aws_kms,
+            pkcs11: None,
+        }
     }
 
     pub fn local_file(path: impl Into<PathBuf>, use_aws_kms: UseAwsKms) -> Self {
         VaultType::LocalFileVault {
             path: path.into(),
             use_aws_kms,
+            pkcs11: None,
+        }
+    }
+
+    /// Store the signing keys of the vault in a PKCS#11 token
+    pub fn with_pkcs11(self, pkcs11: Option<Pkcs11Slot>) -> Self {
+        match self {
+            VaultType::DatabaseVault { use_aws_kms, .. } => VaultType::DatabaseVault {
+                use_aws_kms,
+                pkcs11,
+            },
+            VaultType::LocalFileVault {
+                path, use_aws_kms, ..
+            } => VaultType::LocalFileVault {
+                path,
+                use_aws_kms,
+                pkcs11,
+            },
         }
     }
 
@@ -472,11 +587,16 @@ impl VaultType {
 
     pub fn use_aws_kms(&self) -> bool {
         match self {
-            VaultType::DatabaseVault { use_aws_kms } => use_aws_kms == &UseAwsKms::Yes,
-            VaultType::LocalFileVault {
-                path: _,
-                use_aws_kms,
-            } => use_aws_kms == &UseAwsKms::Yes,
+            VaultType::DatabaseVault { use_aws_kms, .. } => use_aws_kms == &UseAwsKms::Yes,
+            VaultType::LocalFileVault { use_aws_kms, .. } => use_aws_kms == &UseAwsKms::Yes,
+        }
+    }
+
+    /// Return the PKCS#11 slot storing the signing keys if there is one
+    pub fn pkcs11(&self) -> Option<&Pkcs11Slot> {
+        match self {
+            VaultType::DatabaseVault { pkcs11, .. } => pkcs11.as_ref(),
+            VaultType::LocalFileVault { pkcs11, .. } => pkcs11.as_ref(),
         }
     }
 }
@@ -511,6 +631,11 @@ impl NamedVault {
         self.vault_type.use_aws_kms()
     }
 
+    /// Return the PKCS#11 slot if signing keys are stored in a PKCS#11 token
+    pub fn pkcs11(&self) -> Option<&Pkcs11Slot> {
+        self.vault_type.pkcs11()
+    }
+
     /// Return the vault path if the vault data is stored in a local file
     pub fn path(&self) -> Option<&Path> {
         self.vault_type.path()
@@ -547,6 +672,9 @@ impl Output for NamedVault {
         if self.vault_type.use_aws_kms() {
             writeln!(output, "Uses AWS KMS: true",)?;
         }
+        if let Some(pkcs11) = self.vault_type.pkcs11() {
+            writeln!(output, "{pkcs11}")?;
+        }
         Ok(output)
     }
 }
@@ -772,6 +900,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_create_pkcs11_vault() -> Result<()> {
+        let cli = CliState::test().await?;
+
+        let pkcs11 = Pkcs11Slot::new("/usr/lib/softhsm/libsofthsm2.so", 1);
+        let result = cli
+            .create_named_pkcs11_vault(Some("hsm".to_string()), None, pkcs11.clone())
+            .await?;
+        assert_eq!(result.name(), "hsm".to_string());
+        assert_eq!(result.pkcs11(), Some(&pkcs11));
+        assert!(!result.use_aws_kms());
+
+        // the PKCS#11 slot is persisted with the vault
+        let result = cli.get_named_vault("hsm").await?;
+        assert_eq!(result.pkcs11(), Some(&pkcs11));
+
+        // making the vault fails if the PKCS#11 module cannot be loaded
+        let vault = cli.make_vault(result).await;
+        assert!(vault.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_delete_vault() -> Result<()> {
         let cli = CliState::test().await?;
@@ -1,4 +1,7 @@
+use crate::authenticator::enrollment_tokens::AcceptToken;
+use crate::authenticator::jwt::AuthenticateJwt;
 use crate::authenticator::one_time_code::OneTimeCode;
+use crate::authenticator::spiffe::AuthenticateSvid;
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::orchestrator::enroll::auth0::{AuthenticateOidcToken, OidcToken};
 use crate::orchestrator::HasSecureClient;
@@ -8,6 +11,7 @@ use ockam::identity::SecureClient;
 use ockam_core::api::{Reply, Request, Status};
 use ockam_core::async_trait;
 use ockam_node::Context;
+use std::collections::BTreeMap;
 
 const TARGET: &str = "ockam_api::cloud::enroll";
 
@@ -37,9 +41,24 @@ pub trait Enrollment {
         &self,
         ctx: &Context,
         token: &OneTimeCode,
+        parameters: BTreeMap<String, String>,
     ) -> miette::Result<EnrollStatus>;
 
     async fn issue_credential(&self, ctx: &Context) -> miette::Result<CredentialAndPurposeKey>;
+
+    async fn enroll_with_jwt(
+        &self,
+        ctx: &Context,
+        token: &str,
+    ) -> miette::Result<CredentialAndPurposeKey>;
+
+    async fn enroll_with_kubernetes_token(&self, ctx: &Context, token: &str) -> miette::Result<()>;
+
+    async fn enroll_with_svid(
+        &self,
+        ctx: &Context,
+        request: AuthenticateSvid,
+    ) -> miette::Result<CredentialAndPurposeKey>;
 }
 
 #[async_trait]
@@ -68,13 +87,40 @@ impl<T: HasSecureClient + Send + Sync> Enrollment for T {
         &self,
         ctx: &Context,
         token: &OneTimeCode,
+        parameters: BTreeMap<String, String>,
     ) -> miette::Result<EnrollStatus> {
-        self.get_secure_client().present_token(ctx, token).await
+        self.get_secure_client()
+            .present_token(ctx, token, parameters)
+            .await
     }
 
     async fn issue_credential(&self, ctx: &Context) -> miette::Result<CredentialAndPurposeKey> {
         self.get_secure_client().issue_credential(ctx).await
     }
+
+    async fn enroll_with_jwt(
+        &self,
+        ctx: &Context,
+        token: &str,
+    ) -> miette::Result<CredentialAndPurposeKey> {
+        self.get_secure_client().enroll_with_jwt(ctx, token).await
+    }
+
+    async fn enroll_with_kubernetes_token(&self, ctx: &Context, token: &str) -> miette::Result<()> {
+        self.get_secure_client()
+            .enroll_with_kubernetes_token(ctx, token)
+            .await
+    }
+
+    async fn enroll_with_svid(
+        &self,
+        ctx: &Context,
+        request: AuthenticateSvid,
+    ) -> miette::Result<CredentialAndPurposeKey> {
+        self.get_secure_client()
+            .enroll_with_svid(ctx, request)
+            .await
+    }
 }
 
 // FiXME: this has duplicate with AuthorityNodeClient
@@ -125,8 +171,9 @@ impl Enrollment for SecureClient {
         &self,
         ctx: &Context,
         token: &OneTimeCode,
+        parameters: BTreeMap<String, String>,
     ) -> miette::Result<EnrollStatus> {
-        let req = Request::post("/").body(token);
+        let req = Request::post("/").body(AcceptToken::new(token).with_parameters(parameters));
         trace!(target: TARGET, "present a token");
         match self
             .tell(ctx, DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR, req)
@@ -158,4 +205,45 @@ impl Enrollment for SecureClient {
             .success()
             .into_diagnostic()
     }
+
+    #[instrument(skip_all)]
+    async fn enroll_with_jwt(
+        &self,
+        ctx: &Context,
+        token: &str,
+    ) -> miette::Result<CredentialAndPurposeKey> {
+        let req = Request::post("/").body(AuthenticateJwt::new(token));
+        trace!(target: TARGET, "presenting a JWT");
+        self.ask(ctx, DefaultAddress::JWT_AUTHENTICATOR, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    #[instrument(skip_all)]
+    async fn enroll_with_kubernetes_token(&self, ctx: &Context, token: &str) -> miette::Result<()> {
+        let req = Request::post("/").body(AuthenticateJwt::new(token));
+        trace!(target: TARGET, "presenting a Kubernetes service account token");
+        self.tell(ctx, DefaultAddress::KUBERNETES_AUTHENTICATOR, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    #[instrument(skip_all)]
+    async fn enroll_with_svid(
+        &self,
+        ctx: &Context,
+        request: AuthenticateSvid,
+    ) -> miette::Result<CredentialAndPurposeKey> {
+        let req = Request::post("/").body(request);
+        trace!(target: TARGET, "presenting an X.509-SVID");
+        self.ask(ctx, DefaultAddress::SPIFFE_AUTHENTICATOR, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
 }
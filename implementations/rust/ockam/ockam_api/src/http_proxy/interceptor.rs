@@ -0,0 +1,614 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::{Identifier, IdentitiesAttributes};
+use ockam_abac::expr::str;
+use ockam_abac::{Abac, Env, Expr, PolicyExpression, RateLimits};
+use ockam_core::{async_trait, LocalInfoIdentifier, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{Direction, PortalInterceptor, PortalInterceptorFactory};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::http_proxy::parser::{text_response, RequestHead, RequestParser, RequestPart};
+use crate::ApiError;
+
+/// Prefix of the headers injected with the identity of the caller
+pub const IDENTITY_HEADERS_PREFIX: &str = "X-Ockam-";
+/// Header containing the identifier of the caller
+pub const IDENTIFIER_HEADER: &str = "X-Ockam-Identifier";
+/// Prefix of the headers containing the attributes of the caller
+pub const ATTRIBUTE_HEADER_PREFIX: &str = "X-Ockam-Attr-";
+
+/// A routing rule of an HTTP outlet.
+///
+/// A request matches a route if its method is one of the route methods (any method if the route
+/// doesn't specify any) and if its path matches the route path. Methods are case-sensitive: the
+/// route methods are uppercased when parsed, and a request method must match one of them exactly. A route path ending with `*`
+/// matches all the paths starting with the same prefix.
+///
+/// A route can be guarded by a policy expression, evaluated with the attributes of the caller
+/// and the following attributes:
+///
+///  - `request.method`: the method of the request, for example `GET`
+///  - `request.path`: the percent-decoded path of the request, without its query string
+///
+/// Routes are matched against the percent-decoded path. Requests with a path which is not in a
+/// canonical form are rejected, so that a route can't be bypassed with an equivalent path: paths
+/// with `.` or `..` segments, empty segments (`//`), invalid percent-encodings, or encoded
+/// `/`, `\`, `%` and control characters.
+///
+/// A route is written as `[METHOD[,METHOD]*] PATH [POLICY EXPRESSION]`, for example:
+/// `GET,HEAD /api/* (= subject.role "reader")`.
+#[derive(Clone, Debug, PartialEq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct HttpRoute {
+    #[n(1)] pub methods: Vec<String>,
+    #[n(2)] pub path: String,
+    #[n(3)] pub policy: Option<PolicyExpression>,
+}
+
+impl HttpRoute {
+    /// Return true if a request with this method and path matches the route
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        let method_matches = self.methods.is_empty() || self.methods.iter().any(|m| m == method);
+        let path_matches = match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.path,
+        };
+        method_matches && path_matches
+    }
+}
+
+impl FromStr for HttpRoute {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (methods, rest) = if s.starts_with('/') {
+            (vec![], s)
+        } else {
+            let (methods, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+            let methods = methods
+                .split(',')
+                .filter(|m| !m.is_empty())
+                .map(|m| m.to_uppercase())
+                .collect();
+            (methods, rest.trim_start())
+        };
+        let (path, policy) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if !path.starts_with('/') {
+            return Err(ApiError::core(format!(
+                "the route path must start with '/': {s}"
+            )));
+        }
+        let policy = match policy.trim() {
+            "" => None,
+            policy => Some(PolicyExpression::from_str(policy)?),
+        };
+        Ok(Self {
+            methods,
+            path: path.to_string(),
+            policy,
+        })
+    }
+}
+
+/// Routes of an HTTP outlet, with the information necessary to evaluate their policies
+#[derive(Clone)]
+pub struct HttpRoutes {
+    routes: Vec<(HttpRoute, Option<Expr>)>,
+    identities_attributes: Arc<IdentitiesAttributes>,
+    authority: Option<Identifier>,
+    environment: Env,
+    // shared by all the connections of the outlet
+    rate_limits: RateLimits,
+}
+
+/// Decision taken for a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RouteDecision {
+    Allow,
+    BadRequest,
+    NotFound,
+    Forbidden,
+}
+
+impl HttpRoutes {
+    pub fn new(
+        routes: Vec<HttpRoute>,
+        identities_attributes: Arc<IdentitiesAttributes>,
+        authority: Option<Identifier>,
+        environment: Env,
+    ) -> Self {
+        Self {
+            routes: routes
+                .into_iter()
+                .map(|route| {
+                    let expression = route.policy.clone().map(|p| p.into());
+                    (route, expression)
+                })
+                .collect(),
+            identities_attributes,
+            authority,
+            environment,
+            rate_limits: RateLimits::default(),
+        }
+    }
+
+    /// The first route matching a request decides if the request is allowed.
+    /// If there are no routes, all requests are allowed.
+    async fn decide(&self, identifier: Option<&Identifier>, head: &RequestHead) -> RouteDecision {
+        if self.routes.is_empty() {
+            return RouteDecision::Allow;
+        }
+        let path = match canonical_path(head.path_without_query()) {
+            Some(path) => path,
+            None => {
+                debug!(path = %head.path, "http request rejected: the path is not canonical");
+                return RouteDecision::BadRequest;
+            }
+        };
+        let path = path.as_str();
+        let (route, expression) = match self
+            .routes
+            .iter()
+            .find(|(route, _)| route.matches(&head.method, path))
+        {
+            Some(route) => route,
+            None => return RouteDecision::NotFound,
+        };
+        let expression = match expression {
+            Some(expression) => expression,
+            None => return RouteDecision::Allow,
+        };
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => {
+                warn!("http request rejected: the connection is not authenticated");
+                return RouteDecision::Forbidden;
+            }
+        };
+        let mut environment = self.environment.clone();
+        environment.put("request.method", str(head.method.as_str()));
+        environment.put("request.path", str(path));
+        match Abac::is_identity_authorized_static(
+            self.identities_attributes.clone(),
+            &environment,
+            &self.rate_limits,
+            self.authority.as_ref(),
+            identifier,
+            expression,
+        )
+        .await
+        {
+            Ok(true) => RouteDecision::Allow,
+            Ok(false) => {
+                debug!(%identifier, method = %head.method, %path, route = %route.path, "http request rejected by the route policy");
+                RouteDecision::Forbidden
+            }
+            Err(e) => {
+                warn!(%identifier, method = %head.method, %path, "http route policy evaluation failed: {e:?}");
+                RouteDecision::Forbidden
+            }
+        }
+    }
+
+    /// Add the identifier and the attributes of the caller to the request headers.
+    /// Headers with the same prefix sent by the client are always removed so that they can't be
+    /// forged.
+    async fn inject_identity_headers(
+        &self,
+        identifier: Option<&Identifier>,
+        head: &mut RequestHead,
+    ) {
+        head.remove_headers_with_prefix(IDENTITY_HEADERS_PREFIX);
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => return,
+        };
+        head.headers.push((
+            IDENTIFIER_HEADER.to_string(),
+            identifier.to_string().into_bytes(),
+        ));
+
+        let authority = match &self.authority {
+            Some(authority) => authority,
+            None => return,
+        };
+        let attributes = match self
+            .identities_attributes
+            .get_attributes(identifier, authority)
+            .await
+        {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(%identifier, "cannot retrieve the attributes of the caller: {e:?}");
+                return;
+            }
+        };
+        for (name, value) in attributes.attrs() {
+            match (std::str::from_utf8(name), std::str::from_utf8(value)) {
+                (Ok(name), Ok(value)) if is_header_name(name) && is_header_value(value) => {
+                    head.headers.push((
+                        format!("{ATTRIBUTE_HEADER_PREFIX}{name}"),
+                        value.as_bytes().to_vec(),
+                    ));
+                }
+                _ => debug!(%identifier, "skipping an attribute which can't be sent as a header"),
+            }
+        }
+    }
+}
+
+/// Return the percent-decoded path of a request, or None if the path is not canonical:
+///
+///  - it doesn't start with `/`
+///  - it contains an empty segment, or a `.` or `..` segment, once decoded
+///  - it contains an invalid percent-encoding, or decodes to invalid UTF-8
+///  - it contains a `\` or a control character, or encodes a `/` or a `%`
+fn canonical_path(path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(b) = bytes.next() {
+        let b = if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            let b = u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?;
+            if b == b'/' || b == b'%' {
+                return None;
+            }
+            b
+        } else {
+            b
+        };
+        if b == b'\\' || b.is_ascii_control() {
+            return None;
+        }
+        decoded.push(b);
+    }
+    let decoded = String::from_utf8(decoded).ok()?;
+
+    let segments: Vec<&str> = decoded[1..].split('/').collect();
+    let last = segments.len() - 1;
+    for (i, segment) in segments.iter().enumerate() {
+        // a trailing slash is the only empty segment allowed
+        if (segment.is_empty() && i != last) || *segment == "." || *segment == ".." {
+            return None;
+        }
+    }
+    Some(decoded)
+}
+
+/// Return true if a string only contains the characters allowed in a header name (RFC 9110)
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Return true if a string can be sent as a header value, without any control character
+fn is_header_value(value: &str) -> bool {
+    value.bytes().all(|b| b == b'\t' || !b.is_ascii_control())
+}
+
+/// Creates an interceptor for each connection to an HTTP outlet
+pub struct HttpProxyInterceptorFactory {
+    routes: Arc<HttpRoutes>,
+    inject_identity_headers: bool,
+}
+
+impl HttpProxyInterceptorFactory {
+    pub fn new(routes: HttpRoutes, inject_identity_headers: bool) -> Self {
+        Self {
+            routes: Arc::new(routes),
+            inject_identity_headers,
+        }
+    }
+}
+
+impl PortalInterceptorFactory for HttpProxyInterceptorFactory {
+    fn create(&self) -> Arc<dyn PortalInterceptor> {
+        self.create_for_identifier(None)
+    }
+
+    fn create_for_identifier(
+        &self,
+        identifier: Option<LocalInfoIdentifier>,
+    ) -> Arc<dyn PortalInterceptor> {
+        Arc::new(HttpProxyInterceptor {
+            identifier: identifier.map(|i| i.into()),
+            routes: self.routes.clone(),
+            inject_identity_headers: self.inject_identity_headers,
+            state: Arc::new(Mutex::new(HttpProxyConnection::default())),
+        })
+    }
+}
+
+/// Interceptor parsing the HTTP/1.1 requests sent to an outlet, acting as a reverse proxy.
+///
+/// Each request is checked against the routes of the outlet. A rejected request is answered
+/// with a `403 Forbidden` response, or `404 Not Found` if no route matches it, and is never
+/// sent to the server.
+///
+/// The responses of the server are not parsed. Since a rejected request is answered directly,
+/// clients pipelining several requests on the same connection may receive that answer before
+/// the responses to the previous requests.
+struct HttpProxyInterceptor {
+    identifier: Option<Identifier>,
+    routes: Arc<HttpRoutes>,
+    inject_identity_headers: bool,
+    state: Arc<Mutex<HttpProxyConnection>>,
+}
+
+#[derive(Default)]
+struct HttpProxyConnection {
+    parser: RequestParser,
+    /// True if the body of the current request must be discarded
+    discarding_body: bool,
+}
+
+impl HttpProxyInterceptor {
+    async fn process_requests(&self, buffer: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut connection = self.state.lock().await;
+        let mut forward = Vec::with_capacity(buffer.len());
+        let mut reply = vec![];
+        for part in connection.parser.process_http_buffer(buffer)? {
+            match part {
+                RequestPart::Head(mut head) => {
+                    let identifier = self.identifier.as_ref();
+                    let decision = self.routes.decide(identifier, &head).await;
+                    connection.discarding_body = decision != RouteDecision::Allow;
+                    match decision {
+                        RouteDecision::Allow => {
+                            if self.inject_identity_headers {
+                                self.routes
+                                    .inject_identity_headers(identifier, &mut head)
+                                    .await;
+                            }
+                            head.encode_into(&mut forward);
+                        }
+                        RouteDecision::BadRequest => {
+                            reply.extend(text_response(400, "Bad Request", "Bad Request"))
+                        }
+                        RouteDecision::NotFound => {
+                            reply.extend(text_response(404, "Not Found", "Not Found"))
+                        }
+                        RouteDecision::Forbidden => {
+                            reply.extend(text_response(403, "Forbidden", "Forbidden"))
+                        }
+                    }
+                }
+                RequestPart::Body(body) => {
+                    if !connection.discarding_body {
+                        forward.extend_from_slice(&body)
+                    }
+                }
+            }
+        }
+        Ok((forward, reply))
+    }
+}
+
+#[async_trait]
+impl PortalInterceptor for HttpProxyInterceptor {
+    async fn intercept(
+        &self,
+        context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .intercept_with_reply(context, direction, buffer)
+            .await?
+            .0)
+    }
+
+    async fn intercept_with_reply(
+        &self,
+        _context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        match direction {
+            Direction::FromOutletToInlet => Ok((Some(buffer.to_vec()), None)),
+            Direction::FromInletToOutlet => {
+                let (forward, reply) = self.process_requests(buffer).await?;
+                Ok((
+                    (!forward.is_empty()).then_some(forward),
+                    (!reply.is_empty()).then_some(reply),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::utils::now;
+    use ockam::identity::{identities, AttributesEntry};
+    use ockam_core::compat::collections::BTreeMap;
+
+    #[test]
+    fn parse_routes() {
+        let route = HttpRoute::from_str(r#"GET,head /api/* (= subject.role "reader")"#).unwrap();
+        assert_eq!(route.methods, vec!["GET", "HEAD"]);
+        assert_eq!(route.path, "/api/*");
+        assert!(route.policy.is_some());
+        assert!(route.matches("HEAD", "/api/users"));
+        assert!(!route.matches("get", "/api/users"));
+        assert!(!route.matches("POST", "/api/users"));
+        assert!(!route.matches("GET", "/other"));
+
+        let route = HttpRoute::from_str("/health").unwrap();
+        assert!(route.methods.is_empty());
+        assert_eq!(route.policy, None);
+        assert!(route.matches("DELETE", "/health"));
+        assert!(!route.matches("GET", "/health/details"));
+
+        assert!(HttpRoute::from_str("GET api").is_err());
+    }
+
+    #[test]
+    fn canonical_paths() {
+        assert_eq!(canonical_path("/"), Some("/".to_string()));
+        assert_eq!(
+            canonical_path("/api/users/"),
+            Some("/api/users/".to_string())
+        );
+        assert_eq!(
+            canonical_path("/p%75blic/a%20b"),
+            Some("/public/a b".to_string())
+        );
+        assert_eq!(canonical_path("/caf%C3%A9"), Some("/café".to_string()));
+
+        for path in [
+            "",
+            "*",
+            "http://example.com/admin",
+            "/public/../admin",
+            "/public/./admin",
+            "/public/%2e%2e/admin",
+            "/public/%2E%2e/admin",
+            "/public/.%2e/admin",
+            "/public/%2e/admin",
+            "//admin",
+            "/public//admin",
+            "/public/%2fadmin",
+            "/public/%2F..%2Fadmin",
+            "/public/%252e%252e/admin",
+            "/public/..\\admin",
+            "/public/%5c..%5cadmin",
+            "/public/%00",
+            "/public/%zz",
+            "/public/%2",
+            "/public/%ff",
+        ] {
+            assert_eq!(canonical_path(path), None, "{path} should be rejected");
+        }
+    }
+
+    fn interceptor(routes: &HttpRoutes, identifier: &Identifier) -> HttpProxyInterceptor {
+        HttpProxyInterceptor {
+            identifier: Some(identifier.clone()),
+            routes: Arc::new(routes.clone()),
+            inject_identity_headers: true,
+            state: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn check_routes_and_inject_headers() -> Result<()> {
+        let identities = identities().await?;
+        let authority = identities.identities_creation().create_identity().await?;
+        let reader = identities.identities_creation().create_identity().await?;
+        let other = identities.identities_creation().create_identity().await?;
+        let identities_attributes = identities.identities_attributes();
+        identities_attributes
+            .put_attributes(
+                &reader,
+                AttributesEntry::new(
+                    BTreeMap::from([(b"role".to_vec(), b"reader".to_vec())]),
+                    now()?,
+                    None,
+                    Some(authority.clone()),
+                ),
+            )
+            .await?;
+
+        let routes = HttpRoutes::new(
+            vec![
+                HttpRoute::from_str(r#"GET /api/* (= subject.role "reader")"#)?,
+                HttpRoute::from_str("/health")?,
+            ],
+            identities_attributes,
+            Some(authority),
+            Env::new(),
+        );
+        let requests = "GET /api/users?limit=1 HTTP/1.1\r\nX-Ockam-Identifier: forged\r\n\r\n\
+POST /health HTTP/1.1\r\nContent-Length: 2\r\n\r\nok\
+PUT /other HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+
+        let (forward, reply) = interceptor(&routes, &reader)
+            .process_requests(requests.as_bytes())
+            .await?;
+        assert_eq!(
+            String::from_utf8(forward).unwrap(),
+            format!(
+                "GET /api/users?limit=1 HTTP/1.1\r\nX-Ockam-Identifier: {reader}\r\nX-Ockam-Attr-role: reader\r\n\r\n\
+POST /health HTTP/1.1\r\nContent-Length: 2\r\nX-Ockam-Identifier: {reader}\r\nX-Ockam-Attr-role: reader\r\n\r\nok"
+            )
+        );
+        assert!(String::from_utf8(reply)
+            .unwrap()
+            .starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let (forward, reply) = interceptor(&routes, &other)
+            .process_requests(requests.as_bytes())
+            .await?;
+        assert_eq!(
+            String::from_utf8(forward).unwrap(),
+            format!("POST /health HTTP/1.1\r\nContent-Length: 2\r\nX-Ockam-Identifier: {other}\r\n\r\nok")
+        );
+        let reply = String::from_utf8(reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(reply.contains("HTTP/1.1 404 Not Found\r\n"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reject_non_canonical_paths() -> Result<()> {
+        let identities = identities().await?;
+        let caller = identities.identities_creation().create_identity().await?;
+        let routes = HttpRoutes::new(
+            vec![
+                HttpRoute::from_str(r#"/admin/* (= subject.role "admin")"#)?,
+                HttpRoute::from_str("/public/*")?,
+            ],
+            identities.identities_attributes(),
+            None,
+            Env::new(),
+        );
+
+        for path in [
+            "/public/../admin/users",
+            "/public/%2e%2e/admin/users",
+            "//admin/users",
+            "/public/%2fadmin",
+        ] {
+            let request = format!("GET {path} HTTP/1.1\r\nContent-Length: 2\r\n\r\nok");
+            let (forward, reply) = interceptor(&routes, &caller)
+                .process_requests(request.as_bytes())
+                .await?;
+            assert!(forward.is_empty(), "{path} should not be forwarded");
+            assert!(String::from_utf8(reply)
+                .unwrap()
+                .starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        }
+
+        // an encoded path is matched once decoded
+        let request = "GET /%61dmin/users HTTP/1.1\r\n\r\n";
+        let (forward, reply) = interceptor(&routes, &caller)
+            .process_requests(request.as_bytes())
+            .await?;
+        assert!(forward.is_empty());
+        assert!(String::from_utf8(reply)
+            .unwrap()
+            .starts_with("HTTP/1.1 403 Forbidden\r\n"));
+
+        let request = "GET /p%75blic/index.html HTTP/1.1\r\n\r\n";
+        let (forward, reply) = interceptor(&routes, &caller)
+            .process_requests(request.as_bytes())
+            .await?;
+        assert!(forward.starts_with(request[..request.len() - 2].as_bytes()));
+        assert!(reply.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+pub mod interceptor;
+pub mod parser;
+pub mod portal;
+
+pub use interceptor::{HttpProxyInterceptorFactory, HttpRoute, HttpRoutes};
+pub use portal::{HttpOutletConfig, HttpPortals};
@@ -0,0 +1,499 @@
+use std::io::Write;
+
+use httparse::{Header, Status};
+use ockam::errcode::{Kind, Origin};
+use tracing::error;
+
+/// Head of an HTTP/1.x request: request line and headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    pub version: u8,
+    pub headers: Vec<(String, Vec<u8>)>,
+}
+
+impl RequestHead {
+    fn new(req: &httparse::Request) -> Self {
+        Self {
+            method: req.method.unwrap_or_default().to_string(),
+            path: req.path.unwrap_or_default().to_string(),
+            version: req.version.unwrap_or(1),
+            headers: req
+                .headers
+                .iter()
+                .map(|h| (h.name.to_string(), h.value.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Return the path of the request, without its query string
+    pub fn path_without_query(&self) -> &str {
+        self.path
+            .split_once('?')
+            .map(|(path, _)| path)
+            .unwrap_or(&self.path)
+    }
+
+    /// Remove all the headers with a given name
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    }
+
+    /// Remove all the headers starting with a given prefix
+    pub fn remove_headers_with_prefix(&mut self, prefix: &str) {
+        self.headers.retain(|(n, _)| {
+            !(n.len() >= prefix.len() && n[..prefix.len()].eq_ignore_ascii_case(prefix))
+        });
+    }
+
+    /// Serialize the request line and the headers
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        write!(
+            buffer,
+            "{} {} HTTP/1.{}\r\n",
+            self.method, self.path, self.version
+        )
+        .unwrap();
+        for (name, value) in &self.headers {
+            write!(buffer, "{}: ", name).unwrap();
+            buffer.extend_from_slice(value);
+            buffer.extend_from_slice(b"\r\n");
+        }
+        buffer.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Part of an HTTP request stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestPart {
+    /// Head of a new request
+    Head(RequestHead),
+    /// Data belonging to the body of the last request, including the chunked encoding framing
+    Body(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RequestState {
+    ParsingHeader(Option<Vec<u8>>),
+    ParsingChunkedHeader(Option<Vec<u8>>),
+    RemainingInChunk(usize),
+    ParsingChunkEnd(Option<Vec<u8>>),
+    ParsingTrailer(Option<Vec<u8>>),
+    RemainingBody(usize),
+}
+
+/// Parser for a stream of HTTP/1.1 requests.
+///
+/// Data is received in chunks, and there is no warranty on what we get on each:
+/// incomplete requests, multiple requests, etc. The parser splits that data into request heads
+/// and bodies, so that the heads can be inspected and rewritten.
+///
+/// Chunked bodies are parsed strictly, up to the end of their trailer section: every line of the
+/// chunked framing must end with CRLF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestParser {
+    state: RequestState,
+}
+
+impl Default for RequestParser {
+    fn default() -> Self {
+        Self {
+            state: RequestState::ParsingHeader(None),
+        }
+    }
+}
+
+impl RequestParser {
+    /// Return true if the parser is not in the middle of a request
+    pub fn is_between_requests(&self) -> bool {
+        self.state == RequestState::ParsingHeader(None)
+    }
+
+    /// Parse the incoming data and return the request parts that it completes
+    pub fn process_http_buffer(&mut self, buf: &[u8]) -> ockam_core::Result<Vec<RequestPart>> {
+        let mut parts = vec![];
+        let mut cursor = buf;
+        loop {
+            if cursor.is_empty() {
+                return Ok(parts);
+            }
+            match &mut self.state {
+                RequestState::ParsingHeader(prev) => {
+                    let (to_parse, prev_size): (&[u8], usize) = if let Some(b) = prev {
+                        let prev_size = b.len();
+                        b.extend_from_slice(cursor);
+                        (b, prev_size)
+                    } else {
+                        (cursor, 0usize)
+                    };
+                    let mut headers = [httparse::EMPTY_HEADER; 64];
+                    let mut req = httparse::Request::new(&mut headers);
+                    match req.parse(to_parse) {
+                        Ok(httparse::Status::Partial) if prev_size == 0 => {
+                            // No previous buffered, need to copy and own the unparsed data
+                            self.state = RequestState::ParsingHeader(Some(cursor.to_vec()));
+                            return Ok(parts);
+                        }
+                        Ok(httparse::Status::Partial) => {
+                            // There was a previous buffer, and we already added the newly data to it
+                            return Ok(parts);
+                        }
+                        Ok(httparse::Status::Complete(body_offset)) => {
+                            cursor = &cursor[body_offset - prev_size..];
+                            parts.push(RequestPart::Head(RequestHead::new(&req)));
+                            self.state = body_state(req.headers)?;
+                        }
+                        Err(e) => {
+                            error!("Error parsing header: {:?}", e);
+                            return Err(ockam_core::Error::new(
+                                Origin::Transport,
+                                Kind::Invalid,
+                                e,
+                            ));
+                        }
+                    }
+                }
+                RequestState::RemainingBody(remaining) => {
+                    if *remaining <= cursor.len() {
+                        push_body(&mut parts, &cursor[..*remaining]);
+                        cursor = &cursor[*remaining..];
+                        self.state = RequestState::ParsingHeader(None);
+                    } else {
+                        push_body(&mut parts, cursor);
+                        *remaining -= cursor.len();
+                        return Ok(parts);
+                    }
+                }
+                RequestState::ParsingChunkedHeader(prev) => {
+                    let Some(line) = read_line(prev, &mut cursor)? else {
+                        return Ok(parts);
+                    };
+                    let chunk_size = parse_chunk_size(&line)?;
+                    push_body(&mut parts, &line);
+                    self.state = if chunk_size == 0 {
+                        RequestState::ParsingTrailer(None)
+                    } else {
+                        RequestState::RemainingInChunk(chunk_size)
+                    };
+                }
+                RequestState::RemainingInChunk(size) => {
+                    if cursor.len() >= *size {
+                        push_body(&mut parts, &cursor[..*size]);
+                        cursor = &cursor[*size..];
+                        self.state = RequestState::ParsingChunkEnd(None);
+                    } else {
+                        push_body(&mut parts, cursor);
+                        *size -= cursor.len();
+                        return Ok(parts);
+                    }
+                }
+                RequestState::ParsingChunkEnd(prev) => {
+                    let Some(line) = read_line(prev, &mut cursor)? else {
+                        return Ok(parts);
+                    };
+                    if line != b"\r\n" {
+                        return Err(invalid_framing("the chunk data must be followed by CRLF"));
+                    }
+                    push_body(&mut parts, &line);
+                    self.state = RequestState::ParsingChunkedHeader(None);
+                }
+                RequestState::ParsingTrailer(prev) => {
+                    let Some(line) = read_line(prev, &mut cursor)? else {
+                        return Ok(parts);
+                    };
+                    push_body(&mut parts, &line);
+                    if line == b"\r\n" {
+                        self.state = RequestState::ParsingHeader(None);
+                    } else {
+                        check_trailer_field(&line)?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Append some body data to the last part if it is already a body part
+fn push_body(parts: &mut Vec<RequestPart>, data: &[u8]) {
+    match parts.last_mut() {
+        Some(RequestPart::Body(body)) => body.extend_from_slice(data),
+        _ => parts.push(RequestPart::Body(data.to_vec())),
+    }
+}
+
+/// Maximum length of a chunk size line or of a trailer field
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+
+/// Accumulate the incoming data until the end of a line and return the complete line,
+/// including its CRLF. Lines ending with a bare LF are rejected.
+fn read_line(
+    prev: &mut Option<Vec<u8>>,
+    cursor: &mut &[u8],
+) -> ockam_core::Result<Option<Vec<u8>>> {
+    let buffer = prev.get_or_insert_with(Vec::new);
+    let end = cursor.iter().position(|b| *b == b'\n');
+    let (data, rest) = match end {
+        Some(end) => cursor.split_at(end + 1),
+        None => (*cursor, &[][..]),
+    };
+    buffer.extend_from_slice(data);
+    *cursor = rest;
+    if buffer.len() > MAX_LINE_LENGTH {
+        return Err(invalid_framing("chunked encoding line too long"));
+    }
+    if end.is_none() {
+        return Ok(None);
+    }
+    let line = prev.take().unwrap_or_default();
+    if !line.ends_with(b"\r\n") {
+        return Err(invalid_framing("chunked encoding lines must end with CRLF"));
+    }
+    Ok(Some(line))
+}
+
+/// Parse a complete chunk size line, with its optional extensions
+fn parse_chunk_size(line: &[u8]) -> ockam_core::Result<usize> {
+    if !line.first().is_some_and(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid_framing("missing chunk size"));
+    }
+    match httparse::parse_chunk_size(line) {
+        Ok(Status::Complete((end, size))) if end == line.len() => size
+            .try_into()
+            .map_err(|_| invalid_framing("chunk size too large")),
+        _ => Err(invalid_framing("invalid chunk size")),
+    }
+}
+
+/// Check that a trailer line is a valid header field
+fn check_trailer_field(line: &[u8]) -> ockam_core::Result<()> {
+    let mut headers = [httparse::EMPTY_HEADER; 1];
+    match httparse::parse_headers(&[line, b"\r\n"].concat(), &mut headers) {
+        Ok(Status::Complete(_)) => Ok(()),
+        _ => Err(invalid_framing("invalid trailer field")),
+    }
+}
+
+/// Return the state used to parse the body of a request.
+/// A request only has a body if it has a Content-Length or a chunked Transfer-Encoding header.
+///
+/// Requests with an ambiguous framing are rejected, so that the server can't read them
+/// differently (RFC 9112 section 6.3):
+///
+///  - requests with both a Content-Length and a Transfer-Encoding header
+///  - requests with several Content-Length headers, or an invalid Content-Length
+///  - requests with a Transfer-Encoding whose final coding is not `chunked`
+fn body_state(headers: &[Header]) -> ockam_core::Result<RequestState> {
+    let mut content_length: Option<usize> = None;
+    let mut transfer_codings: Vec<String> = vec![];
+    for h in headers {
+        if h.name.eq_ignore_ascii_case("Content-Length") {
+            if content_length.is_some() {
+                return Err(invalid_framing("multiple Content-Length headers"));
+            }
+            let value = std::str::from_utf8(h.value)
+                .map(|v| v.trim())
+                .ok()
+                .filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+                .ok_or_else(|| invalid_framing("invalid Content-Length header"))?;
+            let length = value
+                .parse()
+                .map_err(|_| invalid_framing("invalid Content-Length header"))?;
+            content_length = Some(length);
+        } else if h.name.eq_ignore_ascii_case("Transfer-Encoding") {
+            let value = std::str::from_utf8(h.value)
+                .map_err(|_| invalid_framing("invalid Transfer-Encoding header"))?;
+            transfer_codings.extend(value.split(',').map(|c| c.trim().to_ascii_lowercase()));
+        }
+    }
+
+    match (content_length, transfer_codings.split_last()) {
+        (Some(_), Some(_)) => Err(invalid_framing(
+            "both Content-Length and Transfer-Encoding headers",
+        )),
+        (None, Some((last, others))) => {
+            if last != "chunked" || others.iter().any(|c| c == "chunked") {
+                Err(invalid_framing(
+                    "the final Transfer-Encoding must be chunked, and only applied once",
+                ))
+            } else {
+                Ok(RequestState::ParsingChunkedHeader(None))
+            }
+        }
+        (Some(0), None) | (None, None) => Ok(RequestState::ParsingHeader(None)),
+        (Some(length), None) => Ok(RequestState::RemainingBody(length)),
+    }
+}
+
+fn invalid_framing(reason: &str) -> ockam_core::Error {
+    error!("Rejecting an HTTP request with an invalid framing: {reason}");
+    ockam_core::Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("invalid request framing: {reason}"),
+    )
+}
+
+/// Create a response with a plain text body, sent to a client when a request is rejected
+pub fn text_response(status: u16, reason: &str, body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {status} {reason}\r\n\
+Content-Type: text/plain\r\n\
+Content-Length: {}\r\n\r\n\
+{body}",
+        body.len()
+    )
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_requests_into_heads_and_bodies() {
+        let req = "PUT /data?x=1 HTTP/1.1\r\n\
+Host: foo.example\r\n\
+X-Ockam-Identifier: spoofed\r\n\
+Content-Length: 5\r\n\r\n\
+hello";
+        let data = [req.as_bytes(), req.as_bytes()].concat();
+
+        for size in [1, 5, 32, 1024] {
+            let mut parser = RequestParser::default();
+            let mut parts = vec![];
+            for chunk in data.chunks(size) {
+                for part in parser.process_http_buffer(chunk).unwrap() {
+                    match (parts.last_mut(), part) {
+                        (Some(RequestPart::Body(body)), RequestPart::Body(more)) => {
+                            body.extend_from_slice(&more)
+                        }
+                        (_, part) => parts.push(part),
+                    }
+                }
+            }
+            assert!(parser.is_between_requests());
+            assert_eq!(parts.len(), 4);
+            assert_eq!(parts[1], RequestPart::Body(b"hello".to_vec()));
+            match &parts[2] {
+                RequestPart::Head(head) => {
+                    assert_eq!(head.method, "PUT");
+                    assert_eq!(head.path_without_query(), "/data");
+
+                    let mut head = head.clone();
+                    head.remove_headers_with_prefix("x-ockam-");
+                    let mut encoded = vec![];
+                    head.encode_into(&mut encoded);
+                    assert_eq!(
+                        String::from_utf8(encoded).unwrap(),
+                        "PUT /data?x=1 HTTP/1.1\r\nHost: foo.example\r\nContent-Length: 5\r\n\r\n"
+                    );
+                }
+                part => panic!("unexpected part {part:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn reject_ambiguous_framing() {
+        for headers in [
+            "Content-Length: 5\r\nTransfer-Encoding: chunked\r\n",
+            "Transfer-Encoding: chunked\r\nContent-Length: 5\r\n",
+            "Content-Length: 5\r\nContent-Length: 5\r\n",
+            "Content-Length: 5\r\nContent-Length: 6\r\n",
+            "Content-Length: +5\r\n",
+            "Content-Length: 5, 5\r\n",
+            "Content-Length: -1\r\n",
+            "Content-Length: \r\n",
+            "Transfer-Encoding: gzip\r\n",
+            "Transfer-Encoding: chunked, gzip\r\n",
+            "Transfer-Encoding: chunked\r\nTransfer-Encoding: gzip\r\n",
+            "Transfer-Encoding: chunked, chunked\r\n",
+            "Transfer-Encoding: xchunked\r\n",
+            "Transfer-Encoding: chunked-false\r\n",
+        ] {
+            let request = format!("POST /data HTTP/1.1\r\nHost: foo.example\r\n{headers}\r\n");
+            let mut parser = RequestParser::default();
+            assert!(
+                parser.process_http_buffer(request.as_bytes()).is_err(),
+                "{headers:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn accept_unambiguous_framing() {
+        for (headers, body) in [
+            ("Content-Length: 5\r\n", "hello"),
+            ("Content-Length:  5 \r\n", "hello"),
+            ("Transfer-Encoding: chunked\r\n", "5\r\nhello\r\n0\r\n\r\n"),
+            (
+                "Transfer-Encoding: gzip, Chunked\r\n",
+                "5\r\nhello\r\n0\r\n\r\n",
+            ),
+            (
+                "Transfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n",
+                "5\r\nhello\r\n0\r\n\r\n",
+            ),
+        ] {
+            let request = format!("POST /data HTTP/1.1\r\n{headers}\r\n{body}");
+            let mut parser = RequestParser::default();
+            let parts = parser.process_http_buffer(request.as_bytes()).unwrap();
+            assert_eq!(parts.len(), 2, "{headers:?}");
+            assert_eq!(parts[1], RequestPart::Body(body.as_bytes().to_vec()));
+            assert!(parser.is_between_requests());
+        }
+    }
+
+    #[test]
+    fn parse_chunked_trailers() {
+        let body = "5;ext=1\r\nhello\r\n0\r\nChecksum: abc\r\nExpires: never\r\n\r\n";
+        let request = format!(
+            "POST /data HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{body}GET / HTTP/1.1\r\n\r\n"
+        );
+        for size in [1, 3, 1024] {
+            let mut parser = RequestParser::default();
+            let mut parts = vec![];
+            for chunk in request.as_bytes().chunks(size) {
+                for part in parser.process_http_buffer(chunk).unwrap() {
+                    match (parts.last_mut(), part) {
+                        (Some(RequestPart::Body(body)), RequestPart::Body(more)) => {
+                            body.extend_from_slice(&more)
+                        }
+                        (_, part) => parts.push(part),
+                    }
+                }
+            }
+            assert!(parser.is_between_requests());
+            assert_eq!(parts.len(), 3);
+            assert_eq!(parts[1], RequestPart::Body(body.as_bytes().to_vec()));
+            match &parts[2] {
+                RequestPart::Head(head) => assert_eq!(head.method, "GET"),
+                part => panic!("unexpected part {part:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn reject_invalid_chunked_framing() {
+        for body in [
+            "5\nhello\r\n0\r\n\r\n",
+            "5\r\nhello\n0\r\n\r\n",
+            "5\r\nhelloXX0\r\n\r\n",
+            "0\n\r\n",
+            "0\r\n\n",
+            "0\r\nChecksum: abc\n\r\n",
+            "0\r\nnot a field\r\n\r\n",
+            "\r\n",
+            "x\r\n",
+        ] {
+            let request =
+                format!("POST /data HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{body}");
+            let mut parser = RequestParser::default();
+            assert!(
+                parser.process_http_buffer(request.as_bytes()).is_err(),
+                "{body:?} should be rejected"
+            );
+        }
+    }
+}
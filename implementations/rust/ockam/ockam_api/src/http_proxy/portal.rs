@@ -0,0 +1,223 @@
+use crate::http_proxy::interceptor::{HttpProxyInterceptorFactory, HttpRoute, HttpRoutes};
+use crate::nodes::models::portal::{CreateOutlet, OutletAccessControl, OutletStatus};
+use crate::nodes::{BackgroundNodeClient, NodeManagerWorker};
+use crate::{ApiError, DefaultAddress};
+use minicbor::{CborLen, Decode, Encode};
+use ockam::flow_control::FlowControls;
+use ockam::{Address, Context, Result};
+use ockam_abac::expr::str;
+use ockam_abac::{Action, Env, PolicyExpression, Resource, ResourceType};
+use ockam_core::api::{Error, Request, Response};
+use ockam_core::async_trait;
+use ockam_transport_core::HostnamePort;
+use ockam_transport_tcp::{read_portal_payload_length, PortalOutletInterceptor};
+use std::sync::Arc;
+
+impl NodeManagerWorker {
+    pub(crate) async fn start_http_outlet_service(
+        &self,
+        ctx: &Context,
+        body: CreateHttpOutlet,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        debug!("Starting HTTP Outlet service");
+        let CreateOutlet {
+            hostname_port,
+            worker_addr,
+            reachable_from_default_secure_channel: _,
+            policy_expression,
+            privileged,
+            tls,
+            skip_handshake,
+            enable_nagle,
+            ..
+        } = body.tcp_outlet;
+        let address = self
+            .node_manager
+            .registry
+            .outlets
+            .generate_worker_addr(worker_addr);
+        let outlet_address: Address = format!("{}_outlet", address.address()).into();
+
+        // Start the interceptor
+        self.create_http_proxy_outlet_interceptor(
+            ctx,
+            address.clone(),
+            outlet_address.clone(),
+            policy_expression.clone(),
+            body.http_config,
+        )
+        .await
+        .map_err(|e| Response::bad_request_no_request(&format!("{e:?}")))?;
+
+        // Start the outlet. It must only be reachable via the interceptor, so that the
+        // requests can't be sent to the server without being checked
+        match self
+            .node_manager
+            .create_outlet(
+                ctx,
+                hostname_port,
+                tls,
+                Some(outlet_address),
+                false,
+                OutletAccessControl::WithPolicyExpression(policy_expression),
+                privileged,
+                skip_handshake,
+                enable_nagle,
+            )
+            .await
+        {
+            Ok(outlet_status) => Ok(Response::ok().body(OutletStatus {
+                worker_addr: address,
+                ..outlet_status
+            })),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    async fn create_http_proxy_outlet_interceptor(
+        &self,
+        ctx: &Context,
+        interceptor_address: Address,
+        outlet_address: Address,
+        outlet_policy_expression: Option<PolicyExpression>,
+        http_config: HttpOutletConfig,
+    ) -> Result<(), Error> {
+        debug!(%interceptor_address, %outlet_address, ?outlet_policy_expression, "Creating http proxy outlet interceptor");
+        let default_secure_channel_listener_flow_control_id = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .ok_or_else(|| {
+                ApiError::core("Unable to get flow control for secure channel listener")
+            })?;
+
+        let policy_access_control = self
+            .node_manager
+            .policy_access_control(
+                self.node_manager.project_authority().clone(),
+                Resource::new(outlet_address.to_string(), ResourceType::TcpOutlet),
+                Action::HandleMessage,
+                outlet_policy_expression.clone(),
+            )
+            .await?;
+
+        let mut environment = Env::new();
+        environment.put("resource.id", str(interceptor_address.address()));
+        let routes = HttpRoutes::new(
+            http_config.routes,
+            self.node_manager
+                .cli_state
+                .identities_attributes(&self.node_manager.node_name()),
+            self.node_manager.project_authority(),
+            environment,
+        );
+
+        let spawner_flow_control_id = FlowControls::generate_flow_control_id();
+
+        let http_interceptor_factory = Arc::new(HttpProxyInterceptorFactory::new(
+            routes,
+            http_config.inject_identity_headers,
+        ));
+
+        PortalOutletInterceptor::create(
+            ctx,
+            interceptor_address.clone(),
+            Some(spawner_flow_control_id.clone()),
+            http_interceptor_factory,
+            Arc::new(policy_access_control.create_outgoing(ctx)?),
+            Arc::new(policy_access_control.create_incoming()),
+            read_portal_payload_length(),
+        )?;
+
+        // every secure channel can reach this service
+        let flow_controls = ctx.flow_controls();
+        flow_controls.add_consumer(
+            &interceptor_address,
+            &default_secure_channel_listener_flow_control_id,
+        );
+
+        // this spawner flow control id is used to control communication with dynamically created
+        // outlets
+        flow_controls.add_spawner(&interceptor_address, &spawner_flow_control_id);
+
+        // allow communication with the tcp outlet
+        flow_controls.add_consumer(&outlet_address, &spawner_flow_control_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait HttpPortals {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_http_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        tls: bool,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        http_config: HttpOutletConfig,
+    ) -> miette::Result<OutletStatus>;
+}
+
+#[async_trait]
+impl HttpPortals for BackgroundNodeClient {
+    #[instrument(skip(self, ctx, http_config))]
+    #[allow(clippy::too_many_arguments)]
+    async fn create_http_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        tls: bool,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        http_config: HttpOutletConfig,
+    ) -> miette::Result<OutletStatus> {
+        let mut outlet_payload =
+            CreateOutlet::new(to, tls, from.cloned(), false, false, false, false);
+        if let Some(policy_expression) = policy_expression {
+            outlet_payload.set_policy_expression(policy_expression);
+        }
+        let payload = CreateHttpOutlet::new(outlet_payload, http_config);
+        let req = Request::post("/node/http_outlet").body(payload);
+        self.ask(ctx, req).await
+    }
+}
+
+/// Request body to create an HTTP outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateHttpOutlet {
+    #[n(1)] pub(crate) tcp_outlet: CreateOutlet,
+    #[n(2)] pub(crate) http_config: HttpOutletConfig,
+}
+
+impl CreateHttpOutlet {
+    pub fn new(tcp_outlet: CreateOutlet, http_config: HttpOutletConfig) -> Self {
+        Self {
+            tcp_outlet,
+            http_config,
+        }
+    }
+}
+
+/// Configuration of the HTTP reverse-proxy interceptor of an outlet
+#[derive(Clone, Debug, Default, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct HttpOutletConfig {
+    /// Routes checked for each request, see [`HttpRoute`]
+    #[n(1)] pub(crate) routes: Vec<HttpRoute>,
+    /// If true, the identifier and the attributes of the caller are sent to the server in
+    /// `X-Ockam-*` headers
+    #[n(2)] pub(crate) inject_identity_headers: bool,
+}
+
+impl HttpOutletConfig {
+    pub fn new(routes: Vec<HttpRoute>, inject_identity_headers: bool) -> Self {
+        Self {
+            routes,
+            inject_identity_headers,
+        }
+    }
+}
@@ -2,7 +2,8 @@ use crate::influxdb::gateway::interceptor::HttpAuthInterceptorFactory;
 use crate::influxdb::gateway::token_lease_refresher::TokenLeaseRefresher;
 use crate::influxdb::{LeaseUsage, StartInfluxDBLeaseIssuerRequest};
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletStatus, OutletAccessControl, OutletStatus,
+    CreateInlet, CreateOutlet, InletStatus, InletTlsCertificateProvider, OutletAccessControl,
+    OutletStatus,
 };
 use crate::nodes::service::tcp_inlets::create_inlet_payload;
 use crate::nodes::{BackgroundNodeClient, NodeManagerWorker};
@@ -41,6 +42,7 @@ impl NodeManagerWorker {
             tls,
             skip_handshake,
             enable_nagle,
+            ..
         } = body.tcp_outlet;
         let address = self
             .node_manager
@@ -127,7 +129,13 @@ impl NodeManagerWorker {
             tls_certificate_provider,
             skip_handshake,
             enable_nagle,
+            acme_certificate_provider,
+            tls_client_ca_certificates,
         } = body.tcp_inlet.clone();
+        let tls_certificate_provider = InletTlsCertificateProvider::from_request_fields(
+            tls_certificate_provider,
+            acme_certificate_provider,
+        );
 
         //TODO: should be an easier way to tweak the multiaddr
         let mut issuer_route = outlet_addr.clone();
@@ -195,6 +203,7 @@ impl NodeManagerWorker {
                 disable_tcp_fallback,
                 privileged,
                 tls_certificate_provider,
+                tls_client_ca_certificates,
                 skip_handshake,
                 enable_nagle,
             )
@@ -384,7 +393,10 @@ impl InfluxDBPortals for BackgroundNodeClient {
                 enable_udp_puncture,
                 disable_tcp_fallback,
                 false,
-                tls_certificate_provider,
+                &tls_certificate_provider
+                    .clone()
+                    .map(InletTlsCertificateProvider::Remote),
+                &None,
                 false,
                 false,
             );
@@ -141,6 +141,7 @@ impl KafkaInletController {
                     false,
                     false,
                     None,
+                    None,
                     false,
                     false,
                 )
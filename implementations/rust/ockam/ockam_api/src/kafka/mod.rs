@@ -5,8 +5,10 @@ mod inlet_controller;
 pub(crate) mod key_exchange;
 mod outlet_controller;
 pub(crate) mod protocol_aware;
+pub mod reencryption;
 #[cfg(test)]
 mod tests;
+pub(crate) mod topic_encryption;
 
 pub(crate) use inlet_controller::KafkaInletController;
 pub use key_exchange::{ConsumerPublishing, ConsumerResolution};
@@ -15,6 +17,8 @@ use ockam_abac::expr::{eq, or, str};
 use ockam_abac::{subject_has_credential_policy_expression, subject_identifier_attribute, Expr};
 use ockam_core::Address;
 pub(crate) use outlet_controller::KafkaOutletController;
+pub use protocol_aware::record_format::KafkaRecordFormat;
+pub use topic_encryption::{KafkaTopicEncryption, KafkaTopicEncryptionRule, KafkaTopicPattern};
 
 pub const KAFKA_OUTLET_INTERCEPTOR_ADDRESS: &str = "kafka_interceptor";
 pub const KAFKA_OUTLET_BOOTSTRAP_ADDRESS: &str = "kafka_bootstrap";
@@ -4,6 +4,7 @@ use crate::kafka::protocol_aware::{
     CorrelationId, KafkaMessageInterceptor, KafkaMessageInterceptorWrapper, RequestInfo,
     TopicUuidMap, MAX_KAFKA_MESSAGE_SIZE,
 };
+use crate::kafka::topic_encryption::TopicEncryptionPolicy;
 use crate::kafka::KafkaInletController;
 use ockam_core::async_trait;
 use ockam_core::compat::collections::HashMap;
@@ -22,8 +23,7 @@ pub(crate) struct InletInterceptorImpl {
     uuid_to_name: TopicUuidMap,
     key_exchange_controller: Arc<dyn KafkaKeyExchangeController>,
     inlet_map: KafkaInletController,
-    encrypt_content: bool,
-    encrypted_fields: Vec<String>,
+    encryption: TopicEncryptionPolicy,
 }
 
 #[async_trait]
@@ -34,16 +34,14 @@ impl InletInterceptorImpl {
         key_exchange_controller: Arc<dyn KafkaKeyExchangeController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
-        encrypt_content: bool,
-        encrypted_fields: Vec<String>,
+        encryption: TopicEncryptionPolicy,
     ) -> InletInterceptorImpl {
         Self {
             request_map: Arc::new(Mutex::new(Default::default())),
             uuid_to_name,
             key_exchange_controller,
             inlet_map,
-            encrypt_content,
-            encrypted_fields,
+            encryption,
         }
     }
 
@@ -68,23 +66,20 @@ pub(crate) struct KafkaInletInterceptorFactory {
     secure_channel_controller: KafkaKeyExchangeControllerImpl,
     uuid_to_name: TopicUuidMap,
     inlet_map: KafkaInletController,
-    encrypt_content: bool,
-    encrypted_fields: Vec<String>,
+    encryption: TopicEncryptionPolicy,
 }
 
 impl KafkaInletInterceptorFactory {
     pub(crate) fn new(
         secure_channel_controller: KafkaKeyExchangeControllerImpl,
         inlet_map: KafkaInletController,
-        encrypt_content: bool,
-        encrypted_fields: Vec<String>,
+        encryption: TopicEncryptionPolicy,
     ) -> Self {
         Self {
             secure_channel_controller,
             uuid_to_name: Default::default(),
             inlet_map,
-            encrypt_content,
-            encrypted_fields,
+            encryption,
         }
     }
 }
@@ -96,8 +91,7 @@ impl PortalInterceptorFactory for KafkaInletInterceptorFactory {
                 Arc::new(self.secure_channel_controller.clone()),
                 self.uuid_to_name.clone(),
                 self.inlet_map.clone(),
-                self.encrypt_content,
-                self.encrypted_fields.clone(),
+                self.encryption.clone(),
             )),
             MAX_KAFKA_MESSAGE_SIZE,
         ))
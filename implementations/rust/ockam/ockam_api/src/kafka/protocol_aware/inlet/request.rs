@@ -1,7 +1,9 @@
 use crate::kafka::protocol_aware::inlet::InletInterceptorImpl;
+use crate::kafka::protocol_aware::record_format::RecordFields;
 use crate::kafka::protocol_aware::utils::{decode_body, encode_request};
 use crate::kafka::protocol_aware::RequestInfo;
 use crate::kafka::protocol_aware::{InterceptError, KafkaMessageRequestInterceptor};
+use crate::kafka::topic_encryption::TopicEncryption;
 use bytes::{Bytes, BytesMut};
 use kafka_protocol::messages::fetch_request::FetchRequest;
 use kafka_protocol::messages::produce_request::{PartitionProduceData, ProduceRequest};
@@ -71,7 +73,7 @@ impl KafkaMessageRequestInterceptor for InletInterceptorImpl {
             }
 
             ApiKey::Produce => {
-                if self.encrypt_content {
+                if self.encryption.encrypts_any_topic() {
                     return self
                         .handle_produce_request(context, &mut buffer, &header)
                         .await;
@@ -168,6 +170,11 @@ impl InletInterceptorImpl {
                     })?
             };
 
+            // no key exchange is necessary for the topics sent in cleartext
+            if let TopicEncryption::Cleartext = self.encryption.for_topic(&topic_id) {
+                continue;
+            }
+
             let partitions: Vec<i32> = topic
                 .partitions
                 .iter()
@@ -202,6 +209,10 @@ impl InletInterceptorImpl {
         // for each we wrap the content and add the secure channel identifier of
         // the encrypted content
         for topic in request.topic_data.iter_mut() {
+            let encryption = self.encryption.for_topic(&topic.name);
+            if let TopicEncryption::Cleartext = encryption {
+                continue;
+            }
             for data in &mut topic.partition_data {
                 if let Some(content) = data.records.take() {
                     let mut content = BytesMut::from(content.as_ref());
@@ -213,11 +224,14 @@ impl InletInterceptorImpl {
 
                     for record in records.iter_mut() {
                         if let Some(record_value) = record.value.take() {
-                            let buffer = if !self.encrypted_fields.is_empty() {
-                                // if we encrypt only specific fields, we assume the record must be
-                                // valid JSON map
+                            let buffer = if let TopicEncryption::Fields(encrypted_fields) =
+                                encryption
+                            {
+                                // only the selected fields are encrypted, the record must be
+                                // in the configured format
                                 self.encrypt_specific_fields(
                                     context,
+                                    encrypted_fields,
                                     &topic.name,
                                     data,
                                     &record_value,
@@ -284,39 +298,29 @@ impl InletInterceptorImpl {
     async fn encrypt_specific_fields(
         &self,
         context: &mut Context,
+        encrypted_fields: &RecordFields,
         topic_name: &TopicName,
         data: &mut PartitionProduceData,
         record_value: &Bytes,
     ) -> Result<Vec<u8>, InterceptError> {
-        let mut record_value = serde_json::from_slice::<serde_json::Value>(record_value)?;
-
-        if let serde_json::Value::Object(map) = &mut record_value {
-            for field in &self.encrypted_fields {
-                if let Some(value) = map.get_mut(field) {
-                    let encrypted_content = self
-                        .key_exchange_controller
-                        .encrypt_content(
-                            context,
-                            topic_name,
-                            data.index,
-                            serde_json::to_vec(value).map_err(|_| InterceptError::InvalidData)?,
-                        )
-                        .await
-                        .map_err(InterceptError::Ockam)?;
-
-                    let mut write_buffer = Vec::with_capacity(1024);
-                    let mut encoder = Encoder::new(&mut write_buffer);
-                    encoder
-                        .encode(encrypted_content)
-                        .map_err(|_| InterceptError::InvalidData)?;
-                    *value = serde_json::Value::String(hex::encode(&write_buffer));
-                }
-            }
-        } else {
-            warn!("only JSON objects are supported for field encryption");
-            return Err("Only JSON objects are supported".into());
+        let record = encrypted_fields.read_cleartext(record_value)?;
+
+        let mut encrypted_values = Vec::with_capacity(record.values().len());
+        for value in record.values() {
+            let encrypted_content = self
+                .key_exchange_controller
+                .encrypt_content(context, topic_name, data.index, value.clone())
+                .await
+                .map_err(InterceptError::Ockam)?;
+
+            let mut write_buffer = Vec::with_capacity(1024);
+            let mut encoder = Encoder::new(&mut write_buffer);
+            encoder
+                .encode(encrypted_content)
+                .map_err(|_| InterceptError::InvalidData)?;
+            encrypted_values.push(write_buffer);
         }
 
-        Ok(record_value.to_string().as_bytes().to_vec())
+        encrypted_fields.write(record, encrypted_values)
     }
 }
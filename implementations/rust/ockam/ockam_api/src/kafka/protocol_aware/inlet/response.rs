@@ -1,8 +1,10 @@
 use crate::kafka::protocol_aware::inlet::InletInterceptorImpl;
+use crate::kafka::protocol_aware::record_format::RecordFields;
 use crate::kafka::protocol_aware::utils::{decode_body, encode_response};
 use crate::kafka::protocol_aware::{
     InterceptError, KafkaEncryptedContent, KafkaMessageResponseInterceptor, RequestInfo,
 };
+use crate::kafka::topic_encryption::TopicEncryption;
 use crate::kafka::KafkaInletController;
 use bytes::{Bytes, BytesMut};
 use kafka_protocol::messages::{
@@ -65,7 +67,7 @@ impl KafkaMessageResponseInterceptor for InletInterceptorImpl {
                 }
 
                 ApiKey::Fetch => {
-                    if self.encrypt_content {
+                    if self.encryption.encrypts_any_topic() {
                         return self
                             .handle_fetch_response(context, &mut buffer, &request_info, &header)
                             .await;
@@ -289,6 +291,28 @@ impl InletInterceptorImpl {
         // we take every record batch content, unwrap and decode it
         // using the relative secure channel
         for response in response.responses.iter_mut() {
+            let encryption = if !self.encryption.depends_on_topic() {
+                self.encryption.default_encryption()
+            } else if request_info.request_api_version <= 12 {
+                self.encryption.for_topic(&response.topic)
+            } else {
+                // fetch responses using version >= 13 only contain the topic uuid
+                let topic_id = response.topic_id.to_string();
+                let topic_name = self
+                    .uuid_to_name
+                    .lock()
+                    .unwrap()
+                    .get(&topic_id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        warn!("missing map from uuid {topic_id} to name");
+                        InterceptError::InvalidData
+                    })?;
+                self.encryption.for_topic(&topic_name)
+            };
+            if let TopicEncryption::Cleartext = encryption {
+                continue;
+            }
             for partition in response.partitions.iter_mut() {
                 if let Some(content) = partition.records.take() {
                     let mut content = BytesMut::from(content.as_ref());
@@ -300,10 +324,16 @@ impl InletInterceptorImpl {
 
                     for record in records.iter_mut() {
                         if let Some(record_value) = record.value.take() {
-                            let decrypted_content = if self.encrypted_fields.is_empty() {
-                                self.decrypt_whole_record(context, record_value).await?
-                            } else {
-                                self.decrypt_specific_fields(context, record_value).await?
+                            let decrypted_content = match encryption {
+                                TopicEncryption::Fields(encrypted_fields) => {
+                                    self.decrypt_specific_fields(
+                                        context,
+                                        encrypted_fields,
+                                        record_value,
+                                    )
+                                    .await?
+                                }
+                                _ => self.decrypt_whole_record(context, record_value).await?,
                             };
                             record.value = Some(decrypted_content.into());
                         }
@@ -357,47 +387,28 @@ impl InletInterceptorImpl {
     async fn decrypt_specific_fields(
         &self,
         context: &mut Context,
+        encrypted_fields: &RecordFields,
         record_value: Bytes,
     ) -> Result<Vec<u8>, InterceptError> {
-        let mut record_value = serde_json::from_slice::<serde_json::Value>(&record_value)?;
-
-        if let serde_json::Value::Object(map) = &mut record_value {
-            for field in &self.encrypted_fields {
-                // when the encrypted field is present is expected to be a hex encoded string
-                // wrapped by the KafkaEncryptedContent struct
-                if let Some(value) = map.get_mut(field) {
-                    let encrypted_content = if let serde_json::Value::String(string) = value {
-                        hex::decode(string).map_err(|_| "Encrypted is not a valid hex string")?
-                    } else {
-                        error!("encrypted field is not a hex string");
-                        return Err("The encrypted field is not a hex-encoded string".into());
-                    };
-
-                    let message_wrapper: KafkaEncryptedContent =
-                        Decoder::new(&encrypted_content).decode()?;
-
-                    let decrypted_content = self
-                        .key_exchange_controller
-                        .decrypt_content(
-                            context,
-                            &message_wrapper.consumer_decryptor_address,
-                            message_wrapper.content,
-                        )
-                        .await
-                        .map_err(InterceptError::Ockam)?;
-
-                    *value = serde_json::from_slice(decrypted_content.as_slice())?;
-                }
-            }
-            serde_json::to_vec(&record_value).map_err(|error| {
-                error!("cannot serialize decrypted fields");
-                error.into()
-            })
-        } else {
-            error!(
-                "cannot decrypt specific fields, expected a JSON object but got a different type"
-            );
-            Err("Only JSON objects are supported in the message".into())
+        let record = encrypted_fields.read_encrypted(&record_value)?;
+
+        let mut decrypted_values = Vec::with_capacity(record.values().len());
+        for value in record.values() {
+            // each encrypted field is wrapped by the KafkaEncryptedContent struct
+            let message_wrapper: KafkaEncryptedContent = Decoder::new(value).decode()?;
+
+            let decrypted_content = self
+                .key_exchange_controller
+                .decrypt_content(
+                    context,
+                    &message_wrapper.consumer_decryptor_address,
+                    message_wrapper.content,
+                )
+                .await
+                .map_err(InterceptError::Ockam)?;
+            decrypted_values.push(decrypted_content);
         }
+
+        encrypted_fields.write(record, decrypted_values)
     }
 }
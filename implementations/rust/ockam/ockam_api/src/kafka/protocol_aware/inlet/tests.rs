@@ -1,9 +1,12 @@
 use crate::kafka::key_exchange::KafkaKeyExchangeController;
 use crate::kafka::protocol_aware::inlet::InletInterceptorImpl;
+use crate::kafka::protocol_aware::record_format::RecordFields;
 use crate::kafka::protocol_aware::{
     utils, KafkaEncryptedContent, KafkaMessageRequestInterceptor, KafkaMessageResponseInterceptor,
+    TopicUuidMap,
 };
-use crate::kafka::KafkaInletController;
+use crate::kafka::topic_encryption::{TopicEncryption, TopicEncryptionPolicy};
+use crate::kafka::{KafkaInletController, KafkaRecordFormat};
 use bytes::{Bytes, BytesMut};
 use kafka_protocol::messages::fetch_response::{FetchableTopicResponse, PartitionData};
 use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
@@ -210,12 +213,11 @@ pub async fn json_encrypt_specific_fields(context: &mut Context) -> ockam::Resul
         Arc::new(MockKafkaKeyExchangeController {}),
         Default::default(),
         KafkaInletController::stub(),
-        true,
-        vec![
+        TopicEncryptionPolicy::new(TopicEncryption::Fields(RecordFields::json(vec![
             "field1".to_string(),
             "field2".to_string(),
             "field3".to_string(),
-        ],
+        ]))),
     );
 
     let encrypted_response = interceptor
@@ -291,12 +293,11 @@ pub async fn json_decrypt_specific_fields(context: &mut Context) -> ockam::Resul
         Arc::new(MockKafkaKeyExchangeController {}),
         Default::default(),
         KafkaInletController::stub(),
-        true,
-        vec![
+        TopicEncryptionPolicy::new(TopicEncryption::Fields(RecordFields::json(vec![
             "field1".to_string(),
             "field2".to_string(),
             "field3".to_string(),
-        ],
+        ]))),
     );
 
     interceptor.add_request(1, ApiKey::Fetch, TEST_KAFKA_API_VERSION);
@@ -364,3 +365,45 @@ pub async fn json_decrypt_specific_fields(context: &mut Context) -> ockam::Resul
 
     Ok(())
 }
+
+#[ockam::test]
+pub async fn topic_sent_in_cleartext(context: &mut Context) -> ockam::Result<()> {
+    let rules = vec!["topic-*=cleartext".parse().unwrap()];
+    let encryption =
+        TopicEncryptionPolicy::from_rules(true, vec![], &KafkaRecordFormat::Json, &rules)?;
+    let uuid_to_name: TopicUuidMap = Default::default();
+    uuid_to_name.lock().unwrap().insert(
+        FetchableTopicResponse::default().topic_id.to_string(),
+        "topic-name".to_string(),
+    );
+    let interceptor = InletInterceptorImpl::new(
+        Arc::new(MockKafkaKeyExchangeController {}),
+        uuid_to_name,
+        KafkaInletController::stub(),
+        encryption,
+    );
+
+    let content = json!({"field1": "value1"}).to_string();
+    let request = create_kafka_produce_request(content.as_bytes());
+    let intercepted_request = interceptor
+        .intercept_request(context, request.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        parse_produce_request(&intercepted_request),
+        parse_produce_request(&request)
+    );
+
+    interceptor.add_request(1, ApiKey::Fetch, TEST_KAFKA_API_VERSION);
+    let response = create_kafka_fetch_response(content.as_bytes());
+    let intercepted_response = interceptor
+        .intercept_response(context, response.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        parse_fetch_response(&intercepted_response),
+        parse_fetch_response(&response)
+    );
+
+    Ok(())
+}
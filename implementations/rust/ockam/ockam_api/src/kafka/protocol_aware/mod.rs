@@ -15,6 +15,7 @@ mod tests;
 
 pub(crate) mod inlet;
 mod length_delimited;
+pub(crate) mod record_format;
 pub(super) mod utils;
 
 use crate::kafka::protocol_aware::length_delimited::{length_encode, KafkaMessageDecoder};
@@ -0,0 +1,810 @@
+use crate::kafka::protocol_aware::record_format::segments::{
+    push_raw, read_zigzag, write_zigzag, LengthPrefix, Segment,
+};
+use crate::kafka::protocol_aware::record_format::{FieldsRecord, Mode, RecordContent};
+use crate::kafka::protocol_aware::InterceptError;
+use crate::ApiError;
+use ockam_core::compat::collections::{BTreeMap, HashMap, HashSet};
+use ockam_core::Result;
+use serde_json::Value;
+
+/// Magic bytes starting an Avro object container file
+const CONTAINER_MAGIC: &[u8] = b"Obj\x01";
+/// Size of the synchronization marker of an object container file
+const SYNC_SIZE: usize = 16;
+/// Metadata key of the schema of an object container file
+const SCHEMA_KEY: &str = "avro.schema";
+/// Metadata key of the compression codec of an object container file
+const CODEC_KEY: &str = "avro.codec";
+/// Metadata key used to keep the original schema of an object container file when
+/// some fields are encrypted, since the encrypted fields are written as `bytes`
+const ORIGINAL_SCHEMA_KEY: &str = "ockam.schema";
+
+/// Fields of Avro records, selected by path.
+///
+/// An encrypted field is written as `bytes`, containing the encrypted binary encoding of the
+/// original field value. The other fields are left untouched, so the records can still be read
+/// with a schema where the encrypted fields are declared as `bytes`.
+/// That schema is written in the header of object container files, the original schema
+/// being kept under the `ockam.schema` metadata key, to be restored on decryption.
+///
+/// The records on the path of a field must be defined inline in the schema, and not be
+/// referenced by name elsewhere, so that the encrypted schema only changes the selected fields.
+pub(super) struct AvroFields {
+    /// Schema of the records, or None if the records are object container files
+    schema: Option<AvroSchema>,
+    paths: Vec<Vec<String>>,
+    tree: PathTree,
+}
+
+impl AvroFields {
+    pub(super) fn new(schema: Option<&str>, fields: &[String]) -> Result<Self> {
+        let paths: Vec<Vec<String>> = fields
+            .iter()
+            .map(|f| f.split('.').map(|s| s.to_string()).collect())
+            .collect();
+        let schema = match schema {
+            Some(schema) => {
+                let schema = AvroSchema::parse(schema)?;
+                schema.encrypted_schema(&paths)?;
+                Some(schema)
+            }
+            None => None,
+        };
+        Ok(Self {
+            schema,
+            tree: PathTree::new(&paths),
+            paths,
+        })
+    }
+
+    pub(super) fn read(&self, record: &[u8], mode: Mode) -> Result<FieldsRecord, InterceptError> {
+        let mut reader = Reader::new(record);
+        match &self.schema {
+            Some(schema) => {
+                schema.read_datum(&schema.root, "", &self.tree, mode, &mut reader)?;
+                if reader.position != record.len() {
+                    return Err("The Avro record doesn't match the schema".into());
+                }
+            }
+            None => self.read_container(mode, &mut reader)?,
+        }
+        let (segments, values) = reader.finish();
+        Ok(FieldsRecord {
+            mode,
+            content: RecordContent::Segments(segments),
+            values,
+        })
+    }
+
+    /// Read an object container file, rewrite its schema, and read the datums of each block
+    fn read_container(&self, mode: Mode, reader: &mut Reader) -> Result<(), InterceptError> {
+        if !reader.data.starts_with(CONTAINER_MAGIC) {
+            return Err("The record is not an Avro object container file".into());
+        }
+        reader.position = CONTAINER_MAGIC.len();
+        let mut metadata = vec![];
+        loop {
+            let count = reader.read_long()?;
+            if count == 0 {
+                break;
+            }
+            if count < 0 {
+                reader.read_long()?;
+            }
+            reader.read_items(count.unsigned_abs(), |reader| {
+                let key = String::from_utf8(reader.read_bytes()?.to_vec())
+                    .map_err(|_| InterceptError::InvalidData)?;
+                let value = reader.read_bytes()?.to_vec();
+                metadata.push((key, value));
+                Ok(())
+            })?;
+        }
+        let codec = metadata.iter().find(|(k, _)| k == CODEC_KEY);
+        if codec.is_some_and(|(_, v)| v.as_slice() != b"null") {
+            return Err("Only uncompressed Avro object container files are supported".into());
+        }
+        let schema_of = |key: &str| {
+            metadata
+                .iter()
+                .find(|(k, _)| k == key)
+                .and_then(|(_, v)| String::from_utf8(v.clone()).ok())
+        };
+        let (original_schema, written_schema) = match mode {
+            Mode::Encrypt => {
+                let original = schema_of(SCHEMA_KEY).ok_or("Missing Avro schema")?;
+                let schema = AvroSchema::parse(&original).map_err(InterceptError::Ockam)?;
+                let encrypted = schema
+                    .encrypted_schema(&self.paths)
+                    .map_err(InterceptError::Ockam)?;
+                (original, encrypted.to_string())
+            }
+            Mode::Decrypt => {
+                let original = schema_of(ORIGINAL_SCHEMA_KEY)
+                    .ok_or("Missing original Avro schema in an encrypted record")?;
+                (original.clone(), original)
+            }
+        };
+        let schema = AvroSchema::parse(&original_schema).map_err(InterceptError::Ockam)?;
+
+        // write the header with the new metadata
+        let mut header = CONTAINER_MAGIC.to_vec();
+        let mut entries: Vec<(String, Vec<u8>)> = metadata
+            .into_iter()
+            .filter(|(k, _)| k != SCHEMA_KEY && k != ORIGINAL_SCHEMA_KEY)
+            .collect();
+        entries.push((SCHEMA_KEY.to_string(), written_schema.into_bytes()));
+        if mode == Mode::Encrypt {
+            entries.push((
+                ORIGINAL_SCHEMA_KEY.to_string(),
+                original_schema.into_bytes(),
+            ));
+        }
+        write_zigzag(entries.len() as i64, &mut header);
+        for (key, value) in entries {
+            write_zigzag(key.len() as i64, &mut header);
+            header.extend_from_slice(key.as_bytes());
+            write_zigzag(value.len() as i64, &mut header);
+            header.extend_from_slice(&value);
+        }
+        write_zigzag(0, &mut header);
+        header.extend_from_slice(reader.read_fixed(SYNC_SIZE)?);
+        push_raw(&mut reader.segments, &header);
+        reader.copied = reader.position;
+
+        // read each block of datums
+        while reader.position < reader.data.len() {
+            let block_start = reader.position;
+            let count = u64::try_from(reader.read_long()?).map_err(|_| "Invalid Avro count")?;
+            let count_end = reader.position;
+            let size = reader.read_long()?;
+            let data = reader.read_fixed(usize::try_from(size).map_err(|_| "Invalid size")?)?;
+            let mut block_reader = Reader::new(data);
+            block_reader.values = std::mem::take(&mut reader.values);
+            block_reader.read_items(count, |reader| {
+                schema.read_datum(&schema.root, "", &self.tree, mode, reader)
+            })?;
+            if block_reader.position != data.len() {
+                return Err("The Avro block doesn't match the schema".into());
+            }
+            let (block_segments, values) = block_reader.finish();
+            reader.values = values;
+            push_raw(&mut reader.segments, &reader.data[block_start..count_end]);
+            reader
+                .segments
+                .push(Segment::Sized(LengthPrefix::ZigZag, block_segments));
+            reader.copied = reader.position;
+            reader.read_fixed(SYNC_SIZE)?;
+        }
+        Ok(())
+    }
+}
+
+/// Selected fields, organized as a tree of field names
+#[derive(Debug, Default)]
+struct PathTree {
+    selected: bool,
+    children: BTreeMap<String, PathTree>,
+}
+
+impl PathTree {
+    fn new(paths: &[Vec<String>]) -> Self {
+        let mut tree = PathTree::default();
+        for path in paths {
+            let mut node = &mut tree;
+            for name in path {
+                node = node.children.entry(name.clone()).or_default();
+            }
+            node.selected = true;
+        }
+        tree
+    }
+}
+
+/// Position in a record, and the segments read so far
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// Position up to which the data has been copied into the segments
+    copied: usize,
+    segments: Vec<Segment>,
+    values: Vec<Vec<u8>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            copied: 0,
+            segments: vec![],
+            values: vec![],
+        }
+    }
+
+    fn read_long(&mut self) -> Result<i64, InterceptError> {
+        let (value, position) =
+            read_zigzag(self.data, self.position).ok_or("Invalid Avro long value")?;
+        self.position = position;
+        Ok(value)
+    }
+
+    fn read_fixed(&mut self, size: usize) -> Result<&'a [u8], InterceptError> {
+        let end = self.position.checked_add(size).ok_or("Invalid Avro size")?;
+        let data = self
+            .data
+            .get(self.position..end)
+            .ok_or("Truncated Avro record")?;
+        self.position += size;
+        Ok(data)
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], InterceptError> {
+        let size = self.read_long()?;
+        self.read_fixed(usize::try_from(size).map_err(|_| "Invalid Avro length")?)
+    }
+
+    /// Read the items of a block, given their count.
+    /// Since the count is not bounded by the size of the items, which can be empty, a count
+    /// greater than the remaining data is rejected as soon as an item is empty
+    fn read_items(
+        &mut self,
+        count: u64,
+        mut read_item: impl FnMut(&mut Self) -> Result<(), InterceptError>,
+    ) -> Result<(), InterceptError> {
+        let remaining = (self.data.len() - self.position) as u64;
+        for _ in 0..count {
+            let start = self.position;
+            read_item(self)?;
+            if self.position == start && count > remaining {
+                return Err("Too many empty Avro items".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the data between start and the current position by a field value
+    fn field(&mut self, start: usize, value: Vec<u8>) {
+        push_raw(&mut self.segments, &self.data[self.copied..start]);
+        self.segments.push(Segment::Field(self.values.len()));
+        self.values.push(value);
+        self.copied = self.position;
+    }
+
+    fn finish(mut self) -> (Vec<Segment>, Vec<Vec<u8>>) {
+        push_raw(&mut self.segments, &self.data[self.copied..]);
+        (self.segments, self.values)
+    }
+}
+
+/// An Avro schema, with its named types
+struct AvroSchema {
+    root: Value,
+    names: HashMap<String, Value>,
+    /// Full names of the types referenced by name
+    references: HashSet<String>,
+}
+
+impl AvroSchema {
+    fn parse(schema: &str) -> Result<Self> {
+        let root: Value = serde_json::from_str(schema)
+            .map_err(|e| ApiError::core(format!("Invalid Avro schema: {e}")))?;
+        let mut schema = Self {
+            root: Value::Null,
+            names: HashMap::new(),
+            references: HashSet::new(),
+        };
+        schema.collect_names(&root, "")?;
+        schema.root = root;
+        Ok(schema)
+    }
+
+    /// Register the named types of a schema, and the references to named types
+    fn collect_names(&mut self, schema: &Value, namespace: &str) -> Result<()> {
+        match schema {
+            Value::String(name) if !is_primitive(name) => {
+                let reference = self.resolve_name(name, namespace);
+                self.references.insert(reference);
+            }
+            Value::Array(branches) => {
+                for branch in branches {
+                    self.collect_names(branch, namespace)?;
+                }
+            }
+            Value::Object(object) => match object.get("type") {
+                Some(Value::String(t))
+                    if matches!(t.as_str(), "record" | "error" | "enum" | "fixed") =>
+                {
+                    let (fullname, namespace) = full_name(object, namespace)?;
+                    self.names.insert(fullname, schema.clone());
+                    if let Some(Value::Array(fields)) = object.get("fields") {
+                        for field in fields {
+                            let field_type = field
+                                .get("type")
+                                .ok_or_else(|| ApiError::core("Avro field without type"))?;
+                            self.collect_names(field_type, &namespace)?;
+                        }
+                    }
+                }
+                Some(Value::String(t)) if t == "array" => {
+                    self.collect_names(object.get("items").unwrap_or(&Value::Null), namespace)?
+                }
+                Some(Value::String(t)) if t == "map" => {
+                    self.collect_names(object.get("values").unwrap_or(&Value::Null), namespace)?
+                }
+                Some(t) => self.collect_names(t, namespace)?,
+                None => return Err(ApiError::core("Avro schema without type")),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Return the full name of a referenced type
+    fn resolve_name(&self, name: &str, namespace: &str) -> String {
+        if name.contains('.') || namespace.is_empty() {
+            name.to_string()
+        } else {
+            let fullname = format!("{namespace}.{name}");
+            if self.names.contains_key(&fullname) || !self.names.contains_key(name) {
+                fullname
+            } else {
+                name.to_string()
+            }
+        }
+    }
+
+    /// Return the schema where the selected fields are declared as `bytes`.
+    /// Return an error if a field can't be found
+    fn encrypted_schema(&self, paths: &[Vec<String>]) -> Result<Value> {
+        let mut root = self.root.clone();
+        for path in paths {
+            let mut schema = &mut root;
+            let mut namespace = String::new();
+            for (i, name) in path.iter().enumerate() {
+                let record = inline_record(schema).ok_or_else(|| {
+                    ApiError::core(format!(
+                        "The Avro field {} is not in a record defined inline",
+                        path.join(".")
+                    ))
+                })?;
+                let (fullname, record_namespace) = full_name(
+                    record
+                        .as_object()
+                        .ok_or_else(|| ApiError::core("Invalid Avro record"))?,
+                    &namespace,
+                )?;
+                if self.references.contains(&fullname) {
+                    return Err(ApiError::core(format!(
+                        "The Avro record {fullname} containing the field {} must not be referenced by name",
+                        path.join(".")
+                    )));
+                }
+                namespace = record_namespace;
+                let field = record
+                    .get_mut("fields")
+                    .and_then(|f| f.as_array_mut())
+                    .and_then(|fields| {
+                        fields
+                            .iter_mut()
+                            .find(|f| f.get("name").and_then(|n| n.as_str()) == Some(name))
+                    })
+                    .and_then(|f| f.as_object_mut())
+                    .ok_or_else(|| {
+                        ApiError::core(format!("The Avro field {} doesn't exist", path.join(".")))
+                    })?;
+                if i == path.len() - 1 {
+                    field.insert("type".to_string(), Value::String("bytes".to_string()));
+                    field.remove("default");
+                    break;
+                }
+                schema = field
+                    .get_mut("type")
+                    .ok_or_else(|| ApiError::core("Avro field without type"))?;
+            }
+        }
+        Ok(root)
+    }
+
+    /// Read a datum, extracting the selected fields
+    fn read_datum(
+        &self,
+        schema: &Value,
+        namespace: &str,
+        tree: &PathTree,
+        mode: Mode,
+        reader: &mut Reader,
+    ) -> Result<(), InterceptError> {
+        if tree.children.is_empty() {
+            return self.skip(schema, namespace, reader);
+        }
+        match schema {
+            // a union containing a record with some selected fields
+            Value::Array(branches) => {
+                let index = reader.read_long()?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| branches.get(i))
+                    .ok_or("Invalid Avro union index")?;
+                self.read_datum(branch, namespace, tree, mode, reader)
+            }
+            Value::Object(object) if is_record(object) => {
+                let (_, namespace) =
+                    full_name(object, namespace).map_err(|_| InterceptError::InvalidData)?;
+                let fields = object
+                    .get("fields")
+                    .and_then(|f| f.as_array())
+                    .ok_or(InterceptError::InvalidData)?;
+                for field in fields {
+                    let field_type = field.get("type").ok_or(InterceptError::InvalidData)?;
+                    let child = field
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .and_then(|name| tree.children.get(name));
+                    match child {
+                        Some(child) if child.selected => {
+                            let start = reader.position;
+                            let value = match mode {
+                                Mode::Encrypt => {
+                                    self.skip(field_type, &namespace, reader)?;
+                                    reader.data[start..reader.position].to_vec()
+                                }
+                                Mode::Decrypt => reader.read_bytes()?.to_vec(),
+                            };
+                            reader.field(start, value);
+                        }
+                        Some(child) => {
+                            self.read_datum(field_type, &namespace, child, mode, reader)?
+                        }
+                        None => self.skip(field_type, &namespace, reader)?,
+                    }
+                }
+                Ok(())
+            }
+            // the selected fields are not in this branch of a union
+            _ => self.skip(schema, namespace, reader),
+        }
+    }
+
+    /// Skip a datum
+    fn skip(
+        &self,
+        schema: &Value,
+        namespace: &str,
+        reader: &mut Reader,
+    ) -> Result<(), InterceptError> {
+        match schema {
+            Value::String(name) => match name.as_str() {
+                "null" => {}
+                "boolean" => {
+                    reader.read_fixed(1)?;
+                }
+                "int" | "long" => {
+                    reader.read_long()?;
+                }
+                "float" => {
+                    reader.read_fixed(4)?;
+                }
+                "double" => {
+                    reader.read_fixed(8)?;
+                }
+                "bytes" | "string" => {
+                    reader.read_bytes()?;
+                }
+                name => {
+                    let fullname = self.resolve_name(name, namespace);
+                    let definition = self.names.get(&fullname).ok_or("Unknown Avro named type")?;
+                    let namespace = fullname
+                        .rsplit_once('.')
+                        .map(|(ns, _)| ns)
+                        .unwrap_or_default();
+                    self.skip(definition, namespace, reader)?;
+                }
+            },
+            Value::Array(branches) => {
+                let index = reader.read_long()?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| branches.get(i))
+                    .ok_or("Invalid Avro union index")?;
+                self.skip(branch, namespace, reader)?;
+            }
+            Value::Object(object) => match object.get("type").and_then(|t| t.as_str()) {
+                Some("record") | Some("error") => {
+                    let (_, namespace) =
+                        full_name(object, namespace).map_err(|_| InterceptError::InvalidData)?;
+                    let fields = object
+                        .get("fields")
+                        .and_then(|f| f.as_array())
+                        .ok_or(InterceptError::InvalidData)?;
+                    for field in fields {
+                        let field_type = field.get("type").ok_or(InterceptError::InvalidData)?;
+                        self.skip(field_type, &namespace, reader)?;
+                    }
+                }
+                Some("enum") => {
+                    reader.read_long()?;
+                }
+                Some("fixed") => {
+                    let size = object
+                        .get("size")
+                        .and_then(|s| s.as_u64())
+                        .ok_or(InterceptError::InvalidData)?;
+                    reader.read_fixed(size as usize)?;
+                }
+                Some("array") => {
+                    let items = object.get("items").ok_or(InterceptError::InvalidData)?;
+                    self.skip_blocks(reader, |reader| self.skip(items, namespace, reader))?;
+                }
+                Some("map") => {
+                    let values = object.get("values").ok_or(InterceptError::InvalidData)?;
+                    self.skip_blocks(reader, |reader| {
+                        reader.read_bytes()?;
+                        self.skip(values, namespace, reader)
+                    })?;
+                }
+                _ => {
+                    let inner = object.get("type").ok_or(InterceptError::InvalidData)?;
+                    self.skip(inner, namespace, reader)?;
+                }
+            },
+            _ => return Err(InterceptError::InvalidData),
+        }
+        Ok(())
+    }
+
+    /// Skip the blocks of an array or a map
+    fn skip_blocks(
+        &self,
+        reader: &mut Reader,
+        skip_item: impl Fn(&mut Reader) -> Result<(), InterceptError>,
+    ) -> Result<(), InterceptError> {
+        loop {
+            let count = reader.read_long()?;
+            if count == 0 {
+                return Ok(());
+            }
+            if count < 0 {
+                // a negative count is followed by the size of the block
+                let size = reader.read_long()?;
+                reader.read_fixed(usize::try_from(size).map_err(|_| "Invalid Avro block size")?)?;
+            } else {
+                reader.read_items(count.unsigned_abs(), &skip_item)?;
+            }
+        }
+    }
+}
+
+fn is_primitive(name: &str) -> bool {
+    matches!(
+        name,
+        "null" | "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string"
+    )
+}
+
+fn is_record(object: &serde_json::Map<String, Value>) -> bool {
+    matches!(
+        object.get("type").and_then(|t| t.as_str()),
+        Some("record") | Some("error")
+    )
+}
+
+/// Return the record defined inline in a schema, which can be a union
+fn inline_record(schema: &mut Value) -> Option<&mut Value> {
+    match schema {
+        Value::Object(object) if is_record(object) => Some(schema),
+        Value::Array(branches) => branches
+            .iter_mut()
+            .find(|b| b.as_object().is_some_and(is_record)),
+        _ => None,
+    }
+}
+
+/// Return the full name of a named type, and the namespace of the types it contains
+fn full_name(object: &serde_json::Map<String, Value>, namespace: &str) -> Result<(String, String)> {
+    let name = object
+        .get("name")
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| ApiError::core("Avro named type without name"))?;
+    if let Some((namespace, _)) = name.rsplit_once('.') {
+        return Ok((name.to_string(), namespace.to_string()));
+    }
+    let namespace = object
+        .get("namespace")
+        .and_then(|n| n.as_str())
+        .unwrap_or(namespace);
+    if namespace.is_empty() {
+        Ok((name.to_string(), String::new()))
+    } else {
+        Ok((format!("{namespace}.{name}"), namespace.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::protocol_aware::record_format::segments;
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Order",
+        "namespace": "shop",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+            {"name": "customer", "type": ["null", {
+                "type": "record",
+                "name": "Customer",
+                "fields": [
+                    {"name": "name", "type": "string"},
+                    {"name": "email", "type": "string", "default": ""}
+                ]
+            }]},
+            {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["NEW", "PAID"]}},
+            {"name": "previous_status", "type": ["null", "Status"]}
+        ]
+    }"#;
+
+    fn string(value: &str, buffer: &mut Vec<u8>) {
+        write_zigzag(value.len() as i64, buffer);
+        buffer.extend_from_slice(value.as_bytes());
+    }
+
+    fn order() -> Vec<u8> {
+        let mut buffer = vec![];
+        write_zigzag(42, &mut buffer);
+        // tags array with 2 items
+        write_zigzag(2, &mut buffer);
+        string("a", &mut buffer);
+        string("b", &mut buffer);
+        write_zigzag(0, &mut buffer);
+        // customer, second branch of the union
+        write_zigzag(1, &mut buffer);
+        string("alice", &mut buffer);
+        string("alice@example.com", &mut buffer);
+        // status
+        write_zigzag(1, &mut buffer);
+        // previous status
+        write_zigzag(1, &mut buffer);
+        write_zigzag(0, &mut buffer);
+        buffer
+    }
+
+    fn transform(
+        fields: &AvroFields,
+        record: &[u8],
+        mode: Mode,
+        f: fn(&[u8]) -> Vec<u8>,
+    ) -> Vec<u8> {
+        let record = fields.read(record, mode).unwrap();
+        let values: Vec<Vec<u8>> = record.values.iter().map(|v| f(v)).collect();
+        match record.content {
+            RecordContent::Segments(s) => {
+                segments::write(&s, &values, mode.field_prefix(LengthPrefix::ZigZag))
+            }
+            _ => panic!("unexpected content"),
+        }
+    }
+
+    fn reverse(value: &[u8]) -> Vec<u8> {
+        value.iter().rev().cloned().collect()
+    }
+
+    #[test]
+    fn encrypt_fields_of_datums() {
+        let fields =
+            AvroFields::new(Some(SCHEMA), &["customer.email".into(), "id".into()]).unwrap();
+        let record = order();
+        let read = fields.read(&record, Mode::Encrypt).unwrap();
+        let mut email = vec![];
+        string("alice@example.com", &mut email);
+        let mut id = vec![];
+        write_zigzag(42, &mut id);
+        assert_eq!(read.values, vec![id.clone(), email.clone()]);
+
+        // the encrypted record is valid for the encrypted schema
+        let encrypted = transform(&fields, &record, Mode::Encrypt, reverse);
+        let encrypted_schema = AvroSchema::parse(SCHEMA)
+            .unwrap()
+            .encrypted_schema(&fields.paths)
+            .unwrap();
+        let encrypted_fields = AvroFields::new(Some(&encrypted_schema.to_string()), &[]).unwrap();
+        encrypted_fields.read(&encrypted, Mode::Encrypt).unwrap();
+        assert_eq!(encrypted_schema["fields"][0]["type"], "bytes");
+        assert_eq!(
+            encrypted_schema["fields"][2]["type"][1]["fields"][1]["type"],
+            "bytes"
+        );
+        assert!(encrypted_schema["fields"][2]["type"][1]["fields"][1]
+            .get("default")
+            .is_none());
+
+        let decrypted = transform(&fields, &encrypted, Mode::Decrypt, reverse);
+        assert_eq!(decrypted, record);
+    }
+
+    #[test]
+    fn encrypt_fields_of_container_files() {
+        let fields = AvroFields::new(None, &["customer.name".into()]).unwrap();
+
+        let mut container = CONTAINER_MAGIC.to_vec();
+        write_zigzag(1, &mut container);
+        string(SCHEMA_KEY, &mut container);
+        string(SCHEMA, &mut container);
+        write_zigzag(0, &mut container);
+        container.extend_from_slice(&[7; SYNC_SIZE]);
+        let mut data = order();
+        data.extend(order());
+        write_zigzag(2, &mut container);
+        write_zigzag(data.len() as i64, &mut container);
+        container.extend_from_slice(&data);
+        container.extend_from_slice(&[7; SYNC_SIZE]);
+
+        let read = fields.read(&container, Mode::Encrypt).unwrap();
+        assert_eq!(read.values.len(), 2);
+        let encrypted = transform(&fields, &container, Mode::Encrypt, reverse);
+        assert_ne!(encrypted, container);
+        let decrypted = transform(&fields, &encrypted, Mode::Decrypt, reverse);
+        let read = fields.read(&decrypted, Mode::Encrypt).unwrap();
+        let mut name = vec![];
+        string("alice", &mut name);
+        assert_eq!(read.values, vec![name.clone(), name]);
+    }
+
+    #[test]
+    fn too_many_empty_items() {
+        let schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "a", "type": {"type": "array", "items": "null"}},
+            {"name": "b", "type": "string"}
+        ]}"#;
+        let fields = AvroFields::new(Some(schema), &["b".into()]).unwrap();
+        let record = |count: i64| {
+            let mut buffer = vec![];
+            write_zigzag(count, &mut buffer);
+            write_zigzag(0, &mut buffer);
+            string("b", &mut buffer);
+            buffer
+        };
+        assert!(fields.read(&record(3), Mode::Encrypt).is_ok());
+        assert!(matches!(
+            fields.read(&record(i64::MAX), Mode::Encrypt),
+            Err(InterceptError::Generic("Too many empty Avro items"))
+        ));
+
+        // a container file with a block of empty datums
+        let fields = AvroFields::new(None, &[]).unwrap();
+        let container = |count: i64| {
+            let mut container = CONTAINER_MAGIC.to_vec();
+            write_zigzag(1, &mut container);
+            string(SCHEMA_KEY, &mut container);
+            string(
+                r#"{"type": "record", "name": "E", "fields": []}"#,
+                &mut container,
+            );
+            write_zigzag(0, &mut container);
+            container.extend_from_slice(&[7; SYNC_SIZE]);
+            write_zigzag(count, &mut container);
+            write_zigzag(0, &mut container);
+            container.extend_from_slice(&[7; SYNC_SIZE]);
+            container
+        };
+        assert!(fields.read(&container(0), Mode::Encrypt).is_ok());
+        assert!(matches!(
+            fields.read(&container(i64::MAX), Mode::Encrypt),
+            Err(InterceptError::Generic("Too many empty Avro items"))
+        ));
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(AvroFields::new(Some(SCHEMA), &["unknown".into()]).is_err());
+        assert!(AvroFields::new(Some(SCHEMA), &["tags.name".into()]).is_err());
+        // a record referenced by name can't contain encrypted fields
+        let schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "a", "type": {"type": "record", "name": "S", "fields": [{"name": "x", "type": "int"}]}},
+            {"name": "b", "type": "S"}
+        ]}"#;
+        assert!(AvroFields::new(Some(schema), &["a.x".into()]).is_err());
+        assert!(AvroFields::new(Some(schema), &["a".into()]).is_ok());
+    }
+}
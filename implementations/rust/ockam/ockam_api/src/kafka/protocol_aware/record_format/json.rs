@@ -0,0 +1,77 @@
+use crate::kafka::protocol_aware::record_format::{FieldsRecord, Mode, RecordContent};
+use crate::kafka::protocol_aware::InterceptError;
+
+/// Top-level fields of JSON objects.
+///
+/// An encrypted field is replaced by a string containing its hex-encoded encrypted value.
+pub(super) struct JsonFields {
+    fields: Vec<String>,
+}
+
+impl JsonFields {
+    pub(super) fn new(fields: Vec<String>) -> Self {
+        Self { fields }
+    }
+
+    pub(super) fn read(&self, record: &[u8], mode: Mode) -> Result<FieldsRecord, InterceptError> {
+        let record_value = serde_json::from_slice::<serde_json::Value>(record)?;
+        let map = match &record_value {
+            serde_json::Value::Object(map) => map,
+            _ => {
+                warn!("only JSON objects are supported for field encryption");
+                return Err("Only JSON objects are supported".into());
+            }
+        };
+
+        let mut values = vec![];
+        for value in self.fields.iter().filter_map(|field| map.get(field)) {
+            match mode {
+                Mode::Encrypt => {
+                    values.push(serde_json::to_vec(value).map_err(|_| InterceptError::InvalidData)?)
+                }
+                // when the encrypted field is present is expected to be a hex encoded string
+                Mode::Decrypt => match value {
+                    serde_json::Value::String(string) => values.push(
+                        hex::decode(string).map_err(|_| "Encrypted is not a valid hex string")?,
+                    ),
+                    _ => {
+                        error!("encrypted field is not a hex string");
+                        return Err("The encrypted field is not a hex-encoded string".into());
+                    }
+                },
+            }
+        }
+        Ok(FieldsRecord {
+            mode,
+            content: RecordContent::Json(record_value),
+            values,
+        })
+    }
+
+    pub(super) fn write(
+        &self,
+        mut record_value: serde_json::Value,
+        mode: Mode,
+        values: Vec<Vec<u8>>,
+    ) -> Result<Vec<u8>, InterceptError> {
+        if let serde_json::Value::Object(map) = &mut record_value {
+            let mut values = values.into_iter();
+            for field in &self.fields {
+                let Some(value) = map.get_mut(field) else {
+                    continue;
+                };
+                let Some(new_value) = values.next() else {
+                    break;
+                };
+                *value = match mode {
+                    Mode::Encrypt => serde_json::Value::String(hex::encode(&new_value)),
+                    Mode::Decrypt => serde_json::from_slice(new_value.as_slice())?,
+                };
+            }
+        }
+        serde_json::to_vec(&record_value).map_err(|error| {
+            error!("cannot serialize the record fields");
+            error.into()
+        })
+    }
+}
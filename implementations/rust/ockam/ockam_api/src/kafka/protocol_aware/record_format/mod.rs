@@ -0,0 +1,311 @@
+//! Formats of the values of Kafka records, used to encrypt only some fields of the records.
+//!
+//! Encrypting a field is done in two steps, since the encryption itself is asynchronous:
+//!
+//!  - the record is parsed and the serialized values of the selected fields are extracted.
+//!  - the record is written back, with the encrypted values of these fields.
+//!
+//! Decryption follows the same steps with the encrypted values.
+
+mod avro;
+mod json;
+mod protobuf;
+
+use crate::kafka::protocol_aware::InterceptError;
+use minicbor::bytes::ByteVec;
+use minicbor::{CborLen, Decode, Encode};
+use ockam_core::Result;
+use segments::{LengthPrefix, Segment};
+use std::sync::Arc;
+
+/// Format of the values of the records produced through a Kafka inlet,
+/// used when only some fields of the records are encrypted.
+///
+/// Fields are selected by path, for example `customer.email`.
+/// With JSON, a field is a key of the top-level object.
+/// With Avro and Protobuf the path can select a field in a nested record or message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub enum KafkaRecordFormat {
+    /// The records are JSON objects
+    #[n(1)] #[default] Json,
+    /// The records are Avro datums written with this schema, in JSON.
+    /// Without a schema, the records are Avro object container files embedding their schema.
+    #[n(2)] Avro(#[n(1)] Option<String>),
+    /// The records are Protobuf messages of the given fully-qualified type,
+    /// described in a serialized `FileDescriptorSet`
+    #[n(3)] Protobuf(#[n(1)] ByteVec, #[n(2)] String),
+}
+
+/// The fields to encrypt in the records, and the format used to find them
+#[derive(Clone)]
+pub(crate) struct RecordFields {
+    codec: Arc<FieldsCodec>,
+}
+
+enum FieldsCodec {
+    Json(json::JsonFields),
+    Avro(avro::AvroFields),
+    Protobuf(protobuf::ProtobufFields),
+}
+
+impl RecordFields {
+    pub(crate) fn new(format: &KafkaRecordFormat, fields: Vec<String>) -> Result<Self> {
+        let codec = match format {
+            KafkaRecordFormat::Json => FieldsCodec::Json(json::JsonFields::new(fields)),
+            KafkaRecordFormat::Avro(schema) => {
+                FieldsCodec::Avro(avro::AvroFields::new(schema.as_deref(), &fields)?)
+            }
+            KafkaRecordFormat::Protobuf(descriptor_set, message_type) => FieldsCodec::Protobuf(
+                protobuf::ProtobufFields::new(descriptor_set, message_type, &fields)?,
+            ),
+        };
+        Ok(Self {
+            codec: Arc::new(codec),
+        })
+    }
+
+    /// Fields of JSON records
+    #[cfg(test)]
+    pub(crate) fn json(fields: Vec<String>) -> Self {
+        Self {
+            codec: Arc::new(FieldsCodec::Json(json::JsonFields::new(fields))),
+        }
+    }
+
+    /// Parse a cleartext record and return the serialized values of the fields to encrypt
+    pub(crate) fn read_cleartext(&self, record: &[u8]) -> Result<FieldsRecord, InterceptError> {
+        match self.codec.as_ref() {
+            FieldsCodec::Json(fields) => fields.read(record, Mode::Encrypt),
+            FieldsCodec::Avro(fields) => fields.read(record, Mode::Encrypt),
+            FieldsCodec::Protobuf(fields) => fields.read(record, Mode::Encrypt),
+        }
+    }
+
+    /// Parse an encrypted record and return the encrypted values of its fields
+    pub(crate) fn read_encrypted(&self, record: &[u8]) -> Result<FieldsRecord, InterceptError> {
+        match self.codec.as_ref() {
+            FieldsCodec::Json(fields) => fields.read(record, Mode::Decrypt),
+            FieldsCodec::Avro(fields) => fields.read(record, Mode::Decrypt),
+            FieldsCodec::Protobuf(fields) => fields.read(record, Mode::Decrypt),
+        }
+    }
+
+    /// Write a record back, replacing the values of its fields.
+    /// The values must be given in the same order as [`FieldsRecord::values`].
+    pub(crate) fn write(
+        &self,
+        record: FieldsRecord,
+        values: Vec<Vec<u8>>,
+    ) -> Result<Vec<u8>, InterceptError> {
+        if values.len() != record.values.len() {
+            return Err(InterceptError::Generic(
+                "the number of field values doesn't match the record",
+            ));
+        }
+        match (self.codec.as_ref(), record.content) {
+            (FieldsCodec::Json(fields), RecordContent::Json(value)) => {
+                fields.write(value, record.mode, values)
+            }
+            (FieldsCodec::Avro(_), RecordContent::Segments(segments)) => Ok(segments::write(
+                &segments,
+                &values,
+                record.mode.field_prefix(LengthPrefix::ZigZag),
+            )),
+            (FieldsCodec::Protobuf(_), RecordContent::Segments(segments)) => Ok(segments::write(
+                &segments,
+                &values,
+                record.mode.field_prefix(LengthPrefix::Varint),
+            )),
+            _ => Err(InterceptError::Generic("unexpected record content")),
+        }
+    }
+}
+
+/// Direction of the transformation of a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Encrypt,
+    Decrypt,
+}
+
+impl Mode {
+    /// Encrypted values are written as length-prefixed bytes, while decrypted values are the
+    /// original serialized fields, written as they are
+    fn field_prefix(&self, prefix: LengthPrefix) -> Option<LengthPrefix> {
+        match self {
+            Mode::Encrypt => Some(prefix),
+            Mode::Decrypt => None,
+        }
+    }
+}
+
+/// A parsed record, with the values of the fields to encrypt or decrypt
+pub(crate) struct FieldsRecord {
+    mode: Mode,
+    content: RecordContent,
+    values: Vec<Vec<u8>>,
+}
+
+impl FieldsRecord {
+    /// Serialized values of the selected fields present in the record
+    pub(crate) fn values(&self) -> &[Vec<u8>] {
+        &self.values
+    }
+}
+
+enum RecordContent {
+    Json(serde_json::Value),
+    Segments(Vec<Segment>),
+}
+
+/// Binary records are rebuilt from segments: raw data copied from the original record,
+/// and placeholders for the field values
+pub(crate) mod segments {
+    /// Encoding of a length prefix
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) enum LengthPrefix {
+        /// Protobuf unsigned varint
+        Varint,
+        /// Avro zig-zag encoded long
+        ZigZag,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) enum Segment {
+        /// Data copied as it is
+        Raw(Vec<u8>),
+        /// Placeholder for the value of a field, with the index of the value
+        Field(usize),
+        /// Segments prefixed with their total length
+        Sized(LengthPrefix, Vec<Segment>),
+    }
+
+    /// Append raw data, merging it with the previous segment when possible
+    pub(crate) fn push_raw(segments: &mut Vec<Segment>, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        match segments.last_mut() {
+            Some(Segment::Raw(raw)) => raw.extend_from_slice(data),
+            _ => segments.push(Segment::Raw(data.to_vec())),
+        }
+    }
+
+    /// Write the segments, with the field values prefixed with their length if necessary
+    pub(crate) fn write(
+        segments: &[Segment],
+        values: &[Vec<u8>],
+        field_prefix: Option<LengthPrefix>,
+    ) -> Vec<u8> {
+        let mut buffer = vec![];
+        write_into(segments, values, field_prefix, &mut buffer);
+        buffer
+    }
+
+    fn write_into(
+        segments: &[Segment],
+        values: &[Vec<u8>],
+        field_prefix: Option<LengthPrefix>,
+        buffer: &mut Vec<u8>,
+    ) {
+        for segment in segments {
+            match segment {
+                Segment::Raw(raw) => buffer.extend_from_slice(raw),
+                Segment::Field(index) => {
+                    let value = &values[*index];
+                    if let Some(prefix) = field_prefix {
+                        write_length(prefix, value.len(), buffer);
+                    }
+                    buffer.extend_from_slice(value);
+                }
+                Segment::Sized(prefix, children) => {
+                    let mut child = vec![];
+                    write_into(children, values, field_prefix, &mut child);
+                    write_length(*prefix, child.len(), buffer);
+                    buffer.extend_from_slice(&child);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn write_length(prefix: LengthPrefix, length: usize, buffer: &mut Vec<u8>) {
+        match prefix {
+            LengthPrefix::Varint => write_varint(length as u64, buffer),
+            LengthPrefix::ZigZag => write_zigzag(length as i64, buffer),
+        }
+    }
+
+    pub(crate) fn write_varint(mut value: u64, buffer: &mut Vec<u8>) {
+        while value >= 0x80 {
+            buffer.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buffer.push(value as u8);
+    }
+
+    pub(crate) fn write_zigzag(value: i64, buffer: &mut Vec<u8>) {
+        write_varint(((value << 1) ^ (value >> 63)) as u64, buffer)
+    }
+
+    /// Read an unsigned varint and return it with the position following it
+    pub(crate) fn read_varint(data: &[u8], mut position: usize) -> Option<(u64, usize)> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = *data.get(position)?;
+            position += 1;
+            if shift >= 64 {
+                return None;
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some((value, position));
+            }
+            shift += 7;
+        }
+    }
+
+    /// Read a zig-zag encoded long and return it with the position following it
+    pub(crate) fn read_zigzag(data: &[u8], position: usize) -> Option<(i64, usize)> {
+        let (value, position) = read_varint(data, position)?;
+        Some((((value >> 1) as i64) ^ -((value & 1) as i64), position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::segments::*;
+
+    #[test]
+    fn varints() {
+        for value in [0i64, 1, -1, 63, -64, 64, 300, -300, i64::MAX, i64::MIN] {
+            let mut buffer = vec![];
+            write_zigzag(value, &mut buffer);
+            assert_eq!(read_zigzag(&buffer, 0), Some((value, buffer.len())));
+        }
+        let mut buffer = vec![];
+        write_varint(300, &mut buffer);
+        assert_eq!(buffer, vec![0xac, 0x02]);
+        assert_eq!(read_varint(&buffer, 0), Some((300, 2)));
+        assert_eq!(read_varint(&[0x80], 0), None);
+    }
+
+    #[test]
+    fn write_segments() {
+        let segments = vec![
+            Segment::Raw(vec![1]),
+            Segment::Sized(
+                LengthPrefix::Varint,
+                vec![Segment::Raw(vec![2]), Segment::Field(0)],
+            ),
+        ];
+        let values = vec![vec![7, 7, 7]];
+        assert_eq!(
+            write(&segments, &values, Some(LengthPrefix::Varint)),
+            vec![1, 5, 2, 3, 7, 7, 7]
+        );
+        assert_eq!(write(&segments, &values, None), vec![1, 4, 2, 7, 7, 7]);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::kafka::protocol_aware::record_format::segments::{
+    push_raw, read_varint, write_varint, LengthPrefix, Segment,
+};
+use crate::kafka::protocol_aware::record_format::{FieldsRecord, Mode, RecordContent};
+use crate::kafka::protocol_aware::InterceptError;
+use crate::ApiError;
+use ockam_core::compat::collections::{BTreeMap, HashMap};
+use ockam_core::Result;
+use prost::Message;
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{DescriptorProto, FileDescriptorSet};
+
+/// Wire type of length-delimited values
+const LENGTH_DELIMITED: u64 = 2;
+const START_GROUP: u64 = 3;
+const END_GROUP: u64 = 4;
+/// Maximum number of nested groups, which are skipped recursively
+const MAX_GROUP_DEPTH: usize = 64;
+/// First byte of the records framed by a schema registry, followed by the schema id
+const SCHEMA_REGISTRY_MAGIC: u8 = 0;
+
+/// Fields of Protobuf messages, selected by path and resolved to field numbers with the message
+/// descriptors.
+///
+/// An encrypted field is written as a length-delimited field with the same number, containing the
+/// encrypted encoding of all the occurrences of the original field. The other fields are left
+/// untouched.
+///
+/// This changes the wire format of the encrypted fields: a numeric field changes its wire type,
+/// and a `string` field contains bytes which are not valid UTF-8. The consumers reading the
+/// records without decrypting them must declare the encrypted fields as `bytes`.
+///
+/// Records framed by a schema registry, with a zero magic byte and a schema id, are rejected:
+/// a Protobuf message never starts with a zero byte, since 0 is not a valid field number.
+pub(super) struct ProtobufFields {
+    tree: FieldTree,
+}
+
+impl ProtobufFields {
+    pub(super) fn new(
+        descriptor_set: &[u8],
+        message_type: &str,
+        fields: &[String],
+    ) -> Result<Self> {
+        let descriptor_set = FileDescriptorSet::decode(descriptor_set)
+            .map_err(|e| ApiError::core(format!("Invalid Protobuf descriptor set: {e}")))?;
+        let mut messages = HashMap::new();
+        for file in &descriptor_set.file {
+            let package = file.package();
+            for message in &file.message_type {
+                collect_messages(package, message, &mut messages);
+            }
+        }
+        let message_type = message_type.trim_start_matches('.');
+        if !messages.contains_key(message_type) {
+            return Err(ApiError::core(format!(
+                "The Protobuf message type {message_type} is not in the descriptor set"
+            )));
+        }
+
+        let mut tree = FieldTree::default();
+        for field in fields {
+            let mut node = &mut tree;
+            let mut current = messages[message_type];
+            let path: Vec<&str> = field.split('.').collect();
+            for (i, name) in path.iter().enumerate() {
+                let descriptor = current
+                    .field
+                    .iter()
+                    .find(|f| f.name() == *name)
+                    .ok_or_else(|| {
+                        ApiError::core(format!("The Protobuf field {field} doesn't exist"))
+                    })?;
+                node = node.children.entry(descriptor.number() as u64).or_default();
+                if i == path.len() - 1 {
+                    node.selected = true;
+                    break;
+                }
+                if descriptor.r#type() != Type::Message {
+                    return Err(ApiError::core(format!(
+                        "The Protobuf field {field} is not in a nested message"
+                    )));
+                }
+                let type_name = descriptor.type_name().trim_start_matches('.');
+                current = messages.get(type_name).ok_or_else(|| {
+                    ApiError::core(format!("The Protobuf message type {type_name} is unknown"))
+                })?;
+            }
+        }
+        Ok(Self { tree })
+    }
+
+    pub(super) fn read(&self, record: &[u8], mode: Mode) -> Result<FieldsRecord, InterceptError> {
+        if record.first() == Some(&SCHEMA_REGISTRY_MAGIC) {
+            return Err("Protobuf records framed by a schema registry are not supported".into());
+        }
+        let mut values = vec![];
+        let segments = read_message(record, &self.tree, mode, &mut values)?;
+        Ok(FieldsRecord {
+            mode,
+            content: RecordContent::Segments(segments),
+            values,
+        })
+    }
+}
+
+/// Register the descriptors of a message and its nested messages by fully-qualified name
+fn collect_messages<'a>(
+    prefix: &str,
+    message: &'a DescriptorProto,
+    messages: &mut HashMap<String, &'a DescriptorProto>,
+) {
+    let name = if prefix.is_empty() {
+        message.name().to_string()
+    } else {
+        format!("{prefix}.{}", message.name())
+    };
+    for nested in &message.nested_type {
+        collect_messages(&name, nested, messages);
+    }
+    messages.insert(name, message);
+}
+
+/// Selected fields, organized as a tree of field numbers
+#[derive(Debug, Default)]
+struct FieldTree {
+    selected: bool,
+    children: BTreeMap<u64, FieldTree>,
+}
+
+/// Read a message, extracting the selected fields
+fn read_message(
+    data: &[u8],
+    tree: &FieldTree,
+    mode: Mode,
+    values: &mut Vec<Vec<u8>>,
+) -> Result<Vec<Segment>, InterceptError> {
+    let mut segments = vec![];
+    // index of the value collecting the occurrences of each encrypted field
+    let mut occurrences: HashMap<u64, usize> = HashMap::new();
+    let mut position = 0;
+    while position < data.len() {
+        let start = position;
+        let (tag, value_start) = read_varint(data, position).ok_or("Invalid Protobuf tag")?;
+        let (number, wire_type) = (tag >> 3, tag & 7);
+        let end = skip_value(data, value_start, wire_type, number, 0)?;
+        position = end;
+
+        let Some(child) = tree.children.get(&number) else {
+            push_raw(&mut segments, &data[start..end]);
+            continue;
+        };
+        if child.selected {
+            match mode {
+                Mode::Encrypt => {
+                    if let Some(index) = occurrences.get(&number) {
+                        values[*index].extend_from_slice(&data[start..end]);
+                    } else {
+                        let mut tag = vec![];
+                        write_varint((number << 3) | LENGTH_DELIMITED, &mut tag);
+                        push_raw(&mut segments, &tag);
+                        occurrences.insert(number, values.len());
+                        segments.push(Segment::Field(values.len()));
+                        values.push(data[start..end].to_vec());
+                    }
+                }
+                Mode::Decrypt => {
+                    let payload = length_delimited(data, value_start, wire_type)?;
+                    segments.push(Segment::Field(values.len()));
+                    values.push(payload.to_vec());
+                }
+            }
+        } else {
+            // a nested message containing selected fields
+            let payload = length_delimited(data, value_start, wire_type)?;
+            let nested = read_message(payload, child, mode, values)?;
+            push_raw(&mut segments, &data[start..value_start]);
+            segments.push(Segment::Sized(LengthPrefix::Varint, nested));
+        }
+    }
+    Ok(segments)
+}
+
+/// Return the payload of a length-delimited value
+fn length_delimited(data: &[u8], position: usize, wire_type: u64) -> Result<&[u8], InterceptError> {
+    if wire_type != LENGTH_DELIMITED {
+        return Err("Expected a length-delimited Protobuf field".into());
+    }
+    let (length, start) = read_varint(data, position).ok_or("Invalid Protobuf length")?;
+    let end = value_end(start, length)?;
+    data.get(start..end)
+        .ok_or_else(|| "Truncated Protobuf message".into())
+}
+
+/// Return the position following a length-delimited value, if it doesn't overflow
+fn value_end(start: usize, length: u64) -> Result<usize, InterceptError> {
+    usize::try_from(length)
+        .ok()
+        .and_then(|length| start.checked_add(length))
+        .ok_or_else(|| "Invalid Protobuf length".into())
+}
+
+/// Return the position following a value, at a given depth of nested groups
+fn skip_value(
+    data: &[u8],
+    position: usize,
+    wire_type: u64,
+    number: u64,
+    depth: usize,
+) -> Result<usize, InterceptError> {
+    let end = match wire_type {
+        0 => {
+            read_varint(data, position)
+                .ok_or("Invalid Protobuf varint")?
+                .1
+        }
+        1 => position + 8,
+        LENGTH_DELIMITED => {
+            let (length, start) = read_varint(data, position).ok_or("Invalid Protobuf length")?;
+            value_end(start, length)?
+        }
+        START_GROUP => {
+            if depth >= MAX_GROUP_DEPTH {
+                return Err("Too many nested Protobuf groups".into());
+            }
+            let mut position = position;
+            loop {
+                let (tag, next) = read_varint(data, position).ok_or("Invalid Protobuf tag")?;
+                if tag & 7 == END_GROUP {
+                    if tag >> 3 != number {
+                        return Err("Invalid Protobuf group".into());
+                    }
+                    break next;
+                }
+                position = skip_value(data, next, tag & 7, tag >> 3, depth + 1)?;
+            }
+        }
+        5 => position + 4,
+        _ => return Err("Invalid Protobuf wire type".into()),
+    };
+    if end > data.len() {
+        return Err("Truncated Protobuf message".into());
+    }
+    Ok(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::protocol_aware::record_format::segments;
+    use prost_types::{FieldDescriptorProto, FileDescriptorProto};
+
+    fn field(
+        name: &str,
+        number: i32,
+        r#type: Type,
+        type_name: Option<&str>,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            r#type: Some(r#type as i32),
+            type_name: type_name.map(|t| t.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn descriptor_set() -> Vec<u8> {
+        let customer = DescriptorProto {
+            name: Some("Customer".to_string()),
+            field: vec![
+                field("name", 1, Type::String, None),
+                field("emails", 2, Type::String, None),
+            ],
+            ..Default::default()
+        };
+        let order = DescriptorProto {
+            name: Some("Order".to_string()),
+            field: vec![
+                field("id", 1, Type::Int64, None),
+                field("customer", 2, Type::Message, Some(".shop.Order.Customer")),
+            ],
+            nested_type: vec![customer],
+            ..Default::default()
+        };
+        FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("shop.proto".to_string()),
+                package: Some("shop".to_string()),
+                message_type: vec![order],
+                ..Default::default()
+            }],
+        }
+        .encode_to_vec()
+    }
+
+    fn string(number: u64, value: &str, buffer: &mut Vec<u8>) {
+        write_varint((number << 3) | LENGTH_DELIMITED, buffer);
+        write_varint(value.len() as u64, buffer);
+        buffer.extend_from_slice(value.as_bytes());
+    }
+
+    fn order() -> Vec<u8> {
+        let mut customer = vec![];
+        string(2, "alice@example.com", &mut customer);
+        string(1, "alice", &mut customer);
+        string(2, "alice@example.org", &mut customer);
+        let mut buffer = vec![];
+        write_varint(1 << 3, &mut buffer);
+        write_varint(42, &mut buffer);
+        write_varint((2 << 3) | LENGTH_DELIMITED, &mut buffer);
+        write_varint(customer.len() as u64, &mut buffer);
+        buffer.extend(customer);
+        buffer
+    }
+
+    fn transform(
+        fields: &ProtobufFields,
+        record: &[u8],
+        mode: Mode,
+        f: fn(&[u8]) -> Vec<u8>,
+    ) -> Vec<u8> {
+        let record = fields.read(record, mode).unwrap();
+        let values: Vec<Vec<u8>> = record.values.iter().map(|v| f(v)).collect();
+        match record.content {
+            RecordContent::Segments(s) => {
+                segments::write(&s, &values, mode.field_prefix(LengthPrefix::Varint))
+            }
+            _ => panic!("unexpected content"),
+        }
+    }
+
+    fn reverse(value: &[u8]) -> Vec<u8> {
+        value.iter().rev().cloned().collect()
+    }
+
+    #[test]
+    fn encrypt_nested_repeated_field() {
+        let fields =
+            ProtobufFields::new(&descriptor_set(), "shop.Order", &["customer.emails".into()])
+                .unwrap();
+        let record = order();
+
+        let read = fields.read(&record, Mode::Encrypt).unwrap();
+        let mut emails = vec![];
+        string(2, "alice@example.com", &mut emails);
+        string(2, "alice@example.org", &mut emails);
+        assert_eq!(read.values, vec![emails]);
+
+        let encrypted = transform(&fields, &record, Mode::Encrypt, reverse);
+        // the name is still readable, the emails are replaced by a single field
+        let read = fields.read(&encrypted, Mode::Decrypt).unwrap();
+        assert_eq!(read.values.len(), 1);
+
+        let decrypted = transform(&fields, &encrypted, Mode::Decrypt, reverse);
+        // the occurrences of the encrypted field are grouped
+        let mut customer = vec![];
+        string(2, "alice@example.com", &mut customer);
+        string(2, "alice@example.org", &mut customer);
+        string(1, "alice", &mut customer);
+        let mut expected = vec![];
+        write_varint(1 << 3, &mut expected);
+        write_varint(42, &mut expected);
+        write_varint((2 << 3) | LENGTH_DELIMITED, &mut expected);
+        write_varint(customer.len() as u64, &mut expected);
+        expected.extend(customer);
+        assert_eq!(decrypted, expected);
+    }
+
+    #[test]
+    fn nested_groups() {
+        let fields =
+            ProtobufFields::new(&descriptor_set(), "shop.Order", &["customer.emails".into()])
+                .unwrap();
+        let group = |depth: usize| {
+            let mut buffer = vec![];
+            for _ in 0..depth {
+                write_varint((3 << 3) | START_GROUP, &mut buffer);
+            }
+            for _ in 0..depth {
+                write_varint((3 << 3) | END_GROUP, &mut buffer);
+            }
+            buffer
+        };
+        assert!(fields.read(&group(MAX_GROUP_DEPTH), Mode::Encrypt).is_ok());
+        assert!(fields
+            .read(&group(MAX_GROUP_DEPTH + 1), Mode::Encrypt)
+            .is_err());
+        // the depth is checked before reading the end of the groups
+        assert!(fields
+            .read(&group(100_000)[..100_000], Mode::Encrypt)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let fields =
+            ProtobufFields::new(&descriptor_set(), "shop.Order", &["customer.emails".into()])
+                .unwrap();
+        for number in [2, 3] {
+            let mut buffer = vec![];
+            write_varint((number << 3) | LENGTH_DELIMITED, &mut buffer);
+            write_varint(u64::MAX, &mut buffer);
+            assert!(fields.read(&buffer, Mode::Encrypt).is_err());
+            assert!(fields.read(&buffer, Mode::Decrypt).is_err());
+        }
+    }
+
+    #[test]
+    fn reject_schema_registry_framing() {
+        let fields =
+            ProtobufFields::new(&descriptor_set(), "shop.Order", &["customer.emails".into()])
+                .unwrap();
+        // magic byte, schema id, message indexes
+        let mut record = vec![SCHEMA_REGISTRY_MAGIC, 0, 0, 0, 1, 0];
+        record.extend(order());
+        assert!(fields.read(&record, Mode::Encrypt).is_err());
+        assert!(fields.read(&record, Mode::Decrypt).is_err());
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let descriptor_set = descriptor_set();
+        assert!(ProtobufFields::new(&descriptor_set, "shop.Unknown", &[]).is_err());
+        assert!(ProtobufFields::new(&descriptor_set, "shop.Order", &["unknown".into()]).is_err());
+        assert!(ProtobufFields::new(&descriptor_set, "shop.Order", &["id.value".into()]).is_err());
+        assert!(ProtobufFields::new(&descriptor_set, ".shop.Order", &["customer".into()]).is_ok());
+    }
+}
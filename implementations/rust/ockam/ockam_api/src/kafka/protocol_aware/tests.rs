@@ -7,6 +7,7 @@ mod test {
     use crate::kafka::protocol_aware::{
         KafkaMessageRequestInterceptor, KafkaMessageResponseInterceptor,
     };
+    use crate::kafka::topic_encryption::{TopicEncryption, TopicEncryptionPolicy};
     use crate::kafka::{ConsumerPublishing, ConsumerResolution};
     use crate::port_range::PortRange;
     use crate::test_utils::TestNode;
@@ -71,8 +72,7 @@ mod test {
             Arc::new(secure_channel_controller),
             Default::default(),
             inlet_map,
-            true,
-            vec![],
+            TopicEncryptionPolicy::new(TopicEncryption::Whole),
         );
 
         let mut correlation_id = 0;
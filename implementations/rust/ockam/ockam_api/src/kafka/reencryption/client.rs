@@ -0,0 +1,517 @@
+use crate::ApiError;
+use bytes::{Bytes, BytesMut};
+use kafka_protocol::messages::fetch_request::{FetchPartition, FetchTopic};
+use kafka_protocol::messages::fetch_response::AbortedTransaction;
+use kafka_protocol::messages::list_offsets_request::{ListOffsetsPartition, ListOffsetsTopic};
+use kafka_protocol::messages::metadata_request::MetadataRequestTopic;
+use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
+use kafka_protocol::messages::{
+    ApiKey, BrokerId, FetchRequest, FetchResponse, ListOffsetsRequest, ListOffsetsResponse,
+    MetadataRequest, MetadataResponse, ProduceRequest, ProduceResponse, RequestHeader,
+    ResponseHeader, TopicName,
+};
+use kafka_protocol::protocol::{Decodable, Encodable, HeaderVersion, StrBytes};
+use kafka_protocol::records::{
+    Compression, Record, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
+};
+use ockam_core::compat::collections::{HashMap, HashSet};
+use ockam_core::{async_trait, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const CLIENT_ID: &str = "ockam-kafka-reencryption";
+
+/// Versions of the requests sent by the client.
+/// The fetch requests use a version identifying topics by name, so that the Kafka inlet
+/// doesn't need to map topic ids to names.
+const METADATA_VERSION: i16 = 9;
+const LIST_OFFSETS_VERSION: i16 = 5;
+const FETCH_VERSION: i16 = 11;
+const PRODUCE_VERSION: i16 = 8;
+
+/// Maximum size of the records returned for a partition by a fetch request
+const FETCH_PARTITION_MAX_BYTES: i32 = 1024 * 1024;
+
+/// Special timestamps used to list the earliest and latest offsets of a partition
+pub(super) const EARLIEST_OFFSET: i64 = -2;
+pub(super) const LATEST_OFFSET: i64 = -1;
+
+/// Only read the records of committed transactions. The latest offset of a partition
+/// is then its last stable offset, which excludes the transactions still open
+const READ_COMMITTED: i8 = 1;
+
+/// Type of the control records marking the abort of a transaction
+const ABORT_MARKER: i16 = 0;
+
+/// Records returned by a fetch request
+#[derive(Debug, Default)]
+pub(super) struct FetchedRecords {
+    /// Committed records, at or after the requested offset
+    pub(super) records: Vec<Record>,
+    /// Offset of the last record returned by the broker, including the control records and
+    /// the records of aborted transactions, which are dropped
+    pub(super) last_offset: Option<i64>,
+}
+
+/// Operations used by a re-encryption job to consume and produce the records of a topic
+#[async_trait]
+pub(super) trait KafkaTopicClient: Send {
+    /// Return the partitions of a topic with their leader
+    async fn partitions(&mut self, topic: &str) -> Result<Vec<(i32, BrokerId)>>;
+
+    async fn offset(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        timestamp: i64,
+    ) -> Result<i64>;
+
+    async fn fetch(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        offset: i64,
+    ) -> Result<FetchedRecords>;
+
+    async fn produce(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        records: Vec<Record>,
+    ) -> Result<()>;
+}
+
+/// A minimal Kafka client, consuming and producing records of single partitions.
+///
+/// The client connects to a Kafka inlet, so the records are decrypted when they are fetched,
+/// and encrypted when they are produced.
+pub(super) struct KafkaClient {
+    bootstrap_server: String,
+    brokers: HashMap<BrokerId, String>,
+    connections: HashMap<String, TcpStream>,
+    correlation_id: i32,
+}
+
+impl KafkaClient {
+    pub(super) fn new(bootstrap_server: &str) -> Self {
+        Self {
+            bootstrap_server: bootstrap_server.to_string(),
+            brokers: HashMap::new(),
+            connections: HashMap::new(),
+            correlation_id: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl KafkaTopicClient for KafkaClient {
+    async fn partitions(&mut self, topic: &str) -> Result<Vec<(i32, BrokerId)>> {
+        let request = MetadataRequest::default().with_topics(Some(vec![
+            MetadataRequestTopic::default().with_name(Some(topic_name(topic))),
+        ]));
+        let bootstrap_server = self.bootstrap_server.clone();
+        let response: MetadataResponse = self
+            .send(
+                &bootstrap_server,
+                ApiKey::Metadata,
+                METADATA_VERSION,
+                &request,
+            )
+            .await?;
+
+        for broker in response.brokers {
+            self.brokers
+                .insert(broker.node_id, format!("{}:{}", broker.host, broker.port));
+        }
+        let topic_metadata = response
+            .topics
+            .into_iter()
+            .find(|t| t.name.as_deref().map(|n| n.as_str()) == Some(topic))
+            .ok_or_else(|| ApiError::core(format!("The topic {topic} doesn't exist")))?;
+        check_error_code(topic_metadata.error_code, topic)?;
+
+        let mut partitions: Vec<(i32, BrokerId)> = topic_metadata
+            .partitions
+            .into_iter()
+            .map(|p| (p.partition_index, p.leader_id))
+            .collect();
+        partitions.sort();
+        Ok(partitions)
+    }
+
+    /// Return the earliest offset or the last stable offset of a partition
+    async fn offset(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        timestamp: i64,
+    ) -> Result<i64> {
+        let request = ListOffsetsRequest::default()
+            .with_replica_id(BrokerId(-1))
+            .with_isolation_level(READ_COMMITTED)
+            .with_topics(vec![ListOffsetsTopic::default()
+                .with_name(topic_name(topic))
+                .with_partitions(vec![ListOffsetsPartition::default()
+                    .with_partition_index(partition)
+                    .with_timestamp(timestamp)])]);
+        let address = self.broker_address(leader)?;
+        let response: ListOffsetsResponse = self
+            .send(
+                &address,
+                ApiKey::ListOffsets,
+                LIST_OFFSETS_VERSION,
+                &request,
+            )
+            .await?;
+        let partition_response = response
+            .topics
+            .into_iter()
+            .flat_map(|t| t.partitions)
+            .find(|p| p.partition_index == partition)
+            .ok_or_else(|| ApiError::core("Missing partition in the list offsets response"))?;
+        check_error_code(partition_response.error_code, topic)?;
+        Ok(partition_response.offset)
+    }
+
+    /// Fetch the committed records of a partition starting at an offset
+    async fn fetch(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        offset: i64,
+    ) -> Result<FetchedRecords> {
+        let request = FetchRequest::default()
+            .with_replica_id(BrokerId(-1))
+            .with_max_wait_ms(500)
+            .with_min_bytes(1)
+            .with_max_bytes(FETCH_PARTITION_MAX_BYTES)
+            .with_isolation_level(READ_COMMITTED)
+            .with_session_epoch(-1)
+            .with_topics(vec![FetchTopic::default()
+                .with_topic(topic_name(topic))
+                .with_partitions(vec![FetchPartition::default()
+                    .with_partition(partition)
+                    .with_fetch_offset(offset)
+                    .with_partition_max_bytes(FETCH_PARTITION_MAX_BYTES)])]);
+        let address = self.broker_address(leader)?;
+        let response: FetchResponse = self
+            .send(&address, ApiKey::Fetch, FETCH_VERSION, &request)
+            .await?;
+        check_error_code(response.error_code, topic)?;
+
+        let partition_data = response
+            .responses
+            .into_iter()
+            .flat_map(|t| t.partitions)
+            .find(|p| p.partition_index == partition)
+            .ok_or_else(|| ApiError::core("Missing partition in the fetch response"))?;
+        check_error_code(partition_data.error_code, topic)?;
+
+        let records = match partition_data.records {
+            Some(records) if !records.is_empty() => {
+                let mut records = BytesMut::from(records.as_ref());
+                RecordBatchDecoder::decode::<
+                    BytesMut,
+                    fn(&mut Bytes, Compression) -> core::result::Result<BytesMut, _>,
+                >(&mut records)
+                .map_err(|e| ApiError::core(format!("Cannot decode the fetched records: {e}")))?
+            }
+            _ => vec![],
+        };
+        Ok(committed_records(
+            records,
+            &partition_data.aborted_transactions.unwrap_or_default(),
+            offset,
+        ))
+    }
+
+    /// Produce records into a partition
+    async fn produce(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        leader: BrokerId,
+        records: Vec<Record>,
+    ) -> Result<()> {
+        let records: Vec<Record> = records
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| Record {
+                transactional: false,
+                control: false,
+                partition_leader_epoch: -1,
+                producer_id: -1,
+                producer_epoch: -1,
+                offset: index as i64,
+                sequence: -1,
+                ..record
+            })
+            .collect();
+        let mut encoded = BytesMut::new();
+        RecordBatchEncoder::encode::<
+            BytesMut,
+            std::slice::Iter<'_, Record>,
+            fn(&mut BytesMut, &mut BytesMut, Compression) -> core::result::Result<(), _>,
+        >(
+            &mut encoded,
+            records.iter(),
+            &RecordEncodeOptions {
+                version: 2,
+                compression: Compression::None,
+            },
+        )
+        .map_err(|e| ApiError::core(format!("Cannot encode the records to produce: {e}")))?;
+
+        let request = ProduceRequest::default()
+            .with_acks(-1)
+            .with_timeout_ms(30_000)
+            .with_topic_data(vec![TopicProduceData::default()
+                .with_name(topic_name(topic))
+                .with_partition_data(vec![PartitionProduceData::default()
+                    .with_index(partition)
+                    .with_records(Some(encoded.freeze()))])]);
+        let address = self.broker_address(leader)?;
+        let response: ProduceResponse = self
+            .send(&address, ApiKey::Produce, PRODUCE_VERSION, &request)
+            .await?;
+        for partition_response in response
+            .responses
+            .into_iter()
+            .flat_map(|t| t.partition_responses)
+        {
+            check_error_code(partition_response.error_code, topic)?;
+        }
+        Ok(())
+    }
+}
+
+impl KafkaClient {
+    fn broker_address(&self, broker: BrokerId) -> Result<String> {
+        self.brokers
+            .get(&broker)
+            .cloned()
+            .ok_or_else(|| ApiError::core(format!("Unknown Kafka broker {}", broker.0)))
+    }
+
+    /// Send a request to a broker and wait for its response
+    async fn send<Req, Resp>(
+        &mut self,
+        address: &str,
+        api_key: ApiKey,
+        api_version: i16,
+        request: &Req,
+    ) -> Result<Resp>
+    where
+        Req: Encodable + HeaderVersion,
+        Resp: Decodable + HeaderVersion,
+    {
+        self.correlation_id += 1;
+        let correlation_id = self.correlation_id;
+        let header = RequestHeader::default()
+            .with_request_api_key(api_key as i16)
+            .with_request_api_version(api_version)
+            .with_correlation_id(correlation_id)
+            .with_client_id(Some(StrBytes::from_static_str(CLIENT_ID)));
+
+        let mut message = BytesMut::new();
+        header
+            .encode(&mut message, Req::header_version(api_version))
+            .and_then(|_| request.encode(&mut message, api_version))
+            .map_err(|e| ApiError::core(format!("Cannot encode a Kafka request: {e}")))?;
+
+        let result = self.exchange(address, &message).await;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                // the connection is re-created for the next request
+                self.connections.remove(address);
+                return Err(e);
+            }
+        };
+
+        let mut response = Bytes::from(response);
+        let response_header =
+            ResponseHeader::decode(&mut response, Resp::header_version(api_version))
+                .map_err(|e| ApiError::core(format!("Cannot decode a Kafka response: {e}")))?;
+        if response_header.correlation_id != correlation_id {
+            self.connections.remove(address);
+            return Err(ApiError::core("Unexpected Kafka response"));
+        }
+        Resp::decode(&mut response, api_version)
+            .map_err(|e| ApiError::core(format!("Cannot decode a Kafka response: {e}")))
+    }
+
+    /// Write a length-prefixed message and read the length-prefixed response
+    async fn exchange(&mut self, address: &str, message: &[u8]) -> Result<Vec<u8>> {
+        if !self.connections.contains_key(address) {
+            let stream = TcpStream::connect(address).await.map_err(|e| {
+                ApiError::core(format!(
+                    "Cannot connect to the Kafka inlet at {address}: {e}"
+                ))
+            })?;
+            self.connections.insert(address.to_string(), stream);
+        }
+        let stream = self
+            .connections
+            .get_mut(address)
+            .ok_or_else(|| ApiError::core("Missing connection"))?;
+
+        let io_error = |e: std::io::Error| ApiError::core(format!("Kafka connection error: {e}"));
+        stream
+            .write_all(&(message.len() as u32).to_be_bytes())
+            .await
+            .map_err(io_error)?;
+        stream.write_all(message).await.map_err(io_error)?;
+
+        let length = stream.read_u32().await.map_err(io_error)?;
+        let mut response = vec![0; length as usize];
+        stream.read_exact(&mut response).await.map_err(io_error)?;
+        Ok(response)
+    }
+}
+
+/// Drop the control records and the records of aborted transactions.
+///
+/// A transactional record is aborted when its producer has an aborted transaction starting
+/// at or before the record, and the abort marker of that transaction was not read yet.
+/// The records before the requested offset are also dropped, since a record batch can start
+/// before that offset.
+pub(super) fn committed_records(
+    records: Vec<Record>,
+    aborted_transactions: &[AbortedTransaction],
+    offset: i64,
+) -> FetchedRecords {
+    let last_offset = records
+        .iter()
+        .map(|r| r.offset)
+        .filter(|o| *o >= offset)
+        .max();
+
+    let mut aborted_transactions: Vec<&AbortedTransaction> = aborted_transactions.iter().collect();
+    aborted_transactions.sort_by_key(|t| core::cmp::Reverse(t.first_offset));
+    let mut aborted_producers = HashSet::new();
+
+    let mut committed = vec![];
+    for record in records {
+        while let Some(transaction) = aborted_transactions
+            .last()
+            .filter(|t| t.first_offset <= record.offset)
+        {
+            aborted_producers.insert(transaction.producer_id.0);
+            aborted_transactions.pop();
+        }
+        if record.control {
+            if is_abort_marker(&record) {
+                aborted_producers.remove(&record.producer_id);
+            }
+            continue;
+        }
+        if record.transactional && aborted_producers.contains(&record.producer_id) {
+            continue;
+        }
+        if record.offset >= offset {
+            committed.push(record);
+        }
+    }
+    FetchedRecords {
+        records: committed,
+        last_offset,
+    }
+}
+
+/// The key of a control record contains its version and its type, as 16 bits integers
+fn is_abort_marker(record: &Record) -> bool {
+    match record.key.as_deref() {
+        Some([_, _, high, low, ..]) => i16::from_be_bytes([*high, *low]) == ABORT_MARKER,
+        _ => false,
+    }
+}
+
+fn topic_name(topic: &str) -> TopicName {
+    TopicName(StrBytes::from_string(topic.to_string()))
+}
+
+fn check_error_code(error_code: i16, topic: &str) -> Result<()> {
+    if error_code == 0 {
+        Ok(())
+    } else {
+        Err(ApiError::core(format!(
+            "The Kafka broker returned the error code {error_code} for the topic {topic}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kafka_protocol::messages::ProducerId;
+    use kafka_protocol::records::TimestampType;
+
+    #[test]
+    fn drop_aborted_transactions() {
+        // the producer 1 aborts a transaction, then commits another one, while the producer 2
+        // commits a transaction and a non-transactional record is produced
+        let records = vec![
+            record(0, 1, true, None),
+            record(1, 2, true, None),
+            record(2, 1, true, None),
+            record(3, 1, true, Some(marker(ABORT_MARKER))),
+            record(4, 2, true, Some(marker(1))),
+            record(5, 0, false, None),
+            record(6, 1, true, None),
+            record(7, 1, true, Some(marker(1))),
+        ];
+        let aborted_transactions = vec![AbortedTransaction::default()
+            .with_producer_id(ProducerId(1))
+            .with_first_offset(0)];
+
+        let fetched = committed_records(records.clone(), &aborted_transactions, 0);
+        let offsets: Vec<i64> = fetched.records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![1, 5, 6]);
+        assert_eq!(fetched.last_offset, Some(7));
+
+        // the aborted transactions starting before the requested offset are still dropped
+        let fetched = committed_records(records.clone(), &aborted_transactions, 2);
+        let offsets: Vec<i64> = fetched.records.iter().map(|r| r.offset).collect();
+        assert_eq!(offsets, vec![5, 6]);
+
+        // a fetch can only return dropped records
+        let fetched = committed_records(records[..4].to_vec(), &aborted_transactions, 2);
+        assert!(fetched.records.is_empty());
+        assert_eq!(fetched.last_offset, Some(3));
+    }
+
+    fn record(
+        offset: i64,
+        producer_id: i64,
+        transactional: bool,
+        control: Option<Bytes>,
+    ) -> Record {
+        Record {
+            transactional,
+            control: control.is_some(),
+            partition_leader_epoch: 0,
+            producer_id,
+            producer_epoch: 0,
+            timestamp_type: TimestampType::Creation,
+            offset,
+            sequence: 0,
+            timestamp: offset,
+            key: control,
+            value: None,
+            headers: Default::default(),
+        }
+    }
+
+    /// Key of a control record of the given type
+    fn marker(control_type: i16) -> Bytes {
+        let mut key = 0i16.to_be_bytes().to_vec();
+        key.extend_from_slice(&control_type.to_be_bytes());
+        Bytes::from(key)
+    }
+}
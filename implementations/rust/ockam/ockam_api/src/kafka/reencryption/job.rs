@@ -0,0 +1,525 @@
+use crate::kafka::reencryption::client::{
+    FetchedRecords, KafkaClient, KafkaTopicClient, EARLIEST_OFFSET, LATEST_OFFSET,
+};
+use crate::kafka::reencryption::storage::{KafkaReencryptionProgress, KafkaReencryptionRepository};
+use crate::ApiError;
+use ockam_core::compat::collections::HashMap;
+use ockam_core::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Number of consecutive fetches returning no records before a job fails
+const MAX_EMPTY_FETCHES: u32 = 10;
+/// Delay before fetching again some records after a fetch returned no records
+const EMPTY_FETCH_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Job re-encrypting the records of a Kafka topic into another topic.
+///
+/// The records are consumed through a Kafka inlet which decrypts them with the keys
+/// it already knows, then produced through a Kafka inlet which encrypts them with fresh keys.
+/// Each record is produced in the same partition as the source partition.
+///
+/// The offset of the next record to re-encrypt is stored after each batch of records is
+/// acknowledged by the broker, so an interrupted job resumes where it stopped.
+/// A batch of records can be produced twice if the job is interrupted before its progress
+/// is stored.
+///
+/// Only the records of committed transactions are re-encrypted, and the job stops at the
+/// last stable offset, so the records of open or aborted transactions are never produced.
+///
+/// The job only succeeds once all the records present when it started are re-encrypted.
+/// If the broker keeps returning no records before the end of a partition, the job fails
+/// and can be resumed later.
+pub struct KafkaReencryption {
+    job_name: String,
+    source_bootstrap_server: String,
+    source_topic: String,
+    destination_bootstrap_server: String,
+    destination_topic: String,
+    repository: Arc<dyn KafkaReencryptionRepository>,
+    max_empty_fetches: u32,
+    empty_fetch_backoff: Duration,
+}
+
+impl KafkaReencryption {
+    pub fn new(
+        job_name: &str,
+        source_bootstrap_server: &str,
+        source_topic: &str,
+        destination_bootstrap_server: &str,
+        destination_topic: &str,
+        repository: Arc<dyn KafkaReencryptionRepository>,
+    ) -> Result<Self> {
+        if source_bootstrap_server == destination_bootstrap_server
+            && source_topic == destination_topic
+        {
+            return Err(ApiError::core(
+                "The records must be re-encrypted into a different topic",
+            ));
+        }
+        Ok(Self {
+            job_name: job_name.to_string(),
+            source_bootstrap_server: source_bootstrap_server.to_string(),
+            source_topic: source_topic.to_string(),
+            destination_bootstrap_server: destination_bootstrap_server.to_string(),
+            destination_topic: destination_topic.to_string(),
+            repository,
+            max_empty_fetches: MAX_EMPTY_FETCHES,
+            empty_fetch_backoff: EMPTY_FETCH_BACKOFF,
+        })
+    }
+
+    /// Forget the progress of the job, so that it starts again from the earliest records
+    pub async fn reset(&self) -> Result<()> {
+        self.repository.delete_progress(&self.job_name).await
+    }
+
+    /// Re-encrypt all the records present in the source topic when the job starts.
+    /// The status of the job is reported after each batch of records.
+    pub async fn run(
+        &self,
+        on_progress: impl FnMut(&KafkaReencryptionStatus),
+    ) -> Result<KafkaReencryptionStatus> {
+        let mut source = KafkaClient::new(&self.source_bootstrap_server);
+        let mut destination = KafkaClient::new(&self.destination_bootstrap_server);
+        self.reencrypt(&mut source, &mut destination, on_progress)
+            .await
+    }
+
+    async fn reencrypt(
+        &self,
+        source: &mut impl KafkaTopicClient,
+        destination: &mut impl KafkaTopicClient,
+        mut on_progress: impl FnMut(&KafkaReencryptionStatus),
+    ) -> Result<KafkaReencryptionStatus> {
+        let source_partitions = source.partitions(&self.source_topic).await?;
+        let destination_leaders: HashMap<i32, _> = destination
+            .partitions(&self.destination_topic)
+            .await?
+            .into_iter()
+            .collect();
+
+        let stored: HashMap<i32, KafkaReencryptionProgress> = self
+            .repository
+            .get_progress(&self.job_name)
+            .await?
+            .into_iter()
+            .map(|p| (p.partition(), p))
+            .collect();
+        if let Some(progress) = stored.values().find(|p| {
+            p.source_topic() != self.source_topic || p.destination_topic() != self.destination_topic
+        }) {
+            return Err(ApiError::core(format!(
+                "The job {} re-encrypts the topic {} into the topic {}",
+                self.job_name,
+                progress.source_topic(),
+                progress.destination_topic()
+            )));
+        }
+
+        let mut status = KafkaReencryptionStatus::default();
+        for (partition, leader) in &source_partitions {
+            if !destination_leaders.contains_key(partition) {
+                return Err(ApiError::core(format!(
+                    "The topic {} must have at least as many partitions as the topic {}",
+                    self.destination_topic, self.source_topic
+                )));
+            }
+            let start_offset = source
+                .offset(&self.source_topic, *partition, *leader, EARLIEST_OFFSET)
+                .await?;
+            let end_offset = source
+                .offset(&self.source_topic, *partition, *leader, LATEST_OFFSET)
+                .await?;
+            // the stored offset can be before the start of the partition if old
+            // records have been deleted in the meantime
+            let next_offset = stored
+                .get(partition)
+                .map(|p| p.next_offset().max(start_offset))
+                .unwrap_or(start_offset);
+            status.partitions.push(KafkaReencryptionPartitionStatus {
+                partition: *partition,
+                start_offset,
+                next_offset,
+                end_offset,
+            });
+        }
+        on_progress(&status);
+
+        for index in 0..status.partitions.len() {
+            let partition = status.partitions[index].partition;
+            let source_leader = source_partitions
+                .iter()
+                .find(|(p, _)| *p == partition)
+                .map(|(_, leader)| *leader)
+                .ok_or_else(|| ApiError::core("Missing source partition"))?;
+            let destination_leader = destination_leaders[&partition];
+
+            let mut empty_fetches = 0;
+            while !status.partitions[index].is_complete() {
+                let next_offset = status.partitions[index].next_offset;
+                let end_offset = status.partitions[index].end_offset;
+                let FetchedRecords {
+                    records,
+                    last_offset,
+                } = source
+                    .fetch(&self.source_topic, partition, source_leader, next_offset)
+                    .await?;
+                let Some(last_offset) = last_offset else {
+                    empty_fetches += 1;
+                    if empty_fetches > self.max_empty_fetches {
+                        return Err(ApiError::core(format!(
+                            "No records were returned for the partition {partition} of the topic {} at offset {next_offset}, before the end offset {end_offset}. The job can be resumed later",
+                            self.source_topic
+                        )));
+                    }
+                    warn!(
+                        "no records returned for the partition {partition} of the topic {} at offset {next_offset}, retrying",
+                        self.source_topic
+                    );
+                    tokio::time::sleep(self.empty_fetch_backoff).await;
+                    continue;
+                };
+                empty_fetches = 0;
+
+                let records: Vec<_> = records
+                    .into_iter()
+                    .filter(|r| r.offset < end_offset)
+                    .collect();
+                debug!(
+                    "re-encrypting {} records of the partition {partition} from offset {next_offset}",
+                    records.len()
+                );
+                if !records.is_empty() {
+                    destination
+                        .produce(
+                            &self.destination_topic,
+                            partition,
+                            destination_leader,
+                            records,
+                        )
+                        .await?;
+                }
+
+                let next_offset = (last_offset + 1).min(end_offset);
+                self.repository
+                    .store_progress(&KafkaReencryptionProgress::new(
+                        &self.job_name,
+                        &self.source_topic,
+                        &self.destination_topic,
+                        partition,
+                        next_offset,
+                    ))
+                    .await?;
+                status.partitions[index].next_offset = next_offset;
+                on_progress(&status);
+            }
+        }
+        Ok(status)
+    }
+}
+
+/// Status of a re-encryption job
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KafkaReencryptionStatus {
+    partitions: Vec<KafkaReencryptionPartitionStatus>,
+}
+
+impl KafkaReencryptionStatus {
+    pub fn partitions(&self) -> &[KafkaReencryptionPartitionStatus] {
+        &self.partitions
+    }
+
+    /// Number of records already re-encrypted, including the records of a previous run
+    pub fn reencrypted_records(&self) -> u64 {
+        self.partitions
+            .iter()
+            .map(|p| (p.next_offset - p.start_offset).max(0) as u64)
+            .sum()
+    }
+
+    /// Number of records to re-encrypt
+    pub fn total_records(&self) -> u64 {
+        self.partitions
+            .iter()
+            .map(|p| (p.end_offset - p.start_offset).max(0) as u64)
+            .sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.partitions.iter().all(|p| p.is_complete())
+    }
+}
+
+/// Status of a re-encryption job for a partition of the source topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaReencryptionPartitionStatus {
+    pub partition: i32,
+    /// Offset of the earliest record in the partition
+    pub start_offset: i64,
+    /// Offset of the next record to re-encrypt
+    pub next_offset: i64,
+    /// Offset following the last record to re-encrypt
+    pub end_offset: i64,
+}
+
+impl KafkaReencryptionPartitionStatus {
+    pub fn is_complete(&self) -> bool {
+        self.next_offset >= self.end_offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::reencryption::client::committed_records;
+    use crate::kafka::reencryption::storage::KafkaReencryptionSqlxDatabase;
+    use bytes::Bytes;
+    use kafka_protocol::messages::fetch_response::AbortedTransaction;
+    use kafka_protocol::messages::{BrokerId, ProducerId};
+    use kafka_protocol::records::{Record, TimestampType};
+    use ockam_core::async_trait;
+
+    /// In-memory topics, fetching at most 2 records at a time
+    #[derive(Default)]
+    struct TestTopics {
+        records: HashMap<(String, i32), Vec<Record>>,
+        aborted_transactions: Vec<AbortedTransaction>,
+        /// Number of fetches returning no records, before returning the actual records
+        empty_fetches: u32,
+        fetches: u32,
+    }
+
+    impl TestTopics {
+        fn with_partitions(topic: &str, partitions: &[(i32, usize)]) -> Self {
+            let mut topics = Self::default();
+            for (partition, count) in partitions {
+                let records = (0..*count).map(|i| record(i as i64)).collect();
+                topics
+                    .records
+                    .insert((topic.to_string(), *partition), records);
+            }
+            topics
+        }
+
+        fn values(&self, topic: &str, partition: i32) -> Vec<i64> {
+            self.records
+                .get(&(topic.to_string(), partition))
+                .map(|records| records.iter().map(|r| r.timestamp).collect())
+                .unwrap_or_default()
+        }
+    }
+
+    #[async_trait]
+    impl KafkaTopicClient for TestTopics {
+        async fn partitions(&mut self, topic: &str) -> Result<Vec<(i32, BrokerId)>> {
+            let mut partitions: Vec<_> = self
+                .records
+                .keys()
+                .filter(|(t, _)| t == topic)
+                .map(|(_, p)| (*p, BrokerId(0)))
+                .collect();
+            partitions.sort();
+            Ok(partitions)
+        }
+
+        async fn offset(
+            &mut self,
+            topic: &str,
+            partition: i32,
+            _leader: BrokerId,
+            timestamp: i64,
+        ) -> Result<i64> {
+            let count = self.values(topic, partition).len() as i64;
+            Ok(if timestamp == EARLIEST_OFFSET {
+                0
+            } else {
+                count
+            })
+        }
+
+        async fn fetch(
+            &mut self,
+            topic: &str,
+            partition: i32,
+            _leader: BrokerId,
+            offset: i64,
+        ) -> Result<FetchedRecords> {
+            self.fetches += 1;
+            if self.empty_fetches > 0 {
+                self.empty_fetches -= 1;
+                return Ok(FetchedRecords::default());
+            }
+            let records = self.records[&(topic.to_string(), partition)]
+                .iter()
+                .filter(|r| r.offset >= offset)
+                .take(2)
+                .cloned()
+                .collect();
+            Ok(committed_records(
+                records,
+                &self.aborted_transactions,
+                offset,
+            ))
+        }
+
+        async fn produce(
+            &mut self,
+            topic: &str,
+            partition: i32,
+            _leader: BrokerId,
+            records: Vec<Record>,
+        ) -> Result<()> {
+            let produced = self
+                .records
+                .entry((topic.to_string(), partition))
+                .or_default();
+            for record in records {
+                let offset = produced.len() as i64;
+                produced.push(Record { offset, ..record });
+            }
+            Ok(())
+        }
+    }
+
+    /// Create a record whose timestamp is used to identify it
+    fn record(offset: i64) -> Record {
+        Record {
+            transactional: false,
+            control: false,
+            partition_leader_epoch: 0,
+            producer_id: 0,
+            producer_epoch: 0,
+            timestamp_type: TimestampType::Creation,
+            offset,
+            sequence: 0,
+            timestamp: offset,
+            key: None,
+            value: None,
+            headers: Default::default(),
+        }
+    }
+
+    /// Create a record of a transaction, or the marker ending it
+    fn transactional_record(offset: i64, producer_id: i64, marker: Option<i16>) -> Record {
+        Record {
+            transactional: true,
+            control: marker.is_some(),
+            producer_id,
+            key: marker.map(|marker| {
+                let mut key = 0i16.to_be_bytes().to_vec();
+                key.extend_from_slice(&marker.to_be_bytes());
+                Bytes::from(key)
+            }),
+            ..record(offset)
+        }
+    }
+
+    async fn create_job() -> Result<KafkaReencryption> {
+        let repository = KafkaReencryptionSqlxDatabase::create().await?;
+        let mut job =
+            KafkaReencryption::new("job", "source", "old", "destination", "new", repository)?;
+        job.max_empty_fetches = 2;
+        job.empty_fetch_backoff = Duration::from_millis(1);
+        Ok(job)
+    }
+
+    #[tokio::test]
+    async fn resume_from_stored_offsets() -> Result<()> {
+        let job = create_job().await?;
+        let mut source = TestTopics::with_partitions("old", &[(0, 5), (1, 3)]);
+        let mut destination = TestTopics::with_partitions("new", &[(0, 0), (1, 0)]);
+
+        // the partition 0 was partially re-encrypted by a previous run
+        job.repository
+            .store_progress(&KafkaReencryptionProgress::new("job", "old", "new", 0, 3))
+            .await?;
+
+        let status = job.reencrypt(&mut source, &mut destination, |_| {}).await?;
+        assert!(status.is_complete());
+        assert_eq!(status.reencrypted_records(), 8);
+        assert_eq!(destination.values("new", 0), vec![3, 4]);
+        assert_eq!(destination.values("new", 1), vec![0, 1, 2]);
+
+        let stored: Vec<_> = job
+            .repository
+            .get_progress("job")
+            .await?
+            .iter()
+            .map(|p| (p.partition(), p.next_offset()))
+            .collect();
+        assert_eq!(stored, vec![(0, 5), (1, 3)]);
+
+        // a completed job doesn't produce the records again
+        let status = job.reencrypt(&mut source, &mut destination, |_| {}).await?;
+        assert!(status.is_complete());
+        assert_eq!(destination.values("new", 0), vec![3, 4]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_aborted_transactions() -> Result<()> {
+        let job = create_job().await?;
+        let mut source = TestTopics::with_partitions("old", &[(0, 1)]);
+        let mut destination = TestTopics::with_partitions("new", &[(0, 0)]);
+
+        // the producer 1 aborts a transaction spanning a whole fetch,
+        // then the producer 2 commits a transaction
+        source
+            .records
+            .get_mut(&("old".to_string(), 0))
+            .unwrap()
+            .extend([
+                transactional_record(1, 1, None),
+                transactional_record(2, 1, None),
+                transactional_record(3, 1, Some(0)),
+                transactional_record(4, 2, None),
+                transactional_record(5, 2, Some(1)),
+            ]);
+        source.aborted_transactions = vec![AbortedTransaction::default()
+            .with_producer_id(ProducerId(1))
+            .with_first_offset(1)];
+
+        let status = job.reencrypt(&mut source, &mut destination, |_| {}).await?;
+        assert!(status.is_complete());
+        assert_eq!(destination.values("new", 0), vec![0, 4]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retry_empty_fetches() -> Result<()> {
+        let job = create_job().await?;
+        let mut source = TestTopics::with_partitions("old", &[(0, 3)]);
+        let mut destination = TestTopics::with_partitions("new", &[(0, 0)]);
+
+        // a few empty fetches are retried
+        source.empty_fetches = 2;
+        let status = job.reencrypt(&mut source, &mut destination, |_| {}).await?;
+        assert!(status.is_complete());
+        assert_eq!(destination.values("new", 0), vec![0, 1, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fail_when_the_fetches_stay_empty() -> Result<()> {
+        let job = create_job().await?;
+        let mut source = TestTopics::with_partitions("old", &[(0, 3)]);
+        let mut destination = TestTopics::with_partitions("new", &[(0, 0)]);
+
+        source.empty_fetches = 3;
+        assert!(job
+            .reencrypt(&mut source, &mut destination, |_| {})
+            .await
+            .is_err());
+        assert_eq!(source.fetches, 3);
+        assert!(destination.values("new", 0).is_empty());
+        assert!(job.repository.get_progress("job").await?.is_empty());
+
+        // the job can then be resumed
+        let status = job.reencrypt(&mut source, &mut destination, |_| {}).await?;
+        assert!(status.is_complete());
+        assert_eq!(destination.values("new", 0), vec![0, 1, 2]);
+        Ok(())
+    }
+}
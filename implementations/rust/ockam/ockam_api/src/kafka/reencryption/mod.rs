@@ -0,0 +1,7 @@
+//! Re-encryption of the records of a Kafka topic with fresh keys.
+
+mod client;
+mod job;
+pub mod storage;
+
+pub use job::*;
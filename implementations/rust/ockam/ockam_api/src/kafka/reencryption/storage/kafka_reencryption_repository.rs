@@ -0,0 +1,81 @@
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::retry;
+
+/// This repository stores the progress of Kafka re-encryption jobs, so that an interrupted
+/// job can be resumed where it stopped
+#[async_trait]
+pub trait KafkaReencryptionRepository: Send + Sync + 'static {
+    /// Store the offset of the next record to re-encrypt in a partition of the source topic
+    async fn store_progress(&self, progress: &KafkaReencryptionProgress) -> Result<()>;
+
+    /// Return the progress of a job for each partition of the source topic
+    async fn get_progress(&self, job_name: &str) -> Result<Vec<KafkaReencryptionProgress>>;
+
+    /// Delete the progress of a job
+    async fn delete_progress(&self, job_name: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: KafkaReencryptionRepository> KafkaReencryptionRepository for AutoRetry<T> {
+    async fn store_progress(&self, progress: &KafkaReencryptionProgress) -> Result<()> {
+        retry!(self.wrapped.store_progress(progress))
+    }
+
+    async fn get_progress(&self, job_name: &str) -> Result<Vec<KafkaReencryptionProgress>> {
+        retry!(self.wrapped.get_progress(job_name))
+    }
+
+    async fn delete_progress(&self, job_name: &str) -> Result<()> {
+        retry!(self.wrapped.delete_progress(job_name))
+    }
+}
+
+/// Progress of a re-encryption job for one partition of the source topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KafkaReencryptionProgress {
+    job_name: String,
+    source_topic: String,
+    destination_topic: String,
+    partition: i32,
+    next_offset: i64,
+}
+
+impl KafkaReencryptionProgress {
+    pub fn new(
+        job_name: &str,
+        source_topic: &str,
+        destination_topic: &str,
+        partition: i32,
+        next_offset: i64,
+    ) -> Self {
+        Self {
+            job_name: job_name.to_string(),
+            source_topic: source_topic.to_string(),
+            destination_topic: destination_topic.to_string(),
+            partition,
+            next_offset,
+        }
+    }
+
+    pub fn job_name(&self) -> &str {
+        &self.job_name
+    }
+
+    pub fn source_topic(&self) -> &str {
+        &self.source_topic
+    }
+
+    pub fn destination_topic(&self) -> &str {
+        &self.destination_topic
+    }
+
+    pub fn partition(&self) -> i32 {
+        self.partition
+    }
+
+    pub fn next_offset(&self) -> i64 {
+        self.next_offset
+    }
+}
@@ -0,0 +1,140 @@
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::kafka::reencryption::storage::{KafkaReencryptionProgress, KafkaReencryptionRepository};
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, ToVoid};
+
+/// Implementation of [`KafkaReencryptionRepository`] trait based on an underlying database
+/// using sqlx as its API
+#[derive(Clone)]
+pub struct KafkaReencryptionSqlxDatabase {
+    database: SqlxDatabase,
+}
+
+impl KafkaReencryptionSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase) -> Self {
+        debug!("create a repository for kafka re-encryption jobs");
+        Self { database }
+    }
+
+    /// Create a repository
+    pub fn make_repository(database: SqlxDatabase) -> Arc<dyn KafkaReencryptionRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database)))
+        } else {
+            Arc::new(Self::new(database))
+        }
+    }
+
+    /// Create a new in-memory database
+    #[allow(unused)]
+    pub async fn create() -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::new(
+            SqlxDatabase::in_memory("kafka re-encryption").await?,
+        )))
+    }
+}
+
+#[async_trait]
+impl KafkaReencryptionRepository for KafkaReencryptionSqlxDatabase {
+    async fn store_progress(&self, progress: &KafkaReencryptionProgress) -> Result<()> {
+        let query = query(
+            r#"
+            INSERT INTO kafka_reencryption_progress (job_name, source_topic, destination_topic, partition_index, next_offset)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (job_name, partition_index)
+            DO UPDATE SET source_topic = $2, destination_topic = $3, next_offset = $5"#,
+        )
+        .bind(progress.job_name())
+        .bind(progress.source_topic())
+        .bind(progress.destination_topic())
+        .bind(progress.partition())
+        .bind(progress.next_offset());
+        query.execute(&*self.database.pool).await.void()
+    }
+
+    async fn get_progress(&self, job_name: &str) -> Result<Vec<KafkaReencryptionProgress>> {
+        let query = query_as(
+            r#"
+            SELECT job_name, source_topic, destination_topic, partition_index, next_offset
+            FROM kafka_reencryption_progress
+            WHERE job_name = $1
+            ORDER BY partition_index"#,
+        )
+        .bind(job_name);
+        let rows: Vec<KafkaReencryptionProgressRow> =
+            query.fetch_all(&*self.database.pool).await.into_core()?;
+        Ok(rows.into_iter().map(|r| r.progress()).collect())
+    }
+
+    async fn delete_progress(&self, job_name: &str) -> Result<()> {
+        let query =
+            query("DELETE FROM kafka_reencryption_progress WHERE job_name = $1").bind(job_name);
+        query.execute(&*self.database.pool).await.void()
+    }
+}
+
+// Database serialization / deserialization
+
+/// Low-level representation of a row in the kafka_reencryption_progress table
+#[derive(FromRow)]
+struct KafkaReencryptionProgressRow {
+    job_name: String,
+    source_topic: String,
+    destination_topic: String,
+    partition_index: i32,
+    next_offset: i64,
+}
+
+impl KafkaReencryptionProgressRow {
+    fn progress(self) -> KafkaReencryptionProgress {
+        KafkaReencryptionProgress::new(
+            &self.job_name,
+            &self.source_topic,
+            &self.destination_topic,
+            self.partition_index,
+            self.next_offset,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_node::database::with_dbs;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn KafkaReencryptionRepository> =
+                Arc::new(KafkaReencryptionSqlxDatabase::new(db));
+
+            let progress1 = KafkaReencryptionProgress::new("job", "old", "new", 0, 10);
+            let progress2 = KafkaReencryptionProgress::new("job", "old", "new", 1, 20);
+            let other = KafkaReencryptionProgress::new("other", "old", "new", 0, 30);
+            repository.store_progress(&progress2).await?;
+            repository.store_progress(&progress1).await?;
+            repository.store_progress(&other).await?;
+
+            let actual = repository.get_progress("job").await?;
+            assert_eq!(actual, vec![progress1.clone(), progress2.clone()]);
+
+            // the progress of a partition is updated
+            let progress1 = KafkaReencryptionProgress::new("job", "old", "new", 0, 15);
+            repository.store_progress(&progress1).await?;
+            let actual = repository.get_progress("job").await?;
+            assert_eq!(actual, vec![progress1, progress2]);
+
+            repository.delete_progress("job").await?;
+            assert!(repository.get_progress("job").await?.is_empty());
+            assert_eq!(repository.get_progress("other").await?, vec![other]);
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,5 @@
+pub use kafka_reencryption_repository::*;
+pub use kafka_reencryption_repository_sql::*;
+
+mod kafka_reencryption_repository;
+mod kafka_reencryption_repository_sql;
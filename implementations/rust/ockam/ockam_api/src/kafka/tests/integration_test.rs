@@ -26,6 +26,7 @@ use uuid::Uuid;
 use crate::kafka::key_exchange::controller::KafkaKeyExchangeControllerImpl;
 use crate::kafka::protocol_aware::inlet::KafkaInletInterceptorFactory;
 use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
+use crate::kafka::topic_encryption::{TopicEncryption, TopicEncryptionPolicy};
 use crate::kafka::{ConsumerPublishing, ConsumerResolution, KafkaInletController};
 use crate::test_utils::{NodeManagerHandle, TestNode};
 use ockam::compat::tokio::io::DuplexStream;
@@ -114,8 +115,7 @@ async fn create_kafka_service(
         Arc::new(KafkaInletInterceptorFactory::new(
             secure_channel_controller,
             inlet_controller,
-            true,
-            vec![],
+            TopicEncryptionPolicy::new(TopicEncryption::Whole),
         )),
         Arc::new(AllowAll),
         Arc::new(AllowAll),
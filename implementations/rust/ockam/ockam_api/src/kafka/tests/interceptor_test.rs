@@ -29,6 +29,7 @@ use crate::kafka::key_exchange::controller::KafkaKeyExchangeControllerImpl;
 use crate::kafka::protocol_aware::inlet::InletInterceptorImpl;
 use crate::kafka::protocol_aware::KafkaMessageInterceptorWrapper;
 use crate::kafka::protocol_aware::MAX_KAFKA_MESSAGE_SIZE;
+use crate::kafka::topic_encryption::{TopicEncryption, TopicEncryptionPolicy};
 use crate::kafka::{ConsumerPublishing, ConsumerResolution};
 use crate::port_range::PortRange;
 use crate::test_utils::{NodeManagerHandle, TestNode};
@@ -332,8 +333,7 @@ async fn setup_only_worker(context: &mut Context, handle: &NodeManagerHandle) ->
                 Arc::new(secure_channel_controller),
                 Default::default(),
                 inlet_map,
-                true,
-                vec![],
+                TopicEncryptionPolicy::new(TopicEncryption::Whole),
             )),
             TEST_MAX_KAFKA_MESSAGE_SIZE,
         )),
@@ -430,8 +430,7 @@ async fn kafka_portal_worker__metadata_exchange__response_changed(
                 Arc::new(secure_channel_controller),
                 Default::default(),
                 inlet_map.clone(),
-                true,
-                vec![],
+                TopicEncryptionPolicy::new(TopicEncryption::Whole),
             )),
             MAX_KAFKA_MESSAGE_SIZE,
         )),
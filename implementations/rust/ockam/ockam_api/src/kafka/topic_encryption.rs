@@ -0,0 +1,362 @@
+use crate::kafka::protocol_aware::record_format::RecordFields;
+use crate::kafka::KafkaRecordFormat;
+use crate::ApiError;
+use minicbor::{CborLen, Decode, Encode};
+use ockam_core::Result;
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Topics selected by an encryption rule
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub enum KafkaTopicPattern {
+    /// A topic name
+    #[n(1)] Name(#[n(1)] String),
+    /// All the topics starting with a prefix
+    #[n(2)] Prefix(#[n(1)] String),
+    /// All the topics whose whole name matches a regular expression
+    #[n(3)] Regex(#[n(1)] String),
+}
+
+impl FromStr for KafkaTopicPattern {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ApiError::core("the topic pattern must not be empty"));
+        }
+        if let Some(regex) = s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+            topic_regex(regex)?;
+            Ok(Self::Regex(regex.to_string()))
+        } else if let Some(prefix) = s.strip_suffix('*') {
+            Ok(Self::Prefix(prefix.to_string()))
+        } else {
+            Ok(Self::Name(s.to_string()))
+        }
+    }
+}
+
+/// Compile a topic regular expression so that it must match the whole topic name
+fn topic_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(&format!("^(?:{pattern})$"))
+        .map_err(|e| ApiError::core(format!("invalid topic regex {pattern}: {e}")))
+}
+
+impl Display for KafkaTopicPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name}"),
+            Self::Prefix(prefix) => write!(f, "{prefix}*"),
+            Self::Regex(regex) => write!(f, "/{regex}/"),
+        }
+    }
+}
+
+/// Encryption of the records of a topic
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub enum KafkaTopicEncryption {
+    /// The records are sent in cleartext
+    #[n(1)] Cleartext,
+    /// The whole records are encrypted
+    #[n(2)] Whole,
+    /// Only these fields of the records are encrypted
+    #[n(3)] Fields(#[n(1)] Vec<String>),
+}
+
+impl FromStr for KafkaTopicEncryption {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "cleartext" | "none" => Ok(Self::Cleartext),
+            "whole" | "record" => Ok(Self::Whole),
+            s => match s.strip_prefix("fields:") {
+                Some(fields) => {
+                    let fields: Vec<String> = fields
+                        .split(',')
+                        .map(|f| f.trim().to_string())
+                        .filter(|f| !f.is_empty())
+                        .collect();
+                    if fields.is_empty() {
+                        return Err(ApiError::core("at least one field must be encrypted"));
+                    }
+                    Ok(Self::Fields(fields))
+                }
+                None => Err(ApiError::core(format!(
+                    "invalid topic encryption {s}, expected 'cleartext', 'whole' or 'fields:<FIELD>,...'"
+                ))),
+            },
+        }
+    }
+}
+
+impl Display for KafkaTopicEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cleartext => write!(f, "cleartext"),
+            Self::Whole => write!(f, "whole"),
+            Self::Fields(fields) => write!(f, "fields:{}", fields.join(",")),
+        }
+    }
+}
+
+/// Encryption of the topics matching a pattern.
+///
+/// The rules of a Kafka inlet are checked in order and the first matching rule applies.
+/// The topics not matching any rule are encrypted with the inlet settings.
+/// A rule is written `<TOPIC>=<ENCRYPTION>` where:
+///
+///  - `<TOPIC>` is a topic name, a prefix ending with `*`, or a regular expression between `/`.
+///  - `<ENCRYPTION>` is `cleartext`, `whole`, or `fields:<FIELD>,<FIELD>...`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KafkaTopicEncryptionRule {
+    #[n(1)] pub topic: KafkaTopicPattern,
+    #[n(2)] pub encryption: KafkaTopicEncryption,
+}
+
+impl FromStr for KafkaTopicEncryptionRule {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (topic, encryption) = s.rsplit_once('=').ok_or_else(|| {
+            ApiError::core(format!(
+                "invalid topic encryption rule {s}, expected <TOPIC>=<ENCRYPTION>"
+            ))
+        })?;
+        Ok(Self {
+            topic: topic.parse()?,
+            encryption: encryption.parse()?,
+        })
+    }
+}
+
+impl Display for KafkaTopicEncryptionRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.topic, self.encryption)
+    }
+}
+
+/// Encryption of the records of a topic, with the fields ready to be read
+#[derive(Clone)]
+pub(crate) enum TopicEncryption {
+    Cleartext,
+    Whole,
+    Fields(RecordFields),
+}
+
+impl TopicEncryption {
+    fn new(encryption: &KafkaTopicEncryption, record_format: &KafkaRecordFormat) -> Result<Self> {
+        Ok(match encryption {
+            KafkaTopicEncryption::Cleartext => Self::Cleartext,
+            KafkaTopicEncryption::Whole => Self::Whole,
+            KafkaTopicEncryption::Fields(fields) => {
+                Self::Fields(RecordFields::new(record_format, fields.clone())?)
+            }
+        })
+    }
+}
+
+#[derive(Clone)]
+enum TopicMatcher {
+    Name(String),
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl TopicMatcher {
+    fn matches(&self, topic: &str) -> bool {
+        match self {
+            Self::Name(name) => name == topic,
+            Self::Prefix(prefix) => topic.starts_with(prefix),
+            Self::Regex(regex) => regex.is_match(topic),
+        }
+    }
+}
+
+/// Encryption of the topics of a Kafka inlet
+#[derive(Clone)]
+pub(crate) struct TopicEncryptionPolicy {
+    rules: Vec<(TopicMatcher, TopicEncryption)>,
+    default: TopicEncryption,
+}
+
+impl TopicEncryptionPolicy {
+    /// Create a policy using the same encryption for all the topics
+    pub(crate) fn new(default: TopicEncryption) -> Self {
+        Self {
+            rules: vec![],
+            default,
+        }
+    }
+
+    /// Create a policy from the settings of a Kafka inlet
+    pub(crate) fn from_rules(
+        encrypt_content: bool,
+        encrypted_fields: Vec<String>,
+        record_format: &KafkaRecordFormat,
+        rules: &[KafkaTopicEncryptionRule],
+    ) -> Result<Self> {
+        let default = if !encrypt_content {
+            KafkaTopicEncryption::Cleartext
+        } else if encrypted_fields.is_empty() {
+            KafkaTopicEncryption::Whole
+        } else {
+            KafkaTopicEncryption::Fields(encrypted_fields)
+        };
+        let mut policy = Self::new(TopicEncryption::new(&default, record_format)?);
+        for rule in rules {
+            let matcher = match &rule.topic {
+                KafkaTopicPattern::Name(name) => TopicMatcher::Name(name.clone()),
+                KafkaTopicPattern::Prefix(prefix) => TopicMatcher::Prefix(prefix.clone()),
+                KafkaTopicPattern::Regex(regex) => TopicMatcher::Regex(topic_regex(regex)?),
+            };
+            policy.rules.push((
+                matcher,
+                TopicEncryption::new(&rule.encryption, record_format)?,
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// Return the encryption of a topic
+    pub(crate) fn for_topic(&self, topic: &str) -> &TopicEncryption {
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.matches(topic))
+            .map(|(_, encryption)| encryption)
+            .unwrap_or(&self.default)
+    }
+
+    /// Return the encryption of the topics not matching any rule
+    pub(crate) fn default_encryption(&self) -> &TopicEncryption {
+        &self.default
+    }
+
+    /// Return true if the encryption differs between topics
+    pub(crate) fn depends_on_topic(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Return true if the records of some topics are encrypted
+    pub(crate) fn encrypts_any_topic(&self) -> bool {
+        !matches!(self.default, TopicEncryption::Cleartext)
+            || self
+                .rules
+                .iter()
+                .any(|(_, encryption)| !matches!(encryption, TopicEncryption::Cleartext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rules() {
+        let rule: KafkaTopicEncryptionRule = "orders=whole".parse().unwrap();
+        assert_eq!(rule.topic, KafkaTopicPattern::Name("orders".into()));
+        assert_eq!(rule.encryption, KafkaTopicEncryption::Whole);
+
+        let rule: KafkaTopicEncryptionRule = "logs.*=cleartext".parse().unwrap();
+        assert_eq!(rule.topic, KafkaTopicPattern::Prefix("logs.".into()));
+        assert_eq!(rule.encryption, KafkaTopicEncryption::Cleartext);
+
+        let rule: KafkaTopicEncryptionRule = "/^pay(ments)?-[0-9]+$/=fields:card,customer.email"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rule.topic,
+            KafkaTopicPattern::Regex("^pay(ments)?-[0-9]+$".into())
+        );
+        assert_eq!(
+            rule.encryption,
+            KafkaTopicEncryption::Fields(vec!["card".into(), "customer.email".into()])
+        );
+        assert_eq!(
+            rule.to_string(),
+            "/^pay(ments)?-[0-9]+$/=fields:card,customer.email"
+        );
+
+        assert!("orders".parse::<KafkaTopicEncryptionRule>().is_err());
+        assert!("orders=fields:"
+            .parse::<KafkaTopicEncryptionRule>()
+            .is_err());
+        assert!("orders=unknown"
+            .parse::<KafkaTopicEncryptionRule>()
+            .is_err());
+        assert!("/(/=whole".parse::<KafkaTopicEncryptionRule>().is_err());
+    }
+
+    #[test]
+    fn first_matching_rule_applies() {
+        let rules: Vec<KafkaTopicEncryptionRule> = [
+            "logs.audit=whole",
+            "logs.*=cleartext",
+            "/^users-.+$/=fields:email",
+        ]
+        .iter()
+        .map(|r| r.parse().unwrap())
+        .collect();
+        let policy =
+            TopicEncryptionPolicy::from_rules(true, vec![], &KafkaRecordFormat::Json, &rules)
+                .unwrap();
+        assert!(matches!(
+            policy.for_topic("logs.audit"),
+            TopicEncryption::Whole
+        ));
+        assert!(matches!(
+            policy.for_topic("logs.debug"),
+            TopicEncryption::Cleartext
+        ));
+        assert!(matches!(
+            policy.for_topic("users-eu"),
+            TopicEncryption::Fields(_)
+        ));
+        assert!(matches!(policy.for_topic("orders"), TopicEncryption::Whole));
+        assert!(policy.encrypts_any_topic());
+
+        let policy =
+            TopicEncryptionPolicy::from_rules(false, vec![], &KafkaRecordFormat::Json, &[])
+                .unwrap();
+        assert!(!policy.encrypts_any_topic());
+        let policy =
+            TopicEncryptionPolicy::from_rules(false, vec![], &KafkaRecordFormat::Json, &rules)
+                .unwrap();
+        assert!(matches!(
+            policy.for_topic("orders"),
+            TopicEncryption::Cleartext
+        ));
+        assert!(policy.encrypts_any_topic());
+    }
+
+    #[test]
+    fn regex_rules_match_whole_topic_names() {
+        let rules: Vec<KafkaTopicEncryptionRule> = ["/users-[a-z]+/=whole", "/a|b/=whole"]
+            .iter()
+            .map(|r| r.parse().unwrap())
+            .collect();
+        let policy =
+            TopicEncryptionPolicy::from_rules(false, vec![], &KafkaRecordFormat::Json, &rules)
+                .unwrap();
+        assert!(matches!(
+            policy.for_topic("users-eu"),
+            TopicEncryption::Whole
+        ));
+        assert!(matches!(policy.for_topic("b"), TopicEncryption::Whole));
+
+        // a substring match does not select the topic
+        for topic in ["old-users-eu", "users-eu-1", "abc"] {
+            assert!(matches!(
+                policy.for_topic(topic),
+                TopicEncryption::Cleartext
+            ));
+        }
+    }
+}
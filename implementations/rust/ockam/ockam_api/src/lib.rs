@@ -19,6 +19,7 @@
 #[macro_use]
 extern crate tracing;
 
+pub mod acme;
 pub mod address;
 pub mod authenticator;
 pub mod cli_state;
@@ -27,12 +28,14 @@ pub mod echoer;
 pub mod enroll;
 pub mod error;
 pub mod hop;
+pub mod http_proxy;
 pub mod kafka;
 pub mod minicbor_url;
 pub mod nodes;
 pub mod okta;
 pub mod orchestrator;
 pub mod port_range;
+pub mod postgres;
 pub mod session;
 pub mod uppercase;
 mod version;
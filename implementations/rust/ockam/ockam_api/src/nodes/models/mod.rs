@@ -11,4 +11,5 @@ pub mod relay;
 pub mod secure_channel;
 pub mod services;
 pub mod transport;
+pub mod udp_portal;
 pub mod workers;
@@ -1,10 +1,12 @@
 use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::Identifier;
 use ockam_abac::{
     Action, Expr, PolicyExpression, ResourceName, ResourcePolicy, ResourceType, ResourceTypePolicy,
 };
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Error;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
@@ -25,6 +27,35 @@ impl SetPolicyRequest {
     }
 }
 
+/// Request to evaluate a policy for a given subject, without accessing any resource.
+/// The policy is either given explicitly, or the one set on a resource for the requested action.
+/// The attributes are used to simulate the presentation of a credential by the subject.
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EvaluatePolicyRequest {
+    #[n(1)] pub resource: Option<ResourceTypeOrName>,
+    #[n(2)] pub expression: Option<PolicyExpression>,
+    #[n(3)] pub subject: Identifier,
+    #[n(4)] pub attributes: BTreeMap<String, String>,
+}
+
+impl EvaluatePolicyRequest {
+    pub fn new(
+        resource: Option<ResourceTypeOrName>,
+        expression: Option<PolicyExpression>,
+        subject: Identifier,
+        attributes: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            resource,
+            expression,
+            subject,
+            attributes,
+        }
+    }
+}
+
 #[derive(Debug, Encode, Decode, CborLen, PartialEq, Eq)]
 #[rustfmt::skip]
 #[cbor(map)]
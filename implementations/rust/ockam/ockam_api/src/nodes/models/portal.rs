@@ -6,12 +6,16 @@ use std::time::Duration;
 
 use minicbor::{CborLen, Decode, Encode};
 use ockam::identity::Identifier;
+use ockam::tcp::{
+    TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletTargetStatus, TcpOutletTlsConfig,
+};
 use ockam::transport::HostnamePort;
 use ockam_abac::PolicyExpression;
 use ockam_core::{Address, IncomingAccessControl, OutgoingAccessControl, Route};
 use ockam_multiaddr::MultiAddr;
 use serde::{Deserialize, Serialize};
 
+use crate::acme::AcmeCertificateConfig;
 use crate::colors::{color_primary, color_primary_alt};
 use crate::error::ApiError;
 
@@ -61,6 +65,13 @@ pub struct CreateInlet {
     #[n(14)] pub(crate) skip_handshake: bool,
     /// Enable Nagle's algorithm for potentially higher throughput, but higher latency
     #[n(15)] pub(crate) enable_nagle: bool,
+    /// Configuration of the ACME server providing the TLS certificate.
+    /// It takes precedence over the TLS certificate provider route.
+    #[n(16)] pub(crate) acme_certificate_provider: Option<AcmeCertificateConfig>,
+    /// PEM bundle of the certificate authorities trusted to issue TLS client certificates.
+    /// When set, the TLS clients must present a certificate, and its attributes can be used
+    /// in the inlet policy expression.
+    #[n(17)] pub(crate) tls_client_ca_certificates: Option<String>,
 }
 
 impl CreateInlet {
@@ -91,6 +102,8 @@ impl CreateInlet {
             tls_certificate_provider: None,
             skip_handshake,
             enable_nagle,
+            acme_certificate_provider: None,
+            tls_client_ca_certificates: None,
         }
     }
 
@@ -122,11 +135,24 @@ impl CreateInlet {
             tls_certificate_provider: None,
             skip_handshake,
             enable_nagle,
+            acme_certificate_provider: None,
+            tls_client_ca_certificates: None,
         }
     }
 
-    pub fn set_tls_certificate_provider(&mut self, provider: MultiAddr) {
-        self.tls_certificate_provider = Some(provider);
+    pub fn set_tls_certificate_provider(&mut self, provider: InletTlsCertificateProvider) {
+        match provider {
+            InletTlsCertificateProvider::Remote(route) => {
+                self.tls_certificate_provider = Some(route)
+            }
+            InletTlsCertificateProvider::Acme(config) => {
+                self.acme_certificate_provider = Some(config)
+            }
+        }
+    }
+
+    pub fn set_tls_client_ca_certificates(&mut self, ca_certificates_pem: String) {
+        self.tls_client_ca_certificates = Some(ca_certificates_pem);
     }
 
     pub fn set_wait_ms(&mut self, ms: u64) {
@@ -162,6 +188,30 @@ impl CreateInlet {
     }
 }
 
+/// Source of the TLS certificate used by an inlet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InletTlsCertificateProvider {
+    /// The certificate is retrieved from a certificate provider service, usually on a project
+    Remote(MultiAddr),
+    /// The certificate is obtained and renewed by the node from an ACME server
+    Acme(AcmeCertificateConfig),
+}
+
+impl InletTlsCertificateProvider {
+    /// Return the certificate provider specified by the fields of a [`CreateInlet`] request.
+    /// An ACME configuration takes precedence over a route to a certificate provider
+    pub(crate) fn from_request_fields(
+        tls_certificate_provider: Option<MultiAddr>,
+        acme_certificate_provider: Option<AcmeCertificateConfig>,
+    ) -> Option<Self> {
+        match (acme_certificate_provider, tls_certificate_provider) {
+            (Some(config), _) => Some(InletTlsCertificateProvider::Acme(config)),
+            (None, Some(route)) => Some(InletTlsCertificateProvider::Remote(route)),
+            (None, None) => None,
+        }
+    }
+}
+
 /// Request body to create an outlet
 #[derive(Clone, Debug, Encode, Decode, CborLen)]
 #[rustfmt::skip]
@@ -186,6 +236,10 @@ pub struct CreateOutlet {
     #[n(7)] pub skip_handshake: bool,
     /// Enable Nagle's algorithm for potentially higher throughput, but higher latency
     #[n(8)] pub(crate) enable_nagle: bool,
+    /// Distribute the connections between `hostname_port` and additional targets
+    #[n(9)] pub(crate) load_balancing: Option<OutletLoadBalancing>,
+    /// TLS configuration of the connections to the targets, implies `tls`
+    #[n(10)] pub(crate) tls_config: Option<TcpOutletTlsConfig>,
 }
 
 impl CreateOutlet {
@@ -207,12 +261,49 @@ impl CreateOutlet {
             privileged,
             skip_handshake,
             enable_nagle,
+            load_balancing: None,
+            tls_config: None,
         }
     }
 
     pub fn set_policy_expression(&mut self, expression: PolicyExpression) {
         self.policy_expression = Some(expression);
     }
+
+    pub fn set_load_balancing(&mut self, load_balancing: OutletLoadBalancing) {
+        self.load_balancing = Some(load_balancing);
+    }
+
+    pub fn set_tls_config(&mut self, tls_config: TcpOutletTlsConfig) {
+        self.tls_config = Some(tls_config);
+    }
+}
+
+/// Load balancing configuration of an outlet with several targets
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletLoadBalancing {
+    /// The targets used in addition to the outlet `hostname_port`
+    #[n(1)] pub additional_targets: Vec<HostnamePort>,
+    /// The strategy used to choose the target of a new connection
+    #[n(2)] pub strategy: TcpOutletLoadBalancing,
+    /// If set, the targets are periodically checked and the unhealthy ones are not used
+    #[n(3)] pub health_check: Option<TcpOutletHealthCheck>,
+}
+
+impl OutletLoadBalancing {
+    pub fn new(
+        additional_targets: Vec<HostnamePort>,
+        strategy: TcpOutletLoadBalancing,
+        health_check: Option<TcpOutletHealthCheck>,
+    ) -> Self {
+        Self {
+            additional_targets,
+            strategy,
+            health_check,
+        }
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -312,6 +403,12 @@ pub struct OutletStatus {
     /// An optional status payload
     #[n(3)] pub payload: Option<String>,
     #[n(4)] pub privileged: bool,
+    /// The status of each target, when the outlet distributes its connections between several targets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[n(5)] pub targets: Vec<OutletTargetStatus>,
+    /// The additional targets and load balancing settings of a load balanced outlet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(6)] pub load_balancing: Option<OutletLoadBalancing>,
 }
 
 impl OutletStatus {
@@ -326,9 +423,21 @@ impl OutletStatus {
             worker_addr,
             payload: payload.into(),
             privileged,
+            targets: vec![],
+            load_balancing: None,
         }
     }
 
+    pub fn with_targets(mut self, targets: Vec<OutletTargetStatus>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn with_load_balancing(mut self, load_balancing: Option<OutletLoadBalancing>) -> Self {
+        self.load_balancing = load_balancing;
+        self
+    }
+
     pub fn worker_route(&self) -> Result<MultiAddr, ockam_core::Error> {
         ReverseLocalConverter::convert_address(&self.worker_addr)
     }
@@ -364,10 +473,60 @@ impl Display for OutletStatus {
             )?;
         }
 
+        if let Some(load_balancing) = &self.load_balancing {
+            writeln!(f)?;
+            write!(
+                f,
+                "{}Connections are distributed with the {} strategy",
+                fmt::INDENTATION,
+                color_primary(load_balancing.strategy.to_string())
+            )?;
+            if load_balancing.health_check.is_some() {
+                write!(f, ", the unhealthy targets are not used")?;
+            }
+        }
+
+        for target in &self.targets {
+            writeln!(f)?;
+            write!(f, "{}{}", fmt::INDENTATION, target)?;
+        }
+
         Ok(())
     }
 }
 
+/// Status of one of the targets of a load balanced outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletTargetStatus {
+    #[n(1)] pub to: HostnamePort,
+    #[n(2)] pub healthy: bool,
+    #[n(3)] pub active_connections: u64,
+}
+
+impl From<TcpOutletTargetStatus> for OutletTargetStatus {
+    fn from(status: TcpOutletTargetStatus) -> Self {
+        Self {
+            to: status.hostname_port().clone(),
+            healthy: status.is_healthy(),
+            active_connections: status.active_connections() as u64,
+        }
+    }
+}
+
+impl Display for OutletTargetStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Target {} is {} with {} active connections",
+            color_primary(self.to.to_string()),
+            color_primary(if self.healthy { "healthy" } else { "unhealthy" }),
+            color_primary(self.active_connections.to_string()),
+        )
+    }
+}
+
 impl Output for OutletStatus {
     fn item(&self) -> Result<String, ApiError> {
         Ok(self.padded_display())
@@ -1,5 +1,7 @@
 use crate::colors::{color_primary, color_warn};
-use crate::kafka::{ConsumerPublishing, ConsumerResolution};
+use crate::kafka::{
+    ConsumerPublishing, ConsumerResolution, KafkaRecordFormat, KafkaTopicEncryptionRule,
+};
 use crate::output::Output;
 use minicbor::{CborLen, Decode, Encode};
 use ockam_abac::PolicyExpression;
@@ -100,6 +102,8 @@ pub struct StartKafkaInletRequest {
     #[n(8)] consumer_policy_expression: Option<PolicyExpression>,
     #[n(9)] producer_policy_expression: Option<PolicyExpression>,
     #[n(10)] encrypted_fields: Vec<String>,
+    #[n(11)] record_format: Option<KafkaRecordFormat>,
+    #[n(12)] topic_encryption: Option<Vec<KafkaTopicEncryptionRule>>,
 }
 
 impl StartKafkaInletRequest {
@@ -110,6 +114,8 @@ impl StartKafkaInletRequest {
         kafka_outlet_route: MultiAddr,
         encrypt_content: bool,
         encrypted_fields: Vec<String>,
+        record_format: KafkaRecordFormat,
+        topic_encryption: Vec<KafkaTopicEncryptionRule>,
         consumer_resolution: ConsumerResolution,
         consumer_publishing: ConsumerPublishing,
         inlet_policy_expression: Option<PolicyExpression>,
@@ -127,6 +133,8 @@ impl StartKafkaInletRequest {
             consumer_policy_expression,
             producer_policy_expression,
             encrypted_fields,
+            record_format: Some(record_format),
+            topic_encryption: Some(topic_encryption),
         }
     }
 
@@ -148,6 +156,14 @@ impl StartKafkaInletRequest {
         self.encrypted_fields.clone()
     }
 
+    pub fn record_format(&self) -> KafkaRecordFormat {
+        self.record_format.clone().unwrap_or_default()
+    }
+
+    pub fn topic_encryption(&self) -> Vec<KafkaTopicEncryptionRule> {
+        self.topic_encryption.clone().unwrap_or_default()
+    }
+
     pub fn consumer_resolution(&self) -> ConsumerResolution {
         self.consumer_resolution.clone()
     }
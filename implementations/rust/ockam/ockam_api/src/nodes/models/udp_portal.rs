@@ -0,0 +1,220 @@
+//! UDP inlets and outlets request/response types
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::Identifier;
+use ockam::transport::HostnamePort;
+use ockam_abac::PolicyExpression;
+use ockam_core::{Address, Route};
+use ockam_multiaddr::MultiAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::color_primary;
+use crate::error::ApiError;
+use crate::output::Output;
+use crate::terminal::fmt;
+use crate::ReverseLocalConverter;
+
+/// Request body to create a UDP inlet
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpInlet {
+    /// The address the inlet socket should be bound to
+    #[n(1)] pub(crate) listen_addr: HostnamePort,
+    /// The address of the UDP outlet
+    #[n(2)] pub(crate) outlet_addr: MultiAddr,
+    /// A human-friendly alias for this inlet
+    #[n(3)] pub(crate) alias: String,
+    /// An authorised identity for secure channels.
+    /// Only set for non-project addresses as for projects the project's
+    /// authorised identity will be used.
+    #[n(4)] pub(crate) authorized: Option<Identifier>,
+    /// The expression for the access control policy for this inlet.
+    /// If not set, the policy set for the [UDP inlet resource type](ockam_abac::ResourceType::UdpInlet)
+    /// will be used.
+    #[n(5)] pub(crate) policy_expression: Option<PolicyExpression>,
+    /// The duration after which the session of a silent source address is closed
+    #[n(6)] pub(crate) idle_timeout: Option<Duration>,
+    /// The maximum number of sessions opened at the same time
+    #[n(7)] pub(crate) max_sessions: Option<u64>,
+}
+
+impl CreateUdpInlet {
+    pub fn new(
+        listen_addr: HostnamePort,
+        outlet_addr: MultiAddr,
+        alias: String,
+        authorized: Option<Identifier>,
+    ) -> Self {
+        Self {
+            listen_addr,
+            outlet_addr,
+            alias,
+            authorized,
+            policy_expression: None,
+            idle_timeout: None,
+            max_sessions: None,
+        }
+    }
+
+    pub fn set_policy_expression(&mut self, expression: PolicyExpression) {
+        self.policy_expression = Some(expression);
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout);
+    }
+
+    pub fn set_max_sessions(&mut self, max_sessions: u64) {
+        self.max_sessions = Some(max_sessions);
+    }
+}
+
+/// Request body to create a UDP outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateUdpOutlet {
+    /// The address datagrams are sent to
+    #[n(1)] pub(crate) hostname_port: HostnamePort,
+    /// The address of the outlet worker
+    #[n(2)] pub(crate) worker_addr: Option<Address>,
+    /// The expression for the access control policy for this outlet.
+    /// If not set, the policy set for the [UDP outlet resource type](ockam_abac::ResourceType::UdpOutlet)
+    /// will be used.
+    #[n(3)] pub(crate) policy_expression: Option<PolicyExpression>,
+    /// The duration after which the socket opened for a silent inlet session is closed
+    #[n(4)] pub(crate) idle_timeout: Option<Duration>,
+    /// The maximum number of inlet sessions opened at the same time
+    #[n(5)] pub(crate) max_sessions: Option<u64>,
+}
+
+impl CreateUdpOutlet {
+    pub fn new(hostname_port: HostnamePort, worker_addr: Option<Address>) -> Self {
+        Self {
+            hostname_port,
+            worker_addr,
+            policy_expression: None,
+            idle_timeout: None,
+            max_sessions: None,
+        }
+    }
+
+    pub fn set_policy_expression(&mut self, expression: PolicyExpression) {
+        self.policy_expression = Some(expression);
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout);
+    }
+
+    pub fn set_max_sessions(&mut self, max_sessions: u64) {
+        self.max_sessions = Some(max_sessions);
+    }
+}
+
+/// Response body when interacting with a UDP inlet
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpInletStatus {
+    #[n(1)] pub bind_addr: String,
+    #[n(2)] pub alias: String,
+    #[n(3)] pub outlet_addr: String,
+    #[n(4)] pub outlet_route: Option<String>,
+}
+
+impl UdpInletStatus {
+    pub fn new(
+        bind_addr: impl Into<String>,
+        alias: impl Into<String>,
+        outlet_addr: impl Into<String>,
+        outlet_route: impl Into<Option<String>>,
+    ) -> Self {
+        Self {
+            bind_addr: bind_addr.into(),
+            alias: alias.into(),
+            outlet_addr: outlet_addr.into(),
+            outlet_route: outlet_route.into(),
+        }
+    }
+}
+
+impl Display for UdpInletStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "UDP Inlet {} at {}",
+            color_primary(&self.alias),
+            color_primary(&self.bind_addr),
+        )?;
+        if let Some(r) = self
+            .outlet_route
+            .as_ref()
+            .and_then(Route::parse)
+            .and_then(|r| ReverseLocalConverter::convert_route(&r).ok())
+        {
+            writeln!(
+                f,
+                "{}With route to outlet {}",
+                fmt::INDENTATION,
+                color_primary(r.to_string())
+            )?;
+        }
+        writeln!(
+            f,
+            "{}Outlet Address: {}",
+            fmt::INDENTATION,
+            color_primary(&self.outlet_addr)
+        )
+    }
+}
+
+impl Output for UdpInletStatus {
+    fn item(&self) -> crate::Result<String> {
+        Ok(self.padded_display())
+    }
+}
+
+/// Response body when interacting with a UDP outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen, Serialize, Deserialize, PartialEq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UdpOutletStatus {
+    #[n(1)] pub to: HostnamePort,
+    #[n(2)] pub worker_addr: Address,
+}
+
+impl UdpOutletStatus {
+    pub fn new(to: HostnamePort, worker_addr: Address) -> Self {
+        Self { to, worker_addr }
+    }
+
+    pub fn worker_route(&self) -> Result<MultiAddr, ockam_core::Error> {
+        ReverseLocalConverter::convert_address(&self.worker_addr)
+    }
+}
+
+impl Display for UdpOutletStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "UDP Outlet at {} is sending to {}",
+            color_primary(
+                self.worker_route()
+                    .map_err(|_| std::fmt::Error)?
+                    .to_string()
+            ),
+            color_primary(self.to.to_string()),
+        )
+    }
+}
+
+impl Output for UdpOutletStatus {
+    fn item(&self) -> Result<String, ApiError> {
+        Ok(self.padded_display())
+    }
+}
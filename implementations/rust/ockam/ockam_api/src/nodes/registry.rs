@@ -11,7 +11,11 @@ use ockam_multiaddr::MultiAddr;
 use ockam_node::compat::asynchronous::Mutex as AsyncMutex;
 use ockam_transport_core::HostnamePort;
 
+use crate::nodes::connection::Connection;
+use crate::nodes::models::portal::{OutletLoadBalancing, OutletTargetStatus};
 use crate::session::session::Session;
+use ockam::tcp::TcpOutletTargets;
+use ockam::udp::UdpInlet;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::sync::Arc;
@@ -158,6 +162,10 @@ pub struct OutletInfo {
     pub(crate) to: HostnamePort,
     pub(crate) worker_addr: Address,
     pub(crate) privileged: bool,
+    /// Set when the outlet distributes its connections between several targets
+    pub(crate) targets: Option<TcpOutletTargets>,
+    /// The settings used to create the targets of a load balanced outlet
+    pub(crate) load_balancing: Option<OutletLoadBalancing>,
 }
 
 impl OutletInfo {
@@ -170,8 +178,45 @@ impl OutletInfo {
             to,
             worker_addr,
             privileged,
+            targets: None,
+            load_balancing: None,
         }
     }
+
+    pub(crate) fn with_targets(mut self, targets: Option<TcpOutletTargets>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub(crate) fn with_load_balancing(
+        mut self,
+        load_balancing: Option<OutletLoadBalancing>,
+    ) -> Self {
+        self.load_balancing = load_balancing;
+        self
+    }
+
+    pub(crate) fn target_statuses(&self) -> Vec<OutletTargetStatus> {
+        self.targets
+            .as_ref()
+            .map(|targets| targets.statuses().into_iter().map(Into::into).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct UdpInletInfo {
+    pub(crate) bind_addr: String,
+    pub(crate) outlet_addr: MultiAddr,
+    pub(crate) outlet_route: Route,
+    pub(crate) inlet: UdpInlet,
+    pub(crate) connection: Connection,
+}
+
+#[derive(Clone)]
+pub(crate) struct UdpOutletInfo {
+    pub(crate) to: HostnamePort,
+    pub(crate) worker_addr: Address,
 }
 
 #[derive(Clone)]
@@ -192,6 +237,8 @@ pub(crate) struct Registry {
     pub(crate) relays: RegistryOf<String, RegistryRelayInfo>,
     pub(crate) inlets: RegistryOf<String, InletInfo>,
     pub(crate) outlets: RegistryOf<Address, OutletInfo>,
+    pub(crate) udp_inlets: RegistryOf<String, UdpInletInfo>,
+    pub(crate) udp_outlets: RegistryOf<Address, UdpOutletInfo>,
     pub(crate) influxdb_services: RegistryOf<Address, ()>, // TODO: what should we persist here?
 }
 
@@ -19,6 +19,7 @@ mod secure_channel;
 pub mod tcp_inlets;
 pub mod tcp_outlets;
 mod transport;
+pub mod udp_portals;
 pub mod workers;
 
 mod certificate_provider;
@@ -16,9 +16,14 @@ impl DefaultAddress {
     pub const RENDEZVOUS_SERVICE: &'static str = "rendezvous";
     pub const DIRECT_AUTHENTICATOR: &'static str = "direct_authenticator";
     pub const CREDENTIAL_ISSUER: &'static str = "credential_issuer";
+    pub const REVOCATION_LIST: &'static str = "revocation_list";
     pub const ENROLLMENT_TOKEN_ISSUER: &'static str = "enrollment_token_issuer";
     pub const ENROLLMENT_TOKEN_ACCEPTOR: &'static str = "enrollment_token_acceptor";
     pub const OKTA_IDENTITY_PROVIDER: &'static str = "okta";
+    pub const JWT_AUTHENTICATOR: &'static str = "jwt_authenticator";
+    pub const KUBERNETES_AUTHENTICATOR: &'static str = "kubernetes_authenticator";
+    pub const SPIFFE_AUTHENTICATOR: &'static str = "spiffe_authenticator";
+    pub const AUTHORITY_HEALTH: &'static str = "authority_health";
     pub const KAFKA_OUTLET: &'static str = "kafka_outlet";
     pub const KAFKA_INLET: &'static str = "kafka_inlet";
     pub const LEASE_MANAGER: &'static str = "lease_manager";
@@ -39,9 +44,14 @@ impl DefaultAddress {
             | Self::KEY_EXCHANGER_LISTENER
             | Self::DIRECT_AUTHENTICATOR
             | Self::CREDENTIAL_ISSUER
+            | Self::REVOCATION_LIST
             | Self::ENROLLMENT_TOKEN_ISSUER
             | Self::ENROLLMENT_TOKEN_ACCEPTOR
             | Self::OKTA_IDENTITY_PROVIDER
+            | Self::JWT_AUTHENTICATOR
+            | Self::KUBERNETES_AUTHENTICATOR
+            | Self::SPIFFE_AUTHENTICATOR
+            | Self::AUTHORITY_HEALTH
             | Self::KAFKA_INLET
             | Self::KAFKA_OUTLET
             | Self::LEASE_MANAGER)
@@ -59,9 +69,14 @@ impl DefaultAddress {
             Self::KEY_EXCHANGER_LISTENER,
             Self::DIRECT_AUTHENTICATOR,
             Self::CREDENTIAL_ISSUER,
+            Self::REVOCATION_LIST,
             Self::ENROLLMENT_TOKEN_ISSUER,
             Self::ENROLLMENT_TOKEN_ACCEPTOR,
             Self::OKTA_IDENTITY_PROVIDER,
+            Self::JWT_AUTHENTICATOR,
+            Self::KUBERNETES_AUTHENTICATOR,
+            Self::SPIFFE_AUTHENTICATOR,
+            Self::AUTHORITY_HEALTH,
             Self::KAFKA_INLET,
             Self::KAFKA_OUTLET,
             Self::LEASE_MANAGER,
@@ -14,6 +14,7 @@ use crate::nodes::models::transport::Port;
 use crate::nodes::NodeManager;
 use crate::{ApiError, HttpError, Result};
 use ockam_core::{async_trait, Address, Processor};
+use ockam_node::metrics::{NodeMetrics, OPENMETRICS_CONTENT_TYPE};
 use ockam_node::{Context, ProcessorBuilder};
 use serde::Serialize;
 use tokio::net::TcpListener;
@@ -22,6 +23,7 @@ use tokio::net::TcpListener;
 ///
 /// This server is complementary to the node's API and is intended to be used
 /// for health checks and monitoring of the node's status.
+/// The node metrics are exposed in the OpenMetrics text format on `/metrics`.
 ///
 /// It is not intended to be a full-fledged HTTP version of the node's API.
 pub struct HttpServer;
@@ -42,8 +44,11 @@ impl HttpServer {
             .cli_state
             .set_node_http_server_addr(&node_manager.node_name, &addr.into())
             .await?;
+        // the metrics are only collected when they can be exported
+        context.metrics().enable();
         let processor = HttpServerProcessor {
             node_manager: Arc::downgrade(&node_manager),
+            node_metrics: context.metrics().clone(),
             tcp_listener: Arc::new(listener),
         };
         ProcessorBuilder::new(processor)
@@ -56,12 +61,14 @@ impl HttpServer {
 
 struct HttpServerProcessor {
     node_manager: Weak<NodeManager>,
+    node_metrics: Arc<NodeMetrics>,
     tcp_listener: Arc<TcpListener>,
 }
 
 impl HttpServerProcessor {
     async fn handle_request(
         node_manager: Weak<NodeManager>,
+        node_metrics: Arc<NodeMetrics>,
         req: Request<hyper::body::Incoming>,
     ) -> Result<Response<BoxBody<Bytes, Infallible>>> {
         debug!("Processing request: {req:?}");
@@ -82,6 +89,10 @@ impl HttpServerProcessor {
                 };
                 Self::json_response(node_resources)
             }
+            (&Method::GET, ["metrics"]) => Ok(Response::builder()
+                .header(hyper::header::CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE)
+                .body(Full::new(Bytes::from(node_metrics.encode())).boxed())
+                .map_err(HttpError::from)?),
             _ => {
                 warn!("Request received for a non supported endpoint: {req:?}");
                 Ok(Response::builder()
@@ -121,7 +132,8 @@ impl Processor for HttpServerProcessor {
             let io = TokioIo::new(stream);
             let service = service_fn(|req| {
                 let node_manager = self.node_manager.clone();
-                Self::handle_request(node_manager, req)
+                let node_metrics = self.node_metrics.clone();
+                Self::handle_request(node_manager, node_metrics, req)
             });
             if let Err(err) = http1::Builder::new().serve_connection(io, service).await {
                 error!("Error serving connection: {err:?}");
@@ -157,7 +157,13 @@ impl InMemoryNode {
             .into_diagnostic()?;
 
         let trust_options = cli_state
-            .retrieve_trust_options(&project_name, &authority_identity, &authority_route, &None)
+            .retrieve_trust_options(
+                &project_name,
+                &authority_identity,
+                &authority_route,
+                &[],
+                &None,
+            )
             .await
             .into_diagnostic()?;
 
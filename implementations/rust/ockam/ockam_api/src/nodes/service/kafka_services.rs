@@ -3,10 +3,12 @@ use crate::error::ApiError;
 use crate::kafka::key_exchange::controller::KafkaKeyExchangeControllerImpl;
 use crate::kafka::protocol_aware::inlet::KafkaInletInterceptorFactory;
 use crate::kafka::protocol_aware::outlet::KafkaOutletInterceptorFactory;
+use crate::kafka::topic_encryption::TopicEncryptionPolicy;
 use crate::kafka::KafkaOutletController;
 use crate::kafka::{
     kafka_policy_expression, ConsumerPublishing, ConsumerResolution, KafkaInletController,
-    KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
+    KafkaRecordFormat, KafkaTopicEncryptionRule, KAFKA_OUTLET_BOOTSTRAP_ADDRESS,
+    KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
 };
 use crate::nodes::models::portal::OutletAccessControl;
 use crate::nodes::models::services::{
@@ -48,6 +50,8 @@ impl NodeManagerWorker {
                 request.project_route(),
                 request.encrypt_content(),
                 request.encrypted_fields(),
+                request.record_format(),
+                request.topic_encryption(),
                 request.consumer_resolution(),
                 request.consumer_publishing(),
                 request.inlet_policy_expression(),
@@ -121,12 +125,23 @@ impl InMemoryNode {
         outlet_node_multiaddr: MultiAddr,
         encrypt_content: bool,
         encrypted_fields: Vec<String>,
+        record_format: KafkaRecordFormat,
+        topic_encryption: Vec<KafkaTopicEncryptionRule>,
         consumer_resolution: ConsumerResolution,
         consumer_publishing: ConsumerPublishing,
         inlet_policy_expression: Option<PolicyExpression>,
         consumer_policy_expression: Option<PolicyExpression>,
         producer_policy_expression: Option<PolicyExpression>,
     ) -> Result<()> {
+        // the encryption of each topic is checked here to report configuration errors
+        // when the inlet is created
+        let encryption = TopicEncryptionPolicy::from_rules(
+            encrypt_content,
+            encrypted_fields,
+            &record_format,
+            &topic_encryption,
+        )?;
+
         let consumer_policy_access_control = self
             .policy_access_control(
                 self.project_authority().clone(),
@@ -220,6 +235,7 @@ impl InMemoryNode {
             false,
             false,
             None,
+            None,
             false,
             false,
         )
@@ -240,8 +256,7 @@ impl InMemoryNode {
             Arc::new(KafkaInletInterceptorFactory::new(
                 secure_channel_controller,
                 inlet_controller,
-                encrypt_content,
-                encrypted_fields,
+                encryption,
             )),
             Arc::new(policy_access_control.create_incoming()),
             Arc::new(policy_access_control.create_outgoing(context)?),
@@ -21,18 +21,24 @@ use crate::{ApiError, CliState, DefaultAddress};
 use miette::IntoDiagnostic;
 use ockam::identity::{
     CachedCredentialRetrieverCreator, CredentialRetrieverCreator, Identifier,
-    MemoryCredentialRetrieverCreator, RemoteCredentialRetrieverCreator, SecureChannelListener,
-    SecureChannels,
+    MemoryCredentialRetrieverCreator, RemoteCredentialRetrieverCreator, RevocationListRefresher,
+    SecureChannelListener, SecureChannels,
 };
 use ockam::tcp::TcpTransport;
 use ockam::udp::{
     UdpPunctureNegotiationListener, UdpPunctureNegotiationListenerOptions, UdpTransport,
 };
-use ockam::{RelayService, RelayServiceOptions};
+use ockam::{
+    RelayAuditOptions, RelayAuditSqlxDatabase, RelayMessagesSqlxDatabase, RelayNamePattern,
+    RelayService, RelayServiceOptions, StoreAndForwardOptions,
+};
 use ockam_abac::expr::str;
 use ockam_abac::{
-    Action, Env, Policies, PolicyAccessControl, PolicyExpression, Resource, ResourceType, Resources,
+    Action, Env, Policies, PolicyAccessControl, PolicyExpression, Resource, ResourceName,
+    ResourceType, Resources,
 };
+use ockam_core::env::{get_env, get_env_with_default};
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{
     route, AllowAll, CachedIncomingAccessControl, CachedOutgoingAccessControl,
@@ -40,10 +46,27 @@ use ockam_core::{
 };
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// If set to true, the relays of the node store their messages in the node database
+/// until the registered node acknowledges them
+pub const OCKAM_RELAY_STORE_AND_FORWARD: &str = "OCKAM_RELAY_STORE_AND_FORWARD";
+
+/// Policy expression evaluated against the credential attributes of the identities
+/// registering a relay, for example `(= subject.relay relay.name)`
+pub const OCKAM_RELAY_REGISTRATION_POLICY: &str = "OCKAM_RELAY_REGISTRATION_POLICY";
+
+/// Policy expressions governing the registration of reserved relay names, as a JSON object
+/// mapping relay name patterns to policy expressions, for example `{"prod-*": "subject.prod"}`
+pub const OCKAM_RELAY_RESERVATIONS: &str = "OCKAM_RELAY_RESERVATIONS";
+
+/// If set to true, the relay registrations are recorded in the node database
+pub const OCKAM_RELAY_AUDIT: &str = "OCKAM_RELAY_AUDIT";
+
 /// Node manager provides high-level operations to
 ///  - send messages
 ///  - create secure channels, inlet, outlet
@@ -88,6 +111,25 @@ impl NodeManager {
 
         let secure_channels = cli_state.secure_channels(&node_name).await?;
 
+        // the revocation lists are received from the project authority, or one of its replicas,
+        // whenever a route to it is known
+        if let Some(project_authority) = &trust_options.project_authority {
+            if trust_options.project_authority_routes.is_empty() {
+                warn!(authority=%project_authority,
+                    "no route to the project authority, revoked credentials won't be rejected");
+            } else {
+                debug!("start retrieving the project revocation list");
+                RevocationListRefresher::new(
+                    Arc::new(transport_options.tcp.transport.clone()),
+                    secure_channels.clone(),
+                    project_authority.clone(),
+                    trust_options.project_authority_routes.clone(),
+                    node_identifier.clone(),
+                )
+                .start(ctx)?;
+            }
+        }
+
         let project_member_credential_retriever_creator: Option<
             Arc<dyn CredentialRetrieverCreator>,
         > = match trust_options.project_member_credential_retriever_options {
@@ -243,6 +285,28 @@ impl NodeManager {
                 .relay_as_consumer(api_flow_control_id);
         }
 
+        if get_env_with_default(OCKAM_RELAY_STORE_AND_FORWARD, false)? {
+            options = options.store_and_forward(StoreAndForwardOptions::new(
+                RelayMessagesSqlxDatabase::make_repository(self.cli_state.database()),
+            ));
+        }
+
+        if let Some(policy) = get_env::<String>(OCKAM_RELAY_REGISTRATION_POLICY)? {
+            options = options.registration_policy(PolicyExpression::from_str(&policy)?);
+        }
+
+        if let Some(reservations) = get_env::<String>(OCKAM_RELAY_RESERVATIONS)? {
+            for (pattern, policy) in parse_relay_reservations(&reservations)? {
+                options = options.reserve(pattern, policy);
+            }
+        }
+
+        if get_env_with_default(OCKAM_RELAY_AUDIT, false)? {
+            options = options.audit(RelayAuditOptions::new(
+                RelayAuditSqlxDatabase::make_repository(self.cli_state.database()),
+            ));
+        }
+
         let options = if let Some(authority) = &self.project_authority {
             let policy_access_control = self
                 .policy_access_control(
@@ -400,6 +464,8 @@ impl NodeManager {
                     None,
                     info.privileged,
                 )
+                .with_targets(info.target_statuses())
+                .with_load_balancing(info.load_balancing.clone())
             })
             .collect()
     }
@@ -551,20 +617,7 @@ impl NodeManager {
             let policy_access_control = self
                 .policy_access_control(authority, resource, action, expression)
                 .await?;
-
-            let incoming_ac = policy_access_control.create_incoming();
-            let outgoing_ac = policy_access_control.create_outgoing(ctx)?;
-
-            cfg_if::cfg_if! {
-                if #[cfg(feature = "std")] {
-                    let incoming_ac = CachedIncomingAccessControl::new(Box::new(incoming_ac));
-                    let outgoing_ac = CachedOutgoingAccessControl::new(Box::new(outgoing_ac));
-
-                    Ok((Arc::new(incoming_ac), Arc::new(outgoing_ac)))
-                } else {
-                    Ok((Arc::new(incoming_ac), Arc::new(outgoing_ac)))
-                }
-            }
+            Self::incoming_and_outgoing_access_control(ctx, &policy_access_control).await
         } else {
             // If no expression is given, assume it's AllowAll, but only if no authority
             // was set neither. Why: not sure, but to behave as it was previously if there
@@ -580,6 +633,34 @@ impl NodeManager {
         }
     }
 
+    /// Create the incoming and outgoing access controls evaluating a policy.
+    /// Their decisions are cached, unless the policy uses the `rate-limit` function
+    pub(crate) async fn incoming_and_outgoing_access_control(
+        ctx: &Context,
+        policy_access_control: &PolicyAccessControl,
+    ) -> ockam_core::Result<(
+        Arc<dyn IncomingAccessControl>,
+        Arc<dyn OutgoingAccessControl>,
+    )> {
+        let incoming_ac = policy_access_control.create_incoming();
+        let outgoing_ac = policy_access_control.create_outgoing(ctx)?;
+
+        if policy_access_control.uses_rate_limits().await? {
+            return Ok((Arc::new(incoming_ac), Arc::new(outgoing_ac)));
+        }
+
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "std")] {
+                let incoming_ac = CachedIncomingAccessControl::new(Box::new(incoming_ac));
+                let outgoing_ac = CachedOutgoingAccessControl::new(Box::new(outgoing_ac));
+
+                Ok((Arc::new(incoming_ac), Arc::new(outgoing_ac)))
+            } else {
+                Ok((Arc::new(incoming_ac), Arc::new(outgoing_ac)))
+            }
+        }
+    }
+
     pub fn policies(&self) -> Policies {
         self.cli_state.policies(&self.node_name)
     }
@@ -595,13 +676,7 @@ impl NodeManager {
         action: Action,
         expression: Option<PolicyExpression>,
     ) -> ockam_core::Result<PolicyAccessControl> {
-        let resource_name_str = resource.resource_name.as_str();
-        let action_str = action.as_ref();
-
-        // Populate environment with known attributes:
-        let mut env = Env::new();
-        env.put("resource.id", str(resource_name_str));
-        env.put("action.id", str(action_str));
+        let env = Self::policy_environment(Some(&resource.resource_name), &action);
 
         // Store policy for the given resource and action
         let policies = self.policies();
@@ -625,6 +700,41 @@ impl NodeManager {
             authority,
         ))
     }
+
+    /// Populate the environment of a policy with the known resource and action attributes.
+    /// This environment is used both to control the access to a resource and to evaluate
+    /// its policy with `ockam policy evaluate`
+    pub(crate) fn policy_environment(resource_name: Option<&ResourceName>, action: &Action) -> Env {
+        let mut env = Env::new();
+        if let Some(resource_name) = resource_name {
+            env.put("resource.id", str(resource_name.as_str()));
+        }
+        env.put("action.id", str(action.as_ref()));
+        env
+    }
+}
+
+/// Parse the relay name reservations, for example `{"prod-*": "subject.prod"}`
+fn parse_relay_reservations(
+    reservations: &str,
+) -> ockam_core::Result<Vec<(RelayNamePattern, PolicyExpression)>> {
+    let reservations: BTreeMap<String, String> =
+        serde_json::from_str(reservations).map_err(|e| {
+            ockam_core::Error::new(
+                Origin::Node,
+                Kind::Invalid,
+                format!("invalid {OCKAM_RELAY_RESERVATIONS} value: {e}"),
+            )
+        })?;
+    reservations
+        .iter()
+        .map(|(pattern, policy)| -> ockam_core::Result<_> {
+            Ok((
+                RelayNamePattern::from_str(pattern)?,
+                PolicyExpression::from_str(policy)?,
+            ))
+        })
+        .collect()
 }
 
 #[derive(Debug)]
@@ -707,3 +817,33 @@ impl NodeManagerTransportOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_relay_reservations() {
+        let reservations = parse_relay_reservations(
+            r#"{"prod-*": "(= subject.env \"prod\")", "db": "subject.db"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            reservations,
+            vec![
+                (
+                    RelayNamePattern::Exact("db".to_string()),
+                    PolicyExpression::from_str("subject.db").unwrap()
+                ),
+                (
+                    RelayNamePattern::Prefix("prod-".to_string()),
+                    PolicyExpression::from_str("(= subject.env \"prod\")").unwrap()
+                ),
+            ]
+        );
+
+        assert!(parse_relay_reservations("not json").is_err());
+        assert!(parse_relay_reservations(r#"{"pr*d": "subject.db"}"#).is_err());
+        assert!(parse_relay_reservations(r#"{"db": "(invalid"}"#).is_err());
+    }
+}
@@ -1,10 +1,15 @@
-use ockam_abac::{Action, PolicyExpression};
+use ockam::identity::Identifier;
+use ockam_abac::{Abac, Action, PolicyExplanation, PolicyExpression, RateLimits};
 use ockam_core::api::{Error, Request, Response};
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, Result};
 use ockam_node::Context;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
-use crate::nodes::models::policies::{PoliciesList, Policy, ResourceTypeOrName, SetPolicyRequest};
+use crate::nodes::models::policies::{
+    EvaluatePolicyRequest, PoliciesList, Policy, ResourceTypeOrName, SetPolicyRequest,
+};
 use crate::nodes::{BackgroundNodeClient, NodeManagerWorker};
 
 use super::NodeManager;
@@ -57,6 +62,33 @@ impl NodeManagerWorker {
             Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
         }
     }
+
+    pub(super) async fn evaluate_policy(
+        &self,
+        action: &str,
+        request: EvaluatePolicyRequest,
+    ) -> Result<Response<PolicyExplanation>, Response<Error>> {
+        match self
+            .node_manager
+            .evaluate_policy(
+                request.resource,
+                action,
+                request.expression,
+                &request.subject,
+                &request.attributes,
+            )
+            .await
+        {
+            Ok(explanation) => Ok(Response::ok().body(explanation)),
+            Err(e) => match e.code().kind {
+                Kind::NotFound => Err(Response::not_found_no_request(&e.to_string())),
+                Kind::Misuse | Kind::Invalid => {
+                    Err(Response::bad_request_no_request(&e.to_string()))
+                }
+                _ => Err(Response::internal_error_no_request(&e.to_string())),
+            },
+        }
+    }
 }
 
 impl NodeManager {
@@ -146,6 +178,89 @@ impl NodeManager {
     }
 }
 
+impl NodeManager {
+    /// Evaluate a policy for a subject and explain how the access decision was made.
+    ///
+    /// The policy is either the expression given as an argument or the policy set on the resource
+    /// for the given action. In the latter case, if there is no policy for the resource name, the
+    /// policy of its resource type is used, as is done when the resource is accessed.
+    pub async fn evaluate_policy(
+        &self,
+        resource: Option<ResourceTypeOrName>,
+        action: &str,
+        expression: Option<PolicyExpression>,
+        subject: &Identifier,
+        attributes: &BTreeMap<String, String>,
+    ) -> Result<PolicyExplanation> {
+        let action = Action::from_str(action)?;
+
+        // the environment is populated as it is for the access control of a resource,
+        // where the resource id is only known for a resource name, not for a resource type
+        let resource_name = match &resource {
+            Some(ResourceTypeOrName::Name(resource_name)) => Some(resource_name),
+            _ => None,
+        };
+        let env = Self::policy_environment(resource_name, &action);
+
+        let expression = match (expression, resource) {
+            (Some(expression), _) => expression.into(),
+            (None, Some(ResourceTypeOrName::Name(resource_name))) => {
+                let expression = match self.resources().get_resource(&resource_name).await? {
+                    Some(resource) => {
+                        self.policies()
+                            .get_expression_for_resource(&resource, &action)
+                            .await?
+                    }
+                    None => self
+                        .policies()
+                        .get_policy_for_resource_name(&resource_name, &action)
+                        .await?
+                        .map(|p| p.expression),
+                };
+                expression.ok_or_else(|| {
+                    ockam_core::Error::new(
+                        Origin::Api,
+                        Kind::NotFound,
+                        format!("No policy found for resource '{resource_name}' and action '{action}'"),
+                    )
+                })?
+            }
+            (None, Some(ResourceTypeOrName::Type(resource_type))) => self
+                .policies()
+                .get_policy_for_resource_type(&resource_type, &action)
+                .await?
+                .map(|p| p.expression)
+                .ok_or_else(|| {
+                    ockam_core::Error::new(
+                        Origin::Api,
+                        Kind::NotFound,
+                        format!("No policy found for resource type '{resource_type}' and action '{action}'"),
+                    )
+                })?,
+            (None, None) => {
+                return Err(ockam_core::Error::new(
+                    Origin::Api,
+                    Kind::Misuse,
+                    "A resource or a policy expression must be provided",
+                ))
+            }
+        };
+
+        // the explanation is not tied to a specific access control,
+        // so the rate limits are checked as if no event was recorded
+        Abac::explain_static(
+            self.cli_state.identities_attributes(&self.node_name),
+            &env,
+            &RateLimits::default(),
+            self.project_authority.as_ref(),
+            subject,
+            attributes,
+            &expression,
+        )
+        .await
+    }
+}
+
 pub fn policy_path(a: &Action) -> String {
     format!("/policy/{a}")
 }
@@ -179,6 +294,13 @@ pub trait Policies {
         resource: &ResourceTypeOrName,
         action: &Action,
     ) -> miette::Result<()>;
+
+    async fn evaluate_policy(
+        &self,
+        ctx: &Context,
+        action: &Action,
+        request: &EvaluatePolicyRequest,
+    ) -> miette::Result<PolicyExplanation>;
 }
 
 #[async_trait]
@@ -225,4 +347,14 @@ impl Policies for BackgroundNodeClient {
         self.tell(ctx, request).await?;
         Ok(())
     }
+
+    async fn evaluate_policy(
+        &self,
+        ctx: &Context,
+        action: &Action,
+        request: &EvaluatePolicyRequest,
+    ) -> miette::Result<PolicyExplanation> {
+        let request = Request::post(format!("{}/evaluate", policy_path(action))).body(request);
+        self.ask(ctx, request).await
+    }
 }
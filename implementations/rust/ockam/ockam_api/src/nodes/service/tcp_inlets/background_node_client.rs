@@ -8,7 +8,7 @@ use ockam_node::Context;
 use ockam_transport_core::HostnamePort;
 use std::time::Duration;
 
-use crate::nodes::models::portal::{CreateInlet, InletStatus};
+use crate::nodes::models::portal::{CreateInlet, InletStatus, InletTlsCertificateProvider};
 use crate::nodes::service::tcp_inlets::Inlets;
 use crate::nodes::BackgroundNodeClient;
 
@@ -25,7 +25,8 @@ pub fn create_inlet_payload(
     enable_udp_puncture: bool,
     disable_tcp_fallback: bool,
     privileged: bool,
-    tls_certificate_provider: &Option<MultiAddr>,
+    tls_certificate_provider: &Option<InletTlsCertificateProvider>,
+    tls_client_ca_certificates: &Option<String>,
     skip_handshake: bool,
     enable_nagle: bool,
 ) -> CreateInlet {
@@ -65,6 +66,9 @@ pub fn create_inlet_payload(
     if let Some(tls_provider) = tls_certificate_provider {
         payload.set_tls_certificate_provider(tls_provider.clone())
     }
+    if let Some(ca_certificates_pem) = tls_client_ca_certificates {
+        payload.set_tls_client_ca_certificates(ca_certificates_pem.clone())
+    }
     payload.set_wait_ms(wait_for_outlet_timeout.as_millis() as u64);
     payload
 }
@@ -85,7 +89,8 @@ impl Inlets for BackgroundNodeClient {
         enable_udp_puncture: bool,
         disable_tcp_fallback: bool,
         privileged: bool,
-        tls_certificate_provider: &Option<MultiAddr>,
+        tls_certificate_provider: &Option<InletTlsCertificateProvider>,
+        tls_client_ca_certificates: &Option<String>,
         skip_handshake: bool,
         enable_nagle: bool,
     ) -> miette::Result<Reply<InletStatus>> {
@@ -103,6 +108,7 @@ impl Inlets for BackgroundNodeClient {
                 disable_tcp_fallback,
                 privileged,
                 tls_certificate_provider,
+                tls_client_ca_certificates,
                 skip_handshake,
                 enable_nagle,
             );
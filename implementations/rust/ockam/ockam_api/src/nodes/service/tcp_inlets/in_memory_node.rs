@@ -7,7 +7,7 @@ use ockam_node::Context;
 use ockam_transport_core::HostnamePort;
 use std::time::Duration;
 
-use crate::nodes::models::portal::InletStatus;
+use crate::nodes::models::portal::{InletStatus, InletTlsCertificateProvider};
 use crate::nodes::InMemoryNode;
 
 impl InMemoryNode {
@@ -29,7 +29,8 @@ impl InMemoryNode {
         enable_udp_puncture: bool,
         disable_tcp_fallback: bool,
         privileged: bool,
-        tls_certificate_provider: Option<MultiAddr>,
+        tls_certificate_provider: Option<InletTlsCertificateProvider>,
+        tls_client_ca_certificates: Option<String>,
         skip_handshake: bool,
         enable_nagle: bool,
     ) -> Result<InletStatus> {
@@ -50,6 +51,7 @@ impl InMemoryNode {
                 disable_tcp_fallback,
                 privileged,
                 tls_certificate_provider,
+                tls_client_ca_certificates,
                 skip_handshake,
                 enable_nagle,
             )
@@ -7,7 +7,7 @@ use ockam_node::Context;
 use ockam_transport_core::HostnamePort;
 use std::time::Duration;
 
-use crate::nodes::models::portal::InletStatus;
+use crate::nodes::models::portal::{InletStatus, InletTlsCertificateProvider};
 
 #[async_trait]
 pub trait Inlets {
@@ -26,7 +26,8 @@ pub trait Inlets {
         enable_udp_puncture: bool,
         disable_tcp_fallback: bool,
         privileged: bool,
-        tls_certificate_provider: &Option<MultiAddr>,
+        tls_certificate_provider: &Option<InletTlsCertificateProvider>,
+        tls_client_ca_certificates: &Option<String>,
         skip_handshake: bool,
         enable_nagle: bool,
     ) -> miette::Result<Reply<InletStatus>>;
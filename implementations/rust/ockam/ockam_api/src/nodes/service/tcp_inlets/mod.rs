@@ -4,8 +4,10 @@ mod inlets_trait;
 mod node_manager;
 mod node_manager_worker;
 mod session_replacer;
+mod tls_client_access_control;
 
 pub use inlets_trait::*;
 use session_replacer::*;
+pub use tls_client_access_control::*;
 
 pub use background_node_client::create_inlet_payload;
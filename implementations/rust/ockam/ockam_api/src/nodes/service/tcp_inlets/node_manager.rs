@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::acme::{resolve_dns01_hook, AcmeCertificateConfig, AcmeChallenge};
 use crate::address::get_free_address_for;
 use ockam::identity::Identifier;
 use ockam::Result;
@@ -12,7 +13,7 @@ use ockam_node::compat::asynchronous::Mutex;
 use ockam_node::Context;
 use ockam_transport_core::HostnamePort;
 
-use crate::nodes::models::portal::InletStatus;
+use crate::nodes::models::portal::{InletStatus, InletTlsCertificateProvider};
 use crate::nodes::registry::InletInfo;
 use crate::nodes::service::tcp_inlets::InletSessionReplacer;
 use crate::nodes::NodeManager;
@@ -40,7 +41,8 @@ impl NodeManager {
         // TODO: Introduce mode enum
         disable_tcp_fallback: bool,
         privileged: bool,
-        tls_certificate_provider: Option<MultiAddr>,
+        tls_certificate_provider: Option<InletTlsCertificateProvider>,
+        tls_client_ca_certificates: Option<String>,
         skip_handshake: bool,
         enable_nagle: bool,
     ) -> Result<InletStatus> {
@@ -57,6 +59,23 @@ impl NodeManager {
             "creating inlet"
         }
 
+        if tls_client_ca_certificates.is_some() && tls_certificate_provider.is_none() {
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Invalid,
+                "TLS client authentication requires a TLS certificate provider",
+            ));
+        }
+
+        // the DNS-01 hook is checked before the inlet is created
+        if let Some(InletTlsCertificateProvider::Acme(AcmeCertificateConfig {
+            challenge: AcmeChallenge::Dns01 { hook },
+            ..
+        })) = &tls_certificate_provider
+        {
+            resolve_dns01_hook(hook.as_deref())?;
+        }
+
         let udp_transport = if enable_udp_puncture {
             Some(self.udp_transport.clone().ok_or_else(|| {
                 ockam_core::Error::new(
@@ -124,6 +143,7 @@ impl NodeManager {
             secure_channel_identifier,
             disable_tcp_fallback,
             tls_certificate_provider,
+            tls_client_ca_certificates,
             inlet: None,
             connection: None,
             main_route: None,
@@ -2,7 +2,7 @@ use ockam::{route, Result};
 use ockam_core::api::{Error, Response};
 use ockam_node::Context;
 
-use crate::nodes::models::portal::{CreateInlet, InletStatus};
+use crate::nodes::models::portal::{CreateInlet, InletStatus, InletTlsCertificateProvider};
 use crate::nodes::NodeManagerWorker;
 
 impl NodeManagerWorker {
@@ -32,7 +32,13 @@ impl NodeManagerWorker {
             tls_certificate_provider,
             skip_handshake,
             enable_nagle,
+            acme_certificate_provider,
+            tls_client_ca_certificates,
         } = create_inlet;
+        let tls_certificate_provider = InletTlsCertificateProvider::from_request_fields(
+            tls_certificate_provider,
+            acme_certificate_provider,
+        );
         match self
             .node_manager
             .create_inlet(
@@ -51,6 +57,7 @@ impl NodeManagerWorker {
                 disable_tcp_fallback,
                 privileged,
                 tls_certificate_provider,
+                tls_client_ca_certificates,
                 skip_handshake,
                 enable_nagle,
             )
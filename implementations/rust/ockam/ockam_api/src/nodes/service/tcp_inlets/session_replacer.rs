@@ -1,4 +1,4 @@
-use ockam_transport_tcp::new_certificate_provider_cache;
+use ockam_transport_tcp::{new_certificate_provider_cache, TlsClientAuthentication};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
@@ -17,10 +17,13 @@ use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
 use ockam_transport_tcp::TcpInlet;
 
+use crate::acme::AcmeCertificateProvider;
 use crate::colors::color_primary;
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
+use crate::nodes::models::portal::InletTlsCertificateProvider;
 use crate::nodes::service::certificate_provider::ProjectCertificateProvider;
+use crate::nodes::service::tcp_inlets::TlsClientPolicyAccessControlFactory;
 use crate::nodes::service::SecureChannelType;
 use crate::nodes::NodeManager;
 use crate::session::replacer::{
@@ -43,7 +46,8 @@ pub(super) struct InletSessionReplacer {
     pub(super) policy_expression: Option<PolicyExpression>,
     pub(super) secure_channel_identifier: Option<Identifier>,
     pub(super) disable_tcp_fallback: bool,
-    pub(super) tls_certificate_provider: Option<MultiAddr>,
+    pub(super) tls_certificate_provider: Option<InletTlsCertificateProvider>,
+    pub(super) tls_client_ca_certificates: Option<String>,
 
     // current status
     pub(super) inlet: Option<Arc<TcpInlet>>,
@@ -64,13 +68,8 @@ impl InletSessionReplacer {
         self.udp_transport.is_some()
     }
 
-    async fn access_control(
-        &self,
-        node_manager: &NodeManager,
-    ) -> Result<(
-        Arc<dyn IncomingAccessControl>,
-        Arc<dyn OutgoingAccessControl>,
-    )> {
+    /// Return the authority used to evaluate the inlet policy
+    async fn authority(&self, node_manager: &NodeManager) -> Result<Option<Identifier>> {
         let authority = {
             if let Some(p) = self.outlet_addr.first() {
                 if let Some(p) = p.cast::<ProjectProto>() {
@@ -95,7 +94,17 @@ impl InletSessionReplacer {
             }
         }
         .or(node_manager.project_authority());
+        Ok(authority)
+    }
 
+    async fn access_control(
+        &self,
+        node_manager: &NodeManager,
+        authority: Option<Identifier>,
+    ) -> Result<(
+        Arc<dyn IncomingAccessControl>,
+        Arc<dyn OutgoingAccessControl>,
+    )> {
         node_manager
             .access_control(
                 &self.context,
@@ -107,8 +116,43 @@ impl InletSessionReplacer {
             .await
     }
 
+    /// When TLS client certificates are required, the inlet policy is evaluated for each
+    /// connection with the attributes of its client certificate
+    async fn tls_client_authentication(
+        &self,
+        node_manager: &NodeManager,
+        authority: Option<Identifier>,
+    ) -> Result<Option<TlsClientAuthentication>> {
+        let ca_certificates_pem = match &self.tls_client_ca_certificates {
+            Some(ca_certificates_pem) => ca_certificates_pem.as_bytes().to_vec(),
+            None => return Ok(None),
+        };
+        let tls_client_authentication = TlsClientAuthentication::new(ca_certificates_pem)?;
+
+        // without any policy, all the clients with a valid certificate are accepted
+        if authority.is_none() && self.policy_expression.is_none() {
+            return Ok(Some(tls_client_authentication));
+        }
+
+        let policy_access_control = node_manager
+            .policy_access_control(
+                authority,
+                self.resource.clone(),
+                Action::HandleMessage,
+                self.policy_expression.clone(),
+            )
+            .await?;
+        Ok(Some(tls_client_authentication.with_access_control_factory(
+            Arc::new(TlsClientPolicyAccessControlFactory::new(
+                policy_access_control,
+            )),
+        )))
+    }
+
     async fn inlet_options(&self, node_manager: &NodeManager) -> Result<TcpInletOptions> {
-        let (incoming_ac, outgoing_ac) = self.access_control(node_manager).await?;
+        let authority = self.authority(node_manager).await?;
+        let (incoming_ac, outgoing_ac) =
+            self.access_control(node_manager, authority.clone()).await?;
         let options = TcpInletOptions::new()
             .with_incoming_access_control(incoming_ac)
             .with_outgoing_access_control(outgoing_ac)
@@ -121,12 +165,32 @@ impl InletSessionReplacer {
             options
         };
 
-        let options = if let Some(tls_provider) = &self.tls_certificate_provider {
-            options.with_tls_certificate_provider(new_certificate_provider_cache(Arc::new(
-                ProjectCertificateProvider::new(self.node_manager.clone(), tls_provider.clone()),
-            )))
-        } else {
-            options
+        let options = match &self.tls_certificate_provider {
+            Some(InletTlsCertificateProvider::Remote(tls_provider)) => options
+                .with_tls_certificate_provider(new_certificate_provider_cache(Arc::new(
+                    ProjectCertificateProvider::new(
+                        self.node_manager.clone(),
+                        tls_provider.clone(),
+                    ),
+                ))),
+            Some(InletTlsCertificateProvider::Acme(config)) => options
+                .with_tls_certificate_provider(new_certificate_provider_cache(Arc::new(
+                    AcmeCertificateProvider::new(
+                        config.clone(),
+                        node_manager.cli_state.acme_certificates_repository(),
+                    )?,
+                ))),
+            None => options,
+        };
+
+        let options = match self
+            .tls_client_authentication(node_manager, authority)
+            .await?
+        {
+            Some(tls_client_authentication) => {
+                options.with_tls_client_authentication(tls_client_authentication)
+            }
+            None => options,
         };
 
         Ok(options)
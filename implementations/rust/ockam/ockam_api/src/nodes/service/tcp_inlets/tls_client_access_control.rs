@@ -0,0 +1,174 @@
+use ockam::identity::Identifier;
+use ockam_abac::expr::{seq, str};
+use ockam_abac::{Env, PolicyAccessControl};
+use ockam_core::{async_trait, LocalInfoIdentifier, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TlsClientAccessControlFactory, TlsClientAccessControls, TlsClientCertificate,
+};
+
+use crate::nodes::NodeManager;
+
+/// Prefix of the TLS client certificate attributes in the environment of an inlet policy
+pub const TLS_CLIENT_KEY: &str = "tls.client";
+
+/// This factory creates the access controls of the connections of an inlet requiring
+/// TLS client certificates.
+///
+/// The inlet policy is evaluated with the attributes of the outlet identity, as for any inlet,
+/// and with the attributes of the client certificate of the connection:
+///
+///  - `tls.client.subject`, `tls.client.issuer` and `tls.client.serial_number` are strings
+///  - `tls.client.cn`, `tls.client.o` and `tls.client.ou` are lists of subject attributes
+///  - `tls.client.san.dns`, `tls.client.san.uri`, `tls.client.san.email` and `tls.client.san.ip`
+///    are lists of subject alternative names
+///
+/// For example: `(and (= subject.component "db") (member? "legacy" tls.client.ou))`.
+#[derive(Debug)]
+pub(crate) struct TlsClientPolicyAccessControlFactory {
+    policy_access_control: PolicyAccessControl,
+}
+
+impl TlsClientPolicyAccessControlFactory {
+    pub(crate) fn new(policy_access_control: PolicyAccessControl) -> Self {
+        Self {
+            policy_access_control,
+        }
+    }
+}
+
+#[async_trait]
+impl TlsClientAccessControlFactory for TlsClientPolicyAccessControlFactory {
+    async fn create(
+        &self,
+        context: &Context,
+        their_identifier: Option<LocalInfoIdentifier>,
+        client_certificate: &TlsClientCertificate,
+    ) -> Result<Option<TlsClientAccessControls>> {
+        // without a secure channel to the outlet, the policy can't be satisfied
+        let their_identifier: Identifier = match their_identifier {
+            Some(their_identifier) => their_identifier.into(),
+            None => return Ok(None),
+        };
+
+        let policy_access_control = self
+            .policy_access_control
+            .with_additional_environment(tls_client_environment(client_certificate));
+
+        // reject the connection right away instead of dropping all its messages
+        if !policy_access_control
+            .is_identity_authorized(&their_identifier)
+            .await?
+        {
+            debug!(
+                subject = %client_certificate.subject,
+                "the TLS client certificate doesn't satisfy the inlet policy"
+            );
+            return Ok(None);
+        }
+
+        NodeManager::incoming_and_outgoing_access_control(context, &policy_access_control)
+            .await
+            .map(Some)
+    }
+}
+
+/// Return the attributes of a TLS client certificate, as they can be used in a policy expression
+pub fn tls_client_environment(client_certificate: &TlsClientCertificate) -> Env {
+    let list = |values: &Vec<String>| seq(values.iter().map(str));
+
+    let mut env = Env::new();
+    env.put(
+        format!("{TLS_CLIENT_KEY}.subject"),
+        str(client_certificate.subject.as_str()),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.issuer"),
+        str(client_certificate.issuer.as_str()),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.serial_number"),
+        str(client_certificate.serial_number.as_str()),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.cn"),
+        list(&client_certificate.common_names),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.o"),
+        list(&client_certificate.organizations),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.ou"),
+        list(&client_certificate.organizational_units),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.san.dns"),
+        list(&client_certificate.dns_names),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.san.uri"),
+        list(&client_certificate.uris),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.san.email"),
+        list(&client_certificate.emails),
+    );
+    env.put(
+        format!("{TLS_CLIENT_KEY}.san.ip"),
+        list(&client_certificate.ip_addresses),
+    );
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_abac::{eval, parse, Expr};
+    use rcgen::{CertificateParams, DnType, KeyPair, SanType};
+
+    #[test]
+    fn test_tls_client_certificate_attributes_in_policy() -> Result<()> {
+        let mut params = CertificateParams::new(vec!["client.example.com".to_string()]).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "legacy-client");
+        params
+            .distinguished_name
+            .push(DnType::OrganizationName, "Acme");
+        params
+            .distinguished_name
+            .push(DnType::OrganizationalUnitName, "legacy");
+        params.subject_alt_names.push(SanType::URI(
+            "spiffe://example.com/legacy".try_into().unwrap(),
+        ));
+        params
+            .subject_alt_names
+            .push(SanType::IpAddress("10.0.0.1".parse().unwrap()));
+        let key_pair = KeyPair::generate().unwrap();
+        let certificate = params.self_signed(&key_pair).unwrap();
+
+        let client_certificate = TlsClientCertificate::from_der(certificate.der())?;
+        assert_eq!(client_certificate.common_names, vec!["legacy-client"]);
+        assert_eq!(client_certificate.organizations, vec!["Acme"]);
+        assert_eq!(client_certificate.organizational_units, vec!["legacy"]);
+        assert_eq!(client_certificate.dns_names, vec!["client.example.com"]);
+        assert_eq!(client_certificate.uris, vec!["spiffe://example.com/legacy"]);
+        assert_eq!(client_certificate.ip_addresses, vec!["10.0.0.1"]);
+
+        let env = tls_client_environment(&client_certificate);
+        let is_authorized = |expression: &str| {
+            let expression = parse(expression).unwrap().unwrap();
+            matches!(eval(&expression, &env).unwrap(), Expr::Bool(true))
+        };
+        assert!(is_authorized(
+            r#"(and (member? "legacy" tls.client.ou) (member? "client.example.com" tls.client.san.dns))"#
+        ));
+        assert!(is_authorized(
+            r#"(member? "spiffe://example.com/legacy" tls.client.san.uri)"#
+        ));
+        assert!(!is_authorized(r#"(member? "admin" tls.client.ou)"#));
+        assert!(!is_authorized(r#"(member? "Other" tls.client.o)"#));
+        Ok(())
+    }
+}
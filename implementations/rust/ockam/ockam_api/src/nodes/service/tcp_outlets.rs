@@ -1,4 +1,4 @@
-use ockam::tcp::TcpOutletOptions;
+use ockam::tcp::{TcpOutletOptions, TcpOutletTlsConfig};
 use ockam::transport::HostnamePort;
 use ockam::{Address, Result};
 use ockam_abac::{Action, PolicyExpression, Resource, ResourceType};
@@ -7,7 +7,9 @@ use ockam_core::async_trait;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_node::Context;
 
-use crate::nodes::models::portal::{CreateOutlet, OutletAccessControl, OutletStatus};
+use crate::nodes::models::portal::{
+    CreateOutlet, OutletAccessControl, OutletLoadBalancing, OutletStatus,
+};
 use crate::nodes::registry::OutletInfo;
 use crate::nodes::service::default_address::DefaultAddress;
 use crate::nodes::BackgroundNodeClient;
@@ -30,11 +32,13 @@ impl NodeManagerWorker {
             privileged,
             skip_handshake,
             enable_nagle,
+            load_balancing,
+            tls_config,
         } = create_outlet;
 
         match self
             .node_manager
-            .create_outlet(
+            .create_load_balanced_outlet(
                 ctx,
                 hostname_port,
                 tls,
@@ -44,6 +48,8 @@ impl NodeManagerWorker {
                 privileged,
                 skip_handshake,
                 enable_nagle,
+                load_balancing,
+                tls_config,
             )
             .await
         {
@@ -58,12 +64,16 @@ impl NodeManagerWorker {
     ) -> Result<Response<OutletStatus>, Response<Error>> {
         match self.node_manager.delete_outlet(worker_addr).await {
             Ok(res) => match res {
-                Some(outlet_info) => Ok(Response::ok().body(OutletStatus::new(
-                    outlet_info.to,
-                    outlet_info.worker_addr.clone(),
-                    None,
-                    outlet_info.privileged,
-                ))),
+                Some(outlet_info) => Ok(Response::ok().body(
+                    OutletStatus::new(
+                        outlet_info.to.clone(),
+                        outlet_info.worker_addr.clone(),
+                        None,
+                        outlet_info.privileged,
+                    )
+                    .with_targets(outlet_info.target_statuses())
+                    .with_load_balancing(outlet_info.load_balancing),
+                )),
                 None => Err(Response::bad_request_no_request(&format!(
                     "Outlet with address {worker_addr} not found"
                 ))),
@@ -105,11 +115,55 @@ impl NodeManager {
         privileged: bool,
         skip_handshake: bool,
         enable_nagle: bool,
+    ) -> Result<OutletStatus> {
+        self.create_load_balanced_outlet(
+            ctx,
+            to,
+            tls,
+            worker_addr,
+            reachable_from_default_secure_channel,
+            access_control,
+            privileged,
+            skip_handshake,
+            enable_nagle,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Create an outlet which distributes its connections between `to` and the additional
+    /// targets of the load balancing configuration, if there is one.
+    ///
+    /// When a TLS configuration is given, it is used to connect to the targets even if `tls` is false.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip_all)]
+    pub async fn create_load_balanced_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        tls: bool,
+        worker_addr: Option<Address>,
+        reachable_from_default_secure_channel: bool,
+        access_control: OutletAccessControl,
+        privileged: bool,
+        skip_handshake: bool,
+        enable_nagle: bool,
+        load_balancing: Option<OutletLoadBalancing>,
+        tls_config: Option<TcpOutletTlsConfig>,
     ) -> Result<OutletStatus> {
         let worker_addr = self.registry.outlets.generate_worker_addr(worker_addr);
 
         debug!(%to, address = %worker_addr, "creating outlet");
 
+        if privileged && load_balancing.is_some() {
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Invalid,
+                "Privileged outlets can't be load balanced",
+            ));
+        }
+
         // Check registry for a duplicated key
         if self.registry.outlets.contains_key(&worker_addr) {
             let message = format!("A TCP outlet with address '{worker_addr}' already exists");
@@ -143,6 +197,9 @@ impl NodeManager {
                 .with_tls(tls)
                 .set_skip_handshake(skip_handshake)
                 .set_enable_nagle(enable_nagle);
+            if let Some(tls_config) = tls_config {
+                options = options.with_tls_config(tls_config);
+            }
             if self.project_authority().is_none() {
                 for api_transport_flow_control_id in &self.api_transport_flow_control_ids {
                     options = options.as_consumer(api_transport_flow_control_id)
@@ -157,6 +214,12 @@ impl NodeManager {
                     options = options.as_consumer(&flow_control_id)
                 }
             }
+            if let Some(load_balancing) = &load_balancing {
+                options = options.with_load_balancing(load_balancing.strategy);
+                if let Some(health_check) = load_balancing.health_check.clone() {
+                    options = options.with_health_check(health_check);
+                }
+            }
 
             options
         };
@@ -167,6 +230,7 @@ impl NodeManager {
                 self.tcp_transport
                     .create_privileged_outlet(worker_addr.clone(), to.clone(), options)
                     .await
+                    .map(|_| None)
             }
             #[cfg(not(privileged_portals_support))]
             {
@@ -176,24 +240,41 @@ impl NodeManager {
                     "Privileged Portals support is not enabled",
                 ))
             }
+        } else if let Some(load_balancing) = &load_balancing {
+            let mut targets = vec![to.clone()];
+            targets.extend(load_balancing.additional_targets.clone());
+            self.tcp_transport
+                .create_load_balanced_outlet(worker_addr.clone(), targets, options)
+                .map(Some)
         } else {
             self.tcp_transport
                 .create_outlet(worker_addr.clone(), to.clone(), options)
+                .map(|_| None)
         };
 
         Ok(match res {
-            Ok(_) => {
+            Ok(targets) => {
                 // TODO: Use better way to store outlets?
-                self.registry.outlets.insert(
-                    worker_addr.clone(),
-                    OutletInfo::new(to.clone(), Some(&worker_addr), privileged),
-                );
+                let outlet_info = OutletInfo::new(to.clone(), Some(&worker_addr), privileged)
+                    .with_targets(targets)
+                    .with_load_balancing(load_balancing.clone());
+                let target_statuses = outlet_info.target_statuses();
+                self.registry
+                    .outlets
+                    .insert(worker_addr.clone(), outlet_info);
                 let outlet = self
                     .cli_state
-                    .create_tcp_outlet(&self.node_name, &to, &worker_addr, &None, privileged)
+                    .create_tcp_outlet(
+                        &self.node_name,
+                        &to,
+                        &worker_addr,
+                        &None,
+                        privileged,
+                        load_balancing,
+                    )
                     .await?;
                 info!(%to, address = %worker_addr, "outlet created");
-                outlet
+                outlet.with_targets(target_statuses)
             }
             Err(e) => {
                 warn!(at = %to, err = %e, "Failed to create TCP outlet");
@@ -234,12 +315,16 @@ impl NodeManager {
         info!(%worker_addr, "Handling request to show outlet portal");
         if let Some(outlet_to_show) = self.registry.outlets.get(worker_addr) {
             debug!(%worker_addr, "Outlet not found in node registry");
-            Some(OutletStatus::new(
-                outlet_to_show.to,
-                outlet_to_show.worker_addr.clone(),
-                None,
-                outlet_to_show.privileged,
-            ))
+            Some(
+                OutletStatus::new(
+                    outlet_to_show.to.clone(),
+                    outlet_to_show.worker_addr.clone(),
+                    None,
+                    outlet_to_show.privileged,
+                )
+                .with_targets(outlet_to_show.target_statuses())
+                .with_load_balancing(outlet_to_show.load_balancing.clone()),
+            )
         } else {
             error!(%worker_addr, "Outlet not found in the node registry");
             None
@@ -260,6 +345,8 @@ pub trait Outlets {
         privileged: bool,
         skip_handshake: bool,
         enable_nagle: bool,
+        load_balancing: Option<OutletLoadBalancing>,
+        tls_config: Option<TcpOutletTlsConfig>,
     ) -> miette::Result<OutletStatus>;
 }
 
@@ -276,6 +363,8 @@ impl Outlets for BackgroundNodeClient {
         privileged: bool,
         skip_handshake: bool,
         enable_nagle: bool,
+        load_balancing: Option<OutletLoadBalancing>,
+        tls_config: Option<TcpOutletTlsConfig>,
     ) -> miette::Result<OutletStatus> {
         let mut payload = CreateOutlet::new(
             to,
@@ -289,6 +378,12 @@ impl Outlets for BackgroundNodeClient {
         if let Some(policy_expression) = policy_expression {
             payload.set_policy_expression(policy_expression);
         }
+        if let Some(load_balancing) = load_balancing {
+            payload.set_load_balancing(load_balancing);
+        }
+        if let Some(tls_config) = tls_config {
+            payload.set_tls_config(tls_config);
+        }
         let req = Request::post("/node/outlet").body(payload);
         let result: OutletStatus = self.ask(ctx, req).await?;
         Ok(result)
@@ -1,6 +1,7 @@
 use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::{CredentialRetrieverCreator, Identifier, RemoteCredentialRetrieverInfo};
 use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Route;
 use std::fmt::Display;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -89,6 +90,8 @@ pub struct NodeManagerTrustOptions {
     pub(super) project_authority: Option<Identifier>,
     pub(super) project_admin_credential_retriever_options: NodeManagerCredentialRetrieverOptions,
     pub(super) _account_admin_credential_retriever_options: NodeManagerCredentialRetrieverOptions,
+    // routes to the project authority and its replicas, used to receive its revocation lists
+    pub(super) project_authority_routes: Vec<Route>,
 }
 
 impl NodeManagerTrustOptions {
@@ -103,6 +106,14 @@ impl NodeManagerTrustOptions {
             project_admin_credential_retriever_options,
             project_authority,
             _account_admin_credential_retriever_options: account_admin_credential_retriever_options,
+            project_authority_routes: vec![],
         }
     }
+
+    /// Set the routes to the project authority and its replicas.
+    /// They are used to subscribe to the authority revocation lists
+    pub fn with_project_authority_routes(mut self, project_authority_routes: Vec<Route>) -> Self {
+        self.project_authority_routes = project_authority_routes;
+        self
+    }
 }
@@ -0,0 +1,510 @@
+use std::time::Duration;
+
+use ockam::identity::Identifier;
+use ockam::transport::HostnamePort;
+use ockam::udp::{UdpInletOptions, UdpOutletOptions, UdpTransport};
+use ockam::{Address, Result};
+use ockam_abac::{Action, PolicyExpression, Resource, ResourceType};
+use ockam_core::api::{Error, Request, Response};
+use ockam_core::async_trait;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+
+use crate::cli_state::random_name;
+use crate::nodes::models::udp_portal::{
+    CreateUdpInlet, CreateUdpOutlet, UdpInletStatus, UdpOutletStatus,
+};
+use crate::nodes::registry::{UdpInletInfo, UdpOutletInfo};
+use crate::nodes::service::default_address::DefaultAddress;
+use crate::nodes::BackgroundNodeClient;
+use crate::session::replacer::MAX_CONNECT_TIME;
+
+use super::{NodeManager, NodeManagerWorker};
+
+impl NodeManagerWorker {
+    #[instrument(skip_all)]
+    pub(super) async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        create_inlet: CreateUdpInlet,
+    ) -> Result<Response<UdpInletStatus>, Response<Error>> {
+        let CreateUdpInlet {
+            listen_addr,
+            outlet_addr,
+            alias,
+            authorized,
+            policy_expression,
+            idle_timeout,
+            max_sessions,
+        } = create_inlet;
+
+        match self
+            .node_manager
+            .create_udp_inlet(
+                ctx,
+                listen_addr,
+                outlet_addr,
+                alias,
+                authorized,
+                policy_expression,
+                idle_timeout,
+                max_sessions,
+            )
+            .await
+        {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_inlet(
+        &self,
+        ctx: &Context,
+        alias: &str,
+    ) -> Result<Response<UdpInletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_inlet(ctx, alias).await {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) fn get_udp_inlets(&self) -> Result<Response<Vec<UdpInletStatus>>, Response<Error>> {
+        Ok(Response::ok().body(self.node_manager.list_udp_inlets()))
+    }
+
+    #[instrument(skip_all)]
+    pub(super) async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        create_outlet: CreateUdpOutlet,
+    ) -> Result<Response<UdpOutletStatus>, Response<Error>> {
+        let CreateUdpOutlet {
+            hostname_port,
+            worker_addr,
+            policy_expression,
+            idle_timeout,
+            max_sessions,
+        } = create_outlet;
+
+        match self
+            .node_manager
+            .create_udp_outlet(
+                ctx,
+                hostname_port,
+                worker_addr,
+                policy_expression,
+                idle_timeout,
+                max_sessions,
+            )
+            .await
+        {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) async fn delete_udp_outlet(
+        &self,
+        worker_addr: &Address,
+    ) -> Result<Response<UdpOutletStatus>, Response<Error>> {
+        match self.node_manager.delete_udp_outlet(worker_addr).await {
+            Ok(status) => Ok(Response::ok().body(status)),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    pub(super) fn get_udp_outlets(
+        &self,
+    ) -> Result<Response<Vec<UdpOutletStatus>>, Response<Error>> {
+        Ok(Response::ok().body(self.node_manager.list_udp_outlets()))
+    }
+}
+
+impl NodeManager {
+    fn portal_udp_transport(&self) -> Result<UdpTransport> {
+        self.udp_transport.clone().ok_or_else(|| {
+            ockam_core::Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                "UDP portals can only be created on a node started with UDP enabled",
+            )
+        })
+    }
+
+    /// Create a UDP inlet sending the datagrams received on `listen_address` to the UDP outlet
+    /// at `outlet_address`. Contrary to TCP inlets, the connection to the outlet is not
+    /// re-established if it fails.
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip_all)]
+    pub async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        listen_address: HostnamePort,
+        outlet_address: MultiAddr,
+        alias: String,
+        authorized: Option<Identifier>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> Result<UdpInletStatus> {
+        debug!(%listen_address, %outlet_address, %alias, "creating udp inlet");
+
+        let udp_transport = self.portal_udp_transport()?;
+        let max_sessions = check_sessions_limits(idle_timeout, max_sessions)?;
+
+        if self.registry.udp_inlets.contains_key(&alias) {
+            let message = format!("A UDP inlet with alias '{alias}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let (incoming_ac, outgoing_ac) = self
+            .access_control(
+                ctx,
+                self.project_authority(),
+                Resource::new(alias.clone(), ResourceType::UdpInlet),
+                Action::HandleMessage,
+                policy_expression,
+            )
+            .await?;
+
+        let connection = self
+            .make_connection(
+                ctx,
+                &outlet_address,
+                self.identifier(),
+                authorized,
+                Some(MAX_CONNECT_TIME),
+            )
+            .await?;
+        let outlet_route = connection.route()?;
+
+        let mut options = UdpInletOptions::new()
+            .with_incoming_access_control(incoming_ac)
+            .with_outgoing_access_control(outgoing_ac);
+        if let Some(idle_timeout) = idle_timeout {
+            options = options.with_idle_timeout(idle_timeout);
+        }
+        if let Some(max_sessions) = max_sessions {
+            options = options.with_max_sessions(max_sessions);
+        }
+
+        let inlet = match udp_transport
+            .create_inlet(listen_address.to_string(), outlet_route.clone(), options)
+            .await
+        {
+            Ok(inlet) => inlet,
+            Err(e) => {
+                if let Err(err) = connection.close(ctx, self) {
+                    warn!(%err, "Failed to close the connection of the UDP inlet");
+                }
+                return Err(e);
+            }
+        };
+
+        let bind_addr = inlet.socket_address().to_string();
+        self.registry.udp_inlets.insert(
+            alias.clone(),
+            UdpInletInfo {
+                bind_addr: bind_addr.clone(),
+                outlet_addr: outlet_address.clone(),
+                outlet_route: outlet_route.clone(),
+                inlet,
+                connection,
+            },
+        );
+
+        info!(%bind_addr, %outlet_address, %alias, "udp inlet created");
+
+        Ok(UdpInletStatus::new(
+            bind_addr,
+            alias,
+            outlet_address.to_string(),
+            outlet_route.to_string(),
+        ))
+    }
+
+    pub async fn delete_udp_inlet(&self, ctx: &Context, alias: &str) -> Result<UdpInletStatus> {
+        info!(%alias, "Handling request to delete udp inlet");
+        let Some(inlet_info) = self.registry.udp_inlets.remove(alias) else {
+            let message = format!("UDP inlet with alias {alias} not found");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                message,
+            ));
+        };
+
+        if let Err(err) = inlet_info.inlet.stop(ctx) {
+            warn!(%alias, %err, "Failed to stop the UDP inlet");
+        }
+        if let Err(err) = inlet_info.connection.close(ctx, self) {
+            warn!(%alias, %err, "Failed to close the connection of the UDP inlet");
+        }
+        self.resources().delete_resource(&alias.into()).await?;
+
+        Ok(UdpInletStatus::new(
+            inlet_info.bind_addr,
+            alias,
+            inlet_info.outlet_addr.to_string(),
+            None,
+        ))
+    }
+
+    pub fn list_udp_inlets(&self) -> Vec<UdpInletStatus> {
+        self.registry
+            .udp_inlets
+            .entries()
+            .into_iter()
+            .map(|(alias, info)| {
+                UdpInletStatus::new(
+                    info.bind_addr,
+                    alias,
+                    info.outlet_addr.to_string(),
+                    info.outlet_route.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    pub async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        worker_addr: Option<Address>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> Result<UdpOutletStatus> {
+        let udp_transport = self.portal_udp_transport()?;
+        let max_sessions = check_sessions_limits(idle_timeout, max_sessions)?;
+        let worker_addr = worker_addr.unwrap_or_else(|| random_name().into());
+
+        debug!(%to, address = %worker_addr, "creating udp outlet");
+
+        if self.registry.udp_outlets.contains_key(&worker_addr) {
+            let message = format!("A UDP outlet with address '{worker_addr}' already exists");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::AlreadyExists,
+                message,
+            ));
+        }
+
+        let (incoming_ac, outgoing_ac) = self
+            .access_control(
+                ctx,
+                self.project_authority(),
+                Resource::new(worker_addr.address(), ResourceType::UdpOutlet),
+                Action::HandleMessage,
+                policy_expression,
+            )
+            .await?;
+
+        let mut options = UdpOutletOptions::new()
+            .with_incoming_access_control(incoming_ac)
+            .with_outgoing_access_control(outgoing_ac);
+        if let Some(idle_timeout) = idle_timeout {
+            options = options.with_idle_timeout(idle_timeout);
+        }
+        if let Some(max_sessions) = max_sessions {
+            options = options.with_max_sessions(max_sessions);
+        }
+        if self.project_authority().is_none() {
+            for api_transport_flow_control_id in &self.api_transport_flow_control_ids {
+                options = options.as_consumer(api_transport_flow_control_id)
+            }
+        }
+        // Accept messages from the default secure channel listener
+        if let Some(flow_control_id) = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+        {
+            options = options.as_consumer(&flow_control_id)
+        }
+
+        if let Err(e) = udp_transport.create_outlet(worker_addr.clone(), to.clone(), options) {
+            warn!(at = %to, err = %e, "Failed to create UDP outlet");
+            let message = format!("Failed to create UDP outlet: {}", e);
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Internal,
+                message,
+            ));
+        }
+
+        self.registry.udp_outlets.insert(
+            worker_addr.clone(),
+            UdpOutletInfo {
+                to: to.clone(),
+                worker_addr: worker_addr.clone(),
+            },
+        );
+        info!(%to, address = %worker_addr, "udp outlet created");
+
+        Ok(UdpOutletStatus::new(to, worker_addr))
+    }
+
+    pub async fn delete_udp_outlet(&self, worker_addr: &Address) -> Result<UdpOutletStatus> {
+        info!(%worker_addr, "Handling request to delete udp outlet");
+        let Some(outlet_info) = self.registry.udp_outlets.remove(worker_addr) else {
+            let message = format!("UDP outlet with address {worker_addr} not found");
+            return Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::NotFound,
+                message,
+            ));
+        };
+
+        self.resources()
+            .delete_resource(&worker_addr.address().into())
+            .await?;
+        let udp_transport = self.portal_udp_transport()?;
+        if let Err(err) = udp_transport.stop_outlet(&outlet_info.worker_addr) {
+            warn!(%worker_addr, %err, "Failed to stop the UDP outlet");
+        }
+
+        Ok(UdpOutletStatus::new(
+            outlet_info.to,
+            outlet_info.worker_addr,
+        ))
+    }
+
+    pub fn list_udp_outlets(&self) -> Vec<UdpOutletStatus> {
+        self.registry
+            .udp_outlets
+            .values()
+            .into_iter()
+            .map(|info| UdpOutletStatus::new(info.to, info.worker_addr))
+            .collect()
+    }
+}
+
+/// Check that the sessions of a UDP portal are not closed as soon as they are started,
+/// and that at least one session can be started.
+/// Return the maximum number of sessions, if set, as a `usize`
+fn check_sessions_limits(
+    idle_timeout: Option<Duration>,
+    max_sessions: Option<u64>,
+) -> Result<Option<usize>> {
+    if idle_timeout.is_some_and(|idle_timeout| idle_timeout.is_zero()) {
+        return Err(ockam_core::Error::new(
+            Origin::Node,
+            Kind::Invalid,
+            "The idle timeout of a UDP portal must be greater than 0",
+        ));
+    }
+    max_sessions
+        .map(|max_sessions| match usize::try_from(max_sessions) {
+            Ok(max_sessions) if max_sessions >= 1 => Ok(max_sessions),
+            _ => Err(ockam_core::Error::new(
+                Origin::Node,
+                Kind::Invalid,
+                format!(
+                    "The maximum number of sessions of a UDP portal must be between 1 and {}",
+                    usize::MAX
+                ),
+            )),
+        })
+        .transpose()
+}
+
+#[async_trait]
+pub trait UdpPortals {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        listen_addr: HostnamePort,
+        outlet_addr: MultiAddr,
+        alias: String,
+        authorized: Option<Identifier>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> miette::Result<UdpInletStatus>;
+
+    async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> miette::Result<UdpOutletStatus>;
+}
+
+#[async_trait]
+impl UdpPortals for BackgroundNodeClient {
+    #[instrument(skip_all, fields(listen_addr = % listen_addr, outlet_addr = % outlet_addr))]
+    async fn create_udp_inlet(
+        &self,
+        ctx: &Context,
+        listen_addr: HostnamePort,
+        outlet_addr: MultiAddr,
+        alias: String,
+        authorized: Option<Identifier>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> miette::Result<UdpInletStatus> {
+        let mut payload = CreateUdpInlet::new(listen_addr, outlet_addr, alias, authorized);
+        if let Some(policy_expression) = policy_expression {
+            payload.set_policy_expression(policy_expression);
+        }
+        if let Some(idle_timeout) = idle_timeout {
+            payload.set_idle_timeout(idle_timeout);
+        }
+        if let Some(max_sessions) = max_sessions {
+            payload.set_max_sessions(max_sessions);
+        }
+        let req = Request::post("/node/udp_inlet").body(payload);
+        self.ask(ctx, req).await
+    }
+
+    #[instrument(skip_all, fields(to = % to, from = ? from))]
+    async fn create_udp_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        idle_timeout: Option<Duration>,
+        max_sessions: Option<u64>,
+    ) -> miette::Result<UdpOutletStatus> {
+        let mut payload = CreateUdpOutlet::new(to, from.cloned());
+        if let Some(policy_expression) = policy_expression {
+            payload.set_policy_expression(policy_expression);
+        }
+        if let Some(idle_timeout) = idle_timeout {
+            payload.set_idle_timeout(idle_timeout);
+        }
+        if let Some(max_sessions) = max_sessions {
+            payload.set_max_sessions(max_sessions);
+        }
+        let req = Request::post("/node/udp_outlet").body(payload);
+        self.ask(ctx, req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_sessions_limits() {
+        assert_eq!(check_sessions_limits(None, None).unwrap(), None);
+        assert_eq!(
+            check_sessions_limits(Some(Duration::from_secs(1)), Some(1)).unwrap(),
+            Some(1)
+        );
+        assert!(check_sessions_limits(Some(Duration::ZERO), None).is_err());
+        assert!(check_sessions_limits(None, Some(0)).is_err());
+    }
+}
@@ -187,6 +187,23 @@ impl NodeManagerWorker {
             }
             (Delete, ["node", "portal"]) => todo!(),
 
+            // ==*== UDP Inlets & Outlets ==*==
+            (Get, ["node", "udp_inlet"]) => encode_response(req, self.get_udp_inlets())?,
+            (Get, ["node", "udp_outlet"]) => encode_response(req, self.get_udp_outlets())?,
+            (Post, ["node", "udp_inlet"]) => {
+                encode_response(req, self.create_udp_inlet(ctx, dec.decode()?).await)?
+            }
+            (Post, ["node", "udp_outlet"]) => {
+                encode_response(req, self.create_udp_outlet(ctx, dec.decode()?).await)?
+            }
+            (Delete, ["node", "udp_inlet", alias]) => {
+                encode_response(req, self.delete_udp_inlet(ctx, alias).await)?
+            }
+            (Delete, ["node", "udp_outlet", addr]) => {
+                let addr: Address = addr.to_string().into();
+                encode_response(req, self.delete_udp_outlet(&addr).await)?
+            }
+
             // ==*== InfluxDB Inlets & Outlets  ==*==
             (Post, ["node", "influxdb_inlet"]) => encode_response(
                 req,
@@ -197,6 +214,18 @@ impl NodeManagerWorker {
                 self.start_influxdb_outlet_service(ctx, dec.decode()?).await,
             )?,
 
+            // ==*== HTTP Outlets  ==*==
+            (Post, ["node", "http_outlet"]) => encode_response(
+                req,
+                self.start_http_outlet_service(ctx, dec.decode()?).await,
+            )?,
+
+            // ==*== Postgres Outlets  ==*==
+            (Post, ["node", "postgres_outlet"]) => encode_response(
+                req,
+                self.start_postgres_outlet_service(ctx, dec.decode()?).await,
+            )?,
+
             // ==*== Flow Controls ==*==
             (Post, ["node", "flow_controls", "add_consumer"]) => {
                 encode_response(req, self.add_consumer(ctx, dec.decode()?).await)?
@@ -218,6 +247,9 @@ impl NodeManagerWorker {
                 encode_response(req, self.get_policy(action, dec.decode()?).await)?
             }
             (Get, ["policy"]) => encode_response(req, self.list_policies(dec.decode()?).await)?,
+            (Post, ["policy", action, "evaluate"]) => {
+                encode_response(req, self.evaluate_policy(action, dec.decode()?).await)?
+            }
             (Delete, ["policy", action]) => {
                 encode_response(req, self.delete_policy(action, dec.decode()?).await)?
             }
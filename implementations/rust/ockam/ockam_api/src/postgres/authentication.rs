@@ -0,0 +1,198 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use ockam::errcode::{Kind, Origin};
+use ockam_core::Result;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// Name of the only SASL mechanism supported to authenticate to a PostgreSQL server
+pub(crate) const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+
+/// Return the password to send when the server requests an md5 password:
+/// `"md5" + md5(md5(password + user) + salt)`, in hexadecimal
+pub(crate) fn md5_password(user: &str, password: &str, salt: &[u8]) -> String {
+    let inner = hex::encode(Md5::digest(format!("{password}{user}").as_bytes()));
+    let mut outer = Md5::new();
+    outer.update(inner.as_bytes());
+    outer.update(salt);
+    format!("md5{}", hex::encode(outer.finalize()))
+}
+
+/// Client side of a SCRAM-SHA-256 authentication (RFC 5802 and RFC 7677),
+/// without channel binding.
+///
+/// The user name is not sent in the SCRAM messages since PostgreSQL uses the user name
+/// sent in the startup message.
+pub(crate) struct ScramClient {
+    password: String,
+    client_first_message_bare: String,
+    expected_server_signature: Option<Vec<u8>>,
+}
+
+impl ScramClient {
+    pub(crate) fn new(password: &str) -> Self {
+        let nonce: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(24)
+            .map(char::from)
+            .collect();
+        Self::with_nonce(password, "", &nonce)
+    }
+
+    fn with_nonce(password: &str, user: &str, nonce: &str) -> Self {
+        Self {
+            password: password.to_string(),
+            client_first_message_bare: format!("n={user},r={nonce}"),
+            expected_server_signature: None,
+        }
+    }
+
+    /// Return the client-first-message
+    pub(crate) fn client_first_message(&self) -> String {
+        format!("n,,{}", self.client_first_message_bare)
+    }
+
+    /// Process the server-first-message and return the client-final-message
+    pub(crate) fn client_final_message(&mut self, server_first_message: &str) -> Result<String> {
+        let mut nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+        for attribute in server_first_message.split(',') {
+            match attribute.split_once('=') {
+                Some(("r", value)) => nonce = Some(value),
+                Some(("s", value)) => salt = Some(STANDARD.decode(value).map_err(scram_error)?),
+                Some(("i", value)) => iterations = Some(value.parse::<u32>().map_err(scram_error)?),
+                _ => {}
+            }
+        }
+        let (nonce, salt, iterations) = match (nonce, salt, iterations) {
+            (Some(nonce), Some(salt), Some(iterations)) if iterations > 0 => {
+                (nonce, salt, iterations)
+            }
+            _ => return Err(scram_error("invalid server-first-message")),
+        };
+        let client_nonce = self
+            .client_first_message_bare
+            .split_once(",r=")
+            .map(|(_, n)| n)
+            .unwrap_or_default();
+        if !nonce.starts_with(client_nonce) {
+            return Err(scram_error(
+                "the server nonce does not extend the client nonce",
+            ));
+        }
+
+        let salted_password = hi(self.password.as_bytes(), &salt, iterations);
+        let client_key = hmac(&salted_password, b"Client Key");
+        let stored_key = Sha256::digest(&client_key);
+        let client_final_message_without_proof = format!("c=biws,r={nonce}");
+        let auth_message = format!(
+            "{},{},{}",
+            self.client_first_message_bare,
+            server_first_message,
+            client_final_message_without_proof
+        );
+        let client_signature = hmac(&stored_key, auth_message.as_bytes());
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(client_signature.iter())
+            .map(|(k, s)| k ^ s)
+            .collect();
+
+        let server_key = hmac(&salted_password, b"Server Key");
+        self.expected_server_signature = Some(hmac(&server_key, auth_message.as_bytes()));
+
+        Ok(format!(
+            "{client_final_message_without_proof},p={}",
+            STANDARD.encode(proof)
+        ))
+    }
+
+    /// Check the server signature sent in the server-final-message
+    pub(crate) fn verify_server_final_message(&self, server_final_message: &str) -> Result<()> {
+        let signature = match server_final_message.strip_prefix("v=") {
+            Some(signature) => STANDARD.decode(signature).map_err(scram_error)?,
+            None => return Err(scram_error(server_final_message)),
+        };
+        if Some(signature) == self.expected_server_signature {
+            Ok(())
+        } else {
+            Err(scram_error("invalid server signature"))
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // an HMAC key can have any size
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// PBKDF2 with HMAC-SHA-256, producing a single block
+fn hi(password: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+    let mut u = hmac(password, &[salt, &1u32.to_be_bytes()].concat());
+    let mut result = u.clone();
+    for _ in 1..iterations {
+        u = hmac(password, &u);
+        result.iter_mut().zip(u.iter()).for_each(|(r, u)| *r ^= u);
+    }
+    result
+}
+
+fn scram_error(e: impl core::fmt::Display) -> ockam_core::Error {
+    ockam_core::Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("SCRAM authentication failed: {e}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn md5_authentication() {
+        assert_eq!(
+            md5_password("postgres", "secret", &[0x01, 0x02, 0x03, 0x04]),
+            "md5bb41a296aab6baccb36ff243a562abff"
+        );
+    }
+
+    #[test]
+    fn scram_authentication() {
+        // test vector from RFC 7677
+        let mut client = ScramClient::with_nonce("pencil", "user", "rOprNGfwEbeRWgbNEkqO");
+        assert_eq!(
+            client.client_first_message(),
+            "n,,n=user,r=rOprNGfwEbeRWgbNEkqO"
+        );
+        let client_final = client
+            .client_final_message(
+                "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+            )
+            .unwrap();
+        assert_eq!(
+            client_final,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+        );
+        client
+            .verify_server_final_message("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .unwrap();
+        assert!(client
+            .verify_server_final_message("v=AAAATRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .is_err());
+    }
+
+    #[test]
+    fn scram_authentication_rejects_invalid_nonce() {
+        let mut client = ScramClient::with_nonce("pencil", "", "abc");
+        assert!(client
+            .client_final_message("r=xyz,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096")
+            .is_err());
+    }
+}
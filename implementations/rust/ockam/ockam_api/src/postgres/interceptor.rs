@@ -0,0 +1,974 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use minicbor::{CborLen, Decode, Encode};
+use ockam::identity::{Identifier, IdentitiesAttributes};
+use ockam_abac::expr::str;
+use ockam_abac::{Abac, Env, Expr, RateLimits};
+use ockam_core::{async_trait, LocalInfoIdentifier, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{Direction, PortalInterceptor, PortalInterceptorFactory};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::postgres::authentication::{md5_password, ScramClient, SCRAM_SHA_256};
+use crate::postgres::protocol::*;
+use crate::postgres::statement::{parse_statements, Statement};
+
+/// Credentials used by a Postgres outlet to authenticate to the server,
+/// on behalf of the clients connecting to the outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PostgresCredentials {
+    #[n(1)] pub user: String,
+    #[n(2)] pub password: String,
+}
+
+impl PostgresCredentials {
+    pub fn new(user: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            user: user.into(),
+            password: password.into(),
+        }
+    }
+}
+
+/// Policy checked for each statement sent to the server.
+///
+/// The expression is evaluated with the attributes of the identity which opened the connection,
+/// and the following attributes:
+///
+///  - `query.command`: the command executed by the statement, for example `SELECT` or `DELETE`.
+///    Functions called directly with the function call message use the `FUNCTION CALL` command
+///  - `query.database`: the database requested by the client
+///  - `query.user`: the user requested by the client
+///
+/// A statement executing several commands is only allowed if all its commands are allowed.
+///
+/// The `DO`, `EXECUTE` and `CALL` commands run some code whose statements can't be checked.
+/// They are only allowed if the expression explicitly mentions them, for example with
+/// `(or (= query.command "SELECT") (= query.command "CALL"))`.
+///
+/// This check is best-effort. The commands are found with a keyword heuristic, not with the SQL
+/// parser of the server, and a command doesn't describe everything a statement does: a `SELECT`
+/// can call a function modifying some data, a trigger or a rule can run other statements, etc.
+/// The policy must not replace the privileges granted to the server user, which are the only
+/// enforceable restriction.
+#[derive(Clone)]
+pub struct PostgresQueryPolicy {
+    expression: Expr,
+    identities_attributes: Arc<IdentitiesAttributes>,
+    authority: Option<Identifier>,
+    environment: Env,
+    // shared by all the connections of the outlet
+    rate_limits: RateLimits,
+}
+
+impl PostgresQueryPolicy {
+    pub fn new(
+        expression: Expr,
+        identities_attributes: Arc<IdentitiesAttributes>,
+        authority: Option<Identifier>,
+        environment: Env,
+    ) -> Self {
+        Self {
+            expression,
+            identities_attributes,
+            authority,
+            environment,
+            rate_limits: RateLimits::default(),
+        }
+    }
+
+    async fn is_allowed(
+        &self,
+        identifier: Option<&Identifier>,
+        parameters: Option<&StartupParameters>,
+        command: &str,
+    ) -> Result<bool> {
+        let identifier = match identifier {
+            Some(identifier) => identifier,
+            None => {
+                warn!("statement rejected: the connection is not authenticated");
+                return Ok(false);
+            }
+        };
+        if OPAQUE_COMMANDS.contains(&command) && !mentions(&self.expression, command) {
+            warn!("statement rejected: the {command} command is not explicitly allowed");
+            return Ok(false);
+        }
+        let mut environment = self.environment.clone();
+        environment.put("query.command", str(command));
+        for key in ["database", "user"] {
+            if let Some(value) = parameters.and_then(|p| p.get(key)) {
+                environment.put(format!("query.{key}"), str(value));
+            }
+        }
+        Abac::is_identity_authorized_static(
+            self.identities_attributes.clone(),
+            &environment,
+            &self.rate_limits,
+            self.authority.as_ref(),
+            identifier,
+            &self.expression,
+        )
+        .await
+    }
+}
+
+/// Commands running some code which can't be inspected by the interceptor
+const OPAQUE_COMMANDS: [&str; 3] = ["DO", "EXECUTE", "CALL"];
+
+/// Return true if an expression contains a given string
+fn mentions(expression: &Expr, value: &str) -> bool {
+    match expression {
+        Expr::Str(s) => s == value,
+        Expr::Seq(expressions) | Expr::List(expressions) => {
+            expressions.iter().any(|e| mentions(e, value))
+        }
+        _ => false,
+    }
+}
+
+/// Command checked by the query policy for the function call messages,
+/// which call a function without any SQL statement
+pub const FUNCTION_CALL_COMMAND: &str = "FUNCTION CALL";
+
+/// Creates an interceptor for each connection to a Postgres outlet
+pub struct PostgresInterceptorFactory {
+    credentials: Option<PostgresCredentials>,
+    query_policy: Option<PostgresQueryPolicy>,
+}
+
+impl PostgresInterceptorFactory {
+    pub fn new(
+        credentials: Option<PostgresCredentials>,
+        query_policy: Option<PostgresQueryPolicy>,
+    ) -> Self {
+        Self {
+            credentials,
+            query_policy,
+        }
+    }
+}
+
+impl PortalInterceptorFactory for PostgresInterceptorFactory {
+    fn create(&self) -> Arc<dyn PortalInterceptor> {
+        self.create_for_identifier(None)
+    }
+
+    fn create_for_identifier(
+        &self,
+        identifier: Option<LocalInfoIdentifier>,
+    ) -> Arc<dyn PortalInterceptor> {
+        Arc::new(PostgresInterceptor {
+            state: Arc::new(Mutex::new(PostgresConnection::new(
+                identifier.map(|i| i.into()),
+                self.credentials.clone(),
+                self.query_policy.clone(),
+            ))),
+        })
+    }
+}
+
+/// Interceptor parsing the PostgreSQL wire protocol on the outlet side of a portal.
+///
+/// It can:
+///
+///  - authenticate to the server with some configured credentials, so that clients don't need to
+///    know them. In that case the user sent by the client is replaced by the configured user,
+///    and the authentication requests of the server are answered by the interceptor.
+///    Cleartext, md5 and SCRAM-SHA-256 password authentications are supported.
+///  - reject the statements which are not allowed by a [`PostgresQueryPolicy`].
+///  - emit an audit event for each statement, with the identifier of the caller.
+///
+/// Encrypted connections to the server are not supported, since the traffic must be inspected.
+/// The encryption requests of the clients are declined by the interceptor, the data being already
+/// encrypted by the secure channel used by the portal.
+struct PostgresInterceptor {
+    state: Arc<Mutex<PostgresConnection>>,
+}
+
+#[async_trait]
+impl PortalInterceptor for PostgresInterceptor {
+    async fn intercept(
+        &self,
+        context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .intercept_with_reply(context, direction, buffer)
+            .await?
+            .0)
+    }
+
+    async fn intercept_with_reply(
+        &self,
+        _context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        let mut connection = self.state.lock().await;
+        let output = match direction {
+            Direction::FromInletToOutlet => connection.process_frontend_data(buffer).await?,
+            Direction::FromOutletToInlet => connection.process_backend_data(buffer)?,
+        };
+        Ok((output.forward(), output.reply()))
+    }
+
+    fn can_reply(&self) -> bool {
+        true
+    }
+}
+
+/// Data produced by the interceptor
+#[derive(Debug, Default)]
+struct Output {
+    forward: Vec<u8>,
+    reply: Vec<u8>,
+}
+
+impl Output {
+    fn forward(&self) -> Option<Vec<u8>> {
+        (!self.forward.is_empty()).then(|| self.forward.clone())
+    }
+
+    fn reply(&self) -> Option<Vec<u8>> {
+        (!self.reply.is_empty()).then(|| self.reply.clone())
+    }
+}
+
+/// Messages expected by the client, in the order of its requests
+#[derive(Debug)]
+enum PendingReply {
+    /// ReadyForQuery message sent by the server, preceded by an error of the interceptor if a
+    /// statement was rejected since the previous Sync message
+    Server(Option<Message>),
+    /// Error sent by the interceptor for a rejected request, followed by a ReadyForQuery message
+    Rejection(Option<Message>),
+}
+
+/// State of a connection between a client and the server
+struct PostgresConnection {
+    identifier: Option<Identifier>,
+    credentials: Option<PostgresCredentials>,
+    query_policy: Option<PostgresQueryPolicy>,
+    frontend: MessageReader,
+    backend: MessageReader,
+    /// True once the client has sent its startup message
+    started: bool,
+    /// Parameters sent by the client in the startup message
+    parameters: Option<StartupParameters>,
+    /// SCRAM exchange in progress
+    scram: Option<ScramClient>,
+    /// Transaction status sent by the server in the last ReadyForQuery message
+    transaction_status: u8,
+    /// True once the server has sent the ReadyForQuery message ending the startup
+    ready: bool,
+    /// Replies expected by the client for the requests which have not been answered yet.
+    /// The errors of the interceptor must be sent after the replies of the server to the
+    /// previous requests
+    pending_replies: VecDeque<PendingReply>,
+    /// True when a statement was rejected with the extended query protocol.
+    /// In that case, the messages of the client are discarded until the next Sync message.
+    discarding_until_sync: bool,
+    /// Error sent to the client for the rejected statement, once the server has answered the
+    /// messages forwarded before the next Sync message
+    error_until_sync: Option<Message>,
+    /// True if some messages were forwarded to the server since the last Sync message
+    forwarded_since_sync: bool,
+}
+
+impl PostgresConnection {
+    fn new(
+        identifier: Option<Identifier>,
+        credentials: Option<PostgresCredentials>,
+        query_policy: Option<PostgresQueryPolicy>,
+    ) -> Self {
+        Self {
+            identifier,
+            credentials,
+            query_policy,
+            frontend: MessageReader::default(),
+            backend: MessageReader::default(),
+            started: false,
+            parameters: None,
+            scram: None,
+            transaction_status: b'I',
+            ready: false,
+            pending_replies: VecDeque::new(),
+            discarding_until_sync: false,
+            error_until_sync: None,
+            forwarded_since_sync: false,
+        }
+    }
+
+    /// Process the data sent by the client
+    async fn process_frontend_data(&mut self, buffer: &[u8]) -> Result<Output> {
+        let mut output = Output::default();
+        self.frontend.push(buffer);
+        while let Some(message) = self.frontend.next_message(self.started)? {
+            if self.started {
+                self.process_frontend_message(message, &mut output).await?;
+            } else {
+                self.process_startup_message(message, &mut output)?;
+            }
+        }
+        Ok(output)
+    }
+
+    fn process_startup_message(&mut self, message: Message, output: &mut Output) -> Result<()> {
+        match message.code()? {
+            SSL_REQUEST_CODE | GSSENC_REQUEST_CODE => {
+                // decline the encryption request, the client can then send its startup message
+                output.reply.push(b'N');
+            }
+            CANCEL_REQUEST_CODE => message.encode_into(&mut output.forward),
+            PROTOCOL_VERSION_3 => {
+                let mut parameters = StartupParameters::decode(&message.body)?;
+                let message = match &self.credentials {
+                    Some(credentials) => {
+                        let mut rewritten = parameters.clone();
+                        rewritten.set("user", &credentials.user);
+                        // the client database defaults to its user name
+                        if parameters.get("database").is_none() {
+                            if let Some(user) = parameters.get("user").map(|u| u.to_string()) {
+                                rewritten.set("database", &user);
+                                parameters.set("database", &user);
+                            }
+                        }
+                        rewritten.encode()
+                    }
+                    None => message,
+                };
+                message.encode_into(&mut output.forward);
+                self.parameters = Some(parameters);
+                self.started = true;
+            }
+            code => {
+                return Err(protocol_error(format!(
+                    "unsupported protocol version {code}"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    async fn process_frontend_message(
+        &mut self,
+        message: Message,
+        output: &mut Output,
+    ) -> Result<()> {
+        match message.tag {
+            Some(QUERY) => {
+                let query = query_string(&message)?;
+                if let Some(reason) = self.check_statements(&query).await? {
+                    self.reject(
+                        Some(error_response(INSUFFICIENT_PRIVILEGE, &reason)),
+                        output,
+                    );
+                } else {
+                    message.encode_into(&mut output.forward);
+                    self.pending_replies.push_back(PendingReply::Server(None));
+                }
+            }
+            Some(FUNCTION_CALL) => {
+                let oid = function_call_oid(&message)?;
+                let description = format!("function call {oid}");
+                let commands = [FUNCTION_CALL_COMMAND.to_string()];
+                if let Some(reason) = self.check_commands(&description, &commands).await? {
+                    self.reject(
+                        Some(error_response(INSUFFICIENT_PRIVILEGE, &reason)),
+                        output,
+                    );
+                } else {
+                    message.encode_into(&mut output.forward);
+                    self.pending_replies.push_back(PendingReply::Server(None));
+                }
+            }
+            Some(PARSE) if !self.discarding_until_sync => {
+                let query = parse_query_string(&message)?;
+                if let Some(reason) = self.check_statements(&query).await? {
+                    let error = error_response(INSUFFICIENT_PRIVILEGE, &reason);
+                    // the error can be sent right away if the server has nothing left to send
+                    if !self.forwarded_since_sync && self.pending_replies.is_empty() {
+                        error.encode_into(&mut output.reply);
+                    } else {
+                        self.error_until_sync = Some(error);
+                    }
+                    // the server would ignore all the messages until the next Sync
+                    self.discarding_until_sync = true;
+                } else {
+                    message.encode_into(&mut output.forward);
+                    self.forwarded_since_sync = true;
+                }
+            }
+            Some(SYNC) => {
+                let error = self.error_until_sync.take();
+                if self.discarding_until_sync && !self.forwarded_since_sync {
+                    self.reject(error, output);
+                } else {
+                    message.encode_into(&mut output.forward);
+                    self.pending_replies.push_back(PendingReply::Server(error));
+                }
+                self.discarding_until_sync = false;
+                self.forwarded_since_sync = false;
+            }
+            Some(PASSWORD) if self.credentials.is_some() => {
+                // the authentication is done by the interceptor
+                warn!("password message sent by the client discarded");
+            }
+            _ if self.discarding_until_sync => {}
+            _ => {
+                message.encode_into(&mut output.forward);
+                self.forwarded_since_sync = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send an error and a ReadyForQuery message to the client, after the replies of the server to
+    /// the previous requests
+    fn reject(&mut self, error: Option<Message>, output: &mut Output) {
+        if self.pending_replies.is_empty() {
+            if let Some(error) = error {
+                error.encode_into(&mut output.reply);
+            }
+            ready_for_query(self.transaction_status).encode_into(&mut output.reply);
+        } else {
+            self.pending_replies
+                .push_back(PendingReply::Rejection(error));
+        }
+    }
+
+    /// Check if all the statements of a query are allowed and emit an audit event for each one.
+    /// Return the reason of the rejection if a statement is not allowed.
+    async fn check_statements(&self, query: &str) -> Result<Option<String>> {
+        let Some(statements) = parse_statements(query) else {
+            // the commands can't be checked if the statements can't be parsed
+            let allowed = self.query_policy.is_none();
+            self.audit(query.trim(), &[], allowed);
+            return Ok((!allowed).then(|| {
+                "the statement is ambiguous for the Ockam outlet, \
+                 string constants containing a backslash must use the E'...' syntax"
+                    .to_string()
+            }));
+        };
+        let mut rejection = None;
+        for Statement { sql, commands } in statements {
+            if let Some(reason) = self.check_commands(&sql, &commands).await? {
+                rejection.get_or_insert(reason);
+            }
+        }
+        Ok(rejection)
+    }
+
+    /// Check if all the commands of a statement are allowed and emit an audit event.
+    /// Return the reason of the rejection if a command is not allowed.
+    async fn check_commands(&self, sql: &str, commands: &[String]) -> Result<Option<String>> {
+        let mut rejection = None;
+        if let Some(query_policy) = &self.query_policy {
+            for command in commands {
+                if !query_policy
+                    .is_allowed(self.identifier.as_ref(), self.parameters.as_ref(), command)
+                    .await?
+                {
+                    rejection.get_or_insert_with(|| {
+                        format!("{command} statements are not allowed by the Ockam outlet policy")
+                    });
+                }
+            }
+        }
+        self.audit(sql, commands, rejection.is_none());
+        Ok(rejection)
+    }
+
+    fn audit(&self, sql: &str, commands: &[String], allowed: bool) {
+        let identifier = self
+            .identifier
+            .as_ref()
+            .map(|i| i.to_string())
+            .unwrap_or_default();
+        let parameter = |key| {
+            self.parameters
+                .as_ref()
+                .and_then(|p| p.get(key))
+                .unwrap_or_default()
+                .to_string()
+        };
+        info!(
+            target: "postgres_audit",
+            identifier = %identifier,
+            database = %parameter("database"),
+            user = %parameter("user"),
+            command = %commands.join(","),
+            allowed = %allowed,
+            statement = %sql,
+            "postgres statement"
+        );
+    }
+
+    /// Process the data sent by the server
+    fn process_backend_data(&mut self, buffer: &[u8]) -> Result<Output> {
+        let mut output = Output::default();
+        self.backend.push(buffer);
+        while let Some(message) = self.backend.next_message(true)? {
+            self.process_backend_message(message, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    fn process_backend_message(&mut self, message: Message, output: &mut Output) -> Result<()> {
+        match (message.tag, &self.credentials) {
+            (Some(AUTHENTICATION), Some(credentials)) => {
+                let credentials = credentials.clone();
+                if let Err(e) = self.authenticate(&credentials, &message, output) {
+                    error!("postgres authentication failed: {e}");
+                    error_response(
+                        INVALID_AUTHORIZATION_SPECIFICATION,
+                        &format!("the Ockam outlet could not authenticate to the server: {e}"),
+                    )
+                    .encode_into(&mut output.forward);
+                }
+            }
+            (Some(READY_FOR_QUERY), _) => {
+                if let Some(status) = message.body.first() {
+                    self.transaction_status = *status;
+                }
+                // the first ReadyForQuery message ends the startup
+                if self.ready {
+                    if let Some(PendingReply::Server(Some(error))) =
+                        self.pending_replies.pop_front()
+                    {
+                        error.encode_into(&mut output.forward);
+                    }
+                }
+                self.ready = true;
+                message.encode_into(&mut output.forward);
+                // send the rejections which were waiting for this reply
+                while let Some(reply) = self.pending_replies.pop_front() {
+                    let PendingReply::Rejection(error) = reply else {
+                        self.pending_replies.push_front(reply);
+                        break;
+                    };
+                    if let Some(error) = error {
+                        error.encode_into(&mut output.forward);
+                    }
+                    ready_for_query(self.transaction_status).encode_into(&mut output.forward);
+                }
+            }
+            _ => message.encode_into(&mut output.forward),
+        }
+        Ok(())
+    }
+
+    /// Answer an authentication request of the server with the configured credentials
+    fn authenticate(
+        &mut self,
+        credentials: &PostgresCredentials,
+        message: &Message,
+        output: &mut Output,
+    ) -> Result<()> {
+        let data = message.body.get(4..).unwrap_or_default();
+        match message.code()? {
+            AUTHENTICATION_OK => message.encode_into(&mut output.forward),
+            AUTHENTICATION_CLEARTEXT_PASSWORD => {
+                password_message(&credentials.password).encode_into(&mut output.reply)
+            }
+            AUTHENTICATION_MD5_PASSWORD => {
+                let password = md5_password(&credentials.user, &credentials.password, data);
+                password_message(&password).encode_into(&mut output.reply)
+            }
+            AUTHENTICATION_SASL => {
+                if !sasl_mechanisms(message)?.iter().any(|m| m == SCRAM_SHA_256) {
+                    return Err(protocol_error("no supported SASL mechanism"));
+                }
+                let scram = ScramClient::new(&credentials.password);
+                sasl_initial_response(SCRAM_SHA_256, scram.client_first_message().as_bytes())
+                    .encode_into(&mut output.reply);
+                self.scram = Some(scram);
+            }
+            AUTHENTICATION_SASL_CONTINUE => {
+                let scram = self
+                    .scram
+                    .as_mut()
+                    .ok_or_else(|| protocol_error("unexpected SASL continue message"))?;
+                let server_first_message = String::from_utf8_lossy(data);
+                let client_final_message = scram.client_final_message(&server_first_message)?;
+                sasl_response(client_final_message.as_bytes()).encode_into(&mut output.reply);
+            }
+            AUTHENTICATION_SASL_FINAL => {
+                let scram = self
+                    .scram
+                    .take()
+                    .ok_or_else(|| protocol_error("unexpected SASL final message"))?;
+                scram.verify_server_final_message(&String::from_utf8_lossy(data))?;
+            }
+            code => {
+                return Err(protocol_error(format!(
+                    "unsupported authentication method {code}"
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn startup(user: &str) -> Vec<u8> {
+        let mut body = PROTOCOL_VERSION_3.to_be_bytes().to_vec();
+        body.extend_from_slice(format!("user\0{user}\0database\0db\0\0").as_bytes());
+        Message::untagged(body).encode()
+    }
+
+    fn query(sql: &str) -> Vec<u8> {
+        Message::new(QUERY, format!("{sql}\0").into_bytes()).encode()
+    }
+
+    fn parse(sql: &str) -> Vec<u8> {
+        Message::new(PARSE, format!("\0{sql}\0\0\0").into_bytes()).encode()
+    }
+
+    fn authentication(code: i32, data: &[u8]) -> Vec<u8> {
+        Message::new(AUTHENTICATION, [&code.to_be_bytes(), data].concat()).encode()
+    }
+
+    fn read_messages(data: &[u8]) -> Vec<Message> {
+        let mut reader = MessageReader::default();
+        reader.push(data);
+        let mut messages = vec![];
+        while let Some(message) = reader.next_message(true).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn decline_encryption_requests() {
+        let mut connection = PostgresConnection::new(None, None, None);
+        let ssl_request = Message::untagged(SSL_REQUEST_CODE.to_be_bytes().to_vec()).encode();
+        let output = connection
+            .process_frontend_data(&ssl_request)
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        assert_eq!(output.reply(), Some(b"N".to_vec()));
+
+        let output = connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(startup("alice")));
+    }
+
+    #[tokio::test]
+    async fn inject_credentials() {
+        let credentials = PostgresCredentials::new("postgres", "secret");
+        let mut connection = PostgresConnection::new(None, Some(credentials), None);
+
+        // the user is replaced
+        let output = connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(startup("postgres")));
+
+        // the md5 authentication request is answered by the interceptor
+        let output = connection
+            .process_backend_data(&authentication(AUTHENTICATION_MD5_PASSWORD, &[1, 2, 3, 4]))
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        assert_eq!(
+            read_messages(&output.reply().unwrap()),
+            vec![password_message("md5bb41a296aab6baccb36ff243a562abff")]
+        );
+
+        // the client only receives the authentication result
+        let ok = authentication(AUTHENTICATION_OK, &[]);
+        let output = connection.process_backend_data(&ok).unwrap();
+        assert_eq!(output.forward(), Some(ok));
+        assert_eq!(output.reply(), None);
+    }
+
+    #[tokio::test]
+    async fn reject_statements() {
+        let mut connection = PostgresConnection::new(None, None, None);
+        connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // without a policy, all the statements are forwarded
+        let output = connection
+            .process_frontend_data(&query("DELETE FROM t"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(query("DELETE FROM t")));
+
+        // a connection without an identity is rejected by the policy
+        let identities = ockam::identity::identities().await.unwrap();
+        connection.query_policy = Some(PostgresQueryPolicy::new(
+            Expr::CONST_TRUE,
+            identities.identities_attributes(),
+            None,
+            Env::new(),
+        ));
+        connection
+            .process_backend_data(&Message::new(READY_FOR_QUERY, vec![b'T']).encode())
+            .unwrap();
+        let output = connection
+            .process_frontend_data(&query("DELETE FROM t"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        let reply = read_messages(&output.reply().unwrap());
+        assert_eq!(reply[0].tag, Some(ERROR_RESPONSE));
+        assert_eq!(reply[1], ready_for_query(b'T'));
+    }
+
+    #[tokio::test]
+    async fn reject_parse_after_forwarded_messages() {
+        let identities = ockam::identity::identities().await.unwrap();
+        let identifier = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let expression = ockam_abac::parse(r#"(!= query.command "DELETE")"#)
+            .unwrap()
+            .unwrap();
+        let mut connection = PostgresConnection::new(
+            Some(identifier),
+            None,
+            Some(PostgresQueryPolicy::new(
+                expression,
+                identities.identities_attributes(),
+                None,
+                Env::new(),
+            )),
+        );
+        connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // the messages following the rejected statement are discarded until Sync
+        let bind = Message::new(b'B', vec![0; 8]).encode();
+        let execute = Message::new(b'E', vec![0; 5]).encode();
+        let sync = Message::new(SYNC, vec![]).encode();
+        let messages = [
+            parse("SELECT 1"),
+            bind.clone(),
+            execute.clone(),
+            parse("DELETE FROM t"),
+            bind.clone(),
+            execute.clone(),
+            sync.clone(),
+        ]
+        .concat();
+        let output = connection.process_frontend_data(&messages).await.unwrap();
+        assert_eq!(
+            output.forward(),
+            Some([parse("SELECT 1"), bind, execute, sync].concat())
+        );
+        assert_eq!(output.reply(), None);
+
+        // the error is sent after the replies of the server to the first statement
+        let parse_complete = Message::new(b'1', vec![]).encode();
+        let command_complete = Message::new(b'C', b"SELECT 1\0".to_vec()).encode();
+        let replies = [parse_complete, command_complete].concat();
+        let output = connection
+            .process_backend_data(&[replies.clone(), ready_for_query(b'I').encode()].concat())
+            .unwrap();
+        let forward = output.forward().unwrap();
+        assert!(forward.starts_with(&replies));
+        let messages = read_messages(&forward[replies.len()..]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].tag, Some(ERROR_RESPONSE));
+        assert_eq!(messages[1], ready_for_query(b'I'));
+
+        // a query rejected while the server is still answering is also answered after it
+        let output = connection
+            .process_frontend_data(&query("SELECT 2"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(query("SELECT 2")));
+        let output = connection
+            .process_frontend_data(&query("DELETE FROM t"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        assert_eq!(output.reply(), None);
+        let output = connection
+            .process_backend_data(&ready_for_query(b'T').encode())
+            .unwrap();
+        let messages = read_messages(&output.forward().unwrap());
+        assert_eq!(messages[0], ready_for_query(b'T'));
+        assert_eq!(messages[1].tag, Some(ERROR_RESPONSE));
+        assert_eq!(messages[2], ready_for_query(b'T'));
+    }
+
+    #[tokio::test]
+    async fn reject_ambiguous_statements() {
+        let identities = ockam::identity::identities().await.unwrap();
+        let identifier = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let mut connection = PostgresConnection::new(
+            Some(identifier),
+            None,
+            Some(PostgresQueryPolicy::new(
+                Expr::CONST_TRUE,
+                identities.identities_attributes(),
+                None,
+                Env::new(),
+            )),
+        );
+        connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // escape strings can be parsed
+        let escape_string = query(r"SELECT E'\''; DELETE FROM t; --'");
+        let output = connection
+            .process_frontend_data(&escape_string)
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(escape_string));
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // but not standard strings containing a backslash
+        let output = connection
+            .process_frontend_data(&query(r"SELECT '\'; DELETE FROM t; --'"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        let reply = read_messages(&output.reply().unwrap());
+        assert_eq!(reply[0].tag, Some(ERROR_RESPONSE));
+        assert_eq!(reply[1], ready_for_query(b'I'));
+    }
+
+    #[tokio::test]
+    async fn reject_commands_running_code() {
+        let identities = ockam::identity::identities().await.unwrap();
+        let identifier = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let expression =
+            ockam_abac::parse(r#"(or (!= query.command "DELETE") (= query.command "CALL"))"#)
+                .unwrap()
+                .unwrap();
+        let mut connection = PostgresConnection::new(
+            Some(identifier),
+            None,
+            Some(PostgresQueryPolicy::new(
+                expression,
+                identities.identities_attributes(),
+                None,
+                Env::new(),
+            )),
+        );
+        connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+
+        // the commands which are not mentioned by the policy are rejected
+        for sql in [
+            "DO $$BEGIN DELETE FROM t; END$$",
+            "EXECUTE p",
+            "EXPLAIN ANALYZE DELETE FROM t",
+        ] {
+            let output = connection.process_frontend_data(&query(sql)).await.unwrap();
+            assert_eq!(output.forward(), None, "{sql}");
+        }
+
+        // but the explicitly allowed ones are forwarded
+        let call = query("CALL p()");
+        let output = connection.process_frontend_data(&call).await.unwrap();
+        assert_eq!(output.forward(), Some(call));
+    }
+
+    #[tokio::test]
+    async fn check_function_calls() {
+        let identities = ockam::identity::identities().await.unwrap();
+        let identifier = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let expression =
+            ockam_abac::parse(&format!(r#"(!= query.command "{FUNCTION_CALL_COMMAND}")"#))
+                .unwrap()
+                .unwrap();
+        let mut connection = PostgresConnection::new(
+            Some(identifier),
+            None,
+            Some(PostgresQueryPolicy::new(
+                expression,
+                identities.identities_attributes(),
+                None,
+                Env::new(),
+            )),
+        );
+        connection
+            .process_frontend_data(&startup("alice"))
+            .await
+            .unwrap();
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // statements are still allowed
+        let output = connection
+            .process_frontend_data(&query("SELECT 1"))
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(query("SELECT 1")));
+        connection
+            .process_backend_data(&ready_for_query(b'I').encode())
+            .unwrap();
+
+        // but not the direct function calls
+        let mut body = 1234u32.to_be_bytes().to_vec();
+        body.extend_from_slice(&[0, 0, 0, 0, 0, 1]);
+        let function_call = Message::new(FUNCTION_CALL, body).encode();
+        let output = connection
+            .process_frontend_data(&function_call)
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), None);
+        let reply = read_messages(&output.reply().unwrap());
+        assert_eq!(reply[0].tag, Some(ERROR_RESPONSE));
+        assert_eq!(reply[1], ready_for_query(b'I'));
+
+        // without a policy, function calls are forwarded
+        connection.query_policy = None;
+        let output = connection
+            .process_frontend_data(&function_call)
+            .await
+            .unwrap();
+        assert_eq!(output.forward(), Some(function_call));
+    }
+}
@@ -0,0 +1,8 @@
+mod authentication;
+pub mod interceptor;
+pub mod portal;
+mod protocol;
+mod statement;
+
+pub use interceptor::{PostgresCredentials, PostgresInterceptorFactory, PostgresQueryPolicy};
+pub use portal::{PostgresOutletConfig, PostgresPortals};
@@ -0,0 +1,229 @@
+use crate::nodes::models::portal::{CreateOutlet, OutletAccessControl, OutletStatus};
+use crate::nodes::{BackgroundNodeClient, NodeManagerWorker};
+use crate::postgres::interceptor::{
+    PostgresCredentials, PostgresInterceptorFactory, PostgresQueryPolicy,
+};
+use crate::{ApiError, DefaultAddress};
+use minicbor::{CborLen, Decode, Encode};
+use ockam::flow_control::FlowControls;
+use ockam::{Address, Context, Result};
+use ockam_abac::expr::str;
+use ockam_abac::{Action, Env, PolicyExpression, Resource, ResourceType};
+use ockam_core::api::{Error, Request, Response};
+use ockam_core::async_trait;
+use ockam_transport_core::HostnamePort;
+use ockam_transport_tcp::{read_portal_payload_length, PortalOutletInterceptor};
+use std::sync::Arc;
+
+impl NodeManagerWorker {
+    pub(crate) async fn start_postgres_outlet_service(
+        &self,
+        ctx: &Context,
+        body: CreatePostgresOutlet,
+    ) -> Result<Response<OutletStatus>, Response<Error>> {
+        debug!("Starting Postgres Outlet service");
+        let CreateOutlet {
+            hostname_port,
+            worker_addr,
+            reachable_from_default_secure_channel: _,
+            policy_expression,
+            privileged,
+            tls,
+            skip_handshake,
+            enable_nagle,
+            ..
+        } = body.tcp_outlet;
+        let address = self
+            .node_manager
+            .registry
+            .outlets
+            .generate_worker_addr(worker_addr);
+        let outlet_address: Address = format!("{}_outlet", address.address()).into();
+
+        // Start the interceptor
+        self.create_postgres_outlet_interceptor(
+            ctx,
+            address.clone(),
+            outlet_address.clone(),
+            policy_expression.clone(),
+            body.postgres_config,
+        )
+        .await
+        .map_err(|e| Response::bad_request_no_request(&format!("{e:?}")))?;
+
+        // Start the outlet. It must only be reachable via the interceptor, so that the
+        // statements can't be sent to the server without being checked
+        match self
+            .node_manager
+            .create_outlet(
+                ctx,
+                hostname_port,
+                tls,
+                Some(outlet_address),
+                false,
+                OutletAccessControl::WithPolicyExpression(policy_expression),
+                privileged,
+                skip_handshake,
+                enable_nagle,
+            )
+            .await
+        {
+            Ok(outlet_status) => Ok(Response::ok().body(OutletStatus {
+                worker_addr: address,
+                ..outlet_status
+            })),
+            Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
+        }
+    }
+
+    async fn create_postgres_outlet_interceptor(
+        &self,
+        ctx: &Context,
+        interceptor_address: Address,
+        outlet_address: Address,
+        outlet_policy_expression: Option<PolicyExpression>,
+        postgres_config: PostgresOutletConfig,
+    ) -> Result<(), Error> {
+        debug!(%interceptor_address, %outlet_address, ?outlet_policy_expression, "Creating postgres outlet interceptor");
+        let default_secure_channel_listener_flow_control_id = ctx
+            .flow_controls()
+            .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
+            .ok_or_else(|| {
+                ApiError::core("Unable to get flow control for secure channel listener")
+            })?;
+
+        let policy_access_control = self
+            .node_manager
+            .policy_access_control(
+                self.node_manager.project_authority().clone(),
+                Resource::new(outlet_address.to_string(), ResourceType::TcpOutlet),
+                Action::HandleMessage,
+                outlet_policy_expression.clone(),
+            )
+            .await?;
+
+        let query_policy = postgres_config.query_policy.map(|expression| {
+            let mut env = Env::new();
+            env.put("resource.id", str(interceptor_address.address()));
+            PostgresQueryPolicy::new(
+                expression.into(),
+                self.node_manager
+                    .cli_state
+                    .identities_attributes(&self.node_manager.node_name()),
+                self.node_manager.project_authority(),
+                env,
+            )
+        });
+
+        let spawner_flow_control_id = FlowControls::generate_flow_control_id();
+
+        let postgres_interceptor_factory = Arc::new(PostgresInterceptorFactory::new(
+            postgres_config.credentials,
+            query_policy,
+        ));
+
+        PortalOutletInterceptor::create(
+            ctx,
+            interceptor_address.clone(),
+            Some(spawner_flow_control_id.clone()),
+            postgres_interceptor_factory,
+            Arc::new(policy_access_control.create_outgoing(ctx)?),
+            Arc::new(policy_access_control.create_incoming()),
+            read_portal_payload_length(),
+        )?;
+
+        // every secure channel can reach this service
+        let flow_controls = ctx.flow_controls();
+        flow_controls.add_consumer(
+            &interceptor_address,
+            &default_secure_channel_listener_flow_control_id,
+        );
+
+        // this spawner flow control id is used to control communication with dynamically created
+        // outlets
+        flow_controls.add_spawner(&interceptor_address, &spawner_flow_control_id);
+
+        // allow communication with the tcp outlet
+        flow_controls.add_consumer(&outlet_address, &spawner_flow_control_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait PostgresPortals {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_postgres_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        tls: bool,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        postgres_config: PostgresOutletConfig,
+    ) -> miette::Result<OutletStatus>;
+}
+
+#[async_trait]
+impl PostgresPortals for BackgroundNodeClient {
+    #[instrument(skip(self, ctx, postgres_config))]
+    #[allow(clippy::too_many_arguments)]
+    async fn create_postgres_outlet(
+        &self,
+        ctx: &Context,
+        to: HostnamePort,
+        tls: bool,
+        from: Option<&Address>,
+        policy_expression: Option<PolicyExpression>,
+        postgres_config: PostgresOutletConfig,
+    ) -> miette::Result<OutletStatus> {
+        let mut outlet_payload =
+            CreateOutlet::new(to, tls, from.cloned(), false, false, false, false);
+        if let Some(policy_expression) = policy_expression {
+            outlet_payload.set_policy_expression(policy_expression);
+        }
+        let payload = CreatePostgresOutlet::new(outlet_payload, postgres_config);
+        let req = Request::post("/node/postgres_outlet").body(payload);
+        self.ask(ctx, req).await
+    }
+}
+
+/// Request body to create a postgres outlet
+#[derive(Clone, Debug, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreatePostgresOutlet {
+    #[n(1)] pub(crate) tcp_outlet: CreateOutlet,
+    #[n(2)] pub(crate) postgres_config: PostgresOutletConfig,
+}
+
+impl CreatePostgresOutlet {
+    pub fn new(tcp_outlet: CreateOutlet, postgres_config: PostgresOutletConfig) -> Self {
+        Self {
+            tcp_outlet,
+            postgres_config,
+        }
+    }
+}
+
+/// Configuration of the Postgres protocol interceptor of an outlet
+#[derive(Clone, Debug, Default, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PostgresOutletConfig {
+    /// Credentials used to authenticate to the server on behalf of the clients
+    #[n(1)] pub(crate) credentials: Option<PostgresCredentials>,
+    /// Policy checked for each statement, see [`PostgresQueryPolicy`]
+    #[n(2)] pub(crate) query_policy: Option<PolicyExpression>,
+}
+
+impl PostgresOutletConfig {
+    pub fn new(
+        credentials: Option<PostgresCredentials>,
+        query_policy: Option<PolicyExpression>,
+    ) -> Self {
+        Self {
+            credentials,
+            query_policy,
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use ockam::errcode::{Kind, Origin};
+use ockam_core::Result;
+
+/// Protocol version 3.0, sent in the startup message
+pub(crate) const PROTOCOL_VERSION_3: i32 = 196608;
+/// Code sent by a client to request an SSL encrypted connection
+pub(crate) const SSL_REQUEST_CODE: i32 = 80877103;
+/// Code sent by a client to request a GSSAPI encrypted connection
+pub(crate) const GSSENC_REQUEST_CODE: i32 = 80877104;
+/// Code sent by a client to cancel a running query, on a new connection
+pub(crate) const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// Frontend message tags
+pub(crate) const QUERY: u8 = b'Q';
+pub(crate) const PARSE: u8 = b'P';
+pub(crate) const FUNCTION_CALL: u8 = b'F';
+pub(crate) const SYNC: u8 = b'S';
+pub(crate) const PASSWORD: u8 = b'p';
+
+/// Backend message tags
+pub(crate) const AUTHENTICATION: u8 = b'R';
+pub(crate) const ERROR_RESPONSE: u8 = b'E';
+pub(crate) const READY_FOR_QUERY: u8 = b'Z';
+
+/// Authentication request codes
+pub(crate) const AUTHENTICATION_OK: i32 = 0;
+pub(crate) const AUTHENTICATION_CLEARTEXT_PASSWORD: i32 = 3;
+pub(crate) const AUTHENTICATION_MD5_PASSWORD: i32 = 5;
+pub(crate) const AUTHENTICATION_SASL: i32 = 10;
+pub(crate) const AUTHENTICATION_SASL_CONTINUE: i32 = 11;
+pub(crate) const AUTHENTICATION_SASL_FINAL: i32 = 12;
+
+/// SQLSTATE code used when a statement is rejected
+pub(crate) const INSUFFICIENT_PRIVILEGE: &str = "42501";
+/// SQLSTATE code used when the connection cannot be authenticated
+pub(crate) const INVALID_AUTHORIZATION_SPECIFICATION: &str = "28000";
+
+/// A complete message of the PostgreSQL wire protocol.
+///
+/// All messages have a 1 byte tag, followed by their length, except the messages sent by a client
+/// at the beginning of a connection (startup, SSL request, cancel request),
+/// which only have a length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Message {
+    pub(crate) tag: Option<u8>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Message {
+    pub(crate) fn new(tag: u8, body: Vec<u8>) -> Self {
+        Self {
+            tag: Some(tag),
+            body,
+        }
+    }
+
+    pub(crate) fn untagged(body: Vec<u8>) -> Self {
+        Self { tag: None, body }
+    }
+
+    /// Serialize the message with its tag and length
+    pub(crate) fn encode_into(&self, buffer: &mut Vec<u8>) {
+        if let Some(tag) = self.tag {
+            buffer.push(tag);
+        }
+        buffer.extend_from_slice(&((self.body.len() + 4) as i32).to_be_bytes());
+        buffer.extend_from_slice(&self.body);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.body.len() + 5);
+        self.encode_into(&mut buffer);
+        buffer
+    }
+
+    /// Return the first i32 of the message body
+    pub(crate) fn code(&self) -> Result<i32> {
+        read_i32(&self.body, 0)
+    }
+}
+
+/// Accumulate the data received on a connection and split it into complete messages.
+/// Messages can be split across several buffers, and a buffer can contain several messages.
+#[derive(Debug, Default)]
+pub(crate) struct MessageReader {
+    buffer: Vec<u8>,
+}
+
+impl MessageReader {
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Return the next complete message, if there is one.
+    /// `tagged` must be false for the messages sent by a client before the startup message.
+    pub(crate) fn next_message(&mut self, tagged: bool) -> Result<Option<Message>> {
+        let header_size = if tagged { 5 } else { 4 };
+        if self.buffer.len() < header_size {
+            return Ok(None);
+        }
+        let length = read_i32(&self.buffer, header_size - 4)?;
+        if length < 4 {
+            return Err(protocol_error(format!("invalid message length {length}")));
+        }
+        let total_size = header_size - 4 + length as usize;
+        if self.buffer.len() < total_size {
+            return Ok(None);
+        }
+        let message: Vec<u8> = self.buffer.drain(..total_size).collect();
+        Ok(Some(if tagged {
+            Message::new(message[0], message[5..].to_vec())
+        } else {
+            Message::untagged(message[4..].to_vec())
+        }))
+    }
+}
+
+/// Parameters sent by a client in a startup message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StartupParameters {
+    parameters: Vec<(String, String)>,
+}
+
+impl StartupParameters {
+    /// Decode the body of a startup message: protocol version followed by key/value pairs
+    pub(crate) fn decode(body: &[u8]) -> Result<Self> {
+        let mut parameters = vec![];
+        let mut position = 4;
+        loop {
+            let (key, next) = read_cstring(body, position)?;
+            if key.is_empty() {
+                break;
+            }
+            let (value, next) = read_cstring(body, next)?;
+            parameters.push((key, value));
+            position = next;
+        }
+        Ok(Self { parameters })
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub(crate) fn set(&mut self, key: &str, value: &str) {
+        match self.parameters.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.parameters.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub(crate) fn encode(&self) -> Message {
+        let mut body = PROTOCOL_VERSION_3.to_be_bytes().to_vec();
+        for (key, value) in &self.parameters {
+            write_cstring(&mut body, key);
+            write_cstring(&mut body, value);
+        }
+        body.push(0);
+        Message::untagged(body)
+    }
+}
+
+/// Return the query string of a simple query message
+pub(crate) fn query_string(message: &Message) -> Result<String> {
+    Ok(read_cstring(&message.body, 0)?.0)
+}
+
+/// Return the query string of a parse message, which comes after the prepared statement name
+pub(crate) fn parse_query_string(message: &Message) -> Result<String> {
+    let (_name, next) = read_cstring(&message.body, 0)?;
+    Ok(read_cstring(&message.body, next)?.0)
+}
+
+/// Return the object ID of the function called by a function call message
+pub(crate) fn function_call_oid(message: &Message) -> Result<u32> {
+    let oid = message
+        .body
+        .get(..4)
+        .ok_or_else(|| protocol_error("truncated function call message"))?;
+    Ok(u32::from_be_bytes([oid[0], oid[1], oid[2], oid[3]]))
+}
+
+/// Create an error response message
+pub(crate) fn error_response(code: &str, message: &str) -> Message {
+    let mut body = vec![];
+    for (field, value) in [
+        (b'S', "ERROR"),
+        (b'V', "ERROR"),
+        (b'C', code),
+        (b'M', message),
+    ] {
+        body.push(field);
+        write_cstring(&mut body, value);
+    }
+    body.push(0);
+    Message::new(ERROR_RESPONSE, body)
+}
+
+/// Create a ready for query message with the current transaction status
+pub(crate) fn ready_for_query(transaction_status: u8) -> Message {
+    Message::new(READY_FOR_QUERY, vec![transaction_status])
+}
+
+/// Create a password message with a cleartext or md5 password
+pub(crate) fn password_message(password: &str) -> Message {
+    let mut body = vec![];
+    write_cstring(&mut body, password);
+    Message::new(PASSWORD, body)
+}
+
+/// Create the initial SASL response, selecting a mechanism
+pub(crate) fn sasl_initial_response(mechanism: &str, data: &[u8]) -> Message {
+    let mut body = vec![];
+    write_cstring(&mut body, mechanism);
+    body.extend_from_slice(&(data.len() as i32).to_be_bytes());
+    body.extend_from_slice(data);
+    Message::new(PASSWORD, body)
+}
+
+/// Create a SASL response
+pub(crate) fn sasl_response(data: &[u8]) -> Message {
+    Message::new(PASSWORD, data.to_vec())
+}
+
+/// Return the list of SASL mechanisms proposed by the server
+pub(crate) fn sasl_mechanisms(message: &Message) -> Result<Vec<String>> {
+    let mut mechanisms = vec![];
+    let mut position = 4;
+    loop {
+        let (mechanism, next) = read_cstring(&message.body, position)?;
+        if mechanism.is_empty() {
+            break;
+        }
+        mechanisms.push(mechanism);
+        position = next;
+    }
+    Ok(mechanisms)
+}
+
+fn read_i32(buffer: &[u8], position: usize) -> Result<i32> {
+    buffer
+        .get(position..position + 4)
+        .map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| protocol_error("message too short"))
+}
+
+/// Read a null-terminated string and return the position following it
+fn read_cstring(buffer: &[u8], position: usize) -> Result<(String, usize)> {
+    let remaining = buffer
+        .get(position..)
+        .ok_or_else(|| protocol_error("message too short"))?;
+    let end = remaining
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(|| protocol_error("missing string terminator"))?;
+    let string = String::from_utf8(remaining[..end].to_vec())
+        .map_err(|_| protocol_error("invalid utf-8 string"))?;
+    Ok((string, position + end + 1))
+}
+
+fn write_cstring(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(value.as_bytes());
+    buffer.push(0);
+}
+
+pub(crate) fn protocol_error(message: impl Into<String>) -> ockam_core::Error {
+    ockam_core::Error::new(
+        Origin::Transport,
+        Kind::Invalid,
+        format!("invalid postgres message: {}", message.into()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_messages_split_across_buffers() {
+        let mut data = vec![];
+        let mut parameters = StartupParameters {
+            parameters: vec![("user".into(), "alice".into())],
+        };
+        parameters.set("database", "db");
+        parameters.encode().encode_into(&mut data);
+        Message::new(QUERY, b"SELECT 1\0".to_vec()).encode_into(&mut data);
+
+        for size in [1, 5, 32, 1024] {
+            let mut reader = MessageReader::default();
+            let mut messages = vec![];
+            let mut tagged = false;
+            for chunk in data.chunks(size) {
+                reader.push(chunk);
+                while let Some(message) = reader.next_message(tagged).unwrap() {
+                    messages.push(message);
+                    tagged = true;
+                }
+            }
+            assert_eq!(messages.len(), 2);
+            let decoded = StartupParameters::decode(&messages[0].body).unwrap();
+            assert_eq!(decoded, parameters);
+            assert_eq!(decoded.get("database"), Some("db"));
+            assert_eq!(query_string(&messages[1]).unwrap(), "SELECT 1");
+        }
+    }
+}
@@ -0,0 +1,512 @@
+/// A SQL statement sent by a client, with the commands it executes.
+///
+/// The commands are the uppercased first keywords of the statement, for example `SELECT`,
+/// `INSERT` or `CREATE`. A statement starting with a `WITH` clause executes the command of its
+/// main statement, and the commands of each of its common table expressions, since these
+/// expressions can also modify data.
+///
+/// The statements containing another statement also execute the commands of that statement:
+///
+///  - `EXPLAIN [ANALYZE] [VERBOSE] statement`, since `ANALYZE` executes the statement
+///  - `PREPARE name AS statement`, executed later with `EXECUTE`
+///  - `CREATE RULE ... DO [ALSO | INSTEAD] statement`, executed with the statements matching the
+///    rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Statement {
+    pub(crate) sql: String,
+    pub(crate) commands: Vec<String>,
+}
+
+/// Split a query string into statements.
+/// Empty statements are ignored.
+///
+/// Return `None` if the statements can't be parsed without knowing the configuration of the
+/// server. This is the case for string constants containing a backslash, without the `E'...'`
+/// escape string syntax, since the backslash escapes a quote only if the
+/// `standard_conforming_strings` setting is off.
+pub(crate) fn parse_statements(query: &str) -> Option<Vec<Statement>> {
+    let tokens = tokenize(query)?;
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut depth = 0;
+    for (i, (token, position)) in tokens.iter().enumerate() {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth -= 1,
+            Token::Semicolon if depth <= 0 => {
+                push_statement(&mut statements, query, &tokens[start..i], *position);
+                start = i + 1;
+                depth = 0;
+            }
+            _ => {}
+        }
+    }
+    push_statement(&mut statements, query, &tokens[start..], query.len());
+    Some(statements)
+}
+
+fn push_statement(
+    statements: &mut Vec<Statement>,
+    query: &str,
+    tokens: &[(Token, usize)],
+    end: usize,
+) {
+    if let Some((_, start)) = tokens.first() {
+        let commands = statement_commands(tokens);
+        if !commands.is_empty() {
+            statements.push(Statement {
+                sql: query[*start..end].trim().to_string(),
+                commands,
+            });
+        }
+    }
+}
+
+/// Return the commands executed by a statement
+fn statement_commands(tokens: &[(Token, usize)]) -> Vec<String> {
+    let mut commands = vec![];
+    let mut i = 0;
+    // skip the parentheses around a statement, like `(SELECT 1) UNION (SELECT 2)`
+    while let Some((Token::Open, _)) = tokens.get(i) {
+        i += 1;
+    }
+    match tokens.get(i) {
+        Some((Token::Word(word), _)) if word == "WITH" => {
+            i += 1;
+            if let Some((Token::Word(word), _)) = tokens.get(i) {
+                if word == "RECURSIVE" {
+                    i += 1;
+                }
+            }
+            // each common table expression: name [(columns)] AS [[NOT] MATERIALIZED] (statement)
+            loop {
+                i = skip_to_word(tokens, i, "AS") + 1;
+                while let Some((Token::Word(word), _)) = tokens.get(i) {
+                    if word == "NOT" || word == "MATERIALIZED" {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let end = skip_group(tokens, i);
+                if end > i + 1 {
+                    commands.extend(statement_commands(&tokens[i + 1..end - 1]));
+                }
+                i = end;
+                match tokens.get(i) {
+                    Some((Token::Comma, _)) => i += 1,
+                    _ => break,
+                }
+            }
+            commands.extend(statement_commands(tokens.get(i..).unwrap_or_default()));
+        }
+        Some((Token::Word(word), _)) if word == "EXPLAIN" => {
+            commands.push(word.clone());
+            i += 1;
+            // options: EXPLAIN (ANALYZE, VERBOSE) statement or EXPLAIN ANALYZE VERBOSE statement
+            if let Some((Token::Open, _)) = tokens.get(i) {
+                i = skip_group(tokens, i);
+            }
+            while let Some((Token::Word(word), _)) = tokens.get(i) {
+                if ["ANALYZE", "ANALYSE", "VERBOSE"].contains(&word.as_str()) {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            commands.extend(statement_commands(tokens.get(i..).unwrap_or_default()));
+        }
+        Some((Token::Word(word), _)) if word == "PREPARE" => {
+            // PREPARE name [(types)] AS statement
+            commands.push(word.clone());
+            i = skip_to_word(tokens, i + 1, "AS") + 1;
+            commands.extend(statement_commands(tokens.get(i..).unwrap_or_default()));
+        }
+        Some((Token::Word(word), _)) if word == "CREATE" && is_create_rule(&tokens[i + 1..]) => {
+            // CREATE [OR REPLACE] RULE ... DO [ALSO | INSTEAD] { NOTHING | command | (commands) }
+            commands.push(word.clone());
+            i = skip_to_word(tokens, i + 1, "DO") + 1;
+            while let Some((Token::Word(word), _)) = tokens.get(i) {
+                if word == "ALSO" || word == "INSTEAD" || word == "NOTHING" {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            match tokens.get(i) {
+                Some((Token::Open, _)) => {
+                    let end = skip_group(tokens, i);
+                    let rule_commands = tokens.get(i + 1..end - 1).unwrap_or_default();
+                    for statement in rule_commands.split(|(t, _)| *t == Token::Semicolon) {
+                        commands.extend(statement_commands(statement));
+                    }
+                }
+                _ => commands.extend(statement_commands(tokens.get(i..).unwrap_or_default())),
+            }
+        }
+        Some((Token::Word(word), _)) => commands.push(word.clone()),
+        _ => {}
+    }
+    commands.dedup();
+    commands
+}
+
+/// Return true if the tokens following a `CREATE` keyword create a rule
+fn is_create_rule(tokens: &[(Token, usize)]) -> bool {
+    let words: Vec<&str> = tokens
+        .iter()
+        .take(3)
+        .map(|(t, _)| match t {
+            Token::Word(word) => word.as_str(),
+            _ => "",
+        })
+        .collect();
+    matches!(words.as_slice(), ["RULE", ..] | ["OR", "REPLACE", "RULE"])
+}
+
+/// Return the index of a keyword, skipping the parenthesized groups,
+/// or the number of tokens if the keyword is not found
+fn skip_to_word(tokens: &[(Token, usize)], start: usize, word: &str) -> usize {
+    let mut i = start;
+    while let Some((token, _)) = tokens.get(i) {
+        if matches!(token, Token::Word(w) if w == word) {
+            break;
+        }
+        i = skip_group(tokens, i);
+    }
+    i
+}
+
+/// Return the index of the token following a token, or following a parenthesized group
+fn skip_group(tokens: &[(Token, usize)], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while let Some((token, _)) = tokens.get(i) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+        if depth <= 0 {
+            break;
+        }
+    }
+    i
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Open,
+    Close,
+    Comma,
+    Semicolon,
+    Other,
+}
+
+/// Split a query into tokens, with their position in the query.
+/// String literals, quoted identifiers and comments are skipped.
+/// Return `None` if a string constant is ambiguous.
+fn tokenize(query: &str) -> Option<Vec<(Token, usize)>> {
+    let bytes = query.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = find(bytes, i + 2, b"\n")
+                    .map(|p| p + 1)
+                    .unwrap_or(bytes.len());
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                // block comments can be nested
+                let mut depth = 1;
+                i += 2;
+                while i < bytes.len() && depth > 0 {
+                    if bytes[i..].starts_with(b"/*") {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i..].starts_with(b"*/") {
+                        depth -= 1;
+                        i += 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            b'\'' | b'"' => {
+                tokens.push((Token::Other, i));
+                let end = skip_quoted(bytes, i, c);
+                // the backslashes of a standard string are escape characters
+                // when standard_conforming_strings is off
+                if c == b'\'' && bytes[i..end].contains(&b'\\') {
+                    return None;
+                }
+                i = end;
+            }
+            b'$' if i == 0 || !is_identifier_char(bytes[i - 1]) => {
+                // dollar-quoted string: $tag$ ... $tag$
+                // the tag follows the identifier rules, without any `$`, so that `$1` is a
+                // parameter and `a$b$` an identifier
+                let tag_end = match bytes.get(i + 1) {
+                    Some(b) if is_identifier_start(*b) => bytes[i + 1..]
+                        .iter()
+                        .position(|b| !is_identifier_char(*b) || *b == b'$')
+                        .map(|p| i + 1 + p),
+                    _ => Some(i + 1),
+                };
+                match tag_end {
+                    Some(tag_end) if bytes.get(tag_end) == Some(&b'$') => {
+                        let tag = &bytes[i..=tag_end];
+                        tokens.push((Token::Other, i));
+                        i = find(bytes, tag_end + 1, tag)
+                            .map(|p| p + tag.len())
+                            .unwrap_or(bytes.len());
+                    }
+                    _ => {
+                        tokens.push((Token::Other, i));
+                        i += 1;
+                    }
+                }
+            }
+            b'(' => {
+                tokens.push((Token::Open, i));
+                i += 1;
+            }
+            b')' => {
+                tokens.push((Token::Close, i));
+                i += 1;
+            }
+            b',' => {
+                tokens.push((Token::Comma, i));
+                i += 1;
+            }
+            b';' => {
+                tokens.push((Token::Semicolon, i));
+                i += 1;
+            }
+            c if is_identifier_start(c) => {
+                let start = i;
+                while i < bytes.len() && is_identifier_char(bytes[i]) {
+                    i += 1;
+                }
+                let word = query[start..i].to_ascii_uppercase();
+                match (word.as_str(), bytes.get(i), bytes.get(i + 1)) {
+                    // escape string: E'...', with backslash escapes
+                    ("E", Some(b'\''), _) => {
+                        tokens.push((Token::Other, start));
+                        i = skip_escape_string(bytes, i);
+                    }
+                    // bit strings and unicode strings, without backslash escapes
+                    ("B" | "X", Some(b'\''), _) => {
+                        tokens.push((Token::Other, start));
+                        i = skip_quoted(bytes, i, b'\'');
+                    }
+                    ("U", Some(b'&'), Some(b'\'')) => {
+                        tokens.push((Token::Other, start));
+                        i = skip_quoted(bytes, i + 1, b'\'');
+                    }
+                    _ => tokens.push((Token::Word(word), start)),
+                }
+            }
+            c if c.is_ascii_whitespace() => i += 1,
+            _ => {
+                tokens.push((Token::Other, i));
+                // skip a whole utf-8 character
+                i += query[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// Return true if a byte can start an identifier or a keyword.
+/// Non-ascii characters are always part of identifiers
+fn is_identifier_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || !b.is_ascii()
+}
+
+/// Return true if a byte can continue an identifier or a keyword, which can contain `$`
+fn is_identifier_char(b: u8) -> bool {
+    is_identifier_start(b) || b.is_ascii_digit() || b == b'$'
+}
+
+/// Skip a quoted string or identifier, where quotes are escaped by doubling them
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Skip an escape string, where quotes are escaped by doubling them or with a backslash
+fn skip_escape_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+            b'\'' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn find(bytes: &[u8], from: usize, pattern: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(pattern.len())
+        .position(|w| w == pattern)
+        .map(|p| p + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(query: &str) -> Vec<Vec<String>> {
+        parse_statements(query)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.commands)
+            .collect()
+    }
+
+    #[test]
+    fn simple_statements() {
+        assert_eq!(commands("select * from users"), vec![vec!["SELECT"]]);
+        assert_eq!(
+            commands("INSERT INTO t VALUES (1); delete from t;"),
+            vec![vec!["INSERT"], vec!["DELETE"]]
+        );
+        assert_eq!(commands("  ;; "), Vec::<Vec<String>>::new());
+        assert_eq!(
+            commands("(SELECT 1) UNION (SELECT 2)"),
+            vec![vec!["SELECT"]]
+        );
+    }
+
+    #[test]
+    fn statements_with_comments_and_strings() {
+        assert_eq!(
+            commands(
+                "-- drop table t;\n/* delete /* nested */ ; */ SELECT ';DROP TABLE t' AS \"a;b\""
+            ),
+            vec![vec!["SELECT"]]
+        );
+        assert_eq!(
+            commands("SELECT $$ ; DROP TABLE t $$, $x$;$x$; UPDATE t SET a = 'it''s;'"),
+            vec![vec!["SELECT"], vec!["UPDATE"]]
+        );
+    }
+
+    #[test]
+    fn statements_with_dollar_signs_in_identifiers() {
+        assert_eq!(
+            commands("SELECT 1 AS a$b$; DELETE FROM t; SELECT 1 AS c$b$"),
+            vec![vec!["SELECT"], vec!["DELETE"], vec!["SELECT"]]
+        );
+        assert_eq!(
+            commands("SELECT $1, $2;DELETE FROM t WHERE a = $1"),
+            vec![vec!["SELECT"], vec!["DELETE"]]
+        );
+        assert_eq!(
+            commands("SELECT 1 AS é$$; DELETE FROM t; $$"),
+            vec![vec!["SELECT"], vec!["DELETE"]]
+        );
+    }
+
+    #[test]
+    fn statements_with_escape_strings() {
+        assert_eq!(
+            commands("SELECT E'\\''; DELETE FROM t; --'"),
+            vec![vec!["SELECT"], vec!["DELETE"]]
+        );
+        assert_eq!(
+            commands("SELECT e'\\\\', E'it''s;'; DELETE FROM t"),
+            vec![vec!["SELECT"], vec!["DELETE"]]
+        );
+        assert_eq!(
+            commands("SELECT U&'\\0041;', B'01', X'1F'; DELETE FROM t"),
+            vec![vec!["SELECT"], vec!["DELETE"]]
+        );
+        // the end of a standard string containing a backslash depends on the server settings
+        assert_eq!(parse_statements("SELECT '\\'; DELETE FROM t; --'"), None);
+        assert_eq!(parse_statements("SELECT N'\\'; DELETE FROM t; --'"), None);
+    }
+
+    #[test]
+    fn statements_with_common_table_expressions() {
+        assert_eq!(
+            commands("WITH a AS (SELECT 1), b(x) AS MATERIALIZED (SELECT 2) SELECT * FROM a, b"),
+            vec![vec!["SELECT"]]
+        );
+        assert_eq!(
+            commands(
+                "with recursive d as (delete from t returning *) insert into u select * from d"
+            ),
+            vec![vec!["DELETE", "INSERT"]]
+        );
+    }
+
+    #[test]
+    fn statements_containing_statements() {
+        assert_eq!(
+            commands("EXPLAIN ANALYZE VERBOSE DELETE FROM t"),
+            vec![vec!["EXPLAIN", "DELETE"]]
+        );
+        assert_eq!(
+            commands("explain (analyze, format json) update t set a = 1"),
+            vec![vec!["EXPLAIN", "UPDATE"]]
+        );
+        assert_eq!(
+            commands("PREPARE p (int, text) AS DELETE FROM t WHERE a = $1; EXECUTE p(1, 'a')"),
+            vec![vec!["PREPARE", "DELETE"], vec!["EXECUTE"]]
+        );
+        assert_eq!(
+            commands("CREATE OR REPLACE RULE r AS ON SELECT TO t DO INSTEAD DELETE FROM u"),
+            vec![vec!["CREATE", "DELETE"]]
+        );
+        assert_eq!(
+            commands(
+                "CREATE RULE r AS ON INSERT TO t WHERE (new.a > 1) \
+                 DO ALSO (UPDATE u SET a = 1; DELETE FROM v)"
+            ),
+            vec![vec!["CREATE", "UPDATE", "DELETE"]]
+        );
+        assert_eq!(
+            commands("CREATE RULE r AS ON DELETE TO t DO INSTEAD NOTHING"),
+            vec![vec!["CREATE"]]
+        );
+        assert_eq!(
+            commands("WITH x AS (DELETE FROM t RETURNING *) SELECT * FROM x"),
+            vec![vec!["DELETE", "SELECT"]]
+        );
+    }
+
+    #[test]
+    fn statements_running_code() {
+        assert_eq!(
+            commands("DO $$BEGIN DELETE FROM t; END$$; CALL p(); EXECUTE q"),
+            vec![vec!["DO"], vec!["CALL"], vec!["EXECUTE"]]
+        );
+    }
+
+    #[test]
+    fn statement_sql() {
+        let statements = parse_statements("SELECT 1;\n  UPDATE t SET a = 1 ").unwrap();
+        assert_eq!(statements[0].sql, "SELECT 1");
+        assert_eq!(statements[1].sql, "UPDATE t SET a = 1");
+    }
+}
@@ -1,7 +1,20 @@
-use crate::common::common::{default_configuration, start_authority_node};
+use crate::common::common::{
+    change_client_identifier, default_configuration, start_authority, start_authority_node,
+    start_authority_with_configuration, AuthorityInfo,
+};
+use core::time::Duration;
+use ockam::identity::{secure_channels, Identifier, RevocationListRefresher, SecureChannels};
+use ockam_api::authenticator::direct::{
+    Members, OCKAM_ROLE_ATTRIBUTE_ENROLLER_VALUE, OCKAM_ROLE_ATTRIBUTE_KEY,
+};
+use ockam_api::authenticator::revocation::RevocationLists;
+use ockam_api::enroll::enrollment::Enrollment;
 use ockam_api::nodes::service::default_address::DefaultAddress;
-use ockam_core::{Address, Result};
+use ockam_core::{route, Address, Result};
 use ockam_node::Context;
+use ockam_transport_tcp::TcpTransport;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 mod common;
 
@@ -16,6 +29,7 @@ async fn authority_starts_with_default_configuration(ctx: &mut Context) -> Resul
     assert!(!workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR)));
     assert!(!workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ISSUER)));
     assert!(workers.contains(&Address::from(DefaultAddress::CREDENTIAL_ISSUER)));
+    assert!(workers.contains(&Address::from(DefaultAddress::REVOCATION_LIST)));
     assert!(workers.contains(&Address::from(DefaultAddress::SECURE_CHANNEL_LISTENER)));
     assert!(workers.contains(&Address::from(DefaultAddress::ECHO_SERVICE)));
 
@@ -34,6 +48,7 @@ async fn authority_starts_direct_authenticator(ctx: &mut Context) -> Result<()>
     assert!(!workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR)));
     assert!(!workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ISSUER)));
     assert!(workers.contains(&Address::from(DefaultAddress::CREDENTIAL_ISSUER)));
+    assert!(workers.contains(&Address::from(DefaultAddress::REVOCATION_LIST)));
     assert!(workers.contains(&Address::from(DefaultAddress::SECURE_CHANNEL_LISTENER)));
     assert!(workers.contains(&Address::from(DefaultAddress::ECHO_SERVICE)));
 
@@ -52,8 +67,303 @@ async fn authority_starts_enrollment_token(ctx: &mut Context) -> Result<()> {
     assert!(workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR)));
     assert!(workers.contains(&Address::from(DefaultAddress::ENROLLMENT_TOKEN_ISSUER)));
     assert!(workers.contains(&Address::from(DefaultAddress::CREDENTIAL_ISSUER)));
+    assert!(workers.contains(&Address::from(DefaultAddress::REVOCATION_LIST)));
     assert!(workers.contains(&Address::from(DefaultAddress::SECURE_CHANNEL_LISTENER)));
     assert!(workers.contains(&Address::from(DefaultAddress::ECHO_SERVICE)));
 
     Ok(())
 }
+
+#[ockam_macros::test]
+async fn revoked_member_credential_is_rejected(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let AuthorityInfo {
+        authority_identifier,
+        admins,
+    } = start_authority(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let member = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    admin
+        .client
+        .add_member(ctx, member.clone(), Default::default())
+        .await
+        .unwrap();
+    let member_client = change_client_identifier(&admin.client, &member, None);
+    let credential = member_client.issue_credential(ctx).await.unwrap();
+
+    let verification = secure_channels
+        .identities()
+        .credentials()
+        .credentials_verification();
+    verification
+        .receive_presented_credential(&member, &[authority_identifier.clone()], &credential)
+        .await?;
+
+    admin
+        .client
+        .revoke_member(ctx, member.clone())
+        .await
+        .unwrap();
+
+    // the revoked member is not a member anymore and can't get a new credential
+    assert!(member_client.issue_credential(ctx).await.is_err());
+
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let revocation_list_data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert_eq!(revocation_list_data.revoked_subjects, vec![member.clone()]);
+    assert!(
+        verification
+            .receive_revocation_list(&[authority_identifier.clone()], &revocation_list)
+            .await?
+    );
+
+    let res = verification
+        .receive_presented_credential(&member, &[authority_identifier], &credential)
+        .await;
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn member_cant_revoke_member(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let AuthorityInfo { admins, .. } = start_authority(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let member1 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let member2 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    for member in [&member1, &member2] {
+        admin
+            .client
+            .add_member(ctx, member.clone(), Default::default())
+            .await
+            .unwrap();
+    }
+
+    let member_client = change_client_identifier(&admin.client, &member1, None);
+    let res = member_client.revoke_member(ctx, member2.clone()).await;
+    assert!(res.is_err());
+
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let revocation_list_data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert!(revocation_list_data.revoked_subjects.is_empty());
+    assert_eq!(admin.client.list_member_ids(ctx).await.unwrap().len(), 2);
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn enroller_cant_unrevoke_enroller(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let mut configuration = default_configuration().await?;
+    configuration.enforce_admin_checks = true;
+    let AuthorityInfo { admins, .. } =
+        start_authority_with_configuration(ctx, secure_channels.clone(), 1, configuration).await?;
+    let admin = &admins[0];
+
+    let enroller1 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let enroller2 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let attributes = BTreeMap::from([(
+        OCKAM_ROLE_ATTRIBUTE_KEY.to_string(),
+        OCKAM_ROLE_ATTRIBUTE_ENROLLER_VALUE.to_string(),
+    )]);
+    for enroller in [&enroller1, &enroller2] {
+        admin
+            .client
+            .add_member(ctx, enroller.clone(), attributes.clone())
+            .await
+            .unwrap();
+    }
+    admin
+        .client
+        .revoke_member(ctx, enroller2.clone())
+        .await
+        .unwrap();
+
+    // the revoked enroller is not a member anymore, but its revocation can only be lifted by an admin
+    let enroller_client = change_client_identifier(&admin.client, &enroller1, None);
+    let res = enroller_client
+        .unrevoke_member(ctx, enroller2.clone())
+        .await;
+    assert!(res.is_err());
+
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let revocation_list_data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert_eq!(
+        revocation_list_data.revoked_subjects,
+        vec![enroller2.clone()]
+    );
+
+    admin
+        .client
+        .unrevoke_member(ctx, enroller2.clone())
+        .await
+        .unwrap();
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let revocation_list_data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert!(revocation_list_data.revoked_subjects.is_empty());
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn revocation_list_is_sent_to_subscribers(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let AuthorityInfo {
+        authority_identifier,
+        admins,
+    } = start_authority(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let node = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let member = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    for identifier in [&node, &member] {
+        admin
+            .client
+            .add_member(ctx, identifier.clone(), Default::default())
+            .await
+            .unwrap();
+    }
+
+    // the refresh interval is long enough to make sure that the new list is sent by the authority
+    RevocationListRefresher::new(
+        Arc::new(TcpTransport::create(ctx)?),
+        secure_channels.clone(),
+        authority_identifier.clone(),
+        vec![route![DefaultAddress::SECURE_CHANNEL_LISTENER]],
+        node,
+    )
+    .with_refresh_interval(Duration::from_secs(3600))
+    .start(ctx)?;
+
+    // the current revocation list is sent when the node subscribes
+    let repository = secure_channels.identities().revocation_list_repository();
+    let mut received = false;
+    for _ in 0..50 {
+        received = repository
+            .get_revocation_list(&authority_identifier)
+            .await?
+            .is_some();
+        if received {
+            break;
+        }
+        ctx.sleep(Duration::from_millis(100)).await;
+    }
+    assert!(
+        received,
+        "the current revocation list must be sent on subscription"
+    );
+
+    admin
+        .client
+        .revoke_member(ctx, member.clone())
+        .await
+        .unwrap();
+
+    assert!(
+        has_revocation_status(ctx, &secure_channels, &authority_identifier, &member, true).await?,
+        "the revocation list must be sent to the subscribed node"
+    );
+
+    // the revocation can be lifted when the member is added again
+    admin
+        .client
+        .add_member(ctx, member.clone(), Default::default())
+        .await
+        .unwrap();
+    admin
+        .client
+        .unrevoke_member(ctx, member.clone())
+        .await
+        .unwrap();
+
+    assert!(
+        has_revocation_status(ctx, &secure_channels, &authority_identifier, &member, false).await?,
+        "the new revocation list must be sent to the subscribed node"
+    );
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn non_member_cant_subscribe_to_revocation_lists(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let AuthorityInfo {
+        authority_identifier,
+        ..
+    } = start_authority(ctx, secure_channels.clone(), 1).await?;
+
+    let node = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    RevocationListRefresher::new(
+        Arc::new(TcpTransport::create(ctx)?),
+        secure_channels.clone(),
+        authority_identifier.clone(),
+        vec![route![DefaultAddress::SECURE_CHANNEL_LISTENER]],
+        node,
+    )
+    .with_refresh_interval(Duration::from_secs(3600))
+    .start(ctx)?;
+
+    ctx.sleep(Duration::from_secs(2)).await;
+    let repository = secure_channels.identities().revocation_list_repository();
+    assert!(
+        repository
+            .get_revocation_list(&authority_identifier)
+            .await?
+            .is_none(),
+        "the revocation list must not be sent to a non-member"
+    );
+    Ok(())
+}
+
+/// Wait until a subject is revoked, or not revoked anymore, in the revocation list stored by a node
+async fn has_revocation_status(
+    ctx: &Context,
+    secure_channels: &Arc<SecureChannels>,
+    authority: &Identifier,
+    subject: &Identifier,
+    revoked: bool,
+) -> Result<bool> {
+    let repository = secure_channels.identities().revocation_list_repository();
+    for _ in 0..50 {
+        if repository.is_subject_revoked(authority, subject).await? == revoked {
+            return Ok(true);
+        }
+        ctx.sleep(Duration::from_millis(100)).await;
+    }
+    Ok(false)
+}
@@ -66,9 +66,13 @@ pub fn create_configuration(
         no_direct_authentication: true,
         no_token_enrollment: true,
         okta: None,
+        jwt: None,
+        kubernetes: None,
+        spiffe: None,
         account_authority: None,
         enforce_admin_checks: false,
         disable_trust_context_id: false,
+        replica_name: None,
     })
 }
 
@@ -89,8 +93,22 @@ pub async fn start_authority(
     secure_channels: Arc<SecureChannels>,
     number_of_admins: usize,
 ) -> Result<AuthorityInfo> {
-    let mut configuration = default_configuration().await?;
+    start_authority_with_configuration(
+        ctx,
+        secure_channels,
+        number_of_admins,
+        default_configuration().await?,
+    )
+    .await
+}
 
+// Start an authority node with a specific configuration, and the given number of admins
+pub async fn start_authority_with_configuration(
+    ctx: &Context,
+    secure_channels: Arc<SecureChannels>,
+    number_of_admins: usize,
+    mut configuration: Configuration,
+) -> Result<AuthorityInfo> {
     let account_authority = secure_channels
         .identities()
         .identities_creation()
@@ -168,6 +168,7 @@ pub fn measure_buffer_latency_two_nodes_portal() {
                     false,
                     false,
                     None,
+                    None,
                     false,
                     true,
                 )
@@ -266,6 +267,7 @@ pub fn measure_connection_latency_two_nodes_portal() {
                     false,
                     false,
                     None,
+                    None,
                     true,
                     true,
                 )
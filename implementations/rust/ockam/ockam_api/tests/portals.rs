@@ -62,6 +62,7 @@ async fn inlet_outlet_local_successful(context: &mut Context) -> ockam::Result<(
             false,
             false,
             None,
+            None,
             false,
             false,
         )
@@ -144,6 +145,7 @@ fn portal_node_goes_down_reconnect() {
                     false,
                     false,
                     None,
+                    None,
                     false,
                     false,
                 )
@@ -308,6 +310,7 @@ fn portal_low_bandwidth_connection_keep_working_for_60s() {
                     false,
                     false,
                     None,
+                    None,
                     false,
                     false,
                 )
@@ -426,6 +429,7 @@ fn portal_heavy_load_exchanged() {
                     false,
                     false,
                     None,
+                    None,
                     false,
                     false,
                 )
@@ -583,6 +587,7 @@ fn test_portal_payload_transfer(outgoing_disruption: Disruption, incoming_disrup
                     false,
                     false,
                     None,
+                    None,
                     false,
                     false,
                 )
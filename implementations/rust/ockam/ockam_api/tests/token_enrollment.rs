@@ -395,3 +395,137 @@ async fn usage_count2(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[ockam_macros::test]
+async fn token_with_parameters(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+
+    let AuthorityInfo { admins, .. } = start_authority(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let attributes = BTreeMap::from([("device".to_string(), "sensor-${serial}".to_string())]);
+    let parameters = BTreeMap::from([("serial".to_string(), "[0-9]{4}".to_string())]);
+    let otc = admin
+        .client
+        .create_token_with_parameters(ctx, attributes, parameters, None, Some(2))
+        .await
+        .unwrap();
+
+    let member = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let member_client = change_client_identifier(&admin.client, &member, None);
+
+    // a missing or invalid parameter doesn't use up the token
+    let res = member_client.present_token(ctx, otc).await;
+    assert!(res.is_err());
+    let serial = |value: &str| BTreeMap::from([("serial".to_string(), value.to_string())]);
+    let res = member_client
+        .present_token_with_parameters(ctx, otc, serial("12345"))
+        .await;
+    assert!(res.is_err());
+
+    member_client
+        .present_token_with_parameters(ctx, otc, serial("1234"))
+        .await
+        .unwrap();
+
+    let members = admin.client.list_members(ctx).await.unwrap();
+    let attrs = members.get(&member).unwrap();
+    assert_eq!(
+        attrs.attrs(),
+        &BTreeMap::from([(b"device".to_vec(), b"sensor-1234".to_vec())])
+    );
+
+    // an attribute can't use an undeclared parameter
+    let res = admin
+        .client
+        .create_token(
+            ctx,
+            BTreeMap::from([("device".to_string(), "${other}".to_string())]),
+            None,
+            None,
+        )
+        .await;
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn list_and_revoke_tokens(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+
+    let AuthorityInfo { admins, .. } = start_authority(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let otc = admin
+        .client
+        .create_token(ctx, Default::default(), None, Some(2))
+        .await
+        .unwrap();
+    let other_otc = admin
+        .client
+        .create_token(ctx, Default::default(), None, None)
+        .await
+        .unwrap();
+
+    let tokens = admin.client.list_tokens(ctx).await.unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens.iter().all(|t| t.issued_by == admin.identifier));
+
+    let member1 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let member_client1 = change_client_identifier(&admin.client, &member1, None);
+    member_client1.present_token(ctx, otc).await.unwrap();
+
+    // non-enrollers can't see the tokens
+    assert!(member_client1.list_tokens(ctx).await.is_err());
+
+    let tokens = admin.client.list_tokens(ctx).await.unwrap();
+    let token = tokens.iter().find(|t| t.remaining_uses == 1).unwrap();
+    let redemptions = admin
+        .client
+        .get_token_redemptions(ctx, &token.reference)
+        .await
+        .unwrap();
+    assert_eq!(redemptions.len(), 1);
+    assert_eq!(redemptions[0].redeemed_by, member1);
+    assert_eq!(redemptions[0].issued_by, admin.identifier);
+
+    // a revoked token can't be used anymore
+    admin
+        .client
+        .revoke_token(ctx, &token.reference)
+        .await
+        .unwrap();
+    assert!(admin
+        .client
+        .revoke_token(ctx, &token.reference)
+        .await
+        .is_err());
+    let member2 = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let member_client2 = change_client_identifier(&admin.client, &member2, None);
+    assert!(member_client2.present_token(ctx, otc).await.is_err());
+    member_client2.present_token(ctx, other_otc).await.unwrap();
+
+    // the redemptions are kept after the token is revoked
+    let redemptions = admin
+        .client
+        .get_token_redemptions(ctx, &token.reference)
+        .await
+        .unwrap();
+    assert_eq!(redemptions.len(), 1);
+    assert!(admin.client.list_tokens(ctx).await.unwrap().is_empty());
+
+    Ok(())
+}
@@ -212,6 +212,7 @@ impl AppState {
                 false,
                 false,
                 &None,
+                &None,
                 false,
                 false,
             )
@@ -66,7 +66,7 @@ impl AppState {
 
             debug!(worker_addr = %tcp_outlet.worker_addr, "Restoring outlet");
             let _ = node_manager
-                .create_outlet(
+                .create_load_balanced_outlet(
                     &context,
                     tcp_outlet.to,
                     false,
@@ -79,6 +79,8 @@ impl AppState {
                     false,
                     false,
                     false,
+                    tcp_outlet.load_balancing,
+                    None,
                 )
                 .await
                 .map_err(|e| {
@@ -52,15 +52,22 @@ impl ModelStateRepository for ModelStateSqlxDatabase {
         for tcp_outlet_status in &model_state.tcp_outlets {
             let query = query(
                 r#"
-                 INSERT INTO tcp_outlet_status (node_name, socket_addr, worker_addr, payload, privileged)
-                 VALUES ($1, $2, $3, $4, $5)
+                 INSERT INTO tcp_outlet_status (node_name, socket_addr, worker_addr, payload, privileged, load_balancing)
+                 VALUES ($1, $2, $3, $4, $5, $6)
                  ON CONFLICT DO NOTHING"#,
             )
             .bind(node_name)
             .bind(tcp_outlet_status.to.to_string())
             .bind(tcp_outlet_status.worker_addr.to_string())
             .bind(tcp_outlet_status.payload.as_ref())
-            .bind(tcp_outlet_status.privileged);
+            .bind(tcp_outlet_status.privileged)
+            .bind(
+                tcp_outlet_status
+                    .load_balancing
+                    .as_ref()
+                    .map(ockam_core::cbor_encode_preallocate)
+                    .transpose()?,
+            );
             query.execute(&mut *transaction).await.void()?;
         }
 
@@ -91,7 +98,7 @@ impl ModelStateRepository for ModelStateSqlxDatabase {
 
     async fn load(&self, node_name: &str) -> Result<ModelState> {
         let query1 = query_as(
-            "SELECT socket_addr, worker_addr, payload, privileged FROM tcp_outlet_status WHERE node_name = $1",
+            "SELECT socket_addr, worker_addr, payload, privileged, load_balancing FROM tcp_outlet_status WHERE node_name = $1",
         )
         .bind(node_name);
         let result: Vec<TcpOutletStatusRow> =
@@ -121,18 +128,26 @@ struct TcpOutletStatusRow {
     worker_addr: String,
     payload: Nullable<String>,
     privileged: Boolean,
+    load_balancing: Nullable<Vec<u8>>,
 }
 
 impl TcpOutletStatusRow {
     fn tcp_outlet_status(&self) -> Result<OutletStatus> {
         let to = HostnamePort::from_str(&self.socket_addr)?;
         let worker_addr = Address::from_string(&self.worker_addr);
-        Ok(OutletStatus {
+        let load_balancing = match self.load_balancing.to_option() {
+            Some(load_balancing) => {
+                Some(minicbor::decode(&load_balancing).map_err(ockam_core::Error::from)?)
+            }
+            None => None,
+        };
+        Ok(OutletStatus::new(
             to,
             worker_addr,
-            payload: self.payload.to_option(),
-            privileged: self.privileged.to_bool(),
-        })
+            self.payload.to_option(),
+            self.privileged.to_bool(),
+        )
+        .with_load_balancing(load_balancing))
     }
 }
 
@@ -1,4 +1,5 @@
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 
 use clap::Args;
 use miette::{miette, IntoDiagnostic, WrapErr};
@@ -10,7 +11,10 @@ use ockam::identity::{Identifier, Identity, TimestampInSeconds, Vault};
 use ockam::Context;
 use ockam_api::authenticator::{PreTrustedIdentities, PreTrustedIdentity};
 use ockam_api::authority_node;
-use ockam_api::authority_node::{Authority, OktaConfiguration};
+use ockam_api::authority_node::{
+    Authority, JwtAuthenticatorConfiguration, KubernetesAuthenticatorConfiguration,
+    OktaConfiguration, SpiffeAuthenticatorConfiguration, DEFAULT_KUBERNETES_ISSUER,
+};
 use ockam_api::colors::color_primary;
 use ockam_api::config::lookup::InternetAddress;
 use ockam_api::nodes::service::default_address::DefaultAddress;
@@ -104,6 +108,82 @@ pub struct CreateCommand {
     #[arg(long, value_name = "ATTRIBUTE_NAMES", default_value = None)]
     attributes: Option<Vec<String>>,
 
+    /// JWT: issuer of the OIDC ID tokens or JWTs accepted to issue credentials.
+    /// Setting it starts the JWT authenticator, and requires at least one `--jwt-audience`
+    #[arg(long, value_name = "URL", default_value = None, requires = "jwt_audiences")]
+    jwt_issuer: Option<String>,
+
+    /// JWT: accepted audience of the tokens. Can be repeated to accept several audiences
+    #[arg(
+        long = "jwt-audience",
+        value_name = "AUDIENCE",
+        requires = "jwt_issuer"
+    )]
+    jwt_audiences: Vec<String>,
+
+    /// JWT: URL of the issuer JSON Web Key Set.
+    /// By default, it is discovered from the OpenID configuration of the issuer
+    #[arg(long, value_name = "URL", requires = "jwt_issuer")]
+    jwt_jwks_url: Option<String>,
+
+    /// JWT: claim copied to a member attribute, for example `email=email`
+    /// or `/realm_access/roles=role` for a nested claim
+    #[arg(
+        long = "jwt-claim",
+        value_name = "CLAIM=ATTRIBUTE",
+        value_parser = parse_jwt_claim,
+        requires = "jwt_issuer"
+    )]
+    jwt_claims: Vec<(String, String)>,
+
+    /// Kubernetes: audience of the projected service account tokens accepted to enroll
+    /// identities. Setting it starts the Kubernetes authenticator
+    #[arg(long, value_name = "AUDIENCE", default_value = None)]
+    kubernetes_audience: Option<String>,
+
+    /// Kubernetes: issuer of the service account tokens.
+    /// The default is the issuer of a cluster with the default configuration
+    #[arg(long, value_name = "URL", requires = "kubernetes_audience")]
+    kubernetes_issuer: Option<String>,
+
+    /// Kubernetes: URL of the cluster JSON Web Key Set.
+    /// By default, the keys are retrieved from the Kubernetes API server
+    /// of the cluster running the authority
+    #[arg(long, value_name = "URL", requires = "kubernetes_audience")]
+    kubernetes_jwks_url: Option<String>,
+
+    /// Kubernetes: service account allowed to enroll, for example `payments/api`,
+    /// or `payments/*` for all the service accounts of a namespace.
+    /// At least one is required, use `*/*` to allow any service account
+    #[arg(
+        long = "kubernetes-service-account",
+        value_name = "NAMESPACE/NAME",
+        requires = "kubernetes_audience"
+    )]
+    kubernetes_service_accounts: Vec<String>,
+
+    /// Kubernetes: check with the Kubernetes API server that the pod of a token still exists
+    #[arg(long, requires = "kubernetes_audience")]
+    kubernetes_verify_pods: bool,
+
+    /// Kubernetes: pod label copied to the `kubernetes-label-<LABEL>` member attribute.
+    /// The pods are retrieved from the Kubernetes API server
+    #[arg(
+        long = "kubernetes-pod-label",
+        value_name = "LABEL",
+        requires = "kubernetes_audience"
+    )]
+    kubernetes_pod_labels: Vec<String>,
+
+    /// SPIFFE: trust domain of the X.509-SVIDs accepted to issue credentials,
+    /// for example `example.org`. Setting it starts the SPIFFE authenticator
+    #[arg(long, value_name = "TRUST_DOMAIN", requires = "spiffe_trust_bundle")]
+    spiffe_trust_domain: Option<String>,
+
+    /// SPIFFE: path of the PEM encoded X.509 authorities of the trust domain
+    #[arg(long, value_name = "PATH", requires = "spiffe_trust_domain")]
+    spiffe_trust_bundle: Option<PathBuf>,
+
     /// Full, hex-encoded Identity (change history) of the account authority to trust
     /// for account and project administrator credentials.
     #[arg(long, value_name = "ACCOUNT_AUTHORITY_CHANGE_HISTORY", default_value = None, value_parser = ChangeHistory::import_from_string
@@ -119,6 +199,11 @@ pub struct CreateCommand {
     #[arg(long, value_name = "DISABLE_TRUST_CONTEXT_ID", default_value_t = false)]
     disable_trust_context_id: bool,
 
+    /// Name of this replica, when several authority nodes share the same identity, stored in
+    /// a shared vault, and the same Postgres database. Each replica gets its own node name
+    #[arg(long, value_name = "REPLICA_NAME")]
+    replica_name: Option<String>,
+
     /// Port that a node should connect to when it's up and running, as a way to signal
     /// the parent process
     #[arg(hide = true, long)]
@@ -201,6 +286,65 @@ impl CreateCommand {
             });
         }
 
+        if let Some(jwt_issuer) = &self.jwt_issuer {
+            args.push("--jwt-issuer".to_string());
+            args.push(jwt_issuer.clone());
+        }
+
+        for audience in &self.jwt_audiences {
+            args.push("--jwt-audience".to_string());
+            args.push(audience.clone());
+        }
+
+        if let Some(jwt_jwks_url) = &self.jwt_jwks_url {
+            args.push("--jwt-jwks-url".to_string());
+            args.push(jwt_jwks_url.clone());
+        }
+
+        for (claim, attribute) in &self.jwt_claims {
+            args.push("--jwt-claim".to_string());
+            args.push(format!("{claim}={attribute}"));
+        }
+
+        if let Some(kubernetes_audience) = &self.kubernetes_audience {
+            args.push("--kubernetes-audience".to_string());
+            args.push(kubernetes_audience.clone());
+        }
+
+        if let Some(kubernetes_issuer) = &self.kubernetes_issuer {
+            args.push("--kubernetes-issuer".to_string());
+            args.push(kubernetes_issuer.clone());
+        }
+
+        if let Some(kubernetes_jwks_url) = &self.kubernetes_jwks_url {
+            args.push("--kubernetes-jwks-url".to_string());
+            args.push(kubernetes_jwks_url.clone());
+        }
+
+        for service_account in &self.kubernetes_service_accounts {
+            args.push("--kubernetes-service-account".to_string());
+            args.push(service_account.clone());
+        }
+
+        if self.kubernetes_verify_pods {
+            args.push("--kubernetes-verify-pods".to_string());
+        }
+
+        for label in &self.kubernetes_pod_labels {
+            args.push("--kubernetes-pod-label".to_string());
+            args.push(label.clone());
+        }
+
+        if let Some(spiffe_trust_domain) = &self.spiffe_trust_domain {
+            args.push("--spiffe-trust-domain".to_string());
+            args.push(spiffe_trust_domain.clone());
+        }
+
+        if let Some(spiffe_trust_bundle) = &self.spiffe_trust_bundle {
+            args.push("--spiffe-trust-bundle".to_string());
+            args.push(spiffe_trust_bundle.to_string_lossy().to_string());
+        }
+
         if let Some(identity) = &self.identity {
             args.push("--identity".to_string());
             args.push(identity.clone());
@@ -215,6 +359,10 @@ impl CreateCommand {
         if self.disable_trust_context_id {
             args.push("--disable_trust_context_id".to_string());
         }
+        if let Some(replica_name) = &self.replica_name {
+            args.push("--replica-name".to_string());
+            args.push(replica_name.clone());
+        }
 
         let node_callback = NodeCallback::create().await?;
 
@@ -308,7 +456,7 @@ impl CreateCommand {
         };
 
         let node = state
-            .start_node_with_optional_values(&self.node_name(), &Some(identity_name), None)
+            .start_node_with_optional_values(&self.node_name(), &Some(identity_name.clone()), None)
             .await?;
         state
             .set_tcp_listener_address(&self.node_name(), &self.tcp_listener_address)
@@ -328,6 +476,37 @@ impl CreateCommand {
             _ => None,
         };
 
+        let jwt_configuration = self.jwt_issuer.as_ref().map(|issuer| {
+            let mut configuration = JwtAuthenticatorConfiguration::new(
+                issuer,
+                self.jwt_audiences.clone(),
+                self.jwt_claims.iter().cloned().collect(),
+            );
+            configuration.jwks_url = self.jwt_jwks_url.clone();
+            configuration
+        });
+
+        let kubernetes_configuration = self.kubernetes_audience.as_ref().map(|audience| {
+            let mut configuration = KubernetesAuthenticatorConfiguration::new(
+                self.kubernetes_issuer
+                    .as_deref()
+                    .unwrap_or(DEFAULT_KUBERNETES_ISSUER),
+                audience,
+            );
+            configuration.jwks_url = self.kubernetes_jwks_url.clone();
+            configuration.service_accounts = self.kubernetes_service_accounts.clone();
+            configuration.verify_pods = self.kubernetes_verify_pods;
+            configuration.pod_labels = self.kubernetes_pod_labels.clone();
+            configuration
+        });
+
+        let spiffe_configuration = match (&self.spiffe_trust_domain, &self.spiffe_trust_bundle) {
+            (Some(trust_domain), Some(trust_bundle)) => Some(
+                SpiffeAuthenticatorConfiguration::new(trust_domain, trust_bundle.clone()),
+            ),
+            _ => None,
+        };
+
         let now = now().into_diagnostic()?;
         let trusted_identities = self.trusted_identities(now, &node.clone().identifier());
 
@@ -358,9 +537,13 @@ impl CreateCommand {
             no_direct_authentication: self.no_direct_authentication,
             no_token_enrollment: self.no_token_enrollment,
             okta: okta_configuration,
+            jwt: jwt_configuration,
+            kubernetes: kubernetes_configuration,
+            spiffe: spiffe_configuration,
             account_authority,
             enforce_admin_checks: self.enforce_admin_checks,
             disable_trust_context_id: self.disable_trust_context_id,
+            replica_name: self.replica_name.clone(),
         };
 
         // SQLite doesn't like when the same database is opened by multiple times
@@ -375,7 +558,13 @@ impl CreateCommand {
         // or retrieve it from disk if the node has already been started before
         // The trusted identities in the configuration are used to pre-populate an attribute storage
         // containing those identities and their attributes
-        let authority = Authority::create(&configuration, database)
+        // The identity keys are used from the vault of the identity, which can be shared
+        // by several replicas when it is an AWS KMS or a PKCS#11 vault
+        let named_identity = state.get_named_identity(&identity_name).await?;
+        let vault = state
+            .make_vault(state.get_named_vault(&named_identity.vault_name()).await?)
+            .await?;
+        let authority = Authority::create_with_vault(&configuration, database, Some(vault))
             .await
             .into_diagnostic()?;
 
@@ -427,9 +616,25 @@ impl CreateCommand {
     }
 
     // The name of the authority node is the name of the authority to make sure that there are
-    // no 2 authority nodes with the same name.
+    // no 2 authority nodes with the same name. The replicas of an authority, sharing the same
+    // database, are distinguished by their replica name.
     pub fn node_name(&self) -> String {
-        self.authority_name()
+        match &self.replica_name {
+            Some(replica_name) => format!("{}-{replica_name}", self.authority_name()),
+            None => self.authority_name(),
+        }
+    }
+}
+
+/// Return a claim name and the corresponding attribute name, formatted as CLAIM=ATTRIBUTE
+fn parse_jwt_claim(value: &str) -> Result<(String, String)> {
+    match value.split_once('=') {
+        Some((claim, attribute)) if !claim.is_empty() && !attribute.is_empty() => {
+            Ok((claim.to_string(), attribute.to_string()))
+        }
+        _ => Err(miette!(
+            "A JWT claim mapping must be formatted as CLAIM=ATTRIBUTE, got {value}"
+        )),
     }
 }
 
@@ -519,6 +724,17 @@ mod tests {
     }
 
     /// HELPERS
+    #[test]
+    fn test_parse_jwt_claim() {
+        assert_eq!(
+            parse_jwt_claim("/realm_access/roles=role").unwrap(),
+            ("/realm_access/roles".to_string(), "role".to_string())
+        );
+        assert!(parse_jwt_claim("email").is_err());
+        assert!(parse_jwt_claim("=email").is_err());
+        assert!(parse_jwt_claim("email=").is_err());
+    }
+
     async fn create_identity() -> Result<Identifier> {
         let identities = identities().await?;
         Ok(identities.identities_creation().create_identity().await?)
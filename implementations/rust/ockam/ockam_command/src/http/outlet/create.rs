@@ -0,0 +1,126 @@
+use crate::node::util::initialize_default_node;
+use crate::util::parsers::hostname_parser;
+use crate::{Command, CommandGlobalOpts};
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use ockam::transport::SchemeHostnamePort;
+use ockam::{Address, Context};
+use ockam_abac::PolicyExpression;
+use ockam_api::address::extract_address_value;
+use ockam_api::colors::color_primary;
+use ockam_api::http_proxy::{HttpOutletConfig, HttpPortals, HttpRoute};
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_api::{fmt_log, fmt_ok, fmt_warn};
+use std::str::FromStr;
+
+/// Create HTTP Outlets
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Address of your HTTP Outlet, which is part of a route used in other commands.
+    /// This unique address identifies the HTTP Outlet worker on the Node on your local machine.
+    /// Examples are `/service/my-outlet` or `my-outlet`.
+    /// If not provided, `outlet` will be used, or a random address will be generated if `outlet` is taken.
+    /// You will need this address when creating a TCP Inlet using `ockam tcp-inlet create`.
+    #[arg(value_parser = extract_address_value)]
+    pub name: Option<String>,
+
+    /// Address where your HTTP server is running, in the format `<scheme>://<hostname>:<port>`.
+    /// At least the port must be provided. The default scheme is `tcp` and the default hostname is `127.0.0.1`.
+    #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub to: SchemeHostnamePort,
+
+    /// Alternative to the <NAME> positional argument.
+    /// Address of your HTTP Outlet, which is part of a route used in other commands.
+    #[arg(long, display_order = 902, id = "OUTLET_ADDRESS", value_parser = extract_address_value)]
+    pub from: Option<String>,
+
+    /// Your HTTP Outlet will be created on this node. If you don't provide it, the default
+    /// node will be used
+    #[arg(long, display_order = 903, id = "NODE_NAME", value_parser = extract_address_value)]
+    pub at: Option<String>,
+
+    /// Policy expression that will be used for access control to the HTTP Outlet.
+    /// If you don't provide it, the policy set for the "tcp-outlet" resource type will be used.
+    ///
+    /// You can check the fallback policy with `ockam policy show --resource-type tcp-outlet`.
+    #[arg(
+        long,
+        visible_alias = "expression",
+        display_order = 904,
+        id = "POLICY_EXPRESSION"
+    )]
+    pub allow: Option<PolicyExpression>,
+
+    /// Route of the requests allowed by the Outlet, in the format
+    /// `[METHOD[,METHOD]*] PATH [POLICY EXPRESSION]`. It can be repeated.
+    /// For example: `--route 'GET,HEAD /api/* (= subject.role "reader")' --route '/health'`
+    #[arg(long = "route", display_order = 905, id = "ROUTE", value_parser = HttpRoute::from_str)]
+    pub routes: Vec<HttpRoute>,
+
+    /// Send the identifier and the attributes of the client to the server
+    /// in `X-Ockam-Identifier` and `X-Ockam-Attr-<name>` headers
+    #[arg(long, display_order = 906)]
+    pub inject_identity_headers: bool,
+}
+
+#[async_trait]
+impl Command for CreateCommand {
+    const NAME: &'static str = "http-outlet create";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        initialize_default_node(ctx, &opts).await?;
+        let cmd = self.parse_args(&opts).await?;
+
+        let http_config = HttpOutletConfig::new(cmd.routes.clone(), cmd.inject_identity_headers);
+
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+        let outlet_status = {
+            let pb = opts.terminal.spinner();
+            if let Some(pb) = pb.as_ref() {
+                pb.set_message(format!(
+                    "Creating a new HTTP Outlet to {}...\n",
+                    color_primary(cmd.to.to_string())
+                ));
+            }
+            node.create_http_outlet(
+                ctx,
+                cmd.to.clone().into(),
+                cmd.to.is_tls(),
+                cmd.name.clone().map(Address::from).as_ref(),
+                cmd.allow.clone(),
+                http_config,
+            )
+            .await?
+        };
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Created a new HTTP Outlet in the Node {} at {} bound to {}\n\n",
+                color_primary(node.node_name()),
+                color_primary(&outlet_status.worker_addr),
+                color_primary(&cmd.to)
+            ))
+            .machine(&outlet_status.worker_addr)
+            .json_obj(&outlet_status)?
+            .write_line()?;
+        Ok(())
+    }
+}
+
+impl CreateCommand {
+    async fn parse_args(mut self, opts: &CommandGlobalOpts) -> miette::Result<Self> {
+        if let Some(from) = self.from.as_ref() {
+            if self.name.is_some() {
+                opts.terminal.write_line(
+                    fmt_warn!("The <NAME> argument is being overridden by the --from flag")
+                        + &fmt_log!("Consider using either the <NAME> argument or the --from flag"),
+                )?;
+            }
+            self.name = Some(from.clone());
+        }
+
+        Ok(self)
+    }
+}
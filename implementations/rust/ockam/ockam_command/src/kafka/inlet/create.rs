@@ -11,14 +11,16 @@ use crate::{
     Command, CommandGlobalOpts,
 };
 use async_trait::async_trait;
-use clap::{command, Args};
+use clap::{command, Args, ValueEnum};
 use colorful::Colorful;
-use miette::miette;
+use miette::{miette, IntoDiagnostic};
 use ockam::transport::SchemeHostnamePort;
 use ockam_abac::PolicyExpression;
 use ockam_api::colors::{color_primary, color_warn};
 use ockam_api::config::lookup::InternetAddress;
-use ockam_api::kafka::{ConsumerPublishing, ConsumerResolution};
+use ockam_api::kafka::{
+    ConsumerPublishing, ConsumerResolution, KafkaRecordFormat, KafkaTopicEncryptionRule,
+};
 use ockam_api::nodes::models::services::{StartKafkaInletRequest, StartServiceRequest};
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_api::output::Output;
@@ -28,7 +30,9 @@ use ockam_core::api::Request;
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
 use serde::Serialize;
-use std::fmt::Write;
+use std::fmt::{Display, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 /// Create a new Kafka Inlet.
 /// Kafka clients v3.7.0 and earlier are supported.
@@ -100,7 +104,8 @@ pub struct CreateCommand {
     )]
     pub no_content_encryption: bool,
 
-    /// The fields to encrypt in the kafka messages, assuming the record is a valid JSON map.
+    /// The fields to encrypt in the kafka messages, in the format set by `--record-format`.
+    /// Nested Avro and Protobuf fields are selected with a path, for example `customer.email`.
     /// By default, the whole record is encrypted.
     #[arg(
         long,
@@ -110,6 +115,40 @@ pub struct CreateCommand {
     )]
     pub encrypted_fields: Vec<String>,
 
+    /// The format of the kafka messages when only some fields are encrypted.
+    /// Avro messages are either datums written with `--avro-schema`, or object container files.
+    /// Protobuf messages need `--protobuf-descriptor-set` and `--protobuf-message`.
+    /// Encrypted Protobuf fields are written as `bytes`, which changes their wire type,
+    /// and messages framed by a schema registry are rejected.
+    #[arg(long, value_name = "FORMAT", default_value_t = RecordFormatArg::Json)]
+    pub record_format: RecordFormatArg,
+
+    #[arg(help = docs::about("\
+    The encryption of the topics matching a pattern, overriding the other encryption settings. \
+    Written as <TOPIC>=<ENCRYPTION>, where <TOPIC> is a topic name, a prefix ending with '*' \
+    or a regular expression between '/' matching the whole topic name, and <ENCRYPTION> is 'cleartext', 'whole' or \
+    'fields:<FIELD>,<FIELD>...'. The rules are checked in order and the first matching rule applies. \
+    This flag can be repeated."))]
+    #[arg(long = "topic-encryption", value_name = "RULE", value_parser = KafkaTopicEncryptionRule::from_str)]
+    pub topic_encryption: Vec<KafkaTopicEncryptionRule>,
+
+    /// The file containing the Avro schema, in JSON, of the kafka messages
+    #[arg(long, value_name = "FILE")]
+    pub avro_schema: Option<PathBuf>,
+
+    /// The file containing the serialized `FileDescriptorSet` describing the Protobuf messages,
+    /// as generated by `protoc --descriptor_set_out`
+    #[arg(long, value_name = "FILE", requires = "protobuf_message")]
+    pub protobuf_descriptor_set: Option<PathBuf>,
+
+    /// The fully-qualified type of the Protobuf messages, for example `shop.Order`
+    #[arg(
+        long,
+        value_name = "MESSAGE_TYPE",
+        requires = "protobuf_descriptor_set"
+    )]
+    pub protobuf_message: Option<String>,
+
     #[arg(help = docs::about("\
     Policy expression that will be used for access control to the Kafka Inlet. \
     If you don't provide it, the policy set for the \"tcp-inlet\" resource type will be used. \
@@ -181,6 +220,8 @@ impl Command for CreateCommand {
                 cmd.to.clone(),
                 !cmd.no_content_encryption,
                 cmd.encrypted_fields.clone(),
+                cmd.kafka_record_format()?,
+                cmd.topic_encryption.clone(),
                 consumer_resolution,
                 consumer_publishing,
                 cmd.inlet_policy_expression.clone(),
@@ -251,6 +292,57 @@ impl CreateCommand {
     fn brokers_port_range(&self) -> PortRange {
         self.brokers_port_range.unwrap()
     }
+
+    fn kafka_record_format(&self) -> miette::Result<KafkaRecordFormat> {
+        match self.record_format {
+            RecordFormatArg::Json => {
+                if self.avro_schema.is_some() || self.protobuf_descriptor_set.is_some() {
+                    return Err(miette!(
+                        "The {} flag must be set to use a schema",
+                        color_primary("--record-format")
+                    ));
+                }
+                Ok(KafkaRecordFormat::Json)
+            }
+            RecordFormatArg::Avro => {
+                let schema = match &self.avro_schema {
+                    Some(path) => Some(std::fs::read_to_string(path).into_diagnostic()?),
+                    None => None,
+                };
+                Ok(KafkaRecordFormat::Avro(schema))
+            }
+            RecordFormatArg::Protobuf => {
+                match (&self.protobuf_descriptor_set, &self.protobuf_message) {
+                    (Some(path), Some(message_type)) => Ok(KafkaRecordFormat::Protobuf(
+                        std::fs::read(path).into_diagnostic()?.into(),
+                        message_type.clone(),
+                    )),
+                    _ => Err(miette!(
+                        "The {} and {} flags are required for Protobuf messages",
+                        color_primary("--protobuf-descriptor-set"),
+                        color_primary("--protobuf-message")
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
+pub enum RecordFormatArg {
+    Json,
+    Avro,
+    Protobuf,
+}
+
+impl Display for RecordFormatArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordFormatArg::Json => write!(f, "json"),
+            RecordFormatArg::Avro => write!(f, "avro"),
+            RecordFormatArg::Protobuf => write!(f, "protobuf"),
+        }
+    }
 }
 
 #[derive(Serialize)]
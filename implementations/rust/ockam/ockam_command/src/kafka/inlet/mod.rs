@@ -3,6 +3,7 @@ use clap::{command, Args, Subcommand};
 use crate::kafka::inlet::create::CreateCommand;
 use crate::kafka::inlet::delete::DeleteCommand;
 use crate::kafka::inlet::list::ListCommand;
+use crate::kafka::inlet::reencrypt::ReencryptCommand;
 use crate::kafka::inlet::show::ShowCommand;
 use crate::{Command, CommandGlobalOpts};
 
@@ -11,6 +12,7 @@ use ockam_node::Context;
 pub(crate) mod create;
 pub(crate) mod delete;
 pub(crate) mod list;
+pub(crate) mod reencrypt;
 pub(crate) mod show;
 
 /// Manage Kafka Inlets
@@ -27,6 +29,7 @@ pub enum KafkaInletSubcommand {
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    Reencrypt(ReencryptCommand),
 }
 
 impl KafkaInletCommand {
@@ -36,6 +39,7 @@ impl KafkaInletCommand {
             KafkaInletSubcommand::Show(c) => c.run(ctx, opts).await,
             KafkaInletSubcommand::Delete(c) => c.run(ctx, opts).await,
             KafkaInletSubcommand::List(c) => c.run(ctx, opts).await,
+            KafkaInletSubcommand::Reencrypt(c) => c.run(ctx, opts).await,
         }
     }
 
@@ -45,6 +49,7 @@ impl KafkaInletCommand {
             KafkaInletSubcommand::Show(c) => c.name(),
             KafkaInletSubcommand::Delete(c) => c.name(),
             KafkaInletSubcommand::List(c) => c.name(),
+            KafkaInletSubcommand::Reencrypt(c) => c.name(),
         }
     }
 }
@@ -0,0 +1,127 @@
+use crate::kafka::kafka_default_inlet_bind_address;
+use crate::util::parsers::hostname_parser;
+use crate::{docs, Command, CommandGlobalOpts};
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+use ockam::transport::SchemeHostnamePort;
+use ockam_api::colors::color_primary;
+use ockam_api::fmt_ok;
+use ockam_api::kafka::reencryption::{KafkaReencryption, KafkaReencryptionStatus};
+use ockam_node::Context;
+
+const AFTER_LONG_HELP: &str = include_str!("./static/reencrypt/after_long_help.txt");
+
+/// Re-encrypt the records of a Kafka topic into another topic, with fresh encryption keys.
+///
+/// The records are consumed through a Kafka inlet able to decrypt them, and produced through a
+/// Kafka inlet encrypting them. The progress of the job is stored, so that running the command
+/// again after an interruption resumes the job where it stopped.
+/// A few records can be produced twice when the job is interrupted.
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct ReencryptCommand {
+    /// Name of the re-encryption job, used to resume it
+    pub job_name: String,
+
+    /// The address of the Kafka inlet used to consume the records of the source topic
+    #[arg(long, value_name = "SOCKET_ADDRESS", default_value_t = kafka_default_inlet_bind_address(), value_parser = hostname_parser)]
+    pub from: SchemeHostnamePort,
+
+    /// The address of the Kafka inlet used to produce the records into the destination topic.
+    /// Defaults to the `--from` address
+    #[arg(long, value_name = "SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub to: Option<SchemeHostnamePort>,
+
+    /// The topic containing the records to re-encrypt
+    #[arg(long, value_name = "TOPIC")]
+    pub source_topic: String,
+
+    /// The topic receiving the re-encrypted records.
+    /// It must have at least as many partitions as the source topic
+    #[arg(long, value_name = "TOPIC")]
+    pub destination_topic: String,
+
+    /// Forget the progress of a previous run and start again from the earliest records
+    #[arg(long)]
+    pub reset: bool,
+}
+
+#[async_trait]
+impl Command for ReencryptCommand {
+    const NAME: &'static str = "kafka-inlet reencrypt";
+
+    async fn run(self, _ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        let from = self.from.hostname_port().to_string();
+        let to = self
+            .to
+            .as_ref()
+            .map(|to| to.hostname_port().to_string())
+            .unwrap_or_else(|| from.clone());
+        let job = KafkaReencryption::new(
+            &self.job_name,
+            &from,
+            &self.source_topic,
+            &to,
+            &self.destination_topic,
+            opts.state.kafka_reencryption_repository(),
+        )
+        .into_diagnostic()?;
+        if self.reset {
+            job.reset().await.into_diagnostic()?;
+        }
+
+        let status = {
+            let pb = opts.terminal.spinner();
+            job.run(|status| {
+                if let Some(pb) = pb.as_ref() {
+                    pb.set_message(format!(
+                        "Re-encrypting the topic {} into the topic {}: {}...\n",
+                        color_primary(&self.source_topic),
+                        color_primary(&self.destination_topic),
+                        progress(status)
+                    ));
+                }
+            })
+            .await
+            .into_diagnostic()?
+        };
+
+        let msg = if status.is_complete() {
+            fmt_ok!(
+                "The records of the topic {} have been re-encrypted into the topic {}: {}",
+                color_primary(&self.source_topic),
+                color_primary(&self.destination_topic),
+                progress(&status)
+            )
+        } else {
+            fmt_ok!(
+                "The job {} stopped before re-encrypting all the records: {}. Run the command again to resume it",
+                color_primary(&self.job_name),
+                progress(&status)
+            )
+        };
+        opts.terminal
+            .stdout()
+            .plain(msg)
+            .json(serde_json::json!({
+                "job_name": self.job_name,
+                "source_topic": self.source_topic,
+                "destination_topic": self.destination_topic,
+                "reencrypted_records": status.reencrypted_records(),
+                "total_records": status.total_records(),
+                "complete": status.is_complete(),
+            }))
+            .write_line()?;
+        Ok(())
+    }
+}
+
+fn progress(status: &KafkaReencryptionStatus) -> String {
+    format!(
+        "{}/{} records",
+        status.reencrypted_records(),
+        status.total_records()
+    )
+}
@@ -55,6 +55,11 @@ impl CreateCommand {
             no_publishing: false,
             no_content_encryption: false,
             encrypted_fields: vec![],
+            topic_encryption: vec![],
+            record_format: crate::kafka::inlet::create::RecordFormatArg::Json,
+            avro_schema: None,
+            protobuf_descriptor_set: None,
+            protobuf_message: None,
             inlet_policy_expression: None,
             consumer_policy_expression: None,
             producer_policy_expression: None,
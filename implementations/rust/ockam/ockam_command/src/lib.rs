@@ -43,6 +43,7 @@ pub mod environment;
 pub mod error;
 mod flow_control;
 mod global_args;
+mod http;
 pub mod identity;
 mod influxdb;
 mod kafka;
@@ -56,6 +57,7 @@ mod operation;
 mod output;
 pub mod pager;
 mod policy;
+mod postgres;
 mod project;
 mod project_admin;
 mod project_member;
@@ -75,6 +77,7 @@ mod subcommand;
 mod subscription;
 pub mod tcp;
 mod terminal;
+mod udp;
 mod upgrade;
 pub mod util;
 pub mod value_parsers;
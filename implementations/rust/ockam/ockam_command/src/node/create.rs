@@ -94,7 +94,7 @@ pub struct CreateCommand {
     )]
     pub http_server: bool,
 
-    /// Disable the node's status endpoint that serves the healthcheck endpoint.
+    /// Disable the node's status endpoint that serves the healthcheck and `/metrics` endpoints.
     #[arg(
         long,
         value_name = "BOOL",
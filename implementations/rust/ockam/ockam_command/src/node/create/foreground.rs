@@ -38,6 +38,7 @@ impl CreateCommand {
                 &self.trust_opts.project_name,
                 &self.trust_opts.authority_identity,
                 &self.trust_opts.authority_route,
+                &self.trust_opts.authority_fallback_routes,
                 &self.trust_opts.credential_scope,
             )
             .await
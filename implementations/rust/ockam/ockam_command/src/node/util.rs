@@ -175,6 +175,10 @@ pub fn spawn_node(opts: &CommandGlobalOpts, cmd: CreateCommand) -> miette::Resul
         args.push("--authority-route".to_string());
         args.push(authority_route.to_string());
     }
+    for authority_fallback_route in trust_opts.authority_fallback_routes {
+        args.push("--authority-fallback-route".to_string());
+        args.push(authority_fallback_route.to_string());
+    }
 
     if let Some(opentelemetry_context) = opentelemetry_context {
         args.push("--opentelemetry-context".to_string());
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+
+use ockam::identity::Identifier;
+use ockam::Context;
+use ockam_abac::{Action, PolicyExpression};
+use ockam_api::colors::{color_error, color_primary};
+use ockam_api::nodes::models::policies::{EvaluatePolicyRequest, ResourceTypeOrName};
+use ockam_api::nodes::{BackgroundNodeClient, Policies};
+use ockam_api::{fmt_err, fmt_log, fmt_ok};
+
+use crate::docs;
+use crate::util::parsers::identity_identifier_parser;
+use crate::value_parsers::parse_key_val;
+use crate::{Command, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/evaluate/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/evaluate/after_long_help.txt");
+
+/// Evaluate a policy for a given identity and explain the access decision
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct EvaluateCommand {
+    /// The resource, or resource type, whose policy must be evaluated
+    #[arg(required_unless_present = "POLICY_EXPRESSION")]
+    pub resource: Option<ResourceTypeOrName>,
+
+    /// A policy expression to evaluate instead of the policy set on the resource
+    #[arg(long, id = "POLICY_EXPRESSION")]
+    pub expression: Option<PolicyExpression>,
+
+    /// The action performed on the resource
+    #[arg(long, default_value_t = Action::HandleMessage)]
+    pub action: Action,
+
+    /// Identifier of the identity accessing the resource
+    #[arg(long, value_name = "IDENTIFIER", value_parser = identity_identifier_parser)]
+    pub subject: Identifier,
+
+    /// Attributes in `key=value` format, as if they were attested by a credential of the subject
+    #[arg(short, long = "attribute", value_name = "ATTRIBUTE", value_parser = parse_key_val::<String, String>)]
+    pub attributes: Vec<(String, String)>,
+
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    pub at: Option<String>,
+}
+
+#[async_trait]
+impl Command for EvaluateCommand {
+    const NAME: &'static str = "policy evaluate";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &self.at).await?;
+        let request = EvaluatePolicyRequest::new(
+            self.resource,
+            self.expression,
+            self.subject,
+            self.attributes.into_iter().collect::<BTreeMap<_, _>>(),
+        );
+        let explanation = node.evaluate_policy(ctx, &self.action, &request).await?;
+
+        let decision = if explanation.is_authorized {
+            fmt_ok!("Access is {}", color_primary("allowed"))
+        } else {
+            fmt_err!("Access is {}", color_error("denied"))
+        };
+        let mut plain = format!(
+            "{decision}\n{}\n",
+            fmt_log!(
+                "Policy {}",
+                color_primary(explanation.expression.to_string())
+            )
+        );
+        if let Some(error) = &explanation.error {
+            plain.push_str(&fmt_log!("Evaluation failed: {}\n", color_error(error)));
+        }
+        if !explanation.missing_identifiers.is_empty() {
+            plain.push_str(&fmt_log!(
+                "Missing identifiers: {}\n",
+                color_primary(explanation.missing_identifiers.join(", "))
+            ));
+        }
+        plain.push_str(&fmt_log!("Evaluation steps:\n"));
+        for step in &explanation.steps {
+            plain.push_str(&fmt_log!(
+                "  {} => {}\n",
+                step.expression,
+                color_primary(step.value.to_string())
+            ));
+        }
+
+        opts.terminal
+            .stdout()
+            .plain(plain)
+            .json(serde_json::to_string(&explanation).into_diagnostic()?)
+            .write_line()?;
+        Ok(())
+    }
+}
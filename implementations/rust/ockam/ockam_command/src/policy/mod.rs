@@ -5,6 +5,7 @@ use miette::miette;
 
 pub use crate::policy::create::CreateCommand;
 use crate::policy::delete::DeleteCommand;
+use crate::policy::evaluate::EvaluateCommand;
 use crate::policy::list::ListCommand;
 use crate::policy::show::ShowCommand;
 use crate::{Command, CommandGlobalOpts};
@@ -14,6 +15,7 @@ use ockam_node::Context;
 
 mod create;
 mod delete;
+mod evaluate;
 mod list;
 mod show;
 
@@ -30,6 +32,7 @@ pub enum PolicySubcommand {
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    Evaluate(EvaluateCommand),
 }
 
 impl PolicySubcommand {
@@ -39,6 +42,7 @@ impl PolicySubcommand {
             PolicySubcommand::Show(c) => c.name(),
             PolicySubcommand::Delete(c) => c.name(),
             PolicySubcommand::List(c) => c.name(),
+            PolicySubcommand::Evaluate(c) => c.name(),
         }
     }
 }
@@ -50,6 +54,7 @@ impl PolicyCommand {
             PolicySubcommand::Show(c) => c.run(ctx, opts).await,
             PolicySubcommand::Delete(c) => c.run(ctx, opts).await,
             PolicySubcommand::List(c) => c.run(ctx, opts).await,
+            PolicySubcommand::Evaluate(c) => c.run(ctx, opts).await,
         }
     }
 
@@ -0,0 +1,147 @@
+use crate::node::util::initialize_default_node;
+use crate::util::parsers::hostname_parser;
+use crate::{Command, CommandGlobalOpts};
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+use ockam::transport::SchemeHostnamePort;
+use ockam::{Address, Context};
+use ockam_abac::PolicyExpression;
+use ockam_api::address::extract_address_value;
+use ockam_api::colors::color_primary;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_api::postgres::{PostgresCredentials, PostgresOutletConfig, PostgresPortals};
+use ockam_api::{fmt_log, fmt_ok, fmt_warn};
+
+/// Create Postgres Outlets
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Address of your Postgres Outlet, which is part of a route used in other commands.
+    /// This unique address identifies the Postgres Outlet worker on the Node on your local machine.
+    /// Examples are `/service/my-outlet` or `my-outlet`.
+    /// If not provided, `outlet` will be used, or a random address will be generated if `outlet` is taken.
+    /// You will need this address when creating a TCP Inlet using `ockam tcp-inlet create`.
+    #[arg(value_parser = extract_address_value)]
+    pub name: Option<String>,
+
+    /// Address where your PostgreSQL server is running, in the format `<scheme>://<hostname>:<port>`.
+    /// At least the port must be provided. The default scheme is `tcp` and the default hostname is `127.0.0.1`.
+    #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub to: SchemeHostnamePort,
+
+    /// Alternative to the <NAME> positional argument.
+    /// Address of your Postgres Outlet, which is part of a route used in other commands.
+    #[arg(long, display_order = 902, id = "OUTLET_ADDRESS", value_parser = extract_address_value)]
+    pub from: Option<String>,
+
+    /// Your Postgres Outlet will be created on this node. If you don't provide it, the default
+    /// node will be used
+    #[arg(long, display_order = 903, id = "NODE_NAME", value_parser = extract_address_value)]
+    pub at: Option<String>,
+
+    /// Policy expression that will be used for access control to the Postgres Outlet.
+    /// If you don't provide it, the policy set for the "tcp-outlet" resource type will be used.
+    ///
+    /// You can check the fallback policy with `ockam policy show --resource-type tcp-outlet`.
+    #[arg(
+        long,
+        visible_alias = "expression",
+        display_order = 904,
+        id = "POLICY_EXPRESSION"
+    )]
+    pub allow: Option<PolicyExpression>,
+
+    /// Policy expression checked for each statement sent to the server.
+    /// It can use the attributes of the client identity, and the `query.command`,
+    /// `query.database` and `query.user` attributes.
+    /// For example: `(or (= query.command "SELECT") (= subject.role "admin"))`.
+    /// The `DO`, `EXECUTE` and `CALL` commands must be explicitly mentioned to be allowed.
+    /// This check is best-effort, and doesn't replace the privileges of the server user
+    #[arg(long, display_order = 905, id = "QUERY_POLICY_EXPRESSION")]
+    pub query_policy: Option<PolicyExpression>,
+
+    /// User used by the Outlet to authenticate to the server, on behalf of the clients
+    #[arg(long, display_order = 906)]
+    pub user: Option<String>,
+
+    /// Password used by the Outlet to authenticate to the server.
+    /// It can also be set with the `OCKAM_POSTGRES_PASSWORD` environment variable
+    #[arg(
+        long,
+        display_order = 907,
+        env = "OCKAM_POSTGRES_PASSWORD",
+        hide_env_values = true
+    )]
+    pub password: Option<String>,
+}
+
+#[async_trait]
+impl Command for CreateCommand {
+    const NAME: &'static str = "postgres-outlet create";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        initialize_default_node(ctx, &opts).await?;
+        let cmd = self.parse_args(&opts).await?;
+
+        let credentials = match (cmd.user.clone(), cmd.password.clone()) {
+            (Some(user), Some(password)) => Some(PostgresCredentials::new(user, password)),
+            (None, _) => None,
+            (Some(_), None) => {
+                return Err(miette!(
+                    "Pass a value for `--password` or export the OCKAM_POSTGRES_PASSWORD environment variable"
+                ))?
+            }
+        };
+        let postgres_config = PostgresOutletConfig::new(credentials, cmd.query_policy.clone());
+
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+        let outlet_status = {
+            let pb = opts.terminal.spinner();
+            if let Some(pb) = pb.as_ref() {
+                pb.set_message(format!(
+                    "Creating a new Postgres Outlet to {}...\n",
+                    color_primary(cmd.to.to_string())
+                ));
+            }
+            node.create_postgres_outlet(
+                ctx,
+                cmd.to.clone().into(),
+                cmd.to.is_tls(),
+                cmd.name.clone().map(Address::from).as_ref(),
+                cmd.allow.clone(),
+                postgres_config,
+            )
+            .await?
+        };
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Created a new Postgres Outlet in the Node {} at {} bound to {}\n\n",
+                color_primary(node.node_name()),
+                color_primary(&outlet_status.worker_addr),
+                color_primary(&cmd.to)
+            ))
+            .machine(&outlet_status.worker_addr)
+            .json_obj(&outlet_status)?
+            .write_line()?;
+        Ok(())
+    }
+}
+
+impl CreateCommand {
+    async fn parse_args(mut self, opts: &CommandGlobalOpts) -> miette::Result<Self> {
+        if let Some(from) = self.from.as_ref() {
+            if self.name.is_some() {
+                opts.terminal.write_line(
+                    fmt_warn!("The <NAME> argument is being overridden by the --from flag")
+                        + &fmt_log!("Consider using either the <NAME> argument or the --from flag"),
+                )?;
+            }
+            self.name = Some(from.clone());
+        }
+
+        Ok(self)
+    }
+}
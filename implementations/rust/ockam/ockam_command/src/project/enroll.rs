@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -13,9 +15,10 @@ use crate::credential::CredentialOutput;
 use crate::enroll::OidcServiceExt;
 use crate::shared_args::{IdentityOpts, RetryOpts, TrustOpts};
 use crate::util::parsers::duration_parser;
-use crate::value_parsers::parse_enrollment_ticket;
+use crate::value_parsers::{parse_enrollment_ticket, parse_key_val};
 use crate::{docs, Command, CommandGlobalOpts, Error, Result};
 use ockam::Context;
+use ockam_api::authenticator::spiffe::{svid_proof_of_possession, AuthenticateSvid};
 use ockam_api::cli_state::{EnrollmentTicket, NamedIdentity};
 use ockam_api::colors::color_primary;
 use ockam_api::enroll::enrollment::{EnrollStatus, Enrollment};
@@ -27,11 +30,15 @@ use ockam_api::orchestrator::AuthorityNodeClient;
 use ockam_api::output::{human_readable_time, Output};
 use ockam_api::terminal::fmt;
 use ockam_api::{fmt_log, fmt_ok};
+use rustls::crypto::CryptoProvider;
+use rustls::SignatureScheme;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 
 const LONG_ABOUT: &str = include_str!("./static/enroll/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/enroll/after_long_help.txt");
 
-/// Use an enrollment ticket, or Okta, to enroll an identity with a project
+/// Use an enrollment ticket, Okta, a Kubernetes token or an SVID to enroll with a project
 #[derive(Clone, Args)]
 #[command(
 long_about = docs::about(LONG_ABOUT),
@@ -46,6 +53,17 @@ pub struct EnrollCommand {
     )]
     pub enrollment_ticket: Option<String>,
 
+    /// Value of a parameter of the enrollment ticket, in `name=value` format.
+    /// You can specify this option multiple times for multiple parameters
+    #[arg(
+        display_order = 801,
+        long = "parameter",
+        value_name = "PARAMETER",
+        requires = "enrollment_ticket",
+        value_parser = parse_key_val::<String, String>
+    )]
+    pub parameters: Vec<(String, String)>,
+
     #[command(flatten)]
     pub identity_opts: IdentityOpts,
 
@@ -57,6 +75,31 @@ pub struct EnrollCommand {
     #[arg(display_order = 900, long = "okta", group = "authentication_method")]
     pub okta: bool,
 
+    /// Use a Kubernetes service account token instead of an enrollment ticket.
+    /// The token is read from the given path, usually a projected volume of the pod
+    #[arg(
+        display_order = 901,
+        long = "kubernetes-token",
+        value_name = "PATH",
+        group = "authentication_method"
+    )]
+    pub kubernetes_token: Option<PathBuf>,
+
+    /// Use a SPIFFE X.509-SVID instead of an enrollment ticket.
+    /// The PEM file contains the SVID, followed by its intermediate certificates
+    #[arg(
+        display_order = 902,
+        long = "svid",
+        value_name = "PATH",
+        group = "authentication_method",
+        requires = "svid_key"
+    )]
+    pub svid: Option<PathBuf>,
+
+    /// PEM file containing the private key of the SVID, used to prove its possession
+    #[arg(display_order = 903, long, value_name = "PATH", requires = "svid")]
+    pub svid_key: Option<PathBuf>,
+
     #[command(flatten)]
     pub retry_opts: RetryOpts,
 
@@ -72,9 +115,13 @@ pub struct EnrollCommand {
 impl Debug for EnrollCommand {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("EnrollCommand")
+            .field("parameters", &self.parameters)
             .field("identity_opts", &self.identity_opts)
             .field("trust_opts", &self.trust_opts)
             .field("okta", &self.okta)
+            .field("kubernetes_token", &self.kubernetes_token)
+            .field("svid", &self.svid)
+            .field("svid_key", &self.svid_key)
             .field("retry_opts", &self.retry_opts)
             .field("timeout", &self.timeout)
             .finish()
@@ -128,6 +175,17 @@ impl Command for EnrollCommand {
         // Enroll if applicable
         if self.okta {
             self.use_okta(ctx, &opts, &authority_node_client).await?;
+        } else if let Some(token_path) = &self.kubernetes_token {
+            self.use_kubernetes_token(ctx, &opts, &authority_node_client, token_path)
+                .await?;
+        } else if let (Some(svid_path), Some(svid_key_path)) = (&self.svid, &self.svid_key) {
+            let authority = project
+                .authority_identifier()
+                .ok_or_else(|| miette!("The project {} has no authority", project.name()))?;
+            let message = svid_proof_of_possession(&authority, &identity.identifier());
+            let request = svid_request(svid_path, svid_key_path, &message)?;
+            self.use_svid(ctx, &opts, &authority_node_client, request)
+                .await?;
         } else if let Some(enrollment_ticket) = enrollment_ticket {
             self.use_enrollment_ticket(ctx, &opts, &authority_node_client, enrollment_ticket)
                 .await?;
@@ -190,7 +248,11 @@ impl EnrollCommand {
                 pb.set_message("Using enrollment ticket to enroll identity...");
             }
             authority_node_client
-                .present_token(ctx, &enrollment_ticket.one_time_code)
+                .present_token(
+                    ctx,
+                    &enrollment_ticket.one_time_code,
+                    BTreeMap::from_iter(self.parameters.clone()),
+                )
                 .await?
         };
         match enroll_status {
@@ -215,6 +277,50 @@ impl EnrollCommand {
         Ok(())
     }
 
+    async fn use_kubernetes_token(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        authority_node_client: &AuthorityNodeClient,
+        token_path: &Path,
+    ) -> Result<()> {
+        // The token is rotated by the kubelet, so it is read again on each attempt
+        let token = std::fs::read_to_string(token_path)
+            .into_diagnostic()
+            .wrap_err(format!(
+                "Failed to read the Kubernetes service account token at {}",
+                token_path.display()
+            ))?;
+
+        let pb = opts.terminal.spinner();
+        if let Some(pb) = pb.as_ref() {
+            pb.set_message("Using a Kubernetes service account token to enroll identity...");
+        }
+        authority_node_client
+            .enroll_with_kubernetes_token(ctx, token.trim())
+            .await
+            .map_err(Error::Retry)?;
+        Ok(())
+    }
+
+    async fn use_svid(
+        &self,
+        ctx: &Context,
+        opts: &CommandGlobalOpts,
+        authority_node_client: &AuthorityNodeClient,
+        request: AuthenticateSvid,
+    ) -> Result<()> {
+        let pb = opts.terminal.spinner();
+        if let Some(pb) = pb.as_ref() {
+            pb.set_message("Using an X.509-SVID to enroll identity...");
+        }
+        authority_node_client
+            .enroll_with_svid(ctx, request)
+            .await
+            .map_err(Error::Retry)?;
+        Ok(())
+    }
+
     async fn use_okta(
         &self,
         ctx: &Context,
@@ -249,6 +355,44 @@ impl EnrollCommand {
     }
 }
 
+/// Read an X.509-SVID with its intermediate certificates, and sign a message
+/// with the private key of the SVID to prove its possession
+fn svid_request(svid_path: &Path, key_path: &Path, message: &[u8]) -> Result<AuthenticateSvid> {
+    let certificates = CertificateDer::pem_file_iter(svid_path)
+        .and_then(|certificates| {
+            certificates
+                .map(|c| c.map(|c| c.to_vec()))
+                .collect::<std::result::Result<Vec<_>, _>>()
+        })
+        .into_diagnostic()
+        .wrap_err(format!(
+            "Failed to read the X.509-SVID at {}",
+            svid_path.display()
+        ))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .into_diagnostic()
+        .wrap_err(format!(
+            "Failed to read the SVID private key at {}",
+            key_path.display()
+        ))?;
+
+    let provider =
+        CryptoProvider::get_default().ok_or_else(|| miette!("No crypto provider is installed"))?;
+    let signer = provider
+        .key_provider
+        .load_private_key(key)
+        .into_diagnostic()?
+        .choose_scheme(&[
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            SignatureScheme::ED25519,
+            SignatureScheme::RSA_PKCS1_SHA256,
+        ])
+        .ok_or_else(|| miette!("The type of the SVID private key is not supported"))?;
+    let signature = signer.sign(message).into_diagnostic()?;
+    Ok(AuthenticateSvid::new(certificates, signature))
+}
+
 #[derive(Serialize)]
 struct ProjectEnrollOutput {
     identity: NamedIdentity,
@@ -3,13 +3,14 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use clap::Args;
+use clap::{Args, Subcommand};
 use colorful::Colorful;
 use miette::{miette, IntoDiagnostic};
 use tracing::debug;
 
 use crate::shared_args::{IdentityOpts, RetryOpts, TrustOpts};
 use crate::util::parsers::{duration_parser, duration_to_human_format};
+use crate::value_parsers::parse_key_val;
 use crate::{docs, Command, CommandGlobalOpts, Error, Result};
 use ockam::Context;
 use ockam_api::authenticator::direct::{
@@ -26,6 +27,14 @@ use ockam_api::terminal::fmt;
 use ockam_api::{fmt_info, fmt_log, fmt_ok, fmt_warn};
 use ockam_multiaddr::MultiAddr;
 
+mod list;
+mod revoke;
+mod show;
+
+use list::ListCommand;
+use revoke::RevokeCommand;
+use show::ShowCommand;
+
 const LONG_ABOUT: &str = include_str!("./static/ticket/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/ticket/after_long_help.txt");
 
@@ -36,10 +45,14 @@ pub const OCKAM_RELAY_ATTRIBUTE: &str = "ockam-relay";
 /// Add members to a Project, as an authorized enroller, directly, or via an enrollment ticket
 #[derive(Clone, Debug, Args)]
 #[command(
+args_conflicts_with_subcommands = true,
 long_about = docs::about(LONG_ABOUT),
 after_long_help = docs::after_help(AFTER_LONG_HELP),
 )]
 pub struct TicketCommand {
+    #[command(subcommand)]
+    subcommand: Option<TicketSubcommand>,
+
     /// Orchestrator address to resolve projects present in the `at` argument
     #[command(flatten)]
     identity_opts: IdentityOpts,
@@ -51,6 +64,10 @@ pub struct TicketCommand {
     #[arg(short, long = "attribute", value_name = "ATTRIBUTE")]
     attributes: Vec<String>,
 
+    /// Parameter in `name=regex` format, supplied by the identity using the ticket with `ockam project enroll --parameter name=value`. The value must match the regular expression and replaces the `${name}` placeholders in the values of the attributes. You can specify this option multiple times for multiple parameters
+    #[arg(long = "parameter", value_name = "PARAMETER", value_parser = parse_key_val::<String, String>)]
+    parameters: Vec<(String, String)>,
+
     /// Duration for which the enrollment ticket is valid, if you don't specify this, the default is 10 minutes. Examples: 10000ms, 600s, 600, 10m, 1h, 1d. If you don't specify a length sigil, it is assumed to be seconds
     #[arg(long = "expires-in", value_name = "DURATION", value_parser = duration_parser)]
     expires_in: Option<Duration>,
@@ -88,6 +105,9 @@ impl Command for TicketCommand {
     const NAME: &'static str = "project ticket";
 
     async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> Result<()> {
+        if let Some(subcommand) = self.subcommand {
+            return subcommand.run(ctx, opts).await;
+        }
         let cmd = self.parse_args(&opts).await?;
         let identity = opts
             .state
@@ -112,7 +132,8 @@ impl Command for TicketCommand {
             .await?;
 
         let attributes = cmd.attributes()?;
-        debug!(attributes = ?attributes, "Attributes passed");
+        let parameters = BTreeMap::from_iter(cmd.parameters.clone());
+        debug!(attributes = ?attributes, parameters = ?parameters, "Attributes passed");
 
         // Request an enrollment token that a future member can use to get a
         // credential.
@@ -122,7 +143,13 @@ impl Command for TicketCommand {
                 pb.set_message("Creating an enrollment ticket...");
             }
             authority_node_client
-                .create_token(ctx, attributes.clone(), cmd.expires_in, cmd.usage_count)
+                .create_token_with_parameters(
+                    ctx,
+                    attributes.clone(),
+                    parameters.clone(),
+                    cmd.expires_in,
+                    cmd.usage_count,
+                )
                 .await
                 .map_err(Error::Retry)?
         };
@@ -186,9 +213,26 @@ impl Command for TicketCommand {
             attributes_msg += "\n";
             attributes_msg
         };
+        let parameters_msg = if parameters.is_empty() {
+            "".to_string()
+        } else {
+            let mut parameters_msg =
+                fmt_log!("The redeemer must supply the following parameters:\n");
+            for (name, regex) in &parameters {
+                parameters_msg += &fmt_log!(
+                    "{}{} matching {}\n",
+                    fmt::INDENTATION,
+                    color_primary(name),
+                    color_primary(format!("\"{regex}\""))
+                );
+            }
+            parameters_msg += "\n";
+            parameters_msg
+        };
         opts.terminal.write_line(
             fmt_ok!("Created enrollment ticket\n\n")
                 + &attributes_msg
+                + &parameters_msg
                 + &fmt_info!(
                     "It will expire in {} and it can be used {}\n",
                     color_primary(duration_to_human_format(
@@ -217,6 +261,26 @@ impl Command for TicketCommand {
     }
 }
 
+#[derive(Clone, Debug, Subcommand)]
+enum TicketSubcommand {
+    #[command(display_order = 800)]
+    List(ListCommand),
+    #[command(display_order = 800)]
+    Show(ShowCommand),
+    #[command(display_order = 800)]
+    Revoke(RevokeCommand),
+}
+
+impl TicketSubcommand {
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> Result<()> {
+        match self {
+            TicketSubcommand::List(c) => c.run(ctx, opts).await,
+            TicketSubcommand::Show(c) => c.run(ctx, opts).await,
+            TicketSubcommand::Revoke(c) => c.run(ctx, opts).await,
+        }
+    }
+}
+
 impl TicketCommand {
     async fn parse_args(self, opts: &CommandGlobalOpts) -> miette::Result<Self> {
         // Handle expires_in and usage_count limits
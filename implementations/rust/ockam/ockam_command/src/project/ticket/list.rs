@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use clap::Args;
+
+use ockam::Context;
+use ockam_api::authenticator::enrollment_tokens::TokenIssuer;
+
+use crate::project_member::authority_client;
+use crate::shared_args::IdentityOpts;
+use crate::{Command, CommandGlobalOpts, Result};
+
+/// List the enrollment tickets which can still be used to enroll.
+/// Enrollers only see the tickets they created
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    identity_opts: IdentityOpts,
+
+    /// The Project which issued the tickets
+    #[arg(long, short, value_name = "PROJECT_NAME")]
+    project_name: Option<String>,
+}
+
+#[async_trait]
+impl Command for ListCommand {
+    const NAME: &'static str = "project ticket list";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> Result<()> {
+        let (authority_node_client, _) =
+            authority_client(ctx, &opts, &self.identity_opts, &self.project_name).await?;
+
+        let tokens = authority_node_client.list_tokens(ctx).await?;
+
+        let plain = opts
+            .terminal
+            .build_list(&tokens, "No enrollment tickets found on the Authority node")?;
+        opts.terminal
+            .stdout()
+            .plain(plain)
+            .json_obj(&tokens)?
+            .write_line()?;
+
+        Ok(())
+    }
+}
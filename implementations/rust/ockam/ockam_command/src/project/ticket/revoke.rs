@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_api::authenticator::enrollment_tokens::TokenIssuer;
+use ockam_api::colors::color_primary;
+use ockam_api::fmt_ok;
+
+use crate::project_member::authority_client;
+use crate::shared_args::IdentityOpts;
+use crate::{Command, CommandGlobalOpts, Result};
+
+/// Revoke an enrollment ticket so that it can't be used to enroll anymore.
+/// The identities which already used it stay members of the Project
+#[derive(Clone, Debug, Args)]
+pub struct RevokeCommand {
+    #[command(flatten)]
+    identity_opts: IdentityOpts,
+
+    /// The Project which issued the ticket
+    #[arg(long, short, value_name = "PROJECT_NAME")]
+    project_name: Option<String>,
+
+    /// The reference of the ticket
+    #[arg(value_name = "REFERENCE")]
+    reference: String,
+}
+
+#[async_trait]
+impl Command for RevokeCommand {
+    const NAME: &'static str = "project ticket revoke";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> Result<()> {
+        let (authority_node_client, _) =
+            authority_client(ctx, &opts, &self.identity_opts, &self.project_name).await?;
+
+        authority_node_client
+            .revoke_token(ctx, &self.reference)
+            .await?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "The enrollment ticket {} has been revoked",
+                color_primary(&self.reference)
+            ))
+            .json(serde_json::json!({ "reference": self.reference }))
+            .write_line()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use clap::Args;
+use serde::Serialize;
+use std::fmt::Write;
+
+use ockam::Context;
+use ockam_api::authenticator::enrollment_tokens::{EnrollmentTokenInfo, TokenIssuer};
+use ockam_api::authenticator::EnrollmentTokenRedemption;
+use ockam_api::colors::color_primary;
+use ockam_api::output::Output;
+use ockam_api::terminal::fmt;
+
+use crate::project_member::authority_client;
+use crate::shared_args::IdentityOpts;
+use crate::{Command, CommandGlobalOpts, Result};
+
+/// Show an enrollment ticket and the identities which used it to enroll
+#[derive(Clone, Debug, Args)]
+pub struct ShowCommand {
+    #[command(flatten)]
+    identity_opts: IdentityOpts,
+
+    /// The Project which issued the ticket
+    #[arg(long, short, value_name = "PROJECT_NAME")]
+    project_name: Option<String>,
+
+    /// The reference of the ticket
+    #[arg(value_name = "REFERENCE")]
+    reference: String,
+}
+
+#[async_trait]
+impl Command for ShowCommand {
+    const NAME: &'static str = "project ticket show";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> Result<()> {
+        let (authority_node_client, _) =
+            authority_client(ctx, &opts, &self.identity_opts, &self.project_name).await?;
+
+        // Tickets which were used up, expired or revoked are not listed anymore,
+        // but their redemptions are still available
+        let ticket = authority_node_client
+            .list_tokens(ctx)
+            .await?
+            .into_iter()
+            .find(|t| t.reference == self.reference);
+        let redemptions = authority_node_client
+            .get_token_redemptions(ctx, &self.reference)
+            .await?;
+
+        let output = TicketOutput {
+            reference: self.reference,
+            ticket,
+            redemptions,
+        };
+        opts.terminal
+            .stdout()
+            .plain(output.item()?)
+            .json_obj(&output)?
+            .write_line()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct TicketOutput {
+    reference: String,
+    ticket: Option<EnrollmentTokenInfo>,
+    redemptions: Vec<EnrollmentTokenRedemption>,
+}
+
+impl Output for TicketOutput {
+    fn item(&self) -> ockam_api::Result<String> {
+        let mut f = String::new();
+        match &self.ticket {
+            Some(ticket) => write!(f, "{}", ticket.item()?)?,
+            None => writeln!(
+                f,
+                "{}The ticket {} can't be used anymore",
+                fmt::PADDING,
+                color_primary(&self.reference)
+            )?,
+        }
+        writeln!(f)?;
+        if self.redemptions.is_empty() {
+            writeln!(f, "{}The ticket hasn't been used yet", fmt::PADDING)?;
+        } else {
+            writeln!(
+                f,
+                "{}The ticket has been used {} times:",
+                fmt::PADDING,
+                color_primary(self.redemptions.len())
+            )?;
+            for redemption in &self.redemptions {
+                write!(f, "{}", redemption.item()?)?;
+            }
+        }
+        Ok(f)
+    }
+}
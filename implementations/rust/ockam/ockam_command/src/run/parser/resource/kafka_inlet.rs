@@ -68,6 +68,11 @@ mod tests {
               encrypted-fields:
                 - one
                 - two
+              record-format: avro
+              avro-schema: schema.avsc
+              topic-encryption:
+                - logs.*=cleartext
+                - payments=fields:card.number
         "#;
         let parsed: KafkaInlet = serde_yaml::from_str(unnamed).unwrap();
         let default_node_name = "n1".to_string();
@@ -98,6 +103,16 @@ mod tests {
             cmds[0].encrypted_fields,
             vec!["one".to_string(), "two".to_string()]
         );
+        assert_eq!(cmds[0].record_format, inlet::create::RecordFormatArg::Avro);
+        assert_eq!(cmds[0].avro_schema, Some("schema.avsc".into()));
+        assert_eq!(
+            cmds[0]
+                .topic_encryption
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>(),
+            vec!["logs.*=cleartext", "payments=fields:card.number"]
+        );
 
         let named = r#"
             kafka-inlet:
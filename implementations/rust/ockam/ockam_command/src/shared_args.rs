@@ -22,10 +22,20 @@ pub struct TrustOpts {
     #[arg(long, value_name = "IDENTITY", value_parser = ChangeHistory::import_from_string)]
     pub authority_identity: Option<ChangeHistory>,
 
-    /// Address to the Authority node
+    /// Address to the Authority node. It is used to retrieve credentials when a credential scope
+    /// is given, and to receive the revocation lists of the Authority
     #[arg(long)]
     pub authority_route: Option<MultiAddr>,
 
+    /// Address to another replica of the Authority node, used to retrieve credentials
+    /// and revocation lists when the Authority node can't be reached. Can be repeated
+    #[arg(
+        long = "authority-fallback-route",
+        value_name = "ROUTE",
+        requires = "authority_route"
+    )]
+    pub authority_fallback_routes: Vec<MultiAddr>,
+
     /// Expect credential manually saved to the storage
     #[arg(long)]
     pub credential_scope: Option<String>,
@@ -25,6 +25,7 @@ use crate::docs;
 use crate::enroll::EnrollCommand;
 use crate::environment::EnvironmentCommand;
 use crate::flow_control::FlowControlCommand;
+use crate::http::outlet::HttpOutletCommand;
 use crate::identity::IdentityCommand;
 use crate::influxdb::inlet::InfluxDBInletCommand;
 use crate::influxdb::outlet::InfluxDBOutletCommand;
@@ -39,6 +40,7 @@ use crate::message::MessageCommand;
 use crate::migrate_database::MigrateDatabaseCommand;
 use crate::node::{NodeCommand, NodeSubcommand};
 use crate::policy::PolicyCommand;
+use crate::postgres::outlet::PostgresOutletCommand;
 use crate::project::ProjectCommand;
 use crate::project_admin::ProjectAdminCommand;
 use crate::project_member::ProjectMemberCommand;
@@ -60,6 +62,8 @@ use crate::tcp::connection::TcpConnectionCommand;
 use crate::tcp::inlet::TcpInletCommand;
 use crate::tcp::listener::TcpListenerCommand;
 use crate::tcp::outlet::TcpOutletCommand;
+use crate::udp::inlet::UdpInletCommand;
+use crate::udp::outlet::UdpOutletCommand;
 use crate::vault::VaultCommand;
 use crate::worker::WorkerCommand;
 use crate::Error;
@@ -97,6 +101,14 @@ pub enum OckamSubcommand {
     InfluxDBInlet(InfluxDBInletCommand),
     #[command(name = command::name("influxdb-outlet"), hide = command::hide("influxdb-outlet"))]
     InfluxDBOutlet(InfluxDBOutletCommand),
+    #[command(name = command::name("postgres-outlet"), hide = command::hide("postgres-outlet"))]
+    PostgresOutlet(PostgresOutletCommand),
+    #[command(name = command::name("http-outlet"), hide = command::hide("http-outlet"))]
+    HttpOutlet(HttpOutletCommand),
+    #[command(name = command::name("udp-inlet"), hide = command::hide("udp-inlet"))]
+    UdpInlet(UdpInletCommand),
+    #[command(name = command::name("udp-outlet"), hide = command::hide("udp-outlet"))]
+    UdpOutlet(UdpOutletCommand),
     #[command(name = command::name("rendezvous"), hide = command::hide("rendezvous") || docs::hide())]
     Rendezvous(RendezvousCommand),
     #[command(name = command::name("status"), hide = command::hide("status"))]
@@ -177,6 +189,10 @@ impl OckamSubcommand {
             OckamSubcommand::KafkaOutlet(c) => c.run(ctx, opts).await,
             OckamSubcommand::InfluxDBInlet(c) => c.run(ctx, opts).await,
             OckamSubcommand::InfluxDBOutlet(c) => c.run(ctx, opts).await,
+            OckamSubcommand::PostgresOutlet(c) => c.run(ctx, opts).await,
+            OckamSubcommand::HttpOutlet(c) => c.run(ctx, opts).await,
+            OckamSubcommand::UdpInlet(c) => c.run(ctx, opts).await,
+            OckamSubcommand::UdpOutlet(c) => c.run(ctx, opts).await,
             OckamSubcommand::Rendezvous(c) => c.run(ctx, opts).await,
             OckamSubcommand::Status(c) => c.run(ctx, opts).await,
             OckamSubcommand::Reset(c) => c.run(ctx, opts).await,
@@ -325,6 +341,10 @@ impl OckamSubcommand {
             OckamSubcommand::KafkaOutlet(c) => c.name(),
             OckamSubcommand::InfluxDBInlet(c) => c.name(),
             OckamSubcommand::InfluxDBOutlet(c) => c.name(),
+            OckamSubcommand::PostgresOutlet(c) => c.name(),
+            OckamSubcommand::HttpOutlet(c) => c.name(),
+            OckamSubcommand::UdpInlet(c) => c.name(),
+            OckamSubcommand::UdpOutlet(c) => c.name(),
             OckamSubcommand::Rendezvous(c) => c.name(),
             OckamSubcommand::Status(c) => c.name(),
             OckamSubcommand::Reset(c) => c.name(),
@@ -16,6 +16,9 @@ use ockam::identity::Identifier;
 use ockam::transport::SchemeHostnamePort;
 use ockam::Context;
 use ockam_abac::PolicyExpression;
+use ockam_api::acme::{
+    AcmeCertificateConfig, AcmeChallenge, DEFAULT_HTTP01_LISTEN_ADDRESS, LETS_ENCRYPT_DIRECTORY_URL,
+};
 use ockam_api::address::extract_address_value;
 use ockam_api::cli_state::journeys::{
     JourneyEvent, NODE_NAME, TCP_INLET_ALIAS, TCP_INLET_AT, TCP_INLET_CONNECTION_STATUS,
@@ -23,7 +26,7 @@ use ockam_api::cli_state::journeys::{
 };
 use ockam_api::cli_state::{random_name, CliState};
 use ockam_api::colors::{color_primary, color_primary_alt};
-use ockam_api::nodes::models::portal::InletStatus;
+use ockam_api::nodes::models::portal::{InletStatus, InletTlsCertificateProvider};
 use ockam_api::nodes::service::tcp_inlets::Inlets;
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_api::{fmt_info, fmt_log, fmt_ok, fmt_warn, ConnectionStatus};
@@ -32,6 +35,7 @@ use ockam_multiaddr::proto;
 use ockam_multiaddr::{MultiAddr, Protocol as _};
 use ockam_node::compat::asynchronous::resolve_peer;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 use tracing::trace;
@@ -57,6 +61,7 @@ pub struct CreateCommand {
     /// To enable TLS, the `ockam-tls-certificate` credential attribute is required.
     /// It will use the default project TLS certificate provider `/project/default/service/tls_certificate_provider`.
     /// To specify a different certificate provider, use `--tls-certificate-provider`.
+    /// To obtain the certificate from an ACME server, like Let's Encrypt, use `--acme-domain`.
     #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", hide_default_value = true, default_value_t = tcp_inlet_default_from_addr(), value_parser = hostname_parser)]
     pub from: SchemeHostnamePort,
 
@@ -155,6 +160,52 @@ pub struct CreateCommand {
     #[arg(long, value_name = "ROUTE", hide = true)]
     pub tls_certificate_provider: Option<MultiAddr>,
 
+    /// Enable TLS for the TCP Inlet with a certificate for this domain, obtained and renewed
+    /// by the node from an ACME server. The certificate is stored in the node database.
+    #[arg(
+        long,
+        display_order = 901,
+        id = "DOMAIN",
+        conflicts_with = "tls_certificate_provider"
+    )]
+    pub acme_domain: Option<String>,
+
+    /// Directory URL of the ACME server. Let's Encrypt is used by default
+    #[arg(long, display_order = 901, id = "DIRECTORY_URL", requires = "DOMAIN", default_value = LETS_ENCRYPT_DIRECTORY_URL)]
+    pub acme_directory_url: String,
+
+    /// Email address registered as a contact for the ACME account
+    #[arg(long, display_order = 901, id = "EMAIL", requires = "DOMAIN")]
+    pub acme_email: Option<String>,
+
+    /// Agree to the terms of service of the ACME server, which is required to create an ACME
+    /// account. Review the terms of service of the server before setting this flag
+    #[arg(long, display_order = 901, requires = "DOMAIN")]
+    pub acme_agree_tos: bool,
+
+    /// Address where the node serves the ACME HTTP-01 challenges.
+    /// The ACME server validates the challenges on the port 80 of the domain
+    #[arg(long, display_order = 901, id = "HTTP_ADDRESS", requires = "DOMAIN", default_value = DEFAULT_HTTP01_LISTEN_ADDRESS, value_parser = hostname_parser)]
+    pub acme_http_address: SchemeHostnamePort,
+
+    /// Use a DNS-01 challenge instead of an HTTP-01 challenge. The DNS TXT record of the challenge
+    /// is published by the hook command configured on the node with `OCKAM_ACME_DNS01_HOOK`.
+    /// The command is called with the arguments `present <record name> <record value>`,
+    /// then with the arguments `cleanup <record name> <record value>`
+    #[arg(long, display_order = 901, requires = "DOMAIN")]
+    pub acme_dns: bool,
+
+    /// Publish the DNS-01 challenge with another hook command than the one of the node.
+    /// The command must be listed by the node in `OCKAM_ACME_DNS01_HOOK_ALLOW_LIST`
+    #[arg(long, display_order = 901, id = "COMMAND", requires = "DOMAIN")]
+    pub acme_dns_hook: Option<String>,
+
+    /// Require the TLS clients to present a certificate issued by one of the certificate authorities
+    /// of this PEM file. The attributes of the client certificate can then be used in the inlet policy,
+    /// for example `(member? "legacy" tls.client.ou)`
+    #[arg(long, display_order = 902, value_name = "PATH")]
+    pub tls_client_ca: Option<PathBuf>,
+
     /// Skip Portal handshake for lower latency, but also lower throughput
     /// WARNING: This flag value should be equal on both ends of a portal (inlet and outlet)
     #[arg(long, env = "OCKAM_TCP_PORTAL_SKIP_HANDSHAKE", value_parser = FalseyValueParser::default())]
@@ -208,7 +259,8 @@ impl Command for CreateCommand {
                         cmd.udp || cmd.from.is_udp(),
                         cmd.no_tcp_fallback,
                         cmd.privileged,
-                        &cmd.tls_certificate_provider,
+                        &cmd.inlet_tls_certificate_provider(),
+                        &cmd.tls_client_ca_certificates()?,
                         cmd.skip_handshake,
                         cmd.enable_nagle,
                     )
@@ -298,6 +350,39 @@ impl CreateCommand {
         MultiAddr::from_str(&self.to).unwrap()
     }
 
+    /// Return the provider of the TLS certificate, if TLS is enabled
+    pub fn inlet_tls_certificate_provider(&self) -> Option<InletTlsCertificateProvider> {
+        if let Some(domain) = self.acme_domain.as_ref() {
+            let challenge = match self.acme_dns_hook.as_ref() {
+                Some(hook) => AcmeChallenge::dns01_with_hook(hook),
+                None if self.acme_dns => AcmeChallenge::dns01(),
+                None => AcmeChallenge::Http01 {
+                    listen_address: self.acme_http_address.hostname_port().clone(),
+                },
+            };
+            let mut config = AcmeCertificateConfig::new(domain, challenge)
+                .with_directory_url(&self.acme_directory_url);
+            if let Some(email) = self.acme_email.as_ref() {
+                config = config.with_contact_email(email);
+            }
+            if self.acme_agree_tos {
+                config = config.with_terms_of_service_agreed();
+            }
+            return Some(InletTlsCertificateProvider::Acme(config));
+        }
+        self.tls_certificate_provider
+            .clone()
+            .map(InletTlsCertificateProvider::Remote)
+    }
+
+    /// Return the certificate authorities trusted to issue TLS client certificates, if any
+    pub fn tls_client_ca_certificates(&self) -> miette::Result<Option<String>> {
+        self.tls_client_ca
+            .as_ref()
+            .map(|path| std::fs::read_to_string(path).into_diagnostic())
+            .transpose()
+    }
+
     pub async fn secure_channel_identifier(
         &self,
         state: &CliState,
@@ -361,7 +446,7 @@ impl CreateCommand {
         self.tls_certificate_provider =
             if let Some(tls_certificate_provider) = &self.tls_certificate_provider {
                 Some(tls_certificate_provider.clone())
-            } else if self.tls || self.from.is_tls() {
+            } else if self.acme_domain.is_none() && (self.tls || self.from.is_tls()) {
                 Some(MultiAddr::from_str(
                     "/project/default/service/tls_certificate_provider",
                 )?)
@@ -369,6 +454,19 @@ impl CreateCommand {
                 None
             };
 
+        if self.acme_domain.is_some() && !self.acme_agree_tos {
+            return Err(miette!(
+                "--acme-agree-tos is required to obtain a certificate from an ACME server"
+            ))?;
+        }
+
+        if self.tls_client_ca.is_some()
+            && self.tls_certificate_provider.is_none()
+            && self.acme_domain.is_none()
+        {
+            return Err(miette!("--tls-client-ca can only be used with a TLS inlet"))?;
+        }
+
         Ok(self)
     }
 
@@ -1,11 +1,15 @@
 use crate::node::util::initialize_default_node;
-use crate::util::parsers::hostname_parser;
+use crate::util::parsers::{duration_parser, hostname_parser};
 use crate::{docs, Command, CommandGlobalOpts};
 use async_trait::async_trait;
 use clap::builder::FalseyValueParser;
 use clap::Args;
 use colorful::Colorful;
 use miette::IntoDiagnostic;
+use ockam::tcp::{
+    TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletTlsConfig, TlsCertificate,
+    TlsPublicKeyPin,
+};
 use ockam::transport::SchemeHostnamePort;
 use ockam::Address;
 use ockam::Context;
@@ -15,11 +19,13 @@ use ockam_api::cli_state::journeys::{
     JourneyEvent, NODE_NAME, TCP_OUTLET_AT, TCP_OUTLET_FROM, TCP_OUTLET_TO,
 };
 use ockam_api::colors::{color_primary, color_primary_alt};
-use ockam_api::nodes::models::portal::OutletStatus;
+use ockam_api::nodes::models::portal::{OutletLoadBalancing, OutletStatus};
 use ockam_api::nodes::service::tcp_outlets::Outlets;
 use ockam_api::nodes::BackgroundNodeClient;
 use ockam_api::{fmt_info, fmt_log, fmt_ok, fmt_warn};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
 const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
 const LONG_ABOUT: &str = include_str!("./static/create/long_about.txt");
@@ -83,6 +89,69 @@ pub struct CreateCommand {
     /// Enable Nagle's algorithm for potentially higher throughput, but higher latency
     #[arg(long, env = "OCKAM_TCP_PORTAL_ENABLE_NAGLE", value_parser = FalseyValueParser::default())]
     pub enable_nagle: bool,
+
+    /// Additional TCP address of a replica of your TCP server: domain:port.
+    /// Can be repeated. The connections of the Outlet are then distributed between all its targets
+    #[arg(long, display_order = 905, id = "ADDITIONAL_SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub also_to: Vec<SchemeHostnamePort>,
+
+    /// Strategy used to choose the target of a new connection when the Outlet has several targets:
+    /// round-robin, least-connections, random or consistent-hash.
+    /// The consistent-hash strategy sends the connections of a given identity to the same target
+    #[arg(long, display_order = 905, id = "STRATEGY")]
+    pub load_balancing: Option<TcpOutletLoadBalancing>,
+
+    /// If set, the targets of the Outlet are checked with a TCP connection at this interval,
+    /// and the targets which can't be reached are not used until they recover
+    #[arg(long, display_order = 906, id = "INTERVAL", value_parser = duration_parser)]
+    pub health_check_interval: Option<Duration>,
+
+    /// Time after which a health check connection attempt is considered failed
+    #[arg(long, display_order = 906, id = "TIMEOUT", requires = "INTERVAL", value_parser = duration_parser)]
+    pub health_check_timeout: Option<Duration>,
+
+    /// Number of consecutive failed health checks after which a target is ejected
+    #[arg(long, display_order = 906, requires = "INTERVAL")]
+    pub unhealthy_threshold: Option<u32>,
+
+    /// Number of consecutive successful health checks after which an ejected target is used again
+    #[arg(long, display_order = 906, requires = "INTERVAL")]
+    pub healthy_threshold: Option<u32>,
+
+    /// Path to a PEM bundle of the certificate authorities trusted to issue the certificate
+    /// of the TCP server. If not set, the system certificates are used. Implies --tls
+    #[arg(long, display_order = 907, id = "CA_PATH")]
+    pub tls_ca: Option<PathBuf>,
+
+    /// Path to a PEM certificate chain presented to a TCP server requiring TLS client authentication.
+    /// Implies --tls
+    #[arg(
+        long,
+        display_order = 907,
+        id = "CERTIFICATE_PATH",
+        requires = "KEY_PATH"
+    )]
+    pub tls_client_certificate: Option<PathBuf>,
+
+    /// Path to the PEM private key of the TLS client certificate
+    #[arg(
+        long,
+        display_order = 907,
+        id = "KEY_PATH",
+        requires = "CERTIFICATE_PATH"
+    )]
+    pub tls_client_key: Option<PathBuf>,
+
+    /// Server name sent with SNI and expected in the certificate of the TCP server,
+    /// instead of the hostname of --to. Implies --tls
+    #[arg(long, display_order = 907, id = "SERVER_NAME")]
+    pub tls_server_name: Option<String>,
+
+    /// Hex encoded SHA-256 digest of a public key expected in the certificate of the TCP server.
+    /// Can be repeated. The certificate is still verified with the trusted certificate authorities.
+    /// Implies --tls
+    #[arg(long, display_order = 907, id = "SHA256")]
+    pub tls_pin: Vec<TlsPublicKeyPin>,
 }
 
 #[async_trait]
@@ -112,6 +181,8 @@ impl Command for CreateCommand {
                 cmd.privileged,
                 cmd.skip_handshake,
                 cmd.enable_nagle,
+                cmd.load_balancing_config(),
+                cmd.tls_config()?,
             )
             .await?
         };
@@ -157,9 +228,82 @@ impl CreateCommand {
             self.name = Some(from.clone());
         }
 
+        if self.privileged && self.load_balancing_config().is_some() {
+            return Err(miette::miette!(
+                "A privileged TCP Outlet can't be load balanced between several targets"
+            ));
+        }
+
+        if self.privileged && self.tls_config()?.is_some() {
+            return Err(miette::miette!(
+                "The TLS options can't be used with a privileged TCP Outlet"
+            ));
+        }
+
         Ok(self)
     }
 
+    /// Return the TLS configuration of the connections to the TCP server,
+    /// if any TLS option other than --tls is used
+    fn tls_config(&self) -> miette::Result<Option<TcpOutletTlsConfig>> {
+        if self.tls_ca.is_none()
+            && self.tls_client_certificate.is_none()
+            && self.tls_server_name.is_none()
+            && self.tls_pin.is_empty()
+        {
+            return Ok(None);
+        }
+
+        let mut tls_config = TcpOutletTlsConfig::new();
+        if let Some(path) = &self.tls_ca {
+            tls_config =
+                tls_config.with_ca_certificates_pem(std::fs::read(path).into_diagnostic()?);
+        }
+        if let (Some(certificate), Some(key)) = (&self.tls_client_certificate, &self.tls_client_key)
+        {
+            tls_config = tls_config.with_client_certificate(TlsCertificate {
+                full_chain_pem: std::fs::read(certificate).into_diagnostic()?,
+                private_key_pem: std::fs::read(key).into_diagnostic()?,
+            });
+        }
+        if let Some(server_name) = &self.tls_server_name {
+            tls_config = tls_config.with_server_name(server_name);
+        }
+        for pin in &self.tls_pin {
+            tls_config = tls_config.with_public_key_pin(pin.clone());
+        }
+        Ok(Some(tls_config))
+    }
+
+    fn load_balancing_config(&self) -> Option<OutletLoadBalancing> {
+        if self.also_to.is_empty()
+            && self.load_balancing.is_none()
+            && self.health_check_interval.is_none()
+        {
+            return None;
+        }
+
+        let health_check = self.health_check_interval.map(|interval| {
+            let mut health_check = TcpOutletHealthCheck::default().with_interval(interval);
+            if let Some(timeout) = self.health_check_timeout {
+                health_check = health_check.with_timeout(timeout);
+            }
+            if let Some(threshold) = self.unhealthy_threshold {
+                health_check = health_check.with_unhealthy_threshold(threshold);
+            }
+            if let Some(threshold) = self.healthy_threshold {
+                health_check = health_check.with_healthy_threshold(threshold);
+            }
+            health_check
+        });
+
+        Some(OutletLoadBalancing::new(
+            self.also_to.iter().cloned().map(Into::into).collect(),
+            self.load_balancing.unwrap_or_default(),
+            health_check,
+        ))
+    }
+
     pub async fn add_outlet_created_journey_event(
         &self,
         opts: &CommandGlobalOpts,
@@ -184,6 +328,8 @@ impl CreateCommand {
 #[cfg(test)]
 mod tests {
     use crate::run::parser::resource::utils::parse_cmd_from_args;
+    use crate::tcp::outlet::{TcpOutletCommand, TcpOutletSubCommand};
+    use crate::OckamSubcommand;
 
     use super::*;
 
@@ -195,4 +341,65 @@ mod tests {
         );
         assert!(cmd.is_ok());
     }
+
+    fn parse_create_command(args: &[&str]) -> CreateCommand {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        match parse_cmd_from_args(CreateCommand::NAME, &args).unwrap() {
+            OckamSubcommand::TcpOutlet(TcpOutletCommand {
+                subcommand: TcpOutletSubCommand::Create(cmd),
+            }) => cmd,
+            _ => panic!("expected a tcp-outlet create command"),
+        }
+    }
+
+    #[test]
+    fn load_balancing_is_only_configured_with_several_targets_or_a_strategy() {
+        let cmd = parse_create_command(&["--to", "127.0.0.1:5000"]);
+        assert!(cmd.load_balancing_config().is_none());
+
+        let cmd = parse_create_command(&[
+            "--to",
+            "127.0.0.1:5000",
+            "--also-to",
+            "127.0.0.1:5001",
+            "--also-to",
+            "127.0.0.1:5002",
+            "--load-balancing",
+            "least-connections",
+            "--health-check-interval",
+            "5s",
+        ]);
+        let config = cmd.load_balancing_config().unwrap();
+        assert_eq!(config.additional_targets.len(), 2);
+        assert_eq!(config.strategy, TcpOutletLoadBalancing::LeastConnections);
+        assert_eq!(
+            config.health_check.unwrap().interval(),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn tls_options_create_a_tls_config() {
+        let cmd = parse_create_command(&["--to", "127.0.0.1:5000", "--tls"]);
+        assert!(cmd.tls_config().unwrap().is_none());
+
+        let pin = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let cmd = parse_create_command(&[
+            "--to",
+            "10.0.0.5:5432",
+            "--tls-server-name",
+            "db.internal",
+            "--tls-pin",
+            pin,
+        ]);
+        let tls_config = cmd.tls_config().unwrap().unwrap();
+        assert_eq!(tls_config.server_name, Some("db.internal".to_string()));
+        assert_eq!(tls_config.public_key_pins.len(), 1);
+        assert_eq!(tls_config.public_key_pins[0].to_string(), pin);
+        assert!(tls_config.ca_certificates_pem.is_none());
+
+        let args = ["--to", "127.0.0.1:5000", "--tls-pin", "not-a-pin"];
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        assert!(parse_cmd_from_args(CreateCommand::NAME, &args).is_err());
+    }
 }
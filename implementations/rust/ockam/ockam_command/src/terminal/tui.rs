@@ -292,6 +292,8 @@ pub enum PluralTerm {
     ProjectAdmin,
     TcpInlet,
     TcpOutlet,
+    UdpInlet,
+    UdpOutlet,
     KafkaInlet,
     KafkaOutlet,
     Policy,
@@ -311,6 +313,8 @@ impl PluralTerm {
             PluralTerm::ProjectAdmin => "project admin",
             PluralTerm::TcpInlet => "tcp inlet",
             PluralTerm::TcpOutlet => "tcp outlet",
+            PluralTerm::UdpInlet => "udp inlet",
+            PluralTerm::UdpOutlet => "udp outlet",
             PluralTerm::KafkaInlet => "kafka inlet",
             PluralTerm::KafkaOutlet => "kafka outlet",
             PluralTerm::Policy => "policy",
@@ -330,6 +334,8 @@ impl PluralTerm {
             PluralTerm::ProjectAdmin => "project admins",
             PluralTerm::TcpInlet => "tcp inlets",
             PluralTerm::TcpOutlet => "tcp outlets",
+            PluralTerm::UdpInlet => "udp inlets",
+            PluralTerm::UdpOutlet => "udp outlets",
             PluralTerm::KafkaInlet => "kafka inlets",
             PluralTerm::KafkaOutlet => "kafka outlets",
             PluralTerm::Policy => "policies",
@@ -0,0 +1,133 @@
+use crate::node::util::initialize_default_node;
+use crate::tcp::util::alias_parser;
+use crate::util::parsers::{hostname_parser, non_zero_duration_parser};
+use crate::util::process_nodes_multiaddr;
+use crate::{Command, CommandGlobalOpts};
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use ockam::identity::Identifier;
+use ockam::transport::SchemeHostnamePort;
+use ockam::Context;
+use ockam_abac::PolicyExpression;
+use ockam_api::address::extract_address_value;
+use ockam_api::cli_state::random_name;
+use ockam_api::colors::color_primary;
+use ockam_api::fmt_ok;
+use ockam_api::nodes::service::udp_portals::UdpPortals;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_multiaddr::{proto, MultiAddr, Protocol as _};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Create UDP Inlets
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Assign a name to this UDP Inlet
+    #[arg(id = "NAME", value_parser = alias_parser)]
+    pub name: Option<String>,
+
+    /// Node on which to start the UDP Inlet.
+    #[arg(long, display_order = 900, id = "NODE_NAME", value_parser = extract_address_value)]
+    pub at: Option<String>,
+
+    /// Address on which to receive datagrams, in the format `<hostname>:<port>`.
+    /// At least the port must be provided. The default hostname is `127.0.0.1`.
+    #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub from: SchemeHostnamePort,
+
+    /// Route to a UDP Outlet, for example `/node/n/service/my-udp-outlet` or
+    /// `/project/myproject/service/forward_to_myrelay/secure/api/service/my-udp-outlet`.
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    pub to: String,
+
+    /// Authorized identifier for secure channel connection
+    #[arg(long, name = "AUTHORIZED", display_order = 900)]
+    pub authorized: Option<Identifier>,
+
+    /// Policy expression that will be used for access control to the UDP Inlet.
+    /// If you don't provide it, the policy set for the "udp-inlet" resource type will be used.
+    ///
+    /// You can check the fallback policy with `ockam policy show --resource-type udp-inlet`.
+    #[arg(
+        long,
+        visible_alias = "expression",
+        display_order = 900,
+        id = "POLICY_EXPRESSION"
+    )]
+    pub allow: Option<PolicyExpression>,
+
+    /// Time after which the session of a source address is closed when it doesn't
+    /// send or receive any datagram. Defaults to 60s.
+    #[arg(long, display_order = 900, id = "IDLE_TIMEOUT", value_parser = non_zero_duration_parser)]
+    pub idle_timeout: Option<Duration>,
+
+    /// Maximum number of source addresses with an open session at the same time.
+    /// The datagrams of new source addresses are dropped when this limit is reached.
+    /// Defaults to 1024.
+    #[arg(long, display_order = 900, id = "MAX_SESSIONS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_sessions: Option<u64>,
+}
+
+#[async_trait]
+impl Command for CreateCommand {
+    const NAME: &'static str = "udp-inlet create";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        initialize_default_node(ctx, &opts).await?;
+        let cmd = self.parse_args(&opts).await?;
+
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+        let inlet_status = {
+            let pb = opts.terminal.spinner();
+            if let Some(pb) = pb.as_ref() {
+                pb.set_message(format!(
+                    "Creating a UDP Inlet at {}...\n",
+                    color_primary(cmd.from.hostname_port().to_string())
+                ));
+            }
+            node.create_udp_inlet(
+                ctx,
+                cmd.from.hostname_port().clone(),
+                cmd.to(),
+                cmd.name.clone().unwrap_or_else(random_name),
+                cmd.authorized.clone(),
+                cmd.allow.clone(),
+                cmd.idle_timeout,
+                cmd.max_sessions,
+            )
+            .await?
+        };
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Created a new UDP Inlet in the Node {} bound to {}, sending datagrams to {}\n",
+                color_primary(node.node_name()),
+                color_primary(&inlet_status.bind_addr),
+                color_primary(&cmd.to)
+            ))
+            .machine(&inlet_status.bind_addr)
+            .json_obj(&inlet_status)?
+            .write_line()?;
+        Ok(())
+    }
+}
+
+impl CreateCommand {
+    pub fn to(&self) -> MultiAddr {
+        MultiAddr::from_str(&self.to).unwrap()
+    }
+
+    async fn parse_args(mut self, opts: &CommandGlobalOpts) -> miette::Result<Self> {
+        let to = MultiAddr::from_str(&self.to).into_diagnostic()?;
+        if to.matches(0, &[proto::Project::CODE.into()]) && self.authorized.is_some() {
+            return Err(miette!(
+                "--authorized can not be used with project addresses"
+            ))?;
+        }
+        self.to = process_nodes_multiaddr(&to, &opts.state).await?.to_string();
+        Ok(self)
+    }
+}
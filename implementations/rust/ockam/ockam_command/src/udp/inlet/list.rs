@@ -0,0 +1,66 @@
+use clap::Args;
+use colorful::Colorful;
+use ockam_api::colors::color_primary;
+use ockam_api::fmt_info;
+use tokio::sync::Mutex;
+use tokio::try_join;
+
+use crate::node::NodeOpts;
+use crate::CommandGlobalOpts;
+use ockam_api::nodes::models::udp_portal::UdpInletStatus;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+/// List the UDP Inlets at a given node
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn name(&self) -> String {
+        "udp-inlet list".into()
+    }
+
+    pub async fn run(&self, ctx: &Context, opts: CommandGlobalOpts) -> miette::Result<()> {
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &self.node_opts.at_node).await?;
+
+        let is_finished: Mutex<bool> = Mutex::new(false);
+
+        let send_req = async {
+            let res: Vec<UdpInletStatus> = node.ask(ctx, Request::get("/node/udp_inlet")).await?;
+            *is_finished.lock().await = true;
+            Ok(res)
+        };
+
+        let output_messages = vec![format!(
+            "Listing UDP Inlets on node {}...\n",
+            color_primary(node.node_name())
+        )];
+
+        let progress_output = opts.terminal.loop_messages(&output_messages, &is_finished);
+
+        let (inlets, _) = try_join!(send_req, progress_output)?;
+
+        let list: String = {
+            let empty_message = fmt_info!(
+                "No UDP Inlets found on node {}",
+                color_primary(node.node_name())
+            );
+            match inlets.is_empty() {
+                true => empty_message,
+                false => opts.terminal.build_list(&inlets, &empty_message)?,
+            }
+        };
+
+        opts.terminal
+            .stdout()
+            .plain(list)
+            .json(serde_json::json!(inlets))
+            .write_line()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use clap::{Args, Subcommand};
+
+use create::CreateCommand;
+use delete::DeleteCommand;
+use list::ListCommand;
+use ockam_node::Context;
+
+use crate::{docs, Command, CommandGlobalOpts};
+
+pub(crate) mod create;
+mod delete;
+mod list;
+
+const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
+
+/// Manage UDP Inlets
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    long_about = docs::about(LONG_ABOUT),
+)]
+pub struct UdpInletCommand {
+    #[command(subcommand)]
+    pub subcommand: UdpInletSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdpInletSubCommand {
+    Create(CreateCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+}
+
+impl UdpInletCommand {
+    pub async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> miette::Result<()> {
+        match self.subcommand {
+            UdpInletSubCommand::Create(c) => c.run(ctx, opts).await,
+            UdpInletSubCommand::Delete(c) => c.run(ctx, opts).await,
+            UdpInletSubCommand::List(c) => c.run(ctx, opts).await,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match &self.subcommand {
+            UdpInletSubCommand::Create(c) => c.name(),
+            UdpInletSubCommand::Delete(c) => c.name(),
+            UdpInletSubCommand::List(c) => c.name(),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::node::util::initialize_default_node;
+use crate::util::parsers::{hostname_parser, non_zero_duration_parser};
+use crate::{Command, CommandGlobalOpts};
+use async_trait::async_trait;
+use clap::Args;
+use colorful::Colorful;
+use ockam::transport::SchemeHostnamePort;
+use ockam::{Address, Context};
+use ockam_abac::PolicyExpression;
+use ockam_api::address::extract_address_value;
+use ockam_api::colors::color_primary;
+use ockam_api::nodes::service::udp_portals::UdpPortals;
+use ockam_api::nodes::BackgroundNodeClient;
+use ockam_api::{fmt_log, fmt_ok, fmt_warn};
+use std::time::Duration;
+
+/// Create UDP Outlets
+#[derive(Clone, Debug, Args)]
+pub struct CreateCommand {
+    /// Address of your UDP Outlet, which is part of a route used in other commands.
+    /// This unique address identifies the UDP Outlet worker on the Node on your local machine.
+    /// Examples are `/service/my-udp-outlet` or `my-udp-outlet`.
+    /// If not provided, a random address will be generated.
+    /// You will need this address when creating a UDP Inlet using `ockam udp-inlet create`.
+    #[arg(value_parser = extract_address_value)]
+    pub name: Option<String>,
+
+    /// Address of the UDP server, in the format `<hostname>:<port>`.
+    /// At least the port must be provided. The default hostname is `127.0.0.1`.
+    #[arg(long, display_order = 900, id = "SOCKET_ADDRESS", value_parser = hostname_parser)]
+    pub to: SchemeHostnamePort,
+
+    /// Alternative to the <NAME> positional argument.
+    /// Address of your UDP Outlet, which is part of a route used in other commands.
+    #[arg(long, display_order = 902, id = "OUTLET_ADDRESS", value_parser = extract_address_value)]
+    pub from: Option<String>,
+
+    /// Your UDP Outlet will be created on this node. If you don't provide it, the default
+    /// node will be used
+    #[arg(long, display_order = 903, id = "NODE_NAME", value_parser = extract_address_value)]
+    pub at: Option<String>,
+
+    /// Policy expression that will be used for access control to the UDP Outlet.
+    /// If you don't provide it, the policy set for the "udp-outlet" resource type will be used.
+    ///
+    /// You can check the fallback policy with `ockam policy show --resource-type udp-outlet`.
+    #[arg(
+        long,
+        visible_alias = "expression",
+        display_order = 904,
+        id = "POLICY_EXPRESSION"
+    )]
+    pub allow: Option<PolicyExpression>,
+
+    /// Time after which the socket opened for an Inlet session is closed when it doesn't
+    /// send or receive any datagram. Defaults to 60s.
+    #[arg(long, display_order = 905, id = "IDLE_TIMEOUT", value_parser = non_zero_duration_parser)]
+    pub idle_timeout: Option<Duration>,
+
+    /// Maximum number of Inlet sessions with an open socket at the same time.
+    /// The datagrams of new Inlet sessions are dropped when this limit is reached.
+    /// Defaults to 1024.
+    #[arg(long, display_order = 906, id = "MAX_SESSIONS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_sessions: Option<u64>,
+}
+
+#[async_trait]
+impl Command for CreateCommand {
+    const NAME: &'static str = "udp-outlet create";
+
+    async fn run(self, ctx: &Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+        initialize_default_node(ctx, &opts).await?;
+        let cmd = self.parse_args(&opts).await?;
+
+        let node = BackgroundNodeClient::create(ctx, &opts.state, &cmd.at).await?;
+        let outlet_status = {
+            let pb = opts.terminal.spinner();
+            if let Some(pb) = pb.as_ref() {
+                pb.set_message(format!(
+                    "Creating a new UDP Outlet to {}...\n",
+                    color_primary(cmd.to.hostname_port().to_string())
+                ));
+            }
+            node.create_udp_outlet(
+                ctx,
+                cmd.to.hostname_port().clone(),
+                cmd.name.clone().map(Address::from).as_ref(),
+                cmd.allow.clone(),
+                cmd.idle_timeout,
+                cmd.max_sessions,
+            )
+            .await?
+        };
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "Created a new UDP Outlet in the Node {} at {} sending to {}\n",
+                color_primary(node.node_name()),
+                color_primary(&outlet_status.worker_addr),
+                color_primary(cmd.to.hostname_port().to_string())
+            ))
+            .machine(&outlet_status.worker_addr)
+            .json_obj(&outlet_status)?
+            .write_line()?;
+        Ok(())
+    }
+}
+
+impl CreateCommand {
+    async fn parse_args(mut self, opts: &CommandGlobalOpts) -> miette::Result<Self> {
+        if let Some(from) = self.from.as_ref() {
+            if self.name.is_some() {
+                opts.terminal.write_line(
+                    fmt_warn!("The <NAME> argument is being overridden by the --from flag")
+                        + &fmt_log!("Consider using either the <NAME> argument or the --from flag"),
+                )?;
+            }
+            self.name = Some(from.clone());
+        }
+
+        Ok(self)
+    }
+}
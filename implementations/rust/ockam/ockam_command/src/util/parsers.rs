@@ -48,6 +48,17 @@ pub(crate) fn duration_parser(arg: &str) -> std::result::Result<Duration, clap::
     parse_duration(arg).map_err(|_| Error::raw(ErrorKind::InvalidValue, "Invalid duration"))
 }
 
+pub(crate) fn non_zero_duration_parser(arg: &str) -> std::result::Result<Duration, clap::Error> {
+    let duration = duration_parser(arg)?;
+    if duration.is_zero() {
+        return Err(Error::raw(
+            ErrorKind::InvalidValue,
+            "The duration must be greater than 0",
+        ));
+    }
+    Ok(duration)
+}
+
 pub(crate) fn duration_to_human_format(duration: &Duration) -> String {
     let mut parts = vec![];
     let secs = duration.as_secs();
@@ -62,3 +62,21 @@ teardown() {
   assert_output --partial "invalid value 'component.db or'"
   assert_output --partial 'successfully parsed: `component.db`, but ` or` cannot be parsed'
 }
+
+@test "policies - evaluate a policy" {
+  run_success "$OCKAM" identity create i
+  identifier=$($OCKAM identity show i)
+
+  run_success $OCKAM policy create --resource my_policy --expression '(= subject.component "web")'
+  run_success $OCKAM policy evaluate my_policy --subject $identifier --attribute component=web --output json
+  assert_output --partial '"is_authorized": true'
+
+  run_success $OCKAM policy evaluate my_policy --subject $identifier --output json
+  assert_output --partial '"is_authorized": false'
+  assert_output --partial '"missing_identifiers": [
+    "subject.component"
+  ]'
+
+  run_success $OCKAM policy evaluate --expression '(= subject.identifier "'$identifier'")' --subject $identifier --output json
+  assert_output --partial '"is_authorized": true'
+}
@@ -104,6 +104,14 @@ impl FlowControls {
         producers.get(address).cloned()
     }
 
+    /// Return true if some Producers are registered with the given [`FlowControlId`]
+    pub fn has_producers(&self, flow_control_id: &FlowControlId) -> bool {
+        let producers = self.producers.read().unwrap();
+        producers
+            .values()
+            .any(|info| info.flow_control_id() == flow_control_id)
+    }
+
     /// Get [`ProducerInfo`] for which given [`Address`] is a Producer or is an additional [`Address`]
     /// for that Producer (e.g. Encryptor address for its Decryptor, or TCP Sender for its TCP Receiver)
     pub fn find_flow_control_with_producer_address(
@@ -8,12 +8,14 @@ use ockam_vault::VaultForVerifyingSignatures;
 
 use crate::identities::AttributesEntry;
 use crate::models::{
-    CredentialAndPurposeKey, CredentialData, Identifier, PurposePublicKey, VersionedData,
+    Credential, CredentialAndPurposeKey, CredentialData, CredentialHash, Identifier,
+    PurposeKeyAttestationData, PurposePublicKey, RevocationListAndPurposeKey, RevocationListData,
+    VersionedData,
 };
 use crate::utils::now;
 use crate::{
     CredentialAndPurposeKeyData, IdentityAttributesRepository, IdentityError,
-    PurposeKeyVerification, TimestampInSeconds,
+    PurposeKeyVerification, RevocationListRepository, TimestampInSeconds,
 };
 
 /// We allow Credentials to be created in the future related to this machine's time due to
@@ -25,6 +27,7 @@ pub struct CredentialsVerification {
     purpose_keys_verification: Arc<PurposeKeyVerification>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
     identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocation_list_repository: Arc<dyn RevocationListRepository>,
 }
 
 impl CredentialsVerification {
@@ -33,11 +36,13 @@ impl CredentialsVerification {
         purpose_keys_verification: Arc<PurposeKeyVerification>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
         identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocation_list_repository: Arc<dyn RevocationListRepository>,
     ) -> Self {
         Self {
             purpose_keys_verification,
             verifying_vault,
             identities_attributes_repository,
+            revocation_list_repository,
         }
     }
 }
@@ -202,6 +207,22 @@ impl CredentialsVerification {
         let credential_data = credential.credential_data;
         let purpose_key_data = credential.purpose_key_data;
 
+        let credential_hash = self
+            .compute_credential_hash(&credential_and_purpose_key_attestation.credential)
+            .await?;
+        if self
+            .revocation_list_repository
+            .is_subject_revoked(&purpose_key_data.subject, subject)
+            .await?
+            || self
+                .revocation_list_repository
+                .is_credential_revoked(&purpose_key_data.subject, &credential_hash)
+                .await?
+        {
+            warn!(%subject, %credential_hash, "presented credential has been revoked");
+            return Err(IdentityError::CredentialRevoked)?;
+        }
+
         let attributes_display = credential_data.get_attributes_display();
         let attributes: BTreeMap<_, _> = credential_data
             .subject_attributes
@@ -239,4 +260,125 @@ impl CredentialsVerification {
 
         Ok(())
     }
+
+    /// Compute the [`CredentialHash`] used to reference a [`Credential`] in a revocation list
+    pub async fn compute_credential_hash(&self, credential: &Credential) -> Result<CredentialHash> {
+        Ok(CredentialHash(
+            self.verifying_vault.sha256(&credential.data).await?.0,
+        ))
+    }
+
+    /// Verify a [`super::super::models::RevocationList`]
+    pub async fn verify_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<(RevocationListData, PurposeKeyAttestationData)> {
+        debug!("verify revocation list purpose key attestation");
+        let purpose_key_data = self
+            .purpose_keys_verification
+            .verify_purpose_key_attestation(
+                None,
+                &revocation_list_and_purpose_key.purpose_key_attestation,
+            )
+            .await?;
+
+        debug!("verify revocation list issuer");
+        if !authorities.contains(&purpose_key_data.subject) {
+            warn!(
+                "unknown authority on a revocation list: {}. Accepted authorities: {:?}",
+                purpose_key_data.subject, authorities
+            );
+            return Err(IdentityError::UnknownAuthority)?;
+        }
+
+        let public_key = match purpose_key_data.public_key.clone() {
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(IdentityError::InvalidKeyType)?;
+            }
+            PurposePublicKey::CredentialSigning(public_key) => public_key,
+        };
+
+        debug!("verify revocation list signature");
+        let revocation_list = &revocation_list_and_purpose_key.revocation_list;
+        let versioned_data_hash = self.verifying_vault.sha256(&revocation_list.data).await?;
+        let signature = revocation_list.signature.clone().into();
+        if !self
+            .verifying_vault
+            .verify_signature(&public_key.into(), &versioned_data_hash.0, &signature)
+            .await?
+        {
+            return Err(IdentityError::RevocationListVerificationFailed)?;
+        }
+
+        let revocation_list_data = revocation_list.get_revocation_list_data()?;
+
+        let now = now()?;
+        if revocation_list_data.created_at > now
+            && revocation_list_data.created_at - now > MAX_ALLOWED_TIME_DRIFT
+        {
+            // A revocation list can't be created in the future
+            return Err(IdentityError::RevocationListVerificationFailed)?;
+        }
+
+        Ok((revocation_list_data, purpose_key_data))
+    }
+
+    /// Receive a [`super::super::models::RevocationList`] from an authority: verify it and, if it is
+    /// more recent than the list we already know, store it and remove the attributes of the revoked subjects.
+    ///
+    /// Return true if the revocation list has been applied
+    pub async fn receive_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<bool> {
+        let (revocation_list_data, purpose_key_data) = self
+            .verify_revocation_list(authorities, revocation_list_and_purpose_key)
+            .await?;
+        let authority = purpose_key_data.subject;
+
+        if let Some(current) = self
+            .revocation_list_repository
+            .get_revocation_list(&authority)
+            .await?
+        {
+            if current.get_revocation_list_data()?.version >= revocation_list_data.version {
+                debug!(%authority, version = %revocation_list_data.version, "the revocation list is already known");
+                return Ok(false);
+            }
+        }
+
+        info! {
+            %authority,
+            version = %revocation_list_data.version,
+            revoked_subjects = %revocation_list_data.revoked_subjects.len(),
+            revoked_credentials = %revocation_list_data.revoked_credentials.len(),
+            "received a new revocation list"
+        }
+
+        self.revocation_list_repository
+            .put_revocation_list(
+                &authority,
+                revocation_list_and_purpose_key.clone(),
+                &revocation_list_data,
+            )
+            .await?;
+
+        // The attributes stored for a revoked credential must not be used anymore.
+        // The subject will need to present a valid credential again.
+        let subjects = revocation_list_data.revoked_subjects.iter().chain(
+            revocation_list_data
+                .revoked_credentials
+                .iter()
+                .map(|c| &c.subject),
+        );
+        for subject in subjects {
+            self.identities_attributes_repository
+                .delete_attributes(subject, &authority)
+                .await?;
+        }
+
+        Ok(true)
+    }
 }
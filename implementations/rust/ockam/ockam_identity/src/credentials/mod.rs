@@ -3,8 +3,10 @@ mod credentials;
 mod credentials_creation;
 mod credentials_verification;
 mod retriever;
+mod revocation_list_refresher;
 
 pub use credentials::*;
 pub use credentials_creation::*;
 pub use credentials_verification::*;
 pub use retriever::*;
+pub use revocation_list_refresher::*;
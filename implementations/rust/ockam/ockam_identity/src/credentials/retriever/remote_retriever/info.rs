@@ -1,6 +1,7 @@
 use core::fmt::Display;
 use ockam_core::api::Method;
 use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
 use ockam_core::Route;
 
 use crate::Identifier;
@@ -44,6 +45,9 @@ pub struct RemoteCredentialRetrieverInfo {
     pub issuer: Identifier,
     /// Route used to establish a secure channel to the remote node
     pub route: Route,
+    /// Routes to other replicas of the remote node, tried in order when the remote node
+    /// can't be reached
+    pub fallback_routes: Vec<Route>,
     /// Address of the credentials service on the remote node, e.g. "credential_issuer" or "accounts"
     pub service_address: String,
     /// Request path, e.g. "/" or "/v0/project/$project_id"
@@ -97,9 +101,24 @@ impl RemoteCredentialRetrieverInfo {
         Self {
             issuer,
             route,
+            fallback_routes: Vec::new(),
             service_address,
             api_service_address,
             request_method,
         }
     }
+
+    /// Add routes to other replicas of the remote node
+    pub fn with_fallback_routes(mut self, fallback_routes: Vec<Route>) -> Self {
+        self.fallback_routes = fallback_routes;
+        self
+    }
+
+    /// Return the route to the remote node, followed by the routes to its other replicas
+    pub fn routes(&self) -> Vec<Route> {
+        let mut routes = Vec::with_capacity(1 + self.fallback_routes.len());
+        routes.push(self.route.clone());
+        routes.extend(self.fallback_routes.iter().cloned());
+        routes
+    }
 }
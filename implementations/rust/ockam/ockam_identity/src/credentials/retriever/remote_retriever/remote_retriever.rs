@@ -6,7 +6,8 @@ use ockam_core::compat::string::String;
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::time::Duration;
 use ockam_core::compat::vec::Vec;
-use ockam_core::{route, Address, Result};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{route, Address, Error, Result};
 use ockam_node::compat::asynchronous::Mutex;
 use ockam_node::Context;
 use ockam_transport_core::Transport;
@@ -81,6 +82,9 @@ pub struct RemoteCredentialRetriever {
     pub(super) last_presented_credential: Arc<RwLock<Option<LastPresentedCredential>>>,
     /// Subscribers addresses that we will notify when credential is refreshed
     pub(super) subscribers: Arc<RwLock<Vec<Address>>>,
+    /// Index of the issuer route which was last used to retrieve a credential.
+    /// The other routes lead to replicas of the issuer, used when it can't be reached
+    current_route: Arc<RwLock<usize>>,
 }
 
 impl RemoteCredentialRetriever {
@@ -110,6 +114,7 @@ impl RemoteCredentialRetriever {
             is_initialized: Arc::new(Mutex::new(false)),
             last_presented_credential: Arc::new(RwLock::new(None)),
             subscribers: Default::default(),
+            current_route: Default::default(),
         }
     }
 
@@ -272,28 +277,7 @@ impl RemoteCredentialRetriever {
             .identities
             .cached_credentials_repository();
 
-        let client = SecureClient::new(
-            self.secure_channels.clone(),
-            None,
-            self.transport.clone(),
-            self.issuer_info.route.clone(),
-            &self.issuer_info.issuer,
-            &self.subject,
-            self.timing_options.secure_channel_creation_timeout,
-            self.timing_options.request_timeout,
-        );
-
-        let credential: CredentialAndPurposeKey = client
-            .ask(
-                &self.ctx,
-                &self.issuer_info.service_address,
-                Request::build(
-                    self.issuer_info.request_method,
-                    self.issuer_info.api_service_address.clone(),
-                ),
-            )
-            .await?
-            .success()?;
+        let credential = self.request_credential().await?;
 
         let credential_data = credential.get_credential_data()?;
         let attributes = credential_data.get_attributes_display();
@@ -356,6 +340,65 @@ impl RemoteCredentialRetriever {
         Ok(())
     }
 
+    /// Request a new credential to the issuer.
+    /// If the issuer can't be reached, its replicas are tried in order, starting from
+    /// the route which was last used successfully
+    async fn request_credential(&self) -> Result<CredentialAndPurposeKey> {
+        let routes = self.issuer_info.routes();
+        let start = *self.current_route.read().unwrap() % routes.len();
+
+        let mut last_error = None;
+        for index in (start..routes.len()).chain(0..start) {
+            let route = &routes[index];
+            let client = SecureClient::new(
+                self.secure_channels.clone(),
+                None,
+                self.transport.clone(),
+                route.clone(),
+                &self.issuer_info.issuer,
+                &self.subject,
+                self.timing_options.secure_channel_creation_timeout,
+                self.timing_options.request_timeout,
+            );
+
+            let result = client
+                .ask(
+                    &self.ctx,
+                    &self.issuer_info.service_address,
+                    Request::build(
+                        self.issuer_info.request_method,
+                        self.issuer_info.api_service_address.clone(),
+                    ),
+                )
+                .await
+                .and_then(|reply| reply.success());
+
+            match result {
+                Ok(credential) => {
+                    if index != start {
+                        info!(issuer=%self.issuer_info.issuer, %route,
+                            "switched to another route to retrieve credentials");
+                    }
+                    *self.current_route.write().unwrap() = index;
+                    return Ok(credential);
+                }
+                Err(err) => {
+                    warn!(issuer=%self.issuer_info.issuer, %route, %err,
+                        "could not retrieve a credential");
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            Error::new(
+                Origin::Identity,
+                Kind::NotFound,
+                "no route to the credential issuer",
+            )
+        }))
+    }
+
     fn request_new_credential_in_background(&self, wait: Duration, is_retry: bool) {
         let s = self.clone();
         ockam_node::spawn(async move {
@@ -0,0 +1,251 @@
+use tracing::{debug, info, warn};
+
+use ockam_core::api::Request;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::time::Duration;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, route, Address, Error, Result, Route, Routed, Worker};
+use ockam_node::{Context, DelayedEvent};
+use ockam_transport_core::Transport;
+
+use crate::models::RevocationListAndPurposeKey;
+use crate::{get_default_timeout, Identifier, SecureChannel, SecureChannels, SecureClient};
+
+/// Default interval between two subscriptions to the Authority revocation list
+pub const DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Address of the revocation list service on the Authority node
+pub const REVOCATION_LIST_SERVICE_ADDRESS: &str = "revocation_list";
+
+/// Path used to subscribe to the revocation lists issued by an Authority
+pub const REVOCATION_LIST_SUBSCRIBE_PATH: &str = "/subscribe";
+
+/// This worker subscribes to the revocation lists published by an Authority.
+///
+/// The Authority sends its current revocation list when a subscription is received, and then
+/// each new revocation list as soon as a credential is revoked. Every list is verified and
+/// stored so that revoked credentials are rejected by the node.
+///
+/// The subscription is renewed periodically, and a new secure channel is created when the
+/// Authority did not answer the previous subscription, for example if it was restarted.
+/// If the Authority can't be reached, its replicas are tried in order, starting from the
+/// route which was last used successfully.
+pub struct RevocationListRefresher {
+    transport: Arc<dyn Transport>,
+    secure_channels: Arc<SecureChannels>,
+    authority: Identifier,
+    authority_routes: Vec<Route>,
+    current_route: usize,
+    service_address: String,
+    subject: Identifier,
+    refresh_interval: Duration,
+    refresh: Option<DelayedEvent<Vec<u8>>>,
+    subscription: Option<Subscription>,
+}
+
+struct Subscription {
+    secure_channel: SecureChannel,
+    transport_address: Option<Address>,
+    // true when the Authority sent a revocation list since the last subscription request
+    answered: bool,
+}
+
+impl RevocationListRefresher {
+    /// Create a new revocation list refresher, using the routes to the Authority and its replicas
+    pub fn new(
+        transport: Arc<dyn Transport>,
+        secure_channels: Arc<SecureChannels>,
+        authority: Identifier,
+        authority_routes: Vec<Route>,
+        subject: Identifier,
+    ) -> Self {
+        Self {
+            transport,
+            secure_channels,
+            authority,
+            authority_routes,
+            current_route: 0,
+            service_address: REVOCATION_LIST_SERVICE_ADDRESS.to_string(),
+            subject,
+            refresh_interval: DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL,
+            refresh: None,
+            subscription: None,
+        }
+    }
+
+    /// Set the interval between two subscriptions to the revocation list
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Start the refresher as a worker of the node and return its address.
+    /// The worker is stopped, and its secure channel closed, when the node stops
+    pub fn start(self, ctx: &Context) -> Result<Address> {
+        let address = Address::random_tagged("RevocationListRefresher");
+        ctx.start_worker(address.clone(), self)?;
+        Ok(address)
+    }
+
+    fn client(&self, authority_route: &Route) -> SecureClient {
+        SecureClient::new(
+            self.secure_channels.clone(),
+            None,
+            self.transport.clone(),
+            authority_route.clone(),
+            &self.authority,
+            &self.subject,
+            get_default_timeout(),
+            get_default_timeout(),
+        )
+    }
+
+    async fn receive_revocation_list(
+        &self,
+        revocation_list: &RevocationListAndPurposeKey,
+    ) -> Result<bool> {
+        self.secure_channels
+            .identities()
+            .credentials()
+            .credentials_verification()
+            .receive_revocation_list(&[self.authority.clone()], revocation_list)
+            .await
+    }
+
+    /// Subscribe to the revocation lists of the Authority, which are then sent to this worker.
+    /// The current secure channel is kept if the Authority answered the previous subscription
+    async fn subscribe(&mut self, ctx: &Context) -> Result<()> {
+        if !self.subscription.as_ref().is_some_and(|s| s.answered) {
+            self.unsubscribe(ctx);
+            let (secure_channel, transport_address) = self.create_secure_channel(ctx).await?;
+            ctx.flow_controls()
+                .add_consumer(ctx.primary_address(), secure_channel.flow_control_id());
+            self.subscription = Some(Subscription {
+                secure_channel,
+                transport_address,
+                answered: false,
+            });
+        }
+
+        if let Some(subscription) = self.subscription.as_mut() {
+            debug!(authority=%self.authority, "subscribing to the revocation list");
+            subscription.answered = false;
+            let route = route![
+                subscription.secure_channel.clone(),
+                self.service_address.clone()
+            ];
+            let request = Request::post(REVOCATION_LIST_SUBSCRIBE_PATH).to_vec()?;
+            ctx.send(route, request).await?;
+        }
+        Ok(())
+    }
+
+    /// Create a secure channel to the Authority, or to one of its replicas if it can't be reached
+    async fn create_secure_channel(
+        &mut self,
+        ctx: &Context,
+    ) -> Result<(SecureChannel, Option<Address>)> {
+        let routes_count = self.authority_routes.len();
+        let mut last_error = None;
+        for attempt in 0..routes_count {
+            let index = (self.current_route + attempt) % routes_count;
+            let route = &self.authority_routes[index];
+            debug!(authority=%self.authority, %route, "creating a secure channel to the authority");
+            match self.client(route).create_secure_channel(ctx).await {
+                Ok(secure_channel) => {
+                    if index != self.current_route {
+                        info!(authority=%self.authority, %route,
+                            "switched to another route to subscribe to the revocation list");
+                    }
+                    self.current_route = index;
+                    return Ok(secure_channel);
+                }
+                Err(err) => {
+                    warn!(authority=%self.authority, %route, %err,
+                        "could not create a secure channel to the authority");
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            Error::new(
+                Origin::Identity,
+                Kind::NotFound,
+                "no route to the authority",
+            )
+        }))
+    }
+
+    /// Close the secure channel used by the current subscription
+    fn unsubscribe(&mut self, ctx: &Context) {
+        if let Some(subscription) = self.subscription.take() {
+            let _ = self
+                .secure_channels
+                .stop_secure_channel(ctx, subscription.secure_channel.encryptor_address());
+            if let Some(transport_address) = subscription.transport_address {
+                let _ = self.transport.disconnect(&transport_address);
+            }
+        }
+    }
+
+    async fn renew_subscription(&mut self, ctx: &Context) -> Result<()> {
+        if let Err(err) = self.subscribe(ctx).await {
+            warn!(authority=%self.authority, %err, "error subscribing to the revocation list");
+        }
+        if let Some(refresh) = self.refresh.as_mut() {
+            refresh.schedule(self.refresh_interval)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for RevocationListRefresher {
+    type Message = Vec<u8>;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.refresh = Some(DelayedEvent::create(
+            ctx,
+            ctx.primary_address().clone(),
+            Vec::new(),
+        )?);
+        self.renew_subscription(ctx).await
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.refresh = None;
+        self.unsubscribe(ctx);
+        Ok(())
+    }
+
+    /// An empty message is sent by the refresh timer. Any other message is a revocation
+    /// list sent by the Authority
+    async fn handle_message(&mut self, ctx: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let body = m.into_body()?;
+        if body.is_empty() {
+            return self.renew_subscription(ctx).await;
+        }
+
+        let revocation_list: RevocationListAndPurposeKey = match minicbor::decode(&body) {
+            Ok(revocation_list) => revocation_list,
+            Err(err) => {
+                warn!(authority=%self.authority, %err, "cannot decode a revocation list");
+                return Ok(());
+            }
+        };
+        if let Some(subscription) = self.subscription.as_mut() {
+            subscription.answered = true;
+        }
+        match self.receive_revocation_list(&revocation_list).await {
+            Ok(true) => info!(authority=%self.authority, "updated the revocation list"),
+            Ok(false) => debug!(authority=%self.authority, "the revocation list is up to date"),
+            Err(err) => warn!(authority=%self.authority, %err, "invalid revocation list"),
+        }
+        Ok(())
+    }
+}
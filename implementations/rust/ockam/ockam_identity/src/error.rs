@@ -70,6 +70,16 @@ pub enum IdentityError {
     UnknownRole,
     /// Handshake ended up in an internal invalid state
     HandshakeInternalError,
+    /// Unknown version of the RevocationList
+    UnknownRevocationListVersion,
+    /// Invalid data_type value for RevocationList
+    InvalidRevocationListDataType,
+    /// RevocationList Verification Failed
+    RevocationListVerificationFailed,
+    /// Invalid CredentialHash format
+    InvalidCredentialHash(String),
+    /// Credential was revoked by its Authority
+    CredentialRevoked,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
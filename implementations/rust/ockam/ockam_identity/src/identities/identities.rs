@@ -12,6 +12,8 @@ use crate::identities::storage::CredentialRepository;
 use crate::identities::storage::CredentialSqlxDatabase;
 #[cfg(feature = "storage")]
 use crate::identities::storage::IdentityAttributesSqlxDatabase;
+#[cfg(feature = "storage")]
+use crate::identities::storage::RevocationListSqlxDatabase;
 use crate::identities::{ChangeHistoryRepository, IdentitiesKeys};
 use crate::models::ChangeHistory;
 use crate::purpose_keys::storage::PurposeKeysRepository;
@@ -21,7 +23,7 @@ use crate::purpose_keys::storage::PurposeKeysSqlxDatabase;
 use crate::IdentitiesBuilder;
 use crate::{
     Credentials, Identifier, IdentitiesCreation, IdentitiesVerification, Identity,
-    IdentityAttributesRepository, PurposeKeys, Vault,
+    IdentityAttributesRepository, PurposeKeys, RevocationListRepository, Vault,
 };
 
 /// This struct supports all the services related to identities
@@ -32,6 +34,7 @@ pub struct Identities {
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
     cached_credentials_repository: Arc<dyn CredentialRepository>,
+    revocation_list_repository: Arc<dyn RevocationListRepository>,
 }
 
 impl Identities {
@@ -55,6 +58,11 @@ impl Identities {
         self.cached_credentials_repository.clone()
     }
 
+    /// Return the revocation lists repository
+    pub fn revocation_list_repository(&self) -> Arc<dyn RevocationListRepository> {
+        self.revocation_list_repository.clone()
+    }
+
     /// Get an [`Identity`] from the repository
     pub async fn get_identity(&self, identifier: &Identifier) -> Result<Identity> {
         self.identities_verification()
@@ -78,6 +86,7 @@ impl Identities {
     pub fn identities_attributes(&self) -> Arc<IdentitiesAttributes> {
         Arc::new(IdentitiesAttributes::new(
             self.identity_attributes_repository.clone(),
+            self.revocation_list_repository.clone(),
         ))
     }
 
@@ -124,6 +133,7 @@ impl Identities {
             self.purpose_keys(),
             self.identities_creation().clone(),
             self.identity_attributes_repository.clone(),
+            self.revocation_list_repository.clone(),
         ))
     }
 }
@@ -136,6 +146,7 @@ impl Identities {
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
         purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
         cached_credentials_repository: Arc<dyn CredentialRepository>,
+        revocation_list_repository: Arc<dyn RevocationListRepository>,
     ) -> Identities {
         Identities {
             vault,
@@ -143,6 +154,7 @@ impl Identities {
             identity_attributes_repository,
             purpose_keys_repository,
             cached_credentials_repository,
+            revocation_list_repository,
         }
     }
 
@@ -172,6 +184,10 @@ impl Identities {
             )),
             purpose_keys_repository: Arc::new(PurposeKeysSqlxDatabase::new(database.clone())),
             cached_credentials_repository: Arc::new(CredentialSqlxDatabase::new(
+                database.clone(),
+                node_name,
+            )),
+            revocation_list_repository: Arc::new(RevocationListSqlxDatabase::new(
                 database, node_name,
             )),
         }
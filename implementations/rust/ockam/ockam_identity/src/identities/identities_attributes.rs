@@ -1,7 +1,8 @@
 use crate::utils::now;
-use crate::{AttributesEntry, Identifier, IdentityAttributesRepository};
+use crate::{AttributesEntry, Identifier, IdentityAttributesRepository, RevocationListRepository};
 use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
+use tracing::debug;
 use tracing_attributes::instrument;
 
 /// This struct provides access to the identities attributes stored on a node.
@@ -11,21 +12,30 @@ use tracing_attributes::instrument;
 /// - Setting the time at which a given attribute is persisted
 /// - Deleting expired attributes from storage. This deletion is performed every time the
 ///   repository is accessed to retrieve attributes
+/// - Ignoring the attributes of subjects revoked by the attesting authority
 ///
 #[derive(Clone)]
 pub struct IdentitiesAttributes {
     repository: Arc<dyn IdentityAttributesRepository>,
+    revocation_list_repository: Arc<dyn RevocationListRepository>,
 }
 
 impl IdentitiesAttributes {
     /// Return a new IdentitiesAttributes struct
-    pub fn new(repository: Arc<dyn IdentityAttributesRepository>) -> IdentitiesAttributes {
-        IdentitiesAttributes { repository }
+    pub fn new(
+        repository: Arc<dyn IdentityAttributesRepository>,
+        revocation_list_repository: Arc<dyn RevocationListRepository>,
+    ) -> IdentitiesAttributes {
+        IdentitiesAttributes {
+            repository,
+            revocation_list_repository,
+        }
     }
 
     /// Return the attributes for a given pair subject/attesting authority
     /// If there are expired attributes for any subject, they are deleted before retrieving the attributes for the
     /// current subject.
+    /// No attributes are returned if the subject has been revoked by the attesting authority.
     #[instrument(skip_all, fields(subject = %subject, attested_by = %attested_by))]
     pub async fn get_attributes(
         &self,
@@ -33,6 +43,14 @@ impl IdentitiesAttributes {
         attested_by: &Identifier,
     ) -> Result<Option<AttributesEntry>> {
         self.repository.delete_expired_attributes(now()?).await?;
+        if self
+            .revocation_list_repository
+            .is_subject_revoked(attested_by, subject)
+            .await?
+        {
+            debug!(%subject, %attested_by, "the subject has been revoked");
+            return Ok(None);
+        }
         self.repository.get_attributes(subject, attested_by).await
     }
 
@@ -51,8 +69,11 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
-    use crate::utils::now;
-    use crate::{identities, IdentityAttributesSqlxDatabase, TimestampInSeconds};
+    use crate::models::{CredentialSchemaIdentifier, RevokedCredential};
+    use crate::utils::{now, AttributesBuilder};
+    use crate::{
+        identities, IdentityAttributesSqlxDatabase, RevocationListSqlxDatabase, TimestampInSeconds,
+    };
 
     #[tokio::test]
     async fn test_identities_attributes_expiration() -> Result<()> {
@@ -93,6 +114,100 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_identities_attributes_revocation() -> Result<()> {
+        let identities = identities().await?;
+        let identities_attributes = identities.identities_attributes();
+        let credentials_verification = identities.credentials().credentials_verification();
+        let credentials_creation = identities.credentials().credentials_creation();
+
+        let authority = identities.identities_creation().create_identity().await?;
+        let subject1 = identities.identities_creation().create_identity().await?;
+        let subject2 = identities.identities_creation().create_identity().await?;
+
+        let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+            .with_attribute("name", "alice")
+            .build();
+        let credential1 = credentials_creation
+            .issue_credential(
+                &authority,
+                &subject1,
+                attributes.clone(),
+                Duration::from_secs(60),
+            )
+            .await?;
+        let credential2 = credentials_creation
+            .issue_credential(&authority, &subject2, attributes, Duration::from_secs(60))
+            .await?;
+
+        for (subject, credential) in [(&subject1, &credential1), (&subject2, &credential2)] {
+            credentials_verification
+                .receive_presented_credential(subject, &[authority.clone()], credential)
+                .await?;
+            assert!(identities_attributes
+                .get_attributes(subject, &authority)
+                .await?
+                .is_some());
+        }
+
+        // revoke subject1 and the credential of subject2
+        let credential2_hash = credentials_verification
+            .compute_credential_hash(&credential2.credential)
+            .await?;
+        let revocation_list = credentials_creation
+            .issue_revocation_list(
+                &authority,
+                1,
+                vec![subject1.clone()],
+                vec![RevokedCredential {
+                    subject: subject2.clone(),
+                    credential_hash: credential2_hash,
+                }],
+            )
+            .await?;
+        assert!(
+            credentials_verification
+                .receive_revocation_list(&[authority.clone()], &revocation_list)
+                .await?
+        );
+        assert!(
+            !credentials_verification
+                .receive_revocation_list(&[authority.clone()], &revocation_list)
+                .await?,
+            "the same version can't be applied twice"
+        );
+
+        for (subject, credential) in [(&subject1, &credential1), (&subject2, &credential2)] {
+            assert_eq!(
+                identities_attributes
+                    .get_attributes(subject, &authority)
+                    .await?,
+                None
+            );
+            let result = credentials_verification
+                .receive_presented_credential(subject, &[authority.clone()], credential)
+                .await;
+            assert!(result.is_err(), "a revoked credential must be rejected");
+        }
+
+        // a new credential can still be accepted for subject2
+        let attributes = AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+            .with_attribute("name", "bob")
+            .build();
+        let credential3 = credentials_creation
+            .issue_credential(&authority, &subject2, attributes, Duration::from_secs(60))
+            .await?;
+        credentials_verification
+            .receive_presented_credential(&subject2, &[authority.clone()], &credential3)
+            .await?;
+        assert!(identities_attributes
+            .get_attributes(&subject2, &authority)
+            .await?
+            .is_some());
+
+        Ok(())
+    }
+
     /// HELPERS
     async fn create_attributes_entry(
         identifier: &Identifier,
@@ -116,8 +231,9 @@ mod tests {
     }
 
     async fn create_identities_attributes() -> Result<IdentitiesAttributes> {
-        Ok(IdentitiesAttributes::new(Arc::new(
-            IdentityAttributesSqlxDatabase::create().await?,
-        )))
+        Ok(IdentitiesAttributes::new(
+            Arc::new(IdentityAttributesSqlxDatabase::create().await?),
+            Arc::new(RevocationListSqlxDatabase::create().await?),
+        ))
     }
 }
@@ -8,7 +8,7 @@ use ockam_vault::storage::SecretsRepository;
 use crate::identities::storage::CredentialRepository;
 use crate::identities::{ChangeHistoryRepository, Identities};
 use crate::purpose_keys::storage::PurposeKeysRepository;
-use crate::{IdentityAttributesRepository, Vault};
+use crate::{IdentityAttributesRepository, RevocationListRepository, Vault};
 
 /// Builder for Identities services
 #[derive(Clone)]
@@ -18,6 +18,7 @@ pub struct IdentitiesBuilder {
     pub(crate) identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     pub(crate) purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
     pub(crate) cached_credentials_repository: Arc<dyn CredentialRepository>,
+    pub(crate) revocation_list_repository: Arc<dyn RevocationListRepository>,
 }
 
 /// Return a default identities
@@ -81,6 +82,15 @@ impl IdentitiesBuilder {
         self
     }
 
+    /// Set a specific repository for revocation lists
+    pub fn with_revocation_list_repository(
+        mut self,
+        repository: Arc<dyn RevocationListRepository>,
+    ) -> Self {
+        self.revocation_list_repository = repository;
+        self
+    }
+
     /// Build identities
     pub fn build(self) -> Arc<Identities> {
         Arc::new(Identities::new(
@@ -89,6 +99,7 @@ impl IdentitiesBuilder {
             self.identity_attributes_repository,
             self.purpose_keys_repository,
             self.cached_credentials_repository,
+            self.revocation_list_repository,
         ))
     }
 }
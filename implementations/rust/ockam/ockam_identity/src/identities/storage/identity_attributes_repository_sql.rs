@@ -95,6 +95,20 @@ impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
             .bind(&self.node_name);
         query.execute(&*self.database.pool).await.void()
     }
+
+    async fn delete_attributes(
+        &self,
+        subject: &Identifier,
+        attested_by: &Identifier,
+    ) -> Result<()> {
+        let query = query(
+            "DELETE FROM identity_attributes WHERE identifier = $1 AND attested_by = $2 AND node_name = $3",
+        )
+        .bind(subject)
+        .bind(attested_by)
+        .bind(&self.node_name);
+        query.execute(&*self.database.pool).await.void()
+    }
 }
 
 // Database serialization / deserialization
@@ -0,0 +1,83 @@
+use crate::models::{CredentialHash, RevocationListAndPurposeKey, RevocationListData};
+use crate::Identifier;
+use async_trait::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::Result;
+#[cfg(feature = "std")]
+use ockam_node::database::AutoRetry;
+#[cfg(feature = "std")]
+use ockam_node::retry;
+
+/// This trait supports the persistence of the revocation lists received from authorities
+#[async_trait]
+pub trait RevocationListRepository: Send + Sync + 'static {
+    /// Get the latest revocation list received from a given authority
+    async fn get_revocation_list(
+        &self,
+        authority: &Identifier,
+    ) -> Result<Option<RevocationListAndPurposeKey>>;
+
+    /// Put a verified revocation list for a given authority (overwriting the previous one)
+    async fn put_revocation_list(
+        &self,
+        authority: &Identifier,
+        revocation_list: RevocationListAndPurposeKey,
+        revocation_list_data: &RevocationListData,
+    ) -> Result<()>;
+
+    /// Return true if all the credentials of a subject have been revoked by the authority
+    async fn is_subject_revoked(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool>;
+
+    /// Return true if a specific credential has been revoked by the authority
+    async fn is_credential_revoked(
+        &self,
+        authority: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<bool>;
+}
+
+#[cfg(feature = "std")]
+#[async_trait]
+impl<T: RevocationListRepository> RevocationListRepository for AutoRetry<T> {
+    async fn get_revocation_list(
+        &self,
+        authority: &Identifier,
+    ) -> Result<Option<RevocationListAndPurposeKey>> {
+        retry!(self.wrapped.get_revocation_list(authority))
+    }
+
+    async fn put_revocation_list(
+        &self,
+        authority: &Identifier,
+        revocation_list: RevocationListAndPurposeKey,
+        revocation_list_data: &RevocationListData,
+    ) -> Result<()> {
+        retry!(self.wrapped.put_revocation_list(
+            authority,
+            revocation_list.clone(),
+            revocation_list_data
+        ))
+    }
+
+    async fn is_subject_revoked(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool> {
+        retry!(self.wrapped.is_subject_revoked(authority, subject))
+    }
+
+    async fn is_credential_revoked(
+        &self,
+        authority: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<bool> {
+        retry!(self
+            .wrapped
+            .is_credential_revoked(authority, credential_hash))
+    }
+}
@@ -0,0 +1,254 @@
+use sqlx::*;
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::models::{CredentialHash, Identifier, RevocationListAndPurposeKey, RevocationListData};
+use crate::RevocationListRepository;
+use ockam_core::async_trait;
+use ockam_core::Result;
+use ockam_node::database::AutoRetry;
+use ockam_node::database::{Boolean, FromSqlxError, SqlxDatabase, ToVoid};
+
+/// Implementation of [`RevocationListRepository`] trait based on an underlying database
+/// using sqlx as its API, and Sqlite as its driver
+#[derive(Clone)]
+pub struct RevocationListSqlxDatabase {
+    database: SqlxDatabase,
+    node_name: String,
+}
+
+impl RevocationListSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: SqlxDatabase, node_name: &str) -> Self {
+        debug!("create a repository for revocation lists");
+        Self {
+            database,
+            node_name: node_name.to_string(),
+        }
+    }
+
+    /// Create a repository
+    pub fn make_repository(
+        database: SqlxDatabase,
+        node_name: &str,
+    ) -> Arc<dyn RevocationListRepository> {
+        if database.needs_retry() {
+            Arc::new(AutoRetry::new(Self::new(database, node_name)))
+        } else {
+            Arc::new(Self::new(database, node_name))
+        }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Self> {
+        Ok(Self::new(
+            SqlxDatabase::in_memory("revocation list").await?,
+            "default",
+        ))
+    }
+}
+
+#[async_trait]
+impl RevocationListRepository for RevocationListSqlxDatabase {
+    async fn get_revocation_list(
+        &self,
+        authority: &Identifier,
+    ) -> Result<Option<RevocationListAndPurposeKey>> {
+        let query = query_as(
+            "SELECT revocation_list FROM revocation_list WHERE authority_identifier = $1 AND node_name = $2",
+        )
+        .bind(authority)
+        .bind(&self.node_name);
+        let row: Option<RevocationListRow> = query
+            .fetch_optional(&*self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.revocation_list()).transpose()
+    }
+
+    async fn put_revocation_list(
+        &self,
+        authority: &Identifier,
+        revocation_list: RevocationListAndPurposeKey,
+        revocation_list_data: &RevocationListData,
+    ) -> Result<()> {
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        let query1 = query(
+            r#"
+            INSERT INTO revocation_list (authority_identifier, version, revocation_list, node_name)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (authority_identifier, node_name)
+            DO UPDATE SET version = $2, revocation_list = $3"#,
+        )
+        .bind(authority)
+        .bind(revocation_list_data.version as i64)
+        .bind(revocation_list.encode_as_cbor_bytes()?)
+        .bind(&self.node_name);
+        query1.execute(&mut *transaction).await.void()?;
+
+        let query2 = query(
+            "DELETE FROM revoked_credential WHERE authority_identifier = $1 AND node_name = $2",
+        )
+        .bind(authority)
+        .bind(&self.node_name);
+        query2.execute(&mut *transaction).await.void()?;
+
+        for subject in revocation_list_data.revoked_subjects.iter() {
+            let query3 = query(
+                r#"
+                INSERT INTO revoked_credential (authority_identifier, subject_identifier, credential_hash, node_name)
+                VALUES ($1, $2, $3, $4)"#,
+            )
+            .bind(authority)
+            .bind(subject)
+            .bind(None::<String>)
+            .bind(&self.node_name);
+            query3.execute(&mut *transaction).await.void()?;
+        }
+
+        for revoked_credential in revocation_list_data.revoked_credentials.iter() {
+            let query4 = query(
+                r#"
+                INSERT INTO revoked_credential (authority_identifier, subject_identifier, credential_hash, node_name)
+                VALUES ($1, $2, $3, $4)"#,
+            )
+            .bind(authority)
+            .bind(&revoked_credential.subject)
+            .bind(Some(revoked_credential.credential_hash.to_string()))
+            .bind(&self.node_name);
+            query4.execute(&mut *transaction).await.void()?;
+        }
+
+        transaction.commit().await.void()
+    }
+
+    async fn is_subject_revoked(
+        &self,
+        authority: &Identifier,
+        subject: &Identifier,
+    ) -> Result<bool> {
+        let query = query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM revoked_credential WHERE authority_identifier = $1 AND subject_identifier = $2 AND credential_hash IS NULL AND node_name = $3)",
+        )
+        .bind(authority)
+        .bind(subject)
+        .bind(&self.node_name);
+        let is_revoked: Boolean = query.fetch_one(&*self.database.pool).await.into_core()?;
+        Ok(is_revoked.to_bool())
+    }
+
+    async fn is_credential_revoked(
+        &self,
+        authority: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<bool> {
+        let query = query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM revoked_credential WHERE authority_identifier = $1 AND credential_hash = $2 AND node_name = $3)",
+        )
+        .bind(authority)
+        .bind(credential_hash.to_string())
+        .bind(&self.node_name);
+        let is_revoked: Boolean = query.fetch_one(&*self.database.pool).await.into_core()?;
+        Ok(is_revoked.to_bool())
+    }
+}
+
+// Low-level representation of a table row
+#[derive(FromRow)]
+struct RevocationListRow {
+    revocation_list: Vec<u8>,
+}
+
+impl RevocationListRow {
+    fn revocation_list(&self) -> Result<RevocationListAndPurposeKey> {
+        RevocationListAndPurposeKey::decode_from_cbor_bytes(&self.revocation_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ockam_core::compat::sync::Arc;
+    use ockam_node::database::with_dbs;
+
+    use super::*;
+    use crate::identities;
+    use crate::models::RevokedCredential;
+
+    #[tokio::test]
+    async fn test_revocation_list_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository: Arc<dyn RevocationListRepository> =
+                Arc::new(RevocationListSqlxDatabase::new(db, "node"));
+
+            let identities = identities().await?;
+            let authority = identities.identities_creation().create_identity().await?;
+            let subject1 = identities.identities_creation().create_identity().await?;
+            let subject2 = identities.identities_creation().create_identity().await?;
+            let credential_hash = CredentialHash([1; 32]);
+
+            assert_eq!(repository.get_revocation_list(&authority).await?, None);
+            assert!(!repository.is_subject_revoked(&authority, &subject1).await?);
+
+            let revocation_list = identities
+                .credentials()
+                .credentials_creation()
+                .issue_revocation_list(
+                    &authority,
+                    1,
+                    vec![subject1.clone()],
+                    vec![RevokedCredential {
+                        subject: subject2.clone(),
+                        credential_hash: credential_hash.clone(),
+                    }],
+                )
+                .await?;
+            let revocation_list_data = revocation_list.get_revocation_list_data()?;
+
+            repository
+                .put_revocation_list(&authority, revocation_list.clone(), &revocation_list_data)
+                .await?;
+
+            assert_eq!(
+                repository.get_revocation_list(&authority).await?,
+                Some(revocation_list)
+            );
+            assert!(repository.is_subject_revoked(&authority, &subject1).await?);
+            assert!(
+                !repository.is_subject_revoked(&authority, &subject2).await?,
+                "only one credential of subject 2 is revoked"
+            );
+            assert!(
+                repository
+                    .is_credential_revoked(&authority, &credential_hash)
+                    .await?
+            );
+            assert!(
+                !repository.is_subject_revoked(&subject1, &subject1).await?,
+                "revocations are scoped by authority"
+            );
+
+            // a new list replaces the previous entries
+            let revocation_list = identities
+                .credentials()
+                .credentials_creation()
+                .issue_revocation_list(&authority, 2, vec![subject2.clone()], vec![])
+                .await?;
+            let revocation_list_data = revocation_list.get_revocation_list_data()?;
+            repository
+                .put_revocation_list(&authority, revocation_list, &revocation_list_data)
+                .await?;
+
+            assert!(!repository.is_subject_revoked(&authority, &subject1).await?);
+            assert!(repository.is_subject_revoked(&authority, &subject2).await?);
+            assert!(
+                !repository
+                    .is_credential_revoked(&authority, &credential_hash)
+                    .await?
+            );
+
+            Ok(())
+        })
+        .await
+    }
+}
@@ -2,11 +2,13 @@ use core::fmt::{Display, Formatter};
 use core::str::FromStr;
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 
 use ockam_core::compat::{string::String, vec::Vec};
 #[cfg(feature = "std")]
 use ockam_core::env::FromString;
 use ockam_core::{Error, LocalInfoIdentifier, Result};
+use ockam_vault::X25519PublicKey;
 
 use crate::models::{ChangeHash, CHANGE_HASH_LEN, IDENTIFIER_LEN};
 use crate::{Identifier, IdentityError};
@@ -50,6 +52,23 @@ impl<'de> Deserialize<'de> for Identifier {
 
 impl Identifier {
     const PREFIX: &'static str = "I";
+
+    /// Domain separation tag of the identifiers derived from X25519 public keys
+    const X25519_PUBLIC_KEY_TAG: &'static [u8] = b"OCKAM_X25519_PUBLIC_KEY_IDENTIFIER";
+
+    /// Identifier of a peer which is only known by an X25519 public key, without any identity.
+    /// This is used by the secure channels created with pre-shared static keys.
+    ///
+    /// The identifier is the SHA-256 hash of the public key prefixed with a dedicated tag.
+    /// The identifier of an identity is the SHA-256 hash of its first change, which is CBOR data
+    /// that never starts with that tag, so a derived identifier can't be the identifier
+    /// of an identity.
+    pub fn from_x25519_public_key(public_key: &X25519PublicKey) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(Self::X25519_PUBLIC_KEY_TAG);
+        hasher.update(public_key.0);
+        Self(hasher.finalize().into())
+    }
 }
 
 impl Display for Identifier {
@@ -66,7 +85,7 @@ impl From<Identifier> for String {
 
 impl From<&Identifier> for String {
     fn from(id: &Identifier) -> Self {
-        format!("{}{}", Identifier::PREFIX, hex::encode(id.0.as_ref()))
+        format!("{}{}", Identifier::PREFIX, hex::encode(&id.0[..]))
     }
 }
 
@@ -150,7 +169,7 @@ impl From<ChangeHash> for String {
 
 impl From<&ChangeHash> for String {
     fn from(change_hash: &ChangeHash) -> Self {
-        hex::encode(change_hash.0.as_ref())
+        hex::encode(&change_hash.0[..])
     }
 }
 
@@ -255,4 +274,17 @@ mod test {
             id.to_string().starts_with(Identifier::PREFIX)
         }
     }
+
+    #[test]
+    fn check_from_x25519_public_key() {
+        let public_key = X25519PublicKey([1u8; 32]);
+        let identifier = Identifier::from_x25519_public_key(&public_key);
+
+        assert_eq!(identifier, Identifier::from_x25519_public_key(&public_key));
+        assert_ne!(identifier.0, public_key.0);
+        assert_ne!(
+            identifier,
+            Identifier::from_x25519_public_key(&X25519PublicKey([2u8; 32]))
+        );
+    }
 }
@@ -0,0 +1,123 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::models::{
+    CredentialHash, RevocationList, RevocationListAndPurposeKey, RevocationListData, VersionedData,
+    CREDENTIAL_HASH_LEN, REVOCATION_LIST_DATA_TYPE,
+};
+use crate::{Identifier, IdentityError};
+
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Error, Result};
+
+impl RevocationList {
+    /// Create [`VersionedData`] with corresponding version and data_type
+    pub fn create_versioned_data(data: Vec<u8>) -> VersionedData {
+        VersionedData {
+            version: 1,
+            data_type: REVOCATION_LIST_DATA_TYPE,
+            data,
+        }
+    }
+
+    /// Extract [`RevocationListData`]
+    pub fn get_revocation_list_data(&self) -> Result<RevocationListData> {
+        RevocationListData::get_data(&minicbor::decode(&self.data)?)
+    }
+}
+
+impl RevocationListData {
+    /// Extract [`RevocationListData`] from [`VersionedData`]
+    pub fn get_data(versioned_data: &VersionedData) -> Result<Self> {
+        if versioned_data.version != 1 {
+            return Err(IdentityError::UnknownRevocationListVersion)?;
+        }
+
+        if versioned_data.data_type != REVOCATION_LIST_DATA_TYPE {
+            return Err(IdentityError::InvalidRevocationListDataType)?;
+        }
+
+        Ok(minicbor::decode(&versioned_data.data)?)
+    }
+
+    /// Return true if all the credentials of that subject are revoked
+    pub fn is_subject_revoked(&self, subject: &Identifier) -> bool {
+        self.revoked_subjects.contains(subject)
+    }
+
+    /// Return true if that specific credential is revoked
+    pub fn is_credential_revoked(&self, credential_hash: &CredentialHash) -> bool {
+        self.revoked_credentials
+            .iter()
+            .any(|c| &c.credential_hash == credential_hash)
+    }
+}
+
+impl RevocationListAndPurposeKey {
+    /// Encode the revocation list as CBOR bytes
+    pub fn encode_as_cbor_bytes(&self) -> Result<Vec<u8>> {
+        ockam_core::cbor_encode_preallocate(self)
+    }
+
+    /// Decode the revocation list from bytes
+    pub fn decode_from_cbor_bytes(bytes: &[u8]) -> Result<RevocationListAndPurposeKey> {
+        Ok(minicbor::decode(bytes)?)
+    }
+
+    /// Return the decoded revocation list data
+    pub fn get_revocation_list_data(&self) -> Result<RevocationListData> {
+        self.revocation_list.get_revocation_list_data()
+    }
+}
+
+impl Display for CredentialHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&hex::encode(&self.0[..]))
+    }
+}
+
+impl TryFrom<&str> for CredentialHash {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Ok(data) = hex::decode(value) {
+            data.as_slice().try_into()
+        } else {
+            Err(IdentityError::InvalidCredentialHash(value.into()))?
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for CredentialHash {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if let Ok(value) = <[u8; CREDENTIAL_HASH_LEN]>::try_from(value) {
+            Ok(Self(value))
+        } else {
+            Err(IdentityError::InvalidCredentialHash(hex::encode(value)))?
+        }
+    }
+}
+
+impl FromStr for CredentialHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl From<&CredentialHash> for String {
+    fn from(credential_hash: &CredentialHash) -> Self {
+        credential_hash.to_string()
+    }
+}
+
+impl AsRef<[u8]> for CredentialHash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
@@ -1,6 +1,6 @@
 use core::sync::atomic::Ordering;
 use ockam_core::compat::sync::Arc;
-use ockam_core::{Any, Result, Route, Routed, SecureChannelLocalInfo};
+use ockam_core::{route, Any, Result, Route, Routed, SecureChannelLocalInfo};
 use ockam_core::{Decodable, LocalMessage};
 use ockam_node::Context;
 
@@ -12,8 +12,8 @@ use crate::secure_channel::nonce_tracker::NonceTracker;
 use crate::secure_channel::{Addresses, Role};
 use crate::{
     DecryptionRequest, DecryptionResponse, Identities, IdentityError, Nonce,
-    PlaintextPayloadMessage, RefreshCredentialsMessage, SecureChannelMessage,
-    SecureChannelPaddedMessage, NOISE_NONCE_LEN,
+    PlaintextPayloadMessage, RefreshCredentialsMessage, RehandshakeRequestMessage,
+    RehandshakeResponseMessage, SecureChannelMessage, SecureChannelPaddedMessage, NOISE_NONCE_LEN,
 };
 
 use crate::secure_channel::encryptor_worker::SecureChannelSharedState;
@@ -182,6 +182,77 @@ impl DecryptorHandler {
         Ok(())
     }
 
+    /// Compute the new keys of a re-handshake started by the other side, and let the encryptor
+    /// send our ephemeral public key
+    async fn handle_rehandshake_request(
+        &mut self,
+        ctx: &mut Context,
+        msg: RehandshakeRequestMessage,
+    ) -> Result<()> {
+        let Some(rehandshake) = &self.shared_state.rehandshake else {
+            warn!(
+                "Ignoring re-handshake request received on {}",
+                self.addresses.decryptor_remote
+            );
+            return Ok(());
+        };
+        if self.role.is_initiator() {
+            warn!(
+                "Ignoring re-handshake request received by the initiator on {}",
+                self.addresses.decryptor_remote
+            );
+            return Ok(());
+        }
+
+        let Some(decryption_key) = rehandshake.respond(&msg).await? else {
+            warn!(
+                "Ignoring a re-handshake request which doesn't use the key exchange of {}",
+                self.addresses.decryptor_remote
+            );
+            return Ok(());
+        };
+        self.decryptor.set_next_key(decryption_key).await?;
+
+        debug!(
+            "Answering re-handshake request for {}",
+            self.addresses.decryptor_remote
+        );
+
+        ctx.send_from_address(
+            route![self.addresses.encryptor_rehandshake.clone()],
+            (),
+            self.addresses.decryptor_remote.clone(),
+        )
+        .await
+    }
+
+    /// Compute the new keys of a re-handshake started by us
+    async fn handle_rehandshake_response(&mut self, msg: RehandshakeResponseMessage) -> Result<()> {
+        let Some(rehandshake) = &self.shared_state.rehandshake else {
+            warn!(
+                "Ignoring re-handshake response received on {}",
+                self.addresses.decryptor_remote
+            );
+            return Ok(());
+        };
+
+        match rehandshake.complete(&msg).await? {
+            Some(decryption_key) => {
+                self.decryptor.set_next_key(decryption_key).await?;
+                info!(
+                    "Completed re-handshake for {}",
+                    self.addresses.decryptor_remote
+                );
+            }
+            None => warn!(
+                "Ignoring a re-handshake response to an outdated or downgraded request for {}",
+                self.addresses.decryptor_remote
+            ),
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     pub(crate) async fn handle_decrypt(
         &mut self,
@@ -202,6 +273,13 @@ impl DecryptorHandler {
 
         // Decrypt the binary
         let (decrypted_payload, nonce) = self.decryptor.decrypt(payload.as_mut_slice()).await?;
+
+        // the other side uses the keys of our last re-handshake response, so it received it
+        if self.decryptor.take_switched_to_next_key() {
+            if let Some(rehandshake) = &self.shared_state.rehandshake {
+                rehandshake.confirm().await?;
+            }
+        }
         let decrypted_msg: SecureChannelPaddedMessage = minicbor::decode(decrypted_payload)?;
 
         match decrypted_msg.message {
@@ -213,6 +291,12 @@ impl DecryptorHandler {
                 self.handle_refresh_credentials(ctx, decrypted_msg).await?
             }
             SecureChannelMessage::Close => self.handle_close(ctx)?,
+            SecureChannelMessage::RehandshakeRequest(decrypted_msg) => {
+                self.handle_rehandshake_request(ctx, decrypted_msg).await?
+            }
+            SecureChannelMessage::RehandshakeResponse(decrypted_msg) => {
+                self.handle_rehandshake_response(decrypted_msg).await?
+            }
         };
 
         Ok(())
@@ -228,6 +312,11 @@ pub(crate) struct Decryptor {
     vault: Arc<dyn VaultForSecureChannels>,
     key_tracker: KeyTracker,
     nonce_tracker: Option<NonceTracker>,
+    // Key obtained with a re-handshake, which the other side will use
+    // instead of the next derived key
+    next_key: Option<AeadSecretKeyHandle>,
+    // True if a message was decrypted with the key of a re-handshake since the last check
+    switched_to_next_key: bool,
 }
 
 impl Decryptor {
@@ -236,6 +325,8 @@ impl Decryptor {
             vault,
             key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
             nonce_tracker: Some(NonceTracker::new()),
+            next_key: None,
+            switched_to_next_key: false,
         }
     }
 
@@ -245,9 +336,36 @@ impl Decryptor {
             vault,
             key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
             nonce_tracker: None,
+            next_key: None,
+            switched_to_next_key: false,
         }
     }
 
+    /// Expect the other side to switch to this key, obtained with a re-handshake,
+    /// at the beginning of one of its next rekeying intervals
+    #[instrument(skip_all)]
+    pub(crate) async fn set_next_key(&mut self, key: AeadSecretKeyHandle) -> Result<()> {
+        if let Some(previous_key) = self.next_key.replace(key) {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        Ok(())
+    }
+
+    /// Return true if the other side started using the key of a re-handshake
+    /// since the last call
+    pub(crate) fn take_switched_to_next_key(&mut self) -> bool {
+        core::mem::take(&mut self.switched_to_next_key)
+    }
+
+    /// Check if a message can be decrypted with a given key, without modifying it
+    async fn can_decrypt(&self, key: &AeadSecretKeyHandle, payload: &[u8], nonce: Nonce) -> bool {
+        let mut ciphertext = payload[NOISE_NONCE_LEN..].to_vec();
+        self.vault
+            .aead_decrypt(key, &mut ciphertext, &nonce.to_aes_gcm_nonce(), &[])
+            .await
+            .is_ok()
+    }
+
     #[instrument(skip_all)]
     pub async fn decrypt<'a>(&mut self, payload: &'a mut [u8]) -> Result<(&'a [u8], Nonce)> {
         if payload.len() < NOISE_NONCE_LEN {
@@ -262,6 +380,7 @@ impl Decryptor {
         };
 
         let rekey_key;
+        let mut uses_next_key = false;
 
         let rekeying = self.nonce_tracker.is_some();
         let key = if rekeying {
@@ -270,7 +389,18 @@ impl Decryptor {
             if let Some(key) = self.key_tracker.get_key(nonce)? {
                 key
             } else {
-                rekey_key = Encryptor::rekey(&self.vault, &self.key_tracker.current_key).await?;
+                // the other side may have started a new interval either with the key of a
+                // re-handshake, or with a key derived from the current one
+                rekey_key = match self.next_key.take() {
+                    Some(next_key) if self.can_decrypt(&next_key, payload, nonce).await => {
+                        uses_next_key = true;
+                        next_key
+                    }
+                    next_key => {
+                        self.next_key = next_key;
+                        Encryptor::rekey(&self.vault, &self.key_tracker.current_key).await?
+                    }
+                };
                 &rekey_key
             }
         } else {
@@ -292,6 +422,7 @@ impl Decryptor {
         match result {
             Ok(result) => {
                 self.nonce_tracker = nonce_tracker;
+                self.switched_to_next_key |= uses_next_key;
                 if let Some(key_to_delete) = self.key_tracker.update_key(&key.clone())? {
                     self.vault.delete_aead_secret_key(key_to_delete).await?;
                 }
@@ -311,6 +442,9 @@ impl Decryptor {
         if let Some(previous_key) = self.key_tracker.previous_key.clone() {
             self.vault.delete_aead_secret_key(previous_key).await?;
         };
+        if let Some(next_key) = self.next_key.clone() {
+            self.vault.delete_aead_secret_key(next_key).await?;
+        };
         Ok(())
     }
 }
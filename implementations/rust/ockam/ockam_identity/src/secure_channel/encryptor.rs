@@ -1,17 +1,25 @@
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
 use tracing_attributes::instrument;
 
+use crate::models::TimestampInSeconds;
 use crate::secure_channel::handshake::handshake::AES_GCM_TAGSIZE;
-use crate::{Nonce, MAX_NONCE, NOISE_NONCE_LEN};
+use crate::utils::now;
+use crate::{IdentityError, Nonce, MAX_NONCE, NOISE_NONCE_LEN};
 
 pub(crate) struct Encryptor {
     key: AeadSecretKeyHandle,
     nonce: Nonce,
     vault: Arc<dyn VaultForSecureChannels>,
     rekeying: bool,
+    // If set, the key is also renewed when it has been used for that long
+    rekey_interval: Option<Duration>,
+    key_created_at: Option<TimestampInSeconds>,
+    // Key obtained with a re-handshake, used instead of the next derived key
+    next_key: Option<AeadSecretKeyHandle>,
 }
 
 // To simplify the implementation, we use the same constant for the size of the message
@@ -44,6 +52,10 @@ impl Encryptor {
 
     #[instrument(skip_all)]
     pub async fn encrypt(&mut self, payload: &mut [u8]) -> Result<()> {
+        if self.rekeying && (self.next_key.is_some() || self.rekey_is_due()) {
+            self.skip_to_next_interval()?;
+        }
+
         let current_nonce = self.nonce;
 
         self.nonce.increment()?;
@@ -52,9 +64,13 @@ impl Encryptor {
             && current_nonce.value() > 0
             && current_nonce.value() % KEY_RENEWAL_INTERVAL == 0
         {
-            let new_key = Self::rekey(&self.vault, &self.key).await?;
+            let new_key = match self.next_key.take() {
+                Some(next_key) => next_key,
+                None => Self::rekey(&self.vault, &self.key).await?,
+            };
             let old_key = core::mem::replace(&mut self.key, new_key);
             self.vault.delete_aead_secret_key(old_key).await?;
+            self.key_created_at = now().ok();
         }
 
         payload[..NOISE_NONCE_LEN].copy_from_slice(&current_nonce.to_noise_nonce());
@@ -82,11 +98,57 @@ impl Encryptor {
             nonce,
             vault,
             rekeying,
+            rekey_interval: None,
+            key_created_at: now().ok(),
+            next_key: None,
+        }
+    }
+
+    /// Renew the key when it has been used for longer than the given interval,
+    /// even if fewer than [`KEY_RENEWAL_INTERVAL`] messages were encrypted with it
+    pub fn with_rekey_interval(mut self, rekey_interval: Option<Duration>) -> Self {
+        self.rekey_interval = rekey_interval;
+        self
+    }
+
+    /// Use this key, obtained with a re-handshake, starting from the next message
+    #[instrument(skip_all)]
+    pub(crate) async fn set_next_key(&mut self, key: AeadSecretKeyHandle) -> Result<()> {
+        if let Some(previous_key) = self.next_key.replace(key) {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        Ok(())
+    }
+
+    fn rekey_is_due(&self) -> bool {
+        match (self.rekey_interval, self.key_created_at) {
+            (Some(rekey_interval), Some(key_created_at)) => now()
+                .map(|now| now >= key_created_at + rekey_interval)
+                .unwrap_or(false),
+            _ => false,
         }
     }
 
+    /// Move the nonce to the beginning of the next rekeying interval so that the next message
+    /// is encrypted with a new key. The other side derives the same key from the nonce.
+    fn skip_to_next_interval(&mut self) -> Result<()> {
+        let value = self.nonce.value();
+        if value > 0 && value % KEY_RENEWAL_INTERVAL == 0 {
+            return Ok(());
+        }
+
+        let next_interval_start = (value / KEY_RENEWAL_INTERVAL + 1)
+            .checked_mul(KEY_RENEWAL_INTERVAL)
+            .ok_or(IdentityError::NonceOverflow)?;
+        self.nonce = next_interval_start.into();
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     pub(crate) async fn shutdown(&self) -> Result<()> {
+        if let Some(next_key) = self.next_key.clone() {
+            self.vault.delete_aead_secret_key(next_key).await?;
+        }
         if !self.vault.delete_aead_secret_key(self.key.clone()).await? {
             Err(Error::new(
                 Origin::Ockam,
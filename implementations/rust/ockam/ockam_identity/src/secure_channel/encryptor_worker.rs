@@ -18,6 +18,7 @@ use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
 use crate::secure_channel::encryptor::Encryptor;
 use crate::secure_channel::handshake::handshake::AES_GCM_TAGSIZE;
+use crate::secure_channel::handshake::rehandshake::Rehandshake;
 use crate::{
     ChangeHistoryRepository, CredentialRetriever, Identifier, IdentityError, Nonce,
     PlaintextPayloadMessage, RefreshCredentialsMessage, SecureChannelMessage,
@@ -53,6 +54,8 @@ pub(crate) struct SecureChannelSharedState {
     /// Allows Decryptor to flag that we're closing the channel because we received a Close message from the other side,
     /// therefore, we don't need to send that message again to the other side
     pub(crate) should_send_close: Arc<AtomicBool>,
+    /// State of the in-band re-handshakes, absent if the keys of the channel are never renewed
+    pub(crate) rehandshake: Option<Rehandshake>,
 }
 
 pub(crate) struct EncryptorWorker {
@@ -104,6 +107,16 @@ impl EncryptorWorker {
             encryptor=%self.addresses.encryptor,
             "encrypting message");
 
+        // switch to the key of a completed or confirmed re-handshake
+        if let Some(key) = self
+            .shared_state
+            .rehandshake
+            .as_ref()
+            .and_then(|r| r.take_encryption_key())
+        {
+            self.encryptor.set_next_key(key).await?;
+        }
+
         let expected_len = minicbor::len(&msg);
         let mut destination = vec![0u8; NOISE_NONCE_LEN + expected_len + AES_GCM_TAGSIZE];
         minicbor::encode(&msg, &mut destination[NOISE_NONCE_LEN..])?;
@@ -202,6 +215,8 @@ impl EncryptorWorker {
         // Remove our address
         let _ = onward_route.step();
 
+        self.start_rehandshake_if_due(ctx).await?;
+
         let payload = CowBytes::from(msg.payload);
         let msg = PlaintextPayloadMessage {
             onward_route,
@@ -314,6 +329,69 @@ impl EncryptorWorker {
         Ok(())
     }
 
+    /// Send a new ephemeral public key to the other side if it is time to renew the channel keys
+    #[instrument(skip_all)]
+    async fn start_rehandshake_if_due(&mut self, ctx: &Context) -> Result<()> {
+        let Some(rehandshake) = &self.shared_state.rehandshake else {
+            return Ok(());
+        };
+        let Some(request) = rehandshake.start_if_due().await? else {
+            return Ok(());
+        };
+
+        let msg = SecureChannelMessage::RehandshakeRequest(request);
+        let msg = Self::add_padding(msg);
+        let msg = self.encrypt(ctx, msg).await?;
+
+        debug!(
+            role=%self.role,
+            encryptor=%self.addresses.encryptor,
+            "sending re-handshake request");
+
+        let remote_route = self.shared_state.remote_route.read().unwrap().route.clone();
+        // Send the message to the decryptor on the other side
+        ctx.send_from_address(
+            remote_route,
+            NeutralMessage::from(msg),
+            self.addresses.encryptor.clone(),
+        )
+        .await
+    }
+
+    /// Answer a re-handshake started by the other side with the key prepared by the decryptor.
+    /// The new encryption key is used once the other side confirms that it got the response
+    #[instrument(skip_all)]
+    async fn handle_rehandshake_response(&mut self, ctx: &Context) -> Result<()> {
+        let Some(rehandshake) = &self.shared_state.rehandshake else {
+            return Ok(());
+        };
+        let Some(response) = rehandshake.take_response() else {
+            return Ok(());
+        };
+
+        let msg = SecureChannelMessage::RehandshakeResponse(response);
+        let msg = Self::add_padding(msg);
+        // the response is encrypted with the current key since the other side
+        // needs it to compute the new keys
+        let msg = self.encrypt(ctx, msg).await?;
+
+        let remote_route = self.shared_state.remote_route.read().unwrap().route.clone();
+        // Send the message to the decryptor on the other side
+        ctx.send_from_address(
+            remote_route,
+            NeutralMessage::from(msg),
+            self.addresses.encryptor.clone(),
+        )
+        .await?;
+
+        debug!(
+            role=%self.role,
+            encryptor=%self.addresses.encryptor,
+            "sent re-handshake response");
+
+        Ok(())
+    }
+
     async fn send_close_channel(&mut self, ctx: &Context) -> Result<()> {
         let msg = SecureChannelMessage::Close;
         let msg = Self::add_padding(msg);
@@ -380,6 +458,8 @@ impl Worker for EncryptorWorker {
             self.handle_encrypt_api(ctx, msg).await?;
         } else if msg_addr == &self.addresses.encryptor_internal {
             self.handle_refresh_credentials(ctx).await?;
+        } else if msg_addr == &self.addresses.encryptor_rehandshake {
+            self.handle_rehandshake_response(ctx).await?;
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination)?;
         }
@@ -397,6 +477,9 @@ impl Worker for EncryptorWorker {
         if self.shared_state.should_send_close.load(Ordering::Relaxed) {
             let _ = self.send_close_channel(context).await;
         }
+        if let Some(rehandshake) = &self.shared_state.rehandshake {
+            rehandshake.shutdown().await?;
+        }
         self.encryptor.shutdown().await
     }
 }
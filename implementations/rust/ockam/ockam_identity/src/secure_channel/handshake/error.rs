@@ -16,6 +16,10 @@ pub enum XXError {
     ExceededMaxMessageLen,
     /// Invalid internal state.
     InvalidInternalState,
+    /// The initiator didn't use a hybrid key exchange while it is required.
+    HybridKeyExchangeRequired,
+    /// The initiator static key is not one of the trusted static keys.
+    UntrustedStaticKey,
 }
 
 impl StdError for XXError {}
@@ -29,6 +33,10 @@ impl fmt::Display for XXError {
                 write!(f, "exceeded maximum allowed message length for noise")
             }
             Self::InvalidInternalState => write!(f, "invalid internal state"),
+            Self::HybridKeyExchangeRequired => {
+                write!(f, "a hybrid X25519 + ML-KEM-768 key exchange is required")
+            }
+            Self::UntrustedStaticKey => write!(f, "the initiator static key is not trusted"),
         }
     }
 }
@@ -41,6 +49,8 @@ impl From<XXError> for Error {
             XXError::MessageLenMismatch => Kind::Misuse,
             XXError::ExceededMaxMessageLen => Kind::Invalid,
             XXError::InvalidInternalState => Kind::Internal,
+            XXError::HybridKeyExchangeRequired => Kind::Invalid,
+            XXError::UntrustedStaticKey => Kind::Invalid,
         };
 
         Error::new(Origin::KeyExchange, kind, err)
@@ -1,7 +1,7 @@
 #![allow(unexpected_cfgs)]
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake_state_machine::{HandshakeKeys, Status};
-use crate::secure_channel::Role;
+use crate::secure_channel::{HybridKeyExchange, Role};
 use crate::Nonce;
 use cfg_if::cfg_if;
 use ockam_core::compat::sync::Arc;
@@ -9,8 +9,10 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{
-    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
-    X25519PublicKey, X25519SecretKeyHandle, X25519_PUBLIC_KEY_LENGTH,
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, MlKem768Ciphertext, MlKem768PublicKey,
+    MlKem768SecretKeyHandle, SecretBufferHandle, VaultForSecureChannels, X25519PublicKey,
+    X25519SecretKeyHandle, ML_KEM_768_CIPHERTEXT_LENGTH, ML_KEM_768_PUBLIC_KEY_LENGTH,
+    X25519_PUBLIC_KEY_LENGTH,
 };
 use sha2::{Digest, Sha256};
 #[cfg(feature = "debugger")]
@@ -28,9 +30,15 @@ pub const NOISE_MAX_MESSAGE_SIZE: usize = 65535;
 /// The first members are used in the implementation of some of the protocol steps, for example to
 /// encrypt messages
 /// The variables used in the protocol itself: s, e, rs, re,... are handled in `HandshakeState`
+///
+/// In the hybrid mode, the initiator also sends an ephemeral ML-KEM-768 public key in message 1,
+/// and the responder sends back, in message 2, the encrypted ciphertext of a shared secret
+/// encapsulated for that key. That shared secret is mixed into ck right after DH(e, re) so that
+/// the final keys are secret as long as either X25519 or ML-KEM-768 is not broken.
 pub(super) struct Handshake {
     vault: Arc<dyn VaultForSecureChannels>,
     protocol_name: [u8; 32],
+    hybrid_key_exchange: HybridKeyExchange,
     pub(super) state: HandshakeState,
 }
 
@@ -61,6 +69,14 @@ impl Handshake {
         state.mix_hash(&e_pub_key.0);
         let mut message1 = e_pub_key.0.to_vec();
 
+        // generate ekem, which is only needed by the initiator, and output ekem.pubKey
+        if state.hybrid {
+            state.ekem = Some(self.vault.generate_ephemeral_ml_kem_secret_key().await?);
+            let ekem_pub_key = self.vault.get_ml_kem_public_key(state.ekem()?).await?;
+            state.mix_hash(&ekem_pub_key.0);
+            message1.extend_from_slice(&ekem_pub_key.0);
+        }
+
         // output message 1 payload
         message1.extend_from_slice(payload);
         state.mix_hash(payload);
@@ -82,6 +98,8 @@ impl Handshake {
             return Err(XXError::ExceededMaxMessageLen)?;
         }
 
+        self.negotiate_hybrid_key_exchange(message1).await?;
+
         let mut state = self.state.clone();
         // read e.pubKey
         let key = Self::read_key(message1)?;
@@ -89,8 +107,15 @@ impl Handshake {
 
         state.re = Some(X25519PublicKey(*key));
 
+        // read rekem.pubKey
+        if state.hybrid {
+            let key = Self::read_message1_kem_key(message1)?;
+            state.mix_hash(key);
+            state.rekem = Some(MlKem768PublicKey(*key));
+        }
+
         // decode payload
-        let payload = Self::read_message1_payload(message1)?;
+        let payload = Self::read_message1_payload(message1, state.hybrid)?;
         state.mix_hash(payload);
 
         self.state = state;
@@ -114,6 +139,15 @@ impl Handshake {
         let dh = self.dh(state.e()?, state.re()?).await?;
         self.hkdf(&mut state, dh).await?;
 
+        // encrypt and output the KEM ciphertext
+        // ck, k = HKDF(ck, KEM shared secret, 2)
+        if state.hybrid {
+            let (ciphertext, shared_secret) = self.vault.ml_kem_encapsulate(state.rekem()?).await?;
+            let c = self.encrypt_and_hash(&mut state, &ciphertext.0).await?;
+            message2.extend(c);
+            self.hkdf(&mut state, shared_secret).await?;
+        }
+
         // encrypt and output s.pubKey
         let s_pub_key = self.get_public_key(state.s()?).await?;
         let c = self.encrypt_and_hash(&mut state, &s_pub_key.0).await?;
@@ -154,8 +188,25 @@ impl Handshake {
         let dh = self.dh(state.e()?, state.re()?).await?;
         self.hkdf(&mut state, dh).await?;
 
+        // decrypt the KEM ciphertext
+        // ck, k = HKDF(ck, KEM shared secret, 2)
+        if state.hybrid {
+            let ciphertext = Self::read_message2_encrypted_kem_ciphertext(message2)?;
+            let ciphertext = self.hash_and_decrypt(&mut state, ciphertext).await?;
+            let ciphertext = MlKem768Ciphertext(
+                ciphertext
+                    .try_into()
+                    .map_err(|_| XXError::MessageLenMismatch)?,
+            );
+            let shared_secret = self
+                .vault
+                .ml_kem_decapsulate(state.ekem()?, &ciphertext)
+                .await?;
+            self.hkdf(&mut state, shared_secret).await?;
+        }
+
         // decrypt rs.pubKey
-        let rs_pub_key = Self::read_message2_encrypted_key(message2)?;
+        let rs_pub_key = Self::read_message2_encrypted_key(message2, state.hybrid)?;
         let rs_pub_key = self.hash_and_decrypt(&mut state, rs_pub_key).await?;
         let rs_pub_key = X25519PublicKey(
             rs_pub_key
@@ -169,7 +220,7 @@ impl Handshake {
         self.hkdf(&mut state, dh).await?;
 
         // decrypt payload
-        let c = Self::read_message2_payload(message2)?;
+        let c = Self::read_message2_payload(message2, state.hybrid)?;
         let payload = self.hash_and_decrypt(&mut state, c).await?;
 
         self.state = state;
@@ -253,6 +304,8 @@ impl Handshake {
         state.status = Ready(HandshakeKeys {
             encryption_key,
             decryption_key,
+            chaining_key: state.take_ck()?,
+            hybrid: state.hybrid,
         });
         // now remove the ephemeral keys which are not useful anymore
         self.state = state;
@@ -272,22 +325,260 @@ impl Handshake {
     }
 }
 
+/// Functions used in the state machines of the static key patterns (see `StaticKeyPattern`)
+/// Only the messages 1 and 2 are exchanged since the static keys are known in advance
+impl Handshake {
+    /// Create a new handshake for a static key pattern.
+    /// The static key is only missing for an initiator using the NK pattern
+    pub(super) async fn new_with_static_keys(
+        vault: Arc<dyn VaultForSecureChannels>,
+        pattern: StaticKeyPattern,
+        static_key: Option<X25519SecretKeyHandle>,
+    ) -> Result<Handshake> {
+        let ephemeral_key = Self::generate_ephemeral_key(vault.clone()).await?;
+        Ok(Handshake {
+            vault,
+            protocol_name: *pattern.protocol_name(),
+            hybrid_key_exchange: HybridKeyExchange::Disabled,
+            state: HandshakeState::new(static_key, ephemeral_key),
+        })
+    }
+
+    /// Initialize the handshake variables, then mix the pre-messages: the static public keys
+    /// which are known in advance.
+    /// This can be called again on the responder side to try another initiator static key
+    pub(super) async fn initialize_with_static_keys(
+        &mut self,
+        initiator_static_public_key: Option<&X25519PublicKey>,
+        responder_static_public_key: &X25519PublicKey,
+    ) -> Result<()> {
+        if let Some(ck) = self.state.ck.take() {
+            _ = self.vault.delete_secret_buffer(ck).await?;
+        }
+        self.initialize().await?;
+
+        if let Some(key) = initiator_static_public_key {
+            self.state.mix_hash(&key.0);
+        }
+        self.state.mix_hash(&responder_static_public_key.0);
+        Ok(())
+    }
+
+    /// Encode the first message, sent from the initiator to the responder: e, es, (ss)
+    pub(super) async fn encode_static_key_message1(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "debugger")]
+        debug!("Encoding static key message 1");
+
+        let mut state = self.state.clone();
+        // output e.pubKey
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        let mut message1 = e_pub_key.0.to_vec();
+
+        // ck, k = HKDF(ck, DH(e, rs), 2)
+        let dh = self.dh(state.e()?, state.rs()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        // ck, k = HKDF(ck, DH(s, rs), 2), only for the KK pattern
+        if state.s.is_some() {
+            let dh = self.dh(state.s()?, state.rs()?).await?;
+            self.hkdf(&mut state, dh).await?;
+        }
+
+        // encrypt and output the payload
+        let c = self.encrypt_and_hash(&mut state, payload).await?;
+        message1.extend(c);
+
+        if message1.len() > NOISE_MAX_MESSAGE_SIZE {
+            return Err(XXError::ExceededMaxMessageLen)?;
+        }
+
+        self.state = state;
+        Ok(message1)
+    }
+
+    /// Decode the first message sent by the initiator.
+    /// For the KK pattern, `rs` must have been set to the expected initiator static key.
+    /// If the decryption fails, the intermediate secrets are deleted and the handshake must be
+    /// initialized again before trying another initiator static key
+    pub(super) async fn decode_static_key_message1(&mut self, message1: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "debugger")]
+        debug!("Decoding static key message 1");
+
+        if message1.len() > NOISE_MAX_MESSAGE_SIZE {
+            return Err(XXError::ExceededMaxMessageLen)?;
+        }
+
+        let mut state = self.state.clone();
+        match self.read_static_key_message1(&mut state, message1).await {
+            Ok(payload) => {
+                self.state = state;
+                Ok(payload)
+            }
+            Err(e) => {
+                self.delete_intermediate_secrets(&mut state).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Encode the second message, sent from the responder to the initiator: e, ee, (se)
+    pub(super) async fn encode_static_key_message2(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "debugger")]
+        debug!("Encoding static key message 2");
+
+        let mut state = self.state.clone();
+        // output e.pubKey
+        let e_pub_key = self.get_public_key(state.e()?).await?;
+        state.mix_hash(&e_pub_key.0);
+        let mut message2 = e_pub_key.0.to_vec();
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        // ck, k = HKDF(ck, DH(e, rs), 2), only for the KK pattern
+        if state.rs.is_some() {
+            let dh = self.dh(state.e()?, state.rs()?).await?;
+            self.hkdf(&mut state, dh).await?;
+        }
+
+        // encrypt and output the payload
+        let c = self.encrypt_and_hash(&mut state, payload).await?;
+        message2.extend(c);
+
+        if message2.len() > NOISE_MAX_MESSAGE_SIZE {
+            return Err(XXError::ExceededMaxMessageLen)?;
+        }
+
+        self.state = state;
+        Ok(message2)
+    }
+
+    /// Decode the second message sent by the responder
+    pub(super) async fn decode_static_key_message2(&mut self, message2: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "debugger")]
+        debug!("Decoding static key message 2");
+
+        if message2.len() > NOISE_MAX_MESSAGE_SIZE {
+            return Err(XXError::ExceededMaxMessageLen)?;
+        }
+
+        let mut state = self.state.clone();
+        // read re.pubKey
+        let key = Self::read_key(message2)?;
+        state.mix_hash(key);
+        state.re = Some(X25519PublicKey(*key));
+
+        // ck, k = HKDF(ck, DH(e, re), 2)
+        let dh = self.dh(state.e()?, state.re()?).await?;
+        self.hkdf(&mut state, dh).await?;
+
+        // ck, k = HKDF(ck, DH(s, re), 2), only for the KK pattern
+        if state.s.is_some() {
+            let dh = self.dh(state.s()?, state.re()?).await?;
+            self.hkdf(&mut state, dh).await?;
+        }
+
+        // decrypt the payload
+        let c = Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message2)?;
+        let payload = self.hash_and_decrypt(&mut state, c).await?;
+
+        self.state = state;
+        Ok(payload)
+    }
+
+    /// Read the first message of a static key pattern with a copy of the handshake state
+    async fn read_static_key_message1(
+        &self,
+        state: &mut HandshakeState,
+        message1: &[u8],
+    ) -> Result<Vec<u8>> {
+        // read re.pubKey
+        let key = Self::read_key(message1)?;
+        state.mix_hash(key);
+        state.re = Some(X25519PublicKey(*key));
+
+        // ck, k = HKDF(ck, DH(s, re), 2)
+        let dh = self.dh(state.s()?, state.re()?).await?;
+        self.hkdf(state, dh).await?;
+
+        // ck, k = HKDF(ck, DH(s, rs), 2), only for the KK pattern
+        if state.rs.is_some() {
+            let dh = self.dh(state.s()?, state.rs()?).await?;
+            self.hkdf(state, dh).await?;
+        }
+
+        // decrypt the payload
+        let c = Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message1)?;
+        self.hash_and_decrypt(state, c).await
+    }
+
+    /// Delete the ck and k secrets of a handshake state which won't be used
+    async fn delete_intermediate_secrets(&self, state: &mut HandshakeState) -> Result<()> {
+        if let Some(ck) = state.ck.take() {
+            _ = self.vault.delete_secret_buffer(ck).await?;
+        }
+        if let Some(k) = state.k.take() {
+            _ = self.vault.delete_aead_secret_key(k).await?;
+        }
+        Ok(())
+    }
+}
+
 impl Handshake {
     /// Create a new handshake
     pub(super) async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         static_key: X25519SecretKeyHandle,
+        hybrid_key_exchange: HybridKeyExchange,
     ) -> Result<Handshake> {
         // 1. generate an ephemeral key pair for this handshake and set it to e
         let ephemeral_key = Self::generate_ephemeral_key(vault.clone()).await?;
 
         // 2. initialize the handshake
         // We currently don't use any payload for message 1
-        Ok(Handshake {
+        let mut handshake = Handshake {
             vault,
             protocol_name: *PROTOCOL_NAME,
-            state: HandshakeState::new(static_key, ephemeral_key),
-        })
+            hybrid_key_exchange,
+            state: HandshakeState::new(Some(static_key), ephemeral_key),
+        };
+
+        // 3. the initiator decides to use the hybrid mode, the responder follows if it is allowed
+        if hybrid_key_exchange == HybridKeyExchange::Required {
+            handshake.use_hybrid_key_exchange();
+        }
+
+        Ok(handshake)
+    }
+
+    /// Use the hybrid mode of the handshake.
+    /// This must be called before the handshake is initialized since the protocol name differs
+    fn use_hybrid_key_exchange(&mut self) {
+        self.protocol_name = *HYBRID_PROTOCOL_NAME;
+        self.state.hybrid = true;
+    }
+
+    /// On the responder side, check if the initiator uses the hybrid mode, which is recognized by
+    /// the ML-KEM public key following its ephemeral key in message 1.
+    /// If it does, and the hybrid mode is allowed, re-initialize the handshake with the hybrid
+    /// protocol name
+    async fn negotiate_hybrid_key_exchange(&mut self, message1: &[u8]) -> Result<()> {
+        let requested = message1.len() >= X25519_PUBLIC_KEY_LENGTH + ML_KEM_768_PUBLIC_KEY_LENGTH;
+        match (self.hybrid_key_exchange, requested) {
+            (HybridKeyExchange::Disabled, _) | (HybridKeyExchange::Allowed, false) => Ok(()),
+            (HybridKeyExchange::Required, false) => Err(XXError::HybridKeyExchangeRequired)?,
+            (_, true) => {
+                if !self.state.hybrid {
+                    let ck = self.state.take_ck()?;
+                    self.vault.delete_secret_buffer(ck).await?;
+                    self.use_hybrid_key_exchange();
+                    self.initialize().await?;
+                }
+                Ok(())
+            }
+        }
     }
 
     /// Import the ck secret
@@ -296,7 +587,10 @@ impl Handshake {
     }
 
     /// Return the public key corresponding to a given key id
-    async fn get_public_key(&self, key: &X25519SecretKeyHandle) -> Result<X25519PublicKey> {
+    pub(super) async fn get_public_key(
+        &self,
+        key: &X25519SecretKeyHandle,
+    ) -> Result<X25519PublicKey> {
         self.vault.get_x25519_public_key(key).await
     }
 
@@ -359,7 +653,7 @@ impl Handshake {
         let k1 = self.vault.convert_secret_buffer_to_aead_key(k1).await?;
         let k2 = self.vault.convert_secret_buffer_to_aead_key(k2).await?;
 
-        self.vault.delete_secret_buffer(state.take_ck()?).await?;
+        // ck is kept to chain the keys of the re-handshakes to the keys of this handshake
         self.vault.delete_aead_secret_key(state.take_k()?).await?;
 
         Ok((k1, k2))
@@ -407,6 +701,10 @@ impl Handshake {
             .delete_ephemeral_x25519_secret_key(self.state.take_e()?)
             .await?;
 
+        if let Some(ekem) = self.state.ekem.take() {
+            _ = self.vault.delete_ephemeral_ml_kem_secret_key(ekem).await?;
+        }
+
         Ok(())
     }
 }
@@ -414,10 +712,42 @@ impl Handshake {
 cfg_if! {
     if #[cfg(any(not(feature = "disable_default_noise_protocol"), feature = "OCKAM_XX_25519_AES256_GCM_SHA256"))] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_AES256_GCM_SHA256";
+        pub const HYBRID_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_MLKEM768_AES256\0\0";
     } else if #[cfg(feature = "OCKAM_XX_25519_AES128_GCM_SHA256")] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_AES128_GCM_SHA256";
+        pub const HYBRID_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_MLKEM768_AES128\0\0";
     } else if #[cfg(feature = "OCKAM_XX_25519_ChaChaPolyBLAKE2s")] {
         pub const PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_ChaChaPolyBLAKE2s";
+        pub const HYBRID_PROTOCOL_NAME: &[u8; 32] = b"OCKAM_XX_25519_MLKEM768_ChaCha\0\0";
+    }
+}
+
+/// Noise patterns used by the lightweight secure channels, where the static keys are
+/// exchanged out of band instead of being attested by identities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StaticKeyPattern {
+    /// Both parties know the other party static key in advance:
+    ///   -> s
+    ///   <- s
+    ///   ...
+    ///   -> e, es, ss
+    ///   <- e, ee, se
+    KK,
+    /// Only the responder has a static key, known by the initiator in advance:
+    ///   <- s
+    ///   ...
+    ///   -> e, es
+    ///   <- e, ee
+    NK,
+}
+
+impl StaticKeyPattern {
+    /// Noise protocol name of the pattern, padded to 32 bytes
+    pub(crate) fn protocol_name(&self) -> &'static [u8; 32] {
+        match self {
+            StaticKeyPattern::KK => b"Noise_KK_25519_AESGCM_SHA256\0\0\0\0",
+            StaticKeyPattern::NK => b"Noise_NK_25519_AESGCM_SHA256\0\0\0\0",
+        }
     }
 }
 
@@ -435,21 +765,51 @@ impl Handshake {
         vault.generate_ephemeral_x25519_secret_key().await
     }
 
-    /// Read the message 1 payload which is present after the public key
-    fn read_message1_payload(message: &[u8]) -> Result<&[u8]> {
-        Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message)
+    /// Read the message 1 ML-KEM public key which is present after the public key (hybrid mode)
+    fn read_message1_kem_key(message: &[u8]) -> Result<&[u8; ML_KEM_768_PUBLIC_KEY_LENGTH]> {
+        let key =
+            Self::read_middle::<X25519_PUBLIC_KEY_LENGTH, ML_KEM_768_PUBLIC_KEY_LENGTH>(message)?;
+        Ok(key.try_into().unwrap())
+    }
+
+    /// Read the message 1 payload which is present after the public key(s)
+    fn read_message1_payload(message: &[u8], hybrid: bool) -> Result<&[u8]> {
+        const L: usize = X25519_PUBLIC_KEY_LENGTH + ML_KEM_768_PUBLIC_KEY_LENGTH;
+        if hybrid {
+            Self::read_end::<L>(message)
+        } else {
+            Self::read_end::<X25519_PUBLIC_KEY_LENGTH>(message)
+        }
+    }
+
+    /// Read the message 2 encrypted KEM ciphertext, which is present after the public key
+    /// (hybrid mode)
+    fn read_message2_encrypted_kem_ciphertext(message: &[u8]) -> Result<&[u8]> {
+        const L: usize = ML_KEM_768_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE;
+        Self::read_middle::<X25519_PUBLIC_KEY_LENGTH, L>(message)
     }
 
     /// Read the message 2 encrypted key, which is present after the public key
-    fn read_message2_encrypted_key(message: &[u8]) -> Result<&[u8]> {
+    /// and the encrypted KEM ciphertext, if any
+    fn read_message2_encrypted_key(message: &[u8], hybrid: bool) -> Result<&[u8]> {
+        const N: usize = X25519_PUBLIC_KEY_LENGTH + ML_KEM_768_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE;
         const L: usize = X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
-        Self::read_middle::<X25519_PUBLIC_KEY_LENGTH, L>(message)
+        if hybrid {
+            Self::read_middle::<N, L>(message)
+        } else {
+            Self::read_middle::<X25519_PUBLIC_KEY_LENGTH, L>(message)
+        }
     }
 
     /// Read the message 2 encrypted payload, which is present after the encrypted key
-    fn read_message2_payload(message: &[u8]) -> Result<&[u8]> {
+    fn read_message2_payload(message: &[u8], hybrid: bool) -> Result<&[u8]> {
         const L: usize = 2 * X25519_PUBLIC_KEY_LENGTH + AES_GCM_TAGSIZE;
-        Self::read_end::<L>(message)
+        const HYBRID_L: usize = L + ML_KEM_768_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE;
+        if hybrid {
+            Self::read_end::<HYBRID_L>(message)
+        } else {
+            Self::read_end::<L>(message)
+        }
     }
 
     /// Read the message 3 encrypted key at the beginning of the message
@@ -508,17 +868,24 @@ pub(super) struct HandshakeState {
     n: u64,
     h: [u8; SHA256_SIZE],
     ck: Option<SecretBufferHandle>,
+    // ephemeral ML-KEM keys, only used in the hybrid mode
+    hybrid: bool,
+    ekem: Option<MlKem768SecretKeyHandle>,
+    rekem: Option<MlKem768PublicKey>,
     pub(super) status: Status,
 }
 
 impl HandshakeState {
     /// Create a new HandshakeState with:
-    ///   - a static key
+    ///   - an optional static key
     ///   - an ephemeral key
     ///   - a payload
-    pub(super) fn new(s: X25519SecretKeyHandle, e: X25519SecretKeyHandle) -> HandshakeState {
+    pub(super) fn new(
+        s: Option<X25519SecretKeyHandle>,
+        e: X25519SecretKeyHandle,
+    ) -> HandshakeState {
         HandshakeState {
-            s: Some(s),
+            s,
             e: Some(e),
             k: None,
             re: None,
@@ -526,6 +893,9 @@ impl HandshakeState {
             n: 0,
             h: [0u8; SHA256_SIZE],
             ck: None,
+            hybrid: false,
+            ekem: None,
+            rekem: None,
             status: Initial,
         }
     }
@@ -613,6 +983,26 @@ impl HandshakeState {
         })
     }
 
+    pub(super) fn ekem(&self) -> Result<&MlKem768SecretKeyHandle> {
+        self.ekem.as_ref().ok_or_else(|| {
+            Error::new(
+                Origin::KeyExchange,
+                Kind::Invalid,
+                "key id ekem should have been set",
+            )
+        })
+    }
+
+    pub(super) fn rekem(&self) -> Result<&MlKem768PublicKey> {
+        self.rekem.as_ref().ok_or_else(|| {
+            Error::new(
+                Origin::KeyExchange,
+                Kind::Invalid,
+                "public key id rekem should have been set",
+            )
+        })
+    }
+
     pub(super) fn re(&self) -> Result<&X25519PublicKey> {
         self.re.as_ref().ok_or_else(|| {
             Error::new(
@@ -709,10 +1099,237 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_hybrid_handshake() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(
+            vault.clone(),
+            initiator_static_key,
+            HybridKeyExchange::Required,
+        )
+        .await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(
+            vault.clone(),
+            responder_static_key,
+            HybridKeyExchange::Allowed,
+        )
+        .await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+
+        let message1 = initiator.encode_message1(&[]).await?;
+        assert_eq!(
+            message1.len(),
+            X25519_PUBLIC_KEY_LENGTH + ML_KEM_768_PUBLIC_KEY_LENGTH
+        );
+        responder.decode_message1(&message1).await?;
+        assert!(responder.state.hybrid);
+
+        let message2 = responder.encode_message2(b"responder").await?;
+        assert_eq!(
+            initiator.decode_message2(&message2).await?,
+            b"responder".to_vec()
+        );
+        let message3 = initiator.encode_message3(b"initiator").await?;
+        assert_eq!(
+            responder.decode_message3(&message3).await?,
+            b"initiator".to_vec()
+        );
+
+        initiator.set_final_state(Role::Initiator).await?;
+        responder.set_final_state(Role::Responder).await?;
+        assert_eq!(vault.number_of_ephemeral_ml_kem_secrets(), 0);
+
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+        assert!(initiator_keys.hybrid);
+        assert!(responder_keys.hybrid);
+
+        let nonce = Nonce::new(0).to_aes_gcm_nonce();
+        let mut message = vec![42u8; 1 + AES_GCM_TAGSIZE];
+        vault
+            .aead_encrypt(&initiator_keys.encryption_key, &mut message, &nonce, &[])
+            .await?;
+        let plaintext = vault
+            .aead_decrypt(&responder_keys.decryption_key, &mut message, &nonce, &[])
+            .await?;
+        assert_eq!(plaintext, &[42]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_handshake_required_by_the_responder() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut initiator = Handshake::new(
+            vault.clone(),
+            initiator_static_key,
+            HybridKeyExchange::Disabled,
+        )
+        .await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let mut responder = Handshake::new(
+            vault.clone(),
+            responder_static_key,
+            HybridKeyExchange::Required,
+        )
+        .await?;
+        initiator.initialize().await?;
+        responder.initialize().await?;
+
+        let message1 = initiator.encode_message1(&[]).await?;
+        assert!(responder.decode_message1(&message1).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_static_key_handshake() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let initiator_public_key = vault.get_x25519_public_key(&initiator_static_key).await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let responder_public_key = vault.get_x25519_public_key(&responder_static_key).await?;
+
+        // KK
+        let mut initiator = Handshake::new_with_static_keys(
+            vault.clone(),
+            StaticKeyPattern::KK,
+            Some(initiator_static_key),
+        )
+        .await?;
+        initiator.state.rs = Some(responder_public_key.clone());
+        let mut responder = Handshake::new_with_static_keys(
+            vault.clone(),
+            StaticKeyPattern::KK,
+            Some(responder_static_key.clone()),
+        )
+        .await?;
+        responder.state.rs = Some(initiator_public_key.clone());
+        initiator
+            .initialize_with_static_keys(Some(&initiator_public_key), &responder_public_key)
+            .await?;
+        responder
+            .initialize_with_static_keys(Some(&initiator_public_key), &responder_public_key)
+            .await?;
+        check_static_key_handshake(vault.clone(), initiator, responder).await?;
+
+        // NK
+        let mut initiator =
+            Handshake::new_with_static_keys(vault.clone(), StaticKeyPattern::NK, None).await?;
+        initiator.state.rs = Some(responder_public_key.clone());
+        let mut responder = Handshake::new_with_static_keys(
+            vault.clone(),
+            StaticKeyPattern::NK,
+            Some(responder_static_key),
+        )
+        .await?;
+        initiator
+            .initialize_with_static_keys(None, &responder_public_key)
+            .await?;
+        responder
+            .initialize_with_static_keys(None, &responder_public_key)
+            .await?;
+        check_static_key_handshake(vault.clone(), initiator, responder).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_static_key_handshake_with_a_wrong_initiator_key() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+        let initiator_static_key = vault.generate_static_x25519_secret_key().await?;
+        let initiator_public_key = vault.get_x25519_public_key(&initiator_static_key).await?;
+        let other_static_key = vault.generate_static_x25519_secret_key().await?;
+        let other_public_key = vault.get_x25519_public_key(&other_static_key).await?;
+        let responder_static_key = vault.generate_static_x25519_secret_key().await?;
+        let responder_public_key = vault.get_x25519_public_key(&responder_static_key).await?;
+
+        let mut initiator = Handshake::new_with_static_keys(
+            vault.clone(),
+            StaticKeyPattern::KK,
+            Some(initiator_static_key),
+        )
+        .await?;
+        initiator.state.rs = Some(responder_public_key.clone());
+        initiator
+            .initialize_with_static_keys(Some(&initiator_public_key), &responder_public_key)
+            .await?;
+        let message1 = initiator.encode_static_key_message1(&[]).await?;
+
+        let mut responder = Handshake::new_with_static_keys(
+            vault.clone(),
+            StaticKeyPattern::KK,
+            Some(responder_static_key),
+        )
+        .await?;
+
+        // the message can't be decrypted with another initiator key
+        responder.state.rs = Some(other_public_key.clone());
+        responder
+            .initialize_with_static_keys(Some(&other_public_key), &responder_public_key)
+            .await?;
+        assert!(responder
+            .decode_static_key_message1(&message1)
+            .await
+            .is_err());
+
+        // the handshake can be initialized again to try the right key
+        responder.state.rs = Some(initiator_public_key.clone());
+        responder
+            .initialize_with_static_keys(Some(&initiator_public_key), &responder_public_key)
+            .await?;
+        assert!(responder
+            .decode_static_key_message1(&message1)
+            .await
+            .is_ok());
+
+        Ok(())
+    }
+
     // --------------------
     // TESTS IMPLEMENTATION
     // --------------------
 
+    /// Run a static key handshake and check that both sides get matching keys
+    async fn check_static_key_handshake(
+        vault: Arc<SoftwareVaultForSecureChannels>,
+        mut initiator: Handshake,
+        mut responder: Handshake,
+    ) -> Result<()> {
+        let message1 = initiator.encode_static_key_message1(b"payload 1").await?;
+        let payload1 = responder.decode_static_key_message1(&message1).await?;
+        assert_eq!(payload1, b"payload 1");
+
+        let message2 = responder.encode_static_key_message2(b"payload 2").await?;
+        let payload2 = initiator.decode_static_key_message2(&message2).await?;
+        assert_eq!(payload2, b"payload 2");
+        assert_eq!(initiator.state.h, responder.state.h);
+
+        initiator.set_final_state(Role::Initiator).await?;
+        responder.set_final_state(Role::Responder).await?;
+        let initiator_keys = initiator.get_handshake_keys().unwrap();
+        let responder_keys = responder.get_handshake_keys().unwrap();
+
+        let nonce = Nonce::new(0).to_aes_gcm_nonce();
+        let mut message = b"hello".to_vec();
+        message.extend_from_slice(&[0u8; AES_GCM_TAGSIZE]);
+        vault
+            .aead_encrypt(&initiator_keys.encryption_key, &mut message, &nonce, &[])
+            .await?;
+        let plaintext = vault
+            .aead_decrypt(&responder_keys.decryption_key, &mut message, &nonce, &[])
+            .await?;
+        assert_eq!(plaintext.to_vec(), b"hello".to_vec());
+
+        Ok(())
+    }
+
     struct HandshakeMessages {
         initiator_static_key: X25519SecretKey,
         initiator_ephemeral_key: X25519SecretKey,
@@ -801,7 +1418,8 @@ mod tests {
             Ok(Handshake {
                 vault,
                 protocol_name,
-                state: HandshakeState::new(static_key, ephemeral_key),
+                hybrid_key_exchange: HybridKeyExchange::Disabled,
+                state: HandshakeState::new(Some(static_key), ephemeral_key),
             })
         }
 
@@ -819,7 +1437,8 @@ mod tests {
             Ok(Handshake {
                 vault,
                 protocol_name,
-                state: HandshakeState::new(static_key, ephemeral_key),
+                hybrid_key_exchange: HybridKeyExchange::Disabled,
+                state: HandshakeState::new(Some(static_key), ephemeral_key),
             })
         }
     }
@@ -6,11 +6,12 @@ use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Result};
-use ockam_vault::{AeadSecretKeyHandle, X25519PublicKey};
+use ockam_vault::{AeadSecretKeyHandle, SecretBufferHandle, X25519PublicKey};
 
 use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, PurposeKeyAttestation, PurposePublicKey,
 };
+use crate::secure_channel::HybridKeyExchange;
 use crate::{
     CredentialRetriever, Identifier, Identities, IdentityError, SecureChannelTrustInfo, TrustPolicy,
 };
@@ -52,6 +53,10 @@ pub(crate) enum Status {
 pub(crate) struct HandshakeKeys {
     pub(super) encryption_key: AeadSecretKeyHandle,
     pub(super) decryption_key: AeadSecretKeyHandle,
+    /// Final chaining key of the handshake, mixed into the keys of the re-handshakes
+    pub(super) chaining_key: SecretBufferHandle,
+    /// True if an ML-KEM-768 shared secret was mixed into the keys
+    pub(super) hybrid: bool,
 }
 
 /// The end result of a handshake with identity/credentials exchange is
@@ -63,6 +68,14 @@ pub(crate) struct HandshakeResults {
     pub(super) presented_credential: Option<CredentialAndPurposeKey>,
 }
 
+/// Options of the handshake, shared by the initiator and the responder state machines
+pub(crate) struct HandshakeOptions {
+    pub(crate) credential_retriever: Option<Arc<dyn CredentialRetriever>>,
+    pub(crate) trust_policy: Arc<dyn TrustPolicy>,
+    pub(crate) authority: Option<Identifier>,
+    pub(crate) hybrid_key_exchange: HybridKeyExchange,
+}
+
 /// This struct implements functions common to both initiator and the responder state machines
 pub(crate) struct CommonStateMachine {
     pub(super) identities: Arc<Identities>,
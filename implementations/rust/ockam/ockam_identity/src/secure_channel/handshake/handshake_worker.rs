@@ -4,8 +4,8 @@ use ockam_core::compat::boxed::Box;
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    AddressMetadata, AllowAll, Any, DenyAll, Error, Mailbox, Mailboxes, NeutralMessage,
-    OutgoingAccessControl, Route, Routed, SecureChannelMetadata,
+    AddressMetadata, AllowAll, AllowSourceAddress, Any, DenyAll, Error, Mailbox, Mailboxes,
+    NeutralMessage, OutgoingAccessControl, Route, Routed, SecureChannelMetadata,
 };
 use ockam_core::{Result, Worker};
 use ockam_node::callback::CallbackSender;
@@ -25,11 +25,12 @@ use crate::secure_channel::handshake::handshake_state_machine::Event::{
     Initialize, ReceivedMessage,
 };
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, HandshakeResults, StateMachine,
+    Action, HandshakeOptions, HandshakeResults, StateMachine,
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
+use crate::secure_channel::handshake::rehandshake::Rehandshake;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
-use crate::secure_channel::{Addresses, Role};
+use crate::secure_channel::{Addresses, HybridKeyExchange, KeyRenewalOptions, Role};
 use crate::{
     ChangeHistoryRepository, CredentialRetriever, IdentityError, PersistedSecureChannel,
     SecureChannelPurposeKey, SecureChannelRegistryEntry, SecureChannelRepository, SecureChannels,
@@ -46,6 +47,7 @@ pub(crate) struct HandshakeWorker {
     addresses: Addresses,
     role: Role,
     key_exchange_only: bool,
+    key_renewal: KeyRenewalOptions,
     remote_route: Option<Route>,
     decryptor_handler: Option<DecryptorHandler>,
 
@@ -57,6 +59,9 @@ pub(crate) struct HandshakeWorker {
     secure_channel_repository: Option<Arc<dyn SecureChannelRepository>>,
 
     shared_state: SecureChannelSharedState,
+
+    #[cfg(feature = "std")]
+    handshake_started_at: std::time::Instant,
 }
 
 #[ockam_core::worker]
@@ -111,7 +116,10 @@ impl Worker for HandshakeWorker {
         if self.decryptor_handler.is_some() {
             self.handle_decrypt(context, message).await
         } else {
-            self.handle_handshake(context, message).await
+            let result = self.handle_handshake(context, message).await;
+            #[cfg(feature = "std")]
+            self.record_handshake_metrics(context, &result);
+            result
         }
     }
 
@@ -146,12 +154,20 @@ impl HandshakeWorker {
         timeout: Option<Duration>,
         role: Role,
         key_exchange_only: bool,
+        key_renewal: KeyRenewalOptions,
+        hybrid_key_exchange: HybridKeyExchange,
         secure_channel_repository: Option<Arc<dyn SecureChannelRepository>>,
         encryptor_remote_route: Arc<RwLock<RemoteRoute>>,
     ) -> Result<Option<Identifier>> {
         let vault = secure_channels.identities.vault().secure_channel_vault;
         let identities = secure_channels.identities();
 
+        let options = HandshakeOptions {
+            credential_retriever: credential_retriever.clone(),
+            trust_policy,
+            authority: authority.clone(),
+            hybrid_key_exchange,
+        };
         let state_machine: Box<dyn StateMachine> = if role.is_initiator() {
             Box::new(
                 InitiatorStateMachine::new(
@@ -159,9 +175,7 @@ impl HandshakeWorker {
                     identities.clone(),
                     my_identifier.clone(),
                     purpose_key,
-                    credential_retriever.clone(),
-                    trust_policy,
-                    authority.clone(),
+                    options,
                 )
                 .await?,
             )
@@ -172,14 +186,54 @@ impl HandshakeWorker {
                     identities.clone(),
                     my_identifier.clone(),
                     purpose_key,
-                    credential_retriever.clone(),
-                    trust_policy,
-                    authority.clone(),
+                    options,
                 )
                 .await?,
             )
         };
 
+        Self::start(
+            context,
+            secure_channels,
+            state_machine,
+            addresses,
+            my_identifier,
+            decryptor_outgoing_access_control,
+            credential_retriever,
+            authority,
+            remote_route,
+            timeout,
+            role,
+            key_exchange_only,
+            key_renewal,
+            secure_channel_repository,
+            encryptor_remote_route,
+        )
+        .await
+    }
+
+    /// Start a HandshakeWorker running a given state machine.
+    /// On the initiator side, wait for the end of the handshake and return the other party identifier
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn start(
+        context: &Context,
+        secure_channels: Arc<SecureChannels>,
+        state_machine: Box<dyn StateMachine>,
+        addresses: Addresses,
+        my_identifier: Identifier,
+        decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        credential_retriever: Option<Arc<dyn CredentialRetriever>>,
+        authority: Option<Identifier>,
+        remote_route: Option<Route>,
+        timeout: Option<Duration>,
+        role: Role,
+        key_exchange_only: bool,
+        key_renewal: KeyRenewalOptions,
+        secure_channel_repository: Option<Arc<dyn SecureChannelRepository>>,
+        encryptor_remote_route: Arc<RwLock<RemoteRoute>>,
+    ) -> Result<Option<Identifier>> {
+        let change_history_repository = secure_channels.identities().change_history_repository();
+
         let (callback_waiter, callback_sender) = if role.is_initiator() {
             let callback = ockam_node::callback::new_callback();
             (Some(callback.0), Some(callback.1))
@@ -187,9 +241,19 @@ impl HandshakeWorker {
             (None, None)
         };
 
+        let rehandshake = if key_exchange_only {
+            None
+        } else {
+            Some(Rehandshake::new(
+                secure_channels.identities.vault().secure_channel_vault,
+                role,
+                key_renewal.rehandshake_interval,
+            ))
+        };
         let shared_state = SecureChannelSharedState {
             should_send_close: Arc::new(AtomicBool::new(true)),
             remote_route: encryptor_remote_route,
+            rehandshake,
         };
         let worker = Self {
             secure_channels,
@@ -198,14 +262,17 @@ impl HandshakeWorker {
             my_identifier: my_identifier.clone(),
             role,
             key_exchange_only,
+            key_renewal,
             remote_route: remote_route.clone(),
             addresses: addresses.clone(),
             decryptor_handler: None,
             credential_retriever,
             authority,
-            change_history_repository: identities.change_history_repository(),
+            change_history_repository,
             secure_channel_repository,
             shared_state,
+            #[cfg(feature = "std")]
+            handshake_started_at: std::time::Instant::now(),
         };
 
         WorkerBuilder::new(worker)
@@ -276,19 +343,20 @@ impl HandshakeWorker {
         let return_route = message.return_route;
         let payload = message.payload;
 
-        if let SendMessage(send_message) = self
+        let action = self
             .state_machine
             .as_mut()
             .ok_or(IdentityError::HandshakeInternalError)?
             .on_event(ReceivedMessage(payload))
-            .await?
-        {
-            // set the remote route by taking the most up to date message return route
-            // In the case of the initiator the first return route mentions the secure channel listener
-            // address so we need to wait for the return route corresponding to the remote handshake worker
-            // when it has been spawned
-            self.remote_route = Some(return_route);
+            .await?;
+
+        // set the remote route by taking the most up to date message return route
+        // In the case of the initiator the first return route mentions the secure channel listener
+        // address so we need to wait for the return route corresponding to the remote handshake worker
+        // when it has been spawned
+        self.remote_route = Some(return_route);
 
+        if let SendMessage(send_message) = action {
             context
                 .send_from_address(
                     self.remote_route()?,
@@ -316,6 +384,17 @@ impl HandshakeWorker {
         Ok(())
     }
 
+    /// Count the failed handshakes, and the successful ones once the decryptor is created
+    #[cfg(feature = "std")]
+    fn record_handshake_metrics(&self, context: &Context, result: &Result<()>) {
+        let metrics = context.metrics();
+        if result.is_err() {
+            metrics.handshake_failed(self.role.str());
+        } else if self.decryptor_handler.is_some() {
+            metrics.handshake_succeeded(self.role.str(), self.handshake_started_at.elapsed());
+        }
+    }
+
     /// This function is instrumented as if there was a DecryptorWorker type for a better
     /// readability of traces (Because there's a corresponding EncryptorWorker::handle_message)
     ///
@@ -398,6 +477,20 @@ impl HandshakeWorker {
     ) -> Result<DecryptorHandler> {
         let their_identifier = handshake_results.their_identifier.clone();
 
+        // re-handshakes keep using ML-KEM-768 if it was used by the initial handshake,
+        // and derive their keys from its chaining key
+        let chaining_key = handshake_results.handshake_keys.chaining_key.clone();
+        if let Some(rehandshake) = &self.shared_state.rehandshake {
+            rehandshake.initialize(handshake_results.handshake_keys.hybrid, chaining_key);
+        } else {
+            self.secure_channels
+                .identities
+                .vault()
+                .secure_channel_vault
+                .delete_secret_buffer(chaining_key)
+                .await?;
+        }
+
         // create a decryptor to delegate the processing of all messages after the handshake
         let decryptor = DecryptorHandler::new(
             self.secure_channels.identities.clone(),
@@ -413,11 +506,15 @@ impl HandshakeWorker {
 
         // create a separate encryptor worker which will be started independently
         {
-            let (rekeying, credential_retriever) = if self.key_exchange_only {
+            let (rekeying, rekey_interval, credential_retriever) = if self.key_exchange_only {
                 // only the initial exchange is needed for key exchange only
-                (false, None)
+                (false, None, None)
             } else {
-                (true, self.credential_retriever.clone())
+                (
+                    true,
+                    self.key_renewal.rekey_interval,
+                    self.credential_retriever.clone(),
+                )
             };
 
             self.shared_state.remote_route.write().unwrap().route = self.remote_route()?;
@@ -430,7 +527,8 @@ impl HandshakeWorker {
                     0.into(),
                     self.secure_channels.identities.vault().secure_channel_vault,
                     rekeying,
-                ),
+                )
+                .with_rekey_interval(rekey_interval),
                 self.my_identifier.clone(),
                 self.change_history_repository.clone(),
                 credential_retriever,
@@ -462,10 +560,17 @@ impl HandshakeWorker {
                 Arc::new(DenyAll),
             );
 
+            let rehandshake_mailbox = Mailbox::new(
+                self.addresses.encryptor_rehandshake.clone(),
+                None,
+                Arc::new(AllowSourceAddress(self.addresses.decryptor_remote.clone())),
+                Arc::new(DenyAll),
+            );
+
             WorkerBuilder::new(encryptor)
                 .with_mailboxes(Mailboxes::new(
                     main_mailbox,
-                    vec![api_mailbox, internal_mailbox],
+                    vec![api_mailbox, internal_mailbox, rehandshake_mailbox],
                 ))
                 .start(context)?;
         }
@@ -572,6 +677,7 @@ impl HandshakeWorker {
             addresses,
             role,
             key_exchange_only,
+            key_renewal: KeyRenewalOptions::default(),
             remote_route,
             decryptor_handler,
             authority,
@@ -579,6 +685,8 @@ impl HandshakeWorker {
             credential_retriever,
             secure_channel_repository,
             shared_state,
+            #[cfg(feature = "std")]
+            handshake_started_at: std::time::Instant::now(),
         }
     }
 }
@@ -14,10 +14,10 @@ use crate::models::Identifier;
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeOptions, HandshakeResults,
+    IdentityAndCredentials, StateMachine, Status,
 };
-use crate::{CredentialRetriever, Identities, Role, SecureChannelPurposeKey, TrustPolicy};
+use crate::{Identities, Role, SecureChannelPurposeKey};
 
 /// Implementation of a state machine for the key exchange on the initiator side
 #[async_trait]
@@ -102,22 +102,25 @@ impl InitiatorStateMachine {
         identities: Arc<Identities>,
         identifier: Identifier,
         purpose_key: SecureChannelPurposeKey,
-        credential_retriever: Option<Arc<dyn CredentialRetriever>>,
-        trust_policy: Arc<dyn TrustPolicy>,
-        authority: Option<Identifier>,
+        options: HandshakeOptions,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
             identifier,
             purpose_key.attestation().clone(),
-            credential_retriever,
-            trust_policy,
-            authority,
+            options.credential_retriever,
+            options.trust_policy,
+            options.authority,
         );
 
         Ok(InitiatorStateMachine {
             common,
-            handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
+            handshake: Handshake::new(
+                vault,
+                purpose_key.key().clone(),
+                options.hybrid_key_exchange,
+            )
+            .await?,
         })
     }
 }
@@ -10,4 +10,6 @@ pub(crate) mod handshake;
 pub(crate) mod handshake_state_machine;
 pub(crate) mod handshake_worker;
 mod initiator_state_machine;
+pub(crate) mod rehandshake;
 mod responder_state_machine;
+pub(crate) mod static_key_state_machine;
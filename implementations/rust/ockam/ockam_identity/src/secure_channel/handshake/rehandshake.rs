@@ -0,0 +1,719 @@
+use core::fmt;
+use core::fmt::Formatter;
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Result;
+use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, MlKem768SecretKeyHandle, SecretBufferHandle,
+    VaultForSecureChannels, X25519PublicKey, X25519SecretKeyHandle,
+};
+use tracing::{debug, warn};
+
+use crate::models::TimestampInSeconds;
+use crate::secure_channel::handshake::error::XXError;
+use crate::secure_channel::Role;
+use crate::utils::now;
+use crate::{RehandshakeRequestMessage, RehandshakeResponseMessage};
+
+/// In-band re-handshake of an established secure channel.
+///
+/// The initiator of the channel periodically sends a fresh X25519 ephemeral public key,
+/// the responder answers with its own fresh ephemeral public key, and both sides derive new
+/// encryption and decryption keys, as well as a new chaining key, from the current chaining key
+/// and the Diffie-Hellman secret of the two ephemeral keys. The first chaining key is the final
+/// chaining key of the initial handshake.
+/// Since the ephemeral keys are deleted as soon as the new keys are derived, a compromise of the
+/// current keys doesn't expose the traffic protected by the keys of a previous re-handshake.
+///
+/// The public keys are exchanged inside the channel, so they are authenticated by the current
+/// channel keys. The initiator starts using its new encryption key at the beginning of the next
+/// rekeying interval, once it has received the response. The responder only does so after having
+/// received a message encrypted with the new key, which confirms that the response arrived.
+/// Until then, both sides keep their current keys, so a lost response doesn't break the channel,
+/// and the initiator starts a new re-handshake after another interval.
+///
+/// If the channel was established with a hybrid key exchange, the initiator also sends a fresh
+/// ML-KEM-768 public key, and the responder answers with the ciphertext of a shared secret
+/// which is mixed into the new keys.
+///
+/// This state is shared between the encryptor and the decryptor of the same channel.
+#[derive(Clone)]
+pub(crate) struct Rehandshake {
+    vault: Arc<dyn VaultForSecureChannels>,
+    role: Role,
+    /// Interval between two re-handshakes started by the initiator
+    interval: Option<Duration>,
+    state: Arc<Mutex<RehandshakeState>>,
+}
+
+#[derive(Default)]
+struct RehandshakeState {
+    /// True if the channel was established with a hybrid key exchange
+    hybrid: bool,
+    /// Ephemeral key of the request in flight (initiator only)
+    ephemeral_key: Option<(X25519SecretKeyHandle, X25519PublicKey)>,
+    /// Ephemeral ML-KEM key of the request in flight, in the hybrid mode (initiator only)
+    ml_kem_key: Option<MlKem768SecretKeyHandle>,
+    /// When the last request was sent, or when the channel was created (initiator only)
+    last_started_at: Option<TimestampInSeconds>,
+    /// Chaining key shared by both sides, mixed into the keys of the next re-handshake
+    chaining_key: Option<SecretBufferHandle>,
+    /// Response to send to the initiator (responder only)
+    response: Option<RehandshakeResponseMessage>,
+    /// New encryption key and chaining key of an answered request, which are used once the
+    /// initiator confirms that it received the response (responder only)
+    unconfirmed_keys: Option<(AeadSecretKeyHandle, SecretBufferHandle)>,
+    /// New encryption key, ready to be used
+    encryption_key: Option<AeadSecretKeyHandle>,
+}
+
+impl fmt::Debug for Rehandshake {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rehandshake")
+            .field("role", &self.role)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl Rehandshake {
+    /// Create the re-handshake state of a newly established channel
+    pub(crate) fn new(
+        vault: Arc<dyn VaultForSecureChannels>,
+        role: Role,
+        interval: Option<Duration>,
+    ) -> Self {
+        let state = RehandshakeState {
+            last_started_at: now().ok(),
+            ..Default::default()
+        };
+        Self {
+            vault,
+            role,
+            interval,
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Set the results of the initial handshake: the re-handshakes use ML-KEM-768 in addition to
+    /// X25519 if the initial handshake did, and start from its final chaining key
+    pub(crate) fn initialize(&self, hybrid: bool, chaining_key: SecretBufferHandle) {
+        let mut state = self.state.lock().unwrap();
+        state.hybrid = hybrid;
+        state.chaining_key = Some(chaining_key);
+    }
+
+    /// If a re-handshake is due, generate new ephemeral keys and return the request
+    /// that must be sent to the responder
+    pub(crate) async fn start_if_due(&self) -> Result<Option<RehandshakeRequestMessage>> {
+        let Some(interval) = self.interval else {
+            return Ok(None);
+        };
+        if !self.role.is_initiator() {
+            return Ok(None);
+        }
+
+        let now = now()?;
+        let hybrid = {
+            let state = self.state.lock().unwrap();
+            if let Some(last_started_at) = state.last_started_at {
+                // a request which is still in flight is only replaced if it was lost,
+                // which is assumed after another interval
+                if now < last_started_at + interval {
+                    return Ok(None);
+                }
+            }
+            state.hybrid
+        };
+
+        let ephemeral_key = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&ephemeral_key).await?;
+        let (ml_kem_key, ml_kem_public_key) = if hybrid {
+            let ml_kem_key = self.vault.generate_ephemeral_ml_kem_secret_key().await?;
+            let ml_kem_public_key = self.vault.get_ml_kem_public_key(&ml_kem_key).await?;
+            (Some(ml_kem_key), Some(ml_kem_public_key))
+        } else {
+            (None, None)
+        };
+
+        let (previous, previous_ml_kem_key) = {
+            let mut state = self.state.lock().unwrap();
+            state.last_started_at = Some(now);
+            (
+                state
+                    .ephemeral_key
+                    .replace((ephemeral_key, public_key.clone())),
+                core::mem::replace(&mut state.ml_kem_key, ml_kem_key),
+            )
+        };
+        if let Some((previous, _)) = previous {
+            debug!("the previous re-handshake request was not answered, starting a new one");
+            self.vault
+                .delete_ephemeral_x25519_secret_key(previous)
+                .await?;
+        }
+        if let Some(previous) = previous_ml_kem_key {
+            self.vault
+                .delete_ephemeral_ml_kem_secret_key(previous)
+                .await?;
+        }
+
+        Ok(Some(RehandshakeRequestMessage {
+            ephemeral_public_key: public_key,
+            ml_kem_public_key,
+        }))
+    }
+
+    /// Answer a re-handshake request on the responder side.
+    ///
+    /// The response is kept until the encryptor sends it, and the new decryption key is returned.
+    /// The new encryption key and chaining key are kept until the initiator confirms that it
+    /// received the response (see [`Rehandshake::confirm`]). The request is ignored if it doesn't
+    /// use the same key exchange as the initial handshake.
+    pub(crate) async fn respond(
+        &self,
+        request: &RehandshakeRequestMessage,
+    ) -> Result<Option<AeadSecretKeyHandle>> {
+        let (hybrid, chaining_key) = {
+            let state = self.state.lock().unwrap();
+            (state.hybrid, state.chaining_key.clone())
+        };
+        let chaining_key = chaining_key.ok_or(XXError::InternalVaultError)?;
+        // the initiator is not allowed to downgrade a hybrid channel
+        let (ml_kem_ciphertext, ml_kem_shared_secret) = match &request.ml_kem_public_key {
+            Some(ml_kem_public_key) if hybrid => {
+                let (ciphertext, shared_secret) =
+                    self.vault.ml_kem_encapsulate(ml_kem_public_key).await?;
+                (Some(ciphertext), Some(shared_secret))
+            }
+            None if !hybrid => (None, None),
+            _ => {
+                warn!("the re-handshake request doesn't use the key exchange of the channel");
+                return Ok(None);
+            }
+        };
+
+        let ephemeral_key = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&ephemeral_key).await?;
+        let (encryption_key, decryption_key, new_chaining_key) = self
+            .derive_keys(
+                &chaining_key,
+                ephemeral_key,
+                &request.ephemeral_public_key,
+                ml_kem_shared_secret,
+            )
+            .await?;
+
+        let response = RehandshakeResponseMessage {
+            request_ephemeral_public_key: request.ephemeral_public_key.clone(),
+            ephemeral_public_key: public_key,
+            ml_kem_ciphertext,
+        };
+        // a new request means that the response to the previous one was lost
+        let previous = {
+            let mut state = self.state.lock().unwrap();
+            state.response = Some(response);
+            state
+                .unconfirmed_keys
+                .replace((encryption_key, new_chaining_key))
+        };
+        if let Some((previous_key, previous_chaining_key)) = previous {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+            self.vault
+                .delete_secret_buffer(previous_chaining_key)
+                .await?;
+        }
+
+        Ok(Some(decryption_key))
+    }
+
+    /// Start using the keys of the last answered request on the responder side, once a message
+    /// encrypted with the new key of the initiator was received
+    pub(crate) async fn confirm(&self) -> Result<()> {
+        let (previous_key, previous_chaining_key) = {
+            let mut state = self.state.lock().unwrap();
+            let Some((encryption_key, chaining_key)) = state.unconfirmed_keys.take() else {
+                return Ok(());
+            };
+            (
+                state.encryption_key.replace(encryption_key),
+                state.chaining_key.replace(chaining_key),
+            )
+        };
+        if let Some(previous_key) = previous_key {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        if let Some(previous_chaining_key) = previous_chaining_key {
+            self.vault
+                .delete_secret_buffer(previous_chaining_key)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Complete a re-handshake on the initiator side.
+    ///
+    /// Return the new decryption key, or `None` if the response doesn't correspond to the
+    /// request in flight. The new encryption key is kept until the encryptor picks it up.
+    pub(crate) async fn complete(
+        &self,
+        response: &RehandshakeResponseMessage,
+    ) -> Result<Option<AeadSecretKeyHandle>> {
+        let (ephemeral_key, ml_kem_key, chaining_key) = {
+            let mut state = self.state.lock().unwrap();
+            let is_in_flight = matches!(
+                &state.ephemeral_key,
+                Some((_, public_key)) if public_key == &response.request_ephemeral_public_key
+            );
+            if is_in_flight {
+                (
+                    state.ephemeral_key.take().map(|(key, _)| key),
+                    state.ml_kem_key.take(),
+                    state.chaining_key.clone(),
+                )
+            } else {
+                (None, None, None)
+            }
+        };
+        let Some(ephemeral_key) = ephemeral_key else {
+            return Ok(None);
+        };
+        let chaining_key = chaining_key.ok_or(XXError::InternalVaultError)?;
+
+        let ml_kem_shared_secret = match (ml_kem_key, &response.ml_kem_ciphertext) {
+            (None, None) => None,
+            (Some(ml_kem_key), Some(ciphertext)) => {
+                let shared_secret = self.vault.ml_kem_decapsulate(&ml_kem_key, ciphertext).await;
+                self.vault
+                    .delete_ephemeral_ml_kem_secret_key(ml_kem_key)
+                    .await?;
+                Some(shared_secret?)
+            }
+            (ml_kem_key, _) => {
+                warn!("the re-handshake response doesn't use the key exchange of the channel");
+                if let Some(ml_kem_key) = ml_kem_key {
+                    self.vault
+                        .delete_ephemeral_ml_kem_secret_key(ml_kem_key)
+                        .await?;
+                }
+                self.vault
+                    .delete_ephemeral_x25519_secret_key(ephemeral_key)
+                    .await?;
+                return Ok(None);
+            }
+        };
+
+        let (encryption_key, decryption_key, new_chaining_key) = self
+            .derive_keys(
+                &chaining_key,
+                ephemeral_key,
+                &response.ephemeral_public_key,
+                ml_kem_shared_secret,
+            )
+            .await?;
+
+        // the responder switches to the new chaining key when it receives
+        // the first message encrypted with the new encryption key
+        let (previous_key, previous_chaining_key) = {
+            let mut state = self.state.lock().unwrap();
+            (
+                state.encryption_key.replace(encryption_key),
+                state.chaining_key.replace(new_chaining_key),
+            )
+        };
+        if let Some(previous_key) = previous_key {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        if let Some(previous_chaining_key) = previous_chaining_key {
+            self.vault
+                .delete_secret_buffer(previous_chaining_key)
+                .await?;
+        }
+
+        Ok(Some(decryption_key))
+    }
+
+    /// Return the response to send to the initiator
+    pub(crate) fn take_response(&self) -> Option<RehandshakeResponseMessage> {
+        self.state.lock().unwrap().response.take()
+    }
+
+    /// Return the new encryption key of a completed re-handshake
+    pub(crate) fn take_encryption_key(&self) -> Option<AeadSecretKeyHandle> {
+        self.state.lock().unwrap().encryption_key.take()
+    }
+
+    /// Delete the chaining key and the keys of a re-handshake which didn't complete
+    pub(crate) async fn shutdown(&self) -> Result<()> {
+        let (ephemeral_key, ml_kem_key, chaining_key, unconfirmed_keys, encryption_key) = {
+            let mut state = self.state.lock().unwrap();
+            (
+                state.ephemeral_key.take(),
+                state.ml_kem_key.take(),
+                state.chaining_key.take(),
+                state.unconfirmed_keys.take(),
+                state.encryption_key.take(),
+            )
+        };
+
+        if let Some((ephemeral_key, _)) = ephemeral_key {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(ephemeral_key)
+                .await?;
+        }
+        if let Some(ml_kem_key) = ml_kem_key {
+            self.vault
+                .delete_ephemeral_ml_kem_secret_key(ml_kem_key)
+                .await?;
+        }
+        let (unconfirmed_key, unconfirmed_chaining_key) = unconfirmed_keys.unzip();
+        for key in unconfirmed_key.into_iter().chain(encryption_key) {
+            self.vault.delete_aead_secret_key(key).await?;
+        }
+        for chaining_key in chaining_key.into_iter().chain(unconfirmed_chaining_key) {
+            self.vault.delete_secret_buffer(chaining_key).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Compute the new (encryption, decryption, chaining) keys from the current chaining key,
+    /// our ephemeral key, their ephemeral public key and the ML-KEM shared secret if any,
+    /// then delete our ephemeral key
+    async fn derive_keys(
+        &self,
+        chaining_key: &SecretBufferHandle,
+        ephemeral_key: X25519SecretKeyHandle,
+        their_public_key: &X25519PublicKey,
+        ml_kem_shared_secret: Option<SecretBufferHandle>,
+    ) -> Result<(AeadSecretKeyHandle, AeadSecretKeyHandle, SecretBufferHandle)> {
+        let dh = self
+            .vault
+            .x25519_ecdh(&ephemeral_key, their_public_key)
+            .await;
+        self.vault
+            .delete_ephemeral_x25519_secret_key(ephemeral_key)
+            .await?;
+        let dh = dh?;
+
+        // ck, k = HKDF(ck, DH, 2), then the keys are derived from ck and the ML-KEM secret
+        let (salt, input_key_material) = if let Some(ml_kem_shared_secret) = ml_kem_shared_secret {
+            let hkdf_output = self
+                .vault
+                .hkdf(chaining_key, Some(&dh), HKDFNumberOfOutputs::Two)
+                .await;
+            self.vault.delete_secret_buffer(dh).await?;
+
+            let [ck, unused]: [SecretBufferHandle; 2] = hkdf_output?
+                .0
+                 .0
+                .try_into()
+                .map_err(|_| XXError::InternalVaultError)?;
+            self.vault.delete_secret_buffer(unused).await?;
+
+            (Some(ck), ml_kem_shared_secret)
+        } else {
+            (None, dh)
+        };
+
+        // ck, k1, k2 = HKDF(ck, input key material, 3)
+        let hkdf_output = self
+            .vault
+            .hkdf(
+                salt.as_ref().unwrap_or(chaining_key),
+                Some(&input_key_material),
+                HKDFNumberOfOutputs::Three,
+            )
+            .await;
+        if let Some(salt) = salt {
+            self.vault.delete_secret_buffer(salt).await?;
+        }
+        self.vault.delete_secret_buffer(input_key_material).await?;
+
+        let [ck, k1, k2]: [SecretBufferHandle; 3] = hkdf_output?
+            .0
+             .0
+            .try_into()
+            .map_err(|_| XXError::InternalVaultError)?;
+
+        let k1 = self.vault.convert_secret_buffer_to_aead_key(k1).await?;
+        let k2 = self.vault.convert_secret_buffer_to_aead_key(k2).await?;
+
+        // same convention as the initial handshake
+        Ok(if self.role.is_initiator() {
+            (k2, k1, ck)
+        } else {
+            (k1, k2, ck)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure_channel::encryptor::Encryptor;
+    use crate::secure_channel::Decryptor;
+    use ockam_vault::SoftwareVaultForSecureChannels;
+
+    #[tokio::test]
+    async fn test_rehandshake_derives_the_same_keys_on_both_sides() -> Result<()> {
+        check_rehandshake(false).await
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_rehandshake_derives_the_same_keys_on_both_sides() -> Result<()> {
+        check_rehandshake(true).await
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_rehandshake_cannot_be_downgraded() -> Result<()> {
+        let (initiator_vault, initiator, responder_vault, responder) =
+            create_rehandshakes().await?;
+        initiator.initialize(false, import_chaining_key(&initiator_vault).await?);
+        responder.initialize(true, import_chaining_key(&responder_vault).await?);
+
+        let request = initiator.start_if_due().await?.unwrap();
+        assert!(request.ml_kem_public_key.is_none());
+        assert!(responder.respond(&request).await?.is_none());
+        assert!(responder.take_response().is_none());
+        assert_eq!(responder_vault.number_of_ephemeral_x25519_secrets(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rehandshake_with_a_lost_response() -> Result<()> {
+        let (initiator_vault, initiator, responder_vault, responder) =
+            create_rehandshakes().await?;
+        initiator.initialize(false, import_chaining_key(&initiator_vault).await?);
+        responder.initialize(false, import_chaining_key(&responder_vault).await?);
+
+        // the current channel keys
+        let mut encryptor = Encryptor::new(
+            import_channel_key(&initiator_vault).await?,
+            0.into(),
+            initiator_vault.clone(),
+            true,
+        );
+        let mut decryptor = Decryptor::new(
+            import_channel_key(&responder_vault).await?,
+            responder_vault.clone(),
+        );
+
+        // the response to the first request is lost
+        let request = initiator.start_if_due().await?.unwrap();
+        let lost_decryption_key = responder.respond(&request).await?.unwrap();
+        decryptor.set_next_key(lost_decryption_key).await?;
+        assert!(responder.take_response().is_some());
+
+        // the responder keeps its current encryption key
+        assert!(responder.take_encryption_key().is_none());
+
+        // the initiator starts a new re-handshake, which is answered with
+        // keys derived from the same chaining key on both sides
+        let request = initiator.start_if_due().await?.unwrap();
+        let responder_decryption_key = responder.respond(&request).await?.unwrap();
+        decryptor.set_next_key(responder_decryption_key).await?;
+        let response = responder.take_response().unwrap();
+        let initiator_decryption_key = initiator.complete(&response).await?.unwrap();
+        assert!(responder.take_encryption_key().is_none());
+
+        // messages encrypted with the current key don't confirm the re-handshake,
+        // but the first message encrypted with the new key does
+        let mut ciphertext = vec![0u8; 1 + 24];
+        ciphertext[8] = 41;
+        encryptor.encrypt(&mut ciphertext).await?;
+        assert_eq!(decryptor.decrypt(&mut ciphertext).await?.0, &[41]);
+        assert!(!decryptor.take_switched_to_next_key());
+        encryptor
+            .set_next_key(initiator.take_encryption_key().unwrap())
+            .await?;
+        let mut ciphertext = vec![0u8; 1 + 24];
+        ciphertext[8] = 42;
+        encryptor.encrypt(&mut ciphertext).await?;
+        assert_eq!(decryptor.decrypt(&mut ciphertext).await?.0, &[42]);
+        assert!(decryptor.take_switched_to_next_key());
+        responder.confirm().await?;
+
+        let responder_encryption_key = responder.take_encryption_key().unwrap();
+        let mut decryptor = Decryptor::new(initiator_decryption_key, initiator_vault.clone());
+        check_encryption(
+            &responder_vault,
+            responder_encryption_key,
+            &mut decryptor,
+            43,
+        )
+        .await?;
+
+        // both sides use the same chaining key for the next re-handshake
+        let (initiator_keys, responder_keys) = run_rehandshake(&initiator, &responder).await?;
+        check_keys(
+            &initiator_vault,
+            initiator_keys,
+            &responder_vault,
+            responder_keys,
+        )
+        .await?;
+
+        initiator.shutdown().await?;
+        responder.shutdown().await?;
+        assert_eq!(initiator_vault.number_of_ephemeral_buffer_secrets(), 0);
+        assert_eq!(responder_vault.number_of_ephemeral_buffer_secrets(), 0);
+
+        Ok(())
+    }
+
+    async fn check_rehandshake(hybrid: bool) -> Result<()> {
+        let (initiator_vault, initiator, responder_vault, responder) =
+            create_rehandshakes().await?;
+        initiator.initialize(hybrid, import_chaining_key(&initiator_vault).await?);
+        responder.initialize(hybrid, import_chaining_key(&responder_vault).await?);
+
+        assert!(responder.start_if_due().await?.is_none());
+
+        let request = initiator.start_if_due().await?.unwrap();
+        assert_eq!(request.ml_kem_public_key.is_some(), hybrid);
+        let responder_decryption_key = responder.respond(&request).await?.unwrap();
+        let response = responder.take_response().unwrap();
+        assert_eq!(response.ml_kem_ciphertext.is_some(), hybrid);
+
+        let unknown_request = responder_vault
+            .generate_ephemeral_x25519_secret_key()
+            .await?;
+        let unknown_request = responder_vault
+            .get_x25519_public_key(&unknown_request)
+            .await?;
+        let outdated_response = RehandshakeResponseMessage {
+            request_ephemeral_public_key: unknown_request,
+            ..response.clone()
+        };
+        assert!(initiator.complete(&outdated_response).await?.is_none());
+
+        assert_eq!(
+            response.request_ephemeral_public_key,
+            request.ephemeral_public_key
+        );
+        let initiator_decryption_key = initiator.complete(&response).await?.unwrap();
+        let initiator_encryption_key = initiator.take_encryption_key().unwrap();
+        assert_eq!(initiator_vault.number_of_ephemeral_x25519_secrets(), 0);
+        assert_eq!(initiator_vault.number_of_ephemeral_ml_kem_secrets(), 0);
+
+        // the responder only switches keys once the initiator confirmed the re-handshake
+        assert!(responder.take_encryption_key().is_none());
+        responder.confirm().await?;
+        let responder_encryption_key = responder.take_encryption_key().unwrap();
+
+        check_keys(
+            &initiator_vault,
+            (initiator_encryption_key, initiator_decryption_key),
+            &responder_vault,
+            (responder_encryption_key, responder_decryption_key),
+        )
+        .await?;
+
+        // the next re-handshake is chained to this one
+        let (initiator_keys, responder_keys) = run_rehandshake(&initiator, &responder).await?;
+        check_keys(
+            &initiator_vault,
+            initiator_keys,
+            &responder_vault,
+            responder_keys,
+        )
+        .await?;
+        assert_eq!(initiator_vault.number_of_ephemeral_buffer_secrets(), 1);
+        assert_eq!(responder_vault.number_of_ephemeral_buffer_secrets(), 1);
+
+        Ok(())
+    }
+
+    type Keys = (AeadSecretKeyHandle, AeadSecretKeyHandle);
+
+    async fn create_rehandshakes() -> Result<(
+        Arc<SoftwareVaultForSecureChannels>,
+        Rehandshake,
+        Arc<SoftwareVaultForSecureChannels>,
+        Rehandshake,
+    )> {
+        let initiator_vault = SoftwareVaultForSecureChannels::create().await?;
+        let responder_vault = SoftwareVaultForSecureChannels::create().await?;
+        let initiator = Rehandshake::new(
+            initiator_vault.clone(),
+            Role::Initiator,
+            Some(Duration::ZERO),
+        );
+        let responder = Rehandshake::new(responder_vault.clone(), Role::Responder, None);
+        Ok((initiator_vault, initiator, responder_vault, responder))
+    }
+
+    /// Import the same chaining key in the vault of each side
+    async fn import_chaining_key(
+        vault: &Arc<SoftwareVaultForSecureChannels>,
+    ) -> Result<SecretBufferHandle> {
+        vault.import_secret_buffer(vec![7u8; 32]).await
+    }
+
+    /// Import the same channel key in the vault of each side
+    async fn import_channel_key(
+        vault: &Arc<SoftwareVaultForSecureChannels>,
+    ) -> Result<AeadSecretKeyHandle> {
+        let key = vault.import_secret_buffer(vec![9u8; 32]).await?;
+        vault.convert_secret_buffer_to_aead_key(key).await
+    }
+
+    /// Run a complete re-handshake and return the new
+    /// (encryption, decryption) keys of the initiator and the responder
+    async fn run_rehandshake(
+        initiator: &Rehandshake,
+        responder: &Rehandshake,
+    ) -> Result<(Keys, Keys)> {
+        let request = initiator.start_if_due().await?.unwrap();
+        let responder_decryption_key = responder.respond(&request).await?.unwrap();
+        let response = responder.take_response().unwrap();
+        let initiator_decryption_key = initiator.complete(&response).await?.unwrap();
+        let initiator_encryption_key = initiator.take_encryption_key().unwrap();
+        responder.confirm().await?;
+        let responder_encryption_key = responder.take_encryption_key().unwrap();
+        Ok((
+            (initiator_encryption_key, initiator_decryption_key),
+            (responder_encryption_key, responder_decryption_key),
+        ))
+    }
+
+    async fn check_keys(
+        initiator_vault: &Arc<SoftwareVaultForSecureChannels>,
+        (initiator_encryption_key, initiator_decryption_key): Keys,
+        responder_vault: &Arc<SoftwareVaultForSecureChannels>,
+        (responder_encryption_key, responder_decryption_key): Keys,
+    ) -> Result<()> {
+        let mut decryptor = Decryptor::new(responder_decryption_key, responder_vault.clone());
+        check_encryption(
+            initiator_vault,
+            initiator_encryption_key,
+            &mut decryptor,
+            42,
+        )
+        .await?;
+        let mut decryptor = Decryptor::new(initiator_decryption_key, initiator_vault.clone());
+        check_encryption(
+            responder_vault,
+            responder_encryption_key,
+            &mut decryptor,
+            43,
+        )
+        .await
+    }
+
+    async fn check_encryption(
+        vault: &Arc<SoftwareVaultForSecureChannels>,
+        encryption_key: AeadSecretKeyHandle,
+        decryptor: &mut Decryptor,
+        value: u8,
+    ) -> Result<()> {
+        let mut encryptor = Encryptor::new(encryption_key, 0.into(), vault.clone(), true);
+        let mut ciphertext = vec![0u8; 1 + 24];
+        ciphertext[8] = value;
+        encryptor.encrypt(&mut ciphertext).await?;
+        assert_eq!(decryptor.decrypt(&mut ciphertext).await?.0, &[value]);
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use ockam_core::async_trait;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::{boxed::Box, collections::BTreeMap, vec::Vec};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_vault::{VaultForSecureChannels, X25519PublicKey, X25519SecretKeyHandle};
+use tracing::debug;
+use Action::*;
+use Event::*;
+use Role::*;
+use Status::*;
+
+use crate::models::{Identifier, IDENTIFIER_LEN};
+use crate::secure_channel::handshake::error::XXError;
+use crate::secure_channel::handshake::handshake::{Handshake, StaticKeyPattern};
+use crate::secure_channel::handshake::handshake_state_machine::{
+    Action, Event, HandshakeResults, StateMachine, Status,
+};
+use crate::Role;
+
+/// Implementation of a state machine for the key exchange on the initiator side,
+/// when the static keys are known in advance.
+///
+/// The KK pattern is used if the initiator has a static key, the NK pattern otherwise.
+/// With the KK pattern, message 1 is prefixed with the identifier derived from the initiator
+/// static key, so that the responder can find that key without trying all its trusted keys
+#[async_trait]
+impl StateMachine for StaticKeyInitiatorStateMachine {
+    async fn on_event(&mut self, event: Event) -> Result<Action> {
+        let state = self.handshake.state.clone();
+        match (state.status, event) {
+            // Initialize the handshake and send message 1
+            (Initial, Initialize) => {
+                let static_public_key = match &state.s {
+                    Some(s) => Some(self.handshake.get_public_key(s).await?),
+                    None => None,
+                };
+                self.handshake
+                    .initialize_with_static_keys(
+                        static_public_key.as_ref(),
+                        &self.their_static_public_key,
+                    )
+                    .await?;
+                let mut message1 = match &static_public_key {
+                    Some(static_public_key) => {
+                        Identifier::from_x25519_public_key(static_public_key)
+                            .0
+                            .to_vec()
+                    }
+                    None => Vec::new(),
+                };
+                message1.extend(self.handshake.encode_static_key_message1(&[]).await?);
+
+                // Send message 1 and wait for message 2
+                self.handshake.state.status = WaitingForMessage2;
+                Ok(SendMessage(message1))
+            }
+            // Process message 2, the handshake is then complete
+            (WaitingForMessage2, ReceivedMessage(message)) => {
+                self.handshake.decode_static_key_message2(&message).await?;
+                self.handshake.set_final_state(Initiator).await?;
+                Ok(NoAction)
+            }
+            // incorrect state / event
+            (s, e) => Err(Error::new(
+                Origin::Channel,
+                Kind::Invalid,
+                format!(
+                    "Unexpected combination of static key initiator state and event {:?}/{:?}",
+                    s, e
+                ),
+            )),
+        }
+    }
+
+    fn get_handshake_results(&self) -> Option<HandshakeResults> {
+        self.handshake
+            .get_handshake_keys()
+            .map(|handshake_keys| HandshakeResults {
+                handshake_keys,
+                their_identifier: Identifier::from_x25519_public_key(&self.their_static_public_key),
+                presented_credential: None,
+            })
+    }
+}
+
+/// Implementation of the initiator state machine actions, delegated to the Handshake module
+pub(crate) struct StaticKeyInitiatorStateMachine {
+    handshake: Handshake,
+    their_static_public_key: X25519PublicKey,
+}
+
+impl StaticKeyInitiatorStateMachine {
+    pub(crate) async fn new(
+        vault: Arc<dyn VaultForSecureChannels>,
+        static_key: Option<X25519SecretKeyHandle>,
+        their_static_public_key: X25519PublicKey,
+    ) -> Result<StaticKeyInitiatorStateMachine> {
+        let pattern = if static_key.is_some() {
+            StaticKeyPattern::KK
+        } else {
+            StaticKeyPattern::NK
+        };
+        let mut handshake = Handshake::new_with_static_keys(vault, pattern, static_key).await?;
+        handshake.state.rs = Some(their_static_public_key.clone());
+
+        Ok(StaticKeyInitiatorStateMachine {
+            handshake,
+            their_static_public_key,
+        })
+    }
+
+    /// Return the identifier of the initiator, as seen by the responder:
+    /// it is derived from the static key with the KK pattern and from the ephemeral key with
+    /// the NK pattern
+    pub(crate) async fn identifier(&self) -> Result<Identifier> {
+        let key = match &self.handshake.state.s {
+            Some(s) => s,
+            None => self.handshake.state.e()?,
+        };
+        let public_key = self.handshake.get_public_key(key).await?;
+        Ok(Identifier::from_x25519_public_key(&public_key))
+    }
+}
+
+/// Implementation of a state machine for the key exchange on the responder side,
+/// when the static keys are known in advance.
+///
+/// The KK pattern is used if some initiator static keys are trusted, the NK pattern otherwise
+#[async_trait]
+impl StateMachine for StaticKeyResponderStateMachine {
+    async fn on_event(&mut self, event: Event) -> Result<Action> {
+        let state = self.handshake.state.clone();
+        match (state.status, event) {
+            // The handshake is initialized when message 1 is received, since it depends
+            // on the initiator static key
+            (Initial, Initialize) => {
+                self.handshake.state.status = WaitingForMessage1;
+                Ok(NoAction)
+            }
+            // Process message 1 and send message 2, the handshake is then complete
+            (WaitingForMessage1, ReceivedMessage(message)) => {
+                let their_public_key = self.decode_message1(&message).await?;
+                let message2 = self.handshake.encode_static_key_message2(&[]).await?;
+                self.handshake.set_final_state(Responder).await?;
+                self.their_identifier = Some(Identifier::from_x25519_public_key(&their_public_key));
+                Ok(SendMessage(message2))
+            }
+            // incorrect state / event
+            (s, e) => Err(Error::new(
+                Origin::Channel,
+                Kind::Invalid,
+                format!(
+                    "Unexpected combination of static key responder state and event {:?}/{:?}",
+                    s, e
+                ),
+            )),
+        }
+    }
+
+    fn get_handshake_results(&self) -> Option<HandshakeResults> {
+        match (self.handshake.get_handshake_keys(), &self.their_identifier) {
+            (Some(handshake_keys), Some(their_identifier)) => Some(HandshakeResults {
+                handshake_keys,
+                their_identifier: their_identifier.clone(),
+                presented_credential: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Implementation of the responder state machine actions, delegated to the Handshake module
+pub(crate) struct StaticKeyResponderStateMachine {
+    handshake: Handshake,
+    static_public_key: X25519PublicKey,
+    // trusted initiator static keys, by derived identifier
+    trusted_public_keys: BTreeMap<Identifier, X25519PublicKey>,
+    their_identifier: Option<Identifier>,
+}
+
+impl StaticKeyResponderStateMachine {
+    pub(crate) async fn new(
+        vault: Arc<dyn VaultForSecureChannels>,
+        static_key: X25519SecretKeyHandle,
+        trusted_public_keys: Vec<X25519PublicKey>,
+    ) -> Result<StaticKeyResponderStateMachine> {
+        let pattern = if trusted_public_keys.is_empty() {
+            StaticKeyPattern::NK
+        } else {
+            StaticKeyPattern::KK
+        };
+        let static_public_key = vault.get_x25519_public_key(&static_key).await?;
+        let handshake = Handshake::new_with_static_keys(vault, pattern, Some(static_key)).await?;
+
+        let trusted_public_keys = trusted_public_keys
+            .into_iter()
+            .map(|key| (Identifier::from_x25519_public_key(&key), key))
+            .collect();
+
+        Ok(StaticKeyResponderStateMachine {
+            handshake,
+            static_public_key,
+            trusted_public_keys,
+            their_identifier: None,
+        })
+    }
+
+    /// Decode message 1 and return the public key identifying the initiator:
+    ///  - with the NK pattern this is the initiator ephemeral key
+    ///  - with the KK pattern this is the trusted static key which was used by the initiator,
+    ///    found with the identifier prefixing the message
+    async fn decode_message1(&mut self, message1: &[u8]) -> Result<X25519PublicKey> {
+        if self.trusted_public_keys.is_empty() {
+            self.handshake
+                .initialize_with_static_keys(None, &self.static_public_key)
+                .await?;
+            self.handshake.decode_static_key_message1(message1).await?;
+            return Ok(self.handshake.state.re()?.clone());
+        }
+
+        if message1.len() < IDENTIFIER_LEN {
+            return Err(XXError::MessageLenMismatch)?;
+        }
+        let (identifier, message1) = message1.split_at(IDENTIFIER_LEN);
+        let identifier = Identifier(
+            identifier
+                .try_into()
+                .map_err(|_| XXError::MessageLenMismatch)?,
+        );
+        let trusted_public_key = match self.trusted_public_keys.get(&identifier) {
+            Some(trusted_public_key) => trusted_public_key.clone(),
+            None => {
+                debug!("no trusted static key matches the initiator static key");
+                return Err(XXError::UntrustedStaticKey)?;
+            }
+        };
+        self.handshake.state.rs = Some(trusted_public_key.clone());
+        self.handshake
+            .initialize_with_static_keys(Some(&trusted_public_key), &self.static_public_key)
+            .await?;
+        self.handshake.decode_static_key_message1(message1).await?;
+        Ok(trusted_public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_vault::SoftwareVaultForSecureChannels;
+
+    #[tokio::test]
+    async fn test_initiator_key_hint() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+        let initiator_key = vault.generate_static_x25519_secret_key().await?;
+        let initiator_public_key = vault.get_x25519_public_key(&initiator_key).await?;
+        let other_key = vault.generate_static_x25519_secret_key().await?;
+        let other_public_key = vault.get_x25519_public_key(&other_key).await?;
+        let responder_key = vault.generate_static_x25519_secret_key().await?;
+        let responder_public_key = vault.get_x25519_public_key(&responder_key).await?;
+        let trusted_public_keys = vec![other_public_key.clone(), initiator_public_key.clone()];
+
+        let mut initiator = StaticKeyInitiatorStateMachine::new(
+            vault.clone(),
+            Some(initiator_key.clone()),
+            responder_public_key.clone(),
+        )
+        .await?;
+        let SendMessage(message1) = initiator.on_event(Initialize).await? else {
+            panic!("message 1 must be sent")
+        };
+        assert_eq!(
+            message1[..IDENTIFIER_LEN],
+            Identifier::from_x25519_public_key(&initiator_public_key).0
+        );
+
+        // the responder finds the initiator key with the identifier
+        let mut responder = StaticKeyResponderStateMachine::new(
+            vault.clone(),
+            responder_key.clone(),
+            trusted_public_keys.clone(),
+        )
+        .await?;
+        responder.on_event(Initialize).await?;
+        responder
+            .on_event(ReceivedMessage(message1.clone()))
+            .await?;
+        assert_eq!(
+            responder.get_handshake_results().unwrap().their_identifier,
+            Identifier::from_x25519_public_key(&initiator_public_key)
+        );
+
+        // the message can't be decoded with the key of another trusted identifier
+        let mut responder =
+            StaticKeyResponderStateMachine::new(vault.clone(), responder_key, trusted_public_keys)
+                .await?;
+        responder.on_event(Initialize).await?;
+        let mut message1 = message1;
+        message1[..IDENTIFIER_LEN]
+            .copy_from_slice(&Identifier::from_x25519_public_key(&other_public_key).0);
+        assert!(responder.on_event(ReceivedMessage(message1)).await.is_err());
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use crate::models::{ChangeHistory, CredentialAndPurposeKey};
 use minicbor::{CborLen, Decode, Encode};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{CowBytes, Route};
+use ockam_vault::{MlKem768Ciphertext, MlKem768PublicKey, X25519PublicKey};
 
 /// Secure Channel Message format.
 #[derive(Debug, Encode, Decode, CborLen, Clone)]
@@ -23,6 +24,10 @@ pub enum SecureChannelMessage<'a> {
     #[n(1)] RefreshCredentials(#[n(0)] RefreshCredentialsMessage),
     /// Close the channel.
     #[n(2)] Close,
+    /// Start a new key exchange with a fresh ephemeral key.
+    #[n(3)] RehandshakeRequest(#[n(0)] RehandshakeRequestMessage),
+    /// Complete a key exchange started by the other side.
+    #[n(4)] RehandshakeResponse(#[n(0)] RehandshakeResponseMessage),
 }
 
 /// Secure Channel Message format.
@@ -47,3 +52,27 @@ pub struct RefreshCredentialsMessage {
     /// to verify those Credentials
     #[n(1)] pub credentials: Vec<CredentialAndPurposeKey>,
 }
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, CborLen, Clone)]
+#[rustfmt::skip]
+pub struct RehandshakeRequestMessage {
+    /// Fresh ephemeral public key of the channel initiator
+    #[n(0)] pub ephemeral_public_key: X25519PublicKey,
+    /// Fresh ephemeral ML-KEM public key of the channel initiator, if the channel uses
+    /// a hybrid key exchange
+    #[n(1)] pub ml_kem_public_key: Option<MlKem768PublicKey>,
+}
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, CborLen, Clone)]
+#[rustfmt::skip]
+pub struct RehandshakeResponseMessage {
+    /// Ephemeral public key of the request being answered
+    #[n(0)] pub request_ephemeral_public_key: X25519PublicKey,
+    /// Fresh ephemeral public key of the channel responder
+    #[n(1)] pub ephemeral_public_key: X25519PublicKey,
+    /// Ciphertext of a shared secret encapsulated for the ML-KEM public key of the request,
+    /// if the channel uses a hybrid key exchange
+    #[n(2)] pub ml_kem_ciphertext: Option<MlKem768Ciphertext>,
+}
@@ -14,6 +14,8 @@ mod nonce_tracker;
 mod options;
 mod registry;
 mod role;
+mod static_key_listener;
+mod static_key_options;
 
 /// List of trust policies to setup ABAC controls
 pub mod trust_policy;
@@ -30,14 +32,21 @@ pub use nonce::*;
 pub use options::*;
 pub use registry::*;
 pub(crate) use role::*;
+pub(crate) use static_key_listener::*;
+pub use static_key_options::*;
 pub use trust_policy::*;
 
 #[cfg(test)]
 mod tests {
+    use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
     use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor};
+    use core::time::Duration;
     use ockam_core::compat::rand::RngCore;
+    use ockam_core::compat::sync::Arc;
     use ockam_core::Result;
-    use ockam_vault::{SoftwareVaultForSecureChannels, VaultForSecureChannels};
+    use ockam_vault::{
+        AeadSecretKeyHandle, SoftwareVaultForSecureChannels, VaultForSecureChannels,
+    };
     use rand::seq::SliceRandom;
     use rand::thread_rng;
 
@@ -179,10 +188,74 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_time_based_rekey() {
+        let (encryptor, mut decryptor) = create_encryptor_decryptor().await.unwrap();
+        // with an empty interval, each message starts a new rekeying interval
+        let mut encryptor = encryptor.with_rekey_interval(Some(Duration::ZERO));
+
+        for n in 0..100 {
+            let msg = vec![n];
+            let mut ciphertext = vec![0u8; 1 + 24];
+            ciphertext[8..9].copy_from_slice(msg.as_slice());
+            encryptor.encrypt(&mut ciphertext).await.unwrap();
+            let (plaintext, nonce) = decryptor.decrypt(ciphertext.as_mut_slice()).await.unwrap();
+            assert_eq!(msg, plaintext);
+            assert_eq!(nonce.value(), (n as u64 + 1) * KEY_RENEWAL_INTERVAL);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_next_key() {
+        let vault1 = SoftwareVaultForSecureChannels::create().await.unwrap();
+        let vault2 = SoftwareVaultForSecureChannels::create().await.unwrap();
+        let (key_on_v1, key_on_v2) = create_shared_key(&vault1, &vault2).await.unwrap();
+        let mut encryptor = Encryptor::new(key_on_v1, 0.into(), vault1.clone(), true);
+        let mut decryptor = Decryptor::new(key_on_v2, vault2.clone());
+
+        // the decryptor knows the next key before the encryptor uses it,
+        // and keeps decrypting messages with derived keys in the meantime
+        let (next_key_on_v1, next_key_on_v2) = create_shared_key(&vault1, &vault2).await.unwrap();
+        decryptor.set_next_key(next_key_on_v2).await.unwrap();
+
+        for n in 0..100 {
+            if n == 50 {
+                encryptor
+                    .set_next_key(next_key_on_v1.clone())
+                    .await
+                    .unwrap();
+            }
+            let msg = vec![n];
+            let mut ciphertext = vec![0u8; 1 + 24];
+            ciphertext[8..9].copy_from_slice(msg.as_slice());
+            encryptor.encrypt(&mut ciphertext).await.unwrap();
+            assert_eq!(
+                msg,
+                decryptor
+                    .decrypt(ciphertext.as_mut_slice())
+                    .await
+                    .unwrap()
+                    .0
+            );
+        }
+    }
+
     async fn create_encryptor_decryptor() -> Result<(Encryptor, Decryptor)> {
         let vault1 = SoftwareVaultForSecureChannels::create().await?;
         let vault2 = SoftwareVaultForSecureChannels::create().await?;
 
+        let (key_on_v1, key_on_v2) = create_shared_key(&vault1, &vault2).await?;
+
+        Ok((
+            Encryptor::new(key_on_v1, 0.into(), vault1, true),
+            Decryptor::new(key_on_v2, vault2),
+        ))
+    }
+
+    async fn create_shared_key(
+        vault1: &Arc<SoftwareVaultForSecureChannels>,
+        vault2: &Arc<SoftwareVaultForSecureChannels>,
+    ) -> Result<(AeadSecretKeyHandle, AeadSecretKeyHandle)> {
         let mut rng = thread_rng();
         let mut key = [0u8; 32];
         rng.fill_bytes(&mut key);
@@ -193,9 +266,6 @@ mod tests {
         let key_on_v2 = vault2.import_secret_buffer(key.to_vec()).await?;
         let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
 
-        Ok((
-            Encryptor::new(key_on_v1, 0.into(), vault1, true),
-            Decryptor::new(key_on_v2, vault2),
-        ))
+        Ok((key_on_v1, key_on_v2))
     }
 }
@@ -37,6 +37,30 @@ pub struct SecureChannelOptions {
     pub(crate) key_exchange_only: bool,
     // Secure Channel will be persisted (currently only supported for key_exchange_only = true)
     pub(crate) is_persistent: bool,
+    pub(crate) key_renewal: KeyRenewalOptions,
+    pub(crate) hybrid_key_exchange: HybridKeyExchange,
+}
+
+/// Time-based renewal of the keys of a secure channel
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct KeyRenewalOptions {
+    // Derive new keys when the current ones have been used for that long
+    pub(crate) rekey_interval: Option<Duration>,
+    // Run a key exchange with fresh ephemeral keys at this interval (initiator only)
+    pub(crate) rehandshake_interval: Option<Duration>,
+}
+
+/// Use of an ML-KEM-768 (Kyber-768) encapsulation in addition to the X25519 Diffie-Hellman
+/// keys during the handshake
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum HybridKeyExchange {
+    /// Only X25519 is used
+    #[default]
+    Disabled,
+    /// ML-KEM-768 is used if the initiator asks for it (responder only)
+    Allowed,
+    /// ML-KEM-768 is always used, the handshake fails if the other side doesn't use it
+    Required,
 }
 
 impl fmt::Debug for SecureChannelOptions {
@@ -57,6 +81,8 @@ impl SecureChannelOptions {
             timeout: DEFAULT_TIMEOUT,
             key_exchange_only: false,
             is_persistent: false,
+            key_renewal: KeyRenewalOptions::default(),
+            hybrid_key_exchange: HybridKeyExchange::Disabled,
         }
     }
 
@@ -97,6 +123,31 @@ impl SecureChannelOptions {
         self
     }
 
+    /// Renew the keys of the channel when they have been used for longer than `rekey_interval`,
+    /// in addition to the renewal happening every 32 messages.
+    /// This bounds the period of time protected by a given key on a low-traffic channel
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.key_renewal.rekey_interval = Some(rekey_interval);
+        self
+    }
+
+    /// Periodically run a new key exchange with fresh ephemeral keys inside the channel.
+    /// A compromise of the current keys then doesn't expose the traffic exchanged before the
+    /// last re-handshake. The listener side of the channel must support re-handshakes
+    pub fn with_rehandshake_interval(mut self, rehandshake_interval: Duration) -> Self {
+        self.key_renewal.rehandshake_interval = Some(rehandshake_interval);
+        self
+    }
+
+    /// Mix an ML-KEM-768 (Kyber-768) shared secret into the X25519 key exchange, so that
+    /// the channel keys stay secret even if X25519 gets broken by a quantum computer.
+    /// The re-handshakes of the channel use ML-KEM-768 as well.
+    /// The listener must accept hybrid key exchanges, otherwise the handshake fails
+    pub fn with_hybrid_key_exchange(mut self) -> Self {
+        self.hybrid_key_exchange = HybridKeyExchange::Required;
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn producer_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -185,6 +236,8 @@ pub struct SecureChannelListenerOptions {
     pub(crate) key_exchange_only: bool,
     // Secure Channel will be persisted (currently only supported for key_exchange_only = true)
     pub(crate) is_persistent: bool,
+    pub(crate) key_renewal: KeyRenewalOptions,
+    pub(crate) hybrid_key_exchange: HybridKeyExchange,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -207,6 +260,8 @@ impl SecureChannelListenerOptions {
             credential_retriever_creator: None,
             key_exchange_only: false,
             is_persistent: false,
+            key_renewal: KeyRenewalOptions::default(),
+            hybrid_key_exchange: HybridKeyExchange::Disabled,
         }
     }
 
@@ -250,6 +305,28 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Renew the keys of the spawned channels when they have been used for longer than
+    /// `rekey_interval`, in addition to the renewal happening every 32 messages.
+    /// The spawned channels always answer the re-handshakes started by the initiators
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.key_renewal.rekey_interval = Some(rekey_interval);
+        self
+    }
+
+    /// Accept initiators mixing an ML-KEM-768 (Kyber-768) shared secret into the X25519 key
+    /// exchange. Initiators which don't ask for it still use X25519 only
+    pub fn with_hybrid_key_exchange(mut self) -> Self {
+        self.hybrid_key_exchange = HybridKeyExchange::Allowed;
+        self
+    }
+
+    /// Only accept initiators mixing an ML-KEM-768 (Kyber-768) shared secret into the X25519
+    /// key exchange
+    pub fn with_hybrid_key_exchange_required(mut self) -> Self {
+        self.hybrid_key_exchange = HybridKeyExchange::Required;
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -280,15 +357,55 @@ impl SecureChannelListenerOptions {
         flow_controls: &FlowControls,
         address: &Address,
     ) {
-        for id in &self.consumer {
+        Self::setup_flow_control_for_spawner(
+            &self.consumer,
+            &self.flow_control_id,
+            flow_controls,
+            address,
+        )
+    }
+
+    pub(crate) fn setup_flow_control_for_channel(
+        &self,
+        flow_controls: &FlowControls,
+        listener_address: &Address,
+        addresses: &Addresses,
+    ) -> FlowControlId {
+        Self::setup_flow_control_for_spawned_channel(
+            &self.flow_control_id,
+            flow_controls,
+            listener_address,
+            addresses,
+        )
+    }
+
+    pub(crate) fn create_decryptor_outgoing_access_control(
+        &self,
+        flow_controls: &FlowControls,
+        flow_control_id: FlowControlId,
+    ) -> Arc<dyn OutgoingAccessControl> {
+        Self::create_spawned_decryptor_outgoing_access_control(
+            &self.flow_control_id,
+            flow_controls,
+            flow_control_id,
+        )
+    }
+
+    pub(crate) fn setup_flow_control_for_spawner(
+        consumer: &[FlowControlId],
+        spawner_flow_control_id: &FlowControlId,
+        flow_controls: &FlowControls,
+        address: &Address,
+    ) {
+        for id in consumer {
             flow_controls.add_consumer(address, id);
         }
 
-        flow_controls.add_spawner(address, &self.flow_control_id);
+        flow_controls.add_spawner(address, spawner_flow_control_id);
     }
 
-    pub(crate) fn setup_flow_control_for_channel(
-        &self,
+    pub(crate) fn setup_flow_control_for_spawned_channel(
+        spawner_flow_control_id: &FlowControlId,
         flow_controls: &FlowControls,
         listener_address: &Address,
         addresses: &Addresses,
@@ -312,22 +429,22 @@ impl SecureChannelListenerOptions {
         flow_controls.add_producer(
             &addresses.decryptor_internal,
             &flow_control_id,
-            Some(&self.flow_control_id),
+            Some(spawner_flow_control_id),
             vec![addresses.encryptor.clone()],
         );
 
         flow_control_id
     }
 
-    pub(crate) fn create_decryptor_outgoing_access_control(
-        &self,
+    pub(crate) fn create_spawned_decryptor_outgoing_access_control(
+        spawner_flow_control_id: &FlowControlId,
         flow_controls: &FlowControls,
         flow_control_id: FlowControlId,
     ) -> Arc<dyn OutgoingAccessControl> {
         let ac = FlowControlOutgoingAccessControl::new(
             flow_controls,
             flow_control_id,
-            Some(self.flow_control_id.clone()),
+            Some(spawner_flow_control_id.clone()),
         );
 
         Arc::new(ac)
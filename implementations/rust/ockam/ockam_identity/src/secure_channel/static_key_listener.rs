@@ -0,0 +1,103 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Address, Any, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_vault::X25519PublicKey;
+
+use crate::models::Identifier;
+use crate::secure_channel::addresses::Addresses;
+use crate::secure_channel::encryptor_worker::RemoteRoute;
+use crate::secure_channel::handshake::static_key_state_machine::StaticKeyResponderStateMachine;
+use crate::secure_channel::handshake_worker::HandshakeWorker;
+use crate::secure_channel::role::Role;
+use crate::secure_channel::static_key_options::StaticKeySecureChannelListenerOptions;
+use crate::secure_channels::secure_channels::SecureChannels;
+
+/// Listener spawning the responder side of the secure channels created with static keys
+pub(crate) struct StaticKeySecureChannelListenerWorker {
+    secure_channels: Arc<SecureChannels>,
+    identifier: Identifier,
+    options: StaticKeySecureChannelListenerOptions,
+}
+
+impl StaticKeySecureChannelListenerWorker {
+    /// Start a listener for the static key given in the options
+    pub async fn create(
+        ctx: &Context,
+        secure_channels: Arc<SecureChannels>,
+        address: Address,
+        options: StaticKeySecureChannelListenerOptions,
+    ) -> Result<()> {
+        let public_key: X25519PublicKey = secure_channels
+            .identities
+            .vault()
+            .secure_channel_vault
+            .get_x25519_public_key(&options.static_key)
+            .await?;
+        let identifier = Identifier::from_x25519_public_key(&public_key);
+
+        options.setup_flow_control_for_listener(ctx.flow_controls(), &address);
+
+        let listener = Self {
+            secure_channels,
+            identifier,
+            options,
+        };
+        ctx.start_worker(address, listener)?;
+
+        Ok(())
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for StaticKeySecureChannelListenerWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        message: Routed<Self::Message>,
+    ) -> Result<()> {
+        let addresses = Addresses::generate(Role::Responder);
+        let flow_control_id = self.options.setup_flow_control_for_channel(
+            ctx.flow_controls(),
+            ctx.primary_address(),
+            &addresses,
+        );
+        let decryptor_outgoing_access_control = self
+            .options
+            .create_decryptor_outgoing_access_control(ctx.flow_controls(), flow_control_id);
+
+        let state_machine = StaticKeyResponderStateMachine::new(
+            self.secure_channels.identities.vault().secure_channel_vault,
+            self.options.static_key.clone(),
+            self.options.trusted_public_keys.clone(),
+        )
+        .await?;
+
+        HandshakeWorker::start(
+            ctx,
+            self.secure_channels.clone(),
+            Box::new(state_machine),
+            addresses.clone(),
+            self.identifier.clone(),
+            decryptor_outgoing_access_control,
+            None,
+            None,
+            None,
+            None,
+            Role::Responder,
+            false,
+            self.options.key_renewal,
+            None,
+            RemoteRoute::create(),
+        )
+        .await?;
+
+        let mut local_message = message.into_local_message();
+        local_message = local_message.replace_front_onward_route(addresses.decryptor_remote)?;
+
+        ctx.forward(local_message).await
+    }
+}
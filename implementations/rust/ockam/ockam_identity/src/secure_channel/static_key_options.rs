@@ -0,0 +1,214 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
+use ockam_core::{Address, OutgoingAccessControl};
+use ockam_vault::{X25519PublicKey, X25519SecretKeyHandle};
+
+use crate::secure_channel::options::DEFAULT_TIMEOUT;
+use crate::secure_channel::{Addresses, KeyRenewalOptions};
+use crate::{SecureChannelListenerOptions, SecureChannelOptions};
+
+use core::fmt;
+use core::fmt::Formatter;
+use core::time::Duration;
+
+/// Options for a lightweight Secure Channel where the peers are authenticated by X25519 static
+/// keys exchanged in advance, instead of identities, purpose keys and credentials.
+///
+///  - if the initiator has no static key, the Noise NK pattern is used and only the responder
+///    is authenticated
+///  - if the initiator has a static key, the Noise KK pattern is used and both sides are
+///    authenticated
+///
+/// The identifier of each peer on that channel is derived from its X25519 public key
+/// (see [`crate::Identifier::from_x25519_public_key`])
+pub struct StaticKeySecureChannelOptions {
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) their_public_key: X25519PublicKey,
+    pub(crate) static_key: Option<X25519SecretKeyHandle>,
+    pub(crate) timeout: Duration,
+    pub(crate) key_renewal: KeyRenewalOptions,
+}
+
+impl fmt::Debug for StaticKeySecureChannelOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "FlowId: {}", self.flow_control_id)
+    }
+}
+
+impl StaticKeySecureChannelOptions {
+    /// Connect to a listener having the static key `their_public_key`.
+    /// Mark this Secure Channel Decryptor as a Producer with a random [`FlowControlId`]
+    pub fn new(their_public_key: X25519PublicKey) -> Self {
+        Self {
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            their_public_key,
+            static_key: None,
+            timeout: DEFAULT_TIMEOUT,
+            key_renewal: KeyRenewalOptions::default(),
+        }
+    }
+
+    /// Authenticate this side of the channel with a static key stored in the secure channel vault.
+    /// The listener must trust the corresponding public key
+    pub fn with_static_key(mut self, static_key: X25519SecretKeyHandle) -> Self {
+        self.static_key = Some(static_key);
+        self
+    }
+
+    /// Sets a timeout different from the default one [`DEFAULT_TIMEOUT`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Renew the keys of the channel when they have been used for longer than `rekey_interval`,
+    /// in addition to the renewal happening every 32 messages
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.key_renewal.rekey_interval = Some(rekey_interval);
+        self
+    }
+
+    /// Periodically run a new key exchange with fresh ephemeral keys inside the channel
+    pub fn with_rehandshake_interval(mut self, rehandshake_interval: Duration) -> Self {
+        self.key_renewal.rehandshake_interval = Some(rehandshake_interval);
+        self
+    }
+
+    /// Freshly generated [`FlowControlId`]
+    pub fn producer_flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
+
+impl StaticKeySecureChannelOptions {
+    pub(crate) fn setup_flow_control(
+        &self,
+        flow_controls: &FlowControls,
+        addresses: &Addresses,
+        next: &Address,
+    ) {
+        SecureChannelOptions::setup_flow_control_consumer(flow_controls, addresses, next);
+        SecureChannelOptions::setup_flow_control_producer(
+            &self.flow_control_id,
+            flow_controls,
+            addresses,
+        );
+    }
+
+    pub(crate) fn create_decryptor_outgoing_access_control(
+        &self,
+        flow_controls: &FlowControls,
+    ) -> Arc<dyn OutgoingAccessControl> {
+        let ac = FlowControlOutgoingAccessControl::new(
+            flow_controls,
+            self.flow_control_id.clone(),
+            None,
+        );
+
+        Arc::new(ac)
+    }
+}
+
+/// Options for a lightweight Secure Channel Listener authenticated by an X25519 static key.
+///
+///  - without trusted public keys, any initiator knowing the listener public key can create a
+///    channel (Noise NK pattern). The initiator is then identified by its ephemeral key
+///  - with trusted public keys, only the initiators having one of these static keys can create
+///    a channel (Noise KK pattern)
+pub struct StaticKeySecureChannelListenerOptions {
+    pub(crate) consumer: Vec<FlowControlId>,
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) static_key: X25519SecretKeyHandle,
+    pub(crate) trusted_public_keys: Vec<X25519PublicKey>,
+    pub(crate) key_renewal: KeyRenewalOptions,
+}
+
+impl fmt::Debug for StaticKeySecureChannelListenerOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "SpawnerFlowId: {}", self.flow_control_id)
+    }
+}
+
+impl StaticKeySecureChannelListenerOptions {
+    /// Accept channels for the given static key stored in the secure channel vault.
+    /// Mark spawned Secure Channel Decryptors as Producers for a given Spawner's [`FlowControlId`]
+    pub fn new(static_key: X25519SecretKeyHandle) -> Self {
+        Self {
+            consumer: vec![],
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            static_key,
+            trusted_public_keys: vec![],
+            key_renewal: KeyRenewalOptions::default(),
+        }
+    }
+
+    /// Only accept initiators authenticated with that static key, or one of the other trusted keys.
+    /// The initiators send the identifier derived from their static key, which is used to find
+    /// the trusted key when a channel is created
+    pub fn with_trusted_public_key(mut self, public_key: X25519PublicKey) -> Self {
+        self.trusted_public_keys.push(public_key);
+        self
+    }
+
+    /// Mark that this Secure Channel Listener is a Consumer for to the given [`FlowControlId`]
+    /// Also, in this case spawned Secure Channels will be marked as Consumers with [`FlowControlId`]
+    /// of the message that was used to create the Secure Channel
+    pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
+        self.consumer.push(id.clone());
+        self
+    }
+
+    /// Renew the keys of the spawned channels when they have been used for longer than
+    /// `rekey_interval`, in addition to the renewal happening every 32 messages
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.key_renewal.rekey_interval = Some(rekey_interval);
+        self
+    }
+
+    /// Freshly generated [`FlowControlId`]
+    pub fn spawner_flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
+
+impl StaticKeySecureChannelListenerOptions {
+    pub(crate) fn setup_flow_control_for_listener(
+        &self,
+        flow_controls: &FlowControls,
+        address: &Address,
+    ) {
+        SecureChannelListenerOptions::setup_flow_control_for_spawner(
+            &self.consumer,
+            &self.flow_control_id,
+            flow_controls,
+            address,
+        )
+    }
+
+    pub(crate) fn setup_flow_control_for_channel(
+        &self,
+        flow_controls: &FlowControls,
+        listener_address: &Address,
+        addresses: &Addresses,
+    ) -> FlowControlId {
+        SecureChannelListenerOptions::setup_flow_control_for_spawned_channel(
+            &self.flow_control_id,
+            flow_controls,
+            listener_address,
+            addresses,
+        )
+    }
+
+    pub(crate) fn create_decryptor_outgoing_access_control(
+        &self,
+        flow_controls: &FlowControls,
+        flow_control_id: FlowControlId,
+    ) -> Arc<dyn OutgoingAccessControl> {
+        SecureChannelListenerOptions::create_spawned_decryptor_outgoing_access_control(
+            &self.flow_control_id,
+            flow_controls,
+            flow_control_id,
+        )
+    }
+}
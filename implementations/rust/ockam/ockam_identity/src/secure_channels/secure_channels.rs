@@ -1,4 +1,5 @@
 use core::sync::atomic::AtomicBool;
+use ockam_core::compat::boxed::Box;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::FlowControls;
 use ockam_core::Result;
@@ -8,11 +9,13 @@ use tracing::info;
 
 use crate::identities::Identities;
 use crate::models::Identifier;
+use crate::secure_channel::handshake::static_key_state_machine::StaticKeyInitiatorStateMachine;
 use crate::secure_channel::handshake_worker::HandshakeWorker;
 use crate::secure_channel::{
     Addresses, DecryptorHandler, RemoteRoute, Role, SecureChannelListenerOptions,
     SecureChannelListenerWorker, SecureChannelOptions, SecureChannelRegistry,
-    SecureChannelSharedState,
+    SecureChannelSharedState, StaticKeySecureChannelListenerOptions,
+    StaticKeySecureChannelListenerWorker, StaticKeySecureChannelOptions,
 };
 #[cfg(feature = "storage")]
 use crate::SecureChannelsBuilder;
@@ -171,6 +174,8 @@ impl SecureChannels {
             Some(options.timeout),
             Role::Initiator,
             options.key_exchange_only,
+            options.key_renewal,
+            options.hybrid_key_exchange,
             secure_channel_repository,
             encryptor_remote_route.clone(),
         )
@@ -189,6 +194,87 @@ impl SecureChannels {
         ))
     }
 
+    /// Create a lightweight SecureChannel listener, authenticated by a static key instead of an
+    /// identity, using [`StaticKeySecureChannelListenerOptions`]
+    pub async fn create_static_key_secure_channel_listener(
+        &self,
+        ctx: &Context,
+        address: impl Into<Address>,
+        options: StaticKeySecureChannelListenerOptions,
+    ) -> Result<SecureChannelListener> {
+        let address = address.into();
+        let flow_control_id = options.flow_control_id.clone();
+
+        StaticKeySecureChannelListenerWorker::create(
+            ctx,
+            Arc::new(self.clone()),
+            address.clone(),
+            options,
+        )
+        .await?;
+
+        Ok(SecureChannelListener::new(address, false, flow_control_id))
+    }
+
+    /// Initiate a lightweight SecureChannel using `Route` to a static key SecureChannel listener
+    /// and [`StaticKeySecureChannelOptions`].
+    /// No identity, purpose key attestation or credential is exchanged during the handshake
+    pub async fn create_static_key_secure_channel(
+        &self,
+        ctx: &Context,
+        route: impl Into<Route>,
+        options: StaticKeySecureChannelOptions,
+    ) -> Result<SecureChannel> {
+        let addresses = Addresses::generate(Role::Initiator);
+        let flow_control_id = options.flow_control_id.clone();
+
+        let route = route.into();
+        let next = route.next()?;
+        options.setup_flow_control(ctx.flow_controls(), &addresses, next);
+        let decryptor_outgoing_access_control =
+            options.create_decryptor_outgoing_access_control(ctx.flow_controls());
+
+        let state_machine = StaticKeyInitiatorStateMachine::new(
+            self.vault().secure_channel_vault,
+            options.static_key,
+            options.their_public_key,
+        )
+        .await?;
+        let identifier = state_machine.identifier().await?;
+
+        let encryptor_remote_route = RemoteRoute::create();
+        let Some(their_identifier) = HandshakeWorker::start(
+            ctx,
+            Arc::new(self.clone()),
+            Box::new(state_machine),
+            addresses.clone(),
+            identifier,
+            decryptor_outgoing_access_control,
+            None,
+            None,
+            Some(route),
+            Some(options.timeout),
+            Role::Initiator,
+            false,
+            options.key_renewal,
+            None,
+            encryptor_remote_route.clone(),
+        )
+        .await?
+        else {
+            return Err(IdentityError::HandshakeInternalError)?;
+        };
+
+        Ok(SecureChannel::new(
+            ctx.flow_controls().clone(),
+            their_identifier,
+            encryptor_remote_route,
+            addresses,
+            false,
+            flow_control_id,
+        ))
+    }
+
     /// Start a decryptor side for a previously existed and persisted secure channel
     /// Only decryptor api part is started
     pub async fn start_persisted_secure_channel_decryptor(
@@ -222,6 +308,7 @@ impl SecureChannels {
         let shared_state = SecureChannelSharedState {
             remote_route: RemoteRoute::create(),                 // Unused
             should_send_close: Arc::new(AtomicBool::new(false)), // Don't need to send anything
+            rehandshake: None,                                   // Keys are never renewed
         };
 
         let mut addresses = Addresses::generate(role);
@@ -11,7 +11,8 @@ use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
     DecryptionResponse, EncryptionRequest, EncryptionResponse, IdentityAccessControlBuilder,
-    SecureChannelListenerOptions, SecureChannelOptions, SecureChannels, TrustEveryonePolicy,
+    SecureChannelListenerOptions, SecureChannelOptions, SecureChannels,
+    StaticKeySecureChannelListenerOptions, StaticKeySecureChannelOptions, TrustEveryonePolicy,
     TrustIdentifierPolicy, Vault,
 };
 use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
@@ -317,6 +318,316 @@ async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context)
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn test_channel_rehandshake(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create().await?;
+    let alice_vault = Vault::new(
+        SoftwareVaultForSigning::create().await?,
+        alice_sc_vault.clone(),
+        SoftwareVaultForSigning::create().await?,
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+    let bob_sc_vault = SoftwareVaultForSecureChannels::create().await?;
+    let bob_vault = Vault::new(
+        SoftwareVaultForSigning::create().await?,
+        bob_sc_vault.clone(),
+        SoftwareVaultForSigning::create().await?,
+        SoftwareVaultForVerifyingSignatures::create(),
+    );
+
+    let secure_channels_alice = SecureChannels::builder()
+        .await?
+        .with_vault(alice_vault)
+        .build();
+    let secure_channels_bob = SecureChannels::builder()
+        .await?
+        .with_vault(bob_vault)
+        .build();
+
+    let alice = secure_channels_alice
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(alice.clone()))
+        .with_rekey_interval(Duration::from_secs(1));
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels_bob.create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)?;
+
+    let alice_options = SecureChannelOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(bob.clone()))
+        .with_rehandshake_interval(Duration::from_secs(1))
+        .with_rekey_interval(Duration::from_secs(1));
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels_alice
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx.new_detached_with_mailboxes(Mailboxes::primary(
+        "child",
+        Arc::new(AllowAll),
+        Arc::new(AllowAll),
+    ))?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.primary_address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.primary_address(), &sc_flow_control_id);
+
+    // the keys are renewed between the rounds, while messages keep flowing in both directions
+    for round in 0..3 {
+        for n in 0..40 {
+            let payload = format!("Hello, Bob! {round} {n}");
+            child_ctx
+                .send(
+                    route![alice_channel.clone(), child_ctx.primary_address().clone()],
+                    payload.clone(),
+                )
+                .await?;
+
+            let message = child_ctx.receive::<String>().await?;
+            let return_route = message.return_route().clone();
+            assert_eq!(payload, message.into_body()?);
+
+            let payload = format!("Hello, Alice! {round} {n}");
+            child_ctx.send(return_route, payload.clone()).await?;
+
+            let message = child_ctx.receive::<String>().await?;
+            assert_eq!(payload, message.into_body()?);
+        }
+        ctx.sleep(Duration::from_millis(2100)).await;
+    }
+
+    // the ephemeral keys of the re-handshakes are deleted once the new keys are derived
+    assert_eq!(alice_sc_vault.number_of_ephemeral_x25519_secrets(), 0);
+    assert_eq!(bob_sc_vault.number_of_ephemeral_x25519_secrets(), 0);
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn test_channel_hybrid_key_exchange(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options = SecureChannelListenerOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(alice.clone()))
+        .with_hybrid_key_exchange_required();
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels.create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)?;
+
+    // an initiator which doesn't use ML-KEM-768 is rejected
+    let result = secure_channels
+        .create_secure_channel(
+            ctx,
+            &alice,
+            route!["bob_listener"],
+            SecureChannelOptions::new().with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    let alice_options = SecureChannelOptions::new()
+        .with_trust_policy(TrustIdentifierPolicy::new(bob.clone()))
+        .with_hybrid_key_exchange()
+        .with_rehandshake_interval(Duration::from_secs(1));
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx.new_detached_with_mailboxes(Mailboxes::primary(
+        "child",
+        Arc::new(AllowAll),
+        Arc::new(AllowAll),
+    ))?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.primary_address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.primary_address(), &sc_flow_control_id);
+
+    // the re-handshake happening between the rounds uses ML-KEM-768 as well
+    for round in 0..2 {
+        for n in 0..40 {
+            let payload = format!("Hello, Bob! {round} {n}");
+            child_ctx
+                .send(
+                    route![alice_channel.clone(), child_ctx.primary_address().clone()],
+                    payload.clone(),
+                )
+                .await?;
+
+            let message = child_ctx.receive::<String>().await?;
+            let return_route = message.return_route().clone();
+            assert_eq!(payload, message.into_body()?);
+
+            let payload = format!("Hello, Alice! {round} {n}");
+            child_ctx.send(return_route, payload.clone()).await?;
+
+            let message = child_ctx.receive::<String>().await?;
+            assert_eq!(payload, message.into_body()?);
+        }
+        ctx.sleep(Duration::from_millis(1100)).await;
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn test_channel_static_keys(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let vault = secure_channels.vault().secure_channel_vault;
+
+    let alice_key = vault.generate_static_x25519_secret_key().await?;
+    let alice_public_key = vault.get_x25519_public_key(&alice_key).await?;
+    let bob_key = vault.generate_static_x25519_secret_key().await?;
+    let bob_public_key = vault.get_x25519_public_key(&bob_key).await?;
+    let charlie_key = vault.generate_static_x25519_secret_key().await?;
+    let charlie_public_key = vault.get_x25519_public_key(&charlie_key).await?;
+    let eve_key = vault.generate_static_x25519_secret_key().await?;
+
+    // Bob trusts Charlie and Alice, the matching key is found whatever its position
+    let bob_options = StaticKeySecureChannelListenerOptions::new(bob_key)
+        .with_trusted_public_key(charlie_public_key)
+        .with_trusted_public_key(alice_public_key.clone());
+    let bob_listener = secure_channels
+        .create_static_key_secure_channel_listener(ctx, "bob_listener", bob_options)
+        .await?;
+
+    // an initiator using an untrusted static key is rejected
+    let result = secure_channels
+        .create_static_key_secure_channel(
+            ctx,
+            route!["bob_listener"],
+            StaticKeySecureChannelOptions::new(bob_public_key.clone())
+                .with_static_key(eve_key)
+                .with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    let alice_options =
+        StaticKeySecureChannelOptions::new(bob_public_key.clone()).with_static_key(alice_key);
+    let alice_channel = secure_channels
+        .create_static_key_secure_channel(ctx, route!["bob_listener"], alice_options)
+        .await?;
+    assert_eq!(
+        alice_channel.their_identifier(),
+        &Identifier::from_x25519_public_key(&bob_public_key)
+    );
+
+    let mut child_ctx = ctx.new_detached_with_mailboxes(Mailboxes::primary(
+        "child",
+        Arc::new(AllowAll),
+        Arc::new(AllowAll),
+    ))?;
+    ctx.flow_controls()
+        .add_consumer(&"child".into(), bob_listener.flow_control_id());
+    ctx.flow_controls()
+        .add_consumer(&"child".into(), alice_channel.flow_control_id());
+
+    child_ctx
+        .send(
+            route![alice_channel.clone(), child_ctx.primary_address().clone()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = SecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(
+        Identifier::from(local_info.their_identifier()),
+        Identifier::from_x25519_public_key(&alice_public_key)
+    );
+    let return_route = msg.return_route().clone();
+    assert_eq!("Hello, Bob!", msg.into_body()?);
+
+    child_ctx
+        .send(return_route, "Hello, Alice!".to_string())
+        .await?;
+
+    let msg = child_ctx.receive::<String>().await?;
+    let local_info = SecureChannelLocalInfo::find_info(msg.local_message())?;
+    assert_eq!(
+        Identifier::from(local_info.their_identifier()),
+        Identifier::from_x25519_public_key(&bob_public_key)
+    );
+    assert_eq!("Hello, Alice!", msg.into_body()?);
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn test_channel_static_keys_anonymous_initiator(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let vault = secure_channels.vault().secure_channel_vault;
+
+    let bob_key = vault.generate_static_x25519_secret_key().await?;
+    let bob_public_key = vault.get_x25519_public_key(&bob_key).await?;
+    let other_key = vault.generate_static_x25519_secret_key().await?;
+    let other_public_key = vault.get_x25519_public_key(&other_key).await?;
+
+    let bob_listener = secure_channels
+        .create_static_key_secure_channel_listener(
+            ctx,
+            "bob_listener",
+            StaticKeySecureChannelListenerOptions::new(bob_key),
+        )
+        .await?;
+
+    // the initiator must know the listener static key
+    let result = secure_channels
+        .create_static_key_secure_channel(
+            ctx,
+            route!["bob_listener"],
+            StaticKeySecureChannelOptions::new(other_public_key)
+                .with_timeout(Duration::from_millis(500)),
+        )
+        .await;
+    assert!(result.is_err());
+
+    let alice_channel = secure_channels
+        .create_static_key_secure_channel(
+            ctx,
+            route!["bob_listener"],
+            StaticKeySecureChannelOptions::new(bob_public_key),
+        )
+        .await?;
+
+    let mut child_ctx = ctx.new_detached_with_mailboxes(Mailboxes::primary(
+        "child",
+        Arc::new(AllowAll),
+        Arc::new(AllowAll),
+    ))?;
+    ctx.flow_controls()
+        .add_consumer(&"child".into(), bob_listener.flow_control_id());
+
+    child_ctx
+        .send(
+            route![alice_channel.clone(), child_ctx.primary_address().clone()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+
+    let msg = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Bob!", msg.into_body()?);
+
+    Ok(())
+}
+
 #[ockam_macros::test]
 async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels().await?;
@@ -4,7 +4,7 @@ use std::time::Duration;
 use ockam_core::api::Response;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, Any, Routed, SecureChannelLocalInfo, TryClone, Worker};
-use ockam_core::{route, Result};
+use ockam_core::{route, Result, Route};
 use ockam_identity::models::CredentialSchemaIdentifier;
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
@@ -81,6 +81,7 @@ async fn autorefresh(ctx: &mut Context) -> Result<()> {
         Duration::from_secs(0),
         Duration::from_secs(5),
         timing_options,
+        vec![],
     )
     .await?;
 
@@ -166,6 +167,7 @@ async fn init_fail(ctx: &mut Context) -> Result<()> {
         Duration::from_secs(0),
         Duration::from_secs(5),
         timing_options,
+        vec![],
     )
     .await?;
 
@@ -188,6 +190,53 @@ async fn init_fail(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn failover(ctx: &mut Context) -> Result<()> {
+    let timing_options = RemoteCredentialRetrieverTimingOptions {
+        min_refresh_interval: Duration::from_secs(1),
+        proactive_refresh_gap: 1.into(),
+        clock_skew_gap: 0.into(),
+        request_timeout: Duration::from_secs(1),
+        secure_channel_creation_timeout: Duration::from_secs(1),
+    };
+    // The first route of the issuer can't be reached, the credential is retrieved
+    // with its fallback route
+    let res = init(
+        ctx,
+        Duration::from_secs(0),
+        Duration::from_secs(5),
+        timing_options,
+        vec![route!["unreachable_authority_api"]],
+    )
+    .await?;
+
+    let _channel = res
+        .client_secure_channels
+        .create_secure_channel(
+            ctx,
+            &res.client,
+            route!["server_api"],
+            SecureChannelOptions::new()
+                .with_credential_retriever_creator(res.retriever)?
+                .with_authority(res.authority.clone()),
+        )
+        .await?;
+    assert_eq!(res.call_counter.load(Ordering::Relaxed), 1);
+
+    // The credential is then refreshed with the route which was used last
+    ctx.sleep(Duration::from_secs(5)).await;
+    assert_eq!(res.call_counter.load(Ordering::Relaxed), 2);
+    assert!(res
+        .server_secure_channels
+        .identities()
+        .identities_attributes()
+        .get_attributes(&res.client, &res.authority)
+        .await?
+        .is_some());
+
+    Ok(())
+}
+
 #[allow(dead_code)]
 struct InitResult {
     call_counter: Arc<AtomicU64>,
@@ -209,6 +258,7 @@ async fn init(
     delay: Duration,
     ttl: Duration,
     timing_options: RemoteCredentialRetrieverTimingOptions,
+    unreachable_routes: Vec<Route>,
 ) -> Result<InitResult> {
     let tcp = TcpTransport::create(ctx)?;
 
@@ -275,10 +325,7 @@ async fn init(
         ctx.try_clone()?,
         Arc::new(tcp),
         client_secure_channels.clone(),
-        RemoteCredentialRetrieverInfo::create_for_project_member(
-            authority.clone(),
-            route!["authority_api"],
-        ),
+        issuer_info(&authority, unreachable_routes),
         "test".to_string(),
         timing_options,
     ));
@@ -295,3 +342,16 @@ async fn init(
         retriever,
     })
 }
+
+/// Return the information to retrieve credentials from the authority, which is first
+/// tried with some unreachable routes
+fn issuer_info(
+    authority: &Identifier,
+    unreachable_routes: Vec<Route>,
+) -> RemoteCredentialRetrieverInfo {
+    let mut routes = unreachable_routes;
+    routes.push(route!["authority_api"]);
+    let route = routes.remove(0);
+    RemoteCredentialRetrieverInfo::create_for_project_member(authority.clone(), route)
+        .with_fallback_routes(routes)
+}
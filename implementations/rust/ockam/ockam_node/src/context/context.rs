@@ -12,6 +12,8 @@ use ockam_core::{
     async_trait, Address, AddressMetadata, Error, Mailboxes, RelayMessage, Result, TransportType,
 };
 
+#[cfg(feature = "std")]
+use crate::metrics::NodeMetrics;
 use crate::router::Router;
 #[cfg(feature = "std")]
 use core::fmt::{Debug, Formatter};
@@ -71,6 +73,8 @@ pub struct Context {
     /// List of transports used to resolve external addresses to local workers in routes
     pub(super) transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
     pub(super) flow_controls: FlowControls,
+    #[cfg(feature = "std")]
+    pub(super) node_metrics: Arc<NodeMetrics>,
     pub(super) mode: ContextMode,
     #[cfg(feature = "std")]
     pub(super) tracing_context: OpenTelemetryContext,
@@ -137,6 +141,12 @@ impl Context {
         &self.flow_controls
     }
 
+    /// Shared [`NodeMetrics`] instance
+    #[cfg(feature = "std")]
+    pub fn metrics(&self) -> &Arc<NodeMetrics> {
+        &self.node_metrics
+    }
+
     /// Return the tracing context
     #[cfg(feature = "std")]
     pub fn tracing_context(&self) -> OpenTelemetryContext {
@@ -15,6 +15,8 @@ use ockam_core::{
 use ockam_transport_core::Transport;
 
 use crate::channel_types::{message_channel, oneshot_channel, OneshotReceiver};
+#[cfg(feature = "std")]
+use crate::metrics::NodeMetrics;
 use crate::router::Router;
 use crate::{debugger, Context, ContextMode};
 use crate::{relay::CtrlSignal, router::SenderPair};
@@ -71,6 +73,7 @@ impl Context {
         mode: ContextMode,
         transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
         flow_controls: &FlowControls,
+        #[cfg(feature = "std")] node_metrics: Arc<NodeMetrics>,
         #[cfg(feature = "std")] tracing_context: OpenTelemetryContext,
     ) -> (Self, SenderPair, OneshotReceiver<CtrlSignal>) {
         let (mailbox_tx, receiver) = message_channel();
@@ -86,6 +89,8 @@ impl Context {
                 transports,
                 flow_controls: flow_controls.clone(),
                 #[cfg(feature = "std")]
+                node_metrics,
+                #[cfg(feature = "std")]
                 tracing_context,
             },
             SenderPair {
@@ -100,6 +105,7 @@ impl Context {
         runtime_handle: Handle,
         router: Weak<Router>,
         flow_controls: &FlowControls,
+        #[cfg(feature = "std")] node_metrics: Arc<NodeMetrics>,
         #[cfg(feature = "std")] tracing_context: OpenTelemetryContext,
     ) -> (Self, SenderPair, OneshotReceiver<CtrlSignal>) {
         let addr: Address = "app".into();
@@ -116,6 +122,8 @@ impl Context {
             Default::default(),
             flow_controls,
             #[cfg(feature = "std")]
+            node_metrics,
+            #[cfg(feature = "std")]
             tracing_context,
         )
     }
@@ -133,6 +141,8 @@ impl Context {
             self.transports.clone(),
             &self.flow_controls,
             #[cfg(feature = "std")]
+            self.node_metrics.clone(),
+            #[cfg(feature = "std")]
             OpenTelemetryContext::current(),
         )
     }
@@ -284,6 +284,9 @@ impl Context {
             return Ok(());
         }
 
+        #[cfg(feature = "std")]
+        self.record_flow_control_traffic(&relay_msg);
+
         // Send the packed user message with associated route
         sender
             .send(relay_msg)
@@ -360,6 +363,9 @@ impl Context {
             return Ok(());
         }
 
+        #[cfg(feature = "std")]
+        self.record_flow_control_traffic(&relay_msg);
+
         // Forward the message
         sender
             .send(relay_msg)
@@ -368,4 +374,19 @@ impl Context {
 
         Ok(())
     }
+
+    /// Count the messages sent by the producers of a flow control
+    #[cfg(feature = "std")]
+    fn record_flow_control_traffic(&self, relay_msg: &RelayMessage) {
+        if !self.node_metrics.is_enabled() {
+            return;
+        }
+        if let Some(producer) = self
+            .flow_controls
+            .find_flow_control_with_producer_address(relay_msg.source())
+        {
+            self.node_metrics
+                .flow_control_message(producer.flow_control_id(), relay_msg.payload().len());
+        }
+    }
 }
@@ -35,8 +35,9 @@ pub mod compat;
 /// MPSC channel type aliases
 pub mod channel_types;
 
-#[cfg(feature = "metrics")]
-mod metrics;
+/// Node metrics
+#[cfg(feature = "std")]
+pub mod metrics;
 
 /// Api helpers
 pub mod api;
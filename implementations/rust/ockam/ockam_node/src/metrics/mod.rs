@@ -0,0 +1,11 @@
+//! Metrics collected by a node, exported in the OpenMetrics text format.
+
+mod node_metrics;
+mod registry;
+#[cfg(feature = "metrics")]
+mod runtime;
+
+pub use node_metrics::*;
+pub use registry::*;
+#[cfg(feature = "metrics")]
+pub(crate) use runtime::*;
@@ -0,0 +1,252 @@
+use crate::metrics::{
+    encode_openmetrics, CounterFamily, GaugeFamily, HistogramFamily, MetricFamily,
+    DURATION_BUCKETS, SIZE_BUCKETS,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+use ockam_core::Address;
+
+/// Metrics collected by the workers of a node.
+///
+/// The metrics are shared by all the contexts of a node, see [`Context::metrics`](crate::Context::metrics),
+/// and can be exported in the OpenMetrics text format with [`NodeMetrics::encode`].
+///
+/// The metrics recorded for each message (routed messages, flow control and portal traffic)
+/// are only collected once they are enabled with [`NodeMetrics::enable`], so that nodes
+/// which don't export their metrics don't pay for them.
+#[derive(Debug)]
+pub struct NodeMetrics {
+    /// True if the metrics recorded for each message are collected
+    enabled: AtomicBool,
+    /// Messages routed to a worker, by primary address of the worker
+    pub messages_routed: CounterFamily,
+    /// Messages sent by the producers of a flow control
+    pub flow_control_messages: CounterFamily,
+    /// Payload bytes sent by the producers of a flow control
+    pub flow_control_bytes: CounterFamily,
+    /// Secure channel handshakes, by role and result
+    pub secure_channel_handshakes: CounterFamily,
+    /// Duration of successful secure channel handshakes, by role
+    pub secure_channel_handshake_duration: HistogramFamily,
+    /// Bytes transferred by portals, by portal type and direction
+    pub portal_bytes: CounterFamily,
+    /// Size of the payloads transferred by portals, by portal type and direction
+    pub portal_payload_size: HistogramFamily,
+    /// Connections established by transports, by transport and direction.
+    /// For UDP, a connection is a bound socket
+    pub connections: CounterFamily,
+    /// Connections currently open, by transport
+    pub open_connections: GaugeFamily,
+}
+
+impl Default for NodeMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeMetrics {
+    /// Create a new set of metrics
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            messages_routed: CounterFamily::new(
+                "ockam_messages_routed",
+                "Number of messages routed to a worker",
+                &["worker"],
+            ),
+            flow_control_messages: CounterFamily::new(
+                "ockam_flow_control_messages",
+                "Number of messages sent by the producers of a flow control",
+                &["flow_control_id"],
+            ),
+            flow_control_bytes: CounterFamily::new(
+                "ockam_flow_control_bytes",
+                "Number of payload bytes sent by the producers of a flow control",
+                &["flow_control_id"],
+            ),
+            secure_channel_handshakes: CounterFamily::new(
+                "ockam_secure_channel_handshakes",
+                "Number of secure channel handshakes",
+                &["role", "result"],
+            ),
+            secure_channel_handshake_duration: HistogramFamily::new(
+                "ockam_secure_channel_handshake_duration_seconds",
+                "Duration of the successful secure channel handshakes",
+                &["role"],
+                DURATION_BUCKETS,
+            ),
+            portal_bytes: CounterFamily::new(
+                "ockam_portal_bytes",
+                "Number of bytes transferred by portals",
+                &["portal", "direction"],
+            ),
+            portal_payload_size: HistogramFamily::new(
+                "ockam_portal_payload_size_bytes",
+                "Size of the payloads transferred by portals",
+                &["portal", "direction"],
+                SIZE_BUCKETS,
+            ),
+            connections: CounterFamily::new(
+                "ockam_transport_connections",
+                "Number of connections established by transports",
+                &["transport", "direction"],
+            ),
+            open_connections: GaugeFamily::new(
+                "ockam_transport_open_connections",
+                "Number of connections currently open",
+                &["transport"],
+            ),
+        }
+    }
+
+    /// Start collecting the metrics recorded for each message
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed)
+    }
+
+    /// Return true if the metrics recorded for each message are collected
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Count a message routed to a worker
+    pub fn message_routed(&self, primary_address: &Address) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.messages_routed
+            .with_labels(&[&primary_address.to_string()])
+            .inc();
+    }
+
+    /// Count a message sent by a producer of a flow control
+    pub fn flow_control_message(&self, flow_control_id: &FlowControlId, payload_size: usize) {
+        if !self.is_enabled() {
+            return;
+        }
+        let flow_control_id = flow_control_id.to_string();
+        self.flow_control_messages
+            .with_labels(&[&flow_control_id])
+            .inc();
+        self.flow_control_bytes
+            .with_labels(&[&flow_control_id])
+            .inc_by(payload_size as u64);
+    }
+
+    /// Count a successful secure channel handshake
+    pub fn handshake_succeeded(&self, role: &str, duration: Duration) {
+        self.secure_channel_handshakes
+            .with_labels(&[role, "success"])
+            .inc();
+        self.secure_channel_handshake_duration
+            .with_labels(&[role])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Count a failed secure channel handshake
+    pub fn handshake_failed(&self, role: &str) {
+        self.secure_channel_handshakes
+            .with_labels(&[role, "failure"])
+            .inc();
+    }
+
+    /// Count bytes transferred by a portal
+    pub fn portal_transfer(&self, portal: PortalType, direction: Direction, size: usize) {
+        if !self.is_enabled() {
+            return;
+        }
+        let labels = [portal.as_str(), direction.as_str()];
+        self.portal_bytes.with_labels(&labels).inc_by(size as u64);
+        self.portal_payload_size
+            .with_labels(&labels)
+            .observe(size as f64);
+    }
+
+    /// Count a new connection of a transport
+    pub fn connection_opened(&self, transport: &str, direction: Direction) {
+        self.connections
+            .with_labels(&[transport, direction.as_str()])
+            .inc();
+        self.open_connections.with_labels(&[transport]).inc();
+    }
+
+    /// Count the closing of a connection of a transport
+    pub fn connection_closed(&self, transport: &str) {
+        self.open_connections.with_labels(&[transport]).dec();
+    }
+
+    /// Remove the metrics specific to a worker when it stops, and the metrics of its
+    /// flow control once it has no more producers.
+    ///
+    /// `producer_flow_control_id` is the flow control of the worker, if it was a producer,
+    /// and `flow_controls` must already be cleaned up for the worker address.
+    pub(crate) fn cleanup_address(
+        &self,
+        primary_address: &Address,
+        producer_flow_control_id: Option<&FlowControlId>,
+        flow_controls: &FlowControls,
+    ) {
+        self.messages_routed.remove(&[&primary_address.to_string()]);
+        if let Some(flow_control_id) = producer_flow_control_id {
+            if !flow_controls.has_producers(flow_control_id) {
+                let flow_control_id = flow_control_id.to_string();
+                self.flow_control_messages.remove(&[&flow_control_id]);
+                self.flow_control_bytes.remove(&[&flow_control_id]);
+            }
+        }
+    }
+
+    /// Encode all the metrics in the OpenMetrics text format
+    pub fn encode(&self) -> String {
+        encode_openmetrics(&[
+            MetricFamily::Counter(&self.messages_routed),
+            MetricFamily::Counter(&self.flow_control_messages),
+            MetricFamily::Counter(&self.flow_control_bytes),
+            MetricFamily::Counter(&self.secure_channel_handshakes),
+            MetricFamily::Histogram(&self.secure_channel_handshake_duration),
+            MetricFamily::Counter(&self.portal_bytes),
+            MetricFamily::Histogram(&self.portal_payload_size),
+            MetricFamily::Counter(&self.connections),
+            MetricFamily::Gauge(&self.open_connections),
+        ])
+    }
+}
+
+/// Type of portal transferring bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalType {
+    /// A portal inlet
+    Inlet,
+    /// A portal outlet
+    Outlet,
+}
+
+impl PortalType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PortalType::Inlet => "inlet",
+            PortalType::Outlet => "outlet",
+        }
+    }
+}
+
+/// Direction of a transfer or a connection, relative to the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from a remote peer, or accepted from a remote peer
+    Incoming,
+    /// Sent to a remote peer, or initiated by the node
+    Outgoing,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Incoming => "in",
+            Direction::Outgoing => "out",
+        }
+    }
+}
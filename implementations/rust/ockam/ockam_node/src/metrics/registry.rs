@@ -0,0 +1,439 @@
+use core::fmt::Write;
+use core::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+
+/// Default buckets of histograms measuring durations, in seconds
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Default buckets of histograms measuring sizes, in bytes
+pub const SIZE_BUCKETS: &[f64] = &[
+    64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0,
+];
+
+/// Name, help text and label names of a metric family
+#[derive(Debug, Clone)]
+struct Descriptor {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+}
+
+impl Descriptor {
+    fn write_header(&self, metric_type: &str, output: &mut String) {
+        let _ = writeln!(output, "# TYPE {} {metric_type}", self.name);
+        let _ = writeln!(output, "# HELP {} {}", self.name, self.help);
+    }
+
+    /// Write the labels of a sample, with an optional additional label
+    fn write_labels(&self, values: &[String], extra: Option<(&str, &str)>, output: &mut String) {
+        let labels: Vec<(&str, &str)> = self
+            .label_names
+            .iter()
+            .zip(values.iter())
+            .map(|(name, value)| (*name, value.as_str()))
+            .chain(extra)
+            .collect();
+        if labels.is_empty() {
+            return;
+        }
+        output.push('{');
+        for (i, (name, value)) in labels.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+            let _ = write!(output, "{name}=\"{}\"", escape(value));
+        }
+        output.push('}');
+    }
+}
+
+/// Escape a label value as specified by the OpenMetrics text format
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format a floating point value, using the OpenMetrics notation for infinity
+fn format_float(value: f64) -> String {
+    if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        let mut formatted = value.to_string();
+        if !formatted.contains('.') && !formatted.contains('e') {
+            formatted.push_str(".0");
+        }
+        formatted
+    }
+}
+
+/// A set of metrics sharing a name and distinguished by the values of their labels
+#[derive(Debug)]
+struct Family<M> {
+    descriptor: Descriptor,
+    metrics: RwLock<BTreeMap<Vec<String>, Arc<M>>>,
+}
+
+impl<M: Default> Family<M> {
+    fn new(name: &'static str, help: &'static str, label_names: &'static [&'static str]) -> Self {
+        Self {
+            descriptor: Descriptor {
+                name,
+                help,
+                label_names,
+            },
+            metrics: Default::default(),
+        }
+    }
+
+    fn get_or_create(&self, label_values: &[&str], create: impl FnOnce() -> M) -> Arc<M> {
+        debug_assert_eq!(label_values.len(), self.descriptor.label_names.len());
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        if let Some(metric) = self.metrics.read().unwrap().get(&key) {
+            return metric.clone();
+        }
+        self.metrics
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Arc::new(create()))
+            .clone()
+    }
+
+    fn remove(&self, label_values: &[&str]) {
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        self.metrics.write().unwrap().remove(&key);
+    }
+
+    fn snapshot(&self) -> Vec<(Vec<String>, Arc<M>)> {
+        self.metrics
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+/// A monotonically increasing value
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: AtomicU64,
+}
+
+impl Counter {
+    /// Increment the counter by 1
+    pub fn inc(&self) {
+        self.inc_by(1)
+    }
+
+    /// Increment the counter by some value
+    pub fn inc_by(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Return the current value of the counter
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// A family of counters, one per combination of label values
+#[derive(Debug)]
+pub struct CounterFamily(Family<Counter>);
+
+impl CounterFamily {
+    /// Create a new family of counters.
+    /// The name must not include the `_total` suffix, which is added to the samples
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self(Family::new(name, help, label_names))
+    }
+
+    /// Return the counter with some label values, in the order of the label names
+    pub fn with_labels(&self, label_values: &[&str]) -> Arc<Counter> {
+        self.0.get_or_create(label_values, Counter::default)
+    }
+
+    /// Remove the counter with some label values
+    pub fn remove(&self, label_values: &[&str]) {
+        self.0.remove(label_values)
+    }
+
+    fn encode(&self, output: &mut String) {
+        let descriptor = &self.0.descriptor;
+        descriptor.write_header("counter", output);
+        for (values, counter) in self.0.snapshot() {
+            let _ = write!(output, "{}_total", descriptor.name);
+            descriptor.write_labels(&values, None, output);
+            let _ = writeln!(output, " {}", counter.get());
+        }
+    }
+}
+
+/// A value which can go up and down
+#[derive(Debug, Default)]
+pub struct Gauge {
+    value: AtomicI64,
+}
+
+impl Gauge {
+    /// Increment the gauge by 1
+    pub fn inc(&self) {
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Decrement the gauge by 1
+    pub fn dec(&self) {
+        self.value.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Set the value of the gauge
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    /// Return the current value of the gauge
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// A family of gauges, one per combination of label values
+#[derive(Debug)]
+pub struct GaugeFamily(Family<Gauge>);
+
+impl GaugeFamily {
+    /// Create a new family of gauges
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self(Family::new(name, help, label_names))
+    }
+
+    /// Return the gauge with some label values, in the order of the label names
+    pub fn with_labels(&self, label_values: &[&str]) -> Arc<Gauge> {
+        self.0.get_or_create(label_values, Gauge::default)
+    }
+
+    fn encode(&self, output: &mut String) {
+        let descriptor = &self.0.descriptor;
+        descriptor.write_header("gauge", output);
+        for (values, gauge) in self.0.snapshot() {
+            let _ = write!(output, "{}", descriptor.name);
+            descriptor.write_labels(&values, None, output);
+            let _ = writeln!(output, " {}", gauge.get());
+        }
+    }
+}
+
+/// A distribution of observed values, counted in buckets
+#[derive(Debug)]
+pub struct Histogram {
+    /// Upper bounds of the buckets, in increasing order
+    bounds: &'static [f64],
+    /// Number of observations per bucket, the last bucket counting the values above all bounds
+    buckets: Vec<AtomicU64>,
+    /// Sum of the observations, stored as the bits of a f64
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    /// Record an observation
+    pub fn observe(&self, value: f64) {
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+                Some((f64::from_bits(sum) + value).to_bits())
+            });
+    }
+
+    /// Return the number of observations
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Return the sum of the observations
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+/// A family of histograms, one per combination of label values
+#[derive(Debug)]
+pub struct HistogramFamily {
+    family: Family<Histogram>,
+    bounds: &'static [f64],
+}
+
+impl HistogramFamily {
+    /// Create a new family of histograms with the upper bounds of their buckets
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        bounds: &'static [f64],
+    ) -> Self {
+        Self {
+            family: Family::new(name, help, label_names),
+            bounds,
+        }
+    }
+
+    /// Return the histogram with some label values, in the order of the label names
+    pub fn with_labels(&self, label_values: &[&str]) -> Arc<Histogram> {
+        self.family
+            .get_or_create(label_values, || Histogram::new(self.bounds))
+    }
+
+    fn encode(&self, output: &mut String) {
+        let descriptor = &self.family.descriptor;
+        descriptor.write_header("histogram", output);
+        for (values, histogram) in self.family.snapshot() {
+            let mut cumulative = 0;
+            let bounds = histogram
+                .bounds
+                .iter()
+                .cloned()
+                .chain(core::iter::once(f64::INFINITY));
+            for (bound, bucket) in bounds.zip(histogram.buckets.iter()) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let _ = write!(output, "{}_bucket", descriptor.name);
+                descriptor.write_labels(&values, Some(("le", &format_float(bound))), output);
+                let _ = writeln!(output, " {cumulative}");
+            }
+            let _ = write!(output, "{}_count", descriptor.name);
+            descriptor.write_labels(&values, None, output);
+            let _ = writeln!(output, " {cumulative}");
+            let _ = write!(output, "{}_sum", descriptor.name);
+            descriptor.write_labels(&values, None, output);
+            let _ = writeln!(output, " {}", format_float(histogram.sum()));
+        }
+    }
+}
+
+/// A reference to a family of metrics
+#[derive(Debug, Clone, Copy)]
+pub enum MetricFamily<'a> {
+    /// A family of counters
+    Counter(&'a CounterFamily),
+    /// A family of gauges
+    Gauge(&'a GaugeFamily),
+    /// A family of histograms
+    Histogram(&'a HistogramFamily),
+}
+
+/// Content type of the OpenMetrics text format
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encode families of metrics in the OpenMetrics text format
+pub fn encode_openmetrics(families: &[MetricFamily<'_>]) -> String {
+    let mut output = String::new();
+    for family in families {
+        match family {
+            MetricFamily::Counter(f) => f.encode(&mut output),
+            MetricFamily::Gauge(f) => f.encode(&mut output),
+            MetricFamily::Histogram(f) => f.encode(&mut output),
+        }
+    }
+    output.push_str("# EOF\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_counters_and_gauges() {
+        let counter = CounterFamily::new("ockam_test_messages", "Test messages", &["worker"]);
+        counter.with_labels(&["a\"b"]).inc_by(3);
+        counter.with_labels(&["c"]).inc();
+        counter.with_labels(&["c"]).inc();
+        let gauge = GaugeFamily::new("ockam_test_connections", "Test connections", &[]);
+        gauge.with_labels(&[]).inc();
+
+        let output =
+            encode_openmetrics(&[MetricFamily::Counter(&counter), MetricFamily::Gauge(&gauge)]);
+        assert_eq!(
+            output,
+            "# TYPE ockam_test_messages counter\n\
+             # HELP ockam_test_messages Test messages\n\
+             ockam_test_messages_total{worker=\"a\\\"b\"} 3\n\
+             ockam_test_messages_total{worker=\"c\"} 2\n\
+             # TYPE ockam_test_connections gauge\n\
+             # HELP ockam_test_connections Test connections\n\
+             ockam_test_connections 1\n\
+             # EOF\n"
+        );
+
+        counter.remove(&["c"]);
+        assert!(!encode_openmetrics(&[MetricFamily::Counter(&counter)]).contains("\"c\""));
+    }
+
+    #[test]
+    fn encode_histograms() {
+        let histogram = HistogramFamily::new(
+            "ockam_test_duration_seconds",
+            "Test durations",
+            &["role"],
+            &[0.1, 1.0],
+        );
+        let h = histogram.with_labels(&["initiator"]);
+        h.observe(0.05);
+        h.observe(0.5);
+        h.observe(2.0);
+        assert_eq!(h.count(), 3);
+
+        let output = encode_openmetrics(&[MetricFamily::Histogram(&histogram)]);
+        assert_eq!(
+            output,
+            "# TYPE ockam_test_duration_seconds histogram\n\
+             # HELP ockam_test_duration_seconds Test durations\n\
+             ockam_test_duration_seconds_bucket{role=\"initiator\",le=\"0.1\"} 1\n\
+             ockam_test_duration_seconds_bucket{role=\"initiator\",le=\"1.0\"} 2\n\
+             ockam_test_duration_seconds_bucket{role=\"initiator\",le=\"+Inf\"} 3\n\
+             ockam_test_duration_seconds_count{role=\"initiator\"} 3\n\
+             ockam_test_duration_seconds_sum{role=\"initiator\"} 2.55\n\
+             # EOF\n"
+        );
+    }
+}
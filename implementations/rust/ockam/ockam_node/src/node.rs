@@ -139,6 +139,8 @@ impl NodeBuilder {
             Arc::downgrade(&router),
             &flow_controls,
             #[cfg(feature = "std")]
+            router.node_metrics(),
+            #[cfg(feature = "std")]
             OpenTelemetryContext::current(),
         );
 
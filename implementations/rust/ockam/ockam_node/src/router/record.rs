@@ -1,5 +1,7 @@
 use crate::channel_types::{oneshot_channel, MessageSender, OneshotReceiver, OneshotSender};
 use crate::error::NodeError;
+#[cfg(feature = "std")]
+use crate::metrics::NodeMetrics;
 use crate::relay::CtrlSignal;
 use crate::{WorkerReason, WorkerShutdownPriority};
 use core::default::Default;
@@ -46,6 +48,9 @@ pub struct InternalMap {
     shutdown_yield_sender: SyncMutex<Option<OneshotSender<()>>>,
     /// Access to [`FlowControls`] to clean resources
     flow_controls: FlowControls,
+    /// Metrics of the messages routed to each worker
+    #[cfg(feature = "std")]
+    node_metrics: Arc<NodeMetrics>,
     /// Metrics collection and sharing
     #[cfg(feature = "metrics")]
     metrics: (Arc<AtomicUsize>, Arc<AtomicUsize>),
@@ -57,6 +62,8 @@ impl InternalMap {
         let aliases = self.address_maps.aliases.read().unwrap();
 
         let address_record = if let Some(primary_address) = aliases.get(addr) {
+            #[cfg(feature = "std")]
+            self.node_metrics.message_routed(primary_address);
             records.get(primary_address)
         } else {
             trace!("Resolving worker address '{addr}'... FAILED; no such alias");
@@ -86,13 +93,18 @@ impl InternalMap {
 }
 
 impl InternalMap {
-    pub(super) fn new(flow_controls: &FlowControls) -> Self {
+    pub(super) fn new(
+        flow_controls: &FlowControls,
+        #[cfg(feature = "std")] node_metrics: Arc<NodeMetrics>,
+    ) -> Self {
         Self {
             address_maps: Default::default(),
             stopping: Default::default(),
             stopping_shutdown: Default::default(),
             shutdown_yield_sender: Default::default(),
             flow_controls: flow_controls.clone(),
+            #[cfg(feature = "std")]
+            node_metrics,
             #[cfg(feature = "metrics")]
             metrics: Default::default(),
         }
@@ -121,7 +133,17 @@ impl InternalMap {
 
         debug!(%address, %primary_address, "stopping address");
 
+        #[cfg(feature = "std")]
+        let producer = self
+            .flow_controls
+            .get_flow_control_with_producer(&primary_address);
         self.flow_controls.cleanup_address(&primary_address);
+        #[cfg(feature = "std")]
+        self.node_metrics.cleanup_address(
+            &primary_address,
+            producer.as_ref().map(|p| p.flow_control_id()),
+            &self.flow_controls,
+        );
 
         let record = if let Some(record) = records.remove(&primary_address) {
             record
@@ -3,11 +3,15 @@ use core::sync::atomic::AtomicUsize;
 
 use super::record::InternalMap;
 use crate::channel_types::{MessageSender, OneshotSender};
+#[cfg(feature = "std")]
+use crate::metrics::NodeMetrics;
 use crate::relay::CtrlSignal;
 use crate::{NodeError, NodeReason};
 use alloc::vec::Vec;
 use ockam_core::compat::collections::hash_map::Entry;
 use ockam_core::compat::collections::HashMap;
+#[cfg(feature = "std")]
+use ockam_core::compat::sync::Arc;
 use ockam_core::compat::sync::RwLock as SyncRwLock;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControls;
@@ -49,6 +53,9 @@ pub struct Router {
     pub(super) map: InternalMap,
     /// Externally registered router components
     pub(super) external: SyncRwLock<HashMap<TransportType, Address>>,
+    /// Metrics shared by all the workers of the node
+    #[cfg(feature = "std")]
+    pub(super) node_metrics: Arc<NodeMetrics>,
     #[cfg(feature = "std")]
     pub(super) shutdown_broadcast_sender: SyncRwLock<Option<tokio::sync::broadcast::Sender<()>>>,
 }
@@ -65,16 +72,30 @@ impl Router {
     pub fn new(flow_controls: &FlowControls) -> Self {
         #[cfg(feature = "std")]
         let (shutdown_broadcast_sender, _) = tokio::sync::broadcast::channel(1);
+        #[cfg(feature = "std")]
+        let node_metrics = Arc::new(NodeMetrics::new());
 
         Self {
             state: RouterState::Running.into(),
-            map: InternalMap::new(flow_controls),
+            map: InternalMap::new(
+                flow_controls,
+                #[cfg(feature = "std")]
+                node_metrics.clone(),
+            ),
             external: Default::default(),
             #[cfg(feature = "std")]
+            node_metrics,
+            #[cfg(feature = "std")]
             shutdown_broadcast_sender: SyncRwLock::new(Some(shutdown_broadcast_sender)),
         }
     }
 
+    /// Return the metrics of the node
+    #[cfg(feature = "std")]
+    pub fn node_metrics(&self) -> Arc<NodeMetrics> {
+        self.node_metrics.clone()
+    }
+
     pub fn list_workers(&self) -> Vec<Address> {
         self.map.list_workers()
     }
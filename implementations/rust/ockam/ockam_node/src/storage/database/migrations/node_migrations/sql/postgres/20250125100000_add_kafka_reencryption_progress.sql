@@ -0,0 +1,11 @@
+-- This table stores the progress of the jobs re-encrypting the records of a Kafka topic into another topic
+CREATE TABLE kafka_reencryption_progress
+(
+    job_name          TEXT    NOT NULL, -- Name of the re-encryption job
+    source_topic      TEXT    NOT NULL, -- Topic where the records are consumed
+    destination_topic TEXT    NOT NULL, -- Topic where the re-encrypted records are produced
+    partition_index   INTEGER NOT NULL, -- Partition of the source topic
+    next_offset       BIGINT  NOT NULL  -- Offset of the next record to re-encrypt in the source partition
+);
+
+CREATE UNIQUE INDEX kafka_reencryption_progress_index ON kafka_reencryption_progress (job_name, partition_index);
@@ -0,0 +1,4 @@
+-- A vault can store its signing keys in a PKCS#11 token, identified by the path of the
+-- PKCS#11 module and a slot. The PIN of the token is never stored
+ALTER TABLE vault ADD COLUMN pkcs11_module TEXT NULL;
+ALTER TABLE vault ADD COLUMN pkcs11_slot BIGINT NULL;
@@ -0,0 +1,18 @@
+-- This table stores the accounts created on ACME servers to obtain TLS certificates
+CREATE TABLE acme_account
+(
+    directory_url TEXT PRIMARY KEY, -- URL of the ACME server directory
+    credentials   TEXT NOT NULL     -- JSON serialization of the account credentials, including its private key
+);
+
+-- This table stores the TLS certificates obtained from ACME servers
+CREATE TABLE acme_certificate
+(
+    directory_url   TEXT    NOT NULL, -- URL of the ACME server directory
+    domain          TEXT    NOT NULL, -- Domain name of the certificate
+    full_chain_pem  BYTEA   NOT NULL, -- Certificate chain, in PEM format
+    private_key_pem BYTEA   NOT NULL, -- Private key of the certificate, in PEM format
+    issued_at       BIGINT  NOT NULL  -- Time when the certificate was obtained
+);
+
+CREATE UNIQUE INDEX acme_certificate_index ON acme_certificate (directory_url, domain);
@@ -0,0 +1,16 @@
+-- Parameters which can be supplied by the enrollee to complete the attributes of a token.
+-- This is a serialized map of parameter names to the regular expressions validating their values
+ALTER TABLE authority_enrollment_token ADD COLUMN parameters BYTEA;
+
+-- This table stores the identities which redeemed an enrollment token.
+-- The redemptions are kept after the token has been used up, expired or revoked
+CREATE TABLE authority_enrollment_token_redemption
+(
+    reference   TEXT    NOT NULL, -- Reference of the redeemed enrollment token
+    issued_by   TEXT    NOT NULL, -- Identifier of the enroller who issued the enrollment token
+    redeemed_by TEXT    NOT NULL, -- Identifier of the identity which became a member
+    redeemed_at BIGINT  NOT NULL, -- Time of the redemption
+    attributes  BYTEA   NOT NULL  -- Serialized attributes assigned to the member
+);
+
+CREATE INDEX authority_enrollment_token_redemption_index ON authority_enrollment_token_redemption (reference, redeemed_at);
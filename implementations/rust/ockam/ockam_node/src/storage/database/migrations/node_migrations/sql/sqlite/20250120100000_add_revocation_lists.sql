@@ -0,0 +1,43 @@
+-- This table stores the latest revocation list received from each authority
+CREATE TABLE revocation_list
+(
+    authority_identifier TEXT    NOT NULL, -- Identifier of the authority which signed the revocation list
+    version              INTEGER NOT NULL, -- Version of the revocation list, increased by the authority for each new revocation
+    revocation_list      BLOB    NOT NULL, -- Signed revocation list, as received from the authority
+    node_name            TEXT    NOT NULL  -- Node name to isolate revocation lists that each node knows about
+);
+
+CREATE UNIQUE INDEX revocation_list_authority_index ON revocation_list (authority_identifier, node_name);
+
+-- This table stores the entries of the revocation lists for a fast lookup
+CREATE TABLE revoked_credential
+(
+    authority_identifier TEXT NOT NULL, -- Identifier of the authority which revoked the credential
+    subject_identifier   TEXT NOT NULL, -- Identifier of the credential subject
+    credential_hash      TEXT,          -- Hash of the revoked credential. NULL if all the subject credentials are revoked
+    node_name            TEXT NOT NULL  -- Node name to isolate revocation lists that each node knows about
+);
+
+CREATE INDEX revoked_credential_subject_index ON revoked_credential (authority_identifier, subject_identifier, node_name);
+CREATE INDEX revoked_credential_hash_index ON revoked_credential (authority_identifier, credential_hash, node_name);
+
+-- This table stores the revocations made by an authority node
+CREATE TABLE authority_revocation
+(
+    authority_id       TEXT    NOT NULL, -- Identifier of the authority
+    subject_identifier TEXT    NOT NULL, -- Identifier of the subject whose credentials are revoked
+    credential_hash    TEXT,             -- Hash of the revoked credential. NULL if all the subject credentials are revoked
+    revoked_by         TEXT    NOT NULL, -- Identifier of the enroller who requested the revocation
+    revoked_at         INTEGER NOT NULL, -- Revocation time
+    version            INTEGER NOT NULL  -- Version of the revocation list which first contains this revocation
+);
+
+CREATE INDEX authority_revocation_subject_index ON authority_revocation (authority_id, subject_identifier);
+
+-- This table stores the current version of the revocation list of an authority node.
+-- The version is increased for each revocation, and when a revocation is lifted
+CREATE TABLE authority_revocation_version
+(
+    authority_id TEXT PRIMARY KEY, -- Identifier of the authority
+    version      INTEGER NOT NULL  -- Current version of the revocation list
+);
@@ -0,0 +1,20 @@
+-- This table stores the messages received by the store-and-forward relays
+-- until they are acknowledged by the node which registered the relay
+CREATE TABLE relay_message
+(
+    relay_name TEXT    NOT NULL, -- Name of the relay
+    sequence   INTEGER NOT NULL, -- Sequence number of the message for this relay
+    delivery   BLOB    NOT NULL, -- Serialized message, with its onward and return routes
+    size       INTEGER NOT NULL, -- Size of the message payload
+    stored_at  INTEGER NOT NULL  -- Time when the message was received
+);
+
+CREATE UNIQUE INDEX relay_message_index ON relay_message (relay_name, sequence);
+
+-- This table stores the last sequence number assigned by each store-and-forward relay,
+-- so that the sequence numbers are not reused once all the messages are acknowledged
+CREATE TABLE relay_sequence
+(
+    relay_name    TEXT    PRIMARY KEY, -- Name of the relay
+    last_sequence INTEGER NOT NULL     -- Last sequence number assigned to a message of this relay
+);
@@ -0,0 +1,18 @@
+-- This table stores the identity which last registered each relay
+CREATE TABLE relay_owner
+(
+    relay_name TEXT NOT NULL PRIMARY KEY, -- Name of the relay, including the relay service prefix
+    identifier TEXT NOT NULL              -- Identifier of the identity which registered the relay
+);
+
+-- This table stores the audit log of the relay registrations
+CREATE TABLE relay_audit_event
+(
+    relay_name          TEXT    NOT NULL, -- Name of the relay, including the relay service prefix
+    kind                TEXT    NOT NULL, -- claimed, stolen or denied
+    identifier          TEXT,             -- Identifier of the identity registering the relay, if authenticated
+    previous_identifier TEXT,             -- Identifier of the previous owner of a stolen relay
+    created_at          INTEGER NOT NULL  -- Time of the event
+);
+
+CREATE INDEX relay_audit_event_index ON relay_audit_event (relay_name, created_at);
@@ -0,0 +1,2 @@
+-- Serialized additional targets and load balancing settings of a load balanced TCP outlet
+ALTER TABLE tcp_outlet_status ADD COLUMN load_balancing BLOB;
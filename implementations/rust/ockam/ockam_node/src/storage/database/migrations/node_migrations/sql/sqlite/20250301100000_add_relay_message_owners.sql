@@ -0,0 +1,7 @@
+-- This table stores the identity which registered each store-and-forward relay.
+-- The messages stored for a relay are only delivered to that identity
+CREATE TABLE relay_message_owner
+(
+    relay_name TEXT NOT NULL PRIMARY KEY, -- Name of the relay
+    identifier TEXT                       -- Identifier of the registering identity, if authenticated
+);
@@ -0,0 +1,4 @@
+-- True if the subject was an enroller when all its credentials were revoked.
+-- Only an admin can lift the revocation of an enroller
+ALTER TABLE authority_revocation
+    ADD COLUMN subject_is_enroller INTEGER NOT NULL DEFAULT 0;
@@ -288,6 +288,11 @@ impl SqlxDatabase {
         )
     }
 
+    /// Check that the database is reachable by executing a trivial query
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&*self.pool).await.void()
+    }
+
     async fn create_at(configuration: &DatabaseConfiguration) -> Result<Self> {
         // Creates database file if it doesn't exist
         let pool = Self::create_connection_pool(configuration).await?;
@@ -0,0 +1,96 @@
+use ockam_core::flow_control::FlowControls;
+use ockam_core::{route, Address, DenyAll, Result};
+use ockam_node::{Context, NullWorker, WorkerBuilder};
+use std::time::Duration;
+
+#[ockam_macros::test]
+async fn count_routed_messages(ctx: &mut Context) -> Result<()> {
+    ctx.metrics().enable();
+    WorkerBuilder::new(NullWorker)
+        .with_address("null")
+        .start(ctx)?;
+
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    ctx.flow_controls()
+        .add_producer(ctx.primary_address(), &flow_control_id, None, vec![]);
+
+    ctx.send(route!["null"], "hello".to_string()).await?;
+    ctx.send(route!["null"], "world".to_string()).await?;
+
+    let metrics = ctx.metrics();
+    assert_eq!(metrics.messages_routed.with_labels(&["0#null"]).get(), 2);
+    assert_eq!(
+        metrics
+            .flow_control_messages
+            .with_labels(&[&flow_control_id.to_string()])
+            .get(),
+        2
+    );
+    assert!(
+        metrics
+            .flow_control_bytes
+            .with_labels(&[&flow_control_id.to_string()])
+            .get()
+            > 0
+    );
+
+    let encoded = metrics.encode();
+    assert!(encoded.contains("ockam_messages_routed_total{worker=\"0#null\"} 2\n"));
+    assert!(encoded.ends_with("# EOF\n"));
+
+    // the metrics of a worker are removed when it stops
+    ctx.stop_address(&Address::from("null"))?;
+    ctx.sleep(Duration::from_millis(10)).await;
+    assert!(!ctx.metrics().encode().contains("0#null"));
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn remove_flow_control_metrics(ctx: &mut Context) -> Result<()> {
+    ctx.metrics().enable();
+    WorkerBuilder::new(NullWorker)
+        .with_address("null")
+        .start(ctx)?;
+
+    let producer = Address::from_string("producer");
+    let producer_ctx = ctx.new_detached(producer.clone(), DenyAll, DenyAll)?;
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    ctx.flow_controls()
+        .add_producer(&producer, &flow_control_id, None, vec![]);
+
+    producer_ctx
+        .send(route!["null"], "hello".to_string())
+        .await?;
+    assert!(ctx
+        .metrics()
+        .encode()
+        .contains(&flow_control_id.to_string()));
+
+    // the metrics of a flow control are removed when its producer stops
+    ctx.stop_address(&producer)?;
+    ctx.sleep(Duration::from_millis(10)).await;
+    assert!(!ctx
+        .metrics()
+        .encode()
+        .contains(&flow_control_id.to_string()));
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn disabled_metrics(ctx: &mut Context) -> Result<()> {
+    WorkerBuilder::new(NullWorker)
+        .with_address("null")
+        .start(ctx)?;
+
+    let flow_control_id = FlowControls::generate_flow_control_id();
+    ctx.flow_controls()
+        .add_producer(ctx.primary_address(), &flow_control_id, None, vec![]);
+    ctx.send(route!["null"], "hello".to_string()).await?;
+
+    let encoded = ctx.metrics().encode();
+    assert!(!encoded.contains("0#null"));
+    assert!(!encoded.contains(&flow_control_id.to_string()));
+    Ok(())
+}
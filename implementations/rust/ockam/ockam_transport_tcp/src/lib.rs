@@ -30,7 +30,10 @@ pub use options::{TcpConnectionOptions, TcpListenerOptions};
 pub use portal::{
     new_certificate_provider_cache, Direction, PortalInletInterceptor, PortalInterceptor,
     PortalInterceptorFactory, PortalInterceptorWorker, PortalInternalMessage, PortalMessage,
-    PortalOutletInterceptor, TlsCertificate, TlsCertificateProvider,
+    PortalOutletInterceptor, TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletTargetStatus,
+    TcpOutletTargets, TcpOutletTlsConfig, TlsCertificate, TlsCertificateProvider,
+    TlsClientAccessControlFactory, TlsClientAccessControls, TlsClientAuthentication,
+    TlsClientCertificate, TlsPublicKeyPin,
 };
 pub use protocol_version::*;
 pub use registry::*;
@@ -1,8 +1,9 @@
 use core::fmt::Display;
 use ockam_core::Address;
+use ockam_node::metrics;
 
 /// Enumerate all portal types
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub(crate) enum PortalType {
     Inlet,
     Outlet,
@@ -28,6 +29,15 @@ impl PortalType {
     }
 }
 
+impl From<PortalType> for metrics::PortalType {
+    fn from(value: PortalType) -> Self {
+        match value {
+            PortalType::Inlet | PortalType::PrivilegedInlet => metrics::PortalType::Inlet,
+            PortalType::Outlet | PortalType::PrivilegedOutlet => metrics::PortalType::Outlet,
+        }
+    }
+}
+
 impl Display for PortalType {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.str())
@@ -1,8 +1,8 @@
 use crate::portal::addresses::{Addresses, PortalType};
 use crate::portal::tls_certificate::TlsCertificateProvider;
+use crate::portal::TlsClientAuthentication;
 use crate::portal::{InletSharedState, ReadHalfMaybeTls, WriteHalfMaybeTls};
 use crate::{portal::TcpPortalWorker, TcpInlet, TcpInletOptions, TcpRegistry};
-use log::warn;
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::{Arc, RwLock as SyncRwLock};
 use ockam_core::errcode::{Kind, Origin};
@@ -16,7 +16,7 @@ use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::time::Instant;
 use tokio_rustls::{TlsAcceptor, TlsStream};
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, instrument, warn};
 
 /// A TCP Portal Inlet listen processor
 ///
@@ -84,6 +84,7 @@ impl TcpInletListenProcessor {
     async fn create_acceptor(
         context: &Context,
         certificate_provider: &Arc<dyn TlsCertificateProvider>,
+        tls_client_authentication: Option<&TlsClientAuthentication>,
         timeout: Duration,
     ) -> Result<TlsAcceptor> {
         let now = Instant::now();
@@ -142,8 +143,16 @@ impl TcpInletListenProcessor {
                 }
             };
 
-            let config = rustls::ServerConfig::builder()
-                .with_no_client_auth()
+            let config = rustls::ServerConfig::builder();
+            let config = match tls_client_authentication {
+                Some(tls_client_authentication) => config.with_client_cert_verifier(
+                    tls_client_authentication
+                        .client_certificate_verifier
+                        .clone(),
+                ),
+                None => config.with_no_client_auth(),
+            };
+            let config = config
                 .with_single_cert(chain, private_key)
                 .map_err(|error| ockam_core::Error::new(Origin::Transport, Kind::Parse, error))?;
 
@@ -197,16 +206,45 @@ impl Processor for TcpInletListenProcessor {
             inlet_shared_state.route().next()?,
         );
 
+        let mut incoming_access_control = self.options.incoming_access_control.clone();
+        let mut outgoing_access_control = self.options.outgoing_access_control.clone();
+
         let streams = if let Some(certificate_provider) = &self.options.tls_certificate_provider {
-            let (rx, tx) = tokio::io::split(TlsStream::from(
-                Self::create_acceptor(ctx, certificate_provider, DEFAULT_TIMEOUT)
+            let tls_client_authentication = self.options.tls_client_authentication.as_ref();
+            let tls_stream = Self::create_acceptor(
+                ctx,
+                certificate_provider,
+                tls_client_authentication,
+                DEFAULT_TIMEOUT,
+            )
+            .await?
+            .accept(stream)
+            .await
+            .map_err(|error| ockam_core::Error::new(Origin::Transport, Kind::Protocol, error))?;
+
+            if let Some(tls_client_authentication) = tls_client_authentication {
+                match tls_client_authentication
+                    .access_controls(
+                        ctx,
+                        inlet_shared_state.their_identifier(),
+                        tls_stream.get_ref().1.peer_certificates(),
+                        (incoming_access_control, outgoing_access_control),
+                    )
                     .await?
-                    .accept(stream)
-                    .await
-                    .map_err(|error| {
-                        ockam_core::Error::new(Origin::Transport, Kind::Protocol, error)
-                    })?,
-            ));
+                {
+                    Some((incoming, outgoing)) => {
+                        incoming_access_control = incoming;
+                        outgoing_access_control = outgoing;
+                    }
+                    None => {
+                        warn!(%socket_addr, "the TLS client is not authorized, closing the connection");
+                        // Just drop the stream
+                        return Ok(true);
+                    }
+                }
+            }
+
+            let (rx, tx) = tokio::io::split(TlsStream::from(tls_stream));
             (
                 ReadHalfMaybeTls::ReadHalfWithTls(rx),
                 WriteHalfMaybeTls::WriteHalfWithTls(tx),
@@ -227,8 +265,8 @@ impl Processor for TcpInletListenProcessor {
             inlet_shared_state.route().clone(),
             inlet_shared_state.their_identifier(),
             addresses,
-            self.options.incoming_access_control.clone(),
-            self.options.outgoing_access_control.clone(),
+            incoming_access_control,
+            outgoing_access_control,
             self.options.portal_payload_length,
             self.options.skip_handshake,
         )?;
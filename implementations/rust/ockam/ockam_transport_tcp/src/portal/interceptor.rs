@@ -3,12 +3,14 @@ use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl,
 use ockam_core::{
     async_trait, route, Address, AllowOnwardAddress, AllowSourceAddress, Any,
     AnyIncomingAccessControl, AnyOutgoingAccessControl, Encodable, IncomingAccessControl,
-    LocalInfo, LocalMessage, NeutralMessage, OutgoingAccessControl, Route, Routed, Worker,
+    LocalInfo, LocalInfoIdentifier, LocalMessage, NeutralMessage, OutgoingAccessControl, Route,
+    Routed, SecureChannelLocalInfo, Worker,
 };
 use ockam_node::{Context, WorkerBuilder};
+use ockam_transport_core::TransportError;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 /// Direction of the data being intercepted
 #[derive(Clone, Copy, Debug)]
@@ -32,12 +34,51 @@ pub trait PortalInterceptor: 'static + Send + Sync {
         direction: Direction,
         buffer: &[u8],
     ) -> ockam_core::Result<Option<Vec<u8>>>;
+
+    /// This method is called whenever a message is intercepted in either direction.
+    /// It can be implemented by interceptors which need to answer directly to the sender of
+    /// the data, for example to reject a request without forwarding it.
+    /// The first returned buffer is sent to the original destination and the second one is
+    /// sent back to the original sender.
+    ///
+    /// By default, the data is only intercepted with [`PortalInterceptor::intercept`].
+    /// The interceptors returning a reply must also implement [`PortalInterceptor::can_reply`].
+    async fn intercept_with_reply(
+        &self,
+        context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> ockam_core::Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        Ok((self.intercept(context, direction, buffer).await?, None))
+    }
+
+    /// Return true if the interceptor can return a reply with
+    /// [`PortalInterceptor::intercept_with_reply`].
+    /// In that case, each interceptor worker accepts the data sent by the worker intercepting the
+    /// other direction, and forwards it without intercepting it again.
+    ///
+    /// By default, the interceptor doesn't reply.
+    fn can_reply(&self) -> bool {
+        false
+    }
 }
 
 /// Portal Interceptor Factory
 pub trait PortalInterceptorFactory: 'static + Send + Sync {
     /// Create a new instance of a portal interceptor
     fn create(&self) -> Arc<dyn PortalInterceptor>;
+
+    /// Create a new instance of a portal interceptor for a connection initiated by a remote
+    /// identity, when the portal is used over a secure channel.
+    /// This is only used on the outlet side.
+    ///
+    /// By default, the identity is ignored.
+    fn create_for_identifier(
+        &self,
+        _identifier: Option<LocalInfoIdentifier>,
+    ) -> Arc<dyn PortalInterceptor> {
+        self.create()
+    }
 }
 
 /// Portal interceptor for the outlet side
@@ -96,6 +137,9 @@ impl Worker for PortalOutletInterceptor {
         message: Routed<Self::Message>,
     ) -> ockam_core::Result<()> {
         let source_address = message.src_addr().clone();
+        let their_identifier = SecureChannelLocalInfo::find_info(message.local_message())
+            .map(|info| info.their_identifier())
+            .ok();
         let mut message = message.into_local_message();
 
         // Remove our address
@@ -111,7 +155,8 @@ impl Worker for PortalOutletInterceptor {
             self.spawner_flow_control_id.clone(),
             self.incoming_access_control.clone(),
             self.outgoing_access_control.clone(),
-            self.interceptor_factory.create(),
+            self.interceptor_factory
+                .create_for_identifier(their_identifier),
             self.portal_payload_length,
         )?;
 
@@ -244,6 +289,39 @@ pub struct PortalInterceptorWorker {
     interceptor: Arc<dyn PortalInterceptor>,
     direction: Direction,
     portal_payload_length: usize,
+    // payloads exchanged with the portal workers
+    payload_counters: PacketCounters,
+    // replies exchanged with the other interceptor worker
+    reply_counters: PacketCounters,
+}
+
+/// Counters of the payload messages sent and received by an interceptor worker.
+/// Like the portal workers, the worker numbers the payloads it sends, and checks that the
+/// payloads it receives are not lost or reordered.
+#[derive(Default)]
+struct PacketCounters {
+    next_sent: u16,
+    last_received: Option<u16>,
+}
+
+impl PacketCounters {
+    fn next_sent(&mut self) -> u16 {
+        let counter = self.next_sent;
+        self.next_sent = counter.wrapping_add(1);
+        counter
+    }
+
+    fn check_received(&mut self, counter: Option<u16>) -> ockam_core::Result<()> {
+        if let Some(counter) = counter {
+            let expected = self.last_received.map_or(0, |c| c.wrapping_add(1));
+            if counter != expected {
+                warn!("Received packet with counter {counter} while expecting {expected}");
+                return Err(TransportError::RecvBadMessage)?;
+            }
+            self.last_received = Some(counter);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -267,11 +345,33 @@ impl Worker for PortalInterceptorWorker {
         let portal_message = PortalMessage::decode(routed_message.payload())?;
 
         match portal_message {
-            PortalMessage::Payload(message, _) => {
-                let buffer: Option<Vec<u8>> = self
+            PortalMessage::Payload(message, counter)
+                if self.interceptor.can_reply()
+                    && routed_message.src_addr() == &self.other_worker_address =>
+            {
+                self.reply_counters.check_received(counter)?;
+                // the other worker is sending back a reply produced by the interceptor
+                // this data must not be intercepted again
+                self.split_and_send(
+                    context,
+                    onward_route.clone(),
+                    return_route.clone(),
+                    message,
+                    local_info,
+                )
+                .await?;
+            }
+            PortalMessage::Payload(message, counter) => {
+                self.payload_counters.check_received(counter)?;
+                let (buffer, reply) = self
                     .interceptor
-                    .intercept(context, self.direction, message)
+                    .intercept_with_reply(context, self.direction, message)
                     .await?;
+                if let Some(reply) = reply {
+                    trace!("reply of size {} returned by the interceptor", reply.len());
+                    self.send_reply(context, onward_route, return_route, &reply)
+                        .await?;
+                }
                 match buffer {
                     Some(buffer) => {
                         trace!(
@@ -392,6 +492,18 @@ impl PortalInterceptorWorker {
             fixed_onward_route: Some(inlet_instance),
             interceptor: interceptor.clone(),
             portal_payload_length,
+            payload_counters: PacketCounters::default(),
+            reply_counters: PacketCounters::default(),
+        };
+
+        // allow the other worker to send the replies produced by the interceptor
+        let incoming_access_control: Arc<dyn IncomingAccessControl> = if interceptor.can_reply() {
+            Arc::new(AnyIncomingAccessControl::new(vec![
+                Arc::new(AllowSourceAddress(from_inlet_worker_address.clone())),
+                incoming_access_control,
+            ]))
+        } else {
+            incoming_access_control
         };
 
         WorkerBuilder::new(from_outlet_worker)
@@ -400,12 +512,23 @@ impl PortalInterceptorWorker {
             .start(context)?;
 
         let from_inlet_worker = Self {
-            other_worker_address: from_outlet_worker_address,
+            other_worker_address: from_outlet_worker_address.clone(),
             direction: Direction::FromInletToOutlet,
             disconnect_received: disconnect_received.clone(),
             fixed_onward_route: None,
             interceptor: interceptor.clone(),
             portal_payload_length,
+            payload_counters: PacketCounters::default(),
+            reply_counters: PacketCounters::default(),
+        };
+
+        let outgoing_access_control: Arc<dyn OutgoingAccessControl> = if interceptor.can_reply() {
+            Arc::new(AnyOutgoingAccessControl::new(vec![
+                Arc::new(AllowOnwardAddress::new(from_outlet_worker_address)),
+                outgoing_access_control,
+            ]))
+        } else {
+            outgoing_access_control
         };
 
         WorkerBuilder::new(from_inlet_worker)
@@ -459,6 +582,8 @@ impl PortalInterceptorWorker {
             fixed_onward_route: Some(outlet_route),
             interceptor: interceptor.clone(),
             portal_payload_length,
+            payload_counters: PacketCounters::default(),
+            reply_counters: PacketCounters::default(),
         };
         let from_outlet_worker = Self {
             other_worker_address: from_inlet_worker_address.clone(),
@@ -467,6 +592,8 @@ impl PortalInterceptorWorker {
             fixed_onward_route: None,
             interceptor: interceptor.clone(),
             portal_payload_length,
+            payload_counters: PacketCounters::default(),
+            reply_counters: PacketCounters::default(),
         };
 
         let flow_controls = context.flow_controls();
@@ -478,6 +605,23 @@ impl PortalInterceptorWorker {
             vec![],
         );
 
+        let flow_control_outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+            Arc::new(FlowControlOutgoingAccessControl::new(
+                flow_controls,
+                flow_control_id.clone(),
+                spawner_flow_control_id.clone(),
+            ));
+        // allow sending the replies produced by the interceptor to the other worker
+        let request_outgoing_access_control: Arc<dyn OutgoingAccessControl> =
+            if interceptor.can_reply() {
+                Arc::new(AnyOutgoingAccessControl::new(vec![
+                    Arc::new(AllowOnwardAddress::new(from_outlet_worker_address.clone())),
+                    flow_control_outgoing_access_control,
+                ]))
+            } else {
+                flow_control_outgoing_access_control
+            };
+
         // allow the other worker to forward the `pong` message
         WorkerBuilder::new(from_inlet_worker)
             .with_address(from_inlet_worker_address.clone())
@@ -485,11 +629,7 @@ impl PortalInterceptorWorker {
                 Arc::new(AllowSourceAddress(from_outlet_worker_address.clone())),
                 incoming_access_control,
             ])))
-            .with_outgoing_access_control_arc(Arc::new(FlowControlOutgoingAccessControl::new(
-                flow_controls,
-                flow_control_id.clone(),
-                spawner_flow_control_id.clone(),
-            )))
+            .with_outgoing_access_control_arc(request_outgoing_access_control)
             .start(context)?;
 
         // allow forwarding the `pong` message to the other worker
@@ -549,8 +689,35 @@ impl PortalInterceptorWorker {
         context.forward(local_message).await
     }
 
+    /// Send a reply produced by the interceptor back to the sender of the intercepted data.
+    /// The reply is sent via the worker intercepting the other direction, since that worker
+    /// already forwards data to the sender.
+    async fn send_reply(
+        &mut self,
+        context: &mut Context,
+        onward_route: &Route,
+        return_route: &Route,
+        buffer: &[u8],
+    ) -> ockam_core::Result<()> {
+        let reply_onward_route = route![self.other_worker_address.clone()] + return_route.clone();
+        let reply_return_route: Route = onward_route.clone().modify().pop_front().into();
+
+        for chunk in buffer.chunks(self.portal_payload_length) {
+            let message = LocalMessage::new()
+                .with_onward_route(reply_onward_route.clone())
+                .with_return_route(reply_return_route.clone())
+                .with_payload(
+                    PortalMessage::Payload(chunk, Some(self.reply_counters.next_sent()))
+                        .encode()?,
+                );
+
+            context.forward(message).await?;
+        }
+        Ok(())
+    }
+
     async fn split_and_send(
-        &self,
+        &mut self,
         context: &mut Context,
         provided_onward_route: Route,
         provided_return_route: Route,
@@ -576,7 +743,10 @@ impl PortalInterceptorWorker {
             let message = LocalMessage::new()
                 .with_onward_route(onward_route.clone())
                 .with_return_route(return_route.clone())
-                .with_payload(PortalMessage::Payload(chunk, None).encode()?)
+                .with_payload(
+                    PortalMessage::Payload(chunk, Some(self.payload_counters.next_sent()))
+                        .encode()?,
+                )
                 .with_local_info(local_info.to_vec());
 
             context.forward(message).await?;
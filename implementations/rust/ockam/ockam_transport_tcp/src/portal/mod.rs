@@ -3,12 +3,16 @@ mod inlet_listener;
 mod inlet_shared_state;
 mod interceptor;
 pub mod options;
+mod outlet_health_check;
 mod outlet_listener;
 mod outlet_listener_registry;
+mod outlet_targets;
+mod outlet_tls;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
 mod tls_certificate;
+mod tls_client_authentication;
 
 pub(crate) use inlet_listener::*;
 pub(crate) use inlet_shared_state::*;
@@ -16,8 +20,15 @@ pub use interceptor::{
     Direction, PortalInletInterceptor, PortalInterceptor, PortalInterceptorFactory,
     PortalInterceptorWorker, PortalOutletInterceptor,
 };
+pub(crate) use outlet_health_check::*;
 pub(crate) use outlet_listener::*;
+pub(crate) use outlet_targets::TcpOutletTargetLease;
+pub use outlet_targets::{
+    TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletTargetStatus, TcpOutletTargets,
+};
+pub use outlet_tls::*;
 pub use portal_message::*;
 pub(crate) use portal_receiver::*;
 pub(crate) use portal_worker::*;
 pub use tls_certificate::*;
+pub use tls_client_authentication::*;
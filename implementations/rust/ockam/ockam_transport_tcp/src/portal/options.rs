@@ -1,5 +1,8 @@
 use crate::portal::addresses::Addresses;
-use crate::TlsCertificateProvider;
+use crate::{
+    TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletTlsConfig, TlsCertificateProvider,
+    TlsClientAuthentication,
+};
 use ockam_core::compat::sync::Arc;
 use ockam_core::env::get_env_with_default_ignore_error;
 use ockam_core::flow_control::{FlowControlId, FlowControls};
@@ -17,6 +20,7 @@ pub struct TcpInletOptions {
     pub(crate) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
     pub(crate) is_paused: bool,
     pub(crate) tls_certificate_provider: Option<Arc<dyn TlsCertificateProvider>>,
+    pub(crate) tls_client_authentication: Option<TlsClientAuthentication>,
     pub(crate) portal_payload_length: usize,
     pub(crate) skip_handshake: bool,
     pub(crate) enable_nagle: bool,
@@ -30,6 +34,7 @@ impl TcpInletOptions {
             outgoing_access_control: Arc::new(AllowAll),
             is_paused: false,
             tls_certificate_provider: None,
+            tls_client_authentication: None,
             portal_payload_length: read_portal_payload_length(),
             skip_handshake: false,
             enable_nagle: false,
@@ -76,6 +81,16 @@ impl TcpInletOptions {
         self
     }
 
+    /// Require the TLS clients to present a certificate issued by a trusted authority.
+    /// Only used when a TLS certificate provider is set
+    pub fn with_tls_client_authentication(
+        mut self,
+        tls_client_authentication: TlsClientAuthentication,
+    ) -> Self {
+        self.tls_client_authentication = Some(tls_client_authentication);
+        self
+    }
+
     /// Set Incoming Access Control
     pub fn with_incoming_access_control_impl(
         mut self,
@@ -147,10 +162,12 @@ pub struct TcpOutletOptions {
     pub(crate) consumer: Vec<FlowControlId>,
     pub(crate) incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub(crate) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
-    pub(crate) tls: bool,
+    pub(crate) tls: Option<TcpOutletTlsConfig>,
     pub(crate) portal_payload_length: usize,
     pub(crate) skip_handshake: bool,
     pub(crate) enable_nagle: bool,
+    pub(crate) load_balancing: TcpOutletLoadBalancing,
+    pub(crate) health_check: Option<TcpOutletHealthCheck>,
 }
 
 impl TcpOutletOptions {
@@ -160,13 +177,28 @@ impl TcpOutletOptions {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
             outgoing_access_control: Arc::new(AllowAll),
-            tls: false,
+            tls: None,
             portal_payload_length: read_portal_payload_length(),
             skip_handshake: false,
             enable_nagle: false,
+            load_balancing: TcpOutletLoadBalancing::default(),
+            health_check: None,
         }
     }
 
+    /// Set the strategy used to choose the target of a new connection
+    /// when the Outlet has several targets
+    pub fn with_load_balancing(mut self, load_balancing: TcpOutletLoadBalancing) -> Self {
+        self.load_balancing = load_balancing;
+        self
+    }
+
+    /// Periodically check the targets of the Outlet and stop using the unhealthy ones
+    pub fn with_health_check(mut self, health_check: TcpOutletHealthCheck) -> Self {
+        self.health_check = Some(health_check);
+        self
+    }
+
     /// Skip Portal handshake for lower latency, but also lower throughput
     pub fn set_skip_handshake(mut self, skip_handshake: bool) -> Self {
         self.skip_handshake = skip_handshake;
@@ -209,9 +241,16 @@ impl TcpOutletOptions {
         self
     }
 
-    /// Set TLS
+    /// Set TLS, the targets are verified with the system certificates
     pub fn with_tls(mut self, tls: bool) -> Self {
-        self.tls = tls;
+        self.tls = tls.then(TcpOutletTlsConfig::new);
+        self
+    }
+
+    /// Set TLS with a specific configuration: trusted certificate authorities,
+    /// client certificate, server name or pinned public keys
+    pub fn with_tls_config(mut self, tls_config: TcpOutletTlsConfig) -> Self {
+        self.tls = Some(tls_config);
         self
     }
 
@@ -0,0 +1,66 @@
+use crate::portal::{TcpOutletHealthCheck, TcpOutletTargets};
+use crate::transport::connect;
+use ockam_core::{async_trait, Address, DenyAll, Processor, Result};
+use ockam_node::{Context, ProcessorBuilder};
+use tracing::{debug, instrument, warn};
+
+/// Periodically checks that the targets of a TCP Outlet accept connections,
+/// and ejects the targets which don't.
+///
+/// Created by [`TcpOutletListenWorker`](crate::portal::TcpOutletListenWorker) when
+/// a health check is set in the Outlet options, and stopped with the Outlet listener.
+pub(crate) struct TcpOutletHealthCheckProcessor {
+    targets: TcpOutletTargets,
+    health_check: TcpOutletHealthCheck,
+}
+
+impl TcpOutletHealthCheckProcessor {
+    /// Start a new `TcpOutletHealthCheckProcessor`
+    #[instrument(skip_all, name = "TcpOutletHealthCheckProcessor::start")]
+    pub(crate) fn start(
+        ctx: &Context,
+        address: Address,
+        targets: TcpOutletTargets,
+        health_check: TcpOutletHealthCheck,
+    ) -> Result<()> {
+        let processor = Self {
+            targets,
+            health_check,
+        };
+
+        ProcessorBuilder::new(processor)
+            .with_address(address)
+            .with_incoming_access_control(DenyAll)
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Processor for TcpOutletHealthCheckProcessor {
+    type Context = Context;
+
+    #[instrument(skip_all, name = "TcpOutletHealthCheckProcessor::process")]
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        for (index, hostname_port) in self.targets.hostname_ports().into_iter().enumerate() {
+            let reachable = connect(&hostname_port, false, Some(self.health_check.timeout()))
+                .await
+                .is_ok();
+
+            match self
+                .targets
+                .record_health_check(index, reachable, &self.health_check)
+            {
+                Some(false) => warn!(%hostname_port, "outlet target is unhealthy, ejecting it"),
+                Some(true) => debug!(%hostname_port, "outlet target is healthy again"),
+                None => {}
+            }
+        }
+
+        ctx.sleep(self.health_check.interval()).await;
+
+        Ok(true)
+    }
+}
@@ -1,13 +1,14 @@
 use crate::portal::addresses::{Addresses, PortalType};
 use crate::portal::outlet_listener_registry::{MapKey, OutletListenerRegistry};
+use crate::portal::{TcpOutletHealthCheckProcessor, TcpOutletTargets, TcpOutletTlsConnector};
 use crate::{portal::TcpPortalWorker, PortalMessage, TcpOutletOptions, TcpRegistry};
 use ockam_core::{
     async_trait, route, Address, AllowAll, LocalMessage, NeutralMessage, Result, Routed,
     SecureChannelLocalInfo, Worker,
 };
 use ockam_node::{Context, WorkerBuilder};
-use ockam_transport_core::{HostnamePort, TransportError};
-use tracing::{debug, instrument};
+use ockam_transport_core::TransportError;
+use tracing::{debug, instrument, warn};
 
 /// A TCP Portal Outlet listen worker
 ///
@@ -16,20 +17,35 @@ use tracing::{debug, instrument};
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
-    hostname_port: HostnamePort,
+    targets: TcpOutletTargets,
     options: TcpOutletOptions,
+    tls_connector: Option<TcpOutletTlsConnector>,
     outlet_registry: OutletListenerRegistry,
+    health_check_address: Option<Address>,
 }
 
 impl TcpOutletListenWorker {
-    /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, hostname_port: HostnamePort, options: TcpOutletOptions) -> Self {
-        Self {
+    /// Create a new `TcpOutletListenWorker`.
+    /// The TLS connector is created once, so that an invalid TLS configuration is rejected
+    /// when the outlet is created
+    fn new(
+        registry: TcpRegistry,
+        targets: TcpOutletTargets,
+        options: TcpOutletOptions,
+    ) -> Result<Self> {
+        let tls_connector = options
+            .tls
+            .as_ref()
+            .map(|tls| tls.create_connector())
+            .transpose()?;
+        Ok(Self {
             registry,
-            hostname_port,
+            targets,
             options,
+            tls_connector,
             outlet_registry: Default::default(),
-        }
+            health_check_address: None,
+        })
     }
 
     #[instrument(skip_all, name = "TcpOutletListenWorker::start")]
@@ -37,14 +53,16 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
-        hostname_port: HostnamePort,
+        targets: TcpOutletTargets,
         options: TcpOutletOptions,
     ) -> Result<()> {
         let access_control = options.incoming_access_control.clone();
+        let worker = Self::new(registry, targets, options)?;
 
-        options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
+        worker
+            .options
+            .setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, hostname_port, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
@@ -87,6 +105,17 @@ impl Worker for TcpOutletListenWorker {
         self.registry
             .add_outlet_listener_worker(ctx.primary_address());
 
+        if let Some(health_check) = self.options.health_check.clone() {
+            let address = Address::random_tagged("TcpOutletHealthCheckProcessor");
+            TcpOutletHealthCheckProcessor::start(
+                ctx,
+                address.clone(),
+                self.targets.clone(),
+                health_check,
+            )?;
+            self.health_check_address = Some(address);
+        }
+
         Ok(())
     }
 
@@ -95,6 +124,10 @@ impl Worker for TcpOutletListenWorker {
         self.registry
             .remove_outlet_listener_worker(ctx.primary_address());
 
+        if let Some(address) = self.health_check_address.take() {
+            let _ = ctx.stop_address(&address);
+        }
+
         Ok(())
     }
 
@@ -138,14 +171,19 @@ impl Worker for TcpOutletListenWorker {
             }
         }
 
+        let Some(target) = self.targets.select(their_identifier.as_ref()) else {
+            warn!("all the targets of the outlet are unhealthy, rejecting the connection");
+            return Err(TransportError::PeerNotFound)?;
+        };
+
         let addresses = Addresses::generate(PortalType::Outlet);
 
         if self.options.skip_handshake {
             TcpPortalWorker::start_new_outlet_no_handshake(
                 ctx,
                 self.registry.clone(),
-                self.hostname_port.clone(),
-                self.options.tls,
+                target,
+                self.tls_connector.clone(),
                 msg.return_route.clone(),
                 their_identifier,
                 addresses.clone(),
@@ -174,8 +212,8 @@ impl Worker for TcpOutletListenWorker {
             TcpPortalWorker::start_new_outlet(
                 ctx,
                 self.registry.clone(),
-                self.hostname_port.clone(),
-                self.options.tls,
+                target,
+                self.tls_connector.clone(),
                 msg.return_route.clone(),
                 their_identifier,
                 addresses.clone(),
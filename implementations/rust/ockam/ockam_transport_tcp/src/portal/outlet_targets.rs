@@ -0,0 +1,445 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use core::time::Duration;
+use minicbor::{CborLen, Decode, Encode};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::LocalInfoIdentifier;
+use ockam_transport_core::HostnamePort;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Strategy used by an Outlet with several targets to choose the target of a new connection.
+/// Only the healthy targets are considered.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Encode, Decode, CborLen, Serialize, Deserialize,
+)]
+#[cbor(index_only)]
+#[rustfmt::skip]
+pub enum TcpOutletLoadBalancing {
+    /// Use the targets one after the other
+    #[default]
+    #[n(0)] RoundRobin,
+    /// Use the target with the fewest active connections
+    #[n(1)] LeastConnections,
+    /// Use a random target
+    #[n(2)] Random,
+    /// Use the same target for all the connections of a given caller identifier,
+    /// as long as that target is healthy
+    #[n(3)] ConsistentHash,
+}
+
+impl Display for TcpOutletLoadBalancing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::LeastConnections => write!(f, "least-connections"),
+            Self::Random => write!(f, "random"),
+            Self::ConsistentHash => write!(f, "consistent-hash"),
+        }
+    }
+}
+
+impl FromStr for TcpOutletLoadBalancing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(Self::RoundRobin),
+            "least-connections" => Ok(Self::LeastConnections),
+            "random" => Ok(Self::Random),
+            "consistent-hash" => Ok(Self::ConsistentHash),
+            other => Err(format!(
+                "unknown load balancing strategy: {other}. \
+                Expected one of: round-robin, least-connections, random, consistent-hash"
+            )),
+        }
+    }
+}
+
+/// Active health check of the targets of an Outlet.
+///
+/// Each target is periodically checked by opening a TCP connection to it.
+/// A target is ejected after `unhealthy_threshold` consecutive failed checks,
+/// and used again after `healthy_threshold` consecutive successful checks.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode, CborLen, Serialize, Deserialize)]
+#[cbor(map)]
+#[rustfmt::skip]
+pub struct TcpOutletHealthCheck {
+    #[n(1)] interval: Duration,
+    #[n(2)] timeout: Duration,
+    #[n(3)] unhealthy_threshold: u32,
+    #[n(4)] healthy_threshold: u32,
+}
+
+impl TcpOutletHealthCheck {
+    /// Check the targets every 10 seconds, with a connection timeout of 2 seconds.
+    /// A target is ejected after 3 failed checks and used again after 1 successful check.
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(2),
+            unhealthy_threshold: 3,
+            healthy_threshold: 1,
+        }
+    }
+
+    /// Set the time between two checks of the targets
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the maximum time to establish a connection to a target
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of consecutive failed checks after which a target is ejected
+    pub fn with_unhealthy_threshold(mut self, unhealthy_threshold: u32) -> Self {
+        self.unhealthy_threshold = unhealthy_threshold.max(1);
+        self
+    }
+
+    /// Set the number of consecutive successful checks after which an ejected target is used again
+    pub fn with_healthy_threshold(mut self, healthy_threshold: u32) -> Self {
+        self.healthy_threshold = healthy_threshold.max(1);
+        self
+    }
+
+    /// Time between two checks of the targets
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Maximum time to establish a connection to a target
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Number of consecutive failed checks after which a target is ejected
+    pub fn unhealthy_threshold(&self) -> u32 {
+        self.unhealthy_threshold
+    }
+
+    /// Number of consecutive successful checks after which an ejected target is used again
+    pub fn healthy_threshold(&self) -> u32 {
+        self.healthy_threshold
+    }
+}
+
+impl Default for TcpOutletHealthCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Status of one of the targets of an Outlet
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TcpOutletTargetStatus {
+    hostname_port: HostnamePort,
+    healthy: bool,
+    active_connections: usize,
+}
+
+impl TcpOutletTargetStatus {
+    /// Address of the target
+    pub fn hostname_port(&self) -> &HostnamePort {
+        &self.hostname_port
+    }
+
+    /// Return false if the target was ejected by the health check
+    pub fn is_healthy(&self) -> bool {
+        self.healthy
+    }
+
+    /// Number of portal connections currently opened to the target
+    pub fn active_connections(&self) -> usize {
+        self.active_connections
+    }
+}
+
+#[derive(Debug)]
+struct Target {
+    hostname_port: HostnamePort,
+    healthy: bool,
+    consecutive_failures: u32,
+    consecutive_successes: u32,
+    active_connections: usize,
+}
+
+#[derive(Debug)]
+struct TargetsState {
+    targets: Vec<Target>,
+    next: usize,
+}
+
+/// Targets of an Outlet, shared between the Outlet listener, its portal workers
+/// and its health check.
+///
+/// Returned by [`TcpTransport::create_load_balanced_outlet`](crate::TcpTransport::create_load_balanced_outlet)
+/// to observe the status of each target.
+#[derive(Clone, Debug)]
+pub struct TcpOutletTargets {
+    load_balancing: TcpOutletLoadBalancing,
+    state: Arc<Mutex<TargetsState>>,
+}
+
+impl TcpOutletTargets {
+    pub(crate) fn new(
+        hostname_ports: Vec<HostnamePort>,
+        load_balancing: TcpOutletLoadBalancing,
+    ) -> Self {
+        let targets = hostname_ports
+            .into_iter()
+            .map(|hostname_port| Target {
+                hostname_port,
+                healthy: true,
+                consecutive_failures: 0,
+                consecutive_successes: 0,
+                active_connections: 0,
+            })
+            .collect();
+
+        Self {
+            load_balancing,
+            state: Arc::new(Mutex::new(TargetsState { targets, next: 0 })),
+        }
+    }
+
+    /// Strategy used to choose the target of a new connection
+    pub fn load_balancing(&self) -> TcpOutletLoadBalancing {
+        self.load_balancing
+    }
+
+    /// Status of each target, in the order they were given
+    pub fn statuses(&self) -> Vec<TcpOutletTargetStatus> {
+        self.state
+            .lock()
+            .unwrap()
+            .targets
+            .iter()
+            .map(|t| TcpOutletTargetStatus {
+                hostname_port: t.hostname_port.clone(),
+                healthy: t.healthy,
+                active_connections: t.active_connections,
+            })
+            .collect()
+    }
+
+    pub(crate) fn hostname_ports(&self) -> Vec<HostnamePort> {
+        self.state
+            .lock()
+            .unwrap()
+            .targets
+            .iter()
+            .map(|t| t.hostname_port.clone())
+            .collect()
+    }
+
+    /// Choose the target of a new connection and count that connection until the returned
+    /// lease is dropped. Return `None` if all the targets are unhealthy.
+    pub(crate) fn select(
+        &self,
+        caller: Option<&LocalInfoIdentifier>,
+    ) -> Option<TcpOutletTargetLease> {
+        let mut state = self.state.lock().unwrap();
+        let healthy: Vec<usize> = state
+            .targets
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.healthy)
+            .map(|(index, _)| index)
+            .collect();
+        if healthy.is_empty() {
+            return None;
+        }
+
+        let index = match (self.load_balancing, caller) {
+            (TcpOutletLoadBalancing::LeastConnections, _) => *healthy
+                .iter()
+                .min_by_key(|index| state.targets[**index].active_connections)?,
+            (TcpOutletLoadBalancing::Random, _) => healthy[rand::random::<usize>() % healthy.len()],
+            (TcpOutletLoadBalancing::ConsistentHash, Some(caller)) => {
+                // Rendezvous hashing: only the callers of an ejected target are moved
+                *healthy.iter().max_by_key(|index| {
+                    let mut hasher = DefaultHasher::new();
+                    caller.hash(&mut hasher);
+                    state.targets[**index]
+                        .hostname_port
+                        .to_string()
+                        .hash(&mut hasher);
+                    hasher.finish()
+                })?
+            }
+            // Callers without an identifier can't be pinned to a target
+            (TcpOutletLoadBalancing::RoundRobin, _)
+            | (TcpOutletLoadBalancing::ConsistentHash, None) => {
+                let index = healthy[state.next % healthy.len()];
+                state.next = state.next.wrapping_add(1);
+                index
+            }
+        };
+
+        let target = &mut state.targets[index];
+        target.active_connections += 1;
+
+        Some(TcpOutletTargetLease {
+            targets: self.clone(),
+            index,
+            hostname_port: target.hostname_port.clone(),
+        })
+    }
+
+    /// Record the result of a health check of the target at `index`.
+    /// Return the new health of the target if it changed.
+    pub(crate) fn record_health_check(
+        &self,
+        index: usize,
+        reachable: bool,
+        health_check: &TcpOutletHealthCheck,
+    ) -> Option<bool> {
+        let mut state = self.state.lock().unwrap();
+        let target = state.targets.get_mut(index)?;
+
+        if reachable {
+            target.consecutive_failures = 0;
+            target.consecutive_successes = target.consecutive_successes.saturating_add(1);
+            if !target.healthy && target.consecutive_successes >= health_check.healthy_threshold {
+                target.healthy = true;
+                return Some(true);
+            }
+        } else {
+            target.consecutive_successes = 0;
+            target.consecutive_failures = target.consecutive_failures.saturating_add(1);
+            if target.healthy && target.consecutive_failures >= health_check.unhealthy_threshold {
+                target.healthy = false;
+                return Some(false);
+            }
+        }
+
+        None
+    }
+
+    fn release(&self, index: usize) {
+        if let Some(target) = self.state.lock().unwrap().targets.get_mut(index) {
+            target.active_connections = target.active_connections.saturating_sub(1);
+        }
+    }
+}
+
+/// A connection counted for one of the targets of an Outlet.
+/// The connection stops being counted when the lease is dropped.
+#[derive(Debug)]
+pub(crate) struct TcpOutletTargetLease {
+    targets: TcpOutletTargets,
+    index: usize,
+    hostname_port: HostnamePort,
+}
+
+impl TcpOutletTargetLease {
+    pub(crate) fn hostname_port(&self) -> &HostnamePort {
+        &self.hostname_port
+    }
+}
+
+impl Drop for TcpOutletTargetLease {
+    fn drop(&mut self) {
+        self.targets.release(self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::Result;
+
+    fn targets(load_balancing: TcpOutletLoadBalancing) -> Result<TcpOutletTargets> {
+        Ok(TcpOutletTargets::new(
+            vec![
+                HostnamePort::new("127.0.0.1", 5001)?,
+                HostnamePort::new("127.0.0.1", 5002)?,
+                HostnamePort::new("127.0.0.1", 5003)?,
+            ],
+            load_balancing,
+        ))
+    }
+
+    fn port(lease: &TcpOutletTargetLease) -> u16 {
+        lease.hostname_port().port()
+    }
+
+    #[test]
+    fn round_robin_skips_ejected_targets() -> Result<()> {
+        let targets = targets(TcpOutletLoadBalancing::RoundRobin)?;
+        let health_check = TcpOutletHealthCheck::new().with_unhealthy_threshold(2);
+
+        let ports: Vec<u16> = (0..3)
+            .map(|_| port(&targets.select(None).unwrap()))
+            .collect();
+        assert_eq!(ports, vec![5001, 5002, 5003]);
+
+        assert_eq!(targets.record_health_check(1, false, &health_check), None);
+        assert_eq!(
+            targets.record_health_check(1, false, &health_check),
+            Some(false)
+        );
+        for _ in 0..4 {
+            assert_ne!(port(&targets.select(None).unwrap()), 5002);
+        }
+
+        assert_eq!(
+            targets.record_health_check(1, true, &health_check),
+            Some(true)
+        );
+        assert!(targets.statuses().iter().all(|s| s.is_healthy()));
+        Ok(())
+    }
+
+    #[test]
+    fn least_connections_counts_leases_until_dropped() -> Result<()> {
+        let targets = targets(TcpOutletLoadBalancing::LeastConnections)?;
+
+        let first = targets.select(None).unwrap();
+        let second = targets.select(None).unwrap();
+        assert_eq!((port(&first), port(&second)), (5001, 5002));
+        assert_eq!(targets.statuses()[0].active_connections(), 1);
+
+        drop(first);
+        assert_eq!(targets.statuses()[0].active_connections(), 0);
+        assert_eq!(port(&targets.select(None).unwrap()), 5001);
+        Ok(())
+    }
+
+    #[test]
+    fn consistent_hash_keeps_a_caller_on_the_same_target() -> Result<()> {
+        let targets = targets(TcpOutletLoadBalancing::ConsistentHash)?;
+        let health_check = TcpOutletHealthCheck::new().with_unhealthy_threshold(1);
+        let caller = LocalInfoIdentifier([7; 32]);
+
+        let chosen = port(&targets.select(Some(&caller)).unwrap());
+        for _ in 0..5 {
+            assert_eq!(port(&targets.select(Some(&caller)).unwrap()), chosen);
+        }
+
+        let chosen_index = (chosen - 5001) as usize;
+        targets.record_health_check(chosen_index, false, &health_check);
+        assert_ne!(port(&targets.select(Some(&caller)).unwrap()), chosen);
+        Ok(())
+    }
+
+    #[test]
+    fn no_target_is_selected_when_all_targets_are_unhealthy() -> Result<()> {
+        let targets = targets(TcpOutletLoadBalancing::Random)?;
+        let health_check = TcpOutletHealthCheck::new().with_unhealthy_threshold(1);
+        for index in 0..3 {
+            targets.record_health_check(index, false, &health_check);
+        }
+
+        assert!(targets.select(None).is_none());
+        Ok(())
+    }
+}
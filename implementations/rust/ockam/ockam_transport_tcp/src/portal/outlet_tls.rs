@@ -0,0 +1,355 @@
+use crate::transport::load_native_certificates;
+use crate::TlsCertificate;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use minicbor::{CborLen, Decode, Encode};
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_transport_core::HostnamePort;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+use std::io::BufReader;
+use tokio_rustls::TlsConnector;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// TLS configuration of the connections made by an Outlet to its targets
+#[derive(Clone, Debug, Default, PartialEq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpOutletTlsConfig {
+    /// Certificate authorities trusted to issue the target certificates, in PEM format.
+    /// The system certificates are used when not set.
+    #[cbor(with = "minicbor::bytes")]
+    #[n(1)] pub ca_certificates_pem: Option<Vec<u8>>,
+    /// Certificate presented to the targets requiring TLS client authentication
+    #[n(2)] pub client_certificate: Option<TlsCertificate>,
+    /// Server name sent with SNI and verified in the target certificate,
+    /// instead of the target hostname
+    #[n(3)] pub server_name: Option<String>,
+    /// If not empty, the certificate of the target must have one of these public keys
+    #[n(4)] pub public_key_pins: Vec<TlsPublicKeyPin>,
+}
+
+impl TcpOutletTlsConfig {
+    /// Default configuration: the targets are verified with the system certificates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify the targets with these certificate authorities instead of the system certificates
+    pub fn with_ca_certificates_pem(mut self, ca_certificates_pem: Vec<u8>) -> Self {
+        self.ca_certificates_pem = Some(ca_certificates_pem);
+        self
+    }
+
+    /// Present a client certificate to the targets
+    pub fn with_client_certificate(mut self, client_certificate: TlsCertificate) -> Self {
+        self.client_certificate = Some(client_certificate);
+        self
+    }
+
+    /// Use this server name instead of the target hostname
+    pub fn with_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    /// Add a pinned public key, expected in the certificate of the target
+    pub fn with_public_key_pin(mut self, public_key_pin: TlsPublicKeyPin) -> Self {
+        self.public_key_pins.push(public_key_pin);
+        self
+    }
+
+    /// Create the TLS connector shared by all the connections of an Outlet.
+    /// An error is returned if the certificates or the private key can't be parsed
+    pub(crate) fn create_connector(&self) -> Result<TcpOutletTlsConnector> {
+        let root_cert_store = match &self.ca_certificates_pem {
+            Some(ca_certificates_pem) => {
+                let mut root_cert_store = RootCertStore::empty();
+                for certificate in parse_certificates(ca_certificates_pem)? {
+                    root_cert_store
+                        .add(certificate)
+                        .map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?;
+                }
+                root_cert_store
+            }
+            None => load_native_certificates()?,
+        };
+
+        let builder = ClientConfig::builder();
+        let builder = if self.public_key_pins.is_empty() {
+            builder.with_root_certificates(root_cert_store)
+        } else {
+            let verifier = WebPkiServerVerifier::builder(Arc::new(root_cert_store))
+                .build()
+                .map_err(|e| Error::new(Origin::Transport, Kind::Invalid, e))?;
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedServerCertVerifier {
+                    verifier,
+                    public_key_pins: self.public_key_pins.clone(),
+                }))
+        };
+
+        let config = match &self.client_certificate {
+            Some(client_certificate) => {
+                let chain = parse_certificates(&client_certificate.full_chain_pem)?;
+                let mut reader = BufReader::new(client_certificate.private_key_pem.as_slice());
+                let private_key = rustls_pemfile::private_key(&mut reader)
+                    .map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?
+                    .ok_or_else(|| {
+                        Error::new(
+                            Origin::Transport,
+                            Kind::Parse,
+                            "No private key found in the provided client certificate",
+                        )
+                    })?;
+                builder
+                    .with_client_auth_cert(chain, private_key)
+                    .map_err(|e| Error::new(Origin::Transport, Kind::Invalid, e))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        Ok(TcpOutletTlsConnector {
+            connector: Arc::new(TlsConnector::from(Arc::new(config))),
+            server_name: self.server_name.clone(),
+        })
+    }
+}
+
+/// TLS connector of an Outlet, created once from its [`TcpOutletTlsConfig`]
+#[derive(Clone)]
+pub(crate) struct TcpOutletTlsConnector {
+    connector: Arc<TlsConnector>,
+    server_name: Option<String>,
+}
+
+impl TcpOutletTlsConnector {
+    pub(crate) fn connector(&self) -> &TlsConnector {
+        &self.connector
+    }
+
+    /// Return the name used for SNI and for the verification of the target certificate
+    pub(crate) fn server_name(&self, to: &HostnamePort) -> Result<ServerName<'static>> {
+        let hostname = self
+            .server_name
+            .clone()
+            .unwrap_or_else(|| to.hostname().to_string());
+        ServerName::try_from(hostname).map_err(|e| {
+            Error::new(
+                Origin::Transport,
+                Kind::Io,
+                format!("Cannot create a ServerName for {to}: {e:?}"),
+            )
+        })
+    }
+}
+
+fn parse_certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(pem);
+    let certificates: std::io::Result<Vec<CertificateDer<'static>>> =
+        rustls_pemfile::certs(&mut reader).collect();
+    let certificates = certificates.map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?;
+    if certificates.is_empty() {
+        return Err(Error::new(
+            Origin::Transport,
+            Kind::Parse,
+            "No certificate found in the provided PEM data",
+        ));
+    }
+    Ok(certificates)
+}
+
+/// SHA-256 digest of the DER encoded SubjectPublicKeyInfo of a certificate.
+///
+/// For a PEM certificate it can be computed with:
+/// `openssl x509 -in cert.pem -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256`
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, CborLen)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TlsPublicKeyPin {
+    #[cbor(with = "minicbor::bytes")]
+    #[n(1)] sha256: Vec<u8>,
+}
+
+impl TlsPublicKeyPin {
+    /// Return the pin of the public key of a DER encoded certificate
+    pub fn from_certificate(certificate_der: &[u8]) -> Result<Self> {
+        let (_, certificate) = X509Certificate::from_der(certificate_der)
+            .map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?;
+        Ok(Self {
+            sha256: Sha256::digest(certificate.public_key().raw).to_vec(),
+        })
+    }
+}
+
+impl Display for TlsPublicKeyPin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", hex::encode(&self.sha256))
+    }
+}
+
+impl FromStr for TlsPublicKeyPin {
+    type Err = String;
+
+    /// Parse a hex encoded SHA-256 digest. The bytes can be separated by colons
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let sha256 = hex::decode(s.replace(':', ""))
+            .map_err(|e| format!("invalid public key pin {s}: {e}"))?;
+        if sha256.len() != 32 {
+            return Err(format!(
+                "invalid public key pin {s}: a SHA-256 digest is expected"
+            ));
+        }
+        Ok(Self { sha256 })
+    }
+}
+
+/// This verifier checks the target certificate with the trusted certificate authorities,
+/// then checks that it has a pinned public key.
+///
+/// Only the end-entity certificate is pinned: the intermediate certificates are sent by the
+/// target and any of them can be added to a valid chain without being used to verify it
+#[derive(Debug)]
+struct PinnedServerCertVerifier {
+    verifier: Arc<WebPkiServerVerifier>,
+    public_key_pins: Vec<TlsPublicKeyPin>,
+}
+
+impl ServerCertVerifier for PinnedServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> core::result::Result<ServerCertVerified, rustls::Error> {
+        let verified = self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let is_pinned = TlsPublicKeyPin::from_certificate(end_entity)
+            .map(|pin| self.public_key_pins.contains(&pin))
+            .unwrap_or(false);
+        if is_pinned {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> core::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.verifier.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> core::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.verifier.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.verifier.supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_public_key_pin() {
+        let hex = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let pin = TlsPublicKeyPin::from_str(hex).unwrap();
+        assert_eq!(pin.to_string(), hex);
+
+        let with_colons = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|c| core::str::from_utf8(c).unwrap().to_uppercase())
+            .collect::<Vec<_>>()
+            .join(":");
+        assert_eq!(TlsPublicKeyPin::from_str(&with_colons).unwrap(), pin);
+
+        assert!(TlsPublicKeyPin::from_str("e3b0c442").is_err());
+        assert!(TlsPublicKeyPin::from_str("not a pin").is_err());
+    }
+
+    const CA_CERTIFICATE: &[u8] = include_bytes!("../../tests/fixtures/tls/ca.pem");
+    const SERVER_CERTIFICATE: &[u8] = include_bytes!("../../tests/fixtures/tls/server.pem");
+    const OTHER_SERVER_CERTIFICATE: &[u8] =
+        include_bytes!("../../tests/fixtures/tls/other_server.pem");
+
+    fn certificate(pem: &[u8]) -> CertificateDer<'static> {
+        parse_certificates(pem).unwrap().remove(0)
+    }
+
+    fn pinned_verifier(pinned_certificate: &[u8]) -> PinnedServerCertVerifier {
+        let mut root_cert_store = RootCertStore::empty();
+        root_cert_store.add(certificate(CA_CERTIFICATE)).unwrap();
+        let verifier = WebPkiServerVerifier::builder(Arc::new(root_cert_store))
+            .build()
+            .unwrap();
+        let pin = TlsPublicKeyPin::from_certificate(&certificate(pinned_certificate)).unwrap();
+        PinnedServerCertVerifier {
+            verifier,
+            public_key_pins: vec![pin],
+        }
+    }
+
+    fn verify(
+        verifier: &PinnedServerCertVerifier,
+        end_entity: &[u8],
+        intermediates: &[CertificateDer<'_>],
+    ) -> core::result::Result<ServerCertVerified, rustls::Error> {
+        verifier.verify_server_cert(
+            &certificate(end_entity),
+            intermediates,
+            &ServerName::try_from("localhost").unwrap(),
+            &[],
+            UnixTime::now(),
+        )
+    }
+
+    #[test]
+    fn test_pinned_public_key() {
+        let verifier = pinned_verifier(SERVER_CERTIFICATE);
+        assert!(verify(&verifier, SERVER_CERTIFICATE, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_public_key() {
+        let verifier = pinned_verifier(SERVER_CERTIFICATE);
+        assert!(verify(&verifier, OTHER_SERVER_CERTIFICATE, &[]).is_err());
+    }
+
+    #[test]
+    fn test_pinned_public_key_in_an_injected_intermediate() {
+        // the pinned certificate is not part of the verified chain of the target certificate
+        let verifier = pinned_verifier(SERVER_CERTIFICATE);
+        let intermediates = [certificate(SERVER_CERTIFICATE)];
+        assert!(verify(&verifier, OTHER_SERVER_CERTIFICATE, &intermediates).is_err());
+    }
+}
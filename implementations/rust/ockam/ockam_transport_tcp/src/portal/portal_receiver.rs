@@ -1,10 +1,11 @@
-use crate::portal::addresses::Addresses;
+use crate::portal::addresses::{Addresses, PortalType};
 use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{
     async_trait, Encodable, LocalMessage, OpenTelemetryContext, Route, OCKAM_TRACER_NAME,
 };
 use ockam_core::{route, Processor, Result};
+use ockam_node::metrics::Direction;
 use ockam_node::Context;
 use opentelemetry::global;
 use opentelemetry::trace::Tracer;
@@ -22,6 +23,7 @@ pub(crate) struct TcpPortalRecvProcessor<R> {
     buf: Vec<u8>,
     read_half: R,
     addresses: Addresses,
+    portal_type: PortalType,
     onward_route: Route,
     payload_packet_counter: u16,
     portal_payload_length: usize,
@@ -33,6 +35,7 @@ impl<R: AsyncRead + Unpin + Send + Sync + 'static> TcpPortalRecvProcessor<R> {
         registry: TcpRegistry,
         read_half: R,
         addresses: Addresses,
+        portal_type: PortalType,
         onward_route: Route,
         portal_payload_length: usize,
     ) -> Self {
@@ -41,6 +44,7 @@ impl<R: AsyncRead + Unpin + Send + Sync + 'static> TcpPortalRecvProcessor<R> {
             buf: Vec::with_capacity(portal_payload_length),
             read_half,
             addresses,
+            portal_type,
             onward_route,
             payload_packet_counter: 0,
             portal_payload_length,
@@ -72,7 +76,7 @@ impl<R: AsyncRead + Unpin + Send + Sync + 'static> Processor for TcpPortalRecvPr
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         self.buf.clear();
 
-        let _len = match self.read_half.read_buf(&mut self.buf).await {
+        let len = match self.read_half.read_buf(&mut self.buf).await {
             Ok(len) => len,
             Err(err) => {
                 error!("Tcp Portal connection read failed with error: {}", err);
@@ -122,6 +126,9 @@ impl<R: AsyncRead + Unpin + Send + Sync + 'static> Processor for TcpPortalRecvPr
             return Ok(false);
         }
 
+        ctx.metrics()
+            .portal_transfer(self.portal_type.into(), Direction::Incoming, len);
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(self.portal_payload_length) {
             let msg = LocalMessage::new()
@@ -2,6 +2,7 @@ use crate::portal::addresses::{Addresses, PortalType};
 use crate::portal::outlet_listener_registry::{MapKey, OutletListenerRegistry};
 use crate::portal::portal_worker::ReadHalfMaybeTls::{ReadHalfNoTls, ReadHalfWithTls};
 use crate::portal::portal_worker::WriteHalfMaybeTls::{WriteHalfNoTls, WriteHalfWithTls};
+use crate::portal::{TcpOutletTargetLease, TcpOutletTlsConnector};
 use crate::transport::{connect, connect_tls};
 use crate::{portal::TcpPortalRecvProcessor, PortalInternalMessage, PortalMessage, TcpRegistry};
 use ockam_core::compat::{boxed::Box, sync::Arc};
@@ -11,6 +12,7 @@ use ockam_core::{
     SecureChannelLocalInfo,
 };
 use ockam_core::{Any, Result, Route, Routed, Worker};
+use ockam_node::metrics::Direction;
 use ockam_node::{Context, ProcessorBuilder, WorkerBuilder, WorkerShutdownPriority};
 use ockam_transport_core::{HostnamePort, TransportError};
 use std::time::Duration;
@@ -60,10 +62,12 @@ pub(crate) struct TcpPortalWorker {
     portal_type: PortalType,
     last_received_packet_counter: u16,
     outgoing_access_control: Arc<dyn OutgoingAccessControl>,
-    is_tls: bool,
+    tls_connector: Option<TcpOutletTlsConnector>,
     portal_payload_length: usize,
     handshake_mode: HandshakeMode,
     enable_nagle: bool,
+    /// Counts this connection for its Outlet target until the worker is stopped
+    target_lease: Option<TcpOutletTargetLease>,
 }
 
 pub(crate) enum ReadHalfMaybeTls {
@@ -103,7 +107,7 @@ impl TcpPortalWorker {
             ctx,
             registry,
             hostname_port,
-            false,
+            None,
             State::SendPing { ping_route },
             None,
             their_identifier,
@@ -114,6 +118,7 @@ impl TcpPortalWorker {
             portal_payload_length,
             handshake_mode,
             false,
+            None,
         )
     }
 
@@ -123,8 +128,8 @@ impl TcpPortalWorker {
     pub(super) fn start_new_outlet(
         ctx: &Context,
         registry: TcpRegistry,
-        hostname_port: HostnamePort,
-        tls: bool,
+        target: TcpOutletTargetLease,
+        tls_connector: Option<TcpOutletTlsConnector>,
         pong_route: Route,
         their_identifier: Option<LocalInfoIdentifier>,
         addresses: Addresses,
@@ -135,8 +140,8 @@ impl TcpPortalWorker {
         Self::start(
             ctx,
             registry,
-            hostname_port,
-            tls,
+            target.hostname_port().clone(),
+            tls_connector,
             State::SendPong { pong_route },
             None,
             their_identifier,
@@ -147,6 +152,7 @@ impl TcpPortalWorker {
             portal_payload_length,
             HandshakeMode::Regular,
             false,
+            Some(target),
         )
     }
 
@@ -156,8 +162,8 @@ impl TcpPortalWorker {
     pub(super) fn start_new_outlet_no_handshake(
         ctx: &Context,
         registry: TcpRegistry,
-        hostname_port: HostnamePort,
-        tls: bool,
+        target: TcpOutletTargetLease,
+        tls_connector: Option<TcpOutletTlsConnector>,
         pong_route: Route,
         their_identifier: Option<LocalInfoIdentifier>,
         addresses: Addresses,
@@ -169,8 +175,8 @@ impl TcpPortalWorker {
         Self::start(
             ctx,
             registry,
-            hostname_port,
-            tls,
+            target.hostname_port().clone(),
+            tls_connector,
             State::Initialized,
             Some(pong_route),
             their_identifier,
@@ -184,6 +190,7 @@ impl TcpPortalWorker {
                 map: Some((map_key, outlet_listener_registry)),
             },
             false,
+            Some(target),
         )
     }
 
@@ -194,7 +201,7 @@ impl TcpPortalWorker {
         ctx: &Context,
         registry: TcpRegistry,
         hostname_port: HostnamePort,
-        is_tls: bool,
+        tls_connector: Option<TcpOutletTlsConnector>,
         state: State,
         remote_route: Option<Route>,
         their_identifier: Option<LocalInfoIdentifier>,
@@ -205,12 +212,14 @@ impl TcpPortalWorker {
         portal_payload_length: usize,
         handshake_mode: HandshakeMode,
         enable_nagle: bool,
+        target_lease: Option<TcpOutletTargetLease>,
     ) -> Result<()> {
         let portal_type = if streams.is_some() {
             PortalType::Inlet
         } else {
             PortalType::Outlet
         };
+        let is_tls = tls_connector.is_some();
         debug!(%portal_type, sender_remote=%addresses.sender_remote, %is_tls, "creating portal worker");
 
         let (rx, tx) = match streams {
@@ -234,11 +243,12 @@ impl TcpPortalWorker {
             is_disconnecting: false,
             portal_type,
             last_received_packet_counter: u16::MAX,
-            is_tls,
+            tls_connector,
             outgoing_access_control: outgoing_access_control.clone(),
             portal_payload_length,
             enable_nagle,
             handshake_mode,
+            target_lease,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -304,6 +314,7 @@ impl TcpPortalWorker {
             self.registry.clone(),
             rx,
             self.addresses.clone(),
+            self.portal_type,
             onward_route,
             self.portal_payload_length,
         );
@@ -460,9 +471,10 @@ impl TcpPortalWorker {
     }
 
     async fn connect(&mut self) -> Result<()> {
-        if self.is_tls {
+        if let Some(tls_connector) = &self.tls_connector {
             debug!(portal_type = %self.portal_type, sender_internal = %self.addresses.sender_internal, "connect to {} via TLS", &self.hostname_port);
-            let (rx, tx) = connect_tls(&self.hostname_port, self.enable_nagle).await?;
+            let (rx, tx) =
+                connect_tls(&self.hostname_port, self.enable_nagle, tls_connector).await?;
             self.write_half = Some(WriteHalfWithTls(tx));
             self.read_half = Some(ReadHalfWithTls(rx));
         } else {
@@ -551,6 +563,9 @@ impl Worker for TcpPortalWorker {
         self.registry
             .remove_portal_worker(&self.addresses.sender_remote);
 
+        // Stop counting this connection for the Outlet target
+        self.target_lease.take();
+
         Ok(())
     }
 
@@ -679,6 +694,12 @@ impl TcpPortalWorker {
             );
             self.start_disconnection(ctx, DisconnectionReason::FailedTx)
                 .await?;
+        } else {
+            ctx.metrics().portal_transfer(
+                self.portal_type.into(),
+                Direction::Outgoing,
+                payload.len(),
+            );
         }
 
         Ok(())
@@ -1,7 +1,9 @@
 use core::fmt::{Debug, Display, Formatter};
 use log::warn;
-use minicbor::{Decode, Encode};
+use minicbor::{CborLen, Decode, Encode};
 use ockam_core::async_trait;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Error;
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
 use std::ops::Sub;
@@ -9,6 +11,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::Instant;
+use x509_parser::prelude::{FromDer, X509Certificate};
 
 /// Refresh the certificate every day.
 pub const DEFAULT_CACHE_RETENTION: Duration = Duration::from_secs(60 * 60 * 24);
@@ -18,7 +21,7 @@ pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60 * 12);
 
 /// Structure representing typical TLS certificates with the relative private key
 /// to allow easy deployment
-#[derive(Encode, Decode, Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Encode, Decode, CborLen, Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct TlsCertificate {
@@ -30,6 +33,31 @@ pub struct TlsCertificate {
     #[n(2)] pub private_key_pem: Vec<u8>,
 }
 
+impl TlsCertificate {
+    /// Return the validity period of the first certificate of the chain,
+    /// as the UNIX timestamps of its `notBefore` and `notAfter` fields, in seconds
+    pub fn validity(&self) -> ockam_core::Result<(i64, i64)> {
+        let mut reader = self.full_chain_pem.as_slice();
+        let certificate = rustls_pemfile::certs(&mut reader)
+            .next()
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Transport,
+                    Kind::Parse,
+                    "No certificate found in the provided PEM data",
+                )
+            })?
+            .map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?;
+        let (_, certificate) = X509Certificate::from_der(&certificate)
+            .map_err(|e| Error::new(Origin::Transport, Kind::Parse, e))?;
+        let validity = certificate.validity();
+        Ok((
+            validity.not_before.timestamp(),
+            validity.not_after.timestamp(),
+        ))
+    }
+}
+
 #[async_trait]
 /// TLS certificate provider abstraction, to keep the implementation opaque
 /// to the TCP transport.
@@ -191,11 +219,7 @@ pub mod test {
                     private_key_pem: format!("test-{counter}").into_bytes(),
                 })
             } else {
-                Err(ockam_core::Error::new(
-                    Origin::Transport,
-                    Kind::Timeout,
-                    "timeout",
-                ))
+                Err(Error::new(Origin::Transport, Kind::Timeout, "timeout"))
             }
         }
     }
@@ -0,0 +1,214 @@
+use core::fmt::Debug;
+use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{
+    async_trait, IncomingAccessControl, LocalInfoIdentifier, OutgoingAccessControl, Result,
+};
+use ockam_node::Context;
+use rustls::pki_types::CertificateDer;
+use rustls::server::danger::ClientCertVerifier;
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use std::io::BufReader;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Attributes of the certificate presented by a TLS client
+/// once it has been validated against the trusted certificate authorities
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlsClientCertificate {
+    /// Distinguished name of the subject, for example `CN=client,OU=legacy,O=Acme`
+    pub subject: String,
+    /// Distinguished name of the issuer
+    pub issuer: String,
+    /// Serial number, as colon separated hexadecimal bytes
+    pub serial_number: String,
+    /// Common names (CN) of the subject
+    pub common_names: Vec<String>,
+    /// Organizations (O) of the subject
+    pub organizations: Vec<String>,
+    /// Organizational units (OU) of the subject
+    pub organizational_units: Vec<String>,
+    /// DNS names of the subject alternative name extension
+    pub dns_names: Vec<String>,
+    /// URIs of the subject alternative name extension
+    pub uris: Vec<String>,
+    /// Email addresses of the subject alternative name extension
+    pub emails: Vec<String>,
+    /// IP addresses of the subject alternative name extension
+    pub ip_addresses: Vec<String>,
+}
+
+impl TlsClientCertificate {
+    /// Extract the attributes of a DER encoded certificate
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let (_, certificate) = X509Certificate::from_der(der)
+            .map_err(|e| ockam_core::Error::new(Origin::Transport, Kind::Parse, e))?;
+
+        let subject = certificate.subject();
+        let mut client_certificate = TlsClientCertificate {
+            subject: subject.to_string(),
+            issuer: certificate.issuer().to_string(),
+            serial_number: certificate.raw_serial_as_string(),
+            common_names: subject
+                .iter_common_name()
+                .filter_map(|a| a.as_str().ok().map(String::from))
+                .collect(),
+            organizations: subject
+                .iter_organization()
+                .filter_map(|a| a.as_str().ok().map(String::from))
+                .collect(),
+            organizational_units: subject
+                .iter_organizational_unit()
+                .filter_map(|a| a.as_str().ok().map(String::from))
+                .collect(),
+            ..Default::default()
+        };
+
+        if let Ok(Some(alternative_names)) = certificate.subject_alternative_name() {
+            for name in alternative_names.value.general_names.iter() {
+                match name {
+                    GeneralName::DNSName(dns_name) => {
+                        client_certificate.dns_names.push(dns_name.to_string())
+                    }
+                    GeneralName::URI(uri) => client_certificate.uris.push(uri.to_string()),
+                    GeneralName::RFC822Name(email) => {
+                        client_certificate.emails.push(email.to_string())
+                    }
+                    GeneralName::IPAddress(ip_address) => {
+                        if let Some(ip_address) = ip_address_to_string(ip_address) {
+                            client_certificate.ip_addresses.push(ip_address)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(client_certificate)
+    }
+}
+
+fn ip_address_to_string(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => {
+            let octets: [u8; 4] = bytes.try_into().ok()?;
+            Some(std::net::Ipv4Addr::from(octets).to_string())
+        }
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(std::net::Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Access controls used by the portal of a connection accepted by the inlet
+pub type TlsClientAccessControls = (
+    Arc<dyn IncomingAccessControl>,
+    Arc<dyn OutgoingAccessControl>,
+);
+
+/// This trait creates the access controls of the connections of an inlet
+/// requiring TLS client certificates, to keep the authorization logic opaque
+/// to the TCP transport.
+#[async_trait]
+pub trait TlsClientAccessControlFactory: Send + Sync + Debug + 'static {
+    /// Return the access controls for a connection authenticated with the given client certificate.
+    /// `their_identifier` is the identifier of the outlet side, if the inlet route goes
+    /// through a secure channel.
+    ///
+    /// The connection is closed if `None` is returned.
+    async fn create(
+        &self,
+        context: &Context,
+        their_identifier: Option<LocalInfoIdentifier>,
+        client_certificate: &TlsClientCertificate,
+    ) -> Result<Option<TlsClientAccessControls>>;
+}
+
+/// Mutual TLS configuration of an inlet: the clients must present a certificate
+/// issued by one of the trusted certificate authorities
+#[derive(Clone, Debug)]
+pub struct TlsClientAuthentication {
+    pub(crate) client_certificate_verifier: Arc<dyn ClientCertVerifier>,
+    pub(crate) access_control_factory: Option<Arc<dyn TlsClientAccessControlFactory>>,
+}
+
+impl TlsClientAuthentication {
+    /// Trust the certificate authorities of a PEM bundle.
+    /// The client certificate verifier is created once and shared by all the connections
+    pub fn new(ca_certificates_pem: Vec<u8>) -> Result<Self> {
+        Ok(Self {
+            client_certificate_verifier: Self::create_client_certificate_verifier(
+                &ca_certificates_pem,
+            )?,
+            access_control_factory: None,
+        })
+    }
+
+    /// Create the access controls of each connection from its client certificate.
+    /// When omitted, the access controls of the inlet are used for all the connections
+    pub fn with_access_control_factory(
+        mut self,
+        access_control_factory: Arc<dyn TlsClientAccessControlFactory>,
+    ) -> Self {
+        self.access_control_factory = Some(access_control_factory);
+        self
+    }
+
+    /// Verifier accepting only the client certificates issued by the trusted authorities
+    fn create_client_certificate_verifier(
+        ca_certificates_pem: &[u8],
+    ) -> Result<Arc<dyn ClientCertVerifier>> {
+        let mut reader = BufReader::new(ca_certificates_pem);
+        let ca_certificates: std::io::Result<Vec<CertificateDer<'static>>> =
+            rustls_pemfile::certs(&mut reader).collect();
+        let ca_certificates = ca_certificates
+            .map_err(|e| ockam_core::Error::new(Origin::Transport, Kind::Parse, e))?;
+        if ca_certificates.is_empty() {
+            return Err(ockam_core::Error::new(
+                Origin::Transport,
+                Kind::Parse,
+                "No certificate found in the provided TLS client CA bundle",
+            ));
+        }
+
+        let mut roots = RootCertStore::empty();
+        for ca_certificate in ca_certificates {
+            roots
+                .add(ca_certificate)
+                .map_err(|e| ockam_core::Error::new(Origin::Transport, Kind::Parse, e))?;
+        }
+
+        WebPkiClientVerifier::builder(Arc::new(roots))
+            .build()
+            .map_err(|e| ockam_core::Error::new(Origin::Transport, Kind::Invalid, e))
+    }
+
+    /// Return the access controls for the connection of a client which presented
+    /// the given certificates, or `None` if the connection must be closed
+    pub(crate) async fn access_controls(
+        &self,
+        context: &Context,
+        their_identifier: Option<LocalInfoIdentifier>,
+        peer_certificates: Option<&[CertificateDer<'_>]>,
+        default_access_controls: TlsClientAccessControls,
+    ) -> Result<Option<TlsClientAccessControls>> {
+        // the certificate chain was already verified during the handshake
+        let end_entity_certificate = match peer_certificates.and_then(|c| c.first()) {
+            Some(certificate) => certificate,
+            None => return Ok(None),
+        };
+
+        match &self.access_control_factory {
+            Some(access_control_factory) => {
+                let client_certificate = TlsClientCertificate::from_der(end_entity_certificate)?;
+                access_control_factory
+                    .create(context, their_identifier, &client_certificate)
+                    .await
+            }
+            None => Ok(Some(default_access_controls)),
+        }
+    }
+}
@@ -1,16 +1,15 @@
+use crate::portal::TcpOutletTlsConnector;
 use cfg_if::cfg_if;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_transport_core::{HostnamePort, TransportError};
 use socket2::{SockRef, TcpKeepalive};
-use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{ReadHalf, WriteHalf};
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tokio_rustls::rustls::pki_types::ServerName;
-use tokio_rustls::rustls::{ClientConfig, RootCertStore};
-use tokio_rustls::{TlsConnector, TlsStream};
+use tokio_rustls::rustls::RootCertStore;
+use tokio_rustls::TlsStream;
 use tracing::{debug, instrument};
 
 /// Connect to a socket address via a regular TcpStream
@@ -84,6 +83,7 @@ pub(crate) async fn create_tcp_stream(
 pub(crate) async fn connect_tls(
     to: &HostnamePort,
     enable_nagle: bool,
+    tls_connector: &TcpOutletTlsConnector,
 ) -> Result<(
     ReadHalf<TlsStream<TcpStream>>,
     WriteHalf<TlsStream<TcpStream>>,
@@ -93,20 +93,12 @@ pub(crate) async fn connect_tls(
     // create a tcp stream
     let connection = create_tcp_stream(to, enable_nagle, None).await?;
 
-    // create a TLS connector
-    let tls_connector = create_tls_connector().await?;
-
     // parse destination hostname
-    let hostname = ServerName::try_from(to.hostname()).map_err(|e| {
-        Error::new(
-            Origin::Transport,
-            Kind::Io,
-            format!("Cannot create a ServerName from {to}: {e:?}"),
-        )
-    })?;
+    let hostname = tls_connector.server_name(to)?;
 
     // Connect using TLS over TCP
     let client_tls_stream = tls_connector
+        .connector()
         .connect(hostname, connection)
         .await
         .map_err(|e| {
@@ -121,8 +113,8 @@ pub(crate) async fn connect_tls(
     Ok(tokio::io::split(TlsStream::from(client_tls_stream)))
 }
 
-/// Create a TLS connector using the system certificates
-pub(crate) async fn create_tls_connector() -> Result<TlsConnector> {
+/// Load the system certificates
+pub(crate) fn load_native_certificates() -> Result<RootCertStore> {
     let certificates = rustls_native_certs::load_native_certs();
 
     if let Some(e) = certificates.errors.first() {
@@ -139,10 +131,5 @@ pub(crate) async fn create_tls_connector() -> Result<TlsConnector> {
 
     let mut root_cert_store = RootCertStore::empty();
     root_cert_store.add_parsable_certificates(certificates);
-
-    let config = ClientConfig::builder()
-        .with_root_certificates(root_cert_store)
-        .with_no_client_auth();
-
-    Ok(TlsConnector::from(Arc::new(config)))
+    Ok(root_cert_store)
 }
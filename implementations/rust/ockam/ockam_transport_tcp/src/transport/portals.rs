@@ -1,5 +1,8 @@
 use crate::portal::{InletSharedState, TcpInletListenProcessor};
-use crate::{portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpTransport};
+use crate::{
+    portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpOutletTargets,
+    TcpTransport,
+};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use ockam_core::compat::net::SocketAddr;
@@ -7,7 +10,7 @@ use ockam_core::compat::sync::{Arc, RwLock as SyncRwLock};
 use ockam_core::flow_control::FlowControls;
 use ockam_core::{Address, Result, Route};
 use ockam_node::Context;
-use ockam_transport_core::{parse_socket_addr, HostnamePort};
+use ockam_transport_core::{parse_socket_addr, HostnamePort, TransportError};
 use tracing::{debug, instrument};
 
 impl TcpTransport {
@@ -96,15 +99,57 @@ impl TcpTransport {
         peer: HostnamePort,
         options: TcpOutletOptions,
     ) -> Result<()> {
+        self.create_load_balanced_outlet(address, vec![peer], options)?;
+
+        Ok(())
+    }
+
+    /// Create Tcp Outlet Listener at address, that distributes the connections of Inlets
+    /// between several peers, according to the load balancing strategy and the health check
+    /// set in the options. The returned [`TcpOutletTargets`] give the status of each peer.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpOutletHealthCheck, TcpOutletLoadBalancing, TcpOutletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Address, AllowAll, Result};
+    /// # use ockam_transport_core::HostnamePort;
+    ///
+    /// async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let tcp = TcpTransport::create(&ctx)?;
+    /// let address: Address = "outlet".into();
+    /// let peers = vec![HostnamePort::new("10.0.0.1", 9000)?, HostnamePort::new("10.0.0.2", 9000)?];
+    /// let options = TcpOutletOptions::new()
+    ///     .with_load_balancing(TcpOutletLoadBalancing::LeastConnections)
+    ///     .with_health_check(TcpOutletHealthCheck::new());
+    /// let targets = tcp.create_load_balanced_outlet(address.clone(), peers, options)?;
+    /// assert_eq!(targets.statuses().len(), 2);
+    /// # tcp.stop_outlet(&address)?;
+    /// # Ok(()) }
+    /// ```
+    #[instrument(skip(self), fields(address = ? address.clone().into()))]
+    pub fn create_load_balanced_outlet(
+        &self,
+        address: impl Into<Address> + Clone + Debug,
+        peers: Vec<HostnamePort>,
+        options: TcpOutletOptions,
+    ) -> Result<TcpOutletTargets> {
+        if peers.is_empty() {
+            return Err(TransportError::InvalidAddress(
+                "an outlet needs at least one peer".to_string(),
+            ))?;
+        }
+
+        let targets = TcpOutletTargets::new(peers, options.load_balancing);
         TcpOutletListenWorker::start(
             &self.ctx,
             self.registry.clone(),
             address.into(),
-            peer,
+            targets.clone(),
             options,
         )?;
 
-        Ok(())
+        Ok(targets)
     }
 
     /// Stop outlet at addr
@@ -7,6 +7,7 @@ use ockam_core::{
     AddressMetadata, AllowAll, AllowSourceAddress, DenyAll, LocalMessage,
 };
 use ockam_core::{Any, Decodable, Mailbox, Mailboxes, Message, Result, Routed, Worker};
+use ockam_node::metrics::Direction;
 use ockam_node::{Context, WorkerBuilder, WorkerShutdownPriority};
 
 use crate::transport_message::TcpTransportMessage;
@@ -16,6 +17,9 @@ use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::OwnedWriteHalf;
 use tracing::{debug, instrument, trace, warn};
 
+/// Name of the transport in the connection metrics
+const TCP_TRANSPORT: &str = "tcp";
+
 #[derive(Serialize, Deserialize, Message, Clone)]
 pub(crate) enum TcpSendWorkerMsg {
     ConnectionClosed,
@@ -174,6 +178,11 @@ impl Worker for TcpSendWorker {
             self.mode,
             self.receiver_flow_control_id.clone(),
         ));
+        let direction = match self.mode {
+            TcpConnectionMode::Outgoing => Direction::Outgoing,
+            TcpConnectionMode::Incoming => Direction::Incoming,
+        };
+        ctx.metrics().connection_opened(TCP_TRANSPORT, direction);
 
         // First thing send our protocol version
         if self
@@ -198,6 +207,7 @@ impl Worker for TcpSendWorker {
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry
             .remove_sender_worker(self.addresses.sender_address());
+        ctx.metrics().connection_closed(TCP_TRANSPORT);
 
         if self.rx_should_be_stopped {
             let _ = ctx.stop_address(self.addresses.receiver_address());
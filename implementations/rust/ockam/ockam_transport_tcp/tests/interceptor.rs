@@ -33,19 +33,53 @@ impl PortalInterceptor for MockPortalInterceptor {
     }
 }
 
-struct MockPortalInterceptorFactory {
-    interceptor: Arc<MockPortalInterceptor>,
+/// Interceptor answering directly to the inlet, without forwarding the data to the outlet
+struct ReplyingPortalInterceptor;
+
+#[async_trait]
+impl PortalInterceptor for ReplyingPortalInterceptor {
+    async fn intercept(
+        &self,
+        _context: &mut Context,
+        _direction: Direction,
+        buffer: &[u8],
+    ) -> ockam_core::Result<Option<Vec<u8>>> {
+        Ok(Some(buffer.to_vec()))
+    }
+
+    async fn intercept_with_reply(
+        &self,
+        _context: &mut Context,
+        direction: Direction,
+        buffer: &[u8],
+    ) -> ockam_core::Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        match direction {
+            Direction::FromInletToOutlet => {
+                Ok((None, Some(buffer.iter().rev().cloned().collect())))
+            }
+            Direction::FromOutletToInlet => Ok((Some(buffer.to_vec()), None)),
+        }
+    }
+
+    fn can_reply(&self) -> bool {
+        true
+    }
 }
 
-impl PortalInterceptorFactory for MockPortalInterceptorFactory {
+struct MockPortalInterceptorFactory<T> {
+    interceptor: Arc<T>,
+}
+
+impl<T: PortalInterceptor> PortalInterceptorFactory for MockPortalInterceptorFactory<T> {
     fn create(&self) -> Arc<dyn PortalInterceptor> {
         self.interceptor.clone()
     }
 }
 
-async fn setup(
+async fn setup<T: PortalInterceptor>(
     context: &mut Context,
-) -> ockam_core::Result<(String, TcpListener, Arc<MockPortalInterceptor>)> {
+    interceptor: Arc<T>,
+) -> ockam_core::Result<(String, TcpListener)> {
     let tcp = TcpTransport::create(context)?;
 
     let listener = {
@@ -59,14 +93,10 @@ async fn setup(
         listener
     };
 
-    let mock_portal_interceptor = Arc::new(MockPortalInterceptor::default());
-
     PortalInletInterceptor::create(
         context,
         "interceptor_listener".into(),
-        Arc::new(MockPortalInterceptorFactory {
-            interceptor: mock_portal_interceptor.clone(),
-        }),
+        Arc::new(MockPortalInterceptorFactory { interceptor }),
         Arc::new(AllowAll),
         Arc::new(AllowAll),
         read_portal_payload_length(),
@@ -81,11 +111,7 @@ async fn setup(
         )
         .await?;
 
-    Ok((
-        inlet.socket_address().to_string(),
-        listener,
-        mock_portal_interceptor,
-    ))
+    Ok((inlet.socket_address().to_string(), listener))
 }
 
 const LENGTH: usize = 32;
@@ -111,7 +137,8 @@ async fn interceptor__simple_payload__received(context: &mut Context) -> ockam_c
     let payload1 = generate_binary();
     let payload2 = generate_binary();
 
-    let (inlet_addr, listener, mock_portal_interceptor) = setup(context).await?;
+    let mock_portal_interceptor = Arc::new(MockPortalInterceptor::default());
+    let (inlet_addr, listener) = setup(context, mock_portal_interceptor.clone()).await?;
 
     let handle = tokio::spawn(async move {
         let (mut stream, _) = listener.accept().await.unwrap();
@@ -146,3 +173,35 @@ async fn interceptor__simple_payload__received(context: &mut Context) -> ockam_c
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5_000)]
+async fn interceptor__reply__received_by_inlet(context: &mut Context) -> ockam_core::Result<()> {
+    let payload1 = generate_binary();
+    let mut reversed_payload1 = payload1;
+    reversed_payload1.reverse();
+
+    let (inlet_addr, listener) = setup(context, Arc::new(ReplyingPortalInterceptor)).await?;
+
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        stream
+    });
+
+    // Wait till the listener is up
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    // the reply of the interceptor is received by the inlet
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, reversed_payload1).await;
+
+    // the intercepted data is not received by the outlet
+    let mut outlet_stream = handle.await.unwrap();
+    let mut buffer = [0u8; LENGTH];
+    let read =
+        tokio::time::timeout(Duration::from_millis(250), outlet_stream.read(&mut buffer)).await;
+    assert!(read.is_err());
+
+    Ok(())
+}
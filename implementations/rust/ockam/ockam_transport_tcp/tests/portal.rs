@@ -7,7 +7,8 @@ use ockam_core::compat::rand::random;
 use ockam_core::{route, Result};
 use ockam_node::Context;
 use ockam_transport_tcp::{
-    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletOptions, TcpTransport,
+    TcpConnectionOptions, TcpInletOptions, TcpListenerOptions, TcpOutletHealthCheck,
+    TcpOutletLoadBalancing, TcpOutletOptions, TcpOutletTlsConfig, TcpTransport, TlsCertificate,
 };
 
 const LENGTH: usize = 32;
@@ -395,3 +396,112 @@ async fn portal__update_route__should_succeed__impl(
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 15000)]
+async fn portal__load_balanced_outlet__should_skip_unhealthy_target(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx)?;
+
+    let listener1 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener2 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    let targets = tcp.create_load_balanced_outlet(
+        "outlet",
+        vec![
+            listener1.local_addr().unwrap().into(),
+            unreachable.into(),
+            listener2.local_addr().unwrap().into(),
+        ],
+        TcpOutletOptions::new()
+            .with_load_balancing(TcpOutletLoadBalancing::RoundRobin)
+            .with_health_check(
+                TcpOutletHealthCheck::new()
+                    .with_interval(Duration::from_millis(100))
+                    .with_unhealthy_threshold(1),
+            ),
+    )?;
+
+    let inlet = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+
+    // Wait for the first health check
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let statuses = targets.statuses();
+    assert!(statuses[0].is_healthy());
+    assert!(!statuses[1].is_healthy());
+    assert!(statuses[2].is_healthy());
+
+    let mut streams = vec![];
+    for _ in 0..4 {
+        streams.push(TcpStream::connect(inlet.socket_address()).await.unwrap());
+    }
+
+    // The connections to the targets are established asynchronously
+    let mut active_connections = vec![];
+    for _ in 0..50 {
+        active_connections = targets
+            .statuses()
+            .iter()
+            .map(|s| s.active_connections())
+            .collect::<Vec<_>>();
+        if active_connections.iter().sum::<usize>() == 4 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(active_connections, vec![2, 0, 2]);
+
+    drop(streams);
+    drop((listener1, listener2));
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn portal__outlet_with_invalid_tls_config__should_not_be_created(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx)?;
+    let ca_certificate = include_bytes!("fixtures/tls/ca.pem").to_vec();
+
+    let invalid_configs = [
+        TcpOutletTlsConfig::new().with_ca_certificates_pem(b"not a certificate".to_vec()),
+        TcpOutletTlsConfig::new()
+            .with_ca_certificates_pem(ca_certificate.clone())
+            .with_client_certificate(TlsCertificate {
+                full_chain_pem: include_bytes!("fixtures/tls/client.pem").to_vec(),
+                private_key_pem: b"not a private key".to_vec(),
+            }),
+    ];
+    for (index, tls_config) in invalid_configs.into_iter().enumerate() {
+        let address = format!("invalid_outlet_{index}");
+        let result = tcp.create_outlet(
+            address.clone(),
+            "127.0.0.1:5000".try_into()?,
+            TcpOutletOptions::new().with_tls_config(tls_config),
+        );
+        assert!(result.is_err());
+        assert!(!ctx.list_workers()?.contains(&address.into()));
+    }
+
+    let tls_config = TcpOutletTlsConfig::new()
+        .with_ca_certificates_pem(ca_certificate)
+        .with_client_certificate(TlsCertificate {
+            full_chain_pem: include_bytes!("fixtures/tls/client.pem").to_vec(),
+            private_key_pem: include_bytes!("fixtures/tls/client.key").to_vec(),
+        });
+    tcp.create_outlet(
+        "valid_outlet",
+        "127.0.0.1:5000".try_into()?,
+        TcpOutletOptions::new().with_tls_config(tls_config),
+    )?;
+    Ok(())
+}
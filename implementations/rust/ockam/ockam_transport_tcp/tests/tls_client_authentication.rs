@@ -0,0 +1,232 @@
+use core::fmt::{Display, Formatter};
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+
+use ockam_core::compat::rand::random;
+use ockam_core::{async_trait, route, AllowAll, LocalInfoIdentifier, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpInletOptions, TcpOutletOptions, TcpTransport, TlsCertificate, TlsCertificateProvider,
+    TlsClientAccessControlFactory, TlsClientAccessControls, TlsClientAuthentication,
+    TlsClientCertificate,
+};
+
+const LENGTH: usize = 32;
+
+const CA_CERTIFICATE: &[u8] = include_bytes!("fixtures/tls/ca.pem");
+const SERVER_CERTIFICATE: &[u8] = include_bytes!("fixtures/tls/server.pem");
+const SERVER_PRIVATE_KEY: &[u8] = include_bytes!("fixtures/tls/server.key");
+const CLIENT_CERTIFICATE: &[u8] = include_bytes!("fixtures/tls/client.pem");
+const CLIENT_PRIVATE_KEY: &[u8] = include_bytes!("fixtures/tls/client.key");
+const OTHER_CLIENT_CERTIFICATE: &[u8] = include_bytes!("fixtures/tls/other_client.pem");
+const OTHER_CLIENT_PRIVATE_KEY: &[u8] = include_bytes!("fixtures/tls/other_client.key");
+const UNTRUSTED_CLIENT_CERTIFICATE: &[u8] = include_bytes!("fixtures/tls/untrusted_client.pem");
+const UNTRUSTED_CLIENT_PRIVATE_KEY: &[u8] = include_bytes!("fixtures/tls/untrusted_client.key");
+
+/// Always return the server certificate of the test certificate authority
+#[derive(Debug)]
+struct ServerCertificateProvider;
+
+impl Display for ServerCertificateProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ServerCertificateProvider")
+    }
+}
+
+#[async_trait]
+impl TlsCertificateProvider for ServerCertificateProvider {
+    async fn get_certificate(&self, _context: &Context) -> Result<TlsCertificate> {
+        Ok(TlsCertificate {
+            full_chain_pem: SERVER_CERTIFICATE.to_vec(),
+            private_key_pem: SERVER_PRIVATE_KEY.to_vec(),
+        })
+    }
+}
+
+/// Only accept the clients with a certificate for the given organizational unit
+#[derive(Debug)]
+struct OrganizationalUnitAccessControlFactory {
+    organizational_unit: String,
+}
+
+#[async_trait]
+impl TlsClientAccessControlFactory for OrganizationalUnitAccessControlFactory {
+    async fn create(
+        &self,
+        _context: &Context,
+        _their_identifier: Option<LocalInfoIdentifier>,
+        client_certificate: &TlsClientCertificate,
+    ) -> Result<Option<TlsClientAccessControls>> {
+        if client_certificate
+            .organizational_units
+            .contains(&self.organizational_unit)
+        {
+            Ok(Some((Arc::new(AllowAll), Arc::new(AllowAll))))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+async fn setup(ctx: &Context) -> Result<(String, TcpListener)> {
+    let tcp = TcpTransport::create(ctx)?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(
+        "outlet",
+        bind_address.try_into().unwrap(),
+        TcpOutletOptions::new(),
+    )?;
+
+    let tls_client_authentication = TlsClientAuthentication::new(CA_CERTIFICATE.to_vec())?
+        .with_access_control_factory(Arc::new(OrganizationalUnitAccessControlFactory {
+            organizational_unit: "legacy".to_string(),
+        }));
+    let inlet = tcp
+        .create_inlet(
+            "127.0.0.1:0",
+            route!["outlet"],
+            TcpInletOptions::new()
+                .with_tls_certificate_provider(Arc::new(ServerCertificateProvider))
+                .with_tls_client_authentication(tls_client_authentication),
+        )
+        .await?;
+
+    Ok((inlet.socket_address().to_string(), listener))
+}
+
+fn parse_certificates(pem: &[u8]) -> Vec<CertificateDer<'static>> {
+    rustls_pemfile::certs(&mut BufReader::new(pem))
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap()
+}
+
+/// Create a TLS connector trusting the test certificate authority,
+/// presenting the given client certificate and private key if any
+fn create_connector(client_certificate: Option<(&[u8], &[u8])>) -> TlsConnector {
+    let mut root_cert_store = RootCertStore::empty();
+    for certificate in parse_certificates(CA_CERTIFICATE) {
+        root_cert_store.add(certificate).unwrap();
+    }
+    let builder = ClientConfig::builder().with_root_certificates(root_cert_store);
+
+    let config = match client_certificate {
+        Some((certificate, private_key)) => {
+            let private_key = rustls_pemfile::private_key(&mut BufReader::new(private_key))
+                .unwrap()
+                .unwrap();
+            builder
+                .with_client_auth_cert(parse_certificates(certificate), private_key)
+                .unwrap()
+        }
+        None => builder.with_no_client_auth(),
+    };
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Check that the inlet closes the connection of the client before reaching the outlet.
+/// With TLS 1.3 the client can complete its side of the handshake before being rejected,
+/// in that case the rejection is only visible when reading from the connection.
+async fn assert_rejected(inlet_addr: &str, listener: &TcpListener, connector: TlsConnector) {
+    let stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let server_name = ServerName::try_from("localhost").unwrap();
+    if let Ok(mut stream) = connector.connect(server_name, stream).await {
+        let _ = stream.write_all(&random::<[u8; LENGTH]>()).await;
+        let mut payload = [0u8; LENGTH];
+        let read = stream.read(&mut payload).await;
+        assert!(
+            matches!(read, Ok(0) | Err(_)),
+            "the connection should be closed"
+        );
+    }
+
+    let accepted = tokio::time::timeout(Duration::from_millis(250), listener.accept()).await;
+    assert!(
+        accepted.is_err(),
+        "the outlet should not receive a connection"
+    );
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn tls_client_authentication__authorized_client__should_succeed(
+    ctx: &mut Context,
+) -> Result<()> {
+    let payload1: [u8; LENGTH] = random();
+    let payload2: [u8; LENGTH] = random();
+
+    let (inlet_addr, listener) = setup(ctx).await?;
+
+    let handle = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let mut payload = [0u8; LENGTH];
+        stream.read_exact(&mut payload).await.unwrap();
+        assert_eq!(payload, payload1);
+        stream.write_all(&payload2).await.unwrap();
+        stream
+    });
+
+    let connector = create_connector(Some((CLIENT_CERTIFICATE, CLIENT_PRIVATE_KEY)));
+    let stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let mut stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+
+    stream.write_all(&payload1).await.unwrap();
+    let mut payload = [0u8; LENGTH];
+    stream.read_exact(&mut payload).await.unwrap();
+    assert_eq!(payload, payload2);
+
+    let res = handle.await;
+    assert!(res.is_ok());
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn tls_client_authentication__no_client_certificate__should_be_rejected(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (inlet_addr, listener) = setup(ctx).await?;
+    assert_rejected(&inlet_addr, &listener, create_connector(None)).await;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn tls_client_authentication__untrusted_client_certificate__should_be_rejected(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (inlet_addr, listener) = setup(ctx).await?;
+    let connector = create_connector(Some((
+        UNTRUSTED_CLIENT_CERTIFICATE,
+        UNTRUSTED_CLIENT_PRIVATE_KEY,
+    )));
+    assert_rejected(&inlet_addr, &listener, connector).await;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn tls_client_authentication__disallowed_attributes__should_be_rejected(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (inlet_addr, listener) = setup(ctx).await?;
+    let connector = create_connector(Some((OTHER_CLIENT_CERTIFICATE, OTHER_CLIENT_PRIVATE_KEY)));
+    assert_rejected(&inlet_addr, &listener, connector).await;
+
+    Ok(())
+}
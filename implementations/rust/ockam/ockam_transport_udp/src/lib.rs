@@ -19,6 +19,7 @@ extern crate core;
 mod error;
 mod messages;
 mod options;
+mod portal;
 mod puncture;
 mod size_options;
 mod transport;
@@ -26,9 +27,13 @@ mod workers;
 
 pub use error::*;
 pub use options::UdpBindOptions;
+pub use portal::{
+    read_udp_inlet_max_sessions, read_udp_outlet_max_sessions, read_udp_portal_datagram_size,
+    UdpInletOptions, UdpOutletOptions, DEFAULT_UDP_PORTAL_IDLE_TIMEOUT,
+};
 pub use puncture::*;
 pub use size_options::*;
-pub use transport::{UdpBind, UdpBindArguments, UdpTransport, UdpTransportExtension};
+pub use transport::{UdpBind, UdpBindArguments, UdpInlet, UdpTransport, UdpTransportExtension};
 
 /// Transport type for UDP addresses
 pub const UDP: ockam_core::TransportType = ockam_core::TransportType::new(2);
@@ -0,0 +1,198 @@
+use crate::portal::{
+    SessionActivity, UdpInletOptions, UdpInletSessionWorker, UdpPortalSessionAddresses,
+};
+use crate::workers::{split_socket, UdpSocketRead, UdpSocketWrite};
+use crate::UdpInlet;
+use core::time::Duration;
+use ockam_core::compat::collections::HashMap;
+use ockam_core::{async_trait, route, Address, AllowAll, DenyAll, NeutralMessage, Processor};
+use ockam_core::{Result, Route};
+use ockam_node::{Context, ProcessorBuilder};
+use ockam_transport_core::TransportError;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tracing::{debug, error, instrument, warn};
+
+/// Maximum time between two checks for idle sessions
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Minimum time between two checks for idle sessions, so that a very short idle timeout
+/// doesn't turn the processor into a busy loop
+const MIN_IDLE_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+struct UdpInletSession {
+    addresses: UdpPortalSessionAddresses,
+    activity: SessionActivity,
+}
+
+/// A UDP Portal Inlet listen processor
+///
+/// UDP Portal Inlet listen processors are created by `UdpTransport`
+/// after a call is made to
+/// [`UdpTransport::create_inlet`](crate::UdpTransport::create_inlet).
+///
+/// The processor reads the datagrams received on the Inlet socket and dispatches them
+/// to one [`UdpInletSessionWorker`] per source address.
+///
+/// Sessions are closed once they are idle for the idle timeout of the Inlet. When the maximum
+/// number of sessions is reached, the datagrams of new source addresses are dropped.
+pub(crate) struct UdpInletListenProcessor {
+    socket_read: UdpSocketRead,
+    socket_write: UdpSocketWrite,
+    buffer: Vec<u8>,
+    outlet_route: Route,
+    sessions: HashMap<SocketAddr, UdpInletSession>,
+    options: UdpInletOptions,
+}
+
+impl UdpInletListenProcessor {
+    /// Start a new `UdpInletListenProcessor`
+    #[instrument(skip_all, name = "UdpInletListenProcessor::start")]
+    pub(crate) async fn start(
+        ctx: &Context,
+        outlet_route: Route,
+        addr: SocketAddr,
+        options: UdpInletOptions,
+    ) -> Result<UdpInlet> {
+        let processor_address = Address::random_tagged("UdpInletListenProcessor");
+
+        debug!("Binding UdpInletListenProcessor to {}", addr);
+        let socket = match UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                error!(%addr, %err, "could not bind to address");
+                return Err(TransportError::from(err))?;
+            }
+        };
+        let socket_addr = socket.local_addr().map_err(TransportError::from)?;
+        let (socket_read, socket_write) = split_socket(socket);
+
+        let processor = Self {
+            socket_read,
+            socket_write,
+            buffer: vec![0; options.datagram_size],
+            outlet_route,
+            sessions: Default::default(),
+            options,
+        };
+
+        ProcessorBuilder::new(processor)
+            .with_address(processor_address.clone())
+            .with_incoming_access_control(DenyAll)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)?;
+
+        Ok(UdpInlet::new(socket_addr, processor_address))
+    }
+
+    fn start_session(&mut self, ctx: &Context, peer: SocketAddr) -> Result<Address> {
+        let addresses = UdpPortalSessionAddresses::generate("inlet");
+        let activity = SessionActivity::new();
+
+        UdpInletOptions::setup_flow_control_for_session(
+            ctx.flow_controls(),
+            &addresses.remote,
+            self.outlet_route.next()?,
+        );
+
+        UdpInletSessionWorker::start(
+            ctx,
+            addresses.clone(),
+            ctx.primary_address().clone(),
+            self.socket_write.clone(),
+            peer,
+            self.outlet_route.clone(),
+            activity.clone(),
+            self.options.incoming_access_control.clone(),
+            self.options.outgoing_access_control.clone(),
+        )?;
+
+        debug!(%peer, session = %addresses.internal, "UDP inlet session started");
+
+        let internal_address = addresses.internal.clone();
+        self.sessions.insert(
+            peer,
+            UdpInletSession {
+                addresses,
+                activity,
+            },
+        );
+
+        Ok(internal_address)
+    }
+
+    /// Stop the sessions which didn't have any traffic during the idle timeout
+    fn stop_idle_sessions(&mut self, ctx: &Context) {
+        let idle_timeout = self.options.idle_timeout;
+        self.sessions.retain(|peer, session| {
+            if session.activity.idle_for() < idle_timeout {
+                return true;
+            }
+
+            debug!(%peer, "UDP inlet session expired");
+            if let Err(err) = ctx.stop_address(&session.addresses.internal) {
+                debug!(%peer, %err, "could not stop the UDP inlet session");
+            }
+            false
+        });
+    }
+}
+
+#[async_trait]
+impl Processor for UdpInletListenProcessor {
+    type Context = Context;
+
+    #[instrument(skip_all, name = "UdpInletListenProcessor::shutdown")]
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        for session in self.sessions.values() {
+            let _ = ctx.stop_address(&session.addresses.internal);
+        }
+        self.sessions.clear();
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "UdpInletListenProcessor::process")]
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let check_interval = self
+            .options
+            .idle_timeout
+            .clamp(MIN_IDLE_CHECK_INTERVAL, IDLE_CHECK_INTERVAL);
+        let received =
+            tokio::time::timeout(check_interval, self.socket_read.recv_from(&mut self.buffer))
+                .await;
+
+        self.stop_idle_sessions(ctx);
+
+        let (len, peer) = match received {
+            // Nothing received, the idle sessions have been checked
+            Err(_) => return Ok(true),
+            Ok(Ok(received)) => received,
+            Ok(Err(err)) => {
+                // Some platforms report ICMP errors for previously sent datagrams
+                // as reception errors, the socket itself is still usable
+                warn!(%err, "could not receive a datagram on the UDP inlet");
+                return Ok(true);
+            }
+        };
+
+        let session_address = match self.sessions.get(&peer) {
+            Some(session) => {
+                session.activity.touch();
+                session.addresses.internal.clone()
+            }
+            None if self.sessions.len() >= self.options.max_sessions => {
+                let max_sessions = self.options.max_sessions;
+                debug!(%peer, %max_sessions, "UDP inlet datagram dropped: too many sessions");
+                return Ok(true);
+            }
+            None => self.start_session(ctx, peer)?,
+        };
+
+        let datagram = NeutralMessage::from(self.buffer[..len].to_vec());
+        if let Err(err) = ctx.send(route![session_address], datagram).await {
+            debug!(%peer, %err, "could not forward a datagram to the UDP inlet session");
+        }
+
+        Ok(true)
+    }
+}
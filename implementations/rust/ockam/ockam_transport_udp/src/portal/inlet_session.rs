@@ -0,0 +1,124 @@
+use crate::portal::{SessionActivity, UdpPortalSessionAddresses};
+use crate::workers::UdpSocketWrite;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, route, Address, AllowSourceAddress, DenyAll, IncomingAccessControl, LocalMessage,
+    Mailbox, Mailboxes, NeutralMessage, OutgoingAccessControl, Result, Route, Routed, Worker,
+};
+use ockam_node::metrics::{Direction, PortalType};
+use ockam_node::{Context, WorkerBuilder};
+use std::net::SocketAddr;
+use tracing::{debug, warn};
+
+/// A UDP Portal Inlet session worker
+///
+/// One session is started by the
+/// [`UdpInletListenProcessor`](crate::portal::UdpInletListenProcessor)
+/// for each source address sending datagrams to the Inlet socket.
+/// Datagrams from that source address are forwarded to the Outlet, and the datagrams
+/// received from the Outlet are written back to that source address.
+pub(crate) struct UdpInletSessionWorker {
+    addresses: UdpPortalSessionAddresses,
+    socket_write: UdpSocketWrite,
+    peer: SocketAddr,
+    outlet_route: Route,
+    activity: SessionActivity,
+}
+
+impl UdpInletSessionWorker {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn start(
+        ctx: &Context,
+        addresses: UdpPortalSessionAddresses,
+        listener_address: Address,
+        socket_write: UdpSocketWrite,
+        peer: SocketAddr,
+        outlet_route: Route,
+        activity: SessionActivity,
+        incoming_access_control: Arc<dyn IncomingAccessControl>,
+        outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Result<()> {
+        let worker = Self {
+            addresses: addresses.clone(),
+            socket_write,
+            peer,
+            outlet_route,
+            activity,
+        };
+
+        let internal_mailbox = Mailbox::new(
+            addresses.internal,
+            None,
+            Arc::new(AllowSourceAddress(listener_address)),
+            Arc::new(DenyAll),
+        );
+
+        let remote_mailbox = Mailbox::new(
+            addresses.remote,
+            None,
+            incoming_access_control,
+            outgoing_access_control,
+        );
+
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(internal_mailbox, vec![remote_mailbox]))
+            .start(ctx)?;
+
+        Ok(())
+    }
+
+    /// Forward a datagram read from the local socket to the Outlet
+    ///
+    /// The datagrams are always sent to the Outlet listener, which dispatches them to the
+    /// session it started for us, or starts a new one if that session expired in the meantime.
+    async fn handle_datagram(&mut self, ctx: &Context, datagram: Vec<u8>) -> Result<()> {
+        ctx.metrics()
+            .portal_transfer(PortalType::Inlet, Direction::Incoming, datagram.len());
+
+        let msg = LocalMessage::new()
+            .with_onward_route(self.outlet_route.clone())
+            .with_return_route(route![self.addresses.remote.clone()])
+            .with_payload(datagram);
+
+        ctx.forward_from_address(msg, self.addresses.remote.clone())
+            .await
+    }
+
+    /// Write a datagram received from the Outlet to the source address of the session
+    async fn handle_remote_datagram(&mut self, ctx: &Context, datagram: Vec<u8>) -> Result<()> {
+        ctx.metrics()
+            .portal_transfer(PortalType::Inlet, Direction::Outgoing, datagram.len());
+
+        if let Err(err) = self.socket_write.send_to(&datagram, self.peer).await {
+            warn!(peer = %self.peer, %err, "could not send a datagram from the UDP inlet");
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for UdpInletSessionWorker {
+    type Context = Context;
+    type Message = NeutralMessage;
+
+    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        debug!(peer = %self.peer, "UDP inlet session closed");
+
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        self.activity.touch();
+
+        if msg.msg_addr() == &self.addresses.internal {
+            self.handle_datagram(ctx, msg.into_payload()).await
+        } else {
+            self.handle_remote_datagram(ctx, msg.into_payload()).await
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod inlet_listener;
+mod inlet_session;
+mod options;
+mod outlet_listener;
+mod outlet_session;
+mod session;
+
+pub(crate) use inlet_listener::*;
+pub(crate) use inlet_session::*;
+pub use options::*;
+pub(crate) use outlet_listener::*;
+pub(crate) use outlet_session::*;
+pub(crate) use session::*;
@@ -0,0 +1,264 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::env::get_env_with_default_ignore_error;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+use ockam_core::{Address, AllowAll, Error, IncomingAccessControl, OutgoingAccessControl, Result};
+
+/// Time after which a UDP Portal session without any traffic is closed
+pub const DEFAULT_UDP_PORTAL_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum number of sessions opened at the same time by a UDP Inlet, one per source address
+pub fn read_udp_inlet_max_sessions() -> usize {
+    get_env_with_default_ignore_error("OCKAM_UDP_INLET_MAX_SESSIONS", 1024)
+}
+
+/// Maximum number of sessions opened at the same time by a UDP Outlet, one per Inlet session
+pub fn read_udp_outlet_max_sessions() -> usize {
+    get_env_with_default_ignore_error("OCKAM_UDP_OUTLET_MAX_SESSIONS", 1024)
+}
+
+/// Maximum size of a datagram that can be sent through a UDP Portal
+pub fn read_udp_portal_datagram_size() -> usize {
+    // Largest payload of an IPv4 UDP datagram
+    get_env_with_default_ignore_error("OCKAM_UDP_PORTAL_DATAGRAM_SIZE", 65_507)
+}
+
+/// Return an error if the sessions of a UDP Portal would be closed as soon as they are
+/// started, or if no session could ever be started
+fn check_sessions_limits(idle_timeout: Duration, max_sessions: usize) -> Result<()> {
+    if idle_timeout.is_zero() {
+        return Err(Error::new(
+            Origin::Transport,
+            Kind::Invalid,
+            "the idle timeout of a UDP portal must be greater than 0",
+        ));
+    }
+    if max_sessions == 0 {
+        return Err(Error::new(
+            Origin::Transport,
+            Kind::Invalid,
+            "the maximum number of sessions of a UDP portal must be at least 1",
+        ));
+    }
+    Ok(())
+}
+
+/// Options for a UDP Inlet
+#[derive(Clone, Debug)]
+pub struct UdpInletOptions {
+    pub(crate) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(crate) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    pub(crate) idle_timeout: Duration,
+    pub(crate) max_sessions: usize,
+    pub(crate) datagram_size: usize,
+}
+
+impl UdpInletOptions {
+    /// Default constructor without Incoming Access Control
+    pub fn new() -> Self {
+        Self {
+            incoming_access_control: Arc::new(AllowAll),
+            outgoing_access_control: Arc::new(AllowAll),
+            idle_timeout: DEFAULT_UDP_PORTAL_IDLE_TIMEOUT,
+            max_sessions: read_udp_inlet_max_sessions(),
+            datagram_size: read_udp_portal_datagram_size(),
+        }
+    }
+
+    /// Close the session of a source address after it didn't send or receive
+    /// any datagram for the given duration.
+    /// The Inlet can't be created if this duration is 0
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Limit the number of sessions opened at the same time.
+    /// When the limit is reached, the datagrams of new source addresses are dropped
+    /// until some sessions are closed by the idle timeout. At least 1 session is required
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    /// Set Incoming Access Control
+    pub fn with_incoming_access_control_impl(
+        mut self,
+        access_control: impl IncomingAccessControl,
+    ) -> Self {
+        self.incoming_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Incoming Access Control
+    pub fn with_incoming_access_control(
+        mut self,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Self {
+        self.incoming_access_control = access_control;
+        self
+    }
+
+    /// Set Outgoing Access Control
+    pub fn with_outgoing_access_control_impl(
+        mut self,
+        access_control: impl OutgoingAccessControl,
+    ) -> Self {
+        self.outgoing_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Outgoing Access Control
+    pub fn with_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.outgoing_access_control = access_control;
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        check_sessions_limits(self.idle_timeout, self.max_sessions)
+    }
+
+    pub(crate) fn setup_flow_control_for_session(
+        flow_controls: &FlowControls,
+        address: &Address,
+        next: &Address,
+    ) {
+        if let Some(flow_control_id) = flow_controls
+            .find_flow_control_with_producer_address(next)
+            .map(|x| x.flow_control_id().clone())
+        {
+            // Allow a sender with corresponding flow_control_id send messages to this address
+            flow_controls.add_consumer(address, &flow_control_id);
+        }
+    }
+}
+
+impl Default for UdpInletOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options for a UDP Outlet
+#[derive(Clone, Debug)]
+pub struct UdpOutletOptions {
+    pub(crate) consumer: Vec<FlowControlId>,
+    pub(crate) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(crate) outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    pub(crate) idle_timeout: Duration,
+    pub(crate) max_sessions: usize,
+    pub(crate) datagram_size: usize,
+}
+
+impl UdpOutletOptions {
+    /// Default constructor without Incoming Access Control
+    pub fn new() -> Self {
+        Self {
+            consumer: vec![],
+            incoming_access_control: Arc::new(AllowAll),
+            outgoing_access_control: Arc::new(AllowAll),
+            idle_timeout: DEFAULT_UDP_PORTAL_IDLE_TIMEOUT,
+            max_sessions: read_udp_outlet_max_sessions(),
+            datagram_size: read_udp_portal_datagram_size(),
+        }
+    }
+
+    /// Close the socket opened for an Inlet session after it didn't send or receive
+    /// any datagram for the given duration.
+    /// The Outlet can't be created if this duration is 0
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Limit the number of Inlet sessions, each with its own socket, opened at the same time.
+    /// When the limit is reached, the datagrams of new Inlet sessions are dropped
+    /// until some sessions are closed by the idle timeout. At least 1 session is required
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    /// Set Incoming Access Control
+    pub fn with_incoming_access_control_impl(
+        mut self,
+        access_control: impl IncomingAccessControl,
+    ) -> Self {
+        self.incoming_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Incoming Access Control
+    pub fn with_incoming_access_control(
+        mut self,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Self {
+        self.incoming_access_control = access_control;
+        self
+    }
+
+    /// Set Outgoing Access Control
+    pub fn with_outgoing_access_control_impl(
+        mut self,
+        access_control: impl OutgoingAccessControl,
+    ) -> Self {
+        self.outgoing_access_control = Arc::new(access_control);
+        self
+    }
+
+    /// Set Outgoing Access Control
+    pub fn with_outgoing_access_control(
+        mut self,
+        access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Self {
+        self.outgoing_access_control = access_control;
+        self
+    }
+
+    /// Mark that this Outlet listener is a Consumer for to the given [`FlowControlId`]
+    /// Also, in this case spawned sessions will be marked as Consumers with [`FlowControlId`]
+    /// of the message that was used to create the session
+    pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
+        self.consumer.push(id.clone());
+
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        check_sessions_limits(self.idle_timeout, self.max_sessions)
+    }
+
+    pub(crate) fn setup_flow_control_for_outlet_listener(
+        &self,
+        flow_controls: &FlowControls,
+        address: &Address,
+    ) {
+        for id in &self.consumer {
+            flow_controls.add_consumer(address, id);
+        }
+    }
+
+    pub(crate) fn setup_flow_control_for_session(
+        flow_controls: &FlowControls,
+        address: &Address,
+        src_addr: &Address,
+    ) {
+        // Check if the Worker that send us this message is a Producer
+        // If yes - the session worker will be added to that flow control to be able to receive
+        // further messages from that Producer
+        if let Some(producer_info) = flow_controls.get_flow_control_with_producer(src_addr) {
+            flow_controls.add_consumer(address, producer_info.flow_control_id());
+        }
+    }
+}
+
+impl Default for UdpOutletOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
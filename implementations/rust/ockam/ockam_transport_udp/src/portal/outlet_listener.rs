@@ -0,0 +1,156 @@
+use crate::portal::{connect_socket, UdpOutletOptions, UdpOutletSessionWorker};
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::{Arc, RwLock as SyncRwLock};
+use ockam_core::{
+    async_trait, route, Address, AllowAll, LocalInfoIdentifier, LocalMessage, NeutralMessage,
+    Result, Routed, SecureChannelLocalInfo, Worker,
+};
+use ockam_node::compat::asynchronous::resolve_peer;
+use ockam_node::{Context, WorkerBuilder};
+use ockam_transport_core::HostnamePort;
+use tracing::{debug, instrument};
+
+/// Identifies an Inlet session on the Outlet side
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct UdpOutletSessionKey {
+    pub(crate) identifier: Option<LocalInfoIdentifier>,
+    pub(crate) remote_address: Address,
+}
+
+/// Addresses of the session workers started by an Outlet
+pub(crate) type UdpOutletSessions = Arc<SyncRwLock<HashMap<UdpOutletSessionKey, Address>>>;
+
+/// A UDP Portal Outlet listen worker
+///
+/// UDP Portal Outlet listen workers are created by `UdpTransport`
+/// after a call is made to
+/// [`UdpTransport::create_outlet`](crate::UdpTransport::create_outlet).
+///
+/// The first datagram of each Inlet session starts a [`UdpOutletSessionWorker`] with its
+/// own socket connected to the target, so that the target can tell the sessions apart.
+/// When the maximum number of sessions is reached, the datagrams of new Inlet sessions are dropped.
+pub(crate) struct UdpOutletListenWorker {
+    hostname_port: HostnamePort,
+    options: UdpOutletOptions,
+    sessions: UdpOutletSessions,
+}
+
+impl UdpOutletListenWorker {
+    #[instrument(skip_all, name = "UdpOutletListenWorker::start")]
+    pub(crate) fn start(
+        ctx: &Context,
+        address: Address,
+        hostname_port: HostnamePort,
+        options: UdpOutletOptions,
+    ) -> Result<()> {
+        let access_control = options.incoming_access_control.clone();
+
+        options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
+
+        let worker = Self {
+            hostname_port,
+            options,
+            sessions: Default::default(),
+        };
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control_arc(access_control)
+            .with_outgoing_access_control(AllowAll)
+            .start(ctx)?;
+
+        Ok(())
+    }
+
+    async fn reroute_msg(ctx: &Context, session: Address, msg: LocalMessage) -> Result<()> {
+        let res = ctx
+            .forward_from_address(
+                LocalMessage::new()
+                    .with_onward_route(route![session.clone()])
+                    .with_return_route(msg.return_route)
+                    .with_local_info(msg.local_info)
+                    .with_payload(msg.payload),
+                ctx.primary_address().clone(),
+            )
+            .await;
+
+        if res.is_err() {
+            debug!("Couldn't forward a datagram from the outlet to {}", session)
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for UdpOutletListenWorker {
+    type Context = Context;
+    type Message = NeutralMessage;
+
+    #[instrument(skip_all, name = "UdpOutletListenWorker::shutdown")]
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        let sessions: Vec<Address> = self.sessions.read().unwrap().values().cloned().collect();
+        for session in sessions {
+            let _ = ctx.stop_address(&session);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, name = "UdpOutletListenWorker::handle_message")]
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let their_identifier = SecureChannelLocalInfo::find_info(msg.local_message())
+            .map(|l| l.their_identifier())
+            .ok();
+
+        let src_addr = msg.src_addr().clone();
+        let msg = msg.into_local_message();
+
+        let key = UdpOutletSessionKey {
+            identifier: their_identifier,
+            remote_address: msg.return_route.recipient()?.clone(),
+        };
+
+        let (existing_session, sessions_count) = {
+            let sessions = self.sessions.read().unwrap();
+            (sessions.get(&key).cloned(), sessions.len())
+        };
+        if let Some(session) = existing_session {
+            return Self::reroute_msg(ctx, session, msg).await;
+        }
+        if sessions_count >= self.options.max_sessions {
+            let max_sessions = self.options.max_sessions;
+            debug!(remote_address = %key.remote_address, %max_sessions, "UDP outlet datagram dropped: too many sessions");
+            return Ok(());
+        }
+
+        // The target is resolved for each session to follow DNS changes
+        let peer = resolve_peer(&self.hostname_port).await?;
+        let socket = connect_socket(peer).await?;
+
+        let session = Address::random_tagged("UdpPortalSession.outlet.remote");
+        UdpOutletOptions::setup_flow_control_for_session(ctx.flow_controls(), &session, &src_addr);
+
+        UdpOutletSessionWorker::start(
+            ctx,
+            socket,
+            session.clone(),
+            msg.return_route.clone(),
+            key.clone(),
+            self.sessions.clone(),
+            self.options.idle_timeout,
+            self.options.datagram_size,
+            self.options.incoming_access_control.clone(),
+            self.options.outgoing_access_control.clone(),
+        )?;
+
+        debug!(%peer, %session, "UDP outlet session started");
+
+        self.sessions.write().unwrap().insert(key, session.clone());
+
+        Self::reroute_msg(ctx, session, msg).await
+    }
+}
@@ -0,0 +1,204 @@
+use crate::portal::{SessionActivity, UdpOutletSessionKey, UdpOutletSessions};
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, route, Address, DenyAll, IncomingAccessControl, LocalInfoIdentifier, LocalMessage,
+    NeutralMessage, OutgoingAccessControl, Processor, Result, Route, Routed,
+    SecureChannelLocalInfo, Worker,
+};
+use ockam_node::metrics::{Direction, PortalType};
+use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
+use ockam_transport_core::TransportError;
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+use tracing::{debug, trace, warn};
+
+/// A UDP Portal Outlet session worker
+///
+/// Sessions are started by the [`UdpOutletListenWorker`](crate::portal::UdpOutletListenWorker)
+/// for each Inlet session. The worker writes the datagrams received from the Inlet to a
+/// socket connected to the Outlet target, while the paired [`UdpOutletSessionReceiver`]
+/// sends the datagrams coming from the target back to the Inlet.
+pub(crate) struct UdpOutletSessionWorker {
+    socket: Arc<UdpSocket>,
+    their_identifier: Option<LocalInfoIdentifier>,
+    receiver_address: Address,
+    key: UdpOutletSessionKey,
+    sessions: UdpOutletSessions,
+    activity: SessionActivity,
+}
+
+impl UdpOutletSessionWorker {
+    /// Start the worker and the receiver of a session.
+    /// The socket must already be connected to the Outlet target
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn start(
+        ctx: &Context,
+        socket: UdpSocket,
+        address: Address,
+        inlet_route: Route,
+        key: UdpOutletSessionKey,
+        sessions: UdpOutletSessions,
+        idle_timeout: Duration,
+        datagram_size: usize,
+        incoming_access_control: Arc<dyn IncomingAccessControl>,
+        outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Result<()> {
+        let socket = Arc::new(socket);
+        let activity = SessionActivity::new();
+        let receiver_address = Address::random_tagged("UdpPortalSession.outlet.receiver");
+
+        let worker = Self {
+            socket: socket.clone(),
+            their_identifier: key.identifier.clone(),
+            receiver_address: receiver_address.clone(),
+            key,
+            sessions,
+            activity: activity.clone(),
+        };
+        WorkerBuilder::new(worker)
+            .with_address(address.clone())
+            .with_incoming_access_control_arc(incoming_access_control)
+            .with_outgoing_access_control(DenyAll)
+            .start(ctx)?;
+
+        let receiver = UdpOutletSessionReceiver {
+            socket,
+            buffer: vec![0; datagram_size],
+            worker_address: address,
+            inlet_route,
+            idle_timeout,
+            activity,
+        };
+        ProcessorBuilder::new(receiver)
+            .with_address(receiver_address)
+            .with_incoming_access_control(DenyAll)
+            .with_outgoing_access_control_arc(outgoing_access_control)
+            .start(ctx)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for UdpOutletSessionWorker {
+    type Context = Context;
+    type Message = NeutralMessage;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.sessions.write().unwrap().remove(&self.key);
+        // The receiver may already be stopped if the session expired
+        let _ = ctx.stop_address(&self.receiver_address);
+
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let their_identifier = SecureChannelLocalInfo::find_info(msg.local_message())
+            .map(|l| l.their_identifier())
+            .ok();
+
+        if their_identifier != self.their_identifier {
+            warn!(
+                "dropping a datagram sent by {:?} to a UDP outlet session opened by {:?}",
+                their_identifier.as_ref().map(|i| i.to_string()),
+                self.their_identifier.as_ref().map(|i| i.to_string()),
+            );
+            return Ok(());
+        }
+
+        self.activity.touch();
+
+        let datagram = msg.into_payload();
+        ctx.metrics()
+            .portal_transfer(PortalType::Outlet, Direction::Outgoing, datagram.len());
+
+        if let Err(err) = self.socket.send(&datagram).await {
+            warn!(%err, "could not send a datagram from the UDP outlet");
+        }
+
+        Ok(())
+    }
+}
+
+/// Receives the datagrams sent by the Outlet target and forwards them to the Inlet.
+/// It also closes the session when it stays idle for too long.
+pub(crate) struct UdpOutletSessionReceiver {
+    socket: Arc<UdpSocket>,
+    buffer: Vec<u8>,
+    worker_address: Address,
+    inlet_route: Route,
+    idle_timeout: Duration,
+    activity: SessionActivity,
+}
+
+#[async_trait]
+impl Processor for UdpOutletSessionReceiver {
+    type Context = Context;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // The worker may already be stopped if the Outlet was deleted
+        let _ = ctx.stop_address(&self.worker_address);
+
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let idle_for = self.activity.idle_for();
+        if idle_for >= self.idle_timeout {
+            debug!(session = %self.worker_address, "UDP outlet session expired");
+            return Ok(false);
+        }
+
+        let received = tokio::time::timeout(
+            self.idle_timeout - idle_for,
+            self.socket.recv(&mut self.buffer),
+        )
+        .await;
+
+        let len = match received {
+            // Check again if the session is expired, the worker may have sent datagrams
+            Err(_) => return Ok(true),
+            Ok(Ok(len)) => len,
+            Ok(Err(err)) => {
+                // A connected socket reports an ICMP port unreachable as a reception error,
+                // the target may start listening later
+                trace!(%err, "could not receive a datagram on the UDP outlet");
+                return Ok(true);
+            }
+        };
+
+        self.activity.touch();
+        ctx.metrics()
+            .portal_transfer(PortalType::Outlet, Direction::Incoming, len);
+
+        let msg = LocalMessage::new()
+            .with_onward_route(self.inlet_route.clone())
+            .with_return_route(route![self.worker_address.clone()])
+            .with_payload(self.buffer[..len].to_vec());
+
+        ctx.forward_from_address(msg, ctx.primary_address().clone())
+            .await?;
+
+        Ok(true)
+    }
+}
+
+/// Bind a socket with an ephemeral port and connect it to the Outlet target
+pub(crate) async fn connect_socket(peer: SocketAddr) -> Result<UdpSocket> {
+    let bind_address = if peer.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(bind_address)
+        .await
+        .map_err(|_| TransportError::BindFailed)?;
+    socket.connect(peer).await.map_err(TransportError::from)?;
+
+    Ok(socket)
+}
@@ -0,0 +1,58 @@
+use core::time::Duration;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Address;
+use std::time::Instant;
+
+/// Addresses of a UDP Portal session
+#[derive(Clone, Debug)]
+pub(crate) struct UdpPortalSessionAddresses {
+    /// Used to receive datagrams read from the local socket
+    pub(crate) internal: Address,
+    /// Used to receive messages from the other side of the Portal
+    pub(crate) remote: Address,
+}
+
+impl UdpPortalSessionAddresses {
+    pub(crate) fn generate(portal_type: &str) -> Self {
+        Self {
+            internal: Address::random_tagged(&format!("UdpPortalSession.{}.internal", portal_type)),
+            remote: Address::random_tagged(&format!("UdpPortalSession.{}.remote", portal_type)),
+        }
+    }
+}
+
+/// Time of the last datagram sent or received by a UDP Portal session,
+/// shared between the workers and processors of that session
+#[derive(Clone, Debug)]
+pub(crate) struct SessionActivity(Arc<Mutex<Instant>>);
+
+impl SessionActivity {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    /// Record some traffic for the session
+    pub(crate) fn touch(&self) {
+        *self.0.lock().unwrap() = Instant::now();
+    }
+
+    /// Duration since the last datagram of the session
+    pub(crate) fn idle_for(&self) -> Duration {
+        self.0.lock().unwrap().elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_activity_is_reset_by_touch() {
+        let activity = SessionActivity::new();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(activity.idle_for() >= Duration::from_millis(20));
+
+        activity.clone().touch();
+        assert!(activity.idle_for() < Duration::from_millis(20));
+    }
+}
@@ -1,8 +1,10 @@
 mod bind;
 mod lifecycle;
+mod portals;
 mod puncture;
 
 pub use bind::*;
+pub use portals::*;
 
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, Result};
@@ -0,0 +1,125 @@
+use crate::portal::{UdpInletListenProcessor, UdpOutletListenWorker};
+use crate::{UdpInletOptions, UdpOutletOptions, UdpTransport};
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+use ockam_core::{Address, Result, Route};
+use ockam_node::Context;
+use ockam_transport_core::{parse_socket_addr, HostnamePort};
+use std::net::SocketAddr;
+use tracing::instrument;
+
+impl UdpTransport {
+    /// Create a UDP Inlet that binds to bind_addr and forwards the datagrams it receives to the
+    /// Outlet using outlet_route. Each source address gets its own session, so that the
+    /// datagrams sent back by the Outlet are written to the right source address.
+    /// Pair of corresponding Inlet and Outlet is called Portal.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpInletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let route_path = route!["outlet"];
+    ///
+    /// let udp = UdpTransport::create(&ctx)?;
+    /// let inlet = udp.create_inlet("127.0.0.1:5353", route_path, UdpInletOptions::new()).await?;
+    /// # udp.stop_inlet(inlet.processor_address())?;
+    /// # Ok(()) }
+    /// ```
+    #[instrument(skip(self), fields(address = ? bind_addr.clone().into(), outlet_route = ? outlet_route.clone()))]
+    pub async fn create_inlet(
+        &self,
+        bind_addr: impl Into<String> + Clone + Debug,
+        outlet_route: impl Into<Route> + Clone + Debug,
+        options: UdpInletOptions,
+    ) -> Result<UdpInlet> {
+        options.validate()?;
+        let socket_address = parse_socket_addr(&bind_addr.into())?;
+        UdpInletListenProcessor::start(&self.ctx, outlet_route.into(), socket_address, options)
+            .await
+    }
+
+    /// Stop the inlet with the given processor address, and all its sessions
+    #[instrument(skip(self), fields(address = % address))]
+    pub fn stop_inlet(&self, address: &Address) -> Result<()> {
+        self.ctx.stop_address(address)
+    }
+
+    /// Create a UDP Outlet at address, that forwards the datagrams received from Inlets
+    /// to the peer. Each Inlet session uses a distinct local socket, and the datagrams
+    /// received on that socket are sent back to the Inlet session.
+    /// Pair of corresponding Inlet and Outlet is called Portal.
+    ///
+    /// ```rust
+    /// use ockam_transport_udp::{UdpOutletOptions, UdpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Address, Result};
+    /// # use ockam_transport_core::HostnamePort;
+    ///
+    /// async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let udp = UdpTransport::create(&ctx)?;
+    /// let address: Address = "outlet".into();
+    /// udp.create_outlet(address.clone(), HostnamePort::new("localhost", 53)?, UdpOutletOptions::new())?;
+    /// # udp.stop_outlet(&address)?;
+    /// # Ok(()) }
+    /// ```
+    #[instrument(skip(self), fields(address = ? address.clone().into(), peer = peer.clone().to_string()))]
+    pub fn create_outlet(
+        &self,
+        address: impl Into<Address> + Clone + Debug,
+        peer: HostnamePort,
+        options: UdpOutletOptions,
+    ) -> Result<()> {
+        options.validate()?;
+        UdpOutletListenWorker::start(&self.ctx, address.into(), peer, options)
+    }
+
+    /// Stop the outlet at address, and all its sessions
+    #[instrument(skip(self), fields(address = % address))]
+    pub fn stop_outlet(&self, address: &Address) -> Result<()> {
+        self.ctx.stop_address(address)
+    }
+}
+
+/// Result of [`UdpTransport::create_inlet`] call.
+#[derive(Clone, Debug)]
+pub struct UdpInlet {
+    socket_address: SocketAddr,
+    processor_address: Address,
+}
+
+impl fmt::Display for UdpInlet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Socket: {}. Processor address: {}",
+            self.socket_address, self.processor_address
+        )
+    }
+}
+
+impl UdpInlet {
+    /// Constructor
+    pub(crate) fn new(socket_address: SocketAddr, processor_address: Address) -> Self {
+        Self {
+            socket_address,
+            processor_address,
+        }
+    }
+
+    /// Socket Address
+    pub fn socket_address(&self) -> SocketAddr {
+        self.socket_address
+    }
+
+    /// Processor address
+    pub fn processor_address(&self) -> &Address {
+        &self.processor_address
+    }
+
+    /// Stop the Inlet
+    pub fn stop(&self, ctx: &Context) -> Result<()> {
+        ctx.stop_address(&self.processor_address)
+    }
+}
@@ -6,11 +6,15 @@ use core::str::FromStr;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, Any, Error, Result, Routed, Worker};
 use ockam_node::compat::asynchronous::resolve_peer;
+use ockam_node::metrics::Direction;
 use ockam_node::Context;
 use ockam_transport_core::{HostnamePort, TransportError};
 use std::net::SocketAddr;
 use tracing::{error, trace, warn};
 
+/// Name of the transport in the connection metrics
+const UDP_TRANSPORT: &str = "udp";
+
 /// A sender for the UDP transport
 ///
 /// This worker handles the sending of messages on a
@@ -49,7 +53,20 @@ impl Worker for UdpSenderWorker {
     type Message = Any;
     type Context = Context;
 
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // a socket bound to a specific peer is counted as an outgoing connection
+        let direction = if self.peer.is_some() {
+            Direction::Outgoing
+        } else {
+            Direction::Incoming
+        };
+        ctx.metrics().connection_opened(UDP_TRANSPORT, direction);
+
+        Ok(())
+    }
+
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        ctx.metrics().connection_closed(UDP_TRANSPORT);
         let _ = ctx.stop_address(self.addresses.receiver_address());
 
         Ok(())
@@ -0,0 +1,250 @@
+use ockam_core::{route, Address, Result};
+use ockam_node::Context;
+use ockam_transport_core::{HostnamePort, TransportError};
+use ockam_transport_udp::{UdpInletOptions, UdpOutletOptions, UdpTransport};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Start a UDP server replying with the received datagram prefixed by "echo: "
+async fn start_echo_server() -> Result<SocketAddr> {
+    let socket = UdpSocket::bind("127.0.0.1:0")
+        .await
+        .map_err(TransportError::from)?;
+    let address = socket.local_addr().map_err(TransportError::from)?;
+
+    tokio::spawn(async move {
+        let mut buffer = vec![0; 1024];
+        while let Ok((len, peer)) = socket.recv_from(&mut buffer).await {
+            let reply = [b"echo: ", &buffer[..len]].concat();
+            let _ = socket.send_to(&reply, peer).await;
+        }
+    });
+
+    Ok(address)
+}
+
+async fn send_and_receive(client: &UdpSocket, inlet: SocketAddr, datagram: &[u8]) -> Vec<u8> {
+    client.send_to(datagram, inlet).await.unwrap();
+
+    let mut buffer = vec![0; 1024];
+    let (len, _) = tokio::time::timeout(TIMEOUT, client.recv_from(&mut buffer))
+        .await
+        .unwrap()
+        .unwrap();
+    buffer.truncate(len);
+    buffer
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__datagrams_from_two_clients__are_answered_separately(
+    ctx: &mut Context,
+) -> Result<()> {
+    let udp = UdpTransport::create(ctx)?;
+    let server = start_echo_server().await?;
+
+    let outlet: Address = "outlet".into();
+    udp.create_outlet(
+        outlet.clone(),
+        HostnamePort::from(server),
+        UdpOutletOptions::new(),
+    )?;
+    let inlet = udp
+        .create_inlet("127.0.0.1:0", route![outlet], UdpInletOptions::new())
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    for i in 0..3 {
+        let reply = send_and_receive(&client1, inlet.socket_address(), b"hello 1").await;
+        assert_eq!(reply, b"echo: hello 1", "client 1, datagram {i}");
+
+        let reply = send_and_receive(&client2, inlet.socket_address(), b"hello 2").await;
+        assert_eq!(reply, b"echo: hello 2", "client 2, datagram {i}");
+    }
+
+    inlet.stop(ctx)?;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__idle_session__is_closed(ctx: &mut Context) -> Result<()> {
+    let udp = UdpTransport::create(ctx)?;
+    let server = start_echo_server().await?;
+    let idle_timeout = Duration::from_millis(200);
+
+    let outlet: Address = "outlet".into();
+    udp.create_outlet(
+        outlet.clone(),
+        HostnamePort::from(server),
+        UdpOutletOptions::new().with_idle_timeout(idle_timeout),
+    )?;
+    let inlet = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![outlet],
+            UdpInletOptions::new().with_idle_timeout(idle_timeout),
+        )
+        .await?;
+    let workers_before_session = ctx.list_workers()?.len();
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let reply = send_and_receive(&client, inlet.socket_address(), b"hello").await;
+    assert_eq!(reply, b"echo: hello");
+    assert!(ctx.list_workers()?.len() > workers_before_session);
+
+    tokio::time::sleep(idle_timeout * 8).await;
+    assert_eq!(ctx.list_workers()?.len(), workers_before_session);
+
+    // a new session is started for the next datagram
+    let reply = send_and_receive(&client, inlet.socket_address(), b"hello again").await;
+    assert_eq!(reply, b"echo: hello again");
+
+    inlet.stop(ctx)?;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__too_many_sessions__new_clients_are_dropped(ctx: &mut Context) -> Result<()> {
+    let udp = UdpTransport::create(ctx)?;
+    let server = start_echo_server().await?;
+    let idle_timeout = Duration::from_millis(200);
+
+    let outlet: Address = "outlet".into();
+    udp.create_outlet(
+        outlet.clone(),
+        HostnamePort::from(server),
+        UdpOutletOptions::new(),
+    )?;
+    let inlet = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![outlet],
+            UdpInletOptions::new()
+                .with_max_sessions(1)
+                .with_idle_timeout(idle_timeout),
+        )
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let reply = send_and_receive(&client1, inlet.socket_address(), b"hello 1").await;
+    assert_eq!(reply, b"echo: hello 1");
+
+    // the second client doesn't get a session
+    client2
+        .send_to(b"hello 2", inlet.socket_address())
+        .await
+        .unwrap();
+    let mut buffer = vec![0; 1024];
+    let received = tokio::time::timeout(idle_timeout / 2, client2.recv_from(&mut buffer)).await;
+    assert!(received.is_err());
+
+    // once the first session is idle, the second client gets a session
+    tokio::time::sleep(idle_timeout * 8).await;
+    let reply = send_and_receive(&client2, inlet.socket_address(), b"hello 2").await;
+    assert_eq!(reply, b"echo: hello 2");
+
+    inlet.stop(ctx)?;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__too_many_outlet_sessions__new_inlet_sessions_are_dropped(
+    ctx: &mut Context,
+) -> Result<()> {
+    let udp = UdpTransport::create(ctx)?;
+    let server = start_echo_server().await?;
+    let idle_timeout = Duration::from_millis(200);
+
+    let outlet: Address = "outlet".into();
+    udp.create_outlet(
+        outlet.clone(),
+        HostnamePort::from(server),
+        UdpOutletOptions::new()
+            .with_max_sessions(1)
+            .with_idle_timeout(idle_timeout),
+    )?;
+    let inlet1 = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![outlet.clone()],
+            UdpInletOptions::new().with_idle_timeout(idle_timeout),
+        )
+        .await?;
+    let inlet2 = udp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![outlet],
+            UdpInletOptions::new().with_idle_timeout(idle_timeout),
+        )
+        .await?;
+
+    let client1 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client2 = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let reply = send_and_receive(&client1, inlet1.socket_address(), b"hello 1").await;
+    assert_eq!(reply, b"echo: hello 1");
+
+    // the session of the second inlet doesn't get a socket on the outlet
+    client2
+        .send_to(b"hello 2", inlet2.socket_address())
+        .await
+        .unwrap();
+    let mut buffer = vec![0; 1024];
+    let received = tokio::time::timeout(idle_timeout / 2, client2.recv_from(&mut buffer)).await;
+    assert!(received.is_err());
+
+    // once the first session is idle, the second inlet session gets a socket
+    tokio::time::sleep(idle_timeout * 8).await;
+    let reply = send_and_receive(&client2, inlet2.socket_address(), b"hello 2").await;
+    assert_eq!(reply, b"echo: hello 2");
+
+    inlet1.stop(ctx)?;
+    inlet2.stop(ctx)?;
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn udp_portal__zero_idle_timeout_or_max_sessions__is_rejected(
+    ctx: &mut Context,
+) -> Result<()> {
+    let udp = UdpTransport::create(ctx)?;
+    let server = start_echo_server().await?;
+
+    let outlet_options = [
+        UdpOutletOptions::new().with_idle_timeout(Duration::ZERO),
+        UdpOutletOptions::new().with_max_sessions(0),
+    ];
+    for options in outlet_options {
+        assert!(udp
+            .create_outlet("outlet", HostnamePort::from(server), options)
+            .is_err());
+    }
+
+    let inlet_options = [
+        UdpInletOptions::new().with_idle_timeout(Duration::ZERO),
+        UdpInletOptions::new().with_max_sessions(0),
+    ];
+    for options in inlet_options {
+        assert!(udp
+            .create_inlet("127.0.0.1:0", route!["outlet"], options)
+            .await
+            .is_err());
+    }
+
+    Ok(())
+}
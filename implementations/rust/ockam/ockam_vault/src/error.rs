@@ -39,6 +39,12 @@ pub enum VaultError {
     InsufficientEncryptBuffer,
     /// Buffer is too short during decryption
     InsufficientDecryptBuffer,
+    /// ML-KEM encapsulation failed
+    MlKemEncapsulate,
+    /// ML-KEM decapsulation failed
+    MlKemDecapsulate,
+    /// ML-KEM is not supported by the vault
+    MlKemUnsupported,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -63,6 +69,9 @@ impl core::fmt::Display for VaultError {
             Self::AeadSecretNotFound => write!(f, "aead secret was not found in the storage"),
             Self::InsufficientEncryptBuffer => write!(f, "insufficient encrypt buffer"),
             Self::InsufficientDecryptBuffer => write!(f, "insufficient decrypt buffer"),
+            Self::MlKemEncapsulate => write!(f, "ml-kem encapsulation failed"),
+            Self::MlKemDecapsulate => write!(f, "ml-kem decapsulation failed"),
+            Self::MlKemUnsupported => write!(f, "ml-kem is not supported by this vault"),
         }
     }
 }
@@ -74,6 +83,7 @@ impl From<VaultError> for Error {
         let kind = match err {
             InvalidPublicKey | InvalidKeyType | InvalidHkdfOutputType => Kind::Misuse,
             UnknownEcdhKeyType => Kind::NotFound,
+            MlKemUnsupported => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
@@ -21,6 +21,21 @@ impl X25519SecretKey {
     }
 }
 
+/// ML-KEM-768 Decapsulation (Secret) Key, in its encoded form.
+#[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct MlKem768SecretKey(Vec<u8>);
+
+impl MlKem768SecretKey {
+    /// Constructor.
+    pub fn new(key: Vec<u8>) -> Self {
+        Self(key)
+    }
+
+    pub(crate) fn key(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
 /// Buffer with sensitive data, like HKDF output.
 #[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct BufferSecret(Vec<u8>);
@@ -1,3 +1,5 @@
+use ml_kem::kem::{Decapsulate, Encapsulate};
+use ml_kem::{EncodedSizeUser, KemCore, MlKem768};
 use sha2::{Digest, Sha256};
 use tracing::instrument;
 
@@ -15,18 +17,22 @@ use crate::storage::SecretsSqlxDatabase;
 
 use crate::{
     AeadSecret, AeadSecretKeyHandle, BufferSecret, HKDFNumberOfOutputs, HandleToSecret, HashOutput,
-    HkdfOutput, SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError,
-    VaultForSecureChannels, X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle,
-    AEAD_SECRET_LENGTH,
+    HkdfOutput, MlKem768Ciphertext, MlKem768PublicKey, MlKem768SecretKey, MlKem768SecretKeyHandle,
+    SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle, AEAD_SECRET_LENGTH,
 };
 
 use super::make_aes;
 
+type MlKem768DecapsulationKey = <MlKem768 as KemCore>::DecapsulationKey;
+type MlKem768EncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+
 /// [`SecureChannelVault`] implementation using software
 pub struct SoftwareVaultForSecureChannels {
     ephemeral_buffer_secrets: Arc<RwLock<BTreeMap<SecretBufferHandle, BufferSecret>>>,
     ephemeral_aead_secrets: Arc<RwLock<BTreeMap<AeadSecretKeyHandle, AeadSecret>>>,
     ephemeral_x25519_secrets: Arc<RwLock<BTreeMap<X25519SecretKeyHandle, X25519SecretKey>>>,
+    ephemeral_ml_kem_secrets: Arc<RwLock<BTreeMap<MlKem768SecretKeyHandle, MlKem768SecretKey>>>,
     secrets_repository: Arc<dyn SecretsRepository>,
 }
 
@@ -37,6 +43,7 @@ impl SoftwareVaultForSecureChannels {
             ephemeral_buffer_secrets: Default::default(),
             ephemeral_aead_secrets: Default::default(),
             ephemeral_x25519_secrets: Default::default(),
+            ephemeral_ml_kem_secrets: Default::default(),
             secrets_repository,
         }
     }
@@ -102,6 +109,11 @@ impl SoftwareVaultForSecureChannels {
         self.ephemeral_x25519_secrets.read().unwrap().len()
     }
 
+    /// Return the total number of ephemeral ML-KEM secrets present in the Vault
+    pub fn number_of_ephemeral_ml_kem_secrets(&self) -> usize {
+        self.ephemeral_ml_kem_secrets.read().unwrap().len()
+    }
+
     /// Return the total number of ephemeral buffer secrets present in the Vault
     pub fn number_of_ephemeral_buffer_secrets(&self) -> usize {
         self.ephemeral_buffer_secrets.read().unwrap().len()
@@ -171,6 +183,37 @@ impl SoftwareVaultForSecureChannels {
         X25519SecretKey::new(secret.to_bytes())
     }
 
+    fn generate_ml_kem_secret() -> MlKem768SecretKey {
+        let (decapsulation_key, _) = MlKem768::generate(&mut thread_rng());
+        MlKem768SecretKey::new(decapsulation_key.as_bytes().to_vec())
+    }
+
+    fn import_ml_kem_secret_key(secret: &MlKem768SecretKey) -> Result<MlKem768DecapsulationKey> {
+        let encoded = secret
+            .key()
+            .try_into()
+            .map_err(|_| VaultError::InvalidSecretLength)?;
+        Ok(MlKem768DecapsulationKey::from_bytes(&encoded))
+    }
+
+    fn import_ml_kem_public_key(
+        public_key: &MlKem768PublicKey,
+    ) -> Result<MlKem768EncapsulationKey> {
+        let encoded = public_key
+            .0
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::InvalidPublicLength)?;
+        Ok(MlKem768EncapsulationKey::from_bytes(&encoded))
+    }
+
+    fn get_ml_kem_secret(&self, handle: &MlKem768SecretKeyHandle) -> Result<MlKem768SecretKey> {
+        match self.ephemeral_ml_kem_secrets.read().unwrap().get(handle) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(VaultError::KeyNotFound)?,
+        }
+    }
+
     fn import_buffer_secret_impl(&self, secret: BufferSecret) -> SecretBufferHandle {
         let handle = Self::generate_buffer_handle();
 
@@ -382,6 +425,86 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
         Ok(Self::compute_handle_for_public_key(public_key))
     }
 
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKem768SecretKeyHandle> {
+        let secret = Self::generate_ml_kem_secret();
+        let handle = MlKem768SecretKeyHandle(Self::generate_random_handle());
+
+        self.ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone(), secret);
+
+        Ok(handle)
+    }
+
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        secret_key_handle: MlKem768SecretKeyHandle,
+    ) -> Result<bool> {
+        Ok(self
+            .ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .remove(&secret_key_handle)
+            .is_some())
+    }
+
+    async fn get_ml_kem_public_key(
+        &self,
+        secret_key_handle: &MlKem768SecretKeyHandle,
+    ) -> Result<MlKem768PublicKey> {
+        let secret = self.get_ml_kem_secret(secret_key_handle)?;
+        let decapsulation_key = Self::import_ml_kem_secret_key(&secret)?;
+        let public_key = decapsulation_key
+            .encapsulation_key()
+            .as_bytes()
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::InvalidPublicLength)?;
+
+        Ok(MlKem768PublicKey(public_key))
+    }
+
+    async fn ml_kem_encapsulate(
+        &self,
+        peer_public_key: &MlKem768PublicKey,
+    ) -> Result<(MlKem768Ciphertext, SecretBufferHandle)> {
+        let encapsulation_key = Self::import_ml_kem_public_key(peer_public_key)?;
+        let (ciphertext, shared_secret) = encapsulation_key
+            .encapsulate(&mut thread_rng())
+            .map_err(|_| VaultError::MlKemEncapsulate)?;
+        let ciphertext = ciphertext
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::MlKemEncapsulate)?;
+
+        let shared_secret = BufferSecret::new(shared_secret.to_vec());
+
+        Ok((
+            MlKem768Ciphertext(ciphertext),
+            self.import_buffer_secret_impl(shared_secret),
+        ))
+    }
+
+    async fn ml_kem_decapsulate(
+        &self,
+        secret_key_handle: &MlKem768SecretKeyHandle,
+        ciphertext: &MlKem768Ciphertext,
+    ) -> Result<SecretBufferHandle> {
+        let secret = self.get_ml_kem_secret(secret_key_handle)?;
+        let decapsulation_key = Self::import_ml_kem_secret_key(&secret)?;
+        let ciphertext = ciphertext
+            .0
+            .as_slice()
+            .try_into()
+            .map_err(|_| VaultError::MlKemDecapsulate)?;
+        let shared_secret = decapsulation_key
+            .decapsulate(&ciphertext)
+            .map_err(|_| VaultError::MlKemDecapsulate)?;
+
+        Ok(self.import_buffer_secret_impl(BufferSecret::new(shared_secret.to_vec())))
+    }
+
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle> {
         Ok(self.import_buffer_secret_impl(BufferSecret::new(buffer)))
     }
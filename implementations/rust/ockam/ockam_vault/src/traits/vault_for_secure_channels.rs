@@ -1,5 +1,6 @@
 use crate::{
-    AeadSecretKeyHandle, HashOutput, HkdfOutput, SecretBufferHandle, X25519PublicKey,
+    AeadSecretKeyHandle, HashOutput, HkdfOutput, MlKem768Ciphertext, MlKem768PublicKey,
+    MlKem768SecretKeyHandle, SecretBufferHandle, VaultError, X25519PublicKey,
     X25519SecretKeyHandle,
 };
 
@@ -94,6 +95,48 @@ pub trait VaultForSecureChannels: Send + Sync + 'static {
         public_key: &X25519PublicKey,
     ) -> Result<X25519SecretKeyHandle>;
 
+    /// Generate a fresh ephemeral (not persisted) ML-KEM-768 Decapsulation Key.
+    /// The ML-KEM-768 functions are not supported by default.
+    /// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKem768SecretKeyHandle> {
+        Err(VaultError::MlKemUnsupported.into())
+    }
+
+    /// Delete ephemeral ML-KEM-768 Decapsulation Key.
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        _secret_key_handle: MlKem768SecretKeyHandle,
+    ) -> Result<bool> {
+        Err(VaultError::MlKemUnsupported.into())
+    }
+
+    /// Get [`MlKem768PublicKey`] of the corresponding ML-KEM-768 Decapsulation Key given its Handle.
+    async fn get_ml_kem_public_key(
+        &self,
+        _secret_key_handle: &MlKem768SecretKeyHandle,
+    ) -> Result<MlKem768PublicKey> {
+        Err(VaultError::MlKemUnsupported.into())
+    }
+
+    /// Perform ML-KEM-768 encapsulation for the peer public key.
+    /// Return the ciphertext to send to the peer and the shared secret.
+    async fn ml_kem_encapsulate(
+        &self,
+        _peer_public_key: &MlKem768PublicKey,
+    ) -> Result<(MlKem768Ciphertext, SecretBufferHandle)> {
+        Err(VaultError::MlKemUnsupported.into())
+    }
+
+    /// Perform ML-KEM-768 decapsulation of a ciphertext sent by the peer.
+    /// Return the shared secret.
+    async fn ml_kem_decapsulate(
+        &self,
+        _secret_key_handle: &MlKem768SecretKeyHandle,
+        _ciphertext: &MlKem768Ciphertext,
+    ) -> Result<SecretBufferHandle> {
+        Err(VaultError::MlKemUnsupported.into())
+    }
+
     /// Import a Secret Buffer.
     async fn import_secret_buffer(&self, buffer: Vec<u8>) -> Result<SecretBufferHandle>;
 
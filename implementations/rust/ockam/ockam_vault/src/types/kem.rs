@@ -0,0 +1,34 @@
+use minicbor::{CborLen, Decode, Encode};
+
+/// ML-KEM-768 encapsulation (public) key length.
+pub const ML_KEM_768_PUBLIC_KEY_LENGTH: usize = 1184;
+
+/// ML-KEM-768 ciphertext length.
+pub const ML_KEM_768_CIPHERTEXT_LENGTH: usize = 1088;
+
+/// ML-KEM-768 shared secret length.
+pub const ML_KEM_768_SHARED_SECRET_LENGTH: usize = 32;
+
+/// ML-KEM-768 Encapsulation (Public) Key is used to establish a shared secret that
+/// is resistant to attacks by quantum computers.
+///
+/// - ML-KEM as defined [here][1].
+///
+/// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+#[derive(Encode, Decode, CborLen, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct MlKem768PublicKey(
+    #[cbor(n(0), with = "minicbor::bytes")] pub [u8; ML_KEM_768_PUBLIC_KEY_LENGTH],
+);
+
+/// ML-KEM-768 Ciphertext, produced by the encapsulation of a shared secret
+/// for a given [`MlKem768PublicKey`].
+///
+/// - ML-KEM as defined [here][1].
+///
+/// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+#[derive(Encode, Decode, CborLen, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct MlKem768Ciphertext(
+    #[cbor(n(0), with = "minicbor::bytes")] pub [u8; ML_KEM_768_CIPHERTEXT_LENGTH],
+);
@@ -54,6 +54,10 @@ pub enum SigningKeyType {
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct X25519SecretKeyHandle(pub HandleToSecret);
 
+/// A handle to a ML-KEM-768 Decapsulation (Secret) Key.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct MlKem768SecretKeyHandle(pub HandleToSecret);
+
 /// A handle to a secret Buffer (like an HKDF output).
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct SecretBufferHandle(pub HandleToSecret);
@@ -0,0 +1,30 @@
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("cannot load the PKCS#11 module {path}: {error}")]
+    LoadModule { path: String, error: String },
+    #[error("the PKCS#11 module does not provide the function {0}")]
+    MissingFunction(&'static str),
+    #[error("the PKCS#11 function {function} failed with the error code {code:#x}")]
+    Call { function: &'static str, code: u64 },
+    #[error("the PKCS#11 token returned an attribute which cannot be read")]
+    UnavailableAttribute,
+    #[error("the PKCS#11 token returned an invalid public key")]
+    InvalidPublicKey,
+    #[error("the PKCS#11 token returned an invalid signature")]
+    InvalidSignature,
+    #[error("a blocking call to the PKCS#11 module did not complete: {0}")]
+    BlockingCall(String),
+    #[error("key was not found")]
+    KeyNotFound,
+}
+
+impl From<Error> for ockam_core::Error {
+    #[track_caller]
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
@@ -0,0 +1,218 @@
+//! Minimal bindings to the PKCS#11 (Cryptoki) v2.40 C interface.
+//!
+//! Only the types, constants and functions used by the signing vault are declared.
+//! The layout of the structures follows the `pkcs11.h` header: structures are packed
+//! on Windows and use the native alignment on other platforms.
+#![allow(non_camel_case_types, non_snake_case, missing_docs)]
+
+use core::ffi::{c_uchar, c_ulong, c_void};
+
+pub type CK_BYTE = c_uchar;
+pub type CK_ULONG = c_ulong;
+pub type CK_RV = CK_ULONG;
+pub type CK_FLAGS = CK_ULONG;
+pub type CK_SLOT_ID = CK_ULONG;
+pub type CK_SESSION_HANDLE = CK_ULONG;
+pub type CK_OBJECT_HANDLE = CK_ULONG;
+pub type CK_OBJECT_CLASS = CK_ULONG;
+pub type CK_KEY_TYPE = CK_ULONG;
+pub type CK_USER_TYPE = CK_ULONG;
+pub type CK_ATTRIBUTE_TYPE = CK_ULONG;
+pub type CK_MECHANISM_TYPE = CK_ULONG;
+pub type CK_BBOOL = CK_BYTE;
+
+pub const CK_TRUE: CK_BBOOL = 1;
+pub const CK_FALSE: CK_BBOOL = 0;
+
+pub const CKR_OK: CK_RV = 0x0;
+pub const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x100;
+pub const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x191;
+
+pub const CKF_OS_LOCKING_OK: CK_FLAGS = 0x2;
+pub const CKF_RW_SESSION: CK_FLAGS = 0x2;
+pub const CKF_SERIAL_SESSION: CK_FLAGS = 0x4;
+
+pub const CKU_USER: CK_USER_TYPE = 1;
+
+pub const CKO_PUBLIC_KEY: CK_OBJECT_CLASS = 2;
+pub const CKO_PRIVATE_KEY: CK_OBJECT_CLASS = 3;
+
+pub const CKK_EC: CK_KEY_TYPE = 3;
+
+pub const CKA_CLASS: CK_ATTRIBUTE_TYPE = 0x0;
+pub const CKA_TOKEN: CK_ATTRIBUTE_TYPE = 0x1;
+pub const CKA_PRIVATE: CK_ATTRIBUTE_TYPE = 0x2;
+pub const CKA_LABEL: CK_ATTRIBUTE_TYPE = 0x3;
+pub const CKA_KEY_TYPE: CK_ATTRIBUTE_TYPE = 0x100;
+pub const CKA_ID: CK_ATTRIBUTE_TYPE = 0x102;
+pub const CKA_SENSITIVE: CK_ATTRIBUTE_TYPE = 0x103;
+pub const CKA_SIGN: CK_ATTRIBUTE_TYPE = 0x108;
+pub const CKA_VERIFY: CK_ATTRIBUTE_TYPE = 0x10A;
+pub const CKA_EXTRACTABLE: CK_ATTRIBUTE_TYPE = 0x162;
+pub const CKA_EC_PARAMS: CK_ATTRIBUTE_TYPE = 0x180;
+pub const CKA_EC_POINT: CK_ATTRIBUTE_TYPE = 0x181;
+
+pub const CKM_EC_KEY_PAIR_GEN: CK_MECHANISM_TYPE = 0x1040;
+pub const CKM_ECDSA: CK_MECHANISM_TYPE = 0x1041;
+
+/// Value returned in `ulValueLen` for an attribute which cannot be read
+pub const CK_UNAVAILABLE_INFORMATION: CK_ULONG = !0;
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed(1)))]
+#[derive(Clone, Copy)]
+pub struct CK_VERSION {
+    pub major: CK_BYTE,
+    pub minor: CK_BYTE,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed(1)))]
+pub struct CK_ATTRIBUTE {
+    pub type_: CK_ATTRIBUTE_TYPE,
+    pub pValue: *mut c_void,
+    pub ulValueLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed(1)))]
+pub struct CK_MECHANISM {
+    pub mechanism: CK_MECHANISM_TYPE,
+    pub pParameter: *mut c_void,
+    pub ulParameterLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed(1)))]
+pub struct CK_C_INITIALIZE_ARGS {
+    pub CreateMutex: *mut c_void,
+    pub DestroyMutex: *mut c_void,
+    pub LockMutex: *mut c_void,
+    pub UnlockMutex: *mut c_void,
+    pub flags: CK_FLAGS,
+    pub pReserved: *mut c_void,
+}
+
+type Unused = Option<unsafe extern "C" fn()>;
+
+pub type C_GetFunctionList = unsafe extern "C" fn(*mut *const CK_FUNCTION_LIST) -> CK_RV;
+pub type C_Initialize = unsafe extern "C" fn(*mut c_void) -> CK_RV;
+pub type C_Finalize = unsafe extern "C" fn(*mut c_void) -> CK_RV;
+pub type C_OpenSession = unsafe extern "C" fn(
+    CK_SLOT_ID,
+    CK_FLAGS,
+    *mut c_void,
+    *mut c_void,
+    *mut CK_SESSION_HANDLE,
+) -> CK_RV;
+pub type C_CloseSession = unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV;
+pub type C_Login =
+    unsafe extern "C" fn(CK_SESSION_HANDLE, CK_USER_TYPE, *const CK_BYTE, CK_ULONG) -> CK_RV;
+pub type C_DestroyObject = unsafe extern "C" fn(CK_SESSION_HANDLE, CK_OBJECT_HANDLE) -> CK_RV;
+pub type C_GetAttributeValue =
+    unsafe extern "C" fn(CK_SESSION_HANDLE, CK_OBJECT_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV;
+pub type C_FindObjectsInit =
+    unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV;
+pub type C_FindObjects = unsafe extern "C" fn(
+    CK_SESSION_HANDLE,
+    *mut CK_OBJECT_HANDLE,
+    CK_ULONG,
+    *mut CK_ULONG,
+) -> CK_RV;
+pub type C_FindObjectsFinal = unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV;
+pub type C_SignInit =
+    unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE) -> CK_RV;
+pub type C_Sign = unsafe extern "C" fn(
+    CK_SESSION_HANDLE,
+    *const CK_BYTE,
+    CK_ULONG,
+    *mut CK_BYTE,
+    *mut CK_ULONG,
+) -> CK_RV;
+pub type C_GenerateKeyPair = unsafe extern "C" fn(
+    CK_SESSION_HANDLE,
+    *mut CK_MECHANISM,
+    *mut CK_ATTRIBUTE,
+    CK_ULONG,
+    *mut CK_ATTRIBUTE,
+    CK_ULONG,
+    *mut CK_OBJECT_HANDLE,
+    *mut CK_OBJECT_HANDLE,
+) -> CK_RV;
+
+/// The function list returned by `C_GetFunctionList`.
+/// The functions which are not used are declared with an opaque type
+/// in order to keep the layout of the structure.
+#[repr(C)]
+#[cfg_attr(windows, repr(packed(1)))]
+pub struct CK_FUNCTION_LIST {
+    pub version: CK_VERSION,
+    pub C_Initialize: Option<C_Initialize>,
+    pub C_Finalize: Option<C_Finalize>,
+    pub C_GetInfo: Unused,
+    pub C_GetFunctionList: Unused,
+    pub C_GetSlotList: Unused,
+    pub C_GetSlotInfo: Unused,
+    pub C_GetTokenInfo: Unused,
+    pub C_GetMechanismList: Unused,
+    pub C_GetMechanismInfo: Unused,
+    pub C_InitToken: Unused,
+    pub C_InitPIN: Unused,
+    pub C_SetPIN: Unused,
+    pub C_OpenSession: Option<C_OpenSession>,
+    pub C_CloseSession: Option<C_CloseSession>,
+    pub C_CloseAllSessions: Unused,
+    pub C_GetSessionInfo: Unused,
+    pub C_GetOperationState: Unused,
+    pub C_SetOperationState: Unused,
+    pub C_Login: Option<C_Login>,
+    pub C_Logout: Unused,
+    pub C_CreateObject: Unused,
+    pub C_CopyObject: Unused,
+    pub C_DestroyObject: Option<C_DestroyObject>,
+    pub C_GetObjectSize: Unused,
+    pub C_GetAttributeValue: Option<C_GetAttributeValue>,
+    pub C_SetAttributeValue: Unused,
+    pub C_FindObjectsInit: Option<C_FindObjectsInit>,
+    pub C_FindObjects: Option<C_FindObjects>,
+    pub C_FindObjectsFinal: Option<C_FindObjectsFinal>,
+    pub C_EncryptInit: Unused,
+    pub C_Encrypt: Unused,
+    pub C_EncryptUpdate: Unused,
+    pub C_EncryptFinal: Unused,
+    pub C_DecryptInit: Unused,
+    pub C_Decrypt: Unused,
+    pub C_DecryptUpdate: Unused,
+    pub C_DecryptFinal: Unused,
+    pub C_DigestInit: Unused,
+    pub C_Digest: Unused,
+    pub C_DigestUpdate: Unused,
+    pub C_DigestKey: Unused,
+    pub C_DigestFinal: Unused,
+    pub C_SignInit: Option<C_SignInit>,
+    pub C_Sign: Option<C_Sign>,
+    pub C_SignUpdate: Unused,
+    pub C_SignFinal: Unused,
+    pub C_SignRecoverInit: Unused,
+    pub C_SignRecover: Unused,
+    pub C_VerifyInit: Unused,
+    pub C_Verify: Unused,
+    pub C_VerifyUpdate: Unused,
+    pub C_VerifyFinal: Unused,
+    pub C_VerifyRecoverInit: Unused,
+    pub C_VerifyRecover: Unused,
+    pub C_DigestEncryptUpdate: Unused,
+    pub C_DecryptDigestUpdate: Unused,
+    pub C_SignEncryptUpdate: Unused,
+    pub C_DecryptVerifyUpdate: Unused,
+    pub C_GenerateKey: Unused,
+    pub C_GenerateKeyPair: Option<C_GenerateKeyPair>,
+    pub C_WrapKey: Unused,
+    pub C_UnwrapKey: Unused,
+    pub C_DeriveKey: Unused,
+    pub C_SeedRandom: Unused,
+    pub C_GenerateRandom: Unused,
+    pub C_GetFunctionStatus: Unused,
+    pub C_CancelFunction: Unused,
+    pub C_WaitForSlotEvent: Unused,
+}
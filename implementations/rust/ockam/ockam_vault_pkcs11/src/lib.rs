@@ -0,0 +1,25 @@
+//! PKCS#11 implementation of the ockam_vault::VaultForSigning trait
+//!
+//! Signing keys are generated and used inside a PKCS#11 token (an HSM, a YubiKey, SoftHSM2, ...)
+//! and never leave it.
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+#[allow(unsafe_code)]
+mod ffi;
+mod pkcs11_config;
+mod pkcs11_signing_vault;
+// CK_ULONG is 64 bits on Unix platforms but 32 bits on Windows
+#[allow(unsafe_code, trivial_numeric_casts, clippy::unnecessary_cast)]
+mod pkcs11_token;
+
+pub use error::*;
+pub use pkcs11_config::*;
+pub use pkcs11_signing_vault::*;
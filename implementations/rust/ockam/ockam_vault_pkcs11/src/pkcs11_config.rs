@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+/// Configuration of a PKCS#11 token
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    module_path: PathBuf,
+    slot: u64,
+    pin: Option<String>,
+}
+
+impl Pkcs11Config {
+    /// Create a configuration for the token in a slot of a PKCS#11 module.
+    /// The module is the path of the dynamic library provided by the token vendor,
+    /// for example `/usr/lib/softhsm/libsofthsm2.so` for SoftHSM2.
+    pub fn new(module_path: impl Into<PathBuf>, slot: u64) -> Self {
+        Self {
+            module_path: module_path.into(),
+            slot,
+            pin: None,
+        }
+    }
+
+    /// Set the user PIN used to log in the token
+    pub fn with_pin(mut self, pin: impl Into<String>) -> Self {
+        self.pin = Some(pin.into());
+        self
+    }
+
+    /// Path of the PKCS#11 module
+    pub fn module_path(&self) -> &Path {
+        self.module_path.as_path()
+    }
+
+    /// Slot of the token
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// User PIN of the token, if the user needs to log in
+    pub fn pin(&self) -> Option<&str> {
+        self.pin.as_deref()
+    }
+}
+
+impl core::fmt::Debug for Pkcs11Config {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pkcs11Config")
+            .field("module_path", &self.module_path)
+            .field("slot", &self.slot)
+            .field("pin", &self.pin.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
@@ -0,0 +1,318 @@
+use crate::error::Error;
+use crate::ffi::{
+    CKA_CLASS, CKA_EC_PARAMS, CKA_EC_POINT, CKA_ID, CKA_KEY_TYPE, CKK_EC, CKO_PRIVATE_KEY,
+    CKO_PUBLIC_KEY, CK_OBJECT_HANDLE,
+};
+use crate::pkcs11_token::{bytes_attribute, ulong_attribute, Pkcs11Token, P256_EC_PARAMS};
+use crate::Pkcs11Config;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, Mutex, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning, VerifyingPublicKey,
+    ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH, ECDSA_SHA256_CURVEP256_SIGNATURE_LENGTH,
+};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// Label set on the keys generated by Ockam
+const KEY_LABEL: &str = "ockam";
+
+/// Length of the random identifiers (CKA_ID) of the keys generated by Ockam
+const KEY_ID_LENGTH: usize = 16;
+
+struct Pkcs11KeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+    public_key_object: CK_OBJECT_HANDLE,
+    private_key_object: CK_OBJECT_HANDLE,
+}
+
+/// Security module implementation using a PKCS#11 token.
+///
+/// Only ECDSA P-256 keys are supported. The handle of a key is its PKCS#11 identifier (CKA_ID).
+///
+/// The calls to the token block until the HSM responds, so they are made on the blocking threads
+/// of the runtime. When both locks are needed, the token is always locked before the keys.
+pub struct Pkcs11SigningVault {
+    token: Arc<Mutex<Pkcs11Token>>,
+    // Store mapping from PublicKey to the key objects in memory
+    // This is fetched at the Vault initialization
+    // and is updated locally during add/delete operations
+    // WARNING: The assumption is that there is no concurrent access to the same keys from
+    // different places.
+    keys: Arc<RwLock<Vec<Pkcs11KeyPair>>>,
+}
+
+impl Pkcs11SigningVault {
+    /// Create a security module using the token of a PKCS#11 module.
+    /// All the ECDSA P-256 key pairs stored in the token can be used
+    pub async fn create(config: Pkcs11Config) -> Result<Self> {
+        run_blocking(move || Self::open(&config)).await
+    }
+
+    /// Open the token and load its key pairs
+    fn open(config: &Pkcs11Config) -> Result<Self> {
+        let token = Pkcs11Token::open(config)?;
+
+        let private_key_class = CKO_PRIVATE_KEY;
+        let key_type = CKK_EC;
+        let private_keys = token.find_objects(&mut [
+            ulong_attribute(CKA_CLASS, &private_key_class),
+            ulong_attribute(CKA_KEY_TYPE, &key_type),
+        ])?;
+
+        let mut key_pairs: Vec<Pkcs11KeyPair> = vec![];
+        for private_key_object in private_keys {
+            match Self::load_key_pair(&token, private_key_object) {
+                Ok(Some(key_pair)) => key_pairs.push(key_pair),
+                Ok(None) => (),
+                // The key may have no public key object or the public key may not be readable,
+                // the best strategy is to just skip that key
+                Err(err) => warn!("Error loading a PKCS#11 key pair: {err}"),
+            }
+        }
+
+        Ok(Self {
+            token: Arc::new(Mutex::new(token)),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+
+    /// Load the key pair of a private key if it is a P-256 key
+    fn load_key_pair(
+        token: &Pkcs11Token,
+        private_key_object: CK_OBJECT_HANDLE,
+    ) -> Result<Option<Pkcs11KeyPair>> {
+        let id = token.attribute(private_key_object, CKA_ID)?;
+        let public_key_class = CKO_PUBLIC_KEY;
+        let public_key_object = match token
+            .find_objects(&mut [
+                ulong_attribute(CKA_CLASS, &public_key_class),
+                bytes_attribute(CKA_ID, &id),
+            ])?
+            .first()
+        {
+            Some(object) => *object,
+            None => return Err(Error::KeyNotFound)?,
+        };
+
+        if token.attribute(public_key_object, CKA_EC_PARAMS)? != P256_EC_PARAMS {
+            return Ok(None);
+        }
+        let public_key = decode_ec_point(&token.attribute(public_key_object, CKA_EC_POINT)?)?;
+        Ok(Some(Pkcs11KeyPair {
+            key: SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(id)),
+            public_key,
+            public_key_object,
+            private_key_object,
+        }))
+    }
+
+    fn private_key_object(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<CK_OBJECT_HANDLE> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.key == signing_secret_key_handle {
+                    Some(x.private_key_object)
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    /// Call the token on a blocking thread
+    async fn with_token<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Pkcs11Token) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let token = self.token.clone();
+        run_blocking(move || f(&token.lock().unwrap())).await
+    }
+}
+
+/// Run a blocking function on the blocking threads of the runtime
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::BlockingCall(e.to_string()))?
+}
+
+#[async_trait]
+impl VaultForSigning for Pkcs11SigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let private_key_object = self.private_key_object(signing_secret_key_handle)?;
+        let digest = Sha256::digest(data);
+        let signature = self
+            .with_token(move |token| token.sign_ecdsa(private_key_object, digest.as_slice()))
+            .await?;
+
+        let signature: [u8; ECDSA_SHA256_CURVEP256_SIGNATURE_LENGTH] =
+            signature.try_into().map_err(|_| Error::InvalidSignature)?;
+        Ok(Signature::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256Signature(signature),
+        ))
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType)?;
+        }
+
+        let mut id = vec![0u8; KEY_ID_LENGTH];
+        thread_rng().fill_bytes(&mut id);
+
+        let keys = self.keys.clone();
+        self.with_token(move |token| {
+            let (public_key_object, private_key_object) =
+                token.generate_p256_key_pair(&id, KEY_LABEL)?;
+            let public_key = decode_ec_point(&token.attribute(public_key_object, CKA_EC_POINT)?)?;
+
+            let key = SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(id));
+            keys.write().unwrap().push(Pkcs11KeyPair {
+                key: key.clone(),
+                public_key,
+                public_key_object,
+                private_key_object,
+            });
+            Ok(key)
+        })
+        .await
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.key == signing_secret_key_handle {
+                    Some(x.public_key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        let keys = self.keys.clone();
+        self.with_token(move |token| {
+            let mut keys = keys.write().unwrap();
+            let Some(index) = keys.iter().position(|x| x.key == signing_secret_key_handle) else {
+                return Ok(false);
+            };
+            token.destroy_object(keys[index].private_key_object)?;
+            token.destroy_object(keys[index].public_key_object)?;
+            keys.remove(index);
+            Ok(true)
+        })
+        .await
+    }
+}
+
+/// Decode the CKA_EC_POINT attribute of a P-256 public key.
+///
+/// The point is specified as a DER-encoded OCTET STRING containing the uncompressed point,
+/// but some tokens return the uncompressed point directly
+fn decode_ec_point(ec_point: &[u8]) -> Result<VerifyingPublicKey> {
+    let point = match ec_point {
+        [0x04, length, point @ ..]
+            if *length as usize == ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH
+                && point.len() == ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH =>
+        {
+            point
+        }
+        point => point,
+    };
+    let point: [u8; ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH] =
+        point.try_into().map_err(|_| Error::InvalidPublicKey)?;
+    if point[0] != 0x04 {
+        Err(Error::InvalidPublicKey)?
+    }
+    Ok(VerifyingPublicKey::ECDSASHA256CurveP256(
+        ECDSASHA256CurveP256PublicKey(point),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_ec_point() {
+        let mut point = vec![0x04];
+        point.extend_from_slice(&[1u8; 64]);
+        let expected = VerifyingPublicKey::ECDSASHA256CurveP256(ECDSASHA256CurveP256PublicKey(
+            point.clone().try_into().unwrap(),
+        ));
+
+        // raw uncompressed point
+        assert_eq!(decode_ec_point(&point).unwrap(), expected);
+
+        // uncompressed point wrapped in a DER OCTET STRING
+        let mut wrapped = vec![0x04, 0x41];
+        wrapped.extend_from_slice(&point);
+        assert_eq!(decode_ec_point(&wrapped).unwrap(), expected);
+
+        // compressed points are not supported
+        let mut compressed = vec![0x02];
+        compressed.extend_from_slice(&[1u8; 32]);
+        assert!(decode_ec_point(&compressed).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use crate::error::Error;
+use crate::ffi::*;
+use crate::Pkcs11Config;
+use core::ffi::c_void;
+use core::mem::size_of;
+use core::ptr;
+use libloading::Library;
+use ockam_core::Result;
+
+/// DER encoding of the OID of the P-256 curve (prime256v1), used as the EC parameters of the keys
+pub(crate) const P256_EC_PARAMS: &[u8] =
+    &[0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+
+/// Maximum size of an ECDSA signature returned by a token (P-521)
+const MAX_SIGNATURE_LENGTH: usize = 132;
+
+/// Number of object handles retrieved by each call to C_FindObjects
+const FIND_OBJECTS_BATCH_SIZE: usize = 16;
+
+/// A session opened on the token of a PKCS#11 module.
+///
+/// The token is initialized for multi-threaded access, but a session must not be used
+/// concurrently, so the token is always accessed behind a mutex.
+///
+/// The module is not finalized when the session is closed, since the same module
+/// can be used by other sessions of the process.
+pub(crate) struct Pkcs11Token {
+    functions: *const CK_FUNCTION_LIST,
+    session: CK_SESSION_HANDLE,
+    // the library must be unloaded after the function list is not used anymore
+    _library: Library,
+}
+
+// The function list is static data of the loaded module, and the module is initialized with
+// CKF_OS_LOCKING_OK, so it can be called from any thread
+unsafe impl Send for Pkcs11Token {}
+
+impl Pkcs11Token {
+    /// Load a PKCS#11 module, open a read-write session on a slot and log in if a PIN is configured
+    pub(crate) fn open(config: &Pkcs11Config) -> Result<Self> {
+        let load_error = |e: libloading::Error| Error::LoadModule {
+            path: config.module_path().to_string_lossy().to_string(),
+            error: e.to_string(),
+        };
+        let library = unsafe { Library::new(config.module_path()) }.map_err(load_error)?;
+
+        let mut functions: *const CK_FUNCTION_LIST = ptr::null();
+        unsafe {
+            let get_function_list = library
+                .get::<C_GetFunctionList>(b"C_GetFunctionList\0")
+                .map_err(load_error)?;
+            check("C_GetFunctionList", get_function_list(&mut functions))?;
+        }
+        if functions.is_null() {
+            return Err(Error::MissingFunction("C_GetFunctionList"))?;
+        }
+
+        let mut token = Self {
+            functions,
+            session: 0,
+            _library: library,
+        };
+
+        let mut initialize_args = CK_C_INITIALIZE_ARGS {
+            CreateMutex: ptr::null_mut(),
+            DestroyMutex: ptr::null_mut(),
+            LockMutex: ptr::null_mut(),
+            UnlockMutex: ptr::null_mut(),
+            flags: CKF_OS_LOCKING_OK,
+            pReserved: ptr::null_mut(),
+        };
+        let initialize = token.function(token.functions().C_Initialize, "C_Initialize")?;
+        let rv = unsafe { initialize(ptr::addr_of_mut!(initialize_args).cast()) };
+        if rv != CKR_CRYPTOKI_ALREADY_INITIALIZED {
+            check("C_Initialize", rv)?;
+        }
+
+        let open_session = token.function(token.functions().C_OpenSession, "C_OpenSession")?;
+        let mut session: CK_SESSION_HANDLE = 0;
+        check("C_OpenSession", unsafe {
+            open_session(
+                config.slot() as CK_SLOT_ID,
+                CKF_SERIAL_SESSION | CKF_RW_SESSION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut session,
+            )
+        })?;
+        token.session = session;
+
+        if let Some(pin) = config.pin() {
+            let login = token.function(token.functions().C_Login, "C_Login")?;
+            let rv = unsafe { login(token.session, CKU_USER, pin.as_ptr(), pin.len() as CK_ULONG) };
+            if rv != CKR_USER_ALREADY_LOGGED_IN {
+                check("C_Login", rv)?;
+            }
+        }
+        Ok(token)
+    }
+
+    /// Return the handles of the objects matching some attributes
+    pub(crate) fn find_objects(
+        &self,
+        template: &mut [CK_ATTRIBUTE],
+    ) -> Result<Vec<CK_OBJECT_HANDLE>> {
+        let find_objects_init =
+            self.function(self.functions().C_FindObjectsInit, "C_FindObjectsInit")?;
+        let find_objects = self.function(self.functions().C_FindObjects, "C_FindObjects")?;
+        let find_objects_final =
+            self.function(self.functions().C_FindObjectsFinal, "C_FindObjectsFinal")?;
+
+        check("C_FindObjectsInit", unsafe {
+            find_objects_init(
+                self.session,
+                template.as_mut_ptr(),
+                template.len() as CK_ULONG,
+            )
+        })?;
+
+        let mut objects = vec![];
+        let result = loop {
+            let mut batch: [CK_OBJECT_HANDLE; FIND_OBJECTS_BATCH_SIZE] =
+                [0; FIND_OBJECTS_BATCH_SIZE];
+            let mut count: CK_ULONG = 0;
+            let rv = unsafe {
+                find_objects(
+                    self.session,
+                    batch.as_mut_ptr(),
+                    FIND_OBJECTS_BATCH_SIZE as CK_ULONG,
+                    &mut count,
+                )
+            };
+            if let Err(e) = check("C_FindObjects", rv) {
+                break Err(e);
+            }
+            objects.extend_from_slice(&batch[..count as usize]);
+            if (count as usize) < FIND_OBJECTS_BATCH_SIZE {
+                break Ok(());
+            }
+        };
+        // the search must always be finalized, even if it failed
+        let rv = unsafe { find_objects_final(self.session) };
+        result?;
+        check("C_FindObjectsFinal", rv)?;
+        Ok(objects)
+    }
+
+    /// Return the value of an attribute of an object
+    pub(crate) fn attribute(
+        &self,
+        object: CK_OBJECT_HANDLE,
+        attribute_type: CK_ATTRIBUTE_TYPE,
+    ) -> Result<Vec<u8>> {
+        let get_attribute_value =
+            self.function(self.functions().C_GetAttributeValue, "C_GetAttributeValue")?;
+
+        // the first call returns the length of the value, the second call returns the value
+        let mut attribute = CK_ATTRIBUTE {
+            type_: attribute_type,
+            pValue: ptr::null_mut(),
+            ulValueLen: 0,
+        };
+        check("C_GetAttributeValue", unsafe {
+            get_attribute_value(self.session, object, &mut attribute, 1)
+        })?;
+        if attribute.ulValueLen == CK_UNAVAILABLE_INFORMATION {
+            return Err(Error::UnavailableAttribute)?;
+        }
+
+        let mut value = vec![0u8; attribute.ulValueLen as usize];
+        attribute.pValue = value.as_mut_ptr() as *mut c_void;
+        check("C_GetAttributeValue", unsafe {
+            get_attribute_value(self.session, object, &mut attribute, 1)
+        })?;
+        value.truncate(attribute.ulValueLen as usize);
+        Ok(value)
+    }
+
+    /// Generate a P-256 key pair stored in the token.
+    /// The private key can only be used for signing and cannot be extracted.
+    /// Return the handles of the public key and of the private key
+    pub(crate) fn generate_p256_key_pair(
+        &self,
+        id: &[u8],
+        label: &str,
+    ) -> Result<(CK_OBJECT_HANDLE, CK_OBJECT_HANDLE)> {
+        let generate_key_pair =
+            self.function(self.functions().C_GenerateKeyPair, "C_GenerateKeyPair")?;
+
+        let mut mechanism = CK_MECHANISM {
+            mechanism: CKM_EC_KEY_PAIR_GEN,
+            pParameter: ptr::null_mut(),
+            ulParameterLen: 0,
+        };
+        let mut public_key_template = [
+            bool_attribute(CKA_TOKEN, &CK_TRUE),
+            bool_attribute(CKA_VERIFY, &CK_TRUE),
+            bytes_attribute(CKA_EC_PARAMS, P256_EC_PARAMS),
+            bytes_attribute(CKA_ID, id),
+            bytes_attribute(CKA_LABEL, label.as_bytes()),
+        ];
+        let mut private_key_template = [
+            bool_attribute(CKA_TOKEN, &CK_TRUE),
+            bool_attribute(CKA_PRIVATE, &CK_TRUE),
+            bool_attribute(CKA_SENSITIVE, &CK_TRUE),
+            bool_attribute(CKA_EXTRACTABLE, &CK_FALSE),
+            bool_attribute(CKA_SIGN, &CK_TRUE),
+            bytes_attribute(CKA_ID, id),
+            bytes_attribute(CKA_LABEL, label.as_bytes()),
+        ];
+
+        let mut public_key: CK_OBJECT_HANDLE = 0;
+        let mut private_key: CK_OBJECT_HANDLE = 0;
+        check("C_GenerateKeyPair", unsafe {
+            generate_key_pair(
+                self.session,
+                &mut mechanism,
+                public_key_template.as_mut_ptr(),
+                public_key_template.len() as CK_ULONG,
+                private_key_template.as_mut_ptr(),
+                private_key_template.len() as CK_ULONG,
+                &mut public_key,
+                &mut private_key,
+            )
+        })?;
+        Ok((public_key, private_key))
+    }
+
+    /// Sign a digest with an ECDSA private key.
+    /// The signature is the concatenation of the r and s values
+    pub(crate) fn sign_ecdsa(
+        &self,
+        private_key: CK_OBJECT_HANDLE,
+        digest: &[u8],
+    ) -> Result<Vec<u8>> {
+        let sign_init = self.function(self.functions().C_SignInit, "C_SignInit")?;
+        let sign = self.function(self.functions().C_Sign, "C_Sign")?;
+
+        let mut mechanism = CK_MECHANISM {
+            mechanism: CKM_ECDSA,
+            pParameter: ptr::null_mut(),
+            ulParameterLen: 0,
+        };
+        check("C_SignInit", unsafe {
+            sign_init(self.session, &mut mechanism, private_key)
+        })?;
+
+        let mut signature = vec![0u8; MAX_SIGNATURE_LENGTH];
+        let mut signature_length = signature.len() as CK_ULONG;
+        check("C_Sign", unsafe {
+            sign(
+                self.session,
+                digest.as_ptr(),
+                digest.len() as CK_ULONG,
+                signature.as_mut_ptr(),
+                &mut signature_length,
+            )
+        })?;
+        signature.truncate(signature_length as usize);
+        Ok(signature)
+    }
+
+    /// Delete an object from the token
+    pub(crate) fn destroy_object(&self, object: CK_OBJECT_HANDLE) -> Result<()> {
+        let destroy_object = self.function(self.functions().C_DestroyObject, "C_DestroyObject")?;
+        check("C_DestroyObject", unsafe {
+            destroy_object(self.session, object)
+        })
+    }
+
+    fn functions(&self) -> &CK_FUNCTION_LIST {
+        // the pointer was checked when the module was loaded and stays valid while the library is loaded
+        unsafe { &*self.functions }
+    }
+
+    fn function<F>(&self, function: Option<F>, name: &'static str) -> Result<F> {
+        Ok(function.ok_or(Error::MissingFunction(name))?)
+    }
+}
+
+impl Drop for Pkcs11Token {
+    fn drop(&mut self) {
+        if self.session == 0 {
+            return;
+        }
+        if let Some(close_session) = self.functions().C_CloseSession {
+            let rv = unsafe { close_session(self.session) };
+            if rv != CKR_OK {
+                tracing::warn!("cannot close the PKCS#11 session: error code {rv:#x}");
+            }
+        }
+    }
+}
+
+/// Return an error if a PKCS#11 function did not succeed
+fn check(function: &'static str, rv: CK_RV) -> Result<()> {
+    if rv == CKR_OK {
+        Ok(())
+    } else {
+        Err(Error::Call {
+            function,
+            code: rv as u64,
+        })?
+    }
+}
+
+/// Create an attribute pointing to a value.
+/// The value must outlive the PKCS#11 call using the attribute
+pub(crate) fn bytes_attribute(attribute_type: CK_ATTRIBUTE_TYPE, value: &[u8]) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE {
+        type_: attribute_type,
+        pValue: value.as_ptr() as *mut c_void,
+        ulValueLen: value.len() as CK_ULONG,
+    }
+}
+
+pub(crate) fn bool_attribute(attribute_type: CK_ATTRIBUTE_TYPE, value: &CK_BBOOL) -> CK_ATTRIBUTE {
+    bytes_attribute(attribute_type, core::slice::from_ref(value))
+}
+
+pub(crate) fn ulong_attribute(attribute_type: CK_ATTRIBUTE_TYPE, value: &CK_ULONG) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE {
+        type_: attribute_type,
+        pValue: ptr::addr_of!(*value) as *mut c_void,
+        ulValueLen: size_of::<CK_ULONG>() as CK_ULONG,
+    }
+}
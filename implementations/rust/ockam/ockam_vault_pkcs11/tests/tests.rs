@@ -0,0 +1,90 @@
+use ockam_core::Result;
+use ockam_vault::{
+    SigningKeyType, SoftwareVaultForVerifyingSignatures, VaultForSigning,
+    VaultForVerifyingSignatures,
+};
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
+
+/// These tests need to be executed with a PKCS#11 token, for example SoftHSM2:
+///
+///   softhsm2-util --init-token --free --label ockam --so-pin 1234 --pin 1234
+///
+/// and the following environment variables:
+/// PKCS11_MODULE: path of the PKCS#11 module, for example /usr/lib/softhsm/libsofthsm2.so
+/// PKCS11_SLOT: slot of the initialized token, as displayed by `softhsm2-util --show-slots`
+/// PKCS11_PIN: user PIN of the token
+fn config() -> Pkcs11Config {
+    let module = std::env::var("PKCS11_MODULE").expect("PKCS11_MODULE must be set");
+    let slot = std::env::var("PKCS11_SLOT")
+        .expect("PKCS11_SLOT must be set")
+        .parse()
+        .expect("PKCS11_SLOT must be a number");
+    let pin = std::env::var("PKCS11_PIN").expect("PKCS11_PIN must be set");
+    Pkcs11Config::new(module, slot).with_pin(pin)
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_sign_verify() -> Result<()> {
+    let signing_vault = Pkcs11SigningVault::create(config()).await?;
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+    let message = b"hello world";
+    let signature = signing_vault.sign(&handle, message.as_slice()).await?;
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let verifier = SoftwareVaultForVerifyingSignatures::new();
+    assert!(
+        verifier
+            .verify_signature(&public_key, message, &signature)
+            .await?
+    );
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_keys_management() -> Result<()> {
+    let signing_vault = Pkcs11SigningVault::create(config()).await?;
+
+    let number_of_keys1 = signing_vault.number_of_keys().await?;
+
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+
+    let number_of_keys2 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys1 + 1, number_of_keys2);
+
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let handle2 = signing_vault.get_secret_key_handle(&public_key).await?;
+    assert_eq!(handle, handle2);
+
+    // the key is persisted in the token and found by another vault
+    let signing_vault2 = Pkcs11SigningVault::create(config()).await?;
+    let handle3 = signing_vault2.get_secret_key_handle(&public_key).await?;
+    assert_eq!(handle, handle3);
+    drop(signing_vault2);
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+    let number_of_keys3 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys2, number_of_keys3 + 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_only_p256_keys_are_supported() -> Result<()> {
+    let signing_vault = Pkcs11SigningVault::create(config()).await?;
+    let result = signing_vault
+        .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+        .await;
+    assert!(result.is_err());
+    Ok(())
+}
@@ -179,7 +179,7 @@ impl State {
             .await?;
 
         let trust_options = cli_state
-            .retrieve_trust_options(&None, &None, &None, &None)
+            .retrieve_trust_options(&None, &None, &None, &[], &None)
             .await?;
 
         let node_manager = Arc::new(